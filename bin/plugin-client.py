@@ -199,7 +199,7 @@ class CustomJsonDialog(ModalScreen[str]):
 
     def _get_request_template(self) -> str:
         """Get template for request message"""
-        return json.dumps({"id": self.next_request_id, "method": "", "params": ""}, indent=2)
+        return json.dumps({"jsonrpc": "2.0", "id": self.next_request_id, "method": "", "params": ""}, indent=2)
 
     def _get_notification_template(self) -> str:
         """Get template for notification message"""
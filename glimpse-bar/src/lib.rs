@@ -0,0 +1,3 @@
+//! Helpers for showing glimpse results in status bars such as waybar.
+
+pub mod waybar;
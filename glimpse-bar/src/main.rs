@@ -0,0 +1,167 @@
+use std::{path::Path, time::Duration};
+
+use glimpse_bar::waybar::UnreadTracker;
+use glimpse_client::Client;
+use nix::{
+    sys::signal::{Signal, kill},
+    unistd::Pid,
+};
+use tokio::signal::unix::{SignalKind, signal};
+
+const USAGE: &str = "usage:
+    glimpse-bar watch <query> [--plugin <id>] [--interval <secs>]
+        print waybar JSON with the number of unread results, SIGUSR1 marks them read
+    glimpse-bar subscribe <plugin-id> <topic>
+        same output, fed by updates the plugin pushes for the topic
+    glimpse-bar toggle
+        open the launcher, or close it when it is already running";
+
+struct WatchArgs {
+    query: String,
+    plugin_id: Option<String>,
+    interval: Duration,
+}
+
+fn parse_watch_args(args: &[String]) -> Result<WatchArgs, String> {
+    let mut query = None;
+    let mut plugin_id = None;
+    let mut interval = Duration::from_secs(60);
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--plugin" => plugin_id = Some(args.next().ok_or("--plugin needs a value")?.clone()),
+            "--interval" => {
+                let secs = args.next().ok_or("--interval needs a value")?;
+                let secs = secs
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid interval: {}", secs))?;
+                interval = Duration::from_secs(secs.max(1));
+            }
+            _ if query.is_none() => query = Some(arg.clone()),
+            _ => return Err(format!("unexpected argument: {}", arg)),
+        }
+    }
+
+    Ok(WatchArgs {
+        query: query.ok_or("missing query")?,
+        plugin_id,
+        interval,
+    })
+}
+
+async fn watch(args: WatchArgs) -> Result<(), anyhow::Error> {
+    let daemon_binary =
+        std::env::var("GLIMPSED_BIN").unwrap_or_else(|_| "/usr/bin/glimpsed".to_string());
+    let client = Client::spawn(daemon_binary)?;
+    let mut tracker = UnreadTracker::new();
+    let mut mark_read = signal(SignalKind::user_defined1())?;
+    let mut ticker = tokio::time::interval(args.interval);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                let search = match &args.plugin_id {
+                    Some(plugin_id) => client.search_plugin(&args.query, plugin_id).await?,
+                    None => client.search(&args.query).await?,
+                };
+                let results = search.collect().await?;
+                for error in &results.errors {
+                    tracing::warn!("search error: {}", error);
+                }
+                tracker.update(results.matches);
+            }
+            _ = mark_read.recv() => tracker.mark_read(),
+        }
+
+        // waybar reads one JSON object per line
+        println!("{}", tracker.output().to_line());
+    }
+}
+
+async fn subscribe(plugin_id: &str, topic: &str) -> Result<(), anyhow::Error> {
+    let daemon_binary =
+        std::env::var("GLIMPSED_BIN").unwrap_or_else(|_| "/usr/bin/glimpsed".to_string());
+    let client = Client::spawn(daemon_binary)?;
+    let mut subscription = client.subscribe(plugin_id, topic).await?;
+    let mut tracker = UnreadTracker::new();
+    let mut mark_read = signal(SignalKind::user_defined1())?;
+
+    println!("{}", tracker.output().to_line());
+    loop {
+        tokio::select! {
+            update = subscription.next() => match update {
+                Some(items) => tracker.update(items?),
+                None => return Ok(()),
+            },
+            _ = mark_read.recv() => tracker.mark_read(),
+        }
+
+        println!("{}", tracker.output().to_line());
+    }
+}
+
+/// Pids of running launcher processes, found by the file name of their executable. Unlike
+/// `comm`, which the kernel cuts to 15 bytes, it is never truncated.
+fn launcher_pids(name: &str) -> Vec<Pid> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse::<i32>().ok()?;
+            let exe = std::fs::read_link(entry.path().join("exe")).ok()?;
+            let exe = exe.file_name()?.to_str()?;
+            // a launcher still running after an upgrade replaced its executable
+            let exe = exe.strip_suffix(" (deleted)").unwrap_or(exe);
+            (exe == name).then_some(Pid::from_raw(pid))
+        })
+        .collect()
+}
+
+fn toggle() -> Result<(), anyhow::Error> {
+    let launcher = std::env::var("GLIMPSE_BIN").unwrap_or_else(|_| "/usr/bin/glimpse".to_string());
+    let name = Path::new(&launcher)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("glimpse");
+
+    let pids = launcher_pids(name);
+    if pids.is_empty() {
+        std::process::Command::new(&launcher).spawn()?;
+        return Ok(());
+    }
+
+    for pid in pids {
+        if let Err(err) = kill(pid, Signal::SIGTERM) {
+            tracing::warn!("failed to close the launcher {}: {}", pid, err);
+        }
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(tracing::Level::INFO)
+        .init();
+
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(String::as_str) {
+        Some("watch") => match parse_watch_args(&args[1..]) {
+            Ok(watch_args) => watch(watch_args).await,
+            Err(err) => {
+                eprintln!("{}\n\n{}", err, USAGE);
+                std::process::exit(2);
+            }
+        },
+        Some("subscribe") if args.len() == 3 => subscribe(&args[1], &args[2]).await,
+        Some("toggle") => toggle(),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::HashSet;
+
+use glimpse_sdk::Match;
+use serde::Serialize;
+
+const TOOLTIP_LINES: usize = 10;
+
+/// One line of output for a waybar custom module with `"return-type": "json"`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct WaybarOutput {
+    pub text: String,
+    pub alt: String,
+    pub tooltip: String,
+    pub class: String,
+}
+
+impl WaybarOutput {
+    pub fn to_line(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// Remembers which results were already seen so only new ones count as unread.
+#[derive(Debug, Default)]
+pub struct UnreadTracker {
+    seen: HashSet<String>,
+    current: Vec<Match>,
+}
+
+impl UnreadTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the current results with the latest poll.
+    pub fn update(&mut self, matches: Vec<Match>) {
+        self.current = matches;
+    }
+
+    /// Mark everything currently shown as read.
+    pub fn mark_read(&mut self) {
+        self.seen.extend(self.current.iter().map(match_key));
+    }
+
+    pub fn unread(&self) -> Vec<&Match> {
+        self.current
+            .iter()
+            .filter(|m| !self.seen.contains(&match_key(m)))
+            .collect()
+    }
+
+    pub fn output(&self) -> WaybarOutput {
+        let unread = self.unread();
+        let class = if unread.is_empty() { "read" } else { "unread" };
+
+        let mut tooltip = unread
+            .iter()
+            .take(TOOLTIP_LINES)
+            .map(|m| match m.description.is_empty() {
+                true => m.title.clone(),
+                false => format!("{} - {}", m.title, m.description),
+            })
+            .collect::<Vec<_>>();
+        if unread.len() > TOOLTIP_LINES {
+            tooltip.push(format!("and {} more", unread.len() - TOOLTIP_LINES));
+        }
+
+        WaybarOutput {
+            text: unread.len().to_string(),
+            alt: class.to_string(),
+            tooltip: tooltip.join("\n"),
+            class: class.to_string(),
+        }
+    }
+}
+
+/// Match ids change on every search, so results are identified by their content.
+fn match_key(m: &Match) -> String {
+    format!("{}\u{0}{}", m.title, m.description)
+}
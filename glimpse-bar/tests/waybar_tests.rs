@@ -0,0 +1,65 @@
+use glimpse_bar::waybar::UnreadTracker;
+use glimpse_sdk::Match;
+
+fn create_match(title: &str) -> Match {
+    Match {
+        title: title.to_string(),
+        description: format!("{} description", title),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_empty_output() {
+    let tracker = UnreadTracker::new();
+    let output = tracker.output();
+
+    assert_eq!(output.text, "0");
+    assert_eq!(output.class, "read");
+    assert_eq!(output.tooltip, "");
+}
+
+#[test]
+fn test_counts_unread_results() {
+    let mut tracker = UnreadTracker::new();
+    tracker.update(vec![create_match("a"), create_match("b")]);
+    let output = tracker.output();
+
+    assert_eq!(output.text, "2");
+    assert_eq!(output.class, "unread");
+    assert_eq!(output.tooltip, "a - a description\nb - b description");
+}
+
+#[test]
+fn test_mark_read_only_counts_new_results() {
+    let mut tracker = UnreadTracker::new();
+    tracker.update(vec![create_match("a"), create_match("b")]);
+    tracker.mark_read();
+    assert_eq!(tracker.output().text, "0");
+
+    tracker.update(vec![create_match("b"), create_match("c")]);
+    let unread = tracker.unread();
+    assert_eq!(unread.len(), 1);
+    assert_eq!(unread[0].title, "c");
+}
+
+#[test]
+fn test_tooltip_is_truncated() {
+    let mut tracker = UnreadTracker::new();
+    tracker.update((0..15).map(|i| create_match(&i.to_string())).collect());
+    let output = tracker.output();
+
+    assert_eq!(output.text, "15");
+    assert_eq!(output.tooltip.lines().count(), 11);
+    assert!(output.tooltip.ends_with("and 5 more"));
+}
+
+#[test]
+fn test_output_is_single_json_line() {
+    let output = UnreadTracker::new().output().to_line();
+    let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+    assert!(!output.contains('\n'));
+    assert_eq!(value["text"], "0");
+    assert_eq!(value["class"], "read");
+}
@@ -0,0 +1,123 @@
+use std::{error::Error, fmt::Display};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+}
+
+/// Arguments of `glimpse-cli search`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchArgs {
+    pub query: String,
+    /// Search a single plugin by its metadata id.
+    pub plugin: Option<String>,
+    pub format: Format,
+    /// Index of the match to activate once the search completes.
+    pub activate: Option<usize>,
+    /// Action of the activated match, its default action if not given.
+    pub action: usize,
+    /// Run the action even if it asks for confirmation.
+    pub yes: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgsError {
+    MissingQuery,
+    MissingValue(String),
+    InvalidNumber(String, String),
+    Unknown(String),
+    ActionWithoutActivate,
+}
+
+impl Display for ArgsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgsError::MissingQuery => write!(f, "missing search query"),
+            ArgsError::MissingValue(flag) => write!(f, "{} needs a value", flag),
+            ArgsError::InvalidNumber(flag, value) => {
+                write!(f, "{} expects a number, got {:?}", flag, value)
+            }
+            ArgsError::Unknown(arg) => write!(f, "unexpected argument {:?}", arg),
+            ArgsError::ActionWithoutActivate => write!(f, "--action and --yes need --activate"),
+        }
+    }
+}
+impl Error for ArgsError {}
+
+impl SearchArgs {
+    /// Parse the arguments following `search`.
+    pub fn parse(args: &[String]) -> Result<Self, ArgsError> {
+        let mut query = None;
+        let mut plugin = None;
+        let mut format = Format::Table;
+        let mut activate = None;
+        let mut action = None;
+        let mut yes = false;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--json" => format = Format::Json,
+                "--plugin" => plugin = Some(value(arg, args.next())?.to_string()),
+                "--activate" => activate = Some(number(arg, args.next())?),
+                "--action" => action = Some(number(arg, args.next())?),
+                "--yes" => yes = true,
+                // everything after `--` is the query, even if it looks like a flag
+                "--" if query.is_none() => query = args.next().cloned(),
+                flag if flag.starts_with("--") => return Err(ArgsError::Unknown(arg.clone())),
+                _ if query.is_none() => query = Some(arg.clone()),
+                _ => return Err(ArgsError::Unknown(arg.clone())),
+            }
+        }
+
+        if (action.is_some() || yes) && activate.is_none() {
+            return Err(ArgsError::ActionWithoutActivate);
+        }
+        Ok(Self {
+            query: query.ok_or(ArgsError::MissingQuery)?,
+            plugin,
+            format,
+            activate,
+            action: action.unwrap_or(0),
+            yes,
+        })
+    }
+}
+
+fn value<'a>(flag: &str, value: Option<&'a String>) -> Result<&'a str, ArgsError> {
+    value
+        .map(String::as_str)
+        .ok_or_else(|| ArgsError::MissingValue(flag.to_string()))
+}
+
+fn number(flag: &str, arg: Option<&String>) -> Result<usize, ArgsError> {
+    let arg = value(flag, arg)?;
+    arg.parse()
+        .map_err(|_| ArgsError::InvalidNumber(flag.to_string(), arg.to_string()))
+}
+
+/// Arguments of `glimpse-cli trust`: the plugins to trust, none to list those held back.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TrustArgs {
+    /// Executable paths, as listed.
+    pub paths: Vec<String>,
+    /// Trust every plugin held back.
+    pub all: bool,
+}
+
+impl TrustArgs {
+    /// Parse the arguments following `trust`.
+    pub fn parse(args: &[String]) -> Result<Self, ArgsError> {
+        let mut trust = Self::default();
+        for arg in args {
+            match arg.as_str() {
+                "--all" if trust.paths.is_empty() => trust.all = true,
+                flag if flag.starts_with("--") => return Err(ArgsError::Unknown(arg.clone())),
+                _ if !trust.all => trust.paths.push(arg.clone()),
+                _ => return Err(ArgsError::Unknown(arg.clone())),
+            }
+        }
+        Ok(trust)
+    }
+}
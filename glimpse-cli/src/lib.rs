@@ -0,0 +1,4 @@
+//! Formatting helpers for the `glimpse-cli` command line front-end.
+
+pub mod args;
+pub mod output;
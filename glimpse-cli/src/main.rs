@@ -0,0 +1,193 @@
+use anyhow::{anyhow, bail};
+use glimpse_cli::{
+    args::{Format, SearchArgs, TrustArgs},
+    output::{
+        features_table, matches_json, matches_table, metrics_table, plugin_stats_table,
+        untrusted_table, updates_table,
+    },
+};
+use glimpse_client::Client;
+use glimpse_sdk::Modifiers;
+
+const USAGE: &str = "usage:
+    glimpse-cli search <query> [--plugin <id>] [--json] [--activate <index> [--action <index>] [--yes]]
+        search through the running daemon, or a private one if none is running,
+        and optionally run an action of the match at <index>, --yes confirming
+        actions that ask for it
+    glimpse-cli update
+        check the release manifest for newer versions of glimpsed and its plugins
+    glimpse-cli trust [<path>... | --all]
+        list the plugins the daemon holds back until they are trusted, or trust
+        the ones at <path> after reviewing them
+    glimpse-cli stats
+        show how each plugin served searches: latency, failures and the share of
+        activations, kept across daemon restarts
+    glimpse-cli metrics
+        show search latency, result counts, failures and restarts of each plugin
+        since the daemon started
+    glimpse-cli features
+        list the experimental features the daemon has on and those it uses
+        with each plugin
+    glimpse-cli logs <plugin>
+        print what the plugin with the metadata id <plugin> logged recently,
+        glimpsed for the daemon itself";
+
+fn daemon_binary() -> String {
+    std::env::var("GLIMPSED_BIN").unwrap_or_else(|_| "/usr/bin/glimpsed".to_string())
+}
+
+async fn search(args: SearchArgs) -> Result<(), anyhow::Error> {
+    let client = Client::connect_or_spawn(daemon_binary()).await?;
+    let search = match &args.plugin {
+        Some(plugin) => client.search_plugin(&args.query, plugin).await?,
+        None => client.search(&args.query).await?,
+    };
+    let results = search.collect().await?;
+    for error in &results.errors {
+        eprintln!("warning: {}", error);
+    }
+
+    match args.format {
+        Format::Table => println!("{}", matches_table(&results.matches)),
+        Format::Json => println!("{}", matches_json(&results.matches)),
+    }
+
+    if let Some(index) = args.activate {
+        let item = results.matches.get(index).ok_or_else(|| {
+            anyhow!(
+                "no match at index {}, the search returned {}",
+                index,
+                results.matches.len()
+            )
+        })?;
+        let Some(action) = item.actions.get(args.action) else {
+            bail!(
+                "{:?} has {} action(s), there is no action {}",
+                item.title,
+                item.actions.len(),
+                args.action
+            );
+        };
+        if action.requires_confirmation && !args.yes {
+            bail!("{}\npass --yes to run {:?}", action.prompt(), action.title);
+        }
+        let match_id = item
+            .id
+            .ok_or_else(|| anyhow!("{:?} cannot be activated", item.title))?;
+        if action.requires_confirmation {
+            client
+                .activate_confirmed(
+                    results.generation,
+                    match_id,
+                    args.action,
+                    Modifiers::default(),
+                )
+                .await?;
+        } else {
+            client
+                .activate(
+                    results.generation,
+                    match_id,
+                    args.action,
+                    Modifiers::default(),
+                )
+                .await?;
+        }
+    }
+
+    client.close().await?;
+    Ok(())
+}
+
+async fn update() -> Result<(), anyhow::Error> {
+    let client = Client::connect_or_spawn(daemon_binary()).await?;
+    let updates = client.updates(true).await?;
+    println!("{}", updates_table(&updates));
+    Ok(())
+}
+
+async fn trust(args: TrustArgs) -> Result<(), anyhow::Error> {
+    let client = Client::connect_or_spawn(daemon_binary()).await?;
+    let untrusted = client.untrusted_plugins().await?;
+    if args.paths.is_empty() && !args.all {
+        println!("{}", untrusted_table(&untrusted));
+        return Ok(());
+    }
+
+    for path in &args.paths {
+        if !untrusted.iter().any(|plugin| &plugin.path == path) {
+            bail!("{} is not held back, see glimpse-cli trust", path);
+        }
+    }
+    for plugin in untrusted
+        .iter()
+        .filter(|plugin| args.all || args.paths.contains(&plugin.path))
+    {
+        client.trust_plugin(plugin).await?;
+        println!("trusted {}", plugin.path);
+    }
+    Ok(())
+}
+
+async fn stats() -> Result<(), anyhow::Error> {
+    let client = Client::connect_or_spawn(daemon_binary()).await?;
+    let stats = client.plugin_stats().await?;
+    println!("{}", plugin_stats_table(&stats));
+    Ok(())
+}
+
+async fn metrics() -> Result<(), anyhow::Error> {
+    let client = Client::connect_or_spawn(daemon_binary()).await?;
+    println!("{}", metrics_table(&client.metrics().await?));
+    Ok(())
+}
+
+async fn features() -> Result<(), anyhow::Error> {
+    let client = Client::connect_or_spawn(daemon_binary()).await?;
+    let (daemon, plugins) = client.features().await?;
+    println!("{}", features_table(&daemon, &plugins));
+    Ok(())
+}
+
+async fn logs(plugin_id: &str) -> Result<(), anyhow::Error> {
+    let client = Client::connect_or_spawn(daemon_binary()).await?;
+    for record in client.logs(plugin_id, None).await? {
+        println!("{}", record);
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(tracing::Level::WARN)
+        .init();
+
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(String::as_str) {
+        Some("search") => match SearchArgs::parse(&args[1..]) {
+            Ok(search_args) => search(search_args).await,
+            Err(e) => {
+                eprintln!("{}\n\n{}", e, USAGE);
+                std::process::exit(2);
+            }
+        },
+        Some("update") if args.len() == 1 => update().await,
+        Some("stats") if args.len() == 1 => stats().await,
+        Some("metrics") if args.len() == 1 => metrics().await,
+        Some("features") if args.len() == 1 => features().await,
+        Some("logs") if args.len() == 2 => logs(&args[1]).await,
+        Some("trust") => match TrustArgs::parse(&args[1..]) {
+            Ok(trust_args) => trust(trust_args).await,
+            Err(e) => {
+                eprintln!("{}\n\n{}", e, USAGE);
+                std::process::exit(2);
+            }
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+
+use glimpse_sdk::{AvailableUpdate, Features, Match, PluginMetrics, PluginStats, UntrustedPlugin};
+
+/// Plain text listing of available updates, one component per line.
+pub fn updates_table(updates: &[AvailableUpdate]) -> String {
+    if updates.is_empty() {
+        return "everything is up to date".to_string();
+    }
+
+    let width = updates
+        .iter()
+        .map(|update| update.component.len())
+        .max()
+        .unwrap_or(0);
+    let mut lines = updates
+        .iter()
+        .map(|update| {
+            let mut line = format!(
+                "{:width$}  {} -> {}",
+                update.component,
+                update.installed,
+                update.latest,
+                width = width
+            );
+            if let Some(url) = &update.url {
+                line.push_str("  ");
+                line.push_str(url);
+            }
+            line
+        })
+        .collect::<Vec<_>>();
+    // updates are only reported, installing them is up to the user
+    lines.push(format!(
+        "\n{} update(s) available, nothing was installed",
+        updates.len()
+    ));
+    lines.join("\n")
+}
+
+/// Plain text listing of the plugins the daemon holds back, with the digests to review.
+pub fn untrusted_table(plugins: &[UntrustedPlugin]) -> String {
+    if plugins.is_empty() {
+        return "no plugins are held back".to_string();
+    }
+
+    let width = plugins
+        .iter()
+        .map(|plugin| plugin.path.len())
+        .max()
+        .unwrap_or(0);
+    let mut lines = plugins
+        .iter()
+        .map(|plugin| {
+            format!(
+                "{:width$}  {}  {}",
+                plugin.path,
+                plugin.sha256,
+                if plugin.changed { "changed" } else { "new" },
+                width = width
+            )
+        })
+        .collect::<Vec<_>>();
+    lines.push(format!(
+        "\n{} plugin(s) held back, trust them with `glimpse-cli trust <path>` once reviewed",
+        plugins.len()
+    ));
+    lines.join("\n")
+}
+
+/// Plain text listing of plugin statistics under a header, one plugin per line.
+pub fn plugin_stats_table(stats: &[PluginStats]) -> String {
+    if stats.is_empty() {
+        return "no searches recorded yet".to_string();
+    }
+
+    let width = stats
+        .iter()
+        .map(|plugin| plugin.plugin_id.len())
+        .max()
+        .unwrap_or(0)
+        .max("plugin".len());
+    let mut lines = vec![format!(
+        "{:width$}  {:>8}  {:>11}  {:>6}  {:>8}  {:>11}",
+        "plugin",
+        "searches",
+        "avg latency",
+        "errors",
+        "timeouts",
+        "activations",
+        width = width
+    )];
+    lines.extend(stats.iter().map(|plugin| {
+        format!(
+            "{:width$}  {:>8}  {:>8} ms  {:>6}  {:>8}  {:>11}  ({:.1}% failed, {:.1}% of activations)",
+            plugin.plugin_id,
+            plugin.searches,
+            plugin.average_latency_ms,
+            plugin.errors,
+            plugin.timeouts,
+            plugin.activations,
+            plugin.error_rate * 100.0,
+            plugin.activation_share * 100.0,
+            width = width
+        )
+    }));
+    lines.join("\n")
+}
+
+/// Plain text listing of search metrics since the daemon started, one plugin per line.
+pub fn metrics_table(metrics: &[PluginMetrics]) -> String {
+    if metrics.is_empty() {
+        return "no searches since the daemon started".to_string();
+    }
+
+    let width = metrics
+        .iter()
+        .map(|plugin| plugin.plugin_id.len())
+        .max()
+        .unwrap_or(0)
+        .max("plugin".len());
+    let mut lines = vec![format!(
+        "{:width$}  {:>8}  {:>7}  {:>8}  {:>8}  {:>8}  {:>6}  {:>8}  {:>8}",
+        "plugin",
+        "searches",
+        "results",
+        "avg",
+        "p95",
+        "max",
+        "errors",
+        "timeouts",
+        "restarts",
+        width = width
+    )];
+    lines.extend(metrics.iter().map(|plugin| {
+        format!(
+            "{:width$}  {:>8}  {:>7}  {:>5} ms  {:>5} ms  {:>5} ms  {:>6}  {:>8}  {:>8}",
+            plugin.plugin_id,
+            plugin.searches,
+            plugin.results,
+            plugin.average_latency_ms,
+            plugin.p95_latency_ms,
+            plugin.max_latency_ms,
+            plugin.errors,
+            plugin.timeouts,
+            plugin.restarts,
+            width = width
+        )
+    }));
+    lines.join("\n")
+}
+
+/// Plain text listing of matches, one per line prefixed with the index to activate it by.
+pub fn matches_table(matches: &[Match]) -> String {
+    if matches.is_empty() {
+        return "no matches".to_string();
+    }
+
+    let index_width = (matches.len() - 1).to_string().len();
+    let title_width = matches
+        .iter()
+        .map(|item| item.title.chars().count())
+        .max()
+        .unwrap_or(0);
+    matches
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let line = format!(
+                "{:>index_width$}  {:title_width$}  {}",
+                index,
+                item.title,
+                item.description,
+                index_width = index_width,
+                title_width = title_width
+            );
+            line.trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Matches as a JSON array, in the shape the daemon sends them.
+pub fn matches_json(matches: &[Match]) -> String {
+    serde_json::to_string_pretty(matches).unwrap()
+}
+
+/// Plain text listing of the experimental features of the daemon and of each plugin.
+pub fn features_table(daemon: &Features, plugins: &BTreeMap<String, Features>) -> String {
+    let names = |features: &Features| match features.enabled() {
+        enabled if enabled.is_empty() => "none".to_string(),
+        enabled => enabled.join(", "),
+    };
+    let width = plugins
+        .keys()
+        .map(String::len)
+        .max()
+        .unwrap_or(0)
+        .max("glimpsed".len());
+    let mut lines = vec![format!(
+        "{:width$}  {}",
+        "glimpsed",
+        names(daemon),
+        width = width
+    )];
+    lines.extend(plugins.iter().map(|(plugin_id, features)| {
+        format!("{:width$}  {}", plugin_id, names(features), width = width)
+    }));
+    lines.join("\n")
+}
@@ -0,0 +1,101 @@
+use glimpse_cli::args::{ArgsError, Format, SearchArgs, TrustArgs};
+
+fn parse(args: &[&str]) -> Result<SearchArgs, ArgsError> {
+    let args = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    SearchArgs::parse(&args)
+}
+
+#[test]
+fn test_parse_query_only() {
+    assert_eq!(
+        parse(&["firefox"]).unwrap(),
+        SearchArgs {
+            query: "firefox".to_string(),
+            plugin: None,
+            format: Format::Table,
+            activate: None,
+            action: 0,
+            yes: false,
+        }
+    );
+}
+
+#[test]
+fn test_parse_activation_and_flags() {
+    let args = parse(&[
+        "firefox",
+        "--activate",
+        "0",
+        "--action",
+        "1",
+        "--json",
+        "--plugin",
+        "apps",
+        "--yes",
+    ])
+    .unwrap();
+
+    assert_eq!(args.activate, Some(0));
+    assert_eq!(args.action, 1);
+    assert_eq!(args.format, Format::Json);
+    assert_eq!(args.plugin.as_deref(), Some("apps"));
+    assert!(args.yes);
+}
+
+#[test]
+fn test_parse_query_after_separator() {
+    assert_eq!(parse(&["--", "--help"]).unwrap().query, "--help");
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(parse(&[]), Err(ArgsError::MissingQuery));
+    assert_eq!(
+        parse(&["firefox", "--activate"]),
+        Err(ArgsError::MissingValue("--activate".to_string()))
+    );
+    assert_eq!(
+        parse(&["firefox", "--activate", "first"]),
+        Err(ArgsError::InvalidNumber(
+            "--activate".to_string(),
+            "first".to_string()
+        ))
+    );
+    assert_eq!(
+        parse(&["firefox", "--action", "1"]),
+        Err(ArgsError::ActionWithoutActivate)
+    );
+    assert_eq!(
+        parse(&["firefox", "--yes"]),
+        Err(ArgsError::ActionWithoutActivate)
+    );
+    assert_eq!(
+        parse(&["firefox", "chrome"]),
+        Err(ArgsError::Unknown("chrome".to_string()))
+    );
+    assert_eq!(
+        parse(&["firefox", "--verbose"]),
+        Err(ArgsError::Unknown("--verbose".to_string()))
+    );
+}
+
+#[test]
+fn test_parse_trust() {
+    let trust = |args: &[&str]| {
+        let args = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        TrustArgs::parse(&args)
+    };
+    assert_eq!(
+        trust(&["/plugins/a", "/plugins/b"]).unwrap().paths,
+        vec!["/plugins/a", "/plugins/b"]
+    );
+    assert!(trust(&["--all"]).unwrap().all);
+    assert_eq!(
+        trust(&["--all", "/plugins/a"]),
+        Err(ArgsError::Unknown("/plugins/a".to_string()))
+    );
+    assert_eq!(
+        trust(&["/plugins/a", "--all"]),
+        Err(ArgsError::Unknown("--all".to_string()))
+    );
+}
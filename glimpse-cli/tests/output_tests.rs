@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+
+use glimpse_cli::output::{
+    features_table, matches_json, matches_table, metrics_table, plugin_stats_table,
+    untrusted_table, updates_table,
+};
+use glimpse_sdk::{AvailableUpdate, Features, Match, PluginMetrics, PluginStats, UntrustedPlugin};
+
+fn create_update(component: &str, url: Option<&str>) -> AvailableUpdate {
+    AvailableUpdate {
+        component: component.to_string(),
+        installed: "0.1.0".to_string(),
+        latest: "0.2.0".to_string(),
+        url: url.map(str::to_string),
+        notes: None,
+    }
+}
+
+#[test]
+fn test_updates_table_aligns_components() {
+    let table = updates_table(&[
+        create_update("glimpsed", Some("https://example.com/glimpsed")),
+        create_update("files", None),
+    ]);
+    let lines = table.lines().collect::<Vec<_>>();
+
+    assert_eq!(
+        lines[0],
+        "glimpsed  0.1.0 -> 0.2.0  https://example.com/glimpsed"
+    );
+    assert_eq!(lines[1], "files     0.1.0 -> 0.2.0");
+    assert!(table.ends_with("2 update(s) available, nothing was installed"));
+}
+
+#[test]
+fn test_updates_table_without_updates() {
+    assert_eq!(updates_table(&[]), "everything is up to date");
+}
+
+fn create_match(id: usize, title: &str, description: &str) -> Match {
+    Match {
+        id: Some(id),
+        title: title.to_string(),
+        description: description.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_matches_table_prefixes_indexes() {
+    let matches = (0..11)
+        .map(|i| create_match(i, &format!("App {}", i), ""))
+        .chain([create_match(11, "Firefox", "Web browser")])
+        .collect::<Vec<_>>();
+    let table = matches_table(&matches);
+    let lines = table.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines[0], " 0  App 0");
+    assert_eq!(lines[10], "10  App 10");
+    assert_eq!(lines[11], "11  Firefox  Web browser");
+    assert_eq!(matches_table(&[]), "no matches");
+}
+
+#[test]
+fn test_matches_json_round_trips() {
+    let matches = vec![create_match(3, "Firefox", "Web browser")];
+    let parsed: Vec<Match> = serde_json::from_str(&matches_json(&matches)).unwrap();
+    assert_eq!(parsed, matches);
+}
+
+#[test]
+fn test_untrusted_table() {
+    let plugins = vec![
+        UntrustedPlugin {
+            path: "/plugins/glimpse-plugin-files".to_string(),
+            sha256: "9f86d0".to_string(),
+            changed: false,
+        },
+        UntrustedPlugin {
+            path: "/plugins/glimpse-plugin-a".to_string(),
+            sha256: "60303a".to_string(),
+            changed: true,
+        },
+    ];
+    let table = untrusted_table(&plugins);
+    let lines = table.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines[0], "/plugins/glimpse-plugin-files  9f86d0  new");
+    assert_eq!(lines[1], "/plugins/glimpse-plugin-a      60303a  changed");
+    assert!(lines[3].starts_with("2 plugin(s) held back"));
+    assert_eq!(untrusted_table(&[]), "no plugins are held back");
+}
+
+#[test]
+fn test_plugin_stats_table() {
+    let stats = vec![PluginStats {
+        plugin_id: "me.aresa.glimpse.apps".to_string(),
+        searches: 1200,
+        average_latency_ms: 12,
+        activations: 340,
+        activation_share: 0.68,
+        errors: 3,
+        timeouts: 3,
+        error_rate: 0.005,
+    }];
+    let table = plugin_stats_table(&stats);
+    let lines = table.lines().collect::<Vec<_>>();
+
+    assert_eq!(
+        lines[0],
+        "plugin                 searches  avg latency  errors  timeouts  activations"
+    );
+    assert_eq!(
+        lines[1],
+        "me.aresa.glimpse.apps      1200        12 ms       3         3          340  (0.5% failed, 68.0% of activations)"
+    );
+    assert_eq!(plugin_stats_table(&[]), "no searches recorded yet");
+}
+
+#[test]
+fn test_metrics_table() {
+    let metrics = vec![PluginMetrics {
+        plugin_id: "me.aresa.glimpse.apps".to_string(),
+        searches: 120,
+        results: 4800,
+        errors: 1,
+        timeouts: 2,
+        restarts: 1,
+        error_rate: 0.025,
+        average_latency_ms: 12,
+        p95_latency_ms: 50,
+        max_latency_ms: 310,
+    }];
+    let table = metrics_table(&metrics);
+    let lines = table.lines().collect::<Vec<_>>();
+
+    assert_eq!(
+        lines[0],
+        "plugin                 searches  results       avg       p95       max  errors  timeouts  restarts"
+    );
+    assert_eq!(
+        lines[1],
+        "me.aresa.glimpse.apps       120     4800     12 ms     50 ms    310 ms       1         2         1"
+    );
+    assert_eq!(metrics_table(&[]), "no searches since the daemon started");
+}
+
+#[test]
+fn test_features_table() {
+    let plugins = BTreeMap::from([
+        ("me.aresa.glimpse.apps".to_string(), Features::default()),
+        ("me.aresa.glimpse.calc".to_string(), Features::none()),
+    ]);
+    let table = features_table(&Features::default(), &plugins);
+    let lines = table.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines[0], "glimpsed               streaming, subscriptions");
+    assert_eq!(lines[1], "me.aresa.glimpse.apps  streaming, subscriptions");
+    assert_eq!(lines[2], "me.aresa.glimpse.calc  none");
+}
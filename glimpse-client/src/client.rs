@@ -0,0 +1,570 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    ffi::OsStr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use glimpse_sdk::{
+    AvailableUpdate, Features, Frame, HistoryEntry, LogRecord, Message, Metadata, Method,
+    MethodResult, Modifiers, PluginMetrics, PluginStats, UntrustedPlugin, get_client_socket_path,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    process::{Child, Command},
+    sync::mpsc,
+    task::JoinHandle,
+};
+
+use crate::{ClientError, Search, SearchResults, Subscription};
+
+pub(crate) type Routes = Arc<Mutex<HashMap<usize, mpsc::UnboundedSender<Message>>>>;
+
+/// Connection to a glimpse daemon.
+///
+/// Requests get their own ids and responses are routed back to the search that issued them.
+/// The daemon runs one search per client at a time, so starting a search ends the previous one.
+pub struct Client {
+    next_id: AtomicUsize,
+    writer_tx: mpsc::Sender<Message>,
+    pending: Routes,
+    subscriptions: Routes,
+    requests: Routes,
+    reader_handle: JoinHandle<()>,
+    writer_handle: JoinHandle<()>,
+    _child: Option<Child>,
+}
+
+impl Client {
+    /// Start a private daemon process and talk to it over stdio, the way the GUI does.
+    pub fn spawn(daemon_binary: impl AsRef<OsStr>) -> Result<Self, ClientError> {
+        let mut child = Command::new(daemon_binary)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdin = child.stdin.take().ok_or(ClientError::Disconnected)?;
+        let stdout = child.stdout.take().ok_or(ClientError::Disconnected)?;
+
+        let mut client = Self::from_io(stdout, stdin);
+        client._child = Some(child);
+        Ok(client)
+    }
+
+    /// Connect to a daemon listening on a Unix socket.
+    #[cfg(unix)]
+    pub async fn connect(path: impl AsRef<std::path::Path>) -> Result<Self, ClientError> {
+        let stream = tokio::net::UnixStream::connect(path).await?;
+        let (reader, writer) = stream.into_split();
+        Ok(Self::from_io(reader, writer))
+    }
+
+    /// Connect to a daemon listening on a named pipe, such as `\\.\pipe\glimpse-alice`.
+    #[cfg(windows)]
+    pub async fn connect(path: impl AsRef<std::path::Path>) -> Result<Self, ClientError> {
+        let pipe = tokio::net::windows::named_pipe::ClientOptions::new().open(path.as_ref())?;
+        let (reader, writer) = tokio::io::split(pipe);
+        Ok(Self::from_io(reader, writer))
+    }
+
+    /// Connect to the daemon listening on [`get_client_socket_path`], or start a private one
+    /// over stdio if none is running.
+    pub async fn connect_or_spawn(daemon_binary: impl AsRef<OsStr>) -> Result<Self, ClientError> {
+        match Self::connect(get_client_socket_path()).await {
+            Ok(client) => Ok(client),
+            Err(err) => {
+                tracing::debug!("no daemon listening ({}), starting one", err);
+                Self::spawn(daemon_binary)
+            }
+        }
+    }
+
+    /// Speak the daemon protocol over an arbitrary transport.
+    pub fn from_io<R, W>(reader: R, writer: W) -> Self
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let (writer_tx, writer_rx) = mpsc::channel::<Message>(10);
+        let pending: Routes = Arc::new(Mutex::new(HashMap::new()));
+        let subscriptions: Routes = Arc::new(Mutex::new(HashMap::new()));
+        let requests: Routes = Arc::new(Mutex::new(HashMap::new()));
+
+        Self {
+            next_id: AtomicUsize::new(1),
+            writer_tx,
+            pending: pending.clone(),
+            subscriptions: subscriptions.clone(),
+            requests: requests.clone(),
+            reader_handle: tokio::spawn(read_responses(reader, pending, subscriptions, requests)),
+            writer_handle: tokio::spawn(write_requests(writer, writer_rx)),
+            _child: None,
+        }
+    }
+
+    fn next_id(&self) -> usize {
+        self.next_id.fetch_add(1, Ordering::SeqCst)
+    }
+
+    async fn send(&self, message: Message) -> Result<(), ClientError> {
+        self.writer_tx
+            .send(message)
+            .await
+            .map_err(|_| ClientError::Disconnected)
+    }
+
+    /// Search all plugins.
+    pub async fn search(&self, query: &str) -> Result<Search, ClientError> {
+        self.start_search(query, None).await
+    }
+
+    /// Search a single plugin by its metadata id.
+    pub async fn search_plugin(&self, query: &str, plugin_id: &str) -> Result<Search, ClientError> {
+        self.start_search(query, Some(plugin_id.to_string())).await
+    }
+
+    async fn start_search(
+        &self,
+        query: &str,
+        plugin_id: Option<String>,
+    ) -> Result<Search, ClientError> {
+        let id = self.next_id();
+        let (tx, rx) = mpsc::unbounded_channel();
+        {
+            let mut pending = self.pending.lock().unwrap();
+            // superseded searches will never complete, close their streams
+            pending.clear();
+            pending.insert(id, tx);
+        }
+
+        self.send(Message::Request {
+            id,
+            method: Method::Search(query.to_string()),
+            plugin_id,
+            deadline_ms: None,
+        })
+        .await?;
+        Ok(Search::new(id, rx))
+    }
+
+    /// Ask for the matches `offset..offset + limit` of the search with id `generation`, the
+    /// daemon sends a page of a search at a time. The returned search yields the matches the
+    /// client lacks and completes with the ranking of all pages so far.
+    pub async fn more(
+        &self,
+        generation: usize,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Search, ClientError> {
+        let id = self.next_id();
+        let (tx, rx) = mpsc::unbounded_channel();
+        {
+            let mut pending = self.pending.lock().unwrap();
+            pending.clear();
+            // pages come under the search, a stale search is rejected under the request
+            pending.insert(generation, tx.clone());
+            pending.insert(id, tx);
+        }
+
+        self.send(Message::Request {
+            id,
+            method: Method::More {
+                request_id: generation,
+                offset,
+                limit,
+            },
+            plugin_id: None,
+            deadline_ms: None,
+        })
+        .await?;
+        Ok(Search::new(generation, rx))
+    }
+
+    /// Run an action of a match returned by the search with id `generation`.
+    pub async fn activate(
+        &self,
+        generation: usize,
+        match_id: usize,
+        action: usize,
+        modifiers: Modifiers,
+    ) -> Result<(), ClientError> {
+        let id = self.next_id();
+        self.send(Message::Request {
+            id,
+            method: Method::Activate {
+                generation,
+                match_id,
+                action,
+                modifiers,
+                confirmed: false,
+            },
+            plugin_id: None,
+            deadline_ms: None,
+        })
+        .await
+    }
+
+    /// Run an action the user confirmed, the daemon refuses actions with
+    /// `requires_confirmation` set otherwise.
+    pub async fn activate_confirmed(
+        &self,
+        generation: usize,
+        match_id: usize,
+        action: usize,
+        modifiers: Modifiers,
+    ) -> Result<(), ClientError> {
+        let id = self.next_id();
+        self.send(Message::Request {
+            id,
+            method: Method::Activate {
+                generation,
+                match_id,
+                action,
+                modifiers,
+                confirmed: true,
+            },
+            plugin_id: None,
+            deadline_ms: None,
+        })
+        .await
+    }
+
+    /// Receive updates a plugin pushes for `topic`. Dropping the subscription unsubscribes.
+    pub async fn subscribe(
+        &self,
+        plugin_id: &str,
+        topic: &str,
+    ) -> Result<Subscription, ClientError> {
+        let id = self.next_id();
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.subscriptions.lock().unwrap().insert(id, tx);
+
+        self.send(Message::Request {
+            id,
+            method: Method::Subscribe {
+                plugin_id: plugin_id.to_string(),
+                topic: topic.to_string(),
+            },
+            plugin_id: None,
+            deadline_ms: None,
+        })
+        .await?;
+        Ok(Subscription::new(
+            id,
+            plugin_id.to_string(),
+            topic.to_string(),
+            rx,
+            self.writer_tx.clone(),
+            self.subscriptions.clone(),
+        ))
+    }
+
+    /// Previously activated matches, most frecent first.
+    pub async fn history(&self, limit: usize) -> Result<Vec<HistoryEntry>, ClientError> {
+        match self.request(Method::History { limit }).await? {
+            Message::Response {
+                result: Some(MethodResult::History { items }),
+                ..
+            } => Ok(items),
+            other => Err(ClientError::Daemon(format!(
+                "unexpected history response: {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// Query and ranked matches of the last completed search, if the daemon kept a fresh one.
+    /// Ends the current search; the matches are activated under the returned generation.
+    pub async fn last_results(&self) -> Result<Option<(String, SearchResults)>, ClientError> {
+        self.pending.lock().unwrap().clear();
+        match self.request(Method::LastResults).await? {
+            Message::Response {
+                id,
+                result: Some(MethodResult::LastResults { query, items }),
+                ..
+            } => Ok(Some((
+                query,
+                SearchResults {
+                    generation: id,
+                    matches: items,
+                    errors: vec![],
+                },
+            ))),
+            Message::Response {
+                result: None | Some(MethodResult::None),
+                ..
+            } => Ok(None),
+            other => Err(ClientError::Daemon(format!(
+                "unexpected last results response: {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// Newer releases of the daemon and its plugins. With `check` the daemon fetches the
+    /// release manifest first, otherwise it answers with what its last periodic check found.
+    pub async fn updates(&self, check: bool) -> Result<Vec<AvailableUpdate>, ClientError> {
+        match self.request(Method::Updates { check }).await? {
+            Message::Response {
+                result: Some(MethodResult::Updates { items }),
+                ..
+            } => Ok(items),
+            other => Err(ClientError::Daemon(format!(
+                "unexpected updates response: {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// Metadata of the plugins running and authenticated, by id.
+    pub async fn plugins(&self) -> Result<Vec<Metadata>, ClientError> {
+        match self.request(Method::Plugins).await? {
+            Message::Response {
+                result: Some(MethodResult::Plugins { items }),
+                ..
+            } => Ok(items),
+            other => Err(ClientError::Daemon(format!(
+                "unexpected plugins response: {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// Plugin executables the daemon found but does not run until they are trusted.
+    pub async fn untrusted_plugins(&self) -> Result<Vec<UntrustedPlugin>, ClientError> {
+        match self.request(Method::UntrustedPlugins).await? {
+            Message::Response {
+                result: Some(MethodResult::UntrustedPlugins { items }),
+                ..
+            } => Ok(items),
+            other => Err(ClientError::Daemon(format!(
+                "unexpected untrusted plugins response: {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// Trust a plugin the daemon holds back, it starts unless the executable no longer has
+    /// the digest `plugin` was listed with.
+    pub async fn trust_plugin(&self, plugin: &UntrustedPlugin) -> Result<(), ClientError> {
+        self.request(Method::TrustPlugin {
+            path: plugin.path.clone(),
+            sha256: plugin.sha256.clone(),
+        })
+        .await
+        .map(|_| ())
+    }
+
+    /// How each plugin served searches, busiest first.
+    pub async fn plugin_stats(&self) -> Result<Vec<PluginStats>, ClientError> {
+        match self.request(Method::PluginStats).await? {
+            Message::Response {
+                result: Some(MethodResult::PluginStats { items }),
+                ..
+            } => Ok(items),
+            other => Err(ClientError::Daemon(format!(
+                "unexpected plugin stats response: {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// Search metrics of every plugin since the daemon started.
+    pub async fn metrics(&self) -> Result<Vec<PluginMetrics>, ClientError> {
+        match self.request(Method::Stats).await? {
+            Message::Response {
+                result: Some(MethodResult::Stats { items }),
+                ..
+            } => Ok(items),
+            other => Err(ClientError::Daemon(format!(
+                "unexpected metrics response: {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// The experimental features the daemon has on, and those it uses with each plugin by
+    /// metadata id.
+    pub async fn features(&self) -> Result<(Features, BTreeMap<String, Features>), ClientError> {
+        match self.request(Method::Features).await? {
+            Message::Response {
+                result: Some(MethodResult::Features { daemon, plugins }),
+                ..
+            } => Ok((daemon, plugins)),
+            other => Err(ClientError::Daemon(format!(
+                "unexpected features response: {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// What the plugin with this metadata id logged at or after `since`, unix milliseconds,
+    /// oldest first. `glimpsed` reads the daemon's own log.
+    pub async fn logs(
+        &self,
+        plugin_id: &str,
+        since: Option<u64>,
+    ) -> Result<Vec<LogRecord>, ClientError> {
+        let method = Method::GetLogs {
+            plugin_id: plugin_id.to_string(),
+            since,
+        };
+        match self.request(method).await? {
+            Message::Response {
+                result: Some(MethodResult::Logs { items }),
+                ..
+            } => Ok(items),
+            other => Err(ClientError::Daemon(format!(
+                "unexpected logs response: {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// Send a request answered by a single response, daemon errors become `Err`.
+    async fn request(&self, method: Method) -> Result<Message, ClientError> {
+        let id = self.next_id();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        self.requests.lock().unwrap().insert(id, tx);
+
+        self.send(Message::Request {
+            id,
+            method,
+            plugin_id: None,
+            deadline_ms: None,
+        })
+        .await?;
+
+        match rx.recv().await.ok_or(ClientError::Disconnected)? {
+            Message::Response {
+                error: Some(error), ..
+            } => Err(ClientError::Daemon(error.message)),
+            response => Ok(response),
+        }
+    }
+
+    /// Stop the current search.
+    pub async fn cancel(&self) -> Result<(), ClientError> {
+        self.pending.lock().unwrap().clear();
+        let id = self.next_id();
+        self.send(Message::Request {
+            id,
+            method: Method::Cancel(None),
+            plugin_id: None,
+            deadline_ms: None,
+        })
+        .await
+    }
+
+    /// Send the queued requests and disconnect. A daemon started with `spawn` sees its
+    /// client leave and is waited for, so requests like `activate` are carried out first.
+    pub async fn close(mut self) -> Result<(), ClientError> {
+        let (closed_tx, _) = mpsc::channel(1);
+        drop(std::mem::replace(&mut self.writer_tx, closed_tx));
+        let _ = (&mut self.writer_handle).await;
+        if let Some(child) = self._child.as_mut() {
+            child.wait().await?;
+        }
+        Ok(())
+    }
+
+    /// Ask the daemon and its plugins to shut down.
+    pub async fn quit(&self) -> Result<(), ClientError> {
+        let id = self.next_id();
+        self.send(Message::Request {
+            id,
+            method: Method::Quit,
+            plugin_id: None,
+            deadline_ms: None,
+        })
+        .await
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.reader_handle.abort();
+        self.writer_handle.abort();
+    }
+}
+
+async fn read_responses<R: AsyncRead + Unpin>(
+    reader: R,
+    pending: Routes,
+    subscriptions: Routes,
+    requests: Routes,
+) {
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    let mut queued = VecDeque::new();
+    loop {
+        let Some(message) = queued.pop_front() else {
+            line.clear();
+            match reader.read_line(&mut line).await {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(err) => {
+                    tracing::warn!("failed to read from daemon: {}", err);
+                    break;
+                }
+            }
+
+            match Frame::parse(&line) {
+                Ok(frame) => queued.extend(frame.into_messages()),
+                Err(err) => tracing::warn!("failed to parse daemon JSON: {}", err),
+            }
+            continue;
+        };
+
+        let Message::Response { id, ref result, .. } = message else {
+            tracing::debug!("ignoring daemon message: {:?}", &message);
+            continue;
+        };
+
+        if let Some(tx) = subscriptions.lock().unwrap().get(&id) {
+            let _ = tx.send(message);
+            continue;
+        }
+
+        if let Some(tx) = requests.lock().unwrap().remove(&id) {
+            let _ = tx.send(message);
+            continue;
+        }
+
+        let mut pending = pending.lock().unwrap();
+        let Some(tx) = pending.get(&id) else {
+            tracing::debug!("no pending request for response {}", id);
+            continue;
+        };
+        let completed = matches!(result, Some(MethodResult::Snapshot { .. }));
+        let _ = tx.send(message);
+        if completed {
+            pending.remove(&id);
+        }
+    }
+
+    // closing the senders ends all in-flight searches and subscriptions
+    pending.lock().unwrap().clear();
+    subscriptions.lock().unwrap().clear();
+    requests.lock().unwrap().clear();
+}
+
+async fn write_requests<W: AsyncWrite + Unpin>(mut writer: W, mut rx: mpsc::Receiver<Message>) {
+    while let Some(message) = rx.recv().await {
+        let request = serde_json::to_string(&message).unwrap();
+        if let Err(e) = writer.write_all(request.as_bytes()).await {
+            tracing::error!("failed to write to daemon: {}", e);
+            break;
+        }
+        if let Err(e) = writer.write_all(b"\n").await {
+            tracing::error!("failed to write newline to daemon: {}", e);
+            break;
+        }
+        if let Err(e) = writer.flush().await {
+            tracing::error!("failed to flush daemon stream: {}", e);
+            break;
+        }
+    }
+}
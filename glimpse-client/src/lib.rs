@@ -0,0 +1,56 @@
+//! Client library for embedding glimpse searches in other applications.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), glimpse_client::ClientError> {
+//! let client = glimpse_client::Client::spawn("glimpsed")?;
+//! let results = client.search("firefox").await?.collect().await?;
+//! for item in &results.matches {
+//!     println!("{} - {}", item.title, item.description);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod client;
+pub mod list;
+pub mod search;
+pub mod subscription;
+
+use std::{error::Error, fmt::Display};
+
+pub use client::*;
+pub use list::*;
+pub use search::*;
+pub use subscription::*;
+
+#[derive(Debug)]
+pub enum ClientError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Disconnected,
+    Daemon(String),
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::Io(err) => write!(f, "io: {}", err),
+            ClientError::Json(err) => write!(f, "json: {}", err),
+            ClientError::Disconnected => write!(f, "disconnected from daemon"),
+            ClientError::Daemon(msg) => write!(f, "daemon: {}", msg),
+        }
+    }
+}
+impl Error for ClientError {}
+
+impl From<std::io::Error> for ClientError {
+    fn from(err: std::io::Error) -> Self {
+        ClientError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for ClientError {
+    fn from(err: serde_json::Error) -> Self {
+        ClientError::Json(err)
+    }
+}
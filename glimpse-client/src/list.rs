@@ -0,0 +1,218 @@
+use std::collections::{HashMap, HashSet};
+
+use glimpse_sdk::{Match, SnapshotItem};
+
+use crate::SearchEvent;
+
+/// A change to a result list. Applied in order, the splices of a call turn the list as it
+/// was into [`ResultListModel::items`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Splice {
+    Insert { index: usize, items: Vec<Match> },
+    Remove { index: usize, count: usize },
+}
+
+impl Splice {
+    /// Apply the splice to a copy of the list, e.g. a frontend's own rows.
+    pub fn apply_to<T: From<Match>>(&self, list: &mut Vec<T>) {
+        match self {
+            Splice::Insert { index, items } => {
+                list.splice(*index..*index, items.iter().cloned().map(T::from));
+            }
+            Splice::Remove { index, count } => {
+                list.drain(*index..*index + *count);
+            }
+        }
+    }
+}
+
+/// Identity of a match across batches: the daemon's id, or its text for matches without.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Id(usize),
+    Text(String, String),
+}
+
+fn key(item: &Match) -> Key {
+    match item.id {
+        Some(id) => Key::Id(id),
+        None => Key::Text(item.title.clone(), item.description.clone()),
+    }
+}
+
+/// The matches of a search as a frontend lists them, best first, kept up to date as batches
+/// stream in. Every change comes back as the splices that bring the frontend's list along,
+/// so rows are inserted and removed instead of rebuilt.
+#[derive(Debug, Default)]
+pub struct ResultListModel {
+    items: Vec<Match>,
+}
+
+impl ResultListModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn items(&self) -> &[Match] {
+        &self.items
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Update the list with a search event: batches are merged in, the final snapshot
+    /// re-ranks the list. Errors change nothing.
+    pub fn apply(&mut self, event: &SearchEvent) -> Vec<Splice> {
+        match event {
+            SearchEvent::Matches { items, .. } => self.extend(items.clone()),
+            SearchEvent::Completed(snapshot) => self.rerank(snapshot),
+            SearchEvent::Error { .. } => vec![],
+        }
+    }
+
+    /// Empty the list, e.g. when a new search starts.
+    pub fn clear(&mut self) -> Vec<Splice> {
+        let count = std::mem::take(&mut self.items).len();
+        match count {
+            0 => vec![],
+            count => vec![Splice::Remove { index: 0, count }],
+        }
+    }
+
+    /// Merge a batch in by score. Matches listed already are replaced, moving if their score
+    /// changed, or left alone if nothing changed. Equal scores keep the order they came in.
+    pub fn extend(&mut self, items: Vec<Match>) -> Vec<Splice> {
+        let mut splices = vec![];
+        for item in items {
+            let item_key = key(&item);
+            if let Some(index) = self.items.iter().position(|m| key(m) == item_key) {
+                if self.items[index] == item {
+                    continue;
+                }
+                self.items.remove(index);
+                push(&mut splices, Splice::Remove { index, count: 1 });
+            }
+            let index = self.items.partition_point(|m| m.score >= item.score);
+            self.items.insert(index, item.clone());
+            push(
+                &mut splices,
+                Splice::Insert {
+                    index,
+                    items: vec![item],
+                },
+            );
+        }
+        splices
+    }
+
+    /// Reorder the list as the daemon's final snapshot ranks it, with as few moves as
+    /// possible. Matches left out of the snapshot are removed, scores take the snapshot's.
+    pub fn rerank(&mut self, snapshot: &[SnapshotItem]) -> Vec<Splice> {
+        let current = std::mem::take(&mut self.items);
+        let mut listed = current
+            .iter()
+            .map(|item| (key(item), item))
+            .collect::<HashMap<_, _>>();
+        self.items = snapshot
+            .iter()
+            .filter_map(|ranked| {
+                let mut item = listed.remove(&Key::Id(ranked.id))?.clone();
+                item.score = ranked.score;
+                Some(item)
+            })
+            .collect();
+        let rank = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(rank, item)| (key(item), rank))
+            .collect::<HashMap<_, _>>();
+
+        // matches already in snapshot order stay, everything else moves
+        let ranks = current
+            .iter()
+            .map(|item| rank.get(&key(item)).copied())
+            .collect::<Vec<_>>();
+        let kept = longest_increasing(&ranks);
+
+        let mut splices = vec![];
+        let mut removed = 0;
+        for (index, rank) in ranks.iter().enumerate() {
+            if rank.is_none_or(|rank| !kept.contains(&rank)) {
+                push(
+                    &mut splices,
+                    Splice::Remove {
+                        index: index - removed,
+                        count: 1,
+                    },
+                );
+                removed += 1;
+            }
+        }
+        for (index, item) in self.items.iter().enumerate() {
+            if !kept.contains(&index) {
+                push(
+                    &mut splices,
+                    Splice::Insert {
+                        index,
+                        items: vec![item.clone()],
+                    },
+                );
+            }
+        }
+        splices
+    }
+}
+
+/// Add `splice`, merging it into the previous one where they touch.
+fn push(splices: &mut Vec<Splice>, splice: Splice) {
+    match (splices.last_mut(), splice) {
+        (
+            Some(Splice::Insert { index, items }),
+            Splice::Insert {
+                index: next,
+                items: more,
+            },
+        ) if next == *index + items.len() => items.extend(more),
+        (
+            Some(Splice::Remove { index, count }),
+            Splice::Remove {
+                index: next,
+                count: more,
+            },
+        ) if next == *index => *count += more,
+        (_, splice) => splices.push(splice),
+    }
+}
+
+/// The longest increasing run of the present values, not necessarily adjacent.
+fn longest_increasing(values: &[Option<usize>]) -> HashSet<usize> {
+    // tails[n] is the position of the smallest value ending a run of n + 1
+    let mut tails: Vec<usize> = vec![];
+    let mut previous = vec![None; values.len()];
+    for (position, value) in values.iter().enumerate() {
+        let Some(value) = value else {
+            continue;
+        };
+        let length = tails.partition_point(|tail| values[*tail].is_some_and(|v| v < *value));
+        if length > 0 {
+            previous[position] = Some(tails[length - 1]);
+        }
+        match tails.get_mut(length) {
+            Some(tail) => *tail = position,
+            None => tails.push(position),
+        }
+    }
+    let mut run = HashSet::new();
+    let mut position = tails.last().copied();
+    while let Some(at) = position {
+        run.extend(values[at]);
+        position = previous[at];
+    }
+    run
+}
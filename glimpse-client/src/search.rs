@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use glimpse_sdk::{Match, Message, MethodResult, RpcError, SnapshotItem};
+use tokio::sync::mpsc;
+
+use crate::ClientError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchEvent {
+    /// A batch of matches from one plugin.
+    Matches {
+        plugin_id: Option<String>,
+        items: Vec<Match>,
+    },
+    /// A plugin failed to answer the search.
+    Error {
+        plugin_id: Option<String>,
+        message: String,
+    },
+    /// Every plugin has answered; final ordering of all matches.
+    Completed(Vec<SnapshotItem>),
+}
+
+/// All matches of a completed search, in the daemon's final order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResults {
+    pub generation: usize,
+    pub matches: Vec<Match>,
+    pub errors: Vec<String>,
+}
+
+/// An in-flight search. Events stop once the search completes or a newer search supersedes it.
+pub struct Search {
+    generation: usize,
+    rx: mpsc::UnboundedReceiver<Message>,
+    completed: bool,
+}
+
+impl Search {
+    pub(crate) fn new(generation: usize, rx: mpsc::UnboundedReceiver<Message>) -> Self {
+        Self {
+            generation,
+            rx,
+            completed: false,
+        }
+    }
+
+    /// Request id of the search, needed to activate its matches.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub async fn next(&mut self) -> Option<SearchEvent> {
+        if self.completed {
+            return None;
+        }
+
+        loop {
+            let Message::Response {
+                id,
+                error,
+                result,
+                plugin_id,
+            } = self.rx.recv().await?
+            else {
+                continue;
+            };
+            // a rejected request for more ends the search
+            if id != self.generation && error.is_some() {
+                self.completed = true;
+            }
+
+            let event = match (result, error) {
+                (Some(MethodResult::Snapshot { items }), _) => {
+                    self.completed = true;
+                    SearchEvent::Completed(items)
+                }
+                (Some(MethodResult::Matches { items }), _) => {
+                    SearchEvent::Matches { plugin_id, items }
+                }
+                (Some(MethodResult::Error { message }), _)
+                | (None, Some(RpcError { message, .. })) => {
+                    SearchEvent::Error { plugin_id, message }
+                }
+                _ => continue,
+            };
+            return Some(event);
+        }
+    }
+
+    /// Wait for the search to complete and return its matches ordered by the final snapshot.
+    pub async fn collect(mut self) -> Result<SearchResults, ClientError> {
+        let mut matches = HashMap::new();
+        let mut errors = vec![];
+        while let Some(event) = self.next().await {
+            match event {
+                SearchEvent::Matches { items, .. } => {
+                    matches.extend(items.into_iter().filter_map(|m| m.id.map(|id| (id, m))));
+                }
+                SearchEvent::Error { message, .. } => errors.push(message),
+                SearchEvent::Completed(snapshot) => {
+                    let matches = snapshot
+                        .iter()
+                        .filter_map(|item| matches.remove(&item.id))
+                        .collect();
+                    return Ok(SearchResults {
+                        generation: self.generation,
+                        matches,
+                        errors,
+                    });
+                }
+            }
+        }
+        Err(ClientError::Disconnected)
+    }
+}
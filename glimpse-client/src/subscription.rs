@@ -0,0 +1,76 @@
+use glimpse_sdk::{Match, Message, Method, MethodResult, RpcError};
+use tokio::sync::mpsc;
+
+use crate::{ClientError, client::Routes};
+
+/// Live updates of a plugin topic. Unsubscribes when dropped.
+pub struct Subscription {
+    id: usize,
+    plugin_id: String,
+    topic: String,
+    rx: mpsc::UnboundedReceiver<Message>,
+    writer_tx: mpsc::Sender<Message>,
+    routes: Routes,
+}
+
+impl Subscription {
+    pub(crate) fn new(
+        id: usize,
+        plugin_id: String,
+        topic: String,
+        rx: mpsc::UnboundedReceiver<Message>,
+        writer_tx: mpsc::Sender<Message>,
+        routes: Routes,
+    ) -> Self {
+        Self {
+            id,
+            plugin_id,
+            topic,
+            rx,
+            writer_tx,
+            routes,
+        }
+    }
+
+    pub fn plugin_id(&self) -> &str {
+        &self.plugin_id
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Wait for the next update. Each update replaces the previous one.
+    pub async fn next(&mut self) -> Option<Result<Vec<Match>, ClientError>> {
+        loop {
+            let Message::Response { error, result, .. } = self.rx.recv().await? else {
+                continue;
+            };
+
+            match (result, error) {
+                (Some(MethodResult::Update { items, .. }), _) => return Some(Ok(items)),
+                (Some(MethodResult::Error { message }), _)
+                | (None, Some(RpcError { message, .. })) => {
+                    self.rx.close();
+                    return Some(Err(ClientError::Daemon(message)));
+                }
+                _ => continue,
+            }
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.routes.lock().unwrap().remove(&self.id);
+        let _ = self.writer_tx.try_send(Message::Request {
+            id: self.id,
+            method: Method::Unsubscribe {
+                plugin_id: self.plugin_id.clone(),
+                topic: self.topic.clone(),
+            },
+            plugin_id: None,
+            deadline_ms: None,
+        });
+    }
+}
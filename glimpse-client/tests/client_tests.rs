@@ -0,0 +1,561 @@
+use glimpse_client::{Client, ClientError, SearchEvent};
+use glimpse_sdk::{
+    HistoryEntry, Match, Message, Metadata, Method, MethodResult, Modifiers, RpcError, SnapshotItem,
+};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream, Lines};
+
+/// The daemon side of an in-memory connection.
+struct FakeDaemon {
+    lines: Lines<BufReader<DuplexStream>>,
+    writer: DuplexStream,
+}
+
+impl FakeDaemon {
+    async fn recv(&mut self) -> Message {
+        let line = self.lines.next_line().await.unwrap().unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    async fn send(&mut self, message: Message) {
+        let mut line = serde_json::to_string(&message).unwrap();
+        line.push('\n');
+        self.writer.write_all(line.as_bytes()).await.unwrap();
+    }
+
+    async fn send_matches(&mut self, id: usize, plugin_id: &str, items: Vec<Match>) {
+        self.send(Message::Response {
+            id,
+            error: None,
+            result: Some(MethodResult::Matches { items }),
+            plugin_id: Some(plugin_id.to_string()),
+        })
+        .await;
+    }
+
+    async fn send_snapshot(&mut self, id: usize, items: Vec<SnapshotItem>) {
+        self.send(Message::Response {
+            id,
+            error: None,
+            result: Some(MethodResult::Snapshot { items }),
+            plugin_id: None,
+        })
+        .await;
+    }
+}
+
+fn create_match(id: usize, title: &str, score: f64) -> Match {
+    Match {
+        id: Some(id),
+        title: title.to_string(),
+        score,
+        ..Default::default()
+    }
+}
+
+/// One in-memory pipe per direction between a client and a fake daemon.
+fn connect() -> (Client, FakeDaemon) {
+    let (to_daemon_client, to_daemon_daemon) = tokio::io::duplex(64 * 1024);
+    let (to_client_daemon, to_client_client) = tokio::io::duplex(64 * 1024);
+
+    let client = Client::from_io(to_client_client, to_daemon_client);
+    let daemon = FakeDaemon {
+        lines: BufReader::new(to_daemon_daemon).lines(),
+        writer: to_client_daemon,
+    };
+    (client, daemon)
+}
+
+#[tokio::test]
+async fn test_search_sends_request() {
+    let (client, mut daemon) = connect();
+
+    let search = client.search("firefox").await.unwrap();
+
+    match daemon.recv().await {
+        Message::Request {
+            id,
+            method,
+            plugin_id,
+            deadline_ms: None,
+        } => {
+            assert_eq!(id, search.generation());
+            assert_eq!(method, Method::Search("firefox".to_string()));
+            assert_eq!(plugin_id, None);
+        }
+        other => panic!("expected search request, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_search_plugin_targets_plugin() {
+    let (client, mut daemon) = connect();
+
+    client
+        .search_plugin("2+2", "me.aresa.glimpse.calculator")
+        .await
+        .unwrap();
+
+    match daemon.recv().await {
+        Message::Request { plugin_id, .. } => {
+            assert_eq!(plugin_id.as_deref(), Some("me.aresa.glimpse.calculator"))
+        }
+        other => panic!("expected search request, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_collect_orders_matches_by_snapshot() {
+    let (client, mut daemon) = connect();
+    let search = client.search("fire").await.unwrap();
+    let id = search.generation();
+    daemon.recv().await;
+
+    daemon
+        .send_matches(id, "plugin.a", vec![create_match(0, "low", 0.1)])
+        .await;
+    daemon
+        .send_matches(id, "plugin.b", vec![create_match(1, "high", 0.9)])
+        .await;
+    daemon
+        .send_snapshot(
+            id,
+            vec![
+                SnapshotItem { id: 1, score: 0.9 },
+                SnapshotItem { id: 0, score: 0.1 },
+            ],
+        )
+        .await;
+
+    let results = search.collect().await.unwrap();
+    assert_eq!(results.generation, id);
+    assert_eq!(
+        results
+            .matches
+            .iter()
+            .map(|m| m.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["high", "low"]
+    );
+}
+
+#[tokio::test]
+async fn test_search_events_and_errors() {
+    let (client, mut daemon) = connect();
+    let mut search = client.search("fire").await.unwrap();
+    let id = search.generation();
+    daemon.recv().await;
+
+    daemon
+        .send(Message::Response {
+            id,
+            error: Some(RpcError::plugin("boom")),
+            result: None,
+            plugin_id: Some("plugin.a".to_string()),
+        })
+        .await;
+    daemon.send_snapshot(id, vec![]).await;
+
+    assert_eq!(
+        search.next().await,
+        Some(SearchEvent::Error {
+            plugin_id: Some("plugin.a".to_string()),
+            message: "boom".to_string(),
+        })
+    );
+    assert_eq!(search.next().await, Some(SearchEvent::Completed(vec![])));
+    assert_eq!(search.next().await, None);
+}
+
+#[tokio::test]
+async fn test_responses_for_other_requests_are_not_routed() {
+    let (client, mut daemon) = connect();
+    let mut search = client.search("fire").await.unwrap();
+    let id = search.generation();
+    daemon.recv().await;
+
+    daemon
+        .send_matches(id + 100, "plugin.a", vec![create_match(0, "other", 1.0)])
+        .await;
+    daemon
+        .send_matches(id, "plugin.a", vec![create_match(0, "mine", 1.0)])
+        .await;
+
+    match search.next().await {
+        Some(SearchEvent::Matches { items, .. }) => assert_eq!(items[0].title, "mine"),
+        other => panic!("expected matches, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_new_search_supersedes_previous() {
+    let (client, mut daemon) = connect();
+    let mut first = client.search("fi").await.unwrap();
+    let second = client.search("fire").await.unwrap();
+
+    assert_ne!(first.generation(), second.generation());
+    assert_eq!(first.next().await, None);
+    daemon.recv().await;
+}
+
+#[tokio::test]
+async fn test_activate_sends_generation_and_modifiers() {
+    let (client, mut daemon) = connect();
+    let modifiers = Modifiers {
+        shift: true,
+        ..Default::default()
+    };
+
+    client.activate(4, 2, 1, modifiers).await.unwrap();
+
+    match daemon.recv().await {
+        Message::Request { method, .. } => assert_eq!(
+            method,
+            Method::Activate {
+                generation: 4,
+                match_id: 2,
+                action: 1,
+                modifiers,
+                confirmed: false,
+            }
+        ),
+        other => panic!("expected activate request, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_close_sends_queued_requests() {
+    let (client, mut daemon) = connect();
+
+    client
+        .activate(4, 2, 0, Modifiers::default())
+        .await
+        .unwrap();
+    client.close().await.unwrap();
+
+    assert!(matches!(
+        daemon.recv().await,
+        Message::Request {
+            method: Method::Activate { .. },
+            ..
+        }
+    ));
+    assert_eq!(daemon.lines.next_line().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_disconnect_ends_search() {
+    let (client, daemon) = connect();
+    let search = client.search("fire").await.unwrap();
+
+    drop(daemon);
+
+    assert!(search.collect().await.is_err());
+}
+
+#[tokio::test]
+async fn test_subscription_receives_updates() {
+    let (client, mut daemon) = connect();
+    let mut subscription = client
+        .subscribe("me.aresa.glimpse.debug", "ticker")
+        .await
+        .unwrap();
+
+    let id = match daemon.recv().await {
+        Message::Request { id, method, .. } => {
+            assert_eq!(
+                method,
+                Method::Subscribe {
+                    plugin_id: "me.aresa.glimpse.debug".to_string(),
+                    topic: "ticker".to_string(),
+                }
+            );
+            id
+        }
+        other => panic!("expected subscribe request, got {:?}", other),
+    };
+
+    for tick in ["Tick 0", "Tick 1"] {
+        daemon
+            .send(Message::Response {
+                id,
+                error: None,
+                result: Some(MethodResult::Update {
+                    topic: "ticker".to_string(),
+                    items: vec![create_match(0, tick, 1.0)],
+                }),
+                plugin_id: Some("me.aresa.glimpse.debug".to_string()),
+            })
+            .await;
+        let items = subscription.next().await.unwrap().unwrap();
+        assert_eq!(items[0].title, tick);
+    }
+}
+
+#[tokio::test]
+async fn test_subscription_survives_searches() {
+    let (client, mut daemon) = connect();
+    let mut subscription = client
+        .subscribe("me.aresa.glimpse.debug", "ticker")
+        .await
+        .unwrap();
+    client.search("fire").await.unwrap();
+    let Message::Request { id, .. } = daemon.recv().await else {
+        panic!("expected subscribe request");
+    };
+
+    daemon
+        .send(Message::Response {
+            id,
+            error: None,
+            result: Some(MethodResult::Update {
+                topic: "ticker".to_string(),
+                items: vec![],
+            }),
+            plugin_id: None,
+        })
+        .await;
+
+    assert_eq!(subscription.next().await.unwrap().unwrap(), vec![]);
+}
+
+#[tokio::test]
+async fn test_subscription_error_ends_stream() {
+    let (client, mut daemon) = connect();
+    let mut subscription = client
+        .subscribe("me.aresa.glimpse.debug", "unknown")
+        .await
+        .unwrap();
+    let Message::Request { id, .. } = daemon.recv().await else {
+        panic!("expected subscribe request");
+    };
+
+    daemon
+        .send(Message::Response {
+            id,
+            error: Some(RpcError::plugin("unknown topic: unknown")),
+            result: None,
+            plugin_id: None,
+        })
+        .await;
+
+    assert!(subscription.next().await.unwrap().is_err());
+    assert!(subscription.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_dropping_subscription_unsubscribes() {
+    let (client, mut daemon) = connect();
+    let subscription = client
+        .subscribe("me.aresa.glimpse.debug", "ticker")
+        .await
+        .unwrap();
+    daemon.recv().await;
+
+    drop(subscription);
+
+    match daemon.recv().await {
+        Message::Request { method, .. } => assert_eq!(
+            method,
+            Method::Unsubscribe {
+                plugin_id: "me.aresa.glimpse.debug".to_string(),
+                topic: "ticker".to_string(),
+            }
+        ),
+        other => panic!("expected unsubscribe request, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_history_returns_entries() {
+    let (client, mut daemon) = connect();
+    let entry = HistoryEntry {
+        plugin_id: "test.apps".to_string(),
+        title: "Firefox".to_string(),
+        count: 3,
+        last_used: 1_700_000_000,
+        score: 300.0,
+    };
+
+    let expected = entry.clone();
+    let fake = async move {
+        let Message::Request { id, method, .. } = daemon.recv().await else {
+            panic!("expected a request");
+        };
+        assert_eq!(method, Method::History { limit: 5 });
+        daemon
+            .send(Message::Response {
+                id,
+                error: None,
+                result: Some(MethodResult::History {
+                    items: vec![expected],
+                }),
+                plugin_id: None,
+            })
+            .await;
+        daemon
+    };
+    let (items, _daemon) = tokio::join!(client.history(5), fake);
+
+    assert_eq!(items.unwrap(), vec![entry]);
+}
+
+#[tokio::test]
+async fn test_last_results_are_activated_under_their_request() {
+    let (client, mut daemon) = connect();
+
+    let fake = async move {
+        for answer in [
+            MethodResult::LastResults {
+                query: "fire".to_string(),
+                items: vec![Match {
+                    id: Some(0),
+                    title: "Firefox".to_string(),
+                    ..Default::default()
+                }],
+            },
+            MethodResult::None,
+        ] {
+            let Message::Request { id, method, .. } = daemon.recv().await else {
+                panic!("expected a request");
+            };
+            assert_eq!(method, Method::LastResults);
+            daemon
+                .send(Message::Response {
+                    id,
+                    error: None,
+                    result: Some(answer),
+                    plugin_id: None,
+                })
+                .await;
+        }
+        daemon
+    };
+    let client_side = async {
+        let restored = client.last_results().await.unwrap();
+        let missing = client.last_results().await.unwrap();
+        (restored, missing)
+    };
+    let ((restored, missing), _daemon) = tokio::join!(client_side, fake);
+
+    let (query, results) = restored.unwrap();
+    assert_eq!(query, "fire");
+    assert_eq!(results.matches[0].title, "Firefox");
+    assert!(results.generation > 0);
+    assert!(missing.is_none());
+}
+
+#[tokio::test]
+async fn test_updates_reports_daemon_error() {
+    let (client, mut daemon) = connect();
+
+    let fake = async move {
+        let Message::Request { id, method, .. } = daemon.recv().await else {
+            panic!("expected a request");
+        };
+        assert_eq!(method, Method::Updates { check: true });
+        daemon
+            .send(Message::Response {
+                id,
+                error: Some(RpcError::rejected("update checks are disabled")),
+                result: None,
+                plugin_id: None,
+            })
+            .await;
+        daemon
+    };
+    let (updates, _daemon) = tokio::join!(client.updates(true), fake);
+
+    assert!(matches!(updates, Err(ClientError::Daemon(message)) if message.contains("disabled")));
+}
+
+#[tokio::test]
+async fn test_more_collects_next_page() {
+    let (client, mut daemon) = connect();
+    let search = client.search("fire").await.unwrap();
+    let generation = search.generation();
+    daemon.recv().await;
+
+    let more = client.more(generation, 1, 10).await.unwrap();
+    match daemon.recv().await {
+        Message::Request { method, .. } => assert_eq!(
+            method,
+            Method::More {
+                request_id: generation,
+                offset: 1,
+                limit: 10,
+            }
+        ),
+        other => panic!("expected more request, got {:?}", other),
+    }
+
+    daemon
+        .send_matches(generation, "plugin.a", vec![create_match(1, "Files", 0.5)])
+        .await;
+    daemon
+        .send_snapshot(
+            generation,
+            vec![
+                SnapshotItem { id: 0, score: 0.9 },
+                SnapshotItem { id: 1, score: 0.5 },
+            ],
+        )
+        .await;
+
+    let results = more.collect().await.unwrap();
+    assert_eq!(results.generation, generation);
+    assert_eq!(results.matches.len(), 1);
+    assert_eq!(results.matches[0].title, "Files");
+}
+
+#[tokio::test]
+async fn test_more_of_stale_search_ends() {
+    let (client, mut daemon) = connect();
+
+    let mut more = client.more(7, 50, 50).await.unwrap();
+    let Message::Request { id, .. } = daemon.recv().await else {
+        panic!("expected a request");
+    };
+    daemon
+        .send(Message::Response {
+            id,
+            error: Some(RpcError::rejected("stale search: 7")),
+            result: None,
+            plugin_id: None,
+        })
+        .await;
+
+    assert!(matches!(more.next().await, Some(SearchEvent::Error { .. })));
+    assert_eq!(more.next().await, None);
+}
+
+#[tokio::test]
+async fn test_plugins_returns_metadata() {
+    let (client, mut daemon) = connect();
+    let apps = Metadata {
+        id: "test.apps".to_string(),
+        name: "Apps".to_string(),
+        ..Default::default()
+    };
+
+    let expected = apps.clone();
+    let fake = async move {
+        let Message::Request { id, method, .. } = daemon.recv().await else {
+            panic!("expected a request");
+        };
+        assert_eq!(method, Method::Plugins);
+        daemon
+            .send(Message::Response {
+                id,
+                error: None,
+                result: Some(MethodResult::Plugins {
+                    items: vec![expected],
+                }),
+                plugin_id: None,
+            })
+            .await;
+        daemon
+    };
+    let (items, _daemon) = tokio::join!(client.plugins(), fake);
+
+    assert_eq!(items.unwrap(), vec![apps]);
+}
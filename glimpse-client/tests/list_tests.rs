@@ -0,0 +1,186 @@
+use glimpse_client::{ResultListModel, SearchEvent, Splice};
+use glimpse_sdk::{Match, SnapshotItem};
+
+fn item(id: usize, score: f64) -> Match {
+    Match {
+        id: Some(id),
+        title: format!("match {}", id),
+        score,
+        ..Default::default()
+    }
+}
+
+fn batch(plugin_id: &str, items: Vec<Match>) -> SearchEvent {
+    SearchEvent::Matches {
+        plugin_id: Some(plugin_id.to_string()),
+        items,
+    }
+}
+
+fn ids(items: &[Match]) -> Vec<usize> {
+    items.iter().filter_map(|item| item.id).collect()
+}
+
+/// Apply `event` to the model and its splices to a copy of the list, which must end up the
+/// same as the model.
+fn apply(model: &mut ResultListModel, rows: &mut Vec<Match>, event: &SearchEvent) -> Vec<Splice> {
+    let splices = model.apply(event);
+    for splice in &splices {
+        splice.apply_to(rows);
+    }
+    assert_eq!(ids(rows), ids(model.items()));
+    splices
+}
+
+#[test]
+fn test_interleaved_batches() {
+    let mut model = ResultListModel::new();
+    let mut rows = vec![];
+
+    let splices = apply(
+        &mut model,
+        &mut rows,
+        &batch("apps", vec![item(1, 0.9), item(2, 0.5)]),
+    );
+    assert_eq!(splices.len(), 1);
+    assert!(matches!(&splices[0], Splice::Insert { index: 0, items } if items.len() == 2));
+
+    // a slower plugin's matches land between the first ones
+    let splices = apply(
+        &mut model,
+        &mut rows,
+        &batch("files", vec![item(3, 0.7), item(4, 0.6), item(5, 0.1)]),
+    );
+    assert_eq!(ids(model.items()), vec![1, 3, 4, 2, 5]);
+    let spans = splices
+        .iter()
+        .map(|splice| match splice {
+            Splice::Insert { index, items } => (*index, items.len()),
+            Splice::Remove { .. } => panic!("unexpected {:?}", splice),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(spans, vec![(1, 2), (4, 1)]);
+
+    // equal scores keep their arrival order
+    apply(&mut model, &mut rows, &batch("ssh", vec![item(6, 0.7)]));
+    assert_eq!(ids(model.items()), vec![1, 3, 6, 4, 2, 5]);
+
+    let splices = apply(
+        &mut model,
+        &mut rows,
+        &SearchEvent::Error {
+            plugin_id: Some("broken".to_string()),
+            message: "failed".to_string(),
+        },
+    );
+    assert!(splices.is_empty());
+}
+
+#[test]
+fn test_duplicates_are_merged() {
+    let mut model = ResultListModel::new();
+    let mut rows = vec![];
+    apply(
+        &mut model,
+        &mut rows,
+        &batch("apps", vec![item(1, 0.9), item(2, 0.5)]),
+    );
+
+    // the same match again changes nothing
+    let splices = apply(&mut model, &mut rows, &batch("apps", vec![item(2, 0.5)]));
+    assert!(splices.is_empty());
+    assert_eq!(model.len(), 2);
+
+    // an updated match replaces the listed one in place
+    let mut renamed = item(2, 0.5);
+    renamed.title = "renamed".to_string();
+    let splices = apply(&mut model, &mut rows, &batch("apps", vec![renamed]));
+    assert_eq!(splices.len(), 2);
+    assert_eq!(model.items()[1].title, "renamed");
+    assert_eq!(rows[1].title, "renamed");
+
+    // a better score moves it up
+    apply(&mut model, &mut rows, &batch("apps", vec![item(2, 1.0)]));
+    assert_eq!(ids(model.items()), vec![2, 1]);
+
+    // matches without ids are told apart by their text
+    let unnamed = Match {
+        title: "plain".to_string(),
+        score: 0.1,
+        ..Default::default()
+    };
+    apply(&mut model, &mut rows, &batch("x", vec![unnamed.clone()]));
+    apply(&mut model, &mut rows, &batch("x", vec![unnamed]));
+    assert_eq!(model.len(), 3);
+}
+
+#[test]
+fn test_rerank_moves_few_rows() {
+    let mut model = ResultListModel::new();
+    let mut rows = vec![];
+    apply(
+        &mut model,
+        &mut rows,
+        &batch(
+            "apps",
+            (1..=6).map(|id| item(id, 1.0 - id as f64 / 10.0)).collect(),
+        ),
+    );
+
+    // the ranking moves 6 to the top and drops 3, the rest keeps its order
+    let snapshot = [6, 1, 2, 4, 5]
+        .into_iter()
+        .enumerate()
+        .map(|(rank, id)| SnapshotItem {
+            id,
+            score: 1.0 - rank as f64 / 10.0,
+        })
+        .collect();
+    let splices = apply(&mut model, &mut rows, &SearchEvent::Completed(snapshot));
+    assert_eq!(ids(model.items()), vec![6, 1, 2, 4, 5]);
+    assert_eq!(model.items()[0].score, 1.0);
+    assert_eq!(
+        splices
+            .iter()
+            .map(|splice| match splice {
+                Splice::Insert { index, items } => format!("+{}:{}", index, ids(items)[0]),
+                Splice::Remove { index, count } => format!("-{}:{}", index, count),
+            })
+            .collect::<Vec<_>>(),
+        vec!["-2:1", "-4:1", "+0:6"]
+    );
+
+    // a new search starts from an empty list
+    let splices = model.clear();
+    assert_eq!(splices, vec![Splice::Remove { index: 0, count: 5 }]);
+    assert!(model.is_empty());
+    assert!(model.clear().is_empty());
+}
+
+#[test]
+fn test_streaming_then_rerank_matches_snapshot() {
+    let mut model = ResultListModel::new();
+    let mut rows = vec![];
+    // batches arrive out of order across plugins, some matches show up again
+    let batches = [
+        vec![item(5, 0.2), item(1, 0.8)],
+        vec![item(3, 0.5), item(7, 0.9)],
+        vec![item(1, 0.8), item(2, 0.3)],
+        vec![item(4, 0.5), item(6, 0.1)],
+    ];
+    for items in batches {
+        apply(&mut model, &mut rows, &batch("any", items));
+    }
+    assert_eq!(ids(model.items()), vec![7, 1, 3, 4, 2, 5, 6]);
+
+    let order = [2, 7, 1, 3, 6, 5, 4];
+    let snapshot = order
+        .iter()
+        .map(|id| SnapshotItem {
+            id: *id,
+            score: 0.0,
+        })
+        .collect();
+    apply(&mut model, &mut rows, &SearchEvent::Completed(snapshot));
+    assert_eq!(ids(model.items()), order);
+}
@@ -0,0 +1,121 @@
+use std::{error::Error, fmt::Display, path::PathBuf, str::FromStr};
+
+use crate::synthetic::SyntheticSpec;
+
+/// Arguments of `glimpse-devtools generate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenerateArgs {
+    /// Directory the plugin scripts are written to, usually `GLIMPSE_PLUGIN_DIR`.
+    pub dir: PathBuf,
+    /// Number of plugins to generate.
+    pub plugins: usize,
+    pub spec: SyntheticSpec,
+}
+
+/// Arguments of `glimpse-devtools plugin`, written into the generated scripts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginArgs {
+    /// Tells apart plugins generated together.
+    pub index: usize,
+    pub spec: SyntheticSpec,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgsError {
+    MissingDir,
+    MissingValue(String),
+    InvalidNumber(String, String),
+    Unknown(String),
+}
+
+impl Display for ArgsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgsError::MissingDir => write!(f, "missing plugin directory"),
+            ArgsError::MissingValue(flag) => write!(f, "{} needs a value", flag),
+            ArgsError::InvalidNumber(flag, value) => {
+                write!(f, "{} expects a number, got {:?}", flag, value)
+            }
+            ArgsError::Unknown(arg) => write!(f, "unexpected argument {:?}", arg),
+        }
+    }
+}
+impl Error for ArgsError {}
+
+/// Apply a spec flag, `Ok(false)` if `arg` is not one.
+fn spec_flag<'a>(
+    spec: &mut SyntheticSpec,
+    arg: &str,
+    args: &mut impl Iterator<Item = &'a String>,
+) -> Result<bool, ArgsError> {
+    match arg {
+        "--seed" => spec.seed = number(arg, args.next())?,
+        "--results" => spec.results = number(arg, args.next())?,
+        "--description-len" => spec.description_len = number(arg, args.next())?,
+        "--chunks" => spec.chunks = number(arg, args.next())?,
+        "--latency" => spec.latency_ms = number(arg, args.next())?,
+        "--jitter" => spec.jitter_ms = number(arg, args.next())?,
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+impl GenerateArgs {
+    /// Parse the arguments following `generate`.
+    pub fn parse(args: &[String]) -> Result<Self, ArgsError> {
+        let mut dir = None;
+        let mut plugins = 1;
+        let mut spec = SyntheticSpec::default();
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if spec_flag(&mut spec, arg, &mut args)? {
+                continue;
+            }
+            match arg.as_str() {
+                "--plugins" => plugins = number(arg, args.next())?,
+                flag if flag.starts_with("--") => return Err(ArgsError::Unknown(arg.clone())),
+                _ if dir.is_none() => dir = Some(PathBuf::from(arg)),
+                _ => return Err(ArgsError::Unknown(arg.clone())),
+            }
+        }
+
+        Ok(Self {
+            dir: dir.ok_or(ArgsError::MissingDir)?,
+            plugins,
+            spec,
+        })
+    }
+}
+
+impl PluginArgs {
+    /// Parse the arguments following `plugin`.
+    pub fn parse(args: &[String]) -> Result<Self, ArgsError> {
+        let mut index = 0;
+        let mut spec = SyntheticSpec::default();
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if spec_flag(&mut spec, arg, &mut args)? {
+                continue;
+            }
+            match arg.as_str() {
+                "--index" => index = number(arg, args.next())?,
+                _ => return Err(ArgsError::Unknown(arg.clone())),
+            }
+        }
+        Ok(Self { index, spec })
+    }
+}
+
+fn value<'a>(flag: &str, value: Option<&'a String>) -> Result<&'a str, ArgsError> {
+    value
+        .map(String::as_str)
+        .ok_or_else(|| ArgsError::MissingValue(flag.to_string()))
+}
+
+fn number<T: FromStr>(flag: &str, arg: Option<&String>) -> Result<T, ArgsError> {
+    let arg = value(flag, arg)?;
+    arg.parse()
+        .map_err(|_| ArgsError::InvalidNumber(flag.to_string(), arg.to_string()))
+}
@@ -0,0 +1,4 @@
+//! Tools for developing glimpse: synthetic plugins for benchmarks and GUI load testing.
+
+pub mod args;
+pub mod synthetic;
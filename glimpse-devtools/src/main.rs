@@ -0,0 +1,100 @@
+use anyhow::Context as _;
+use async_trait::async_trait;
+use glimpse_devtools::{
+    args::{GenerateArgs, PluginArgs},
+    synthetic::{SyntheticSpec, write_plugins},
+};
+use glimpse_sdk::{
+    Match, Metadata, Permission, Plugin, PluginError, SearchSink, run_plugin, setup_logging,
+};
+
+const USAGE: &str = "usage:
+    glimpse-devtools generate <dir> [--plugins <n>] [spec flags]
+        write <n> synthetic plugins into <dir>, point GLIMPSE_PLUGIN_DIR at it
+    glimpse-devtools plugin [--index <i>] [spec flags]
+        run a synthetic plugin, the generated scripts do this
+
+spec flags, the same seed always produces the same results:
+    --seed <n> --results <n> --description-len <chars>
+    --chunks <n> --latency <ms> --jitter <ms>";
+
+/// Answers every search with reproducible matches, streamed with the configured delays.
+struct SyntheticPlugin {
+    index: usize,
+    spec: SyntheticSpec,
+}
+
+#[async_trait]
+impl Plugin for SyntheticPlugin {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            id: format!("me.aresa.glimpse.synthetic.{}", self.index),
+            name: format!("Synthetic {}", self.index),
+            version: "0.1.0".to_string(),
+            description: format!(
+                "{} generated results per search, seed {}.",
+                self.spec.results, self.spec.seed
+            ),
+            author: "Alex Oleshkevich <alex.oleshkevich@gmail.com>".to_string(),
+            permissions: vec![Permission::Clipboard],
+            ..Default::default()
+        }
+    }
+
+    async fn search(&self, query: String, sink: &SearchSink) -> Result<(), PluginError> {
+        for (delay, chunk) in self.spec.chunks(&query) {
+            tokio::time::sleep(delay).await;
+            sink.send(chunk).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        Ok(self.spec.matches(&query))
+    }
+}
+
+async fn generate(args: GenerateArgs) -> Result<(), anyhow::Error> {
+    let binary = std::env::current_exe().context("cannot locate glimpse-devtools")?;
+    let written = write_plugins(&args.dir, &binary, args.plugins, &args.spec)
+        .with_context(|| format!("cannot write plugins to {}", args.dir.display()))?;
+    for path in &written {
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(String::as_str) {
+        Some("generate") => match GenerateArgs::parse(&args[1..]) {
+            Ok(generate_args) => generate(generate_args).await,
+            Err(e) => {
+                eprintln!("{}\n\n{}", e, USAGE);
+                std::process::exit(2);
+            }
+        },
+        Some("plugin") => match PluginArgs::parse(&args[1..]) {
+            Ok(plugin_args) => {
+                setup_logging(tracing::Level::INFO);
+                let plugin = SyntheticPlugin {
+                    index: plugin_args.index,
+                    spec: plugin_args.spec,
+                };
+                if let Err(err) = run_plugin(plugin).await {
+                    tracing::error!("error running plugin: {}", err);
+                }
+                Ok(())
+            }
+            Err(e) => {
+                eprintln!("{}\n\n{}", e, USAGE);
+                std::process::exit(2);
+            }
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+}
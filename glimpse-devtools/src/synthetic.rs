@@ -0,0 +1,193 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use glimpse_sdk::{Action, Match, MatchAction};
+
+const WORDS: &[&str] = &[
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliet",
+    "kilo", "lima", "mike", "november", "oscar", "papa", "quebec", "romeo", "sierra", "tango",
+    "uniform", "victor", "whiskey", "xray", "yankee", "zulu",
+];
+
+/// What a synthetic plugin answers every search with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntheticSpec {
+    /// The same seed and query always produce the same matches and delays.
+    pub seed: u64,
+    /// Matches per search.
+    pub results: usize,
+    /// Length of each description, in characters.
+    pub description_len: usize,
+    /// Chunks the matches are streamed in.
+    pub chunks: usize,
+    /// Delay before each chunk, in ms.
+    pub latency_ms: u64,
+    /// Up to this many ms added to each delay.
+    pub jitter_ms: u64,
+}
+
+impl Default for SyntheticSpec {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            results: 100,
+            description_len: 40,
+            chunks: 1,
+            latency_ms: 0,
+            jitter_ms: 0,
+        }
+    }
+}
+
+/// SplitMix64, small and stable across platforms and releases, unlike the std hashers.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, 0 if `n` is 0.
+    pub fn below(&mut self, n: u64) -> u64 {
+        if n == 0 { 0 } else { self.next_u64() % n }
+    }
+
+    /// A number in `0.0..1.0`.
+    pub fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn word(&mut self) -> &'static str {
+        WORDS[self.below(WORDS.len() as u64) as usize]
+    }
+}
+
+/// FNV-1a, so each query gets its own reproducible results.
+fn query_seed(seed: u64, query: &str) -> u64 {
+    query
+        .bytes()
+        .fold(seed ^ 0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+impl SyntheticSpec {
+    /// The matches for `query`, every title starts with the query so ranking sees a match.
+    pub fn matches(&self, query: &str) -> Vec<Match> {
+        let mut rng = Rng::new(query_seed(self.seed, query));
+        (0..self.results)
+            .map(|i| {
+                let title = format!("{} {} {} #{}", query.trim(), rng.word(), rng.word(), i);
+                let mut description = String::new();
+                while description.len() < self.description_len {
+                    if !description.is_empty() {
+                        description.push(' ');
+                    }
+                    description.push_str(rng.word());
+                }
+                description.truncate(self.description_len);
+                Match {
+                    actions: vec![MatchAction {
+                        title: "Copy".to_string(),
+                        close_on_action: true,
+                        action: Action::Clipboard {
+                            text: title.clone().into(),
+                        },
+                        alternates: vec![],
+                        requires_confirmation: false,
+                        confirmation_prompt: None,
+                        expand: vec![],
+                    }],
+                    title,
+                    description,
+                    score: rng.unit(),
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    /// The matches for `query` split into chunks, each with the delay before it is sent.
+    pub fn chunks(&self, query: &str) -> Vec<(Duration, Vec<Match>)> {
+        let mut rng = Rng::new(query_seed(self.seed, query).rotate_left(32));
+        let matches = self.matches(query);
+        let size = matches.len().div_ceil(self.chunks.max(1)).max(1);
+        matches
+            .chunks(size)
+            .map(|chunk| {
+                let delay = self.latency_ms + rng.below(self.jitter_ms + 1);
+                (Duration::from_millis(delay), chunk.to_vec())
+            })
+            .collect()
+    }
+
+    /// Command line flags reproducing this spec, as read by [`crate::args::PluginArgs::parse`].
+    pub fn to_args(&self) -> Vec<String> {
+        [
+            ("--seed", self.seed),
+            ("--results", self.results as u64),
+            ("--description-len", self.description_len as u64),
+            ("--chunks", self.chunks as u64),
+            ("--latency", self.latency_ms),
+            ("--jitter", self.jitter_ms),
+        ]
+        .into_iter()
+        .flat_map(|(flag, value)| [flag.to_string(), value.to_string()])
+        .collect()
+    }
+}
+
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// Write `count` executable scripts into `dir` that start `binary` as synthetic plugins,
+/// each with its own index and a seed derived from `spec.seed`.
+pub fn write_plugins(
+    dir: &Path,
+    binary: &Path,
+    count: usize,
+    spec: &SyntheticSpec,
+) -> io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut written = vec![];
+    for index in 0..count {
+        let spec = SyntheticSpec {
+            seed: spec.seed.wrapping_add(index as u64),
+            ..spec.clone()
+        };
+        let mut args = vec![
+            binary.to_string_lossy().to_string(),
+            "plugin".to_string(),
+            "--index".to_string(),
+            index.to_string(),
+        ];
+        args.extend(spec.to_args());
+        let command = args
+            .iter()
+            .map(|arg| shell_quote(arg))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let path = dir.join(format!("synthetic-{}", index));
+        std::fs::write(&path, format!("#!/bin/sh\nexec {}\n", command))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        }
+        written.push(path);
+    }
+    Ok(written)
+}
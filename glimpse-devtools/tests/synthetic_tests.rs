@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use glimpse_devtools::{
+    args::{ArgsError, GenerateArgs, PluginArgs},
+    synthetic::{SyntheticSpec, write_plugins},
+};
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn create_spec() -> SyntheticSpec {
+    SyntheticSpec {
+        seed: 7,
+        results: 10,
+        description_len: 25,
+        chunks: 3,
+        latency_ms: 20,
+        jitter_ms: 5,
+    }
+}
+
+#[test]
+fn test_matches_are_reproducible() {
+    let spec = create_spec();
+    let matches = spec.matches("fire");
+
+    assert_eq!(matches, spec.matches("fire"));
+    assert_ne!(matches, spec.matches("water"));
+    assert_ne!(matches, SyntheticSpec { seed: 8, ..spec }.matches("fire"));
+    assert_eq!(matches.len(), 10);
+    assert!(matches.iter().all(|item| item.title.starts_with("fire ")));
+    assert!(matches.iter().all(|item| item.description.len() == 25));
+    assert!(matches.iter().all(|item| (0.0..1.0).contains(&item.score)));
+}
+
+#[test]
+fn test_chunks_split_matches_with_delays() {
+    let spec = create_spec();
+    let chunks = spec.chunks("fire");
+
+    assert_eq!(
+        chunks
+            .iter()
+            .map(|(_, chunk)| chunk.len())
+            .collect::<Vec<_>>(),
+        vec![4, 4, 2]
+    );
+    assert!(chunks.iter().all(|(delay, _)| {
+        (Duration::from_millis(20)..=Duration::from_millis(25)).contains(delay)
+    }));
+    let streamed = chunks
+        .into_iter()
+        .flat_map(|(_, chunk)| chunk)
+        .collect::<Vec<_>>();
+    assert_eq!(streamed, spec.matches("fire"));
+    assert_eq!(spec.chunks("fire").len(), 3);
+}
+
+#[test]
+fn test_parse_generate_args() {
+    let parsed = GenerateArgs::parse(&args(&[
+        "var/synthetic",
+        "--plugins",
+        "4",
+        "--results",
+        "5000",
+        "--latency",
+        "50",
+    ]))
+    .unwrap();
+    assert_eq!(parsed.dir.to_str(), Some("var/synthetic"));
+    assert_eq!(parsed.plugins, 4);
+    assert_eq!(parsed.spec.results, 5000);
+    assert_eq!(parsed.spec.latency_ms, 50);
+
+    assert_eq!(GenerateArgs::parse(&[]), Err(ArgsError::MissingDir));
+    assert_eq!(
+        GenerateArgs::parse(&args(&["dir", "--chunks", "many"])),
+        Err(ArgsError::InvalidNumber(
+            "--chunks".to_string(),
+            "many".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_spec_round_trips_through_args() {
+    let spec = create_spec();
+    let mut plugin_args = args(&["--index", "3"]);
+    plugin_args.extend(spec.to_args());
+
+    assert_eq!(
+        PluginArgs::parse(&plugin_args),
+        Ok(PluginArgs { index: 3, spec })
+    );
+}
+
+#[test]
+fn test_write_plugins() {
+    let dir = tempfile::tempdir().unwrap();
+    let written = write_plugins(
+        dir.path(),
+        "/opt/it's here/glimpse-devtools".as_ref(),
+        2,
+        &create_spec(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        written,
+        vec![
+            dir.path().join("synthetic-0"),
+            dir.path().join("synthetic-1")
+        ]
+    );
+    let script = std::fs::read_to_string(&written[1]).unwrap();
+    assert!(script.starts_with("#!/bin/sh\nexec '/opt/it'\\''s here/glimpse-devtools' 'plugin'"));
+    assert!(script.contains("'--index' '1' '--seed' '8'"));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&written[0]).unwrap().permissions().mode();
+        assert_eq!(mode & 0o111, 0o111);
+    }
+}
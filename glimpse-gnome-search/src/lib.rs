@@ -0,0 +1,3 @@
+//! Shows glimpse results in the GNOME Shell overview through its search provider interface.
+
+pub mod provider;
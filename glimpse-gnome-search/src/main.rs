@@ -0,0 +1,171 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use glimpse_client::Client;
+use glimpse_gnome_search::provider::{self, Activation, PATH, ResultMeta, SERVICE, SerializedIcon};
+use glimpse_sdk::{Match, Modifiers};
+use tokio::signal::unix::{SignalKind, signal};
+use zbus::{
+    fdo,
+    zvariant::{OwnedValue, StructureBuilder, Value},
+};
+
+/// `org.gnome.Shell.SearchProvider2`, answering overview searches with glimpse searches.
+/// Matches of the latest search are kept for their metas and activation.
+struct SearchProvider {
+    client: Client,
+    latest: Mutex<HashMap<String, Match>>,
+}
+
+impl SearchProvider {
+    async fn search(&self, terms: &[String]) -> fdo::Result<Vec<String>> {
+        let search = self.client.search(&terms.join(" ")).await.map_err(failed)?;
+        // a newer search supersedes it, the overview no longer waits for it
+        let Ok(results) = search.collect().await else {
+            return Ok(vec![]);
+        };
+        for error in &results.errors {
+            tracing::debug!("search error: {}", error);
+        }
+
+        let ids = provider::result_ids(results.generation, &results.matches);
+        *self.latest.lock().unwrap() = ids.iter().cloned().zip(results.matches).collect();
+        Ok(ids)
+    }
+
+    fn open_launcher(&self) -> fdo::Result<()> {
+        let launcher =
+            std::env::var("GLIMPSE_BIN").unwrap_or_else(|_| "/usr/bin/glimpse".to_string());
+        let mut child = tokio::process::Command::new(&launcher)
+            .spawn()
+            .map_err(|e| fdo::Error::SpawnFailed(format!("{}: {}", launcher, e)))?;
+        // the provider outlives the launcher, reap it once closed
+        tokio::spawn(async move {
+            let _ = child.wait().await;
+        });
+        Ok(())
+    }
+}
+
+#[zbus::interface(name = "org.gnome.Shell.SearchProvider2")]
+impl SearchProvider {
+    async fn get_initial_result_set(&self, terms: Vec<String>) -> fdo::Result<Vec<String>> {
+        self.search(&terms).await
+    }
+
+    /// Searched again rather than narrowed down, plugins may match more of a longer query.
+    async fn get_subsearch_result_set(
+        &self,
+        _previous_results: Vec<String>,
+        terms: Vec<String>,
+    ) -> fdo::Result<Vec<String>> {
+        self.search(&terms).await
+    }
+
+    async fn get_result_metas(&self, identifiers: Vec<String>) -> Vec<HashMap<String, OwnedValue>> {
+        let latest = self.latest.lock().unwrap();
+        identifiers
+            .iter()
+            .filter_map(|id| latest.get(id).map(|item| ResultMeta::of(id, item)))
+            .map(|meta| result_meta(&meta))
+            .collect()
+    }
+
+    async fn activate_result(
+        &self,
+        identifier: String,
+        _terms: Vec<String>,
+        _timestamp: u32,
+    ) -> fdo::Result<()> {
+        let (generation, match_id) = provider::parse_result_id(&identifier)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("unknown result: {}", identifier)))?;
+        let activation = self
+            .latest
+            .lock()
+            .unwrap()
+            .get(&identifier)
+            .map(Activation::of)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("unknown result: {}", identifier)))?;
+        match activation {
+            Activation::Run(action) => self
+                .client
+                .activate(generation, match_id, action, Modifiers::default())
+                .await
+                .map_err(failed),
+            Activation::OpenLauncher => self.open_launcher(),
+        }
+    }
+
+    async fn launch_search(&self, _terms: Vec<String>, _timestamp: u32) -> fdo::Result<()> {
+        self.open_launcher()
+    }
+}
+
+fn result_meta(meta: &ResultMeta) -> HashMap<String, OwnedValue> {
+    let mut properties = HashMap::from([
+        ("id".to_string(), property(meta.id.clone())),
+        ("name".to_string(), property(meta.name.clone())),
+        (
+            "description".to_string(),
+            property(meta.description.clone()),
+        ),
+    ]);
+    if let Some(icon) = &meta.icon {
+        properties.insert("icon".to_string(), serialize_icon(icon));
+    }
+    properties
+}
+
+/// The icon as the `(sv)` GVariant `g_icon_serialize` produces.
+fn serialize_icon(icon: &SerializedIcon) -> OwnedValue {
+    let value = match icon {
+        SerializedIcon::Themed(names) => Value::from(names.clone()),
+        SerializedIcon::File(path) => Value::from(path.clone()),
+        SerializedIcon::Bytes(bytes) => Value::from(bytes.clone()),
+    };
+    let structure = StructureBuilder::new()
+        .add_field(icon.kind())
+        .add_field(value)
+        .build()
+        .expect("an icon has two fields");
+    property(structure)
+}
+
+fn property<'a>(value: impl Into<Value<'a>>) -> OwnedValue {
+    value
+        .into()
+        .try_into()
+        .expect("result metas hold no file descriptors")
+}
+
+fn failed(err: glimpse_client::ClientError) -> fdo::Error {
+    fdo::Error::Failed(err.to_string())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(tracing::Level::INFO)
+        .init();
+
+    let daemon_binary =
+        std::env::var("GLIMPSED_BIN").unwrap_or_else(|_| "/usr/bin/glimpsed".to_string());
+    let client = Client::connect_or_spawn(daemon_binary).await?;
+    let provider = SearchProvider {
+        client,
+        latest: Mutex::new(HashMap::new()),
+    };
+    let _connection = zbus::connection::Builder::session()?
+        .name(SERVICE)?
+        .serve_at(PATH, provider)?
+        .build()
+        .await?;
+    tracing::info!("serving GNOME Shell searches as {} at {}", SERVICE, PATH);
+
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+    Ok(())
+}
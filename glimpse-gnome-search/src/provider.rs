@@ -0,0 +1,100 @@
+use base64::Engine;
+use glimpse_sdk::{Icon, Match};
+
+/// Well-known name the provider takes on the session bus, named by its search provider file.
+pub const SERVICE: &str = "org.glimpse.SearchProvider";
+/// Object path of the `org.gnome.Shell.SearchProvider2` interface.
+pub const PATH: &str = "/org/glimpse/SearchProvider";
+
+/// A GIcon in the form `g_icon_deserialize` reads from the `icon` result meta.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SerializedIcon {
+    /// `('themed', <['name']>)`
+    Themed(Vec<String>),
+    /// `('file', <'path'>)`
+    File(String),
+    /// `('bytes', <[...]>)`, the encoded image.
+    Bytes(Vec<u8>),
+}
+
+impl SerializedIcon {
+    /// `None` for icons GNOME Shell cannot draw, such as emoji.
+    pub fn of(icon: &Icon) -> Option<Self> {
+        match icon {
+            Icon::Freedesktop { name } => Some(SerializedIcon::Themed(vec![name.clone()])),
+            Icon::Path { path } | Icon::Thumbnail { path } => {
+                Some(SerializedIcon::File(path.clone()))
+            }
+            Icon::Data { data, .. } => base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .ok()
+                .map(SerializedIcon::Bytes),
+            // the daemon resolves bundled icons to paths before clients see them
+            Icon::Emoji { .. } | Icon::Bundled { .. } => None,
+        }
+    }
+
+    /// The GIcon type name.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SerializedIcon::Themed(_) => "themed",
+            SerializedIcon::File(_) => "file",
+            SerializedIcon::Bytes(_) => "bytes",
+        }
+    }
+}
+
+/// What GNOME Shell shows for a result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultMeta {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub icon: Option<SerializedIcon>,
+}
+
+impl ResultMeta {
+    pub fn of(id: &str, item: &Match) -> Self {
+        Self {
+            id: id.to_string(),
+            name: item.title.clone(),
+            description: item.description.clone(),
+            icon: item.icon.as_ref().and_then(SerializedIcon::of),
+        }
+    }
+}
+
+/// What activating a result in the overview does. GNOME Shell only activates results, so
+/// the default action is run and everything else is left to the launcher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activation {
+    /// Run the action at this index through the daemon.
+    Run(usize),
+    /// Open the launcher, for matches without actions or whose default action must be
+    /// confirmed first.
+    OpenLauncher,
+}
+
+impl Activation {
+    pub fn of(item: &Match) -> Self {
+        match item.actions.first() {
+            Some(action) if !action.requires_confirmation => Activation::Run(0),
+            _ => Activation::OpenLauncher,
+        }
+    }
+}
+
+/// Result ids of the matches of the search `generation`, in the daemon's order.
+pub fn result_ids(generation: usize, matches: &[Match]) -> Vec<String> {
+    matches
+        .iter()
+        .filter_map(|item| item.id)
+        .map(|id| format!("{}:{}", generation, id))
+        .collect()
+}
+
+/// The search generation and match id of a result id.
+pub fn parse_result_id(id: &str) -> Option<(usize, usize)> {
+    let (generation, match_id) = id.split_once(':')?;
+    Some((generation.parse().ok()?, match_id.parse().ok()?))
+}
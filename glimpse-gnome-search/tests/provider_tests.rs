@@ -0,0 +1,95 @@
+use glimpse_gnome_search::provider::{
+    Activation, ResultMeta, SerializedIcon, parse_result_id, result_ids,
+};
+use glimpse_sdk::{Action, Icon, Match, MatchAction};
+
+fn create_action(requires_confirmation: bool) -> MatchAction {
+    MatchAction {
+        title: "Open".to_string(),
+        action: Action::Open {
+            uri: "file:///tmp".to_string(),
+        },
+        close_on_action: true,
+        alternates: vec![],
+        requires_confirmation,
+        confirmation_prompt: None,
+        expand: vec![],
+    }
+}
+
+fn create_match(id: usize, title: &str) -> Match {
+    Match {
+        id: Some(id),
+        title: title.to_string(),
+        description: format!("{} description", title),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_result_ids_keep_the_daemon_order() {
+    let matches = vec![create_match(4, "a"), Match::default(), create_match(2, "b")];
+
+    assert_eq!(result_ids(9, &matches), vec!["9:4", "9:2"]);
+    assert_eq!(parse_result_id("9:4"), Some((9, 4)));
+    assert_eq!(parse_result_id("9"), None);
+}
+
+#[test]
+fn test_result_meta() {
+    let mut item = create_match(1, "Firefox");
+    item.icon = Some(Icon::freedesktop("firefox"));
+
+    assert_eq!(
+        ResultMeta::of("3:1", &item),
+        ResultMeta {
+            id: "3:1".to_string(),
+            name: "Firefox".to_string(),
+            description: "Firefox description".to_string(),
+            icon: Some(SerializedIcon::Themed(vec!["firefox".to_string()])),
+        }
+    );
+}
+
+#[test]
+fn test_icons_are_serialized_by_kind() {
+    let file = SerializedIcon::of(&Icon::path("/tmp/a.png")).unwrap();
+    assert_eq!(file, SerializedIcon::File("/tmp/a.png".to_string()));
+    assert_eq!(file.kind(), "file");
+
+    let thumbnail = SerializedIcon::of(&Icon::thumbnail("/tmp/b.jpg"));
+    assert_eq!(
+        thumbnail,
+        Some(SerializedIcon::File("/tmp/b.jpg".to_string()))
+    );
+
+    let bytes = SerializedIcon::of(&Icon::data("image/png", "iVBORw==")).unwrap();
+    assert_eq!(bytes, SerializedIcon::Bytes(vec![0x89, b'P', b'N', b'G']));
+    assert_eq!(bytes.kind(), "bytes");
+}
+
+#[test]
+fn test_icons_the_shell_cannot_draw_are_left_out() {
+    assert_eq!(SerializedIcon::of(&Icon::emoji("🦀")), None);
+    assert_eq!(
+        SerializedIcon::of(&Icon::data("image/png", "not base64!")),
+        None
+    );
+}
+
+#[test]
+fn test_activation_runs_the_default_action() {
+    let mut item = create_match(1, "a");
+    item.actions = vec![create_action(false), create_action(true)];
+
+    assert_eq!(Activation::of(&item), Activation::Run(0));
+}
+
+#[test]
+fn test_activation_leaves_confirmations_to_the_launcher() {
+    let mut item = create_match(1, "a");
+    assert_eq!(Activation::of(&item), Activation::OpenLauncher);
+
+    item.actions = vec![create_action(true), create_action(false)];
+    assert_eq!(Activation::of(&item), Activation::OpenLauncher);
+}
@@ -0,0 +1,79 @@
+import 'dart:convert';
+import 'dart:io';
+
+import 'package:glimpse/keymap.dart';
+
+/// GUI preferences stored in $XDG_CONFIG_HOME/glimpse/gui.json.
+class GuiConfig {
+  final Set<String> mutedErrorPlugins;
+  // edited by hand, kept as written so saving the config does not rewrite it
+  final Map<String, dynamic> keymapOverrides;
+  // results on the left, the detail of the highlighted match on the right
+  bool splitView;
+  // typed by the command palette shortcut, the same as `prefix` under [commands] in glimpsed.toml
+  final String commandPrefix;
+  // read by the GTK runner before the window shows: an overlay layer surface on compositors
+  // with layer-shell, a regular window when false
+  final bool layerShell;
+  // results under a heading per category, at most `categoryLimit` of each until expanded
+  final bool groupResults;
+  final int categoryLimit;
+
+  GuiConfig({
+    Set<String>? mutedErrorPlugins,
+    Map<String, dynamic>? keymapOverrides,
+    this.splitView = true,
+    this.commandPrefix = '>',
+    this.layerShell = true,
+    this.groupResults = true,
+    this.categoryLimit = 5,
+  })
+    : mutedErrorPlugins = mutedErrorPlugins ?? {},
+      keymapOverrides = keymapOverrides ?? {};
+
+  Keymap get keymap => Keymap.fromJson(keymapOverrides);
+
+  static File get file {
+    final configHome =
+        Platform.environment['XDG_CONFIG_HOME'] ?? '${Platform.environment['HOME'] ?? '.'}/.config';
+    return File('$configHome/glimpse/gui.json');
+  }
+
+  factory GuiConfig.fromJson(Map<String, dynamic> json) {
+    return GuiConfig(
+      mutedErrorPlugins: ((json['muted_error_plugins'] as List<dynamic>?) ?? []).map((e) => e as String).toSet(),
+      keymapOverrides: json['keymap'] as Map<String, dynamic>?,
+      splitView: json['split_view'] as bool? ?? true,
+      commandPrefix: json['command_prefix'] as String? ?? '>',
+      layerShell: json['layer_shell'] as bool? ?? true,
+      groupResults: json['group_results'] as bool? ?? true,
+      categoryLimit: json['category_limit'] as int? ?? 5,
+    );
+  }
+
+  Map<String, dynamic> toJson() => {
+    'muted_error_plugins': mutedErrorPlugins.toList()..sort(),
+    'split_view': splitView,
+    if (keymapOverrides.isNotEmpty) 'keymap': keymapOverrides,
+    if (commandPrefix != '>') 'command_prefix': commandPrefix,
+    if (!layerShell) 'layer_shell': false,
+    if (!groupResults) 'group_results': false,
+    if (categoryLimit != 5) 'category_limit': categoryLimit,
+  };
+
+  static Future<GuiConfig> load() async {
+    try {
+      return GuiConfig.fromJson(jsonDecode(await file.readAsString()) as Map<String, dynamic>);
+    } on PathNotFoundException {
+      return GuiConfig();
+    } catch (e) {
+      print('Failed to read ${file.path}, using defaults: $e');
+      return GuiConfig();
+    }
+  }
+
+  Future<void> save() async {
+    await file.parent.create(recursive: true);
+    await file.writeAsString(const JsonEncoder.withIndent('  ').convert(toJson()));
+  }
+}
@@ -0,0 +1,61 @@
+import 'dart:math';
+
+import 'package:flutter/services.dart';
+
+/// Who holds keyboard focus while the launcher window is open.
+enum FocusOwner { entry, actionMenu }
+
+/// Keeps the search entry usable after actions: focus goes back to it after every activation
+/// that leaves the window open, and after the action menu closes, with the cursor where the
+/// user left it.
+class FocusPolicy {
+  FocusOwner _owner = FocusOwner.entry;
+  TextSelection _selection = const TextSelection.collapsed(offset: -1);
+
+  FocusOwner get owner => _owner;
+
+  /// Remember the cursor while the user types. Changes seen while the entry is unfocused are
+  /// ignored, focus moving away may collapse the selection.
+  void entryChanged(TextEditingValue value, {required bool focused}) {
+    if (_owner == FocusOwner.entry && focused && value.selection.isValid) {
+      _selection = value.selection;
+    }
+  }
+
+  /// The query was replaced, e.g. by picking a recent item, the cursor moves to its end.
+  void textReplaced(String text) {
+    _selection = TextSelection.collapsed(offset: text.length);
+  }
+
+  void menuOpened() {
+    _owner = FocusOwner.actionMenu;
+  }
+
+  /// The action menu closed without an action. Returns whether focus must go back to the entry.
+  bool menuClosed() {
+    if (_owner != FocusOwner.actionMenu) {
+      return false;
+    }
+    _owner = FocusOwner.entry;
+    return true;
+  }
+
+  /// An action was dispatched. Returns whether focus must go back to the entry, which it does
+  /// unless the window closes.
+  bool activated({required bool closesWindow}) {
+    _owner = FocusOwner.entry;
+    return !closesWindow;
+  }
+
+  /// The cursor to restore in `text`, kept inside it if the text got shorter meanwhile.
+  /// The end of the text if the user never placed it.
+  TextSelection selectionFor(String text) {
+    if (!_selection.isValid) {
+      return TextSelection.collapsed(offset: text.length);
+    }
+    return _selection.copyWith(
+      baseOffset: min(_selection.baseOffset, text.length),
+      extentOffset: min(_selection.extentOffset, text.length),
+    );
+  }
+}
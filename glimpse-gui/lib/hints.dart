@@ -0,0 +1,34 @@
+/// Letter hints for result rows, in the order results arrive.
+///
+/// Hints stick to match ids for the whole search, so rows keep their hint when
+/// streaming updates or the final snapshot reorder them.
+class HintAssigner {
+  static const alphabet = 'asdfghjklqwertyuiopzxcvbnm';
+
+  int _generation = 0;
+  final _hints = <int, String>{};
+
+  void assign(int generation, Iterable<int?> matchIds) {
+    if (generation != _generation) {
+      _generation = generation;
+      _hints.clear();
+    }
+    for (final id in matchIds.whereType<int>()) {
+      if (_hints.length >= alphabet.length) {
+        return;
+      }
+      _hints.putIfAbsent(id, () => alphabet[_hints.length]);
+    }
+  }
+
+  String? hintFor(int? matchId) => _hints[matchId];
+
+  int? matchIdFor(String hint) {
+    for (final entry in _hints.entries) {
+      if (entry.value == hint) {
+        return entry.key;
+      }
+    }
+    return null;
+  }
+}
@@ -0,0 +1,240 @@
+import 'package:flutter/services.dart';
+
+/// Things the launcher window does on a key press.
+enum Command {
+  next,
+  previous,
+  close,
+  clear,
+  activate,
+  // the numbered action after the default one of the selected match
+  activateAlt1,
+  activateAlt2,
+  activateAlt3,
+  activateAlt4,
+  activateAlt5,
+  activateAlt6,
+  activateAlt7,
+  activateAlt8,
+  activateAlt9,
+  actionMenu,
+  toggleDetails,
+  commandPalette,
+  pluginLogs,
+  // the first match of the next or previous section of grouped results
+  nextSection,
+  previousSection;
+
+  /// The action of the selected match an `activateAlt` command runs, null for the others.
+  int? get alternateAction {
+    final number = index - activateAlt1.index + 1;
+    return number >= 1 && number <= 9 ? number : null;
+  }
+}
+
+/// How a binding recognizes its key.
+enum KeyMatch {
+  /// By position on the keyboard, as on a US layout, so Alt+K stays where it is on Dvorak,
+  /// Workman and non-Latin layouts. Keys without a known position fall back to [character].
+  physical,
+
+  /// By the character the key types on the active layout, following the key labels.
+  character,
+}
+
+const _physicalKeys = <String, PhysicalKeyboardKey>{
+  'a': PhysicalKeyboardKey.keyA,
+  'b': PhysicalKeyboardKey.keyB,
+  'c': PhysicalKeyboardKey.keyC,
+  'd': PhysicalKeyboardKey.keyD,
+  'e': PhysicalKeyboardKey.keyE,
+  'f': PhysicalKeyboardKey.keyF,
+  'g': PhysicalKeyboardKey.keyG,
+  'h': PhysicalKeyboardKey.keyH,
+  'i': PhysicalKeyboardKey.keyI,
+  'j': PhysicalKeyboardKey.keyJ,
+  'k': PhysicalKeyboardKey.keyK,
+  'l': PhysicalKeyboardKey.keyL,
+  'm': PhysicalKeyboardKey.keyM,
+  'n': PhysicalKeyboardKey.keyN,
+  'o': PhysicalKeyboardKey.keyO,
+  'p': PhysicalKeyboardKey.keyP,
+  'q': PhysicalKeyboardKey.keyQ,
+  'r': PhysicalKeyboardKey.keyR,
+  's': PhysicalKeyboardKey.keyS,
+  't': PhysicalKeyboardKey.keyT,
+  'u': PhysicalKeyboardKey.keyU,
+  'v': PhysicalKeyboardKey.keyV,
+  'w': PhysicalKeyboardKey.keyW,
+  'x': PhysicalKeyboardKey.keyX,
+  'y': PhysicalKeyboardKey.keyY,
+  'z': PhysicalKeyboardKey.keyZ,
+  '1': PhysicalKeyboardKey.digit1,
+  '2': PhysicalKeyboardKey.digit2,
+  '3': PhysicalKeyboardKey.digit3,
+  '4': PhysicalKeyboardKey.digit4,
+  '5': PhysicalKeyboardKey.digit5,
+  '6': PhysicalKeyboardKey.digit6,
+  '7': PhysicalKeyboardKey.digit7,
+  '8': PhysicalKeyboardKey.digit8,
+  '9': PhysicalKeyboardKey.digit9,
+  '0': PhysicalKeyboardKey.digit0,
+};
+
+// the same on every layout
+const _namedKeys = <String, LogicalKeyboardKey>{
+  'enter': LogicalKeyboardKey.enter,
+  'escape': LogicalKeyboardKey.escape,
+  'tab': LogicalKeyboardKey.tab,
+  'space': LogicalKeyboardKey.space,
+  'backspace': LogicalKeyboardKey.backspace,
+  'up': LogicalKeyboardKey.arrowUp,
+  'down': LogicalKeyboardKey.arrowDown,
+  'left': LogicalKeyboardKey.arrowLeft,
+  'right': LogicalKeyboardKey.arrowRight,
+  'pageup': LogicalKeyboardKey.pageUp,
+  'pagedown': LogicalKeyboardKey.pageDown,
+};
+
+/// The letter or digit a key types on a US layout, for non-Latin layouts typing none.
+String? positionalCharacter(KeyEvent event) =>
+    _physicalKeys.entries.where((entry) => entry.value == event.physicalKey).firstOrNull?.key;
+
+/// A key with the modifiers held with it, written like "ctrl+shift+c".
+class KeyChord {
+  final String key;
+  final bool ctrl;
+  final bool alt;
+  final bool shift;
+  final bool meta;
+
+  const KeyChord(this.key, {this.ctrl = false, this.alt = false, this.shift = false, this.meta = false});
+
+  /// Null for an empty key or an unknown modifier.
+  static KeyChord? parse(String chord) {
+    final parts = chord.toLowerCase().split('+').map((part) => part.trim()).toList();
+    final key = parts.removeLast();
+    if (key.isEmpty || (key.length > 1 && !_namedKeys.containsKey(key))) {
+      return null;
+    }
+    final modifiers = parts.toSet();
+    if (!modifiers.every(const {'ctrl', 'alt', 'shift', 'meta'}.contains)) {
+      return null;
+    }
+    return KeyChord(
+      key,
+      ctrl: modifiers.contains('ctrl'),
+      alt: modifiers.contains('alt'),
+      shift: modifiers.contains('shift'),
+      meta: modifiers.contains('meta'),
+    );
+  }
+
+  int get modifierCount => [ctrl, alt, shift, meta].where((held) => held).length;
+
+  /// Modifiers not part of the chord may be held too, activation passes them on.
+  bool matches(KeyEvent event, KeyMatch match, HardwareKeyboard keyboard) {
+    if ((ctrl && !keyboard.isControlPressed) ||
+        (alt && !keyboard.isAltPressed) ||
+        (shift && !keyboard.isShiftPressed) ||
+        (meta && !keyboard.isMetaPressed)) {
+      return false;
+    }
+    final named = _namedKeys[key];
+    if (named != null) {
+      return event.logicalKey == named;
+    }
+    final physical = _physicalKeys[key];
+    if (match == KeyMatch.physical && physical != null) {
+      return event.physicalKey == physical;
+    }
+    // the logical key label is the unshifted character, unlike event.character
+    return event.logicalKey.keyLabel.toLowerCase() == key;
+  }
+}
+
+/// Key chords of one command and how they are matched.
+class Binding {
+  final List<KeyChord> chords;
+  final KeyMatch match;
+
+  const Binding(this.chords, {this.match = KeyMatch.physical});
+
+  factory Binding.fromJson(Map<String, dynamic> json, Binding fallback) {
+    final keys = json['keys'] as List<dynamic>?;
+    final chords = keys?.map((key) => KeyChord.parse(key as String)).whereType<KeyChord>().toList();
+    final match = KeyMatch.values.where((match) => match.name == json['match']).firstOrNull;
+    return Binding(chords ?? fallback.chords, match: match ?? fallback.match);
+  }
+}
+
+/// Shortcuts of the launcher window, overridable under "keymap" in gui.json:
+///
+///     "keymap": {"action_menu": {"keys": ["alt+k", "alt+enter"], "match": "character"}}
+///
+/// Commands are named in snake case: `next`, `previous`, `close`, `clear`, `activate`,
+/// `activate_alt_1` to `activate_alt_9`, `action_menu`, `toggle_details`, `command_palette`,
+/// `plugin_logs`, `next_section` and `previous_section`.
+class Keymap {
+  static const defaults = <Command, Binding>{
+    Command.next: Binding([KeyChord('down')]),
+    Command.previous: Binding([KeyChord('up')]),
+    Command.close: Binding([KeyChord('escape')]),
+    Command.clear: Binding([KeyChord('u', ctrl: true)]),
+    Command.activate: Binding([KeyChord('enter')]),
+    Command.activateAlt1: Binding([KeyChord('1', alt: true)]),
+    Command.activateAlt2: Binding([KeyChord('2', alt: true)]),
+    Command.activateAlt3: Binding([KeyChord('3', alt: true)]),
+    Command.activateAlt4: Binding([KeyChord('4', alt: true)]),
+    Command.activateAlt5: Binding([KeyChord('5', alt: true)]),
+    Command.activateAlt6: Binding([KeyChord('6', alt: true)]),
+    Command.activateAlt7: Binding([KeyChord('7', alt: true)]),
+    Command.activateAlt8: Binding([KeyChord('8', alt: true)]),
+    Command.activateAlt9: Binding([KeyChord('9', alt: true)]),
+    Command.actionMenu: Binding([
+      KeyChord('k', alt: true),
+      KeyChord('enter', alt: true),
+      KeyChord('tab'),
+      KeyChord('right'),
+    ]),
+    Command.toggleDetails: Binding([KeyChord('d', alt: true)]),
+    Command.commandPalette: Binding([KeyChord('p', ctrl: true, shift: true)]),
+    Command.pluginLogs: Binding([KeyChord('l', ctrl: true, shift: true)]),
+    Command.nextSection: Binding([KeyChord('down', ctrl: true)]),
+    Command.previousSection: Binding([KeyChord('up', ctrl: true)]),
+  };
+
+  final Map<Command, Binding> bindings;
+
+  const Keymap([this.bindings = defaults]);
+
+  /// `actionMenu` is written `action_menu`, `activateAlt1` is `activate_alt_1`.
+  static String jsonName(Command command) =>
+      command.name.replaceAllMapped(RegExp(r'[A-Z]|\d+'), (m) => '_${m[0]!.toLowerCase()}');
+
+  factory Keymap.fromJson(Map<String, dynamic> json) {
+    return Keymap({
+      for (final MapEntry(key: command, value: fallback) in defaults.entries)
+        command: switch (json[jsonName(command)]) {
+          final Map<String, dynamic> binding => Binding.fromJson(binding, fallback),
+          _ => fallback,
+        },
+    });
+  }
+
+  /// The command bound to a key press. Chords with more modifiers win, so Alt+Enter opens
+  /// the action menu rather than activating with Alt held.
+  Command? lookup(KeyEvent event, HardwareKeyboard keyboard) {
+    Command? found;
+    var foundModifiers = -1;
+    for (final MapEntry(key: command, value: binding) in bindings.entries) {
+      for (final chord in binding.chords) {
+        if (chord.modifierCount > foundModifiers && chord.matches(event, binding.match, keyboard)) {
+          found = command;
+          foundModifiers = chord.modifierCount;
+        }
+      }
+    }
+    return found;
+  }
+}
@@ -4,10 +4,19 @@ import 'dart:io';
 
 import 'package:flutter/material.dart';
 import 'package:flutter/services.dart';
+import 'package:glimpse/config.dart';
 import 'package:glimpse/dbus_service.dart';
+import 'package:glimpse/focus_policy.dart';
+import 'package:glimpse/hints.dart';
+import 'package:glimpse/keymap.dart';
 import 'package:glimpse/protocol/request.dart';
 import 'package:glimpse/protocol/response.dart';
 import 'package:glimpse/protocol/match.dart';
+import 'package:glimpse/sections.dart';
+import 'package:glimpse/widgets/action_progress_row.dart';
+import 'package:glimpse/widgets/detail_pane.dart';
+import 'package:glimpse/widgets/error_toast.dart';
+import 'package:glimpse/widgets/highlighted_text.dart';
 import 'package:glimpse/widgets/tile_icon.dart';
 import 'package:window_manager/window_manager.dart';
 
@@ -55,18 +64,80 @@ class _AppState extends State<MainApp> {
   final _inputController = TextEditingController();
   final _inputStreamController = StreamController<Method>();
   final _searchItems = <Match>[];
+  // live rows pushed by subscribed plugins, keyed by "plugin_id/topic"
+  final _liveItems = <String, List<Match>>{};
+  // previously activated results shown while the input is empty
+  final _recentItems = <HistoryEntry>[];
+  // long-running actions by activate request id, shown until the daemon answers the request
+  final _actionProgress = <int, ActionProgress>{};
+  final _cancelledActions = <int>{};
+  // plugins the user was asked to trust this session, by path and digest
+  final _askedTrust = <String>{};
+  // activations by request id, retried once the user grants a permission they were refused for
+  final _sentActivations = <int, Activate>{};
+  // thumbnails the daemon rendered after sending their matches, by file
+  final _thumbnails = <String, String>{};
+  final _errorToasts = ErrorToastController(GuiConfig());
+  final _hints = HintAssigner();
+  Keymap _keymap = const Keymap();
+  bool _hintMode = false;
+  bool _altTapped = false;
 
   late StreamSubscription<Method> _stdinSubscription;
   late StreamSubscription<String> _stdoutSubscription;
   late StreamSubscription<String> _stderrSubscription;
   final _popupMenuKey = GlobalKey<PopupMenuButtonState<int>>();
   final _inputFocusNode = FocusNode();
-  int selectedIndex = -1;
-  Timer? _debounceTimer;
+  final _focusPolicy = FocusPolicy();
+  int _selectedIndex = -1;
+  int get selectedIndex => _selectedIndex;
+  set selectedIndex(int index) {
+    _selectedIndex = index;
+    highlightSelected();
+  }
+
+  // sections of grouped results the user folded away, kept across searches, and those
+  // showing past the per-category cap, reset by a new search
+  final _collapsedSections = <String>{};
+  final _expandedSections = <String>{};
+
+  int _generation = 0;
+  // matches asked for of the current search, the daemon sends a page at a time
+  static const _pageSize = 50;
+  int _pageEnd = _pageSize;
+  GuiConfig _config = GuiConfig();
+  // details of matches of the current search sent without one, by match id; null while asked
+  // for and for matches without a detail
+  final _details = <int, MatchDetail?>{};
+  // details requests by request id
+  final _sentDetails = <int, DetailsMethod>{};
+  // plugin keys of the matches of the current search by match id, for their logs
+  final _matchSources = <int, String>{};
+  // plugins whose logs were asked for by request id
+  final _sentLogs = <int, String>{};
+  // rows on screen, the first ones until the list is scrolled, and the last hint sent
+  int _firstVisible = 0;
+  int _lastVisible = 11;
+  String _viewport = '';
+  // the match the detail pane shows, following the selection and the pointer after a delay
+  int? _highlightedId;
+  int? _highlightTarget;
+  Timer? _highlightTimer;
+  static const _highlightDelay = Duration(milliseconds: 150);
+  // narrower windows show the results alone
+  static const _splitViewMinWidth = 560.0;
 
   @override
   void initState() {
     super.initState();
+    _inputController.addListener(
+      () => _focusPolicy.entryChanged(_inputController.value, focused: _inputFocusNode.hasFocus),
+    );
+    GuiConfig.load().then((config) {
+      setState(() => _config = config);
+      _errorToasts.config = config;
+      _keymap = config.keymap;
+    });
     _startDaemon();
   }
 
@@ -79,23 +150,44 @@ class _AppState extends State<MainApp> {
     );
 
     _stdinSubscription = _inputStreamController.stream.listen((method) async {
+      if (method.isNotification) {
+        _process.stdin.writeln(RPCNotification(method).toJsonString());
+        await _process.stdin.flush();
+        return;
+      }
       id += 1;
       final request = RPCRequest(id, method);
+      if (method is Activate) {
+        _sentActivations[id] = method;
+      }
+      if (method is DetailsMethod) {
+        _sentDetails[id] = method;
+      }
+      if (method is GetLogs) {
+        _sentLogs[id] = method.pluginId;
+      }
       _process.stdin.writeln(request.toJsonString());
       await _process.stdin.flush();
     });
 
     _stdoutSubscription = _process.stdout.transform(const Utf8Decoder()).transform(const LineSplitter()).listen((data) {
-      final message = RPCResponse.fromJson(jsonDecode(data));
-      switch (message.result) {
-        case List<Match> items:
-          addSearchItems(items);
-          break;
-        default:
-          break;
+      // a line holds a message or a JSON-RPC batch of them
+      final json = jsonDecode(data);
+      for (final message in json is List ? json : [json]) {
+        handleMessage(message as Map<String, dynamic>);
       }
     });
 
+    // GLIMPSE_SUBSCRIPTIONS="plugin.id/topic,..." selects live rows shown while the input is empty
+    final subscriptions = Platform.environment['GLIMPSE_SUBSCRIPTIONS'] ?? '';
+    for (final entry in subscriptions.split(',').where((e) => e.contains('/'))) {
+      final separator = entry.lastIndexOf('/');
+      _inputStreamController.add(Subscribe(entry.substring(0, separator), entry.substring(separator + 1)));
+    }
+
+    loadRecentItems();
+    _inputStreamController.add(LastResultsMethod());
+
     _stderrSubscription = _process.stderr.transform(const Utf8Decoder()).transform(const LineSplitter()).listen((data) {
       print(data);
     });
@@ -109,36 +201,100 @@ class _AppState extends State<MainApp> {
     _stderrSubscription.cancel();
     _inputStreamController.close();
     _inputFocusNode.dispose();
+    _errorToasts.dispose();
+    _highlightTimer?.cancel();
     _process.kill();
     super.dispose();
   }
 
-  void addSearchItems(List<Match> items) {
+  void addSearchItems(int generation, List<Match> items) {
     setState(() {
-      _searchItems.clear();
+      if (generation != _generation) {
+        _generation = generation;
+        _searchItems.clear();
+        // a new search shows from the top
+        _lastVisible -= _firstVisible;
+        _firstVisible = 0;
+        _pageEnd = _pageSize;
+        _details.clear();
+        _matchSources.clear();
+        _highlightTarget = null;
+        _highlightedId = null;
+        _expandedSections.clear();
+      }
       _searchItems.addAll(items);
+      _hints.assign(generation, items.map((item) => item.id));
     });
-    if (items.isNotEmpty) {
-      selectedIndex = 0;
-    } else {
-      selectedIndex = -1;
+    selectedIndex = resultLayout.visible.firstOrNull ?? -1;
+    sendViewport();
+  }
+
+  void applySnapshot(int generation, Snapshot snapshot) {
+    if (generation != _generation) {
+      return;
+    }
+
+    final byId = {for (final item in _searchItems) item.id: item};
+    setState(() {
+      _searchItems
+        ..clear()
+        ..addAll(snapshot.items.map((entry) => byId[entry.id]).whereType<Match>());
+      selectedIndex = resultLayout.visible.firstOrNull ?? -1;
+    });
+    sendViewport(ranked: true);
+  }
+
+  /// Ask for the next page of the search once the list reaches the end of a full page.
+  void requestMore() {
+    // a page that is not full has no successor, or is still arriving
+    if (_searchItems.length < _pageEnd) {
+      return;
     }
+    _pageEnd = _searchItems.length + _pageSize;
+    _inputStreamController.add(MoreMethod(_generation, _searchItems.length, _pageSize));
   }
 
+  /// Rows of the result list, grouped by category unless turned off in gui.json.
+  SectionLayout get resultLayout => _config.groupResults
+      ? SectionLayout.group(
+          _searchItems,
+          limit: _config.categoryLimit,
+          expanded: _expandedSections,
+          collapsed: _collapsedSections,
+        )
+      : SectionLayout.flat(_searchItems.length);
+
   KeyEventResult selectNextItem(int direction) {
+    setState(() => selectedIndex = resultLayout.move(selectedIndex, direction));
+
+    return KeyEventResult.handled;
+  }
+
+  KeyEventResult selectNextSection(int direction) {
+    setState(() => selectedIndex = resultLayout.jumpSection(selectedIndex, direction));
+
+    return KeyEventResult.handled;
+  }
+
+  /// Fold a section away or back open, the selection leaving a folded section.
+  void toggleSection(String category) {
     setState(() {
-      selectedIndex += direction;
-      if (selectedIndex < 0) {
-        selectedIndex = 0;
-      } else if (selectedIndex >= _searchItems.length) {
-        selectedIndex = _searchItems.length - 1;
+      if (!_collapsedSections.remove(category)) {
+        _collapsedSections.add(category);
+      }
+      final visible = resultLayout.visible;
+      if (!visible.contains(selectedIndex)) {
+        selectedIndex = visible.firstOrNull ?? -1;
       }
     });
+  }
 
-    return KeyEventResult.handled;
+  /// Show the matches of a section held back by the per-category cap.
+  void expandSection(String category) {
+    setState(() => _expandedSections.add(category));
   }
 
-  KeyEventResult activateAction(int itemIndex, {int actionIndex = 0}) {
+  KeyEventResult activateAction(int itemIndex, {int actionIndex = 0, Modifiers modifiers = const Modifiers()}) {
     print('Activating default action for selected index: $actionIndex');
     if (itemIndex < 0 || itemIndex >= _searchItems.length) {
       print('No item selected or index out of range');
@@ -158,13 +314,49 @@ class _AppState extends State<MainApp> {
 
     print('Activating action: $action for item: ${item.title}');
 
-    _inputStreamController.add(Activate(itemIndex, actionIndex));
-    if (action.closeOnAction) {
+    if (item.id == null) {
+      print('Item has no match id, cannot activate');
+      return KeyEventResult.handled;
+    }
+
+    _inputStreamController.add(Activate(_generation, item.id!, actionIndex, modifiers: modifiers));
+    // the daemon asks for confirmation first, the window stays to show the question
+    if (_focusPolicy.activated(closesWindow: action.closeOnAction && !action.requiresConfirmation)) {
+      // tapping a row or picking from the menu moved focus away from the entry
+      restoreEntryFocus();
+    } else {
       windowManager.hide();
     }
     return KeyEventResult.handled;
   }
 
+  /// Give the entry its focus and cursor back once the frame that moved them is done.
+  void restoreEntryFocus() {
+    WidgetsBinding.instance.addPostFrameCallback((_) {
+      if (!mounted) {
+        return;
+      }
+      _inputFocusNode.requestFocus();
+      _inputController.selection = _focusPolicy.selectionFor(_inputController.text);
+    });
+  }
+
+  /// Activates the default action with the held modifiers. Shift+Enter falls back to the
+  /// second action when the default one declares no Shift alternate.
+  KeyEventResult activateWithModifiers(int itemIndex) {
+    final keyboard = HardwareKeyboard.instance;
+    final modifiers = Modifiers(shift: keyboard.isShiftPressed, ctrl: keyboard.isControlPressed);
+    if (itemIndex >= 0 && itemIndex < _searchItems.length && modifiers.shift && !modifiers.ctrl) {
+      final actions = _searchItems[itemIndex].actions;
+      final hasAlternate =
+          actions.isNotEmpty && actions.first.alternates.any((a) => a.modifiers.shift && !a.modifiers.ctrl && !a.modifiers.alt);
+      if (!hasAlternate) {
+        return activateAction(itemIndex, actionIndex: 1);
+      }
+    }
+    return activateAction(itemIndex, modifiers: modifiers);
+  }
+
   KeyEventResult showActionMenu(int itemIndex) {
     if (itemIndex < 0 || itemIndex >= _searchItems.length) {
       print('Invalid item index');
@@ -191,23 +383,499 @@ class _AppState extends State<MainApp> {
         _searchItems.clear();
         selectedIndex = -1;
       });
+      loadRecentItems();
     }
   }
 
-  KeyEventResult handleEsc() {
+  void handleMessage(Map<String, dynamic> json) {
+    if (!json.containsKey('id')) {
+      handleNotification(json);
+      return;
+    }
+    final message = RPCResponse.fromJson(json);
+    if (_actionProgress.containsKey(message.id)) {
+      setState(() => _actionProgress.remove(message.id));
+    }
+    final detailsRequest = _sentDetails.remove(message.id);
+    final logsRequest = _sentLogs.remove(message.id);
+    final activation = _sentActivations.remove(message.id);
+    if (activation != null && message.error?.code == RpcError.permissionRequired) {
+      askPermission(message.error!, activation);
+      return;
+    }
+    if (activation != null && message.error?.code == RpcError.confirmationRequired) {
+      askConfirmation(message.error!, activation);
+      return;
+    }
+    // cancelling an action fails its request, that is no error worth showing
+    if (message.error != null && !_cancelledActions.remove(message.id)) {
+      _errorToasts.report(message.source ?? 'glimpsed', message.error!.message);
+    }
+    switch (message.result) {
+      case List<Match> items:
+        addSearchItems(message.id, items);
+        if (message.source != null) {
+          _matchSources.addAll({for (final item in items.where((item) => item.id != null)) item.id!: message.source!});
+        }
+        break;
+      case Snapshot snapshot:
+        applySnapshot(message.id, snapshot);
+        break;
+      case PluginFailure failure:
+        _errorToasts.report(message.source ?? 'glimpsed', failure.message);
+        break;
+      case SequenceResult sequence when sequence.failure != null:
+        final step = sequence.statuses.indexOf('failed') + 1;
+        _errorToasts.report('glimpsed', 'step $step of ${sequence.statuses.length} failed: ${sequence.failure}');
+        break;
+      case Update update:
+        setState(() => _liveItems['${message.source}/${update.topic}'] = update.items);
+        break;
+      case LastResults last:
+        restoreLastResults(message.id, last);
+        break;
+      case Details details:
+        // answers for an earlier search name matches of its own
+        if (detailsRequest != null && detailsRequest.generation == _generation) {
+          setState(() => _details[detailsRequest.matchId] = details.detail);
+        }
+        break;
+      case Logs logs when logsRequest != null:
+        showLogs(logsRequest, logs);
+        break;
+      case History history:
+        setState(() => _recentItems
+          ..clear()
+          ..addAll(history.items));
+        break;
+      default:
+        break;
+    }
+  }
+
+  /// Ask the user whether a plugin may do what it was refused, retrying the activation if so.
+  Future<void> askPermission(RpcError error, Activate activation) async {
+    final pluginId = error.data?['plugin_id'] as String?;
+    final permission = error.data?['permission'] as String?;
+    if (pluginId == null || permission == null) {
+      _errorToasts.report('glimpsed', error.message);
+      return;
+    }
+
+    // the activation may have hidden the window already
+    await windowManager.show();
+    if (!mounted) {
+      return;
+    }
+    final allowed = await showDialog<bool>(
+      context: context,
+      builder: (context) => AlertDialog(
+        title: const Text('Allow plugin?'),
+        content: Text(error.message),
+        actions: [
+          TextButton(onPressed: () => Navigator.of(context).pop(false), child: const Text('Deny')),
+          TextButton(onPressed: () => Navigator.of(context).pop(true), child: const Text('Allow')),
+        ],
+      ),
+    );
+    if (allowed == true) {
+      _inputStreamController.add(GrantPermission(pluginId, permission));
+      _inputStreamController.add(activation);
+    }
+    restoreEntryFocus();
+  }
+
+  /// Ask the user about each plugin the daemon holds back, once per executable and digest.
+  Future<void> askTrust(List<Map<String, dynamic>> plugins) async {
+    for (final plugin in plugins) {
+      final path = plugin['path'] as String;
+      final sha256 = plugin['sha256'] as String;
+      if (!_askedTrust.add('$path:$sha256')) {
+        continue;
+      }
+      await windowManager.show();
+      if (!mounted) {
+        return;
+      }
+      final changed = plugin['changed'] == true;
+      final trusted = await showDialog<bool>(
+        context: context,
+        builder: (context) => AlertDialog(
+          title: Text(changed ? 'Plugin changed' : 'New plugin'),
+          content: SelectableText(
+            '${changed ? 'This plugin changed since you trusted it.' : 'Glimpse found a plugin it has not run before.'} '
+            'It runs with your permissions, only trust plugins you installed.\n\n$path\nSHA-256 $sha256',
+          ),
+          actions: [
+            TextButton(onPressed: () => Navigator.of(context).pop(false), child: const Text('Not now')),
+            TextButton(onPressed: () => Navigator.of(context).pop(true), child: const Text('Trust')),
+          ],
+        ),
+      );
+      if (trusted == true) {
+        _inputStreamController.add(TrustPlugin(path, sha256));
+      }
+    }
+    restoreEntryFocus();
+  }
+
+  /// Ask the user to confirm a destructive action, activating it again confirmed if so.
+  Future<void> askConfirmation(RpcError error, Activate activation) async {
+    final prompt = error.data?['prompt'] as String? ?? error.message;
+    await windowManager.show();
+    if (!mounted) {
+      return;
+    }
+    final confirmed = await showDialog<bool>(
+      context: context,
+      builder: (context) => AlertDialog(
+        title: const Text('Are you sure?'),
+        content: Text(prompt),
+        actions: [
+          TextButton(onPressed: () => Navigator.of(context).pop(false), child: const Text('Cancel')),
+          TextButton(onPressed: () => Navigator.of(context).pop(true), child: const Text('Confirm')),
+        ],
+      ),
+    );
+    if (confirmed == true) {
+      _inputStreamController.add(activation.confirm());
+    }
+    restoreEntryFocus();
+  }
+
+  void handleNotification(Map<String, dynamic> json) {
+    switch (json['method']) {
+      // plugins were added or removed, the results on screen may be missing some or be orphaned
+      case 'plugins_changed':
+        onSearchInputChanged(_inputController.text);
+        break;
+      case 'action_progress':
+        final progress = ActionProgress.fromJson(json['params'] as Map<String, dynamic>);
+        setState(() => _actionProgress[progress.actionId] = progress);
+        break;
+      case 'thumbnail_ready':
+        final params = json['params'] as Map<String, dynamic>;
+        if (params['icon'] case {'type': 'path', 'path': String icon}) {
+          setState(() => _thumbnails[params['path'] as String] = icon);
+        }
+        break;
+      // a match on screen was described ahead of being highlighted
+      case 'detail_ready':
+        final params = json['params'] as Map<String, dynamic>;
+        if (params['generation'] == _generation) {
+          final detail = MatchDetail.fromJson(params['detail'] as Map<String, dynamic>);
+          setState(() => _details[params['match_id'] as int] = detail);
+        }
+        break;
+      case 'plugins_held_back':
+        final items = (json['params']['items'] as List<dynamic>).cast<Map<String, dynamic>>();
+        askTrust(items);
+        break;
+    }
+  }
+
+  /// Show the search the user last saw, unless they started typing in the meantime.
+  void restoreLastResults(int generation, LastResults last) {
     if (_inputController.text.isNotEmpty) {
+      return;
+    }
+    _focusPolicy.textReplaced(last.query);
+    _inputController.text = last.query;
+    addSearchItems(generation, last.items);
+  }
+
+  void cancelAction(int actionId) {
+    _cancelledActions.add(actionId);
+    _inputStreamController.add(CancelAction(actionId));
+  }
+
+  void loadRecentItems() {
+    _inputStreamController.add(HistoryMethod(10));
+  }
+
+  /// Recent items only carry a title, searching for it brings the match back to the top.
+  void searchRecentItem(HistoryEntry entry) {
+    _focusPolicy.textReplaced(entry.title);
+    setState(() => _inputController.text = entry.title);
+    onSearchInputChanged(entry.title);
+    restoreEntryFocus();
+  }
+
+  /// Hint mode is toggled by tapping Alt on its own. Letters activate the row with that hint,
+  /// digits activate the numbered action of the selected row.
+  KeyEventResult? handleHintKey(KeyEvent event) {
+    final isAlt = event.logicalKey == LogicalKeyboardKey.altLeft || event.logicalKey == LogicalKeyboardKey.altRight;
+    if (event is KeyDownEvent) {
+      _altTapped = isAlt;
+    } else if (event is KeyUpEvent && isAlt && _altTapped) {
+      _altTapped = false;
+      setState(() => _hintMode = !_hintMode && _searchItems.isNotEmpty);
+      return KeyEventResult.handled;
+    }
+
+    if (!_hintMode || event is! KeyDownEvent) {
+      return null;
+    }
+
+    if (event.logicalKey == LogicalKeyboardKey.arrowDown || event.logicalKey == LogicalKeyboardKey.arrowUp) {
+      return null;
+    }
+
+    // hints are Latin letters, on other layouts the key in their place types them
+    final typed = event.character?.toLowerCase() ?? '';
+    final key = RegExp(r'^[a-z0-9]$').hasMatch(typed) ? typed : positionalCharacter(event) ?? typed;
+    if (event.logicalKey == LogicalKeyboardKey.escape) {
+      setState(() => _hintMode = false);
+      return KeyEventResult.handled;
+    }
+
+    final actionNumber = int.tryParse(key);
+    if (actionNumber != null && actionNumber > 0) {
+      setState(() => _hintMode = false);
+      return activateAction(selectedIndex, actionIndex: actionNumber - 1);
+    }
+
+    final matchId = _hints.matchIdFor(key);
+    final index = _searchItems.indexWhere((item) => matchId != null && item.id == matchId);
+    if (index >= 0) {
       setState(() {
-        _inputController.clear();
-        _searchItems.clear();
-        selectedIndex = -1;
+        _hintMode = false;
+        selectedIndex = index;
       });
-      FocusScope.of(context).requestFocus(_inputFocusNode);
-    } else {
-      windowManager.hide();
+      return activateAction(index);
+    }
+
+    // swallow everything else so stray keys do not edit the query
+    return KeyEventResult.handled;
+  }
+
+  /// Show the detail of `item` once it stayed highlighted for a moment, asking the daemon for
+  /// details the match was sent without. Scrolling through the results asks for none.
+  void highlight(Match item) {
+    final matchId = item.id;
+    if (matchId == null || matchId == _highlightTarget) {
+      return;
+    }
+    _highlightTarget = matchId;
+    _highlightTimer?.cancel();
+    _highlightTimer = Timer(_highlightDelay, () {
+      if (!mounted || _highlightTarget != matchId) {
+        return;
+      }
+      setState(() => _highlightedId = matchId);
+      if (_config.splitView && item.detail == null && !_details.containsKey(matchId)) {
+        // asked once per search, the answer replaces the placeholder
+        _details[matchId] = null;
+        _inputStreamController.add(DetailsMethod(_generation, matchId));
+      }
+    });
+  }
+
+  /// Tell the daemon which rows are on screen, so their details and thumbnails are ready by
+  /// the time they are highlighted. `ranked` sends the hint again after the ranking changed
+  /// which matches the rows hold.
+  void sendViewport({bool ranked = false}) {
+    if (_searchItems.isEmpty) {
+      return;
+    }
+    final last = _lastVisible.clamp(_firstVisible, _searchItems.length - 1);
+    final viewport = '$_generation:$_firstVisible:$last';
+    if (viewport == _viewport && !ranked) {
+      return;
+    }
+    _viewport = viewport;
+    _inputStreamController.add(Viewport(_generation, _firstVisible, last));
+  }
+
+  /// Work out the matches on screen from the scroll position, rows being about the same
+  /// height. Grouped, they are the lowest to highest ranked of the rows on screen.
+  bool onResultsScrolled(ScrollNotification notification) {
+    final metrics = notification.metrics;
+    final layout = resultLayout;
+    if (layout.rows.isEmpty || metrics.axis != Axis.vertical) {
+      return false;
+    }
+    final rowExtent = (metrics.maxScrollExtent + metrics.viewportDimension) / layout.rows.length;
+    if (rowExtent <= 0) {
+      return false;
+    }
+    final firstRow = (metrics.pixels / rowExtent).floor();
+    final lastRow = ((metrics.pixels + metrics.viewportDimension) / rowExtent).ceil() - 1;
+    final items = layout.itemsIn(firstRow, lastRow);
+    if (items == null) {
+      return false;
+    }
+    (_firstVisible, _lastVisible) = items;
+    sendViewport();
+    return false;
+  }
+
+  /// The pointer left the results, the pane goes back to the selected match.
+  void highlightSelected() {
+    if (selectedIndex >= 0 && selectedIndex < _searchItems.length) {
+      highlight(_searchItems[selectedIndex]);
     }
+  }
+
+  /// Detail of the highlighted match, shown beside the results.
+  MatchDetail? get highlightedDetail {
+    final item = _searchItems.where((item) => item.id == _highlightedId).firstOrNull;
+    return item?.detail ?? _details[_highlightedId];
+  }
+
+  /// The pane is collapsed on windows too narrow for two columns, the preference stays.
+  bool showsDetailPane(double width) => _config.splitView && width >= _splitViewMinWidth && _searchItems.isNotEmpty;
+
+  KeyEventResult toggleSplitView() {
+    setState(() => _config.splitView = !_config.splitView);
+    _config.save();
+    // details are not asked for while the pane is hidden
+    _highlightTarget = null;
+    highlightSelected();
     return KeyEventResult.handled;
   }
 
+  Widget buildHintBadge(String hint) {
+    return Container(
+      padding: const EdgeInsets.symmetric(horizontal: 6, vertical: 2),
+      decoration: BoxDecoration(color: Colors.amber[300], borderRadius: BorderRadius.circular(4)),
+      child: Text(hint, style: const TextStyle(fontWeight: FontWeight.bold, fontFamily: 'monospace')),
+    );
+  }
+
+  /// Heading of a section, tapped to fold it, or the row showing what the cap held back.
+  Widget buildSectionRow(ResultRow row) {
+    return switch (row) {
+      SectionHeader(:final category, :final count, :final collapsed) => ListTile(
+        dense: true,
+        title: Text(category, style: const TextStyle(fontWeight: FontWeight.bold)),
+        trailing: Row(
+          mainAxisSize: MainAxisSize.min,
+          children: [Text('$count'), Icon(collapsed ? Icons.expand_more : Icons.expand_less)],
+        ),
+        onTap: () => toggleSection(category),
+      ),
+      MoreResults(:final category, :final hidden) => ListTile(
+        dense: true,
+        title: Text('Show $hidden more', style: TextStyle(color: Colors.grey[700])),
+        onTap: () => expandSection(category),
+      ),
+      ResultItem() => const SizedBox.shrink(),
+    };
+  }
+
+  KeyEventResult openCommandPalette() {
+    final query = '${_config.commandPrefix} ';
+    _inputController.value = TextEditingValue(
+      text: query,
+      selection: TextSelection.collapsed(offset: query.length),
+    );
+    onSearchInputChanged(query);
+    return KeyEventResult.handled;
+  }
+
+  /// Ask for the logs of the plugin of the selected match, the daemon's without one.
+  KeyEventResult openPluginLogs() {
+    final item = selectedIndex >= 0 && selectedIndex < _searchItems.length ? _searchItems[selectedIndex] : null;
+    _inputStreamController.add(GetLogs(_matchSources[item?.id] ?? 'glimpsed'));
+    return KeyEventResult.handled;
+  }
+
+  void showLogs(String pluginId, Logs logs) {
+    final name = pluginId.split('/').last;
+    showDialog<void>(
+      context: context,
+      builder: (context) => AlertDialog(
+        title: Text('Logs of $name'),
+        content: SizedBox(
+          width: 640,
+          height: 400,
+          child: logs.items.isEmpty
+              ? const Center(child: Text('Nothing logged yet'))
+              : ListView(
+                  reverse: true,
+                  children: [
+                    for (final record in logs.items.reversed)
+                      SelectableText(
+                        '${DateTime.fromMillisecondsSinceEpoch(record.ts).toIso8601String()} '
+                        '${record.level.toUpperCase()} ${record.message}',
+                        style: TextStyle(
+                          fontFamily: 'monospace',
+                          fontSize: 12,
+                          color: switch (record.level) {
+                            'error' => Colors.red[800],
+                            'warn' => Colors.orange[900],
+                            _ => null,
+                          },
+                        ),
+                      ),
+                  ],
+                ),
+        ),
+        actions: [TextButton(onPressed: () => Navigator.of(context).pop(), child: const Text('Close'))],
+      ),
+    );
+  }
+
+  KeyEventResult handleEsc() {
+    if (_inputController.text.isNotEmpty) {
+      return clearQuery();
+    }
+    windowManager.hide();
+    return KeyEventResult.handled;
+  }
+
+  /// Empty the query and go back to the recent items, keeping the window open.
+  KeyEventResult clearQuery() {
+    setState(() {
+      _inputController.clear();
+      _searchItems.clear();
+      selectedIndex = -1;
+    });
+    loadRecentItems();
+    FocusScope.of(context).requestFocus(_inputFocusNode);
+    return KeyEventResult.handled;
+  }
+
+  /// The command bound to a key press. Right arrow moves the caret through the query and
+  /// opens the action menu only from its end.
+  Command? commandFor(KeyEvent event) {
+    final command = _keymap.lookup(event, HardwareKeyboard.instance);
+    final selection = _inputController.selection;
+    final caretInside = !selection.isCollapsed || selection.baseOffset < _inputController.text.length;
+    if (event.logicalKey == LogicalKeyboardKey.arrowRight && caretInside && selection.isValid) {
+      return null;
+    }
+    return command;
+  }
+
+  /// Run the command bound to a key, keys bound to none are left to the focused widget.
+  KeyEventResult runCommand(Command? command) {
+    return switch (command) {
+      Command.next => selectNextItem(1),
+      Command.previous => selectNextItem(-1),
+      Command.close => handleEsc(),
+      Command.clear => clearQuery(),
+      Command.actionMenu => showActionMenu(selectedIndex),
+      Command.activate => activateWithModifiers(selectedIndex),
+      Command.activateAlt1 ||
+      Command.activateAlt2 ||
+      Command.activateAlt3 ||
+      Command.activateAlt4 ||
+      Command.activateAlt5 ||
+      Command.activateAlt6 ||
+      Command.activateAlt7 ||
+      Command.activateAlt8 ||
+      Command.activateAlt9 => activateAction(selectedIndex, actionIndex: command!.alternateAction!),
+      Command.toggleDetails => toggleSplitView(),
+      Command.commandPalette => openCommandPalette(),
+      Command.pluginLogs => openPluginLogs(),
+      Command.nextSection => selectNextSection(1),
+      Command.previousSection => selectNextSection(-1),
+      null => KeyEventResult.ignored,
+    };
+  }
+
   @override
   Widget build(BuildContext context) {
     return MaterialApp(
@@ -225,86 +893,158 @@ class _AppState extends State<MainApp> {
         ),
       ),
       home: Focus(
-        onKeyEvent: (node, event) => switch (event is KeyDownEvent ? event.logicalKey : null) {
-          LogicalKeyboardKey.arrowDown => selectNextItem(1),
-          LogicalKeyboardKey.arrowUp => selectNextItem(-1),
-          LogicalKeyboardKey.escape => handleEsc(),
-          LogicalKeyboardKey.keyK => switch (HardwareKeyboard.instance.isAltPressed) {
-            true => showActionMenu(selectedIndex),
-            false => KeyEventResult.ignored,
-          },
-          LogicalKeyboardKey.enter =>
-            HardwareKeyboard.instance.isShiftPressed
-                ? activateAction(selectedIndex, actionIndex: 1)
-                : HardwareKeyboard.instance.isAltPressed
-                ? showActionMenu(selectedIndex)
-                : activateAction(selectedIndex),
-          _ => KeyEventResult.ignored,
-        },
+        onKeyEvent: (node, event) =>
+            handleHintKey(event) ??
+            runCommand(event is KeyDownEvent ? commandFor(event) : null),
         child: Scaffold(
-          body: Column(
+          body: Stack(
             children: [
-              Row(
+              Column(
                 children: [
+                  Row(
+                    children: [
+                      Expanded(
+                        child: TextField(
+                          controller: _inputController,
+                          decoration: const InputDecoration(hintText: 'Start typing to search...'),
+                          autofocus: true,
+                          readOnly: _hintMode,
+                          canRequestFocus: true,
+                          focusNode: _inputFocusNode,
+                          // every keystroke is sent, the daemon waits for typing to pause
+                          onChanged: onSearchInputChanged,
+                          onSubmitted: onSearchInputChanged,
+                        ),
+                      ),
+                    ],
+                  ),
+                  for (final progress in _actionProgress.values)
+                    ActionProgressRow(progress: progress, onCancel: () => cancelAction(progress.actionId)),
+                  if (_inputController.text.isEmpty && _liveItems.isNotEmpty)
+                    Expanded(
+                      child: ListView(
+                        children: _liveItems.values
+                            .expand((items) => items)
+                            .map(
+                              (item) => ListTile(
+                                title: Text(item.title),
+                                subtitle: Text(item.description),
+                                leading: matchIcon(item, thumbnails: _thumbnails),
+                              ),
+                            )
+                            .toList(),
+                      ),
+                    ),
+                  if (_inputController.text.isEmpty && _recentItems.isNotEmpty)
+                    Expanded(
+                      child: ListView(
+                        children: _recentItems
+                            .map(
+                              (entry) => ListTile(
+                                title: Text(entry.title),
+                                subtitle: Text('Used ${entry.count} times'),
+                                leading: const Icon(Icons.history),
+                                onTap: () => searchRecentItem(entry),
+                              ),
+                            )
+                            .toList(),
+                      ),
+                    ),
                   Expanded(
-                    child: TextField(
-                      controller: _inputController,
-                      decoration: const InputDecoration(hintText: 'Start typing to search...'),
-                      autofocus: true,
-                      canRequestFocus: true,
-                      focusNode: _inputFocusNode,
-                      onChanged: (value) {
-                        _debounceTimer?.cancel();
-                        _debounceTimer = Timer(const Duration(milliseconds: 50), () {
-                          if (_inputController.text == value) {
-                            onSearchInputChanged(value);
-                          }
-                        });
-                      },
-                      onSubmitted: onSearchInputChanged,
+                    child: LayoutBuilder(
+                      builder: (context, constraints) => Row(
+                        crossAxisAlignment: CrossAxisAlignment.start,
+                        children: [
+                          Expanded(
+                            child: NotificationListener<ScrollNotification>(
+                              onNotification: onResultsScrolled,
+                              child: Builder(builder: (context) {
+                                final layout = resultLayout;
+                                return ListView.builder(
+                                itemCount: layout.rows.length,
+                                itemBuilder: (context, rowIndex) {
+                                  if (rowIndex == layout.rows.length - 1) {
+                                    requestMore();
+                                  }
+                                  final index = switch (layout.rows[rowIndex]) {
+                                    SectionHeader() || MoreResults() => null,
+                                    ResultItem(:final index) => index,
+                                  };
+                                  if (index == null) {
+                                    return buildSectionRow(layout.rows[rowIndex]);
+                                  }
+                                  final item = _searchItems[index];
+                                  final isSelected = index == selectedIndex;
+                if (isSelected) {
+                  WidgetsBinding.instance.addPostFrameCallback((_) {
+                    final renderObject = context.findRenderObject();
+                    if (renderObject != null && renderObject.attached) {
+                      Scrollable.ensureVisible(context, duration: const Duration(milliseconds: 100), alignment: 0.5);
+                    }
+                  });
+                }
+                                  return PopupMenuButton<int>(
+                                    key: selectedIndex == index ? _popupMenuKey : null,
+                                    enabled: selectedIndex == index && item.actions.isNotEmpty,
+                                    onSelected: (value) => activateAction(selectedIndex, actionIndex: value),
+                                    onOpened: _focusPolicy.menuOpened,
+                                    onCanceled: () {
+                                      if (_focusPolicy.menuClosed()) {
+                                        restoreEntryFocus();
+                                      }
+                                    },
+                                    itemBuilder: (BuildContext context) => item.actions.asMap().entries.map((entry) {
+                                      final actionIndex = entry.key;
+                                      final action = entry.value;
+                                      final hints = action.alternates.map((a) => '${a.modifiers.label}: ${a.title}').join(', ');
+                                      return PopupMenuItem<int>(
+                                        value: actionIndex,
+                                        child: Text(hints.isEmpty ? action.title : '${action.title}  ($hints)'),
+                                      );
+                                    }).toList(),
+                                    child: MouseRegion(
+                                      onEnter: (_) => highlight(item),
+                                      onExit: (_) => highlightSelected(),
+                                      child: ListTile(
+                                        title: HighlightedText(item.title, item.titleHighlights),
+                                        subtitle: _hintMode && isSelected
+                                            ? Text(item.actions.asMap().entries.map((e) => '${e.key + 1} ${e.value.title}').join('  ·  '))
+                                            : HighlightedText(item.description, item.descriptionHighlights),
+                                        trailing: _hintMode && _hints.hintFor(item.id) != null
+                                            ? buildHintBadge(_hints.hintFor(item.id)!)
+                                            : null,
+                                        selected: isSelected,
+                                        focusColor: isSelected ? Colors.blue : null,
+                                        hoverColor: Colors.grey[300],
+                                        tileColor: isSelected ? Colors.blue[500] : null,
+                                        onTap: () => activateAction(index),
+                                        selectedColor: Colors.black,
+                                        selectedTileColor: Colors.grey[300],
+                                        leading: matchIcon(item, thumbnails: _thumbnails),
+                                      ),
+                                    ),
+                                  );
+                                },
+                              );
+                              }),
+                            ),
+                          ),
+                          if (showsDetailPane(constraints.maxWidth)) ...[
+                            const VerticalDivider(width: 1),
+                            SizedBox(
+                              width: constraints.maxWidth * 0.45,
+                              child: highlightedDetail != null
+                                  ? DetailPane(detail: highlightedDetail!)
+                                  : const Center(child: Text('No details')),
+                            ),
+                          ],
+                        ],
+                      ),
                     ),
                   ),
                 ],
               ),
-              Expanded(
-                child: ListView.builder(
-                  itemCount: _searchItems.length,
-                  itemBuilder: (context, index) {
-                    final item = _searchItems[index];
-                    final isSelected = index == selectedIndex;
-  if (isSelected) {
-    WidgetsBinding.instance.addPostFrameCallback((_) {
-      final renderObject = context.findRenderObject();
-      if (renderObject != null && renderObject.attached) {
-        Scrollable.ensureVisible(context, duration: const Duration(milliseconds: 100), alignment: 0.5);
-      }
-    });
-  }
-                    return PopupMenuButton<int>(
-                      key: selectedIndex == index ? _popupMenuKey : null,
-                      enabled: selectedIndex == index && item.actions.isNotEmpty,
-                      onSelected: (value) => activateAction(selectedIndex, actionIndex: value),
-                      itemBuilder: (BuildContext context) => item.actions.asMap().entries.map((entry) {
-                        final actionIndex = entry.key;
-                        final action = entry.value;
-                        return PopupMenuItem<int>(value: actionIndex, child: Text(action.title));
-                      }).toList(),
-                      child: ListTile(
-                        title: Text(item.title),
-                        subtitle: Text(item.description),
-                        selected: isSelected,
-                        focusColor: isSelected ? Colors.blue : null,
-                        hoverColor: Colors.grey[300],
-                        tileColor: isSelected ? Colors.blue[500] : null,
-                        onTap: () => activateAction(index),
-                        selectedColor: Colors.black,
-                        selectedTileColor: Colors.grey[300],
-                        leading: item.icon != null ? TileIcon(path: item.icon!) : null,
-                      ),
-                    );
-                  },
-                ),
-              ),
+              ErrorToastStack(controller: _errorToasts),
             ],
           ),
         ),
@@ -41,6 +41,16 @@ class ClipboardHandler extends ActionHandler {
   }
 }
 
+/// Text the daemon types into the window focused once the launcher closed.
+class TypeTextAction extends ActionHandler {
+  final String text;
+  TypeTextAction(this.text);
+
+  factory TypeTextAction.fromJson(Map<String, dynamic> json) {
+    return TypeTextAction(json['text'] as String);
+  }
+}
+
 class CallbackAction extends ActionHandler {
   final String name;
   final Map<String, dynamic> parameters;
@@ -51,41 +61,226 @@ class CallbackAction extends ActionHandler {
   }
 }
 
+/// A desktop notification the daemon shows after [after], even across its restarts.
+class ScheduleAction extends ActionHandler {
+  final Duration after;
+  final String title;
+  final String? body;
+  final bool openLauncher;
+  ScheduleAction(this.after, this.title, {this.body, this.openLauncher = false});
+
+  factory ScheduleAction.fromJson(Map<String, dynamic> json) {
+    return ScheduleAction(
+      Duration(seconds: json['after_secs'] as int),
+      json['title'] as String,
+      body: json['body'] as String?,
+      openLauncher: json['open_launcher'] as bool? ?? false,
+    );
+  }
+}
+
+/// A power or session command the daemon runs through systemd-logind, like `power_off`
+/// or `lock_screen`.
+class SystemAction extends ActionHandler {
+  final String command;
+  SystemAction(this.command);
+
+  factory SystemAction.fromJson(Map<String, dynamic> json) {
+    return SystemAction(json['command'] as String);
+  }
+}
+
+/// A Bluetooth device or a network the daemon connects or disconnects. [target] holds its
+/// `kind`, `bluetooth` with an `address` or `network` with a `uuid`.
+class ConnectionAction extends ActionHandler {
+  final Map<String, dynamic> target;
+  final bool connect;
+  ConnectionAction(this.target, this.connect);
+
+  factory ConnectionAction.fromJson(Map<String, dynamic> json) {
+    return ConnectionAction(json['target'] as Map<String, dynamic>, json['connect'] as bool);
+  }
+}
+
+/// Actions run in order, stopping at the first that fails.
+class SequenceAction extends ActionHandler {
+  final List<ActionHandler> actions;
+  SequenceAction(this.actions);
+
+  factory SequenceAction.fromJson(Map<String, dynamic> json) {
+    return SequenceAction(
+      (json['actions'] as List<dynamic>).map((e) => parseActionHandler(e as Map<String, dynamic>)).toList(),
+    );
+  }
+}
+
+ActionHandler parseActionHandler(Map<String, dynamic> json) {
+  return switch (json['type']) {
+    'exec' => ShellExecHandler.fromJson(json),
+    'open' => OpenURIHandler.fromJson(json),
+    'clipboard' => ClipboardHandler.fromJson(json),
+    'expiring_clipboard' => ClipboardHandler.fromJson(json),
+    'type_text' => TypeTextAction.fromJson(json),
+    'callback' => CallbackAction.fromJson(json),
+    'launch' => LaunchHandler.fromJson(json),
+    'schedule' => ScheduleAction.fromJson(json),
+    'system' => SystemAction.fromJson(json),
+    'connection' => ConnectionAction.fromJson(json),
+    'sequence' => SequenceAction.fromJson(json),
+    _ => throw Exception('Unknown action type: ${json['type']}'),
+  };
+}
+
+final class Modifiers {
+  final bool shift;
+  final bool ctrl;
+  final bool alt;
+
+  const Modifiers({this.shift = false, this.ctrl = false, this.alt = false});
+
+  factory Modifiers.fromJson(Map<String, dynamic> json) {
+    return Modifiers(shift: json['shift'] ?? false, ctrl: json['ctrl'] ?? false, alt: json['alt'] ?? false);
+  }
+
+  Map<String, dynamic> toJson() => {'shift': shift, 'ctrl': ctrl, 'alt': alt};
+
+  /// Human readable chord, e.g. "Ctrl+Shift+Enter".
+  String get label => [if (ctrl) 'Ctrl', if (alt) 'Alt', if (shift) 'Shift', 'Enter'].join('+');
+}
+
+final class AlternateAction {
+  final Modifiers modifiers;
+  final String title;
+  final ActionHandler action;
+
+  AlternateAction(this.modifiers, this.title, this.action);
+
+  factory AlternateAction.fromJson(Map<String, dynamic> json) {
+    return AlternateAction(
+      Modifiers.fromJson(json['modifiers'] as Map<String, dynamic>),
+      json['title'] as String,
+      parseActionHandler(json['action'] as Map<String, dynamic>),
+    );
+  }
+}
+
 final class MatchAction {
   final String title;
   final ActionHandler action;
   final bool closeOnAction;
+  final List<AlternateAction> alternates;
+  /// Destructive actions the daemon only runs once the user confirmed them.
+  final bool requiresConfirmation;
+  final String? confirmationPrompt;
 
-  MatchAction(this.title, this.action, {this.closeOnAction = true});
+  MatchAction(
+    this.title,
+    this.action, {
+    this.closeOnAction = true,
+    this.alternates = const [],
+    this.requiresConfirmation = false,
+    this.confirmationPrompt,
+  });
+}
+
+enum DetailFormat { text, markdown }
+
+/// Longer content shown beside the results while the match is selected.
+final class MatchDetail {
+  final DetailFormat format;
+  final String content;
+  final String? image;
+
+  MatchDetail(this.content, {this.format = DetailFormat.text, this.image});
+
+  factory MatchDetail.fromJson(Map<String, dynamic> json) {
+    return MatchDetail(
+      json['content'] as String,
+      format: json['format'] == 'markdown' ? DetailFormat.markdown : DetailFormat.text,
+      image: json['image'] as String?,
+    );
+  }
 }
 
 final class Match {
+  final int? id;
   final String title;
   final String description;
+  /// Path of an image to load, the daemon resolves theme icons and rasterizes SVGs. Small
+  /// icons bundled with plugins come inline as a `data:` URI.
   final String? icon;
+  final String? emoji;
+  /// File whose thumbnail the daemon is rendering, announced with `thumbnail_ready`.
+  final String? thumbnailOf;
   final double? score;
   final List<MatchAction> actions;
+  final MatchDetail? detail;
+  /// Section the match is grouped under, the plugin's name unless the plugin picked one.
+  final String? category;
+  /// Characters of the title and description that matched the query, as `(start, end)`
+  /// ranges of Unicode code points.
+  final List<(int, int)> titleHighlights;
+  final List<(int, int)> descriptionHighlights;
 
-  Match(this.title, this.description, {this.icon, this.score, this.actions = const []});
+  Match(
+    this.title,
+    this.description, {
+    this.id,
+    this.icon,
+    this.emoji,
+    this.thumbnailOf,
+    this.score,
+    this.actions = const [],
+    this.detail,
+    this.category,
+    this.titleHighlights = const [],
+    this.descriptionHighlights = const [],
+  });
 
   factory Match.fromJson(Map<String, dynamic> json) {
     return Match(
       json['title'] as String,
       json['description'] as String,
-      icon: json['icon'] as String?,
+      id: json['id'] as int?,
+      icon: switch (json['icon']) {
+        {'type': 'path', 'path': String path} => path,
+        {'type': 'data', 'mime': String mime, 'data': String data} => 'data:$mime;base64,$data',
+        // daemons predating icon objects send a path
+        String path => path,
+        _ => null,
+      },
+      emoji: switch (json['icon']) {
+        {'type': 'emoji', 'emoji': String emoji} => emoji,
+        _ => null,
+      },
+      thumbnailOf: switch (json['icon']) {
+        {'type': 'thumbnail', 'path': String path} => path,
+        _ => null,
+      },
       score: (json['score'] as num?)?.toDouble(),
+      detail: json['detail'] != null ? MatchDetail.fromJson(json['detail'] as Map<String, dynamic>) : null,
+      category: json['category'] as String?,
+      titleHighlights: _ranges(json['highlights']?['title']),
+      descriptionHighlights: _ranges(json['highlights']?['description']),
       actions: (json['actions'] as List<dynamic>? ?? []).map((actionItem) {
-        final actionJson = actionItem['action'] as Map<String, dynamic>;
-        final action = switch (actionJson['type']) {
-          'exec' => ShellExecHandler.fromJson(actionJson),
-          'open' => OpenURIHandler.fromJson(actionJson),
-          'clipboard' => ClipboardHandler.fromJson(actionJson),
-          'callback' => CallbackAction.fromJson(actionJson),
-          'launch' => LaunchHandler.fromJson(actionJson),
-          _ => throw Exception('Unknown action type: ${actionJson['type']}'),
-        };
-        return MatchAction(actionItem['title'], action, closeOnAction: actionItem['close_on_action'] ?? true);
+        final action = parseActionHandler(actionItem['action'] as Map<String, dynamic>);
+        final alternates = (actionItem['alternates'] as List<dynamic>? ?? [])
+            .map((e) => AlternateAction.fromJson(e as Map<String, dynamic>))
+            .toList();
+        return MatchAction(
+          actionItem['title'],
+          action,
+          closeOnAction: actionItem['close_on_action'] ?? true,
+          alternates: alternates,
+          requiresConfirmation: actionItem['requires_confirmation'] ?? false,
+          confirmationPrompt: actionItem['confirmation_prompt'] as String?,
+        );
       }).toList(),
     );
   }
 }
+
+List<(int, int)> _ranges(dynamic json) => [
+  for (final range in json as List<dynamic>? ?? const [])
+    if (range case [int start, int end]) (start, end),
+];
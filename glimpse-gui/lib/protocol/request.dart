@@ -1,8 +1,13 @@
 import 'dart:convert';
 
+import 'package:glimpse/protocol/match.dart';
+
 abstract class Method {
   String get methodName;
   dynamic asParams();
+
+  /// Sent without an id, the daemon answers none.
+  bool get isNotification => false;
 }
 
 class SearchMethod extends Method {
@@ -18,28 +23,213 @@ class SearchMethod extends Method {
 }
 
 class Activate extends Method {
-  final int itemIndex;
+  final int generation;
+  final int matchId;
   final int actionIndex;
+  final Modifiers modifiers;
+  /// Set once the user confirmed an action that requires it.
+  final bool confirmed;
 
   @override
   String get methodName => 'activate';
 
   @override
-  dynamic asParams() => [itemIndex, actionIndex];
+  dynamic asParams() => {
+    'generation': generation,
+    'match_id': matchId,
+    'action': actionIndex,
+    'modifiers': modifiers.toJson(),
+    if (confirmed) 'confirmed': true,
+  };
+
+  Activate(this.generation, this.matchId, this.actionIndex, {this.modifiers = const Modifiers(), this.confirmed = false});
+
+  /// The same activation, confirmed by the user.
+  Activate confirm() => Activate(generation, matchId, actionIndex, modifiers: modifiers, confirmed: true);
+}
+
+/// Matches `offset..offset + limit` of the search with id `requestId`. The daemon sends a page
+/// of a search at a time, the following pages come as more results of the search.
+class MoreMethod extends Method {
+  final int requestId;
+  final int offset;
+  final int limit;
+
+  @override
+  String get methodName => 'more';
+
+  @override
+  dynamic asParams() => {'request_id': requestId, 'offset': offset, 'limit': limit};
+
+  MoreMethod(this.requestId, this.offset, this.limit);
+}
+
+/// The detail of a match of the search with id `generation`, for matches sent without one.
+class DetailsMethod extends Method {
+  final int generation;
+  final int matchId;
+
+  @override
+  String get methodName => 'details';
+
+  @override
+  dynamic asParams() => {'generation': generation, 'match_id': matchId};
+
+  DetailsMethod(this.generation, this.matchId);
+}
+
+/// Rows `firstIndex` to `lastIndex` of the results of the search with id `generation` are on
+/// screen. The daemon fetches their details and thumbnails ahead of the others and announces
+/// each detail with `detail_ready`.
+class Viewport extends Method {
+  final int generation;
+  final int firstIndex;
+  final int lastIndex;
+
+  @override
+  String get methodName => 'viewport';
+
+  @override
+  dynamic asParams() => {'generation': generation, 'first_index': firstIndex, 'last_index': lastIndex};
+
+  @override
+  bool get isNotification => true;
+
+  Viewport(this.generation, this.firstIndex, this.lastIndex);
+}
+
+/// What a plugin logged, `glimpsed` for the daemon itself. `since` is in unix milliseconds.
+class GetLogs extends Method {
+  final String pluginId;
+  final int? since;
+
+  @override
+  String get methodName => 'get_logs';
+
+  @override
+  dynamic asParams() => {'plugin_id': pluginId, if (since != null) 'since': since};
+
+  GetLogs(this.pluginId, {this.since});
+}
+
+class Subscribe extends Method {
+  final String pluginId;
+  final String topic;
+
+  @override
+  String get methodName => 'subscribe';
+
+  @override
+  dynamic asParams() => {'plugin_id': pluginId, 'topic': topic};
+
+  Subscribe(this.pluginId, this.topic);
+}
+
+class Unsubscribe extends Method {
+  final String pluginId;
+  final String topic;
+
+  @override
+  String get methodName => 'unsubscribe';
+
+  @override
+  dynamic asParams() => {'plugin_id': pluginId, 'topic': topic};
+
+  Unsubscribe(this.pluginId, this.topic);
+}
+
+class HistoryMethod extends Method {
+  final int limit;
+
+  @override
+  String get methodName => 'history';
+
+  @override
+  dynamic asParams() => {'limit': limit};
+
+  HistoryMethod(this.limit);
+}
+
+/// The last completed search, answered while it is fresh. Sent on startup to show what the
+/// user saw before the launcher went away.
+class LastResultsMethod extends Method {
+  @override
+  String get methodName => 'last_results';
+
+  @override
+  dynamic asParams() => null;
+}
+
+/// Sent once the user allowed a plugin a sensitive permission, after an activation failed
+/// with `RpcError.permissionRequired`.
+class GrantPermission extends Method {
+  final String pluginId;
+  final String permission;
+
+  @override
+  String get methodName => 'grant_permission';
+
+  @override
+  dynamic asParams() => {'plugin_id': pluginId, 'permission': permission};
 
-  Activate(this.itemIndex, this.actionIndex);
+  GrantPermission(this.pluginId, this.permission);
+}
+
+/// Sent once the user trusted a plugin the daemon holds back, `sha256` being the digest
+/// they were shown.
+class TrustPlugin extends Method {
+  final String path;
+  final String sha256;
+
+  @override
+  String get methodName => 'trust_plugin';
+
+  @override
+  dynamic asParams() => {'path': path, 'sha256': sha256};
+
+  TrustPlugin(this.path, this.sha256);
+}
+
+class CancelAction extends Method {
+  final int actionId;
+
+  @override
+  String get methodName => 'cancel_action';
+
+  @override
+  dynamic asParams() => {'action_id': actionId};
+
+  CancelAction(this.actionId);
+}
+
+/// Value of the `jsonrpc` member of every message, as in `glimpse_sdk::protocol`.
+const jsonRpcVersion = '2.0';
+
+class RPCNotification {
+  final Method method;
+
+  RPCNotification(this.method);
+
+  String toJsonString() {
+    return jsonEncode({'jsonrpc': jsonRpcVersion, 'method': method.methodName, 'params': method.asParams()});
+  }
 }
 
 class RPCRequest {
   final int id;
   final Method method;
-  final String? target;
-  final String? context;
+  final String? pluginId;
 
-  RPCRequest(this.id, this.method, {this.target, this.context});
+  RPCRequest(this.id, this.method, {this.pluginId});
 
   Map<String, dynamic> toJson() {
-    return {'id': id, 'method': method.methodName, 'params': method.asParams(), 'target': target, 'context': context};
+    return {
+      'jsonrpc': jsonRpcVersion,
+      'id': id,
+      'method': method.methodName,
+      'params': method.asParams(),
+      'plugin_id': pluginId,
+    };
   }
 
   String toJsonString() {
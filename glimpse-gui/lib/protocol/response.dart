@@ -9,20 +9,197 @@ class Matches {
   }
 }
 
+class SnapshotItem {
+  final int id;
+  final double score;
+  SnapshotItem(this.id, this.score);
+
+  factory SnapshotItem.fromJson(Map<String, dynamic> json) {
+    return SnapshotItem(json['id'] as int, (json['score'] as num).toDouble());
+  }
+}
+
+class Snapshot {
+  final List<SnapshotItem> items;
+  Snapshot(this.items);
+
+  factory Snapshot.fromJson(Map<String, dynamic> json) {
+    return Snapshot((json['items'] as List<dynamic>).map((e) => SnapshotItem.fromJson(e as Map<String, dynamic>)).toList());
+  }
+}
+
+class Update {
+  final String topic;
+  final List<Match> items;
+  Update(this.topic, this.items);
+
+  factory Update.fromJson(Map<String, dynamic> json) {
+    return Update(
+      json['topic'] as String,
+      (json['items'] as List<dynamic>).map((e) => Match.fromJson(e as Map<String, dynamic>)).toList(),
+    );
+  }
+}
+
+class HistoryEntry {
+  final String pluginId;
+  final String title;
+  final int count;
+  final int lastUsed;
+  HistoryEntry(this.pluginId, this.title, this.count, this.lastUsed);
+
+  factory HistoryEntry.fromJson(Map<String, dynamic> json) {
+    return HistoryEntry(json['plugin_id'] as String, json['title'] as String, json['count'] as int, json['last_used'] as int);
+  }
+}
+
+class History {
+  final List<HistoryEntry> items;
+  History(this.items);
+
+  factory History.fromJson(Map<String, dynamic> json) {
+    return History((json['items'] as List<dynamic>).map((e) => HistoryEntry.fromJson(e as Map<String, dynamic>)).toList());
+  }
+}
+
+/// Matches of the last completed search, activated under the id of the request.
+class LastResults {
+  final String query;
+  final List<Match> items;
+  LastResults(this.query, this.items);
+
+  factory LastResults.fromJson(Map<String, dynamic> json) {
+    return LastResults(
+      json['query'] as String,
+      (json['items'] as List<dynamic>).map((e) => Match.fromJson(e as Map<String, dynamic>)).toList(),
+    );
+  }
+}
+
+/// Answer to a details request, `detail` is null for matches without one.
+class Details {
+  final MatchDetail? detail;
+  Details(this.detail);
+
+  factory Details.fromJson(Map<String, dynamic> json) {
+    final detail = json['detail'] as Map<String, dynamic>?;
+    return Details(detail != null ? MatchDetail.fromJson(detail) : null);
+  }
+}
+
+/// Progress of a running callback action, `actionId` is the id of the activate request.
+class ActionProgress {
+  final int actionId;
+  final int pct;
+  final String message;
+  ActionProgress(this.actionId, this.pct, this.message);
+
+  factory ActionProgress.fromJson(Map<String, dynamic> json) {
+    return ActionProgress(json['action_id'] as int, json['pct'] as int, json['message'] as String);
+  }
+}
+
+/// How the steps of an activated sequence went, the message of the step that failed if one did.
+class SequenceResult {
+  final List<String> statuses;
+  final String? failure;
+  SequenceResult(this.statuses, this.failure);
+
+  factory SequenceResult.fromJson(Map<String, dynamic> json) {
+    final steps = (json['steps'] as List<dynamic>).cast<Map<String, dynamic>>();
+    final failed = steps.where((step) => step['status'] == 'failed');
+    return SequenceResult(
+      steps.map((step) => step['status'] as String).toList(),
+      failed.isEmpty ? null : failed.first['message'] as String,
+    );
+  }
+}
+
+class LogRecord {
+  final int ts;
+  final String level;
+  final String target;
+  final String message;
+  LogRecord(this.ts, this.level, this.target, this.message);
+
+  factory LogRecord.fromJson(Map<String, dynamic> json) {
+    return LogRecord(
+      json['ts'] as int,
+      json['level'] as String,
+      json['target'] as String? ?? '',
+      json['message'] as String,
+    );
+  }
+}
+
+/// Records a plugin logged, oldest first.
+class Logs {
+  final List<LogRecord> items;
+  Logs(this.items);
+
+  factory Logs.fromJson(Map<String, dynamic> json) {
+    return Logs((json['items'] as List<dynamic>).map((e) => LogRecord.fromJson(e as Map<String, dynamic>)).toList());
+  }
+}
+
+class PluginFailure {
+  final String message;
+  PluginFailure(this.message);
+}
+
+/// A JSON-RPC 2.0 error object, codes as in `glimpse_sdk::RpcError`.
+class RpcError {
+  static const internalError = -32603;
+  static const pluginError = -32000;
+  static const rejected = -32001;
+
+  /// The plugin needs a sensitive permission the user has not granted, `data` holds its
+  /// `plugin_id` and `permission`.
+  static const permissionRequired = -32002;
+
+  /// The action requires confirmation, `data` holds the `prompt` to ask the user.
+  static const confirmationRequired = -32003;
+
+  final int code;
+  final String message;
+  final dynamic data;
+
+  RpcError(this.code, this.message, {this.data});
+
+  /// Older daemons send the message alone.
+  factory RpcError.fromJson(dynamic json) {
+    return switch (json) {
+      String message => RpcError(internalError, message),
+      _ => RpcError(json['code'] as int, json['message'] as String, data: json['data']),
+    };
+  }
+}
+
 class RPCResponse {
   final int id;
   final dynamic result;
   final String? source;
-  final String? error;
+  final RpcError? error;
 
   RPCResponse(this.id, this.result, {this.source, this.error});
 
   factory RPCResponse.fromJson(Map<String, dynamic> json) {
-    final result = switch (json['result']['type']) {
+    final resultJson = json['result'] as Map<String, dynamic>?;
+    final result = switch (resultJson?['type']) {
+      null || 'none' || 'done' => null,
+      'error' => PluginFailure(resultJson!['message'] as String),
       'matches' => (json['result']['items'] as List<dynamic>).map((e) => Match.fromJson(e)).toList(),
-      _ => throw UnimplementedError('Unknown MethodResult type: ${json['result']['type']}'),
+      'snapshot' => Snapshot.fromJson(json['result']),
+      'update' => Update.fromJson(json['result']),
+      'history' => History.fromJson(json['result']),
+      'last_results' => LastResults.fromJson(json['result']),
+      'details' => Details.fromJson(json['result']),
+      'sequence' => SequenceResult.fromJson(json['result']),
+      'logs' => Logs.fromJson(json['result']),
+      _ => throw UnimplementedError('Unknown MethodResult type: ${resultJson!['type']}'),
     };
 
-    return RPCResponse(json['id'] as int, result, source: json['plugin_id'] as String?, error: json['error'] as String?);
+    final error = json['error'] == null ? null : RpcError.fromJson(json['error']);
+    return RPCResponse(json['id'] as int, result, source: json['plugin_id'] as String?, error: error);
   }
 }
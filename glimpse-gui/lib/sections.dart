@@ -0,0 +1,123 @@
+import 'package:glimpse/protocol/match.dart';
+
+/// Section of matches older daemons send without a category.
+const otherCategory = 'Other';
+
+/// A row of the result list.
+sealed class ResultRow {}
+
+/// Heading of a section, a collapsed one shows none of its matches.
+final class SectionHeader extends ResultRow {
+  final String category;
+  final int count;
+  final bool collapsed;
+  SectionHeader(this.category, this.count, {this.collapsed = false});
+}
+
+/// A match, by its index in the ranked results.
+final class ResultItem extends ResultRow {
+  final int index;
+  ResultItem(this.index);
+}
+
+/// Matches of a section held back by the per-category cap.
+final class MoreResults extends ResultRow {
+  final String category;
+  final int hidden;
+  MoreResults(this.category, this.hidden);
+}
+
+/// The rows of the result list, matches grouped under their category or in one flat list.
+class SectionLayout {
+  final List<ResultRow> rows;
+  // indexes of the matches each section shows, sections without any left out
+  final List<List<int>> _sections;
+
+  SectionLayout._(this.rows, this._sections);
+
+  /// Every match in ranked order, without headings.
+  factory SectionLayout.flat(int count) {
+    final indexes = List<int>.generate(count, (index) => index);
+    return SectionLayout._(indexes.map(ResultItem.new).toList(), [if (count > 0) indexes]);
+  }
+
+  /// Sections in the order of their best match, each keeping the ranked order. `limit` caps
+  /// the matches a section shows unless it is in `expanded`, 0 shows them all. Sections in
+  /// `collapsed` show their heading alone.
+  factory SectionLayout.group(
+    List<Match> items, {
+    int limit = 0,
+    Set<String> expanded = const {},
+    Set<String> collapsed = const {},
+  }) {
+    final byCategory = <String, List<int>>{};
+    for (final (index, item) in items.indexed) {
+      byCategory.putIfAbsent(item.category ?? otherCategory, () => []).add(index);
+    }
+
+    final rows = <ResultRow>[];
+    final sections = <List<int>>[];
+    for (final MapEntry(key: category, value: indexes) in byCategory.entries) {
+      final isCollapsed = collapsed.contains(category);
+      rows.add(SectionHeader(category, indexes.length, collapsed: isCollapsed));
+      if (isCollapsed) {
+        continue;
+      }
+      final capped = limit > 0 && !expanded.contains(category) && indexes.length > limit;
+      final shown = capped ? indexes.sublist(0, limit) : indexes;
+      rows.addAll(shown.map(ResultItem.new));
+      if (capped) {
+        rows.add(MoreResults(category, indexes.length - limit));
+      }
+      sections.add(shown);
+    }
+    return SectionLayout._(rows, sections);
+  }
+
+  /// Indexes of the matches on screen, top to bottom.
+  List<int> get visible => _sections.expand((indexes) => indexes).toList();
+
+  /// The match `steps` matches below `index` on screen, above for negative steps, stopping
+  /// at either end. -1 when no match shows.
+  int move(int index, int steps) {
+    final visible = this.visible;
+    if (visible.isEmpty) {
+      return -1;
+    }
+    final position = visible.indexOf(index);
+    if (position < 0) {
+      return visible.first;
+    }
+    return visible[(position + steps).clamp(0, visible.length - 1)];
+  }
+
+  /// The first match of the section after the one of `index`, or before it for a negative
+  /// `direction`, staying put past the last or first section.
+  int jumpSection(int index, int direction) {
+    if (_sections.isEmpty) {
+      return -1;
+    }
+    final current = _sections.indexWhere((indexes) => indexes.contains(index));
+    if (current < 0) {
+      return _sections.first.first;
+    }
+    final target = current + direction.sign;
+    if (target < 0 || target >= _sections.length) {
+      return index;
+    }
+    return _sections[target].first;
+  }
+
+  /// Lowest and highest index of the matches in rows `first` to `last`, null when those rows
+  /// hold none.
+  (int, int)? itemsIn(int first, int last) {
+    final indexes = rows
+        .sublist(first.clamp(0, rows.length), (last + 1).clamp(0, rows.length))
+        .whereType<ResultItem>()
+        .map((row) => row.index);
+    if (indexes.isEmpty) {
+      return null;
+    }
+    return (indexes.reduce((a, b) => a < b ? a : b), indexes.reduce((a, b) => a > b ? a : b));
+  }
+}
@@ -0,0 +1,19 @@
+import 'package:flutter/material.dart';
+import 'package:glimpse/protocol/response.dart';
+
+/// A running action with its progress bar and a control to cancel it.
+class ActionProgressRow extends StatelessWidget {
+  final ActionProgress progress;
+  final VoidCallback onCancel;
+  const ActionProgressRow({super.key, required this.progress, required this.onCancel});
+
+  @override
+  Widget build(BuildContext context) {
+    return ListTile(
+      dense: true,
+      title: Text(progress.message.isEmpty ? 'Working...' : progress.message),
+      subtitle: LinearProgressIndicator(value: progress.pct / 100),
+      trailing: IconButton(icon: const Icon(Icons.close), tooltip: 'Cancel', onPressed: onCancel),
+    );
+  }
+}
@@ -0,0 +1,154 @@
+import 'dart:io';
+
+import 'package:flutter/material.dart';
+import 'package:glimpse/protocol/match.dart';
+
+/// The detail of the selected match, beside the result list.
+class DetailPane extends StatelessWidget {
+  final MatchDetail detail;
+  const DetailPane({super.key, required this.detail});
+
+  Widget? buildImage() {
+    final image = detail.image;
+    if (image == null || image.isEmpty) {
+      return null;
+    }
+    final file = File(image.startsWith('file://') ? Uri.parse(image).toFilePath() : image);
+    if (!file.existsSync()) {
+      return null;
+    }
+    return Padding(
+      padding: const EdgeInsets.only(bottom: 12),
+      child: Image.file(file, fit: BoxFit.contain, errorBuilder: (context, error, stackTrace) => const SizedBox()),
+    );
+  }
+
+  @override
+  Widget build(BuildContext context) {
+    final image = buildImage();
+    return SingleChildScrollView(
+      padding: const EdgeInsets.all(16),
+      child: Column(
+        crossAxisAlignment: CrossAxisAlignment.start,
+        children: [
+          if (image != null) image,
+          switch (detail.format) {
+            DetailFormat.text => SelectableText(detail.content),
+            DetailFormat.markdown => Markdown(source: detail.content),
+          },
+        ],
+      ),
+    );
+  }
+}
+
+/// Renders the markdown plugins use for details: headings, lists, code blocks and
+/// paragraphs with bold, italic and inline code.
+class Markdown extends StatelessWidget {
+  final String source;
+  const Markdown({super.key, required this.source});
+
+  static final _heading = RegExp(r'^(#{1,6})\s+(.*)$');
+  static final _bullet = RegExp(r'^\s*[-*+]\s+(.*)$');
+  static final _numbered = RegExp(r'^\s*(\d+)[.)]\s+(.*)$');
+  static final _inline = RegExp(r'(\*\*|__)(.+?)\1|(\*|_)(.+?)\3|`([^`]+)`');
+
+  List<InlineSpan> buildInline(String text, TextStyle style) {
+    final spans = <InlineSpan>[];
+    var start = 0;
+    for (final match in _inline.allMatches(text)) {
+      if (match.start > start) {
+        spans.add(TextSpan(text: text.substring(start, match.start)));
+      }
+      if (match.group(2) != null) {
+        spans.add(TextSpan(text: match.group(2), style: const TextStyle(fontWeight: FontWeight.bold)));
+      } else if (match.group(4) != null) {
+        spans.add(TextSpan(text: match.group(4), style: const TextStyle(fontStyle: FontStyle.italic)));
+      } else {
+        spans.add(TextSpan(text: match.group(5), style: style));
+      }
+      start = match.end;
+    }
+    if (start < text.length) {
+      spans.add(TextSpan(text: text.substring(start)));
+    }
+    return spans;
+  }
+
+  @override
+  Widget build(BuildContext context) {
+    final theme = Theme.of(context).textTheme;
+    final code = TextStyle(fontFamily: 'monospace', backgroundColor: Colors.grey[300]);
+    final blocks = <Widget>[];
+    final paragraph = <String>[];
+    List<String>? fence;
+
+    void text(String content, TextStyle? style, {String prefix = ''}) {
+      blocks.add(
+        Padding(
+          padding: const EdgeInsets.only(bottom: 8),
+          child: SelectableText.rich(TextSpan(style: style, children: [TextSpan(text: prefix), ...buildInline(content, code)])),
+        ),
+      );
+    }
+
+    void flush() {
+      if (paragraph.isNotEmpty) {
+        text(paragraph.join(' '), theme.bodyMedium);
+        paragraph.clear();
+      }
+    }
+
+    for (final line in source.split('\n')) {
+      if (line.trimLeft().startsWith('```')) {
+        if (fence == null) {
+          flush();
+          fence = [];
+        } else {
+          blocks.add(
+            Container(
+              width: double.infinity,
+              margin: const EdgeInsets.only(bottom: 8),
+              padding: const EdgeInsets.all(8),
+              color: Colors.grey[300],
+              child: SelectableText(fence.join('\n'), style: const TextStyle(fontFamily: 'monospace')),
+            ),
+          );
+          fence = null;
+        }
+        continue;
+      }
+      if (fence != null) {
+        fence.add(line);
+        continue;
+      }
+      if (line.trim().isEmpty) {
+        flush();
+        continue;
+      }
+      final heading = _heading.firstMatch(line);
+      final bullet = _bullet.firstMatch(line);
+      final numbered = _numbered.firstMatch(line);
+      if (heading != null) {
+        flush();
+        final level = heading.group(1)!.length;
+        text(heading.group(2)!, level == 1 ? theme.titleLarge : level == 2 ? theme.titleMedium : theme.titleSmall);
+      } else if (bullet != null) {
+        flush();
+        text(bullet.group(1)!, theme.bodyMedium, prefix: '•  ');
+      } else if (numbered != null) {
+        flush();
+        text(numbered.group(2)!, theme.bodyMedium, prefix: '${numbered.group(1)}.  ');
+      } else {
+        paragraph.add(line.trim());
+      }
+    }
+    // an unclosed fence still shows its code
+    if (fence != null) {
+      paragraph.addAll(fence);
+    }
+    flush();
+
+    return Column(crossAxisAlignment: CrossAxisAlignment.start, children: blocks);
+  }
+}
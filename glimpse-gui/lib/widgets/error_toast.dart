@@ -0,0 +1,105 @@
+import 'dart:async';
+
+import 'package:flutter/material.dart';
+import 'package:glimpse/config.dart';
+
+class ErrorToast {
+  final String pluginId;
+  final String message;
+  ErrorToast(this.pluginId, this.message);
+}
+
+/// Collects plugin errors and decides which ones are worth showing.
+///
+/// Each plugin gets at most one toast per [window]; muted plugins never show toasts.
+class ErrorToastController extends ChangeNotifier {
+  GuiConfig config;
+  final Duration window;
+  final Duration ttl;
+  final _lastShown = <String, DateTime>{};
+  final _toasts = <ErrorToast>[];
+
+  ErrorToastController(
+    this.config, {
+    this.window = const Duration(seconds: 30),
+    this.ttl = const Duration(seconds: 6),
+  });
+
+  List<ErrorToast> get toasts => List.unmodifiable(_toasts);
+
+  void report(String pluginId, String message) {
+    if (config.mutedErrorPlugins.contains(pluginId)) {
+      return;
+    }
+
+    final now = DateTime.now();
+    final last = _lastShown[pluginId];
+    if (last != null && now.difference(last) < window) {
+      print('Suppressed error from $pluginId: $message');
+      return;
+    }
+    _lastShown[pluginId] = now;
+
+    final toast = ErrorToast(pluginId, message);
+    _toasts.add(toast);
+    notifyListeners();
+    Timer(ttl, () => dismiss(toast));
+  }
+
+  void dismiss(ErrorToast toast) {
+    if (_toasts.remove(toast)) {
+      notifyListeners();
+    }
+  }
+
+  Future<void> mute(String pluginId) async {
+    config.mutedErrorPlugins.add(pluginId);
+    _toasts.removeWhere((toast) => toast.pluginId == pluginId);
+    notifyListeners();
+    await config.save();
+  }
+}
+
+/// Toasts stacked at the bottom of the window. Does not take focus from the search input.
+class ErrorToastStack extends StatelessWidget {
+  final ErrorToastController controller;
+  const ErrorToastStack({super.key, required this.controller});
+
+  @override
+  Widget build(BuildContext context) {
+    return ListenableBuilder(
+      listenable: controller,
+      builder: (context, _) => ExcludeFocus(
+        child: Align(
+          alignment: Alignment.bottomCenter,
+          child: Padding(
+            padding: const EdgeInsets.all(8),
+            child: Column(
+              mainAxisSize: MainAxisSize.min,
+              children: controller.toasts.map((toast) => _buildToast(toast)).toList(),
+            ),
+          ),
+        ),
+      ),
+    );
+  }
+
+  Widget _buildToast(ErrorToast toast) {
+    return Card(
+      color: Colors.red[50],
+      child: ListTile(
+        dense: true,
+        leading: Icon(Icons.error_outline, color: Colors.red[700]),
+        title: Text(toast.message, maxLines: 2, overflow: TextOverflow.ellipsis),
+        subtitle: Text(toast.pluginId),
+        trailing: Row(
+          mainAxisSize: MainAxisSize.min,
+          children: [
+            TextButton(onPressed: () => controller.mute(toast.pluginId), child: const Text("Don't show again")),
+            IconButton(icon: const Icon(Icons.close), onPressed: () => controller.dismiss(toast)),
+          ],
+        ),
+      ),
+    );
+  }
+}
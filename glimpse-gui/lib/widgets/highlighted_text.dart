@@ -0,0 +1,47 @@
+import 'package:flutter/material.dart';
+
+/// `text` split into the parts outside and inside `ranges`, ranges of Unicode code points
+/// as the daemon sends them. Ranges past the end of the text are cut short.
+List<(String, bool)> highlightedParts(String text, List<(int, int)> ranges) {
+  final runes = text.runes.toList();
+  final parts = <(String, bool)>[];
+  var position = 0;
+  for (final (start, end) in ranges) {
+    final from = start.clamp(position, runes.length);
+    final to = end.clamp(from, runes.length);
+    if (from > position) {
+      parts.add((String.fromCharCodes(runes, position, from), false));
+    }
+    if (to > from) {
+      parts.add((String.fromCharCodes(runes, from, to), true));
+    }
+    position = to;
+  }
+  if (position < runes.length) {
+    parts.add((String.fromCharCodes(runes, position), false));
+  }
+  return parts;
+}
+
+/// Text with the parts that matched the query in bold.
+class HighlightedText extends StatelessWidget {
+  final String text;
+  final List<(int, int)> ranges;
+
+  const HighlightedText(this.text, this.ranges, {super.key});
+
+  @override
+  Widget build(BuildContext context) {
+    if (ranges.isEmpty) {
+      return Text(text);
+    }
+    return Text.rich(
+      TextSpan(
+        children: [
+          for (final (part, matched) in highlightedParts(text, ranges))
+            TextSpan(text: part, style: matched ? const TextStyle(fontWeight: FontWeight.bold) : null),
+        ],
+      ),
+    );
+  }
+}
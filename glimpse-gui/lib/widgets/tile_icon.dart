@@ -2,6 +2,29 @@ import 'dart:io';
 
 import 'package:flutter/material.dart';
 import 'package:flutter_svg/svg.dart';
+import 'package:glimpse/protocol/match.dart';
+
+/// The leading icon of a match row, null for matches without one. `thumbnails` holds the
+/// thumbnails rendered so far by file, a match waiting for one shows a placeholder.
+Widget? matchIcon(Match item, {Map<String, String> thumbnails = const {}}) {
+  final icon = item.icon ?? thumbnails[item.thumbnailOf];
+  if (icon != null) {
+    return TileIcon(path: icon);
+  }
+  if (item.thumbnailOf != null) {
+    return const TileIcon(path: '');
+  }
+  if (item.emoji != null) {
+    return Container(
+      width: 40,
+      height: 40,
+      margin: const EdgeInsets.only(right: 10),
+      alignment: Alignment.center,
+      child: Text(item.emoji!, style: const TextStyle(fontSize: 28)),
+    );
+  }
+  return null;
+}
 
 class TileIcon extends StatelessWidget {
   final String path;
@@ -16,6 +39,14 @@ class TileIcon extends StatelessWidget {
       return defaultIcon;
     }
 
+    if (path.startsWith('data:')) {
+      final data = UriData.parse(path);
+      if (data.mimeType == 'image/svg+xml') {
+        return SvgPicture.memory(data.contentAsBytes(), width: size, height: size, placeholderBuilder: (context) => defaultIcon);
+      }
+      return Image.memory(data.contentAsBytes(), width: size, height: size, errorBuilder: (context, error, stackTrace) => errorIcon);
+    }
+
     if (path.toLowerCase().endsWith('.svg')) {
       return buildSVGImage(path);
     }
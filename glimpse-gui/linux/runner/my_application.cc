@@ -4,6 +4,10 @@
 #ifdef GDK_WINDOWING_X11
 #include <gdk/gdkx.h>
 #endif
+#ifdef HAVE_GTK_LAYER_SHELL
+#include <gtk-layer-shell/gtk-layer-shell.h>
+#include <json-glib/json-glib.h>
+#endif
 
 #include "flutter/generated_plugin_registrant.h"
 
@@ -20,6 +24,22 @@ static void first_frame_cb(MyApplication* self, FlView *view)
   gtk_widget_show(gtk_widget_get_toplevel(GTK_WIDGET(view)));
 }
 
+#ifdef HAVE_GTK_LAYER_SHELL
+// Whether `layer_shell` in gui.json leaves the layer surface on, as it is by default.
+static gboolean layer_shell_enabled() {
+  g_autofree gchar* path = g_build_filename(g_get_user_config_dir(), "glimpse", "gui.json", nullptr);
+  g_autoptr(JsonParser) parser = json_parser_new();
+  if (!json_parser_load_from_file(parser, path, nullptr)) {
+    return TRUE;
+  }
+  JsonNode* root = json_parser_get_root(parser);
+  if (root == nullptr || !JSON_NODE_HOLDS_OBJECT(root)) {
+    return TRUE;
+  }
+  return json_object_get_boolean_member_with_default(json_node_get_object(root), "layer_shell", TRUE);
+}
+#endif
+
 // Implements GApplication::activate.
 static void my_application_activate(GApplication* application) {
   MyApplication* self = MY_APPLICATION(application);
@@ -54,6 +74,16 @@ static void my_application_activate(GApplication* application) {
   }
 
   gtk_window_set_default_size(window, 800, 500);
+#ifdef HAVE_GTK_LAYER_SHELL
+  // An overlay the compositor centers, holding the keyboard while shown. Compositors
+  // without layer-shell, and X11, get a regular window.
+  if (layer_shell_enabled() && gtk_layer_is_supported()) {
+    gtk_layer_init_for_window(window);
+    gtk_layer_set_namespace(window, "glimpse");
+    gtk_layer_set_layer(window, GTK_LAYER_SHELL_LAYER_OVERLAY);
+    gtk_layer_set_keyboard_mode(window, GTK_LAYER_SHELL_KEYBOARD_MODE_EXCLUSIVE);
+  }
+#endif
   gtk_widget_realize(GTK_WIDGET(window));
 
   g_autoptr(FlDartProject) project = fl_dart_project_new();
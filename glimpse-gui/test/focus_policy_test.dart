@@ -0,0 +1,62 @@
+import 'package:flutter/services.dart';
+import 'package:flutter_test/flutter_test.dart';
+import 'package:glimpse/focus_policy.dart';
+
+TextEditingValue typed(String text, int cursor) =>
+    TextEditingValue(text: text, selection: TextSelection.collapsed(offset: cursor));
+
+void main() {
+  test('non-closing activation restores the entry with its cursor', () {
+    final policy = FocusPolicy();
+    policy.entryChanged(typed('firefox', 4), focused: true);
+
+    expect(policy.activated(closesWindow: false), isTrue);
+    expect(policy.owner, FocusOwner.entry);
+    expect(policy.selectionFor('firefox'), const TextSelection.collapsed(offset: 4));
+  });
+
+  test('closing activation leaves focus alone', () {
+    final policy = FocusPolicy();
+    expect(policy.activated(closesWindow: true), isFalse);
+  });
+
+  test('losing focus does not move the remembered cursor', () {
+    final policy = FocusPolicy();
+    policy.entryChanged(typed('firefox', 2), focused: true);
+    policy.entryChanged(typed('firefox', 0), focused: false);
+
+    expect(policy.selectionFor('firefox'), const TextSelection.collapsed(offset: 2));
+  });
+
+  test('action menu hands focus back when closed or used', () {
+    final policy = FocusPolicy();
+    policy.entryChanged(typed('term', 4), focused: true);
+
+    policy.menuOpened();
+    expect(policy.owner, FocusOwner.actionMenu);
+    // typing is not tracked while the menu is open
+    policy.entryChanged(typed('term', 1), focused: true);
+    expect(policy.menuClosed(), isTrue);
+    expect(policy.owner, FocusOwner.entry);
+    expect(policy.menuClosed(), isFalse);
+
+    policy.menuOpened();
+    expect(policy.activated(closesWindow: false), isTrue);
+    expect(policy.menuClosed(), isFalse);
+    expect(policy.selectionFor('term'), const TextSelection.collapsed(offset: 4));
+  });
+
+  test('cursor stays inside shorter text and defaults to the end', () {
+    final policy = FocusPolicy();
+    expect(policy.selectionFor('abc'), const TextSelection.collapsed(offset: 3));
+
+    policy.entryChanged(
+      const TextEditingValue(text: 'firefox', selection: TextSelection(baseOffset: 2, extentOffset: 7)),
+      focused: true,
+    );
+    expect(policy.selectionFor('fire'), const TextSelection(baseOffset: 2, extentOffset: 4));
+
+    policy.textReplaced('Terminal');
+    expect(policy.selectionFor('Terminal'), const TextSelection.collapsed(offset: 8));
+  });
+}
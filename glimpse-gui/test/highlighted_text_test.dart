@@ -0,0 +1,22 @@
+import 'package:flutter_test/flutter_test.dart';
+import 'package:glimpse/widgets/highlighted_text.dart';
+
+void main() {
+  test('ranges split the text into matched and unmatched parts', () {
+    expect(highlightedParts('Firefox', [(0, 2), (4, 6)]), [
+      ('Fi', true),
+      ('re', false),
+      ('fo', true),
+      ('x', false),
+    ]);
+  });
+
+  test('ranges count code points and stop at the end of the text', () {
+    expect(highlightedParts('🦀 crab', [(2, 4), (6, 9)]), [
+      ('🦀 ', false),
+      ('cr', true),
+      ('ab', false),
+    ]);
+    expect(highlightedParts('', [(0, 1)]), isEmpty);
+  });
+}
@@ -0,0 +1,39 @@
+import 'package:flutter_test/flutter_test.dart';
+import 'package:glimpse/keymap.dart';
+
+void main() {
+  test('commands are configured by their snake case names', () {
+    expect(Keymap.jsonName(Command.actionMenu), 'action_menu');
+    expect(Keymap.jsonName(Command.clear), 'clear');
+    expect(Keymap.jsonName(Command.activateAlt3), 'activate_alt_3');
+  });
+
+  test('overrides replace the defaults of their command alone', () {
+    final keymap = Keymap.fromJson({
+      'activate_alt_1': {
+        'keys': ['ctrl+1'],
+      },
+      'clear': {'match': 'character'},
+    });
+
+    final alternate = keymap.bindings[Command.activateAlt1]!.chords.single;
+    expect(alternate.key, '1');
+    expect(alternate.ctrl, isTrue);
+    expect(alternate.alt, isFalse);
+    expect(keymap.bindings[Command.clear]!.match, KeyMatch.character);
+    expect(keymap.bindings[Command.clear]!.chords, Keymap.defaults[Command.clear]!.chords);
+    expect(keymap.bindings[Command.next], Keymap.defaults[Command.next]);
+  });
+
+  test('alternate actions follow the default one', () {
+    expect(Command.activateAlt1.alternateAction, 1);
+    expect(Command.activateAlt9.alternateAction, 9);
+    expect(Command.activate.alternateAction, isNull);
+    expect(Command.actionMenu.alternateAction, isNull);
+  });
+
+  test('tab and right arrow open the action menu', () {
+    final keys = Keymap.defaults[Command.actionMenu]!.chords.where((chord) => chord.modifierCount == 0).map((chord) => chord.key);
+    expect(keys, containsAll(['tab', 'right']));
+  });
+}
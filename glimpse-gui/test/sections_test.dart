@@ -0,0 +1,48 @@
+import 'package:flutter_test/flutter_test.dart';
+import 'package:glimpse/protocol/match.dart';
+import 'package:glimpse/sections.dart';
+
+List<Match> matches(List<String?> categories) => [
+  for (final (index, category) in categories.indexed) Match('$index', '', id: index, category: category),
+];
+
+void main() {
+  test('sections follow their best match and keep the ranking', () {
+    final layout = SectionLayout.group(matches(['Files', 'Apps', 'Files', null]));
+
+    final headers = layout.rows.whereType<SectionHeader>().map((header) => header.category);
+    expect(headers, ['Files', 'Apps', otherCategory]);
+    expect(layout.visible, [0, 2, 1, 3]);
+  });
+
+  test('the cap holds matches back until the section is expanded', () {
+    final items = matches(['Apps', 'Apps', 'Apps', 'Files']);
+
+    final capped = SectionLayout.group(items, limit: 2);
+    expect(capped.visible, [0, 1, 3]);
+    expect(capped.rows.whereType<MoreResults>().single.hidden, 1);
+
+    final expanded = SectionLayout.group(items, limit: 2, expanded: {'Apps'});
+    expect(expanded.visible, [0, 1, 2, 3]);
+    expect(expanded.rows.whereType<MoreResults>(), isEmpty);
+  });
+
+  test('navigation skips collapsed sections', () {
+    final layout = SectionLayout.group(matches(['Apps', 'Files', 'Calculator', 'Apps']), collapsed: {'Files'});
+
+    expect(layout.move(3, 1), 2);
+    expect(layout.move(2, 1), 2);
+    expect(layout.jumpSection(0, 1), 2);
+    expect(layout.jumpSection(2, -1), 0);
+    expect(layout.jumpSection(2, 1), 2);
+  });
+
+  test('a flat layout has no headings', () {
+    final layout = SectionLayout.flat(3);
+
+    expect(layout.rows.whereType<SectionHeader>(), isEmpty);
+    expect(layout.move(0, 5), 2);
+    expect(layout.jumpSection(1, 1), 1);
+    expect(layout.itemsIn(1, 9), (1, 2));
+  });
+}
@@ -0,0 +1,157 @@
+use glimpse_sdk::{Action, Icon, Match};
+
+/// Well-known name the bridge takes on the session bus, named by the runner's desktop file.
+pub const SERVICE: &str = "org.glimpse.KRunner";
+/// Object path of the `org.kde.krunner1` interface.
+pub const PATH: &str = "/runner";
+
+/// `Plasma::QueryMatch::Type` values KRunner sorts matches by.
+pub const POSSIBLE_MATCH: i32 = 30;
+pub const EXACT_MATCH: i32 = 100;
+
+/// Kinds of actions KRunner offers beside running a match. KRunner asks for its actions once,
+/// so secondary glimpse actions are grouped by what they do rather than by their titles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionKind {
+    Run,
+    Open,
+    Copy,
+    Other,
+}
+
+impl ActionKind {
+    pub const ALL: [ActionKind; 4] = [
+        ActionKind::Run,
+        ActionKind::Open,
+        ActionKind::Copy,
+        ActionKind::Other,
+    ];
+
+    pub fn of(action: &Action) -> Self {
+        match action {
+            Action::Exec { .. } | Action::Launch { .. } => ActionKind::Run,
+            Action::Open { .. } => ActionKind::Open,
+            Action::Clipboard { .. } | Action::ExpiringClipboard { .. } => ActionKind::Copy,
+            Action::TypeText { .. }
+            | Action::Callback { .. }
+            | Action::Schedule { .. }
+            | Action::System { .. }
+            | Action::Connection { .. }
+            | Action::Sequence { .. } => ActionKind::Other,
+        }
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            ActionKind::Run => "run",
+            ActionKind::Open => "open",
+            ActionKind::Copy => "copy",
+            ActionKind::Other => "other",
+        }
+    }
+
+    fn text(&self) -> &'static str {
+        match self {
+            ActionKind::Run => "Run",
+            ActionKind::Open => "Open",
+            ActionKind::Copy => "Copy to clipboard",
+            ActionKind::Other => "More",
+        }
+    }
+
+    fn icon(&self) -> &'static str {
+        match self {
+            ActionKind::Run => "system-run",
+            ActionKind::Open => "document-open",
+            ActionKind::Copy => "edit-copy",
+            ActionKind::Other => "view-more-horizontal",
+        }
+    }
+}
+
+/// The runner's actions as KRunner lists them, (id, text, icon name).
+pub fn actions() -> Vec<(String, String, String)> {
+    ActionKind::ALL
+        .iter()
+        .map(|kind| {
+            (
+                kind.id().to_string(),
+                kind.text().to_string(),
+                kind.icon().to_string(),
+            )
+        })
+        .collect()
+}
+
+/// A glimpse match translated for KRunner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KrunnerMatch {
+    /// The search generation and the match id, see [`parse_match_id`].
+    pub id: String,
+    pub text: String,
+    /// A freedesktop icon name, empty for icons KRunner cannot load by name.
+    pub icon_name: String,
+    pub match_type: i32,
+    /// Between 0 and 1, falling with the match's place in the daemon's order.
+    pub relevance: f64,
+    pub subtext: String,
+    /// KRunner actions offered besides running the match, with the index of the glimpse
+    /// action each one runs: the first secondary action of its kind.
+    pub actions: Vec<(ActionKind, usize)>,
+}
+
+impl KrunnerMatch {
+    /// Index of the glimpse action to run for the KRunner action `action_id`, the default
+    /// action for an empty id.
+    pub fn action_index(&self, action_id: &str) -> Option<usize> {
+        if action_id.is_empty() {
+            return Some(0);
+        }
+        self.actions
+            .iter()
+            .find(|(kind, _)| kind.id() == action_id)
+            .map(|(_, index)| *index)
+    }
+}
+
+/// Matches of the search `generation` for `query`, in the daemon's order.
+pub fn translate(query: &str, generation: usize, matches: &[Match]) -> Vec<KrunnerMatch> {
+    let count = matches.len().max(1) as f64;
+    matches
+        .iter()
+        .enumerate()
+        .filter_map(|(position, item)| {
+            let id = item.id?;
+            let icon_name = match &item.icon {
+                Some(Icon::Freedesktop { name }) => name.clone(),
+                _ => String::new(),
+            };
+            let match_type = match item.title.trim().eq_ignore_ascii_case(query.trim()) {
+                true => EXACT_MATCH,
+                false => POSSIBLE_MATCH,
+            };
+            let mut actions: Vec<(ActionKind, usize)> = vec![];
+            for (index, action) in item.actions.iter().enumerate().skip(1) {
+                let kind = ActionKind::of(&action.action);
+                if !actions.iter().any(|(seen, _)| *seen == kind) {
+                    actions.push((kind, index));
+                }
+            }
+            Some(KrunnerMatch {
+                id: format!("{}:{}", generation, id),
+                text: item.title.clone(),
+                icon_name,
+                match_type,
+                relevance: 1.0 - position as f64 / count,
+                subtext: item.description.clone(),
+                actions,
+            })
+        })
+        .collect()
+}
+
+/// The search generation and match id of a KRunner match id.
+pub fn parse_match_id(id: &str) -> Option<(usize, usize)> {
+    let (generation, match_id) = id.split_once(':')?;
+    Some((generation.parse().ok()?, match_id.parse().ok()?))
+}
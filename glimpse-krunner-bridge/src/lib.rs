@@ -0,0 +1,3 @@
+//! Shows glimpse results in KRunner through its D-Bus runner interface.
+
+pub mod krunner;
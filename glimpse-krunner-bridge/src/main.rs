@@ -0,0 +1,142 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use glimpse_client::Client;
+use glimpse_krunner_bridge::krunner::{self, KrunnerMatch, PATH, SERVICE};
+use glimpse_sdk::Modifiers;
+use tokio::signal::unix::{SignalKind, signal};
+use zbus::{
+    fdo,
+    zvariant::{OwnedValue, Value},
+};
+
+/// A match as KRunner reads it, signature `(sssida{sv})`.
+type RemoteMatch = (
+    String,
+    String,
+    String,
+    i32,
+    f64,
+    HashMap<String, OwnedValue>,
+);
+
+/// `org.kde.krunner1`, answering KRunner queries with glimpse searches. Matches of the
+/// latest search are kept to resolve the actions KRunner runs.
+struct Runner {
+    client: Client,
+    latest: Mutex<Vec<KrunnerMatch>>,
+}
+
+#[zbus::interface(name = "org.kde.krunner1")]
+impl Runner {
+    async fn actions(&self) -> Vec<(String, String, String)> {
+        krunner::actions()
+    }
+
+    #[zbus(name = "Match")]
+    async fn find(&self, query: String) -> fdo::Result<Vec<RemoteMatch>> {
+        let search = self.client.search(&query).await.map_err(failed)?;
+        // a newer query supersedes the search, KRunner no longer waits for it
+        let Ok(results) = search.collect().await else {
+            return Ok(vec![]);
+        };
+        for error in &results.errors {
+            tracing::debug!("search error: {}", error);
+        }
+
+        let matches = krunner::translate(&query, results.generation, &results.matches);
+        let remote = matches.iter().map(remote_match).collect();
+        *self.latest.lock().unwrap() = matches;
+        Ok(remote)
+    }
+
+    /// Actions that need confirmation are refused by the daemon, KRunner cannot ask for it.
+    async fn run(&self, match_id: String, action_id: String) -> fdo::Result<()> {
+        let (generation, id) = krunner::parse_match_id(&match_id)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("unknown match: {}", match_id)))?;
+        let action = match action_id.is_empty() {
+            true => Some(0),
+            false => self
+                .latest
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|item| item.id == match_id)
+                .and_then(|item| item.action_index(&action_id)),
+        }
+        .ok_or_else(|| fdo::Error::InvalidArgs(format!("unknown action: {}", action_id)))?;
+
+        self.client
+            .activate(generation, id, action, Modifiers::default())
+            .await
+            .map_err(failed)
+    }
+
+    /// KRunner closed, the running search is of no use anymore.
+    async fn teardown(&self) {
+        if let Err(e) = self.client.cancel().await {
+            tracing::debug!("failed to cancel the search: {}", e);
+        }
+        self.latest.lock().unwrap().clear();
+    }
+}
+
+fn remote_match(item: &KrunnerMatch) -> RemoteMatch {
+    let actions = item
+        .actions
+        .iter()
+        .map(|(kind, _)| kind.id().to_string())
+        .collect::<Vec<_>>();
+    // without the list KRunner would offer every action on every match
+    let properties = HashMap::from([
+        ("subtext".to_string(), property(item.subtext.clone())),
+        ("actions".to_string(), property(actions)),
+    ]);
+    (
+        item.id.clone(),
+        item.text.clone(),
+        item.icon_name.clone(),
+        item.match_type,
+        item.relevance,
+        properties,
+    )
+}
+
+fn property<'a>(value: impl Into<Value<'a>>) -> OwnedValue {
+    value
+        .into()
+        .try_into()
+        .expect("match properties hold no file descriptors")
+}
+
+fn failed(err: glimpse_client::ClientError) -> fdo::Error {
+    fdo::Error::Failed(err.to_string())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(tracing::Level::INFO)
+        .init();
+
+    let daemon_binary =
+        std::env::var("GLIMPSED_BIN").unwrap_or_else(|_| "/usr/bin/glimpsed".to_string());
+    let client = Client::connect_or_spawn(daemon_binary).await?;
+    let runner = Runner {
+        client,
+        latest: Mutex::new(vec![]),
+    };
+    let _connection = zbus::connection::Builder::session()?
+        .name(SERVICE)?
+        .serve_at(PATH, runner)?
+        .build()
+        .await?;
+    tracing::info!("serving KRunner as {} at {}", SERVICE, PATH);
+
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+    Ok(())
+}
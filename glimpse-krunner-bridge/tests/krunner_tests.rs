@@ -0,0 +1,120 @@
+use glimpse_krunner_bridge::krunner::{
+    ActionKind, EXACT_MATCH, POSSIBLE_MATCH, actions, parse_match_id, translate,
+};
+use glimpse_sdk::{Action, Icon, Match, MatchAction};
+
+fn create_action(title: &str, action: Action) -> MatchAction {
+    MatchAction {
+        title: title.to_string(),
+        action,
+        close_on_action: true,
+        alternates: vec![],
+        requires_confirmation: false,
+        confirmation_prompt: None,
+        expand: vec![],
+    }
+}
+
+fn create_match(id: usize, title: &str) -> Match {
+    Match {
+        id: Some(id),
+        title: title.to_string(),
+        description: format!("{} description", title),
+        ..Default::default()
+    }
+}
+
+fn open(uri: &str) -> Action {
+    Action::Open {
+        uri: uri.to_string(),
+    }
+}
+
+#[test]
+fn test_matches_keep_the_daemon_order() {
+    let matches = vec![create_match(4, "a"), create_match(2, "b")];
+    let translated = translate("x", 9, &matches);
+
+    assert_eq!(translated.len(), 2);
+    assert_eq!(translated[0].id, "9:4");
+    assert_eq!(translated[0].text, "a");
+    assert_eq!(translated[0].subtext, "a description");
+    assert_eq!(translated[0].relevance, 1.0);
+    assert_eq!(translated[1].id, "9:2");
+    assert_eq!(translated[1].relevance, 0.5);
+}
+
+#[test]
+fn test_title_equal_to_the_query_is_an_exact_match() {
+    let matches = vec![
+        create_match(1, "Firefox"),
+        create_match(2, "Firefox Nightly"),
+    ];
+    let translated = translate(" firefox", 1, &matches);
+
+    assert_eq!(translated[0].match_type, EXACT_MATCH);
+    assert_eq!(translated[1].match_type, POSSIBLE_MATCH);
+}
+
+#[test]
+fn test_only_freedesktop_icons_are_named() {
+    let mut named = create_match(1, "a");
+    named.icon = Some(Icon::freedesktop("firefox"));
+    let mut path = create_match(2, "b");
+    path.icon = Some(Icon::path("/tmp/b.png"));
+    let translated = translate("", 1, &[named, path, create_match(3, "c")]);
+
+    assert_eq!(translated[0].icon_name, "firefox");
+    assert_eq!(translated[1].icon_name, "");
+    assert_eq!(translated[2].icon_name, "");
+}
+
+#[test]
+fn test_matches_without_an_id_are_left_out() {
+    let matches = vec![Match::default(), create_match(1, "a")];
+
+    assert_eq!(translate("", 1, &matches).len(), 1);
+}
+
+#[test]
+fn test_secondary_actions_are_offered_by_kind() {
+    let mut item = create_match(1, "notes.txt");
+    item.actions = vec![
+        create_action("Open", open("file:///tmp/notes.txt")),
+        create_action("Open folder", open("file:///tmp")),
+        create_action(
+            "Copy path",
+            Action::Clipboard {
+                text: "/tmp/notes.txt".into(),
+            },
+        ),
+        create_action("Open with", open("file:///tmp/notes.txt")),
+    ];
+    let translated = translate("", 1, &[item]).remove(0);
+
+    assert_eq!(
+        translated.actions,
+        vec![(ActionKind::Open, 1), (ActionKind::Copy, 2)]
+    );
+    assert_eq!(translated.action_index(""), Some(0));
+    assert_eq!(translated.action_index("open"), Some(1));
+    assert_eq!(translated.action_index("copy"), Some(2));
+    assert_eq!(translated.action_index("run"), None);
+}
+
+#[test]
+fn test_every_action_kind_is_listed() {
+    let ids = actions()
+        .into_iter()
+        .map(|(id, _, _)| id)
+        .collect::<Vec<_>>();
+
+    assert_eq!(ids, vec!["run", "open", "copy", "other"]);
+}
+
+#[test]
+fn test_parse_match_id() {
+    assert_eq!(parse_match_id("9:4"), Some((9, 4)));
+    assert_eq!(parse_match_id("9"), None);
+    assert_eq!(parse_match_id("a:4"), None);
+}
@@ -0,0 +1,184 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufReader},
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt},
+    path::{Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+use ignore::WalkBuilder;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveKind {
+    pub fn of(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// A file stored in an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    /// Path inside the archive.
+    pub name: String,
+    pub size: u64,
+}
+
+impl Member {
+    pub fn file_name(&self) -> &str {
+        self.name.rsplit('/').next().unwrap_or(&self.name)
+    }
+
+    /// Whether the path contains every word of `query`, ignoring case.
+    pub fn matches(&self, query: &str) -> bool {
+        contains_words(&self.name, query)
+    }
+}
+
+pub fn contains_words(text: &str, query: &str) -> bool {
+    let text = text.to_lowercase();
+    query
+        .split_whitespace()
+        .all(|word| text.contains(&word.to_lowercase()))
+}
+
+/// Archives under `roots`, at most `max_depth` levels down. Hidden and ignored entries are
+/// skipped like in the files plugin.
+pub fn find_archives(roots: &[PathBuf], max_depth: usize) -> Vec<PathBuf> {
+    let mut archives = vec![];
+    for root in roots {
+        let walker = WalkBuilder::new(root).max_depth(Some(max_depth)).build();
+        for entry in walker.flatten() {
+            let is_file = entry.file_type().is_some_and(|kind| kind.is_file());
+            if is_file && ArchiveKind::of(entry.path()).is_some() {
+                archives.push(entry.into_path());
+            }
+        }
+    }
+    archives
+}
+
+/// Files in the archive, directories left out.
+pub fn list(path: &Path) -> io::Result<Vec<Member>> {
+    let kind = ArchiveKind::of(path).ok_or_else(|| io::Error::other("not an archive"))?;
+    let file = BufReader::new(File::open(path)?);
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(file).map_err(io::Error::other)?;
+            let mut members = Vec::with_capacity(archive.len());
+            for index in 0..archive.len() {
+                let entry = archive.by_index_raw(index).map_err(io::Error::other)?;
+                if entry.is_file() {
+                    members.push(Member {
+                        name: entry.name().to_string(),
+                        size: entry.size(),
+                    });
+                }
+            }
+            Ok(members)
+        }
+        ArchiveKind::Tar => list_tar(tar::Archive::new(file)),
+        ArchiveKind::TarGz => list_tar(tar::Archive::new(GzDecoder::new(file))),
+    }
+}
+
+fn list_tar<R: io::Read>(mut archive: tar::Archive<R>) -> io::Result<Vec<Member>> {
+    let mut members = vec![];
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if entry.header().entry_type().is_file() {
+            members.push(Member {
+                name: entry.path()?.to_string_lossy().to_string(),
+                size: entry.size(),
+            });
+        }
+    }
+    Ok(members)
+}
+
+/// Copy `member` of the archive at `path` into `dest`, a directory only the user can read,
+/// which must not be shared with other users. Only the file name of the member is kept, so
+/// no entry lands outside `dest`, and links found in its place are replaced, not followed.
+pub fn extract(path: &Path, member: &str, dest: &Path) -> io::Result<PathBuf> {
+    let kind = ArchiveKind::of(path).ok_or_else(|| io::Error::other("not an archive"))?;
+    let file_name = Path::new(member)
+        .file_name()
+        .ok_or_else(|| io::Error::other(format!("invalid member {:?}", member)))?;
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dest)?;
+    if !std::fs::symlink_metadata(dest)?.is_dir() {
+        return Err(io::Error::other(format!(
+            "{} is not a directory",
+            dest.display()
+        )));
+    }
+    std::fs::set_permissions(dest, std::fs::Permissions::from_mode(0o700))?;
+    let target = dest.join(file_name);
+    // extracted before, or planted
+    match std::fs::remove_file(&target) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+
+    let copied = copy_member(path, kind, member, &target);
+    if copied.is_err() {
+        let _ = std::fs::remove_file(&target);
+    }
+    copied.map(|_| target)
+}
+
+fn copy_member(path: &Path, kind: ArchiveKind, member: &str, target: &Path) -> io::Result<()> {
+    let file = BufReader::new(File::open(path)?);
+    let mut output = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(target)?;
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(file).map_err(io::Error::other)?;
+            let mut entry = archive.by_name(member).map_err(io::Error::other)?;
+            io::copy(&mut entry, &mut output)?;
+        }
+        ArchiveKind::Tar => extract_tar(tar::Archive::new(file), member, &mut output)?,
+        ArchiveKind::TarGz => {
+            extract_tar(tar::Archive::new(GzDecoder::new(file)), member, &mut output)?
+        }
+    }
+    Ok(())
+}
+
+fn extract_tar<R: io::Read>(
+    mut archive: tar::Archive<R>,
+    member: &str,
+    output: &mut File,
+) -> io::Result<()> {
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.header().entry_type().is_file() && entry.path()?.to_string_lossy() == member {
+            io::copy(&mut entry, output)?;
+            return Ok(());
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} is not in the archive", member),
+    ))
+}
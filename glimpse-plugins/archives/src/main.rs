@@ -0,0 +1,302 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+use glimpse_plugins_archives::archive::{self, Member};
+use glimpse_sdk::{
+    Action, ConfigField, ConfigKind, ConfigSchema, Match, MatchAction, Metadata, Permission,
+    Plugin, PluginError, SearchSink, Settings, run_plugin, setup_logging,
+};
+use serde::Deserialize;
+
+const DEFAULT_MAX_RESULTS: usize = 30;
+const DEFAULT_MAX_DEPTH: usize = 3;
+const DEFAULT_ROOTS: &[&str] = &["~/Downloads", "~/Documents"];
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+struct ArchivesSettings {
+    max_results: usize,
+    max_depth: usize,
+    roots: Vec<String>,
+}
+
+impl Default for ArchivesSettings {
+    fn default() -> Self {
+        Self {
+            max_results: DEFAULT_MAX_RESULTS,
+            max_depth: DEFAULT_MAX_DEPTH,
+            roots: DEFAULT_ROOTS.iter().map(|root| root.to_string()).collect(),
+        }
+    }
+}
+
+impl ArchivesSettings {
+    /// Roots as absolute paths; `~` and relative roots resolve against the home directory.
+    fn root_paths(&self, home: &Path) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = vec![];
+        for root in &self.roots {
+            let path = match root.strip_prefix('~') {
+                Some(rest) => home.join(rest.trim_start_matches('/')),
+                None => home.join(root),
+            };
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        paths
+    }
+}
+
+/// Members of an archive, valid while the archive keeps its modification time.
+type Listing = (SystemTime, Arc<Vec<Member>>);
+
+struct ArchivesPlugin {
+    home: PathBuf,
+    listings: Arc<Mutex<HashMap<PathBuf, Listing>>>,
+    settings: Settings<ArchivesSettings>,
+}
+
+impl ArchivesPlugin {
+    fn new(home: PathBuf) -> Self {
+        Self {
+            home,
+            listings: Arc::new(Mutex::new(HashMap::new())),
+            settings: Settings::default(),
+        }
+    }
+
+    /// Members of the archive at `path`, read again only when the archive changed.
+    async fn members(&self, path: &Path) -> Option<Arc<Vec<Member>>> {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+        if let Some((listed_at, members)) = self.listings.lock().unwrap().get(path)
+            && *listed_at == modified
+        {
+            return Some(members.clone());
+        }
+
+        let list_path = path.to_path_buf();
+        let members = match tokio::task::spawn_blocking(move || archive::list(&list_path)).await {
+            Ok(Ok(members)) => Arc::new(members),
+            Ok(Err(err)) => {
+                tracing::debug!("failed to list {}: {}", path.display(), err);
+                Arc::new(vec![])
+            }
+            Err(_) => return None,
+        };
+        self.listings
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), (modified, members.clone()));
+        Some(members)
+    }
+
+    fn display_path(&self, path: &Path) -> String {
+        match path.strip_prefix(&self.home) {
+            Ok(rel) => format!("~/{}", rel.display()),
+            Err(_) => path.display().to_string(),
+        }
+    }
+
+    fn to_match(&self, archive_path: &Path, member: &Member, query: &str) -> Match {
+        let archive_path = archive_path.to_string_lossy().to_string();
+        let params = HashMap::from([
+            ("archive".to_string(), archive_path.clone()),
+            ("member".to_string(), member.name.clone()),
+        ]);
+        // matches on the file name itself beat matches on its directories
+        let score = match archive::contains_words(member.file_name(), query) {
+            true => 1.0,
+            false => 0.5,
+        };
+
+        Match {
+            title: member.file_name().to_string(),
+            description: format!(
+                "{} in {}",
+                member.name,
+                self.display_path(Path::new(&archive_path))
+            ),
+            actions: vec![
+                MatchAction {
+                    title: "Extract and open".to_string(),
+                    close_on_action: true,
+                    action: Action::Callback {
+                        key: "open".to_string(),
+                        params,
+                    },
+                    alternates: vec![],
+                    requires_confirmation: false,
+                    confirmation_prompt: None,
+                    expand: vec![],
+                },
+                MatchAction {
+                    title: "Open archive".to_string(),
+                    close_on_action: true,
+                    action: Action::open_file(&archive_path),
+                    alternates: vec![],
+                    requires_confirmation: false,
+                    confirmation_prompt: None,
+                    expand: vec![],
+                },
+            ],
+            score,
+            ..Default::default()
+        }
+    }
+
+    /// Look through the archives one by one, sending each archive's matches to `sink` as soon
+    /// as it is read, or collecting them all without one.
+    async fn scan(
+        &self,
+        query: &str,
+        sink: Option<&SearchSink>,
+    ) -> Result<Vec<Match>, PluginError> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(vec![]);
+        }
+        let settings = self.settings.get();
+        let roots = settings.root_paths(&self.home);
+        let archives =
+            tokio::task::spawn_blocking(move || archive::find_archives(&roots, settings.max_depth))
+                .await
+                .map_err(|e| PluginError::Other(e.to_string()))?;
+
+        let mut collected = vec![];
+        let mut remaining = settings.max_results;
+        for path in archives {
+            if remaining == 0 {
+                break;
+            }
+            let Some(members) = self.members(&path).await else {
+                continue;
+            };
+            let found = members
+                .iter()
+                .filter(|member| member.matches(query))
+                .take(remaining)
+                .map(|member| self.to_match(&path, member, query))
+                .collect::<Vec<_>>();
+            remaining -= found.len();
+            match sink {
+                Some(sink) => sink.send(found).await?,
+                None => collected.extend(found),
+            }
+        }
+        Ok(collected)
+    }
+
+    /// Extract into a directory per archive member, so files of the same name do not clash.
+    /// The user's cache, unlike the temporary directory, is not shared with other users.
+    fn extract_dir(archive_path: &str, member: &str) -> Option<PathBuf> {
+        let mut hasher = DefaultHasher::new();
+        (archive_path, member).hash(&mut hasher);
+        Some(
+            dirs::cache_dir()?
+                .join("glimpse")
+                .join("archives")
+                .join(format!("{:016x}", hasher.finish())),
+        )
+    }
+}
+
+#[async_trait]
+impl Plugin for ArchivesPlugin {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            id: "me.aresa.glimpse.archives".to_string(),
+            name: "Archives".to_string(),
+            version: "0.1.0".to_string(),
+            description: "Finds files inside zip and tar archives.".to_string(),
+            author: "Alex Oleshkevich <alex.oleshkevich@gmail.com>".to_string(),
+            permissions: vec![Permission::HomeRead],
+            prefix: Some("zip ".to_string()),
+            prefix_only: true,
+            config_schema: Some(
+                ConfigSchema::new()
+                    .field(
+                        ConfigField::new("max_results", ConfigKind::Integer)
+                            .default_value(DEFAULT_MAX_RESULTS)
+                            .description("Maximum number of archive members returned per search"),
+                    )
+                    .field(
+                        ConfigField::new("max_depth", ConfigKind::Integer)
+                            .default_value(DEFAULT_MAX_DEPTH)
+                            .description("How deep to look for archives below each root"),
+                    )
+                    .field(
+                        ConfigField::new("roots", ConfigKind::StringList)
+                            .default_value(DEFAULT_ROOTS.to_vec())
+                            .description(
+                                "Directories with archives, relative to the home directory",
+                            ),
+                    ),
+            ),
+            ..Default::default()
+        }
+    }
+
+    async fn configure(&self, config: serde_json::Value) -> Result<(), PluginError> {
+        self.settings.apply(config)
+    }
+
+    async fn search(&self, query: String, sink: &SearchSink) -> Result<(), PluginError> {
+        self.scan(&query, Some(sink)).await.map(|_| ())
+    }
+
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        self.scan(&query, None).await
+    }
+
+    async fn handle_action(&self, action: String, params: HashMap<String, String>) {
+        let (Some(archive_path), Some(member)) = (params.get("archive"), params.get("member"))
+        else {
+            tracing::warn!("unhandled action: {} {:?}", action, params);
+            return;
+        };
+        if action != "open" {
+            tracing::warn!("unhandled action: {} {:?}", action, params);
+            return;
+        }
+
+        let Some(dest) = Self::extract_dir(archive_path, member) else {
+            tracing::error!("cannot determine the cache directory to extract into");
+            return;
+        };
+        let (archive_path, member) = (PathBuf::from(archive_path), member.clone());
+        let extracted =
+            tokio::task::spawn_blocking(move || archive::extract(&archive_path, &member, &dest))
+                .await
+                .map_err(std::io::Error::other)
+                .and_then(|extracted| extracted);
+        let path = match extracted {
+            Ok(path) => path,
+            Err(err) => {
+                tracing::error!("failed to extract: {}", err);
+                return;
+            }
+        };
+        if let Err(err) = tokio::process::Command::new("xdg-open").arg(&path).spawn() {
+            tracing::error!("failed to open {}: {}", path.display(), err);
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    setup_logging(tracing::Level::INFO);
+    let Some(home) = dirs::home_dir() else {
+        return Err("cannot determine the home directory".into());
+    };
+    if let Err(err) = run_plugin(ArchivesPlugin::new(home)).await {
+        tracing::error!("error running plugin: {}", err);
+    }
+    Ok(())
+}
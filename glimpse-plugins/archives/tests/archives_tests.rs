@@ -0,0 +1,142 @@
+use std::{io::Write, os::unix::fs::PermissionsExt, path::Path};
+
+use flate2::{Compression, write::GzEncoder};
+use glimpse_plugins_archives::archive::{self, ArchiveKind, Member};
+
+fn create_zip(path: &Path) {
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    let options = zip::write::SimpleFileOptions::default();
+    writer.add_directory("reports/", options).unwrap();
+    writer.start_file("reports/q3-report.txt", options).unwrap();
+    writer.write_all(b"revenue").unwrap();
+    writer.start_file("notes.md", options).unwrap();
+    writer.write_all(b"# notes").unwrap();
+    writer.finish().unwrap();
+}
+
+fn create_tar_gz(path: &Path) {
+    let encoder = GzEncoder::new(std::fs::File::create(path).unwrap(), Compression::fast());
+    let mut builder = tar::Builder::new(encoder);
+    for (name, data) in [("src/main.rs", "fn main() {}"), ("Report.pdf", "%PDF")] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, name, data.as_bytes())
+            .unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap();
+}
+
+fn names(members: &[Member]) -> Vec<&str> {
+    members.iter().map(|member| member.name.as_str()).collect()
+}
+
+#[test]
+fn test_kind_from_file_name() {
+    assert_eq!(ArchiveKind::of(Path::new("a.ZIP")), Some(ArchiveKind::Zip));
+    assert_eq!(
+        ArchiveKind::of(Path::new("a.tar.gz")),
+        Some(ArchiveKind::TarGz)
+    );
+    assert_eq!(
+        ArchiveKind::of(Path::new("a.tgz")),
+        Some(ArchiveKind::TarGz)
+    );
+    assert_eq!(ArchiveKind::of(Path::new("a.tar")), Some(ArchiveKind::Tar));
+    assert_eq!(ArchiveKind::of(Path::new("a.gz")), None);
+}
+
+#[test]
+fn test_list_skips_directories() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip = dir.path().join("docs.zip");
+    let tar = dir.path().join("code.tar.gz");
+    create_zip(&zip);
+    create_tar_gz(&tar);
+
+    let zip_members = archive::list(&zip).unwrap();
+    assert_eq!(
+        names(&zip_members),
+        vec!["reports/q3-report.txt", "notes.md"]
+    );
+    assert_eq!(zip_members[0].file_name(), "q3-report.txt");
+    assert_eq!(zip_members[0].size, 7);
+    assert_eq!(
+        names(&archive::list(&tar).unwrap()),
+        vec!["src/main.rs", "Report.pdf"]
+    );
+}
+
+#[test]
+fn test_member_matches_all_words() {
+    let member = Member {
+        name: "reports/Q3-Report.txt".to_string(),
+        size: 0,
+    };
+    assert!(member.matches("report q3"));
+    assert!(member.matches("reports txt"));
+    assert!(!member.matches("q4"));
+}
+
+#[test]
+fn test_find_archives_respects_depth() {
+    let dir = tempfile::tempdir().unwrap();
+    let nested = dir.path().join("a").join("b");
+    std::fs::create_dir_all(&nested).unwrap();
+    create_zip(&dir.path().join("top.zip"));
+    create_zip(&nested.join("deep.zip"));
+    std::fs::write(dir.path().join("readme.txt"), "").unwrap();
+
+    let found = archive::find_archives(&[dir.path().to_path_buf()], 1);
+    assert_eq!(found, vec![dir.path().join("top.zip")]);
+    assert_eq!(
+        archive::find_archives(&[dir.path().to_path_buf()], 3).len(),
+        2
+    );
+}
+
+#[test]
+fn test_extract_single_member() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip = dir.path().join("docs.zip");
+    let tar = dir.path().join("code.tar.gz");
+    create_zip(&zip);
+    create_tar_gz(&tar);
+    let dest = dir.path().join("out");
+
+    let extracted = archive::extract(&zip, "reports/q3-report.txt", &dest).unwrap();
+    assert_eq!(extracted, dest.join("q3-report.txt"));
+    assert_eq!(std::fs::read_to_string(&extracted).unwrap(), "revenue");
+    let mode = std::fs::metadata(&dest).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o700);
+
+    let extracted = archive::extract(&tar, "Report.pdf", &dest).unwrap();
+    assert_eq!(std::fs::read_to_string(extracted).unwrap(), "%PDF");
+
+    assert!(archive::extract(&tar, "missing.txt", &dest).is_err());
+    assert!(!dest.join("missing.txt").exists());
+}
+
+#[test]
+fn test_extract_does_not_follow_links() {
+    let dir = tempfile::tempdir().unwrap();
+    let zip = dir.path().join("docs.zip");
+    create_zip(&zip);
+    let victim = dir.path().join("victim.txt");
+    std::fs::write(&victim, "precious").unwrap();
+
+    let dest = dir.path().join("out");
+    std::fs::create_dir(&dest).unwrap();
+    std::os::unix::fs::symlink(&victim, dest.join("q3-report.txt")).unwrap();
+    let extracted = archive::extract(&zip, "reports/q3-report.txt", &dest).unwrap();
+    assert!(!extracted.symlink_metadata().unwrap().is_symlink());
+    assert_eq!(std::fs::read_to_string(&extracted).unwrap(), "revenue");
+    assert_eq!(std::fs::read_to_string(&victim).unwrap(), "precious");
+
+    // a linked directory is refused
+    let linked = dir.path().join("linked");
+    std::os::unix::fs::symlink(dir.path(), &linked).unwrap();
+    assert!(archive::extract(&zip, "reports/q3-report.txt", &linked).is_err());
+}
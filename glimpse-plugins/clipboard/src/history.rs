@@ -0,0 +1,154 @@
+use std::{collections::VecDeque, io, os::unix::fs::PermissionsExt, path::Path};
+
+use glimpse_sdk::Detail;
+use serde::{Deserialize, Serialize};
+
+/// Longer titles are cut, the full text is still copied.
+const TITLE_CHARS: usize = 80;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub id: u64,
+    pub text: String,
+    /// Unix timestamp of the latest copy, in seconds.
+    pub copied_at: u64,
+}
+
+impl Entry {
+    /// First non-blank line, shortened to fit a result row.
+    pub fn title(&self) -> String {
+        let line = self
+            .text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or_default();
+        match line.char_indices().nth(TITLE_CHARS) {
+            Some((end, _)) => format!("{}…", &line[..end]),
+            None => line.to_string(),
+        }
+    }
+
+    pub fn describe(&self, now: u64) -> String {
+        let lines = self.text.lines().count();
+        let age = age(now.saturating_sub(self.copied_at));
+        match lines {
+            0 | 1 => format!("copied {}", age),
+            lines => format!("{} lines, copied {}", lines, age),
+        }
+    }
+
+    /// The whole text, for entries the title does not show in full.
+    pub fn detail(&self) -> Option<Detail> {
+        (self.title() != self.text).then(|| Detail::text(self.text.clone()))
+    }
+}
+
+fn age(secs: u64) -> String {
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{} min ago", secs / 60),
+        3600..86400 => format!("{} h ago", secs / 3600),
+        _ => format!("{} days ago", secs / 86400),
+    }
+}
+
+/// Recently copied texts, newest first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ClipboardHistory {
+    entries: VecDeque<Entry>,
+    last_id: u64,
+}
+
+impl ClipboardHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let data = std::fs::read(path)?;
+        serde_json::from_slice(&data).map_err(io::Error::other)
+    }
+
+    /// Write the history atomically, readable by the owner only since it may hold secrets.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(self).map_err(io::Error::other)?)?;
+        std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(tmp, path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter()
+    }
+
+    /// Remember a copied text, keeping at most `capacity` entries. Copying a known text again
+    /// moves it to the top. Returns false if nothing changed.
+    pub fn record(&mut self, text: &str, now: u64, capacity: usize) -> bool {
+        if text.trim().is_empty() {
+            return false;
+        }
+        if self.entries.front().is_some_and(|entry| entry.text == text) {
+            return false;
+        }
+
+        let entry = match self.entries.iter().position(|entry| entry.text == text) {
+            Some(position) => {
+                let mut entry = self.entries.remove(position).unwrap();
+                entry.copied_at = now;
+                entry
+            }
+            None => {
+                self.last_id += 1;
+                Entry {
+                    id: self.last_id,
+                    text: text.to_string(),
+                    copied_at: now,
+                }
+            }
+        };
+        self.entries.push_front(entry);
+        self.entries.truncate(capacity);
+        true
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<Entry> {
+        let position = self.entries.iter().position(|entry| entry.id == id)?;
+        self.entries.remove(position)
+    }
+
+    /// Drop the oldest entries beyond `capacity`, e.g. after the setting was lowered.
+    pub fn truncate(&mut self, capacity: usize) -> bool {
+        let before = self.entries.len();
+        self.entries.truncate(capacity);
+        self.entries.len() != before
+    }
+
+    /// Entries containing every word of `query`, ignoring case, newest first.
+    /// An empty query lists the most recent entries.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<&Entry> {
+        let words = query
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+        self.entries
+            .iter()
+            .filter(|entry| {
+                let text = entry.text.to_lowercase();
+                words.iter().all(|word| text.contains(word))
+            })
+            .take(limit)
+            .collect()
+    }
+}
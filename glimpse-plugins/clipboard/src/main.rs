@@ -0,0 +1,362 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    path::PathBuf,
+    process::Stdio,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use glimpse_plugins_clipboard::history::{ClipboardHistory, Entry};
+use glimpse_sdk::{
+    Action, ConfigField, ConfigKind, ConfigSchema, Context, Icon, Match, MatchAction, Metadata,
+    PaletteCommand, Permission, Plugin, PluginError, PowerProfile, Settings, run_plugin,
+    setup_logging,
+};
+use serde::Deserialize;
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    process::Command,
+    sync::watch,
+};
+
+const DEFAULT_MAX_ENTRIES: usize = 100;
+const DEFAULT_POLL_MS: u64 = 1000;
+const MAX_RESULTS: usize = 50;
+/// Larger copies, like whole files, are not worth keeping.
+const MAX_TEXT_BYTES: usize = 1024 * 1024;
+/// Set by password managers on copied secrets.
+const PASSWORD_HINT: &str = "x-kde-passwordManagerHint";
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+struct ClipboardSettings {
+    max_entries: usize,
+    poll_ms: u64,
+}
+
+impl Default for ClipboardSettings {
+    fn default() -> Self {
+        Self {
+            max_entries: DEFAULT_MAX_ENTRIES,
+            poll_ms: DEFAULT_POLL_MS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    /// wl-clipboard tells about every change.
+    Wayland,
+    /// xclip has no change notification, the clipboard is polled.
+    X11,
+}
+
+impl Backend {
+    fn detect() -> Option<Self> {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            Some(Backend::Wayland)
+        } else if std::env::var_os("DISPLAY").is_some() {
+            Some(Backend::X11)
+        } else {
+            None
+        }
+    }
+
+    /// Current clipboard text, if any.
+    async fn read(self) -> Option<String> {
+        let output = match self {
+            Backend::Wayland => {
+                let types = run("wl-paste", &["--list-types"]).await?;
+                if types.lines().any(|line| line == PASSWORD_HINT) {
+                    return None;
+                }
+                run("wl-paste", &["--no-newline", "--type", "text"]).await?
+            }
+            Backend::X11 => run("xclip", &["-selection", "clipboard", "-o"]).await?,
+        };
+        Some(output)
+    }
+}
+
+async fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .inspect_err(|err| tracing::debug!("failed to run {}: {}", program, err))
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// Keeps the stored history in sync with the clipboard.
+struct Recorder {
+    history: Arc<Mutex<ClipboardHistory>>,
+    settings: Settings<ClipboardSettings>,
+    path: PathBuf,
+    /// Last text seen, so an unchanged clipboard does not bring back a deleted entry.
+    last: Option<String>,
+}
+
+impl Recorder {
+    async fn seen(&mut self, text: String) {
+        if self.last.as_ref() == Some(&text) {
+            return;
+        }
+        self.last = Some(text.clone());
+        if text.len() > MAX_TEXT_BYTES {
+            return;
+        }
+
+        let capacity = self.settings.get().max_entries;
+        let recorded = self.history.lock().unwrap().record(&text, now(), capacity);
+        if recorded {
+            save(self.history.clone(), self.path.clone()).await;
+        }
+    }
+
+    async fn watch(mut self, backend: Backend, mut power: watch::Receiver<PowerProfile>) {
+        match backend {
+            Backend::Wayland => {
+                let child = Command::new("wl-paste")
+                    .args(["--watch", "echo"])
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::piped())
+                    .kill_on_drop(true)
+                    .spawn();
+                let mut child = match child {
+                    Ok(child) => child,
+                    Err(err) => {
+                        tracing::error!("failed to watch the clipboard: {}", err);
+                        return;
+                    }
+                };
+                let Some(stdout) = child.stdout.take() else {
+                    return;
+                };
+                let mut changes = BufReader::new(stdout).lines();
+                while let Ok(Some(_)) = changes.next_line().await {
+                    if let Some(text) = backend.read().await {
+                        self.seen(text).await;
+                    }
+                }
+                tracing::warn!("clipboard watcher exited");
+            }
+            Backend::X11 => loop {
+                // poll less often on a low battery
+                let mut interval = Duration::from_millis(self.settings.get().poll_ms.max(100));
+                if *power.borrow_and_update() == PowerProfile::LowPower {
+                    interval *= 4;
+                }
+                tokio::time::sleep(interval).await;
+                if let Some(text) = backend.read().await {
+                    self.seen(text).await;
+                }
+            },
+        }
+    }
+}
+
+async fn save(history: Arc<Mutex<ClipboardHistory>>, path: PathBuf) {
+    let snapshot = history.lock().unwrap().clone();
+    let saved = tokio::task::spawn_blocking(move || snapshot.save(&path)).await;
+    if let Ok(Err(err)) = saved {
+        tracing::warn!("failed to save clipboard history: {}", err);
+    }
+}
+
+struct ClipboardPlugin {
+    path: PathBuf,
+    history: Arc<Mutex<ClipboardHistory>>,
+    settings: Settings<ClipboardSettings>,
+    power: watch::Sender<PowerProfile>,
+}
+
+impl ClipboardPlugin {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            history: Arc::new(Mutex::new(ClipboardHistory::new())),
+            settings: Settings::default(),
+            power: watch::Sender::new(PowerProfile::Normal),
+        }
+    }
+
+    fn to_match(entry: &Entry, position: usize, total: usize, now: u64) -> Match {
+        Match {
+            title: entry.title(),
+            description: entry.describe(now),
+            actions: vec![
+                MatchAction {
+                    title: "Copy".to_string(),
+                    close_on_action: true,
+                    action: Action::Clipboard {
+                        text: entry.text.clone().into(),
+                    },
+                    alternates: vec![],
+                    requires_confirmation: false,
+                    confirmation_prompt: None,
+                    expand: vec![],
+                },
+                MatchAction {
+                    title: "Delete from history".to_string(),
+                    close_on_action: true,
+                    action: Action::Callback {
+                        key: "delete".to_string(),
+                        params: HashMap::from([("id".to_string(), entry.id.to_string())]),
+                    },
+                    alternates: vec![],
+                    requires_confirmation: true,
+                    confirmation_prompt: Some(
+                        "Delete this entry from the clipboard history?".to_string(),
+                    ),
+                    expand: vec![],
+                },
+            ],
+            // newest first
+            score: 1.0 - position as f64 / total.max(1) as f64,
+            detail: entry.detail(),
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for ClipboardPlugin {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            id: "me.aresa.glimpse.clipboard".to_string(),
+            name: "Clipboard".to_string(),
+            version: "0.1.0".to_string(),
+            description: "Searches recently copied text.".to_string(),
+            author: "Alex Oleshkevich <alex.oleshkevich@gmail.com>".to_string(),
+            permissions: vec![Permission::Clipboard],
+            prefix: Some("clip ".to_string()),
+            prefix_only: true,
+            sensitive: true,
+            config_schema: Some(
+                ConfigSchema::new()
+                    .field(
+                        ConfigField::new("max_entries", ConfigKind::Integer)
+                            .default_value(DEFAULT_MAX_ENTRIES)
+                            .description("Number of copied texts to remember"),
+                    )
+                    .field(
+                        ConfigField::new("poll_ms", ConfigKind::Integer)
+                            .default_value(DEFAULT_POLL_MS)
+                            .description("How often to check the clipboard on X11, in ms"),
+                    ),
+            ),
+            commands: vec![
+                PaletteCommand::new("clear", "Clear clipboard history")
+                    .description("Forget every copied text")
+                    .icon(Icon::freedesktop("edit-clear-history")),
+            ],
+            ..Default::default()
+        }
+    }
+
+    async fn configure(&self, config: serde_json::Value) -> Result<(), PluginError> {
+        self.settings.apply(config)
+    }
+
+    async fn power_profile_changed(&self, profile: PowerProfile) -> Result<(), PluginError> {
+        self.power.send_replace(profile);
+        Ok(())
+    }
+
+    async fn initialize(&self, _context: &Context) -> Result<(), PluginError> {
+        match ClipboardHistory::load(&self.path) {
+            Ok(mut loaded) => {
+                loaded.truncate(self.settings.get().max_entries);
+                tracing::info!("loaded {} clipboard entries", loaded.len());
+                *self.history.lock().unwrap() = loaded;
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => tracing::warn!("failed to load clipboard history: {}", err),
+        }
+
+        // forget the oldest entries when the limit is lowered
+        let history = self.history.clone();
+        let path = self.path.clone();
+        self.settings.on_change(move |settings| {
+            if history.lock().unwrap().truncate(settings.max_entries) {
+                tokio::spawn(save(history.clone(), path.clone()));
+            }
+        });
+
+        let Some(backend) = Backend::detect() else {
+            tracing::warn!("no display found, the clipboard is not recorded");
+            return Ok(());
+        };
+        tracing::info!("recording the clipboard with {:?}", backend);
+        let recorder = Recorder {
+            history: self.history.clone(),
+            settings: self.settings.clone(),
+            path: self.path.clone(),
+            last: None,
+        };
+        tokio::spawn(recorder.watch(backend, self.power.subscribe()));
+        Ok(())
+    }
+
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        let history = self.history.lock().unwrap();
+        let now = now();
+        let found = history.search(&query, MAX_RESULTS);
+        let total = found.len();
+        Ok(found
+            .into_iter()
+            .enumerate()
+            .map(|(position, entry)| Self::to_match(entry, position, total, now))
+            .collect())
+    }
+
+    async fn handle_action(&self, action: String, params: HashMap<String, String>) {
+        if action == "clear" {
+            let cleared = self.history.lock().unwrap().truncate(0);
+            if cleared {
+                save(self.history.clone(), self.path.clone()).await;
+            }
+            return;
+        }
+        if action != "delete" {
+            tracing::warn!("unhandled action: {} {:?}", action, params);
+            return;
+        }
+        let Some(id) = params.get("id").and_then(|id| id.parse().ok()) else {
+            tracing::warn!("delete without a valid id: {:?}", params);
+            return;
+        };
+        let removed = self.history.lock().unwrap().remove(id).is_some();
+        if removed {
+            save(self.history.clone(), self.path.clone()).await;
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    setup_logging(tracing::Level::INFO);
+    let Some(data_dir) = dirs::data_dir() else {
+        return Err("cannot determine the data directory".into());
+    };
+    let path = data_dir.join("glimpse").join("clipboard.json");
+    if let Err(err) = run_plugin(ClipboardPlugin::new(path)).await {
+        tracing::error!("error running plugin: {}", err);
+    }
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use glimpse_plugins_clipboard::history::ClipboardHistory;
+use glimpse_sdk::Detail;
+
+fn texts(history: &ClipboardHistory) -> Vec<&str> {
+    history.iter().map(|entry| entry.text.as_str()).collect()
+}
+
+#[test]
+fn test_record_keeps_newest_first_up_to_capacity() {
+    let mut history = ClipboardHistory::new();
+    assert!(history.record("one", 1, 2));
+    assert!(history.record("two", 2, 2));
+    assert!(history.record("three", 3, 2));
+
+    assert_eq!(texts(&history), vec!["three", "two"]);
+}
+
+#[test]
+fn test_record_moves_repeated_copy_to_top() {
+    let mut history = ClipboardHistory::new();
+    history.record("one", 1, 10);
+    history.record("two", 2, 10);
+    let id = history.iter().last().unwrap().id;
+
+    assert!(history.record("one", 5, 10));
+    assert!(!history.record("one", 6, 10));
+    assert!(!history.record("  \n", 7, 10));
+
+    let top = history.iter().next().unwrap();
+    assert_eq!((top.id, top.copied_at), (id, 5));
+    assert_eq!(history.len(), 2);
+}
+
+#[test]
+fn test_remove_and_truncate() {
+    let mut history = ClipboardHistory::new();
+    history.record("one", 1, 10);
+    history.record("two", 2, 10);
+    history.record("three", 3, 10);
+    let id = history.iter().nth(1).unwrap().id;
+
+    assert_eq!(
+        history.remove(id).map(|entry| entry.text),
+        Some("two".into())
+    );
+    assert!(history.remove(id).is_none());
+    assert!(history.truncate(1));
+    assert!(!history.truncate(1));
+    assert_eq!(texts(&history), vec!["three"]);
+}
+
+#[test]
+fn test_search_matches_all_words_ignoring_case() {
+    let mut history = ClipboardHistory::new();
+    history.record("git push origin main", 1, 10);
+    history.record("https://example.com", 2, 10);
+    history.record("Git status", 3, 10);
+
+    let found = |query, limit| -> Vec<String> {
+        history
+            .search(query, limit)
+            .into_iter()
+            .map(|entry| entry.text.clone())
+            .collect()
+    };
+    assert_eq!(found("git", 10), vec!["Git status", "git push origin main"]);
+    assert_eq!(found("main git", 10), vec!["git push origin main"]);
+    assert_eq!(found("", 2), vec!["Git status", "https://example.com"]);
+}
+
+#[test]
+fn test_title_uses_first_line() {
+    let mut history = ClipboardHistory::new();
+    history.record("\n  fn main() {\n}\n", 100, 10);
+    history.record(&"x".repeat(100), 100, 10);
+
+    let entries = history.iter().collect::<Vec<_>>();
+    assert_eq!(entries[0].title().chars().count(), 81);
+    assert_eq!(entries[1].title(), "fn main() {");
+    assert_eq!(entries[1].describe(220), "3 lines, copied 2 min ago");
+}
+
+#[test]
+fn test_detail_shows_text_cut_from_title() {
+    let mut history = ClipboardHistory::new();
+    history.record("git status", 100, 10);
+    history.record("fn main() {\n}", 100, 10);
+    history.record(&"x".repeat(100), 100, 10);
+
+    let entries = history.iter().collect::<Vec<_>>();
+    assert_eq!(entries[0].detail(), Some(Detail::text("x".repeat(100))));
+    assert_eq!(entries[1].detail(), Some(Detail::text("fn main() {\n}")));
+    assert_eq!(entries[2].detail(), None);
+}
+
+#[test]
+fn test_save_and_load() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("glimpse").join("clipboard.json");
+    let mut history = ClipboardHistory::new();
+    history.record("one", 1, 10);
+    history.record("two", 2, 10);
+
+    history.save(&path).unwrap();
+    let loaded = ClipboardHistory::load(&path).unwrap();
+
+    assert_eq!(loaded, history);
+    // ids keep growing after a restart
+    let mut loaded = loaded;
+    loaded.record("three", 3, 10);
+    assert_eq!(loaded.iter().next().unwrap().id, 3);
+}
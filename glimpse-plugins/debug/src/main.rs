@@ -1,10 +1,10 @@
 use freedesktop_desktop_entry::{DesktopEntry, default_paths, get_languages_from_env};
-use std::{collections::HashMap, error::Error};
+use std::{collections::HashMap, error::Error, time::Duration};
 
 use async_trait::async_trait;
-use freedesktop_icons::lookup;
 use glimpse_sdk::{
-    Action, Match, MatchAction, Metadata, Plugin, PluginError, run_plugin, setup_logging,
+    Action, AlternateAction, Icon, Match, MatchAction, Metadata, Modifiers, Permission, Plugin,
+    PluginError, Publisher, SearchSink, run_plugin, setup_logging,
 };
 
 struct EchoPlugin {}
@@ -36,6 +36,10 @@ impl EchoPlugin {
                     de.name(&locales).unwrap_or_else(|| "Unknown".into())
                 ),
                 close_on_action: true,
+                alternates: vec![],
+                requires_confirmation: false,
+                confirmation_prompt: None,
+                expand: vec![],
                 action: Action::Launch {
                     app_id: de.id().to_string(),
                     action: None,
@@ -53,6 +57,10 @@ impl EchoPlugin {
                                 .unwrap_or_else(|| "Launch".into())
                                 .to_string(),
                             close_on_action: true,
+                            alternates: vec![],
+                            requires_confirmation: false,
+                            confirmation_prompt: None,
+                            expand: vec![],
                             action: Action::Launch {
                                 app_id: de.id().to_string(),
                                 action: Some(action_name.to_string()),
@@ -72,13 +80,10 @@ impl EchoPlugin {
                     .comment(&locales)
                     .unwrap_or_else(|| "".into())
                     .to_string(),
-                icon: de.icon().and_then(|icon_name| {
-                    lookup(&icon_name)
-                        .find()
-                        .map(|p| p.to_string_lossy().to_string())
-                }),
+                icon: de.icon().map(Icon::freedesktop),
                 actions,
                 score: 1.0,
+                ..Default::default()
             }
         })
         .collect::<Vec<_>>();
@@ -86,60 +91,80 @@ impl EchoPlugin {
             Match {
                 title: "No actions".to_string(),
                 description: "A result with no actions".to_string(),
-                icon: lookup("dialog-information")
-                    .find()
-                    .map(|p| p.to_string_lossy().to_string()),
+                icon: Some(Icon::freedesktop("dialog-information")),
                 actions: vec![],
                 score: 0.9,
+                ..Default::default()
             },
             Match {
                 title: "Copy to Clipboard".to_string(),
                 description: "Copies text to clipboard".to_string(),
-                icon: lookup("edit-copy")
-                    .find()
-                    .map(|p| p.to_string_lossy().to_string()),
+                icon: Some(Icon::freedesktop("edit-copy")),
                 actions: vec![
                     MatchAction {
                         title: "Copy Hello World".to_string(),
                         close_on_action: true,
+                        alternates: vec![],
+                        requires_confirmation: false,
+                        confirmation_prompt: None,
+                        expand: vec![],
                         action: Action::Clipboard {
-                            text: "Hello World".to_string(),
+                            text: "Hello World".into(),
                         },
                     },
                     MatchAction {
                         title: "Copy Hello World and keep open".to_string(),
                         close_on_action: false,
+                        alternates: vec![],
+                        requires_confirmation: false,
+                        confirmation_prompt: None,
+                        expand: vec![],
                         action: Action::Clipboard {
-                            text: "Hello World".to_string(),
+                            text: "Hello World".into(),
                         },
                     },
                 ],
                 score: 0.8,
+                ..Default::default()
             },
             Match {
                 title: "Open Rust Website".to_string(),
                 description: "Opens the Rust programming language website".to_string(),
-                icon: lookup("applications-internet")
-                    .find()
-                    .map(|p| p.to_string_lossy().to_string()),
+                icon: Some(Icon::freedesktop("applications-internet")),
                 actions: vec![MatchAction {
                     title: "Open https://www.rust-lang.org".to_string(),
                     close_on_action: true,
+                    alternates: vec![AlternateAction {
+                        modifiers: Modifiers {
+                            shift: true,
+                            ..Default::default()
+                        },
+                        title: "Open https://doc.rust-lang.org".to_string(),
+                        action: Action::Open {
+                            uri: "https://doc.rust-lang.org".to_string(),
+                        },
+                    }],
+                    requires_confirmation: false,
+                    confirmation_prompt: None,
+                    expand: vec![],
                     action: Action::Open {
                         uri: "https://www.rust-lang.org".to_string(),
                     },
                 }],
                 score: 0.7,
+                ..Default::default()
             },
             Match {
                 title: "Open home directory".to_string(),
                 description: "Opens the home directory in the file manager".to_string(),
-                icon: lookup("user-home")
-                    .find()
-                    .map(|p| p.to_string_lossy().to_string()),
+                icon: Some(Icon::freedesktop("user-home")),
                 actions: vec![MatchAction {
                     title: "Open Home".to_string(),
                     close_on_action: true,
+                    alternates: vec![],
+                    requires_confirmation: false,
+                    confirmation_prompt: None,
+                    expand: vec![],
                     action: Action::Open {
                         uri: format!(
                             "file:///home/{}",
@@ -148,32 +173,38 @@ impl EchoPlugin {
                     },
                 }],
                 score: 0.6,
+                ..Default::default()
             },
             Match {
                 title: "Run htop Command".to_string(),
                 description: "Runs the htop command in a terminal".to_string(),
-                icon: lookup("htop")
-                    .find()
-                    .map(|p| p.to_string_lossy().to_string()),
+                icon: Some(Icon::freedesktop("htop")),
                 actions: vec![MatchAction {
                     title: "Run htop".to_string(),
                     close_on_action: true,
+                    alternates: vec![],
+                    requires_confirmation: false,
+                    confirmation_prompt: None,
+                    expand: vec![],
                     action: Action::Exec {
                         command: "ghostty".to_string(),
                         args: vec!["-e".to_string(), "htop".to_string()],
                     },
                 }],
                 score: 0.6,
+                ..Default::default()
             },
             Match {
                 title: "Execute Plugin callback".to_string(),
                 description: "Executes a callback action".to_string(),
-                icon: lookup("system-run")
-                    .find()
-                    .map(|p| p.to_string_lossy().to_string()),
+                icon: Some(Icon::freedesktop("system-run")),
                 actions: vec![MatchAction {
                     title: "Execute Callback".to_string(),
                     close_on_action: false,
+                    alternates: vec![],
+                    requires_confirmation: false,
+                    confirmation_prompt: None,
+                    expand: vec![],
                     action: Action::Callback {
                         key: "example_callback".to_string(),
                         params: {
@@ -184,6 +215,7 @@ impl EchoPlugin {
                     },
                 }],
                 score: 0.6,
+                ..Default::default()
             },
         ]);
         results
@@ -200,7 +232,34 @@ impl Plugin for EchoPlugin {
             description: "A simple debug plugin that returns the search query as a result."
                 .to_string(),
             author: "Your Name <you@example.com>".to_string(),
+            permissions: vec![
+                Permission::Clipboard,
+                Permission::Exec,
+                Permission::Network,
+                Permission::HomeRead,
+            ],
+            ..Default::default()
+        }
+    }
+
+    async fn search(&self, query: String, sink: &SearchSink) -> Result<(), PluginError> {
+        if query.trim() != "stream" {
+            let items = self.handle_search(query).await?;
+            return sink.send(items).await;
+        }
+
+        // simulate a slow provider that finds one result at a time
+        for chunk in 0..5 {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            let item = Match {
+                title: format!("Streamed result {}", chunk),
+                description: "Sent as a separate chunk".to_string(),
+                score: 1.0 - chunk as f64 / 10.0,
+                ..Default::default()
+            };
+            sink.send(vec![item]).await?;
         }
+        Ok(())
     }
 
     async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
@@ -225,6 +284,28 @@ impl Plugin for EchoPlugin {
             }
         }
     }
+
+    async fn subscribe(&self, topic: String, publisher: Publisher) -> Result<(), PluginError> {
+        if topic != "ticker" {
+            return Err(PluginError::Other(format!("unknown topic: {}", topic)));
+        }
+
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        for tick in 0.. {
+            tokio::select! {
+                _ = publisher.closed() => break,
+                _ = interval.tick() => {
+                    let item = Match {
+                        title: format!("Tick {}", tick),
+                        description: "Pushed by the debug plugin every second".to_string(),
+                        ..Default::default()
+                    };
+                    publisher.publish(vec![item]).await?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 #[tokio::main]
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use glimpse_sdk::ConnectionTarget;
+use zbus::{
+    Connection,
+    fdo::{ManagedObjects, ObjectManagerProxy},
+    zvariant::OwnedValue,
+};
+
+use crate::devices::{Device, Kind};
+
+const SERVICE: &str = "org.bluez";
+const DEVICE_INTERFACE: &str = "org.bluez.Device1";
+
+/// The paired devices among the objects BlueZ manages, on any adapter.
+pub fn paired(objects: &ManagedObjects) -> Vec<Device> {
+    objects
+        .values()
+        .flat_map(|interfaces| interfaces.iter())
+        .filter(|(interface, _)| interface.as_str() == DEVICE_INTERFACE)
+        .filter_map(|(_, properties)| device(properties))
+        .collect()
+}
+
+/// The device of `org.bluez.Device1` properties, None unless paired.
+fn device(properties: &HashMap<String, OwnedValue>) -> Option<Device> {
+    let flag = |name: &str| {
+        properties
+            .get(name)
+            .and_then(|value| bool::try_from(value).ok())
+            .unwrap_or(false)
+    };
+    let text = |name: &str| {
+        properties
+            .get(name)
+            .and_then(|value| <&str>::try_from(value).ok())
+            .filter(|text| !text.is_empty())
+    };
+    if !flag("Paired") {
+        return None;
+    }
+    let address = text("Address")?;
+    Some(Device {
+        // the name the user gave it, BlueZ falls back to the announced one
+        name: text("Alias")
+            .or(text("Name"))
+            .unwrap_or(address)
+            .to_string(),
+        kind: Kind::Bluetooth,
+        target: ConnectionTarget::Bluetooth {
+            address: address.to_string(),
+        },
+        connected: flag("Connected"),
+        icon: text("Icon").map(str::to_string),
+    })
+}
+
+/// Paired Bluetooth devices, as BlueZ reports them on the system bus.
+pub async fn devices(connection: &Connection) -> zbus::Result<Vec<Device>> {
+    let objects = ObjectManagerProxy::builder(connection)
+        .destination(SERVICE)?
+        .path("/")?
+        .build()
+        .await?
+        .get_managed_objects()
+        .await?;
+    Ok(paired(&objects))
+}
@@ -0,0 +1,84 @@
+use glimpse_sdk::{ConnectionTarget, matcher::Matcher};
+
+/// Matches on the kind, like `bluetooth`, count for a little less than those on the name.
+const KIND_WEIGHT: f64 = 0.9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Bluetooth,
+    Wifi,
+}
+
+impl Kind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::Bluetooth => "Bluetooth",
+            Kind::Wifi => "Wi-Fi",
+        }
+    }
+
+    /// Other words people search for the kind by.
+    fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            Kind::Bluetooth => &["bluetooth"],
+            Kind::Wifi => &["wifi", "wi-fi", "wireless", "network"],
+        }
+    }
+
+    fn icon(&self) -> &'static str {
+        match self {
+            Kind::Bluetooth => "bluetooth",
+            Kind::Wifi => "network-wireless",
+        }
+    }
+}
+
+/// A paired Bluetooth device or a saved Wi-Fi network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Device {
+    pub name: String,
+    pub kind: Kind,
+    pub target: ConnectionTarget,
+    pub connected: bool,
+    /// The icon name the device announces, like `audio-headset`.
+    pub icon: Option<String>,
+}
+
+impl Device {
+    pub fn icon(&self) -> &str {
+        self.icon.as_deref().unwrap_or(self.kind.icon())
+    }
+
+    pub fn status(&self) -> &'static str {
+        match self.connected {
+            true => "Connected",
+            false => "Not connected",
+        }
+    }
+}
+
+/// Devices matching `query` by name or kind, best first, connected ones first among equals.
+/// An empty query finds them all.
+pub fn search<'a>(devices: &'a [Device], query: &str) -> Vec<(&'a Device, f64)> {
+    let matcher = Matcher::new(query.trim());
+    let mut found = devices
+        .iter()
+        .filter_map(|device| {
+            let name = matcher.score(&device.name);
+            let kind = device
+                .kind
+                .keywords()
+                .iter()
+                .filter_map(|keyword| matcher.score(keyword))
+                .map(|score| score * KIND_WEIGHT);
+            let score = name.into_iter().chain(kind).reduce(f64::max)?;
+            Some((device, score))
+        })
+        .collect::<Vec<_>>();
+    found.sort_by(|a, b| {
+        b.1.total_cmp(&a.1)
+            .then(b.0.connected.cmp(&a.0.connected))
+            .then(a.0.name.cmp(&b.0.name))
+    });
+    found
+}
@@ -0,0 +1,3 @@
+pub mod bluez;
+pub mod devices;
+pub mod network_manager;
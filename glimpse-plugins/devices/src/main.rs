@@ -0,0 +1,100 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use glimpse_plugins_devices::{
+    bluez,
+    devices::{self, Device},
+    network_manager,
+};
+use glimpse_sdk::{
+    Action, GlimpsePlugin, Icon, Match, MatchAction, Metadata, Plugin, PluginError, PluginMetadata,
+};
+use zbus::Connection;
+
+#[derive(GlimpsePlugin)]
+#[glimpse(
+    id = "me.aresa.glimpse.devices",
+    name = "Devices",
+    description = "Connects and disconnects paired Bluetooth devices and saved Wi-Fi networks.",
+    author = "Alex Oleshkevich <alex.oleshkevich@gmail.com>",
+    prefix = "dev ",
+    prefix_only,
+    permissions(Devices)
+)]
+struct DevicesPlugin {
+    /// The system bus, None where there is none to reach, like in a container.
+    connection: Option<Connection>,
+}
+
+impl DevicesPlugin {
+    /// Bluetooth devices and Wi-Fi networks, either missing if its service is not running.
+    async fn devices(&self) -> Vec<Device> {
+        let Some(connection) = &self.connection else {
+            return vec![];
+        };
+        let (bluetooth, networks) = tokio::join!(
+            bluez::devices(connection),
+            network_manager::networks(connection)
+        );
+        let bluetooth = bluetooth.unwrap_or_else(|e| {
+            tracing::debug!("failed to list bluetooth devices: {}", e);
+            vec![]
+        });
+        let networks = networks.unwrap_or_else(|e| {
+            tracing::debug!("failed to list networks: {}", e);
+            vec![]
+        });
+        bluetooth.into_iter().chain(networks).collect()
+    }
+
+    /// The daemon connects or disconnects, the plugin only lists.
+    fn to_match(device: &Device, score: f64) -> Match {
+        let title = match device.connected {
+            true => "Disconnect",
+            false => "Connect",
+        };
+        Match {
+            title: device.name.clone(),
+            description: format!("{} · {}", device.kind.name(), device.status()),
+            icon: Some(Icon::freedesktop(device.icon())),
+            actions: vec![MatchAction {
+                title: title.to_string(),
+                action: Action::Connection {
+                    target: device.target.clone(),
+                    connect: !device.connected,
+                },
+                close_on_action: true,
+                alternates: vec![],
+                requires_confirmation: false,
+                confirmation_prompt: None,
+                expand: vec![],
+            }],
+            score,
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for DevicesPlugin {
+    fn metadata(&self) -> Metadata {
+        Self::plugin_metadata()
+    }
+
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        let devices = self.devices().await;
+        Ok(devices::search(&devices, &query)
+            .into_iter()
+            .map(|(device, score)| Self::to_match(device, score))
+            .collect())
+    }
+}
+
+#[glimpse_sdk::main]
+async fn main() -> Result<DevicesPlugin, Box<dyn Error>> {
+    let connection = Connection::system()
+        .await
+        .inspect_err(|e| tracing::warn!("no system bus, no devices to list: {}", e))
+        .ok();
+    Ok(DevicesPlugin { connection })
+}
@@ -0,0 +1,93 @@
+use std::collections::{HashMap, HashSet};
+
+use glimpse_sdk::ConnectionTarget;
+use zbus::{
+    Connection, Proxy,
+    proxy::{Builder, CacheProperties},
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue},
+};
+
+use crate::devices::{Device, Kind};
+
+const SERVICE: &str = "org.freedesktop.NetworkManager";
+const PATH: &str = "/org/freedesktop/NetworkManager";
+const INTERFACE: &str = "org.freedesktop.NetworkManager";
+const SETTINGS_PATH: &str = "/org/freedesktop/NetworkManager/Settings";
+const SETTINGS_INTERFACE: &str = "org.freedesktop.NetworkManager.Settings";
+const CONNECTION_INTERFACE: &str = "org.freedesktop.NetworkManager.Settings.Connection";
+const ACTIVE_INTERFACE: &str = "org.freedesktop.NetworkManager.Connection.Active";
+
+/// The `connection.type` of Wi-Fi connections.
+const WIFI_TYPE: &str = "802-11-wireless";
+
+/// Settings of a saved connection, by setting name, like `connection` or `802-11-wireless`.
+pub type Settings = HashMap<String, HashMap<String, OwnedValue>>;
+
+/// The network of saved connection `settings`, None unless it is a Wi-Fi one. `active`
+/// holds the UUIDs of the connections up.
+pub fn wifi(settings: &Settings, active: &HashSet<String>) -> Option<Device> {
+    let connection = settings.get("connection")?;
+    let text = |name: &str| {
+        connection
+            .get(name)
+            .and_then(|value| <&str>::try_from(value).ok())
+    };
+    if text("type")? != WIFI_TYPE {
+        return None;
+    }
+    let uuid = text("uuid")?;
+    Some(Device {
+        name: text("id").unwrap_or(uuid).to_string(),
+        kind: Kind::Wifi,
+        target: ConnectionTarget::Network {
+            uuid: uuid.to_string(),
+        },
+        connected: active.contains(uuid),
+        icon: None,
+    })
+}
+
+/// Saved Wi-Fi networks, as NetworkManager reports them on the system bus.
+pub async fn networks(connection: &Connection) -> zbus::Result<Vec<Device>> {
+    let manager = proxy(connection, PATH, INTERFACE).await?;
+    let mut active = HashSet::new();
+    for path in manager
+        .get_property::<Vec<OwnedObjectPath>>("ActiveConnections")
+        .await?
+    {
+        let uuid: String = proxy(connection, path, ACTIVE_INTERFACE)
+            .await?
+            .get_property("Uuid")
+            .await?;
+        active.insert(uuid);
+    }
+
+    let saved: Vec<OwnedObjectPath> = proxy(connection, SETTINGS_PATH, SETTINGS_INTERFACE)
+        .await?
+        .call("ListConnections", &())
+        .await?;
+    let mut networks = vec![];
+    for path in saved {
+        let settings: Settings = proxy(connection, path, CONNECTION_INTERFACE)
+            .await?
+            .call("GetSettings", &())
+            .await?;
+        networks.extend(wifi(&settings, &active));
+    }
+    Ok(networks)
+}
+
+/// A proxy reading properties when asked for, connections come and go between searches.
+async fn proxy(
+    connection: &Connection,
+    path: impl TryInto<ObjectPath<'static>, Error: Into<zbus::Error>>,
+    interface: &'static str,
+) -> zbus::Result<Proxy<'static>> {
+    Builder::new(connection)
+        .destination(SERVICE)?
+        .path(path)?
+        .interface(interface)?
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await
+}
@@ -0,0 +1,196 @@
+use std::collections::{HashMap, HashSet};
+
+use glimpse_plugins_devices::{
+    bluez,
+    devices::{self, Device, Kind},
+    network_manager,
+};
+use glimpse_sdk::ConnectionTarget;
+use zbus::{
+    fdo::ManagedObjects,
+    names::OwnedInterfaceName,
+    zvariant::{OwnedObjectPath, OwnedValue, Str},
+};
+
+fn text(value: &str) -> OwnedValue {
+    OwnedValue::from(Str::from(value.to_string()))
+}
+
+fn bluetooth(
+    address: &str,
+    alias: &str,
+    paired: bool,
+    connected: bool,
+) -> HashMap<String, OwnedValue> {
+    HashMap::from([
+        ("Address".to_string(), text(address)),
+        ("Alias".to_string(), text(alias)),
+        ("Icon".to_string(), text("audio-headset")),
+        ("Paired".to_string(), OwnedValue::from(paired)),
+        ("Connected".to_string(), OwnedValue::from(connected)),
+    ])
+}
+
+fn objects(devices: Vec<(&str, &str, HashMap<String, OwnedValue>)>) -> ManagedObjects {
+    devices
+        .into_iter()
+        .map(|(path, interface, properties)| {
+            (
+                OwnedObjectPath::try_from(path).unwrap(),
+                HashMap::from([(OwnedInterfaceName::try_from(interface).unwrap(), properties)]),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn lists_paired_bluetooth_devices() {
+    let objects = objects(vec![
+        (
+            "/org/bluez/hci0/dev_00_1A_7D_DA_71_13",
+            "org.bluez.Device1",
+            bluetooth("00:1A:7D:DA:71:13", "WH-1000XM4", true, true),
+        ),
+        (
+            "/org/bluez/hci0/dev_A4_C1_38_0F_22_91",
+            "org.bluez.Device1",
+            bluetooth("A4:C1:38:0F:22:91", "Neighbour's TV", false, false),
+        ),
+        (
+            "/org/bluez/hci0",
+            "org.bluez.Adapter1",
+            bluetooth("5C:F3:70:8B:12:0A", "laptop", true, false),
+        ),
+    ]);
+
+    assert_eq!(
+        bluez::paired(&objects),
+        vec![Device {
+            name: "WH-1000XM4".to_string(),
+            kind: Kind::Bluetooth,
+            target: ConnectionTarget::Bluetooth {
+                address: "00:1A:7D:DA:71:13".to_string(),
+            },
+            connected: true,
+            icon: Some("audio-headset".to_string()),
+        }]
+    );
+}
+
+#[test]
+fn unnamed_bluetooth_devices_go_by_address() {
+    let mut properties = bluetooth("00:1A:7D:DA:71:13", "", true, false);
+    properties.remove("Icon");
+    let objects = objects(vec![(
+        "/org/bluez/hci0/dev_00_1A_7D_DA_71_13",
+        "org.bluez.Device1",
+        properties,
+    )]);
+
+    let devices = bluez::paired(&objects);
+    assert_eq!(devices[0].name, "00:1A:7D:DA:71:13");
+    assert_eq!(devices[0].icon(), "bluetooth");
+}
+
+fn settings(kind: &str, id: &str, uuid: &str) -> network_manager::Settings {
+    HashMap::from([(
+        "connection".to_string(),
+        HashMap::from([
+            ("type".to_string(), text(kind)),
+            ("id".to_string(), text(id)),
+            ("uuid".to_string(), text(uuid)),
+        ]),
+    )])
+}
+
+#[test]
+fn lists_wifi_networks() {
+    let active = HashSet::from(["7d5e6c1a-8f3b-4a52-9c0e-2b1f4d6a8e90".to_string()]);
+
+    let home = network_manager::wifi(
+        &settings(
+            "802-11-wireless",
+            "Home",
+            "7d5e6c1a-8f3b-4a52-9c0e-2b1f4d6a8e90",
+        ),
+        &active,
+    )
+    .unwrap();
+    assert_eq!(home.name, "Home");
+    assert_eq!(home.kind, Kind::Wifi);
+    assert_eq!(
+        home.target,
+        ConnectionTarget::Network {
+            uuid: "7d5e6c1a-8f3b-4a52-9c0e-2b1f4d6a8e90".to_string()
+        }
+    );
+    assert!(home.connected);
+    assert_eq!(home.icon(), "network-wireless");
+
+    let cafe = network_manager::wifi(
+        &settings(
+            "802-11-wireless",
+            "Cafe",
+            "0b3f9c2e-51d4-4e8a-a6f7-93c1d2e4b5a6",
+        ),
+        &active,
+    )
+    .unwrap();
+    assert!(!cafe.connected);
+}
+
+#[test]
+fn skips_other_connections() {
+    let wired = settings(
+        "802-3-ethernet",
+        "Wired connection 1",
+        "2f1e4d3c-6b5a-4987-8c7d-1e2f3a4b5c6d",
+    );
+    assert_eq!(network_manager::wifi(&wired, &HashSet::new()), None);
+    assert_eq!(
+        network_manager::wifi(&HashMap::new(), &HashSet::new()),
+        None
+    );
+}
+
+fn device(name: &str, kind: Kind, connected: bool) -> Device {
+    Device {
+        name: name.to_string(),
+        kind,
+        target: ConnectionTarget::Network {
+            uuid: name.to_string(),
+        },
+        connected,
+        icon: None,
+    }
+}
+
+#[test]
+fn searches_by_name_and_kind() {
+    let devices = vec![
+        device("WH-1000XM4", Kind::Bluetooth, false),
+        device("Home", Kind::Wifi, false),
+        device("Office", Kind::Wifi, true),
+    ];
+
+    let found = devices::search(&devices, "home");
+    assert_eq!(found[0].0.name, "Home");
+
+    let names = |query| {
+        devices::search(&devices, query)
+            .into_iter()
+            .map(|(device, _)| device.name.as_str())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names("wifi"), vec!["Office", "Home"]);
+    assert_eq!(names("bluetooth"), vec!["WH-1000XM4"]);
+    assert_eq!(names(""), vec!["Office", "Home", "WH-1000XM4"]);
+}
+
+#[test]
+fn describes_devices() {
+    let headset = device("WH-1000XM4", Kind::Bluetooth, true);
+    assert_eq!(headset.status(), "Connected");
+    assert_eq!(headset.kind.name(), "Bluetooth");
+    assert_eq!(device("Home", Kind::Wifi, false).status(), "Not connected");
+}
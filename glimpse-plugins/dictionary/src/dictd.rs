@@ -0,0 +1,63 @@
+use std::io;
+
+use tokio::process::Command;
+
+/// `dict` exits with this when the word is in none of the dictionaries.
+const NO_MATCH: i32 = 20;
+
+/// A definition from one dictionary of a dictd server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// The dictionary, e.g. `The Collaborative International Dictionary of English v.0.48`.
+    pub source: String,
+    pub text: String,
+}
+
+/// Definitions of `word` from the dictd server `dict` is configured for. None if `dict` is
+/// not installed.
+pub async fn define(word: &str) -> io::Result<Option<Vec<Entry>>> {
+    let output = match Command::new("dict").arg("--").arg(word).output().await {
+        Ok(output) => output,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    match output.status.code() {
+        Some(0) => Ok(Some(parse(&String::from_utf8_lossy(&output.stdout)))),
+        Some(NO_MATCH) => Ok(Some(vec![])),
+        _ => Err(io::Error::other(format!(
+            "dict failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))),
+    }
+}
+
+/// Entries of `dict` output: a `From <source>:` line heading each, the text below it
+/// indented.
+pub fn parse(output: &str) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = vec![];
+    for line in output.lines() {
+        if let Some(source) = line.strip_prefix("From ").and_then(|s| s.strip_suffix(':')) {
+            let source = match source.rsplit_once(" [") {
+                Some((source, _)) => source,
+                None => source,
+            };
+            entries.push(Entry {
+                source: source.to_string(),
+                text: String::new(),
+            });
+            continue;
+        }
+        let Some(entry) = entries.last_mut() else {
+            // the `N definitions found` summary
+            continue;
+        };
+        let line = line.strip_prefix("  ").unwrap_or(line);
+        entry.text.push_str(line.trim_end());
+        entry.text.push('\n');
+    }
+    for entry in &mut entries {
+        entry.text = entry.text.trim().to_string();
+    }
+    entries.retain(|entry| !entry.text.is_empty());
+    entries
+}
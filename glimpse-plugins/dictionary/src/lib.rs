@@ -0,0 +1,2 @@
+pub mod dictd;
+pub mod wordnet;
@@ -0,0 +1,204 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use glimpse_plugins_dictionary::{
+    dictd::{self, Entry},
+    wordnet::{DEFAULT_DIRS, Sense, WordNet},
+};
+use glimpse_sdk::{
+    Action, AlternateAction, ConfigField, ConfigKind, ConfigSchema, Detail, GlimpsePlugin, Icon,
+    Match, MatchAction, Metadata, Modifiers, Plugin, PluginError, PluginMetadata, Settings,
+};
+use serde::Deserialize;
+
+const DEFAULT_MAX_RESULTS: usize = 10;
+const ICON: &str = "accessories-dictionary";
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+struct DictionarySettings {
+    max_results: usize,
+    /// Directories searched for a WordNet database, the first holding one is used.
+    wordnet_dirs: Vec<String>,
+}
+
+impl Default for DictionarySettings {
+    fn default() -> Self {
+        Self {
+            max_results: DEFAULT_MAX_RESULTS,
+            wordnet_dirs: DEFAULT_DIRS.iter().map(|dir| dir.to_string()).collect(),
+        }
+    }
+}
+
+#[derive(GlimpsePlugin)]
+#[glimpse(
+    id = "me.aresa.glimpse.dictionary",
+    name = "Dictionary",
+    description = "Defines words from a local WordNet database, or dictd.",
+    author = "Alex Oleshkevich <alex.oleshkevich@gmail.com>",
+    prefix = "define ",
+    prefix_only,
+    permissions(Clipboard)
+)]
+struct DictionaryPlugin {
+    settings: Settings<DictionarySettings>,
+}
+
+impl DictionaryPlugin {
+    fn copy_action(text: String, alternate: Option<(&str, String)>) -> MatchAction {
+        MatchAction {
+            title: "Copy definition".to_string(),
+            action: Action::Clipboard { text: text.into() },
+            close_on_action: true,
+            alternates: alternate
+                .into_iter()
+                .map(|(title, text)| AlternateAction {
+                    modifiers: Modifiers {
+                        shift: true,
+                        ..Default::default()
+                    },
+                    title: title.to_string(),
+                    action: Action::Clipboard { text: text.into() },
+                })
+                .collect(),
+            requires_confirmation: false,
+            confirmation_prompt: None,
+            expand: vec![],
+        }
+    }
+
+    fn sense_match(word: &str, sense: &Sense, score: f64) -> Match {
+        let mut content = format!(
+            "**{}** *{}*\n\n{}\n",
+            word,
+            sense.part_of_speech.name(),
+            sense.definition
+        );
+        for example in &sense.examples {
+            content.push_str(&format!("\n> {}\n", example));
+        }
+        let synonyms = sense
+            .words
+            .iter()
+            .filter(|synonym| !synonym.eq_ignore_ascii_case(word))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !synonyms.is_empty() {
+            content.push_str(&format!("\nSynonyms: {}\n", synonyms.join(", ")));
+        }
+
+        Match {
+            title: word.to_string(),
+            description: format!("{} · {}", sense.part_of_speech.name(), sense.definition),
+            icon: Some(Icon::freedesktop(ICON)),
+            actions: vec![Self::copy_action(
+                sense.definition.clone(),
+                Some((
+                    "Copy with the word",
+                    format!("{}: {}", word, sense.definition),
+                )),
+            )],
+            detail: Some(Detail::markdown(content)),
+            score,
+            ..Default::default()
+        }
+    }
+
+    fn entry_match(word: &str, entry: &Entry, score: f64) -> Match {
+        let summary = entry
+            .text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.eq_ignore_ascii_case(word))
+            .unwrap_or_default();
+        Match {
+            title: word.to_string(),
+            description: format!("{} · {}", entry.source, summary),
+            icon: Some(Icon::freedesktop(ICON)),
+            actions: vec![Self::copy_action(entry.text.clone(), None)],
+            detail: Some(Detail::text(entry.text.clone())),
+            score,
+            ..Default::default()
+        }
+    }
+
+    /// Definitions from WordNet when there is a database, from dictd otherwise.
+    async fn define(settings: &DictionarySettings, word: &str) -> Result<Vec<Match>, PluginError> {
+        let dirs = settings.wordnet_dirs.clone();
+        let lookup = word.to_string();
+        let senses = tokio::task::spawn_blocking(move || {
+            WordNet::find(&dirs).map(|wordnet| wordnet.lookup(&lookup))
+        })
+        .await
+        .map_err(|e| PluginError::Other(e.to_string()))?;
+        if let Some(senses) = senses {
+            let senses = senses.map_err(PluginError::Io)?;
+            return Ok(senses
+                .iter()
+                .take(settings.max_results)
+                .enumerate()
+                .map(|(rank, sense)| Self::sense_match(word, sense, score(rank)))
+                .collect());
+        }
+
+        match dictd::define(word).await.map_err(PluginError::Io)? {
+            Some(entries) => Ok(entries
+                .iter()
+                .take(settings.max_results)
+                .enumerate()
+                .map(|(rank, entry)| Self::entry_match(word, entry, score(rank)))
+                .collect()),
+            None => {
+                tracing::warn!("no wordnet database nor dict found, nothing to define with");
+                Ok(vec![])
+            }
+        }
+    }
+}
+
+/// Scores keeping the dictionary's order, its most common senses first.
+fn score(rank: usize) -> f64 {
+    1.0 / (rank + 1) as f64
+}
+
+#[async_trait]
+impl Plugin for DictionaryPlugin {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            config_schema: Some(
+                ConfigSchema::new()
+                    .field(
+                        ConfigField::new("max_results", ConfigKind::Integer)
+                            .default_value(DEFAULT_MAX_RESULTS)
+                            .description("Maximum number of definitions returned per search"),
+                    )
+                    .field(
+                        ConfigField::new("wordnet_dirs", ConfigKind::StringList)
+                            .default_value(DEFAULT_DIRS.to_vec())
+                            .description("Directories searched for a WordNet database"),
+                    ),
+            ),
+            ..Self::plugin_metadata()
+        }
+    }
+
+    async fn configure(&self, config: serde_json::Value) -> Result<(), PluginError> {
+        self.settings.apply(config)
+    }
+
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        let word = query.trim();
+        if word.is_empty() {
+            return Ok(vec![]);
+        }
+        Self::define(&self.settings.get(), word).await
+    }
+}
+
+#[glimpse_sdk::main]
+async fn main() -> Result<DictionaryPlugin, Box<dyn Error>> {
+    Ok(DictionaryPlugin {
+        settings: Settings::default(),
+    })
+}
@@ -0,0 +1,223 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+/// Where distributions install the WordNet database, e.g. Debian's `wordnet-base`.
+pub const DEFAULT_DIRS: &[&str] = &[
+    "/usr/share/wordnet",
+    "/usr/share/wordnet/dict",
+    "/usr/local/share/wordnet/dict",
+    "/usr/share/WordNet-3.0/dict",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartOfSpeech {
+    Noun,
+    Verb,
+    Adjective,
+    Adverb,
+}
+
+impl PartOfSpeech {
+    pub const ALL: [PartOfSpeech; 4] = [
+        PartOfSpeech::Noun,
+        PartOfSpeech::Verb,
+        PartOfSpeech::Adjective,
+        PartOfSpeech::Adverb,
+    ];
+
+    /// Suffix of the `index.*` and `data.*` files.
+    fn file_suffix(&self) -> &'static str {
+        match self {
+            PartOfSpeech::Noun => "noun",
+            PartOfSpeech::Verb => "verb",
+            PartOfSpeech::Adjective => "adj",
+            PartOfSpeech::Adverb => "adv",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PartOfSpeech::Noun => "noun",
+            PartOfSpeech::Verb => "verb",
+            PartOfSpeech::Adjective => "adjective",
+            PartOfSpeech::Adverb => "adverb",
+        }
+    }
+}
+
+/// One meaning of a word, a WordNet synset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sense {
+    pub part_of_speech: PartOfSpeech,
+    /// Words sharing the meaning, the looked up one among them.
+    pub words: Vec<String>,
+    pub definition: String,
+    pub examples: Vec<String>,
+}
+
+/// A WordNet database directory holding the `index.*` and `data.*` files.
+#[derive(Debug, Clone)]
+pub struct WordNet {
+    dir: PathBuf,
+}
+
+impl WordNet {
+    /// The first of `dirs` holding a WordNet database.
+    pub fn find<P: AsRef<Path>>(dirs: &[P]) -> Option<Self> {
+        dirs.iter()
+            .map(|dir| dir.as_ref())
+            .find(|dir| dir.join("index.noun").is_file())
+            .map(|dir| Self {
+                dir: dir.to_path_buf(),
+            })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Senses of `word` in the order WordNet ranks them, nouns first. Inflected words, like
+    /// `dogs`, are looked up by their base form when they are not words of their own.
+    pub fn lookup(&self, word: &str) -> io::Result<Vec<Sense>> {
+        for lemma in base_forms(word) {
+            let mut senses = vec![];
+            for part_of_speech in PartOfSpeech::ALL {
+                senses.extend(self.lookup_in(&lemma, part_of_speech)?);
+            }
+            if !senses.is_empty() {
+                return Ok(senses);
+            }
+        }
+        Ok(vec![])
+    }
+
+    fn lookup_in(&self, lemma: &str, part_of_speech: PartOfSpeech) -> io::Result<Vec<Sense>> {
+        let suffix = part_of_speech.file_suffix();
+        let index = match File::open(self.dir.join(format!("index.{}", suffix))) {
+            Ok(index) => BufReader::new(index),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err),
+        };
+        let mut offsets = None;
+        for line in index.lines() {
+            if let Some((found, found_offsets)) = parse_index_line(&line?)
+                && found == lemma
+            {
+                offsets = Some(found_offsets);
+                break;
+            }
+        }
+        let Some(offsets) = offsets else {
+            return Ok(vec![]);
+        };
+
+        let mut data = BufReader::new(File::open(self.dir.join(format!("data.{}", suffix)))?);
+        let mut senses = vec![];
+        for offset in offsets {
+            data.seek(SeekFrom::Start(offset))?;
+            let mut line = String::new();
+            data.read_line(&mut line)?;
+            match parse_data_line(&line, part_of_speech) {
+                Some(sense) => senses.push(sense),
+                None => tracing::warn!("invalid wordnet synset at {} in data.{}", offset, suffix),
+            }
+        }
+        Ok(senses)
+    }
+}
+
+/// `word` as WordNet keys it, lowercase with underscores for spaces, followed by the base
+/// forms it may be an inflection of.
+pub fn base_forms(word: &str) -> Vec<String> {
+    let lemma = word
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("_")
+        .to_lowercase();
+    if lemma.is_empty() {
+        return vec![];
+    }
+    let mut forms = vec![lemma.clone()];
+    let stripped = [
+        ("ies", "y"),
+        ("es", ""),
+        ("s", ""),
+        ("ed", ""),
+        ("ed", "e"),
+        ("ing", ""),
+        ("ing", "e"),
+    ];
+    for (suffix, replacement) in stripped {
+        if let Some(stem) = lemma.strip_suffix(suffix)
+            && stem.len() > 1
+        {
+            let form = format!("{}{}", stem, replacement);
+            if !forms.contains(&form) {
+                forms.push(form);
+            }
+        }
+    }
+    forms
+}
+
+/// The lemma of an `index.*` line and the offsets of its synsets in the `data.*` file.
+/// The license lines heading the file, which start with spaces, give None.
+///
+/// `lemma pos synset_cnt p_cnt [ptr_symbol...] sense_cnt tagsense_cnt synset_offset...`
+pub fn parse_index_line(line: &str) -> Option<(&str, Vec<u64>)> {
+    if line.starts_with(' ') {
+        return None;
+    }
+    let fields = line.split_whitespace().collect::<Vec<_>>();
+    let lemma = fields.first()?;
+    let pointers: usize = fields.get(3)?.parse().ok()?;
+    let offsets = fields
+        .get(4 + pointers + 2..)?
+        .iter()
+        .map(|offset| offset.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    Some((lemma, offsets))
+}
+
+/// The sense of a `data.*` line.
+///
+/// `synset_offset lex_filenum ss_type w_cnt word lex_id [word lex_id...] p_cnt ... | gloss`,
+/// with `w_cnt` in hexadecimal.
+pub fn parse_data_line(line: &str, part_of_speech: PartOfSpeech) -> Option<Sense> {
+    let (head, gloss) = line.split_once(" | ")?;
+    let fields = head.split_whitespace().collect::<Vec<_>>();
+    let count = usize::from_str_radix(fields.get(3)?, 16).ok()?;
+    let words = (0..count)
+        .map(|i| fields.get(4 + i * 2).map(|word| display_word(word)))
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut definition = vec![];
+    let mut examples = vec![];
+    for part in gloss.trim().split("; ") {
+        let part = part.trim();
+        match part.strip_prefix('"') {
+            Some(example) => examples.push(example.trim_end_matches('"').to_string()),
+            None if !part.is_empty() => definition.push(part),
+            None => {}
+        }
+    }
+    Some(Sense {
+        part_of_speech,
+        words,
+        definition: definition.join("; "),
+        examples,
+    })
+}
+
+/// A word of a synset as written: spaces for underscores, without the syntactic marker
+/// adjectives may carry, like `(a)` in `galore(ip)`.
+fn display_word(word: &str) -> String {
+    let word = match word.find('(') {
+        Some(marker) if word.ends_with(')') => &word[..marker],
+        _ => word,
+    };
+    word.replace('_', " ")
+}
@@ -0,0 +1,173 @@
+use std::fs;
+
+use glimpse_plugins_dictionary::{
+    dictd,
+    wordnet::{self, PartOfSpeech, WordNet},
+};
+
+const DOG_INDEX: &str =
+    "dog n 7 5 @ ~ #m #p %p 7 1 02086723 10133978 10042764 09905672 07676602 03907626 02712903  ";
+const DOG_DATA: &str = "02086723 05 n 03 dog 0 domestic_dog 0 Canis_familiaris 0 023 @ 02085998 n 0000 | a member of the genus Canis (probably descended from the common wolf) that has been domesticated by man since prehistoric times; occurs in many breeds; \"the dog barked all night\"  ";
+
+#[test]
+fn parses_index_lines() {
+    let (lemma, offsets) = wordnet::parse_index_line(DOG_INDEX).unwrap();
+    assert_eq!(lemma, "dog");
+    assert_eq!(
+        offsets,
+        vec![
+            2086723, 10133978, 10042764, 9905672, 7676602, 3907626, 2712903
+        ]
+    );
+}
+
+#[test]
+fn skips_license_lines() {
+    assert_eq!(
+        wordnet::parse_index_line("  1 This software and database is being provided"),
+        None
+    );
+}
+
+#[test]
+fn parses_data_lines() {
+    let sense = wordnet::parse_data_line(DOG_DATA, PartOfSpeech::Noun).unwrap();
+    assert_eq!(sense.part_of_speech, PartOfSpeech::Noun);
+    assert_eq!(sense.words, vec!["dog", "domestic dog", "Canis familiaris"]);
+    assert_eq!(
+        sense.definition,
+        "a member of the genus Canis (probably descended from the common wolf) that has been \
+         domesticated by man since prehistoric times; occurs in many breeds"
+    );
+    assert_eq!(sense.examples, vec!["the dog barked all night"]);
+}
+
+#[test]
+fn strips_adjective_markers() {
+    let line = "00013160 00 s 01 galore(ip) 0 000 | existing in abundance; \"bargains galore\"";
+    let sense = wordnet::parse_data_line(line, PartOfSpeech::Adjective).unwrap();
+    assert_eq!(sense.words, vec!["galore"]);
+    assert_eq!(sense.definition, "existing in abundance");
+}
+
+#[test]
+fn rejects_lines_without_gloss() {
+    assert_eq!(
+        wordnet::parse_data_line("02086723 05 n 03 dog 0", PartOfSpeech::Noun),
+        None
+    );
+}
+
+#[test]
+fn base_forms_start_with_the_word() {
+    assert_eq!(wordnet::base_forms("Hot Dog"), vec!["hot_dog"]);
+    assert_eq!(wordnet::base_forms("  "), Vec::<String>::new());
+
+    let forms = wordnet::base_forms("ponies");
+    assert_eq!(forms[0], "ponies");
+    assert!(forms.contains(&"pony".to_string()));
+
+    let forms = wordnet::base_forms("baked");
+    assert!(forms.contains(&"bake".to_string()));
+}
+
+fn database() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let header = "  1 This software and database is being provided to you, the LICENSEE\n";
+    let synsets = [
+        "05 n 01 cat 0 000 | feline mammal usually having thick soft fur; \"cats purr\"",
+        "05 n 02 dog 0 domestic_dog 0 000 | a domesticated carnivorous mammal",
+        "06 n 01 dog 1 000 | a dull unattractive unpleasant girl or woman",
+    ];
+    let mut data = header.to_string();
+    let mut offsets = vec![];
+    for synset in synsets {
+        offsets.push(data.len());
+        data.push_str(&format!("{:08} {}  \n", data.len(), synset));
+    }
+    fs::write(dir.path().join("data.noun"), data).unwrap();
+
+    let index = format!(
+        "{}cat n 1 0 1 1 {:08}  \ndog n 2 0 2 1 {:08} {:08}  \n",
+        header, offsets[0], offsets[1], offsets[2]
+    );
+    fs::write(dir.path().join("index.noun"), index).unwrap();
+    dir
+}
+
+#[test]
+fn finds_the_first_database() {
+    let empty = tempfile::tempdir().unwrap();
+    let database = database();
+    let wordnet = WordNet::find(&[empty.path(), database.path()]).unwrap();
+    assert_eq!(wordnet.dir(), database.path());
+
+    assert!(WordNet::find(&[empty.path()]).is_none());
+}
+
+#[test]
+fn looks_up_senses_in_order() {
+    let database = database();
+    let wordnet = WordNet::find(&[database.path()]).unwrap();
+
+    let senses = wordnet.lookup("dog").unwrap();
+    let definitions = senses
+        .iter()
+        .map(|sense| sense.definition.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        definitions,
+        vec![
+            "a domesticated carnivorous mammal",
+            "a dull unattractive unpleasant girl or woman"
+        ]
+    );
+    assert_eq!(senses[0].words, vec!["dog", "domestic dog"]);
+}
+
+#[test]
+fn looks_up_inflected_words() {
+    let database = database();
+    let wordnet = WordNet::find(&[database.path()]).unwrap();
+
+    let senses = wordnet.lookup("Cats").unwrap();
+    assert_eq!(senses.len(), 1);
+    assert_eq!(senses[0].examples, vec!["cats purr"]);
+
+    assert!(wordnet.lookup("unicorn").unwrap().is_empty());
+}
+
+#[test]
+fn parses_dict_output() {
+    let output = "2 definitions found
+
+From WordNet (r) 3.0 (2006) [wn]:
+
+  dog
+      n 1: a member of the genus Canis
+      v 1: go after with the intent to catch
+
+From The Free On-line Dictionary of Computing (30 December 2018) [foldoc]:
+
+  dog
+     A {dongle}.
+";
+    let entries = dictd::parse(output);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].source, "WordNet (r) 3.0 (2006)");
+    assert_eq!(
+        entries[0].text,
+        "dog\n    n 1: a member of the genus Canis\n    v 1: go after with the intent to catch"
+    );
+    assert_eq!(
+        entries[1].source,
+        "The Free On-line Dictionary of Computing (30 December 2018)"
+    );
+    assert_eq!(entries[1].text, "dog\n   A {dongle}.");
+}
+
+#[test]
+fn parses_empty_dict_output() {
+    assert!(dictd::parse("").is_empty());
+    assert!(dictd::parse("No definitions found for \"qwxz\"\n").is_empty());
+}
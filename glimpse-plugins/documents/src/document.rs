@@ -0,0 +1,254 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Characters of page text kept around a match for the description.
+const SNIPPET_CHARS: usize = 120;
+/// Page text stored per page, enough for a first page and to keep the index small.
+const MAX_PAGE_CHARS: usize = 4000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    Pdf,
+    Epub,
+}
+
+impl DocumentKind {
+    pub fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "pdf" => Some(DocumentKind::Pdf),
+            "epub" => Some(DocumentKind::Epub),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Document {
+    pub path: PathBuf,
+    /// Modification time of the file when it was read, in seconds.
+    pub modified: u64,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    /// Text of the first pages, chapters for epubs.
+    pub pages: Vec<String>,
+}
+
+impl Document {
+    /// Read metadata and the text of up to `pages` pages.
+    pub fn read(path: &Path, modified: u64, pages: usize) -> io::Result<Self> {
+        let kind = DocumentKind::of(path).ok_or_else(|| io::Error::other("not a document"))?;
+        let mut document = match kind {
+            DocumentKind::Pdf => read_pdf(path, pages)?,
+            DocumentKind::Epub => read_epub(path, pages)?,
+        };
+        document.path = path.to_path_buf();
+        document.modified = modified;
+        for page in &mut document.pages {
+            *page = truncate(&collapse_whitespace(page), MAX_PAGE_CHARS);
+        }
+        Ok(document)
+    }
+
+    /// Title from the metadata, the file name if there is none.
+    pub fn display_title(&self) -> String {
+        self.title.clone().unwrap_or_else(|| {
+            self.path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// Title and author fields of `pdfinfo` output.
+pub fn parse_pdfinfo(output: &str) -> (Option<String>, Option<String>) {
+    let field = |name: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    (field("Title"), field("Author"))
+}
+
+/// Metadata and text through poppler's `pdfinfo` and `pdftotext`.
+fn read_pdf(path: &Path, pages: usize) -> io::Result<Document> {
+    let info = run(Command::new("pdfinfo").arg(path))?;
+    let (title, author) = parse_pdfinfo(&info);
+    let text = run(Command::new("pdftotext")
+        .args(["-q", "-enc", "UTF-8", "-f", "1", "-l"])
+        .arg(pages.max(1).to_string())
+        .arg(path)
+        .arg("-"))?;
+    Ok(Document {
+        title,
+        author,
+        // pages end with a form feed
+        pages: text
+            .split('\x0c')
+            .filter(|page| !page.trim().is_empty())
+            .take(pages)
+            .map(str::to_string)
+            .collect(),
+        ..Default::default()
+    })
+}
+
+fn run(command: &mut Command) -> io::Result<String> {
+    let output = command
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "{:?} exited with {}",
+            command.get_program(),
+            output.status
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Metadata from the package document, text from the first documents of the spine.
+fn read_epub(path: &Path, pages: usize) -> io::Result<Document> {
+    let mut archive =
+        zip::ZipArchive::new(BufReader::new(File::open(path)?)).map_err(io::Error::other)?;
+    let container = read_entry(&mut archive, "META-INF/container.xml")?;
+    let container = roxmltree::Document::parse(&container).map_err(io::Error::other)?;
+    let package_path = container
+        .descendants()
+        .find(|node| node.has_tag_name("rootfile"))
+        .and_then(|node| node.attribute("full-path"))
+        .ok_or_else(|| io::Error::other("no rootfile in container.xml"))?
+        .to_string();
+    let package = read_entry(&mut archive, &package_path)?;
+    let package = roxmltree::Document::parse(&package).map_err(io::Error::other)?;
+
+    let text_of = |name: &str| {
+        package
+            .descendants()
+            .find(|node| node.tag_name().name() == name)
+            .and_then(|node| node.text())
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)
+    };
+    let (title, author) = (text_of("title"), text_of("creator"));
+
+    // spine entries point into the manifest, hrefs are relative to the package document
+    let base = package_path
+        .rsplit_once('/')
+        .map(|(dir, _)| format!("{}/", dir))
+        .unwrap_or_default();
+    let hrefs = package
+        .descendants()
+        .filter(|node| node.has_tag_name("itemref"))
+        .filter_map(|itemref| {
+            let id = itemref.attribute("idref")?;
+            package
+                .descendants()
+                .find(|item| item.has_tag_name("item") && item.attribute("id") == Some(id))?
+                .attribute("href")
+        })
+        .map(|href| format!("{}{}", base, href))
+        .collect::<Vec<_>>();
+
+    let mut texts = vec![];
+    for href in hrefs {
+        if texts.len() >= pages {
+            break;
+        }
+        let Ok(markup) = read_entry(&mut archive, &href) else {
+            continue;
+        };
+        let text = strip_markup(&markup);
+        if !text.trim().is_empty() {
+            texts.push(text);
+        }
+    }
+    Ok(Document {
+        title,
+        author,
+        pages: texts,
+        ..Default::default()
+    })
+}
+
+fn read_entry<R: io::Read + io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> io::Result<String> {
+    let mut entry = archive.by_name(name).map_err(io::Error::other)?;
+    let mut text = String::new();
+    entry.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Text of an XHTML document. Not a parser: chapters use HTML entities XML parsers reject,
+/// and only words are needed for searching.
+pub fn strip_markup(markup: &str) -> String {
+    let body = markup
+        .find("<body")
+        .map(|start| &markup[start..])
+        .unwrap_or(markup);
+    let mut text = String::with_capacity(body.len());
+    let mut in_tag = false;
+    for c in body.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn truncate(text: &str, chars: usize) -> String {
+    match text.char_indices().nth(chars) {
+        Some((end, _)) => text[..end].to_string(),
+        None => text.to_string(),
+    }
+}
+
+/// Text around the first occurrence of `word` in `text`, ignoring case.
+pub fn snippet(text: &str, word: &str) -> String {
+    let lower = text.to_lowercase();
+    // lowercasing may change byte offsets, fall back to the start of the text then
+    let start = lower
+        .find(&word.to_lowercase())
+        .filter(|_| lower.len() == text.len())
+        .unwrap_or(0);
+    let from = text[..start]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CHARS / 3)
+        .map(|(index, _)| index)
+        .unwrap_or(0);
+    let snippet = truncate(&text[from..], SNIPPET_CHARS);
+    match from {
+        0 => snippet,
+        _ => format!("…{}", snippet),
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::document::{self, Document, DocumentKind};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentMatch {
+    pub path: PathBuf,
+    pub title: String,
+    /// Where the query was found, counting from 1, if not in the title or author.
+    pub page: Option<usize>,
+    /// Author, or page text around the match.
+    pub description: String,
+    pub score: f64,
+}
+
+/// Documents found under the configured roots, keyed by path.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct DocumentIndex {
+    documents: BTreeMap<PathBuf, Document>,
+}
+
+/// Documents under `roots` with their modification times, at most `max_depth` levels down.
+pub fn find_documents(roots: &[PathBuf], max_depth: usize) -> Vec<(PathBuf, u64)> {
+    let mut found = vec![];
+    for root in roots {
+        let walker = WalkBuilder::new(root).max_depth(Some(max_depth)).build();
+        for entry in walker.flatten() {
+            if DocumentKind::of(entry.path()).is_none() {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|modified| modified.as_secs())
+                .unwrap_or_default();
+            found.push((entry.into_path(), modified));
+        }
+    }
+    found
+}
+
+fn contains_words(text: &str, words: &[String]) -> bool {
+    let text = text.to_lowercase();
+    words.iter().all(|word| text.contains(word))
+}
+
+impl DocumentIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let data = std::fs::read(path)?;
+        serde_json::from_slice(&data).map_err(io::Error::other)
+    }
+
+    /// Write the index atomically so a crash never leaves a truncated file behind.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(self).map_err(io::Error::other)?)?;
+        std::fs::rename(tmp, path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&Document> {
+        self.documents.get(path)
+    }
+
+    /// Forget documents missing from `found` and return the ones to read again:
+    /// new files and files modified since they were read.
+    pub fn sync(&mut self, found: &[(PathBuf, u64)]) -> Vec<PathBuf> {
+        let found_paths = found
+            .iter()
+            .map(|(path, _)| path)
+            .collect::<std::collections::HashSet<_>>();
+        self.documents.retain(|path, _| found_paths.contains(path));
+        found
+            .iter()
+            .filter(|(path, modified)| {
+                self.documents
+                    .get(path)
+                    .is_none_or(|document| document.modified != *modified)
+            })
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    pub fn insert(&mut self, document: Document) {
+        self.documents.insert(document.path.clone(), document);
+    }
+
+    /// Documents with every word of `query` in their title, file name, author or pages,
+    /// best matches first.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<DocumentMatch> {
+        let words = query
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+        if words.is_empty() {
+            return vec![];
+        }
+
+        let mut found = self
+            .documents
+            .values()
+            .filter_map(|document| {
+                let title = document.display_title();
+                let file_name = document
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let author = document.author.clone().unwrap_or_default();
+                let (page, score) =
+                    if contains_words(&title, &words) || contains_words(&file_name, &words) {
+                        (None, 1.0)
+                    } else if contains_words(&format!("{} {}", title, author), &words) {
+                        (None, 0.8)
+                    } else {
+                        let page = document
+                            .pages
+                            .iter()
+                            .position(|page| contains_words(page, &words))?;
+                        (Some(page + 1), 0.5)
+                    };
+                let description = match page {
+                    Some(page) => document::snippet(&document.pages[page - 1], &words[0]),
+                    None => author,
+                };
+                Some(DocumentMatch {
+                    path: document.path.clone(),
+                    title,
+                    page,
+                    description,
+                    score,
+                })
+            })
+            .collect::<Vec<_>>();
+        found.sort_by(|a, b| b.score.total_cmp(&a.score));
+        found.truncate(limit);
+        found
+    }
+}
@@ -0,0 +1,330 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use glimpse_plugins_documents::{
+    document::Document,
+    index::{DocumentIndex, DocumentMatch, find_documents},
+};
+use glimpse_sdk::{
+    Action, ConfigField, ConfigKind, ConfigSchema, Context, Match, MatchAction, Metadata,
+    Permission, Plugin, PluginError, PowerProfile, Settings, run_plugin, setup_logging,
+};
+use serde::Deserialize;
+use tokio::sync::{mpsc, watch};
+
+const DEFAULT_MAX_RESULTS: usize = 20;
+const DEFAULT_MAX_DEPTH: usize = 4;
+const DEFAULT_PAGES: usize = 1;
+const DEFAULT_ROOTS: &[&str] = &["~/Documents", "~/Downloads"];
+const DEFAULT_PAGE_COMMAND: &[&str] = &["evince", "--page-index={page}", "{path}"];
+/// Documents rarely change, a periodic rescan is cheaper than watching every folder.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(600);
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+struct DocumentsSettings {
+    max_results: usize,
+    max_depth: usize,
+    /// Pages of text indexed per document.
+    pages: usize,
+    roots: Vec<String>,
+    /// Viewer command opening a document at a page, `{path}` and `{page}` are replaced.
+    page_command: Vec<String>,
+}
+
+impl Default for DocumentsSettings {
+    fn default() -> Self {
+        Self {
+            max_results: DEFAULT_MAX_RESULTS,
+            max_depth: DEFAULT_MAX_DEPTH,
+            pages: DEFAULT_PAGES,
+            roots: DEFAULT_ROOTS.iter().map(|root| root.to_string()).collect(),
+            page_command: DEFAULT_PAGE_COMMAND
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl DocumentsSettings {
+    /// Roots as absolute paths; `~` and relative roots resolve against the home directory.
+    fn root_paths(&self, home: &Path) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = vec![];
+        for root in &self.roots {
+            let path = match root.strip_prefix('~') {
+                Some(rest) => home.join(rest.trim_start_matches('/')),
+                None => home.join(root),
+            };
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        paths
+    }
+
+    /// The page command for `path` at `page`, `None` if no viewer is configured.
+    fn open_at_page(&self, path: &Path, page: usize) -> Option<Action> {
+        let (command, args) = self.page_command.split_first()?;
+        let path = path.to_string_lossy();
+        let args = args
+            .iter()
+            .map(|arg| {
+                arg.replace("{path}", &path)
+                    .replace("{page}", &page.to_string())
+            })
+            .collect();
+        Some(Action::Exec {
+            command: command.clone(),
+            args,
+        })
+    }
+}
+
+fn cache_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("glimpse")
+        .join("documents-index.json")
+}
+
+struct DocumentsPlugin {
+    home: PathBuf,
+    index: Arc<RwLock<DocumentIndex>>,
+    settings: Settings<DocumentsSettings>,
+    power: watch::Sender<PowerProfile>,
+}
+
+/// Bring the index up to date with the documents under the roots, reading only new and
+/// changed files. Stops early when the power profile drops to low power.
+async fn reindex(
+    index: &Arc<RwLock<DocumentIndex>>,
+    settings: &DocumentsSettings,
+    home: &Path,
+    power: &watch::Receiver<PowerProfile>,
+) {
+    let roots = settings.root_paths(home);
+    let max_depth = settings.max_depth;
+    let Ok(found) = tokio::task::spawn_blocking(move || find_documents(&roots, max_depth)).await
+    else {
+        return;
+    };
+    let stale = index.write().unwrap().sync(&found);
+    tracing::info!("found {} documents, {} to read", found.len(), stale.len());
+
+    for path in stale {
+        if *power.borrow() == PowerProfile::LowPower {
+            tracing::info!("pausing document indexing in low power profile");
+            break;
+        }
+        let Some(modified) = found
+            .iter()
+            .find(|(found, _)| found == &path)
+            .map(|(_, modified)| *modified)
+        else {
+            continue;
+        };
+        let pages = settings.pages;
+        let read_path = path.clone();
+        match tokio::task::spawn_blocking(move || Document::read(&read_path, modified, pages)).await
+        {
+            Ok(Ok(document)) => index.write().unwrap().insert(document),
+            Ok(Err(err)) => {
+                tracing::debug!("failed to read {}: {}", path.display(), err);
+                // keep the file name searchable and do not retry until it changes
+                index.write().unwrap().insert(Document {
+                    path,
+                    modified,
+                    ..Default::default()
+                });
+            }
+            Err(_) => break,
+        }
+    }
+
+    let snapshot = index.read().unwrap().clone();
+    let saved = tokio::task::spawn_blocking(move || snapshot.save(&cache_path())).await;
+    if let Ok(Err(err)) = saved {
+        tracing::warn!("failed to save document index: {}", err);
+    }
+}
+
+impl DocumentsPlugin {
+    fn new(home: PathBuf) -> Self {
+        Self {
+            home,
+            index: Arc::new(RwLock::new(DocumentIndex::new())),
+            settings: Settings::default(),
+            power: watch::Sender::new(PowerProfile::Normal),
+        }
+    }
+
+    fn to_match(&self, settings: &DocumentsSettings, found: DocumentMatch) -> Match {
+        let mut actions = vec![];
+        if let Some(page) = found.page
+            && let Some(action) = settings.open_at_page(&found.path, page)
+        {
+            actions.push(MatchAction {
+                title: format!("Open at page {}", page),
+                close_on_action: true,
+                action,
+                alternates: vec![],
+                requires_confirmation: false,
+                confirmation_prompt: None,
+                expand: vec![],
+            });
+        }
+        actions.push(MatchAction {
+            title: "Open".to_string(),
+            close_on_action: true,
+            action: Action::open_file(&found.path),
+            alternates: vec![],
+            requires_confirmation: false,
+            confirmation_prompt: None,
+            expand: vec![],
+        });
+
+        let description = match found.path.strip_prefix(&self.home) {
+            _ if !found.description.is_empty() => found.description,
+            Ok(rel) => format!("~/{}", rel.display()),
+            Err(_) => found.path.to_string_lossy().to_string(),
+        };
+        Match {
+            title: found.title,
+            description,
+            actions,
+            score: found.score,
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for DocumentsPlugin {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            id: "me.aresa.glimpse.documents".to_string(),
+            name: "Documents".to_string(),
+            version: "0.1.0".to_string(),
+            description: "Finds PDFs and epubs by title, author and first pages.".to_string(),
+            author: "Alex Oleshkevich <alex.oleshkevich@gmail.com>".to_string(),
+            permissions: vec![Permission::Exec, Permission::HomeRead],
+            config_schema: Some(
+                ConfigSchema::new()
+                    .field(
+                        ConfigField::new("max_results", ConfigKind::Integer)
+                            .default_value(DEFAULT_MAX_RESULTS)
+                            .description("Maximum number of documents returned per search"),
+                    )
+                    .field(
+                        ConfigField::new("max_depth", ConfigKind::Integer)
+                            .default_value(DEFAULT_MAX_DEPTH)
+                            .description("How deep to look for documents below each root"),
+                    )
+                    .field(
+                        ConfigField::new("pages", ConfigKind::Integer)
+                            .default_value(DEFAULT_PAGES)
+                            .description("Pages of text to index per document"),
+                    )
+                    .field(
+                        ConfigField::new("roots", ConfigKind::StringList)
+                            .default_value(DEFAULT_ROOTS.to_vec())
+                            .description("Directories to index, relative to the home directory"),
+                    )
+                    .field(
+                        ConfigField::new("page_command", ConfigKind::StringList)
+                            .default_value(DEFAULT_PAGE_COMMAND.to_vec())
+                            .description(
+                                "Viewer opening a document at {page}, empty to only open files",
+                            ),
+                    ),
+            ),
+            ..Default::default()
+        }
+    }
+
+    async fn configure(&self, config: serde_json::Value) -> Result<(), PluginError> {
+        self.settings.apply(config)
+    }
+
+    async fn power_profile_changed(&self, profile: PowerProfile) -> Result<(), PluginError> {
+        self.power.send_replace(profile);
+        Ok(())
+    }
+
+    async fn initialize(&self, _context: &Context) -> Result<(), PluginError> {
+        match DocumentIndex::load(&cache_path()) {
+            Ok(cached) => {
+                tracing::info!("loaded {} cached documents", cached.len());
+                *self.index.write().unwrap() = cached;
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => tracing::warn!("failed to load document index: {}", err),
+        }
+
+        let (changed_tx, mut changed_rx) = mpsc::unbounded_channel();
+        self.settings.on_change(move |settings| {
+            let _ = changed_tx.send(settings.clone());
+        });
+
+        let index = self.index.clone();
+        let home = self.home.clone();
+        let mut power = self.power.subscribe();
+        let mut settings = self.settings.get();
+        tokio::spawn(async move {
+            let mut rescan = tokio::time::interval(RESCAN_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = rescan.tick() => {}
+                    changed = changed_rx.recv() => {
+                        let Some(changed) = changed else { break };
+                        settings = changed;
+                    }
+                    changed = power.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                        // catch up with what was skipped on a low battery
+                        if *power.borrow_and_update() == PowerProfile::LowPower {
+                            continue;
+                        }
+                    }
+                }
+                if *power.borrow() == PowerProfile::Normal {
+                    reindex(&index, &settings, &home, &power).await;
+                }
+            }
+        });
+        Ok(())
+    }
+
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        let settings = self.settings.get();
+        let found = self
+            .index
+            .read()
+            .unwrap()
+            .search(&query, settings.max_results);
+        Ok(found
+            .into_iter()
+            .map(|found| self.to_match(&settings, found))
+            .collect())
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    setup_logging(tracing::Level::INFO);
+    let Some(home) = dirs::home_dir() else {
+        return Err("cannot determine the home directory".into());
+    };
+    if let Err(err) = run_plugin(DocumentsPlugin::new(home)).await {
+        tracing::error!("error running plugin: {}", err);
+    }
+    Ok(())
+}
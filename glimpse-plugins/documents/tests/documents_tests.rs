@@ -0,0 +1,167 @@
+use std::{io::Write, path::Path};
+
+use glimpse_plugins_documents::{
+    document::{self, Document, DocumentKind},
+    index::{DocumentIndex, find_documents},
+};
+
+fn create_epub(path: &Path) {
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    let options = zip::write::SimpleFileOptions::default();
+    let files = [
+        (
+            "META-INF/container.xml",
+            r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles>
+</container>"#,
+        ),
+        (
+            "OEBPS/content.opf",
+            r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" xmlns:dc="http://purl.org/dc/elements/1.1/" version="3.0">
+  <metadata><dc:title>The Rust Book</dc:title><dc:creator>Steve Klabnik</dc:creator></metadata>
+  <manifest>
+    <item id="c2" href="text/ch2.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c1" href="text/ch1.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine><itemref idref="c1"/><itemref idref="c2"/></spine>
+</package>"#,
+        ),
+        (
+            "OEBPS/text/ch1.xhtml",
+            "<html><head><title>x</title></head><body><h1>Getting&nbsp;Started</h1>\n<p>Install rustup &amp; cargo.</p></body></html>",
+        ),
+        (
+            "OEBPS/text/ch2.xhtml",
+            "<html><body><p>Ownership rules</p></body></html>",
+        ),
+    ];
+    for (name, content) in files {
+        writer.start_file(name, options).unwrap();
+        writer.write_all(content.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap();
+}
+
+fn document(path: &str, title: Option<&str>, pages: &[&str]) -> Document {
+    Document {
+        path: path.into(),
+        modified: 1,
+        title: title.map(str::to_string),
+        author: Some("Jane Doe".to_string()),
+        pages: pages.iter().map(|page| page.to_string()).collect(),
+    }
+}
+
+#[test]
+fn test_kind_from_extension() {
+    assert_eq!(
+        DocumentKind::of(Path::new("a/Book.EPUB")),
+        Some(DocumentKind::Epub)
+    );
+    assert_eq!(
+        DocumentKind::of(Path::new("paper.pdf")),
+        Some(DocumentKind::Pdf)
+    );
+    assert_eq!(DocumentKind::of(Path::new("notes.txt")), None);
+}
+
+#[test]
+fn test_parse_pdfinfo() {
+    let output = "Title:           Annual Report\nAuthor:          \nPages:           12\n";
+    assert_eq!(
+        document::parse_pdfinfo(output),
+        (Some("Annual Report".to_string()), None)
+    );
+}
+
+#[test]
+fn test_read_epub_metadata_and_chapters() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("book.epub");
+    create_epub(&path);
+
+    let read = Document::read(&path, 42, 1).unwrap();
+    assert_eq!(read.title.as_deref(), Some("The Rust Book"));
+    assert_eq!(read.author.as_deref(), Some("Steve Klabnik"));
+    assert_eq!(read.pages, vec!["Getting Started Install rustup & cargo."]);
+    assert_eq!(read.modified, 42);
+
+    assert_eq!(Document::read(&path, 42, 5).unwrap().pages.len(), 2);
+}
+
+#[test]
+fn test_snippet_around_match() {
+    let text = format!("{} needle in the text", "word ".repeat(40));
+    let snippet = document::snippet(&text, "NEEDLE");
+    assert!(snippet.starts_with('…'));
+    assert!(snippet.contains("needle in the text"));
+    assert_eq!(document::snippet("short text", "missing"), "short text");
+}
+
+#[test]
+fn test_search_ranks_titles_over_pages() {
+    let mut index = DocumentIndex::new();
+    index.insert(document(
+        "/docs/a.pdf",
+        Some("Invoice March"),
+        &["total due"],
+    ));
+    index.insert(document(
+        "/docs/b.pdf",
+        None,
+        &["cover", "the march invoice is attached"],
+    ));
+    index.insert(document("/docs/c.pdf", Some("Other"), &["nothing"]));
+
+    let found = index.search("march invoice", 10);
+    assert_eq!(found.len(), 2);
+    assert_eq!(
+        (found[0].title.as_str(), found[0].page),
+        ("Invoice March", None)
+    );
+    assert_eq!(found[0].description, "Jane Doe");
+    assert_eq!((found[1].title.as_str(), found[1].page), ("b.pdf", Some(2)));
+    assert_eq!(found[1].description, "the march invoice is attached");
+    assert_eq!(index.search("jane", 10).len(), 3);
+    assert!(index.search("  ", 10).is_empty());
+}
+
+#[test]
+fn test_sync_returns_new_and_changed_documents() {
+    let mut index = DocumentIndex::new();
+    index.insert(document("/docs/kept.pdf", None, &[]));
+    index.insert(document("/docs/changed.pdf", None, &[]));
+    index.insert(document("/docs/deleted.pdf", None, &[]));
+
+    let stale = index.sync(&[
+        ("/docs/kept.pdf".into(), 1),
+        ("/docs/changed.pdf".into(), 2),
+        ("/docs/new.epub".into(), 1),
+    ]);
+
+    assert_eq!(
+        stale,
+        vec![Path::new("/docs/changed.pdf"), Path::new("/docs/new.epub")]
+    );
+    assert_eq!(index.len(), 2);
+    assert!(index.get(Path::new("/docs/deleted.pdf")).is_none());
+}
+
+#[test]
+fn test_find_documents_and_save() {
+    let dir = tempfile::tempdir().unwrap();
+    create_epub(&dir.path().join("book.epub"));
+    std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+    let found = find_documents(&[dir.path().to_path_buf()], 2);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].0, dir.path().join("book.epub"));
+
+    let mut index = DocumentIndex::new();
+    index.insert(document("/docs/a.pdf", Some("A"), &["text"]));
+    let cache = dir.path().join("cache").join("documents-index.json");
+    index.save(&cache).unwrap();
+    assert_eq!(DocumentIndex::load(&cache).unwrap(), index);
+}
@@ -0,0 +1,257 @@
+use std::{
+    collections::BTreeSet,
+    io,
+    path::{Path, PathBuf},
+};
+
+use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
+use ignore::{
+    WalkBuilder,
+    overrides::{Override, OverrideBuilder},
+};
+use serde::{Deserialize, Serialize};
+
+/// Which entries under a root are indexed, on top of the hidden and ignored ones skipped
+/// anyway.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Scope {
+    /// Gitignore-style globs of entries to skip, matched against paths relative to the root.
+    pub ignore: Vec<String>,
+    /// Levels below the root to descend, `None` for no limit.
+    pub max_depth: Option<usize>,
+}
+
+/// Paths under a root directory, stored relative to the root.
+///
+/// Hidden entries and anything excluded by `.gitignore`/`.ignore` files are skipped, as are
+/// entries outside the index's [`Scope`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct FileIndex {
+    root: PathBuf,
+    // indexes cached before scopes existed were built without limits
+    #[serde(default)]
+    scope: Scope,
+    files: BTreeSet<String>,
+    dirs: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileMatch {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub score: i64,
+}
+
+impl FileIndex {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_scope(mut self, scope: Scope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Walk the whole root.
+    pub fn build(root: impl Into<PathBuf>) -> Self {
+        Self::build_scoped(root, Scope::default())
+    }
+
+    /// Walk the root within `scope`.
+    pub fn build_scoped(root: impl Into<PathBuf>, scope: Scope) -> Self {
+        let mut index = Self::new(root).with_scope(scope);
+        let root = index.root.clone();
+        index.scan(&root, None);
+        index
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let data = std::fs::read(path)?;
+        serde_json::from_slice(&data).map_err(io::Error::other)
+    }
+
+    /// Write the index atomically so a crash never leaves a truncated file behind.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(self).map_err(io::Error::other)?)?;
+        std::fs::rename(tmp, path)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn scope(&self) -> &Scope {
+        &self.scope
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len() + self.dirs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.dirs.is_empty()
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.relative(path)
+            .is_some_and(|rel| self.files.contains(&rel) || self.dirs.contains(&rel))
+    }
+
+    /// Indexed directories as absolute paths.
+    pub fn dirs(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        std::iter::once(self.root.clone()).chain(self.dirs.iter().map(|rel| self.root.join(rel)))
+    }
+
+    /// Bring the index up to date after `path` was created, changed or removed.
+    ///
+    /// Returns directories that appeared, so the caller can start watching them.
+    pub fn update(&mut self, path: &Path) -> Vec<PathBuf> {
+        let Some(rel) = self.relative(path) else {
+            return vec![];
+        };
+        if rel.is_empty() {
+            return vec![];
+        }
+
+        self.remove(&rel);
+        if !path.exists() {
+            return vec![];
+        }
+
+        // rescanning the parent applies the ignore rules to the new entry
+        let parent = path.parent().unwrap_or(&self.root).to_path_buf();
+        self.scan(&parent, Some(1));
+        if !self.dirs.contains(&rel) {
+            return vec![];
+        }
+
+        self.scan(path, None);
+        let mut added = vec![path.to_path_buf()];
+        added.extend(
+            self.dirs
+                .iter()
+                .filter(|dir| dir.starts_with(&format!("{}/", rel)))
+                .map(|dir| self.root.join(dir)),
+        );
+        added
+    }
+
+    /// Fuzzy match file names first, then full relative paths.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<FileMatch> {
+        let query = query.trim();
+        if query.is_empty() {
+            return vec![];
+        }
+
+        let matcher = SkimMatcherV2::default().smart_case();
+        let candidates = self
+            .files
+            .iter()
+            .map(|rel| (rel, false))
+            .chain(self.dirs.iter().map(|rel| (rel, true)));
+
+        let mut matches = candidates
+            .filter_map(|(rel, is_dir)| {
+                let name = rel.rsplit('/').next().unwrap_or(rel);
+                let score = match matcher.fuzzy_match(name, query) {
+                    // a hit on the name beats the same hit somewhere in the path
+                    Some(score) => score * 2,
+                    None => matcher.fuzzy_match(rel, query)?,
+                };
+                Some(FileMatch {
+                    path: self.root.join(rel),
+                    is_dir,
+                    score,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        matches.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.path.as_os_str().len().cmp(&b.path.as_os_str().len()))
+        });
+        matches.truncate(limit);
+        matches
+    }
+
+    /// The scope's ignore globs as walker overrides, invalid globs left out.
+    fn ignored(&self) -> Override {
+        let mut builder = OverrideBuilder::new(&self.root);
+        for glob in &self.scope.ignore {
+            if let Err(err) = builder.add(&format!("!{}", glob)) {
+                tracing::warn!("skipping ignore glob {}: {}", glob, err);
+            }
+        }
+        builder.build().unwrap_or_else(|err| {
+            tracing::warn!("ignoring the ignore globs: {}", err);
+            Override::empty()
+        })
+    }
+
+    fn relative(&self, path: &Path) -> Option<String> {
+        let rel = path.strip_prefix(&self.root).ok()?;
+        Some(rel.to_string_lossy().to_string())
+    }
+
+    fn remove(&mut self, rel: &str) {
+        let prefix = format!("{}/", rel);
+        self.files.remove(rel);
+        self.dirs.remove(rel);
+        self.files.retain(|file| !file.starts_with(&prefix));
+        self.dirs.retain(|dir| !dir.starts_with(&prefix));
+    }
+
+    fn scan(&mut self, dir: &Path, max_depth: Option<usize>) {
+        // the scope limits depth below the root, `dir` may already be some levels down
+        let below_root = self
+            .relative(dir)
+            .filter(|rel| !rel.is_empty())
+            .map_or(0, |rel| rel.split('/').count());
+        let max_depth = match (max_depth, self.scope.max_depth) {
+            (_, Some(limit)) if below_root > limit => return,
+            (Some(depth), Some(limit)) => Some(depth.min(limit - below_root)),
+            (None, Some(limit)) => Some(limit - below_root),
+            (depth, None) => depth,
+        };
+
+        let walker = WalkBuilder::new(dir)
+            .hidden(true)
+            .git_ignore(true)
+            .git_global(true)
+            .require_git(false)
+            .follow_links(false)
+            .max_depth(max_depth)
+            .overrides(self.ignored())
+            .build();
+
+        for entry in walker {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    tracing::debug!("skipping entry: {}", err);
+                    continue;
+                }
+            };
+            let Some(rel) = self.relative(entry.path()) else {
+                continue;
+            };
+            if rel.is_empty() {
+                continue;
+            }
+
+            match entry.file_type() {
+                Some(file_type) if file_type.is_dir() => self.dirs.insert(rel),
+                Some(_) => self.files.insert(rel),
+                None => false,
+            };
+        }
+    }
+}
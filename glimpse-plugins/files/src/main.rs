@@ -0,0 +1,449 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use glimpse_plugins_files::index::{FileIndex, FileMatch, Scope};
+use glimpse_sdk::{
+    Action, AlternateAction, ConfigField, ConfigKind, ConfigSchema, Context, Icon, Match,
+    MatchAction, Metadata, Modifiers, Permission, Plugin, PluginError, PowerProfile, Settings,
+    run_plugin, setup_logging,
+};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher, event::ModifyKind};
+use serde::Deserialize;
+use tokio::sync::{mpsc, watch};
+
+const DEFAULT_MAX_RESULTS: usize = 20;
+const DEFAULT_ROOT: &str = "~";
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+struct FilesSettings {
+    max_results: usize,
+    roots: Vec<String>,
+    /// Gitignore-style globs of files and folders left out of the index.
+    ignore: Vec<String>,
+    /// Levels below each root to index, 0 for no limit.
+    max_depth: usize,
+    thumbnails: bool,
+}
+
+impl Default for FilesSettings {
+    fn default() -> Self {
+        Self {
+            max_results: DEFAULT_MAX_RESULTS,
+            roots: vec![DEFAULT_ROOT.to_string()],
+            ignore: vec![],
+            max_depth: 0,
+            thumbnails: true,
+        }
+    }
+}
+
+impl FilesSettings {
+    /// Roots as absolute paths; `~` and relative roots resolve against the home directory.
+    fn root_paths(&self, home: &Path) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = vec![];
+        for root in &self.roots {
+            let path = match root.strip_prefix('~') {
+                Some(rest) => home.join(rest.trim_start_matches('/')),
+                None => home.join(root),
+            };
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        paths
+    }
+
+    fn scope(&self) -> Scope {
+        Scope {
+            ignore: self.ignore.clone(),
+            max_depth: (self.max_depth > 0).then_some(self.max_depth),
+        }
+    }
+}
+
+struct FilesPlugin {
+    home: PathBuf,
+    indexes: Arc<RwLock<Vec<FileIndex>>>,
+    settings: Settings<FilesSettings>,
+    power: watch::Sender<PowerProfile>,
+}
+
+/// Owns the watcher and keeps the indexes of the configured roots up to date.
+struct Indexer {
+    indexes: Arc<RwLock<Vec<FileIndex>>>,
+    watcher: RecommendedWatcher,
+    dirty: bool,
+    /// Changes seen while paused, applied on resume.
+    deferred: HashSet<PathBuf>,
+}
+
+impl Indexer {
+    fn start(
+        indexes: Arc<RwLock<Vec<FileIndex>>>,
+    ) -> Result<(Self, mpsc::UnboundedReceiver<PathBuf>), PluginError> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let event = match event {
+                Ok(event) => event,
+                Err(err) => {
+                    tracing::warn!("watch error: {}", err);
+                    return;
+                }
+            };
+            let relevant = matches!(
+                event.kind,
+                EventKind::Create(_)
+                    | EventKind::Remove(_)
+                    | EventKind::Modify(ModifyKind::Name(_))
+            );
+            if relevant {
+                for path in event.paths {
+                    let _ = tx.send(path);
+                }
+            }
+        })
+        .map_err(|e| PluginError::Other(e.to_string()))?;
+
+        let indexer = Self {
+            indexes,
+            watcher,
+            dirty: false,
+            deferred: HashSet::new(),
+        };
+        Ok((indexer, rx))
+    }
+
+    fn cache_path(root: &Path) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        root.hash(&mut hasher);
+        dirs::cache_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("glimpse")
+            .join("files-index")
+            .join(format!("{:016x}.json", hasher.finish()))
+    }
+
+    /// Watch indexed directories one by one, so ignored trees cost no inotify watches.
+    fn watch(&mut self, dirs: impl IntoIterator<Item = PathBuf>) {
+        for dir in dirs {
+            if let Err(err) = self.watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                tracing::warn!("failed to watch {}: {}", dir.display(), err);
+            }
+        }
+    }
+
+    /// Switch to `roots` indexed within `scope`. Roots already indexed within it are kept as
+    /// they are, others serve cached entries right away, then are rebuilt in the background
+    /// and the fresh index swapped in.
+    async fn index(&mut self, roots: Vec<PathBuf>, scope: Scope) {
+        let mut previous = std::mem::take(&mut *self.indexes.write().unwrap());
+        let mut indexes = vec![];
+        let mut stale = vec![];
+        for root in &roots {
+            let kept = previous
+                .iter()
+                .position(|index| index.root() == root && index.scope() == &scope);
+            if let Some(position) = kept {
+                indexes.push(previous.swap_remove(position));
+                continue;
+            }
+            stale.push(indexes.len());
+            indexes.push(match FileIndex::load(&Self::cache_path(root)) {
+                Ok(cached) if cached.root() == root && cached.scope() == &scope => {
+                    tracing::info!(
+                        "loaded {} cached entries under {}",
+                        cached.len(),
+                        root.display()
+                    );
+                    cached
+                }
+                _ => FileIndex::new(root).with_scope(scope.clone()),
+            });
+        }
+        for dir in previous.iter().flat_map(|index| index.dirs()) {
+            let _ = self.watcher.unwatch(&dir);
+        }
+        *self.indexes.write().unwrap() = indexes;
+
+        for position in stale {
+            let root = roots[position].clone();
+            let (build_root, build_scope) = (root.clone(), scope.clone());
+            let Ok(fresh) = tokio::task::spawn_blocking(move || {
+                FileIndex::build_scoped(build_root, build_scope)
+            })
+            .await
+            else {
+                tracing::error!("failed to build file index for {}", root.display());
+                continue;
+            };
+            tracing::info!("indexed {} entries under {}", fresh.len(), root.display());
+
+            let dirs = fresh.dirs().collect::<Vec<_>>();
+            self.indexes.write().unwrap()[position] = fresh;
+            self.watch(dirs);
+        }
+        self.dirty = true;
+    }
+
+    fn update(&mut self, path: &Path) {
+        let added = self
+            .indexes
+            .write()
+            .unwrap()
+            .iter_mut()
+            .filter(|index| path.starts_with(index.root()))
+            .flat_map(|index| index.update(path))
+            .collect::<Vec<_>>();
+        self.watch(added);
+        self.dirty = true;
+    }
+
+    /// Catch up with the changes seen while paused.
+    fn resume(&mut self) {
+        let deferred = std::mem::take(&mut self.deferred);
+        tracing::info!("resuming indexing, {} deferred changes", deferred.len());
+        for path in deferred {
+            self.update(&path);
+        }
+    }
+
+    async fn save(&mut self) {
+        if !std::mem::take(&mut self.dirty) {
+            return;
+        }
+        let snapshot = self.indexes.read().unwrap().clone();
+        let saved = tokio::task::spawn_blocking(move || {
+            snapshot
+                .iter()
+                .try_for_each(|index| index.save(&Self::cache_path(index.root())))
+        })
+        .await;
+        if let Ok(Err(err)) = saved {
+            tracing::warn!("failed to save file index: {}", err);
+        }
+    }
+}
+
+impl FilesPlugin {
+    fn new(home: PathBuf) -> Self {
+        Self {
+            home,
+            indexes: Arc::new(RwLock::new(vec![])),
+            settings: Settings::default(),
+            power: watch::Sender::new(PowerProfile::Normal),
+        }
+    }
+
+    fn to_match(&self, home: &Path, file: FileMatch, thumbnails: bool, best_score: i64) -> Match {
+        let path = file.path.to_string_lossy().to_string();
+        let display_path = match file.path.strip_prefix(home) {
+            Ok(rel) => format!("~/{}", rel.display()),
+            Err(_) => path.clone(),
+        };
+        let parent = file
+            .path
+            .parent()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.clone());
+
+        Match {
+            title: file
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.clone()),
+            description: display_path,
+            // the daemon renders thumbnails of images and videos, other files get none
+            icon: (thumbnails && !file.is_dir).then(|| Icon::thumbnail(path.clone())),
+            actions: vec![
+                MatchAction {
+                    title: "Open".to_string(),
+                    close_on_action: true,
+                    action: Action::open_file(&path),
+                    alternates: vec![AlternateAction {
+                        modifiers: Modifiers {
+                            shift: true,
+                            ..Default::default()
+                        },
+                        title: "Open containing folder".to_string(),
+                        action: Action::open_file(&parent),
+                    }],
+                    requires_confirmation: false,
+                    confirmation_prompt: None,
+                    expand: vec![],
+                },
+                MatchAction {
+                    title: "Copy path".to_string(),
+                    close_on_action: true,
+                    action: Action::Clipboard { text: path.into() },
+                    alternates: vec![],
+                    requires_confirmation: false,
+                    confirmation_prompt: None,
+                    expand: vec![],
+                },
+            ],
+            score: file.score as f64 / best_score.max(1) as f64,
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for FilesPlugin {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            id: "me.aresa.glimpse.files".to_string(),
+            name: "Files".to_string(),
+            version: "0.1.0".to_string(),
+            description: "Finds files and folders in the configured directories.".to_string(),
+            author: "Alex Oleshkevich <alex.oleshkevich@gmail.com>".to_string(),
+            permissions: vec![Permission::Clipboard, Permission::HomeRead],
+            config_schema: Some(
+                ConfigSchema::new()
+                    .field(
+                        ConfigField::new("max_results", ConfigKind::Integer)
+                            .default_value(DEFAULT_MAX_RESULTS)
+                            .description("Maximum number of files returned per search"),
+                    )
+                    .field(
+                        ConfigField::new("roots", ConfigKind::StringList)
+                            .default_value(vec![DEFAULT_ROOT])
+                            .description("Directories to index, relative to the home directory"),
+                    )
+                    .field(
+                        ConfigField::new("ignore", ConfigKind::StringList)
+                            .default_value(Vec::<String>::new())
+                            .description("Files and folders to leave out, as gitignore globs"),
+                    )
+                    .field(
+                        ConfigField::new("max_depth", ConfigKind::Integer)
+                            .default_value(0)
+                            .description("Folder levels to index below each root, 0 for all"),
+                    )
+                    .field(
+                        ConfigField::new("thumbnails", ConfigKind::Boolean)
+                            .default_value(true)
+                            .description("Show thumbnails of images and videos"),
+                    ),
+            ),
+            ..Default::default()
+        }
+    }
+
+    async fn configure(&self, config: serde_json::Value) -> Result<(), PluginError> {
+        self.settings.apply(config)
+    }
+
+    async fn power_profile_changed(&self, profile: PowerProfile) -> Result<(), PluginError> {
+        self.power.send_replace(profile);
+        Ok(())
+    }
+
+    async fn initialize(&self, _context: &Context) -> Result<(), PluginError> {
+        let (mut indexer, mut events) = Indexer::start(self.indexes.clone())?;
+        let mut power = self.power.subscribe();
+
+        // reindex when the roots or their scope change, other settings are read on every search
+        let (scopes_tx, mut scopes_rx) = mpsc::unbounded_channel();
+        let home = self.home.clone();
+        self.settings.on_change(move |settings| {
+            let _ = scopes_tx.send((settings.root_paths(&home), settings.scope()));
+        });
+
+        let settings = self.settings.get();
+        let mut scopes = (settings.root_paths(&self.home), settings.scope());
+        tokio::spawn(async move {
+            indexer.index(scopes.0.clone(), scopes.1.clone()).await;
+
+            let mut save_timer = tokio::time::interval(SAVE_INTERVAL);
+            loop {
+                tokio::select! {
+                    changed = scopes_rx.recv() => {
+                        let Some(changed) = changed else { break };
+                        if changed == scopes {
+                            continue;
+                        }
+                        tracing::info!("roots changed to {:?} within {:?}, reindexing", changed.0, changed.1);
+                        scopes = changed;
+                        indexer.index(scopes.0.clone(), scopes.1.clone()).await;
+                    }
+                    path = events.recv() => {
+                        let Some(path) = path else { break };
+                        // on a low battery, only remember what changed
+                        let profile = *power.borrow();
+                        match profile {
+                            PowerProfile::Normal => indexer.update(&path),
+                            PowerProfile::LowPower => {
+                                indexer.deferred.insert(path);
+                            }
+                        }
+                    }
+                    changed = power.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                        let profile = *power.borrow_and_update();
+                        if profile == PowerProfile::Normal {
+                            indexer.resume();
+                        } else {
+                            tracing::info!("pausing indexing in low power profile");
+                        }
+                    }
+                    _ = save_timer.tick() => {
+                        if *power.borrow() == PowerProfile::Normal {
+                            indexer.save().await;
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        let indexes = self.indexes.clone();
+        let settings = self.settings.get();
+        let limit = settings.max_results;
+        let found = tokio::task::spawn_blocking(move || {
+            let mut found = indexes
+                .read()
+                .unwrap()
+                .iter()
+                .flat_map(|index| index.search(&query, limit))
+                .collect::<Vec<_>>();
+            found.sort_by_key(|file| std::cmp::Reverse(file.score));
+            found.truncate(limit);
+            found
+        })
+        .await
+        .map_err(|e| PluginError::Other(e.to_string()))?;
+
+        let best_score = found.first().map(|f| f.score).unwrap_or(1);
+        Ok(found
+            .into_iter()
+            .map(|file| self.to_match(&self.home, file, settings.thumbnails, best_score))
+            .collect())
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    setup_logging(tracing::Level::INFO);
+    let Some(home) = dirs::home_dir() else {
+        return Err("cannot determine the home directory".into());
+    };
+    if let Err(err) = run_plugin(FilesPlugin::new(home)).await {
+        tracing::error!("error running plugin: {}", err);
+    }
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use std::{fs, path::Path};
+
+use glimpse_plugins_files::index::{FileIndex, Scope};
+
+fn touch(root: &Path, rel: &str) {
+    let path = root.join(rel);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, "").unwrap();
+}
+
+fn create_tree() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    touch(dir.path(), "Documents/report-2024.pdf");
+    touch(dir.path(), "Documents/notes.txt");
+    touch(dir.path(), "projects/glimpse/Cargo.toml");
+    touch(dir.path(), "projects/glimpse/target/debug/glimpsed");
+    touch(dir.path(), ".config/secret.toml");
+    fs::write(dir.path().join("projects/glimpse/.gitignore"), "target/\n").unwrap();
+    dir
+}
+
+#[test]
+fn test_build_respects_ignore_rules() {
+    let dir = create_tree();
+    let index = FileIndex::build(dir.path());
+
+    assert!(index.contains(&dir.path().join("Documents/notes.txt")));
+    assert!(index.contains(&dir.path().join("projects/glimpse")));
+    assert!(!index.contains(&dir.path().join(".config/secret.toml")));
+    assert!(!index.contains(&dir.path().join("projects/glimpse/target/debug/glimpsed")));
+}
+
+#[test]
+fn test_search_prefers_file_name_matches() {
+    let dir = create_tree();
+    let index = FileIndex::build(dir.path());
+
+    let matches = index.search("report", 10);
+    assert_eq!(
+        matches[0].path,
+        dir.path().join("Documents/report-2024.pdf")
+    );
+    assert!(!matches[0].is_dir);
+
+    let matches = index.search("docnotes", 10);
+    assert_eq!(matches[0].path, dir.path().join("Documents/notes.txt"));
+}
+
+#[test]
+fn test_search_limit_and_empty_query() {
+    let dir = create_tree();
+    let index = FileIndex::build(dir.path());
+
+    assert!(index.search("  ", 10).is_empty());
+    assert_eq!(index.search("o", 2).len(), 2);
+}
+
+#[test]
+fn test_update_adds_new_entries() {
+    let dir = create_tree();
+    let mut index = FileIndex::build(dir.path());
+
+    touch(dir.path(), "Music/album/track.flac");
+    let added = index.update(&dir.path().join("Music"));
+
+    assert!(index.contains(&dir.path().join("Music/album/track.flac")));
+    assert!(added.contains(&dir.path().join("Music")));
+    assert!(added.contains(&dir.path().join("Music/album")));
+}
+
+#[test]
+fn test_update_skips_ignored_entries() {
+    let dir = create_tree();
+    let mut index = FileIndex::build(dir.path());
+
+    touch(dir.path(), "projects/glimpse/target/release/glimpsed");
+    let added = index.update(&dir.path().join("projects/glimpse/target"));
+
+    assert!(added.is_empty());
+    assert!(!index.contains(&dir.path().join("projects/glimpse/target")));
+}
+
+#[test]
+fn test_update_removes_deleted_entries() {
+    let dir = create_tree();
+    let mut index = FileIndex::build(dir.path());
+    let documents = dir.path().join("Documents");
+
+    fs::remove_dir_all(&documents).unwrap();
+    index.update(&documents);
+
+    assert!(!index.contains(&documents));
+    assert!(!index.contains(&documents.join("notes.txt")));
+}
+
+#[test]
+fn test_scope_ignore_globs() {
+    let dir = create_tree();
+    touch(dir.path(), "projects/web/node_modules/left-pad/index.js");
+    let scope = Scope {
+        ignore: vec!["node_modules".to_string(), "*.pdf".to_string()],
+        max_depth: None,
+    };
+    let mut index = FileIndex::build_scoped(dir.path(), scope);
+
+    assert!(index.contains(&dir.path().join("projects/web")));
+    assert!(!index.contains(&dir.path().join("projects/web/node_modules")));
+    assert!(!index.contains(&dir.path().join("Documents/report-2024.pdf")));
+    assert!(index.contains(&dir.path().join("Documents/notes.txt")));
+
+    touch(dir.path(), "Documents/invoice.pdf");
+    index.update(&dir.path().join("Documents/invoice.pdf"));
+    assert!(!index.contains(&dir.path().join("Documents/invoice.pdf")));
+}
+
+#[test]
+fn test_scope_max_depth() {
+    let dir = create_tree();
+    let scope = Scope {
+        ignore: vec![],
+        max_depth: Some(2),
+    };
+    let mut index = FileIndex::build_scoped(dir.path(), scope);
+
+    assert!(index.contains(&dir.path().join("Documents/notes.txt")));
+    assert!(index.contains(&dir.path().join("projects/glimpse")));
+    assert!(!index.contains(&dir.path().join("projects/glimpse/Cargo.toml")));
+
+    // entries created below the limit stay out, the folders holding them come in
+    touch(dir.path(), "Music/album/track.flac");
+    let added = index.update(&dir.path().join("Music"));
+    assert_eq!(
+        added,
+        vec![dir.path().join("Music"), dir.path().join("Music/album")]
+    );
+    assert!(!index.contains(&dir.path().join("Music/album/track.flac")));
+}
+
+#[test]
+fn test_cached_indexes_without_scope_load_unlimited() {
+    let cache = tempfile::tempdir().unwrap();
+    let cache_path = cache.path().join("files-index.json");
+    fs::write(
+        &cache_path,
+        r#"{"root": "/home/alice", "files": ["notes.txt"], "dirs": []}"#,
+    )
+    .unwrap();
+
+    let index = FileIndex::load(&cache_path).unwrap();
+
+    assert_eq!(index.scope(), &Scope::default());
+    assert!(index.contains(Path::new("/home/alice/notes.txt")));
+}
+
+#[test]
+fn test_save_and_load_roundtrip() {
+    let dir = create_tree();
+    let cache = tempfile::tempdir().unwrap();
+    let cache_path = cache.path().join("glimpse/files-index.json");
+    let index = FileIndex::build(dir.path());
+
+    index.save(&cache_path).unwrap();
+
+    assert_eq!(FileIndex::load(&cache_path).unwrap(), index);
+    assert!(!cache_path.with_extension("tmp").exists());
+}
@@ -0,0 +1,121 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use sha2::{Digest, Sha256};
+
+/// Remote covers larger than this are not downloaded.
+const MAX_BYTES: u64 = 5 * 1024 * 1024;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where remote covers are kept, `~/.cache/glimpse/media-art`.
+pub fn cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("glimpse").join("media-art"))
+}
+
+/// The local file of a `file://` cover, percent-decoded. None for other addresses.
+pub fn file_path(url: &str) -> Option<PathBuf> {
+    let path = url.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match bytes[i] {
+            b'%' => path
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    let path = PathBuf::from(String::from_utf8_lossy(&decoded).into_owned());
+    path.is_absolute().then_some(path)
+}
+
+pub fn is_remote(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+/// Image types covers come in, by MIME type, as extensions the thumbnailer goes by.
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("image/jpeg", "jpg"),
+    ("image/png", "png"),
+    ("image/webp", "webp"),
+    ("image/gif", "gif"),
+];
+
+/// Whether `path` names an image the thumbnailer takes, by its extension. Browsers keep
+/// covers in files without one.
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            let extension = extension.to_ascii_lowercase();
+            extension == "jpeg" || EXTENSIONS.iter().any(|(_, known)| *known == extension)
+        })
+}
+
+/// The file name a remote cover is cached under, the hash of its address.
+fn cache_name(url: &str) -> String {
+    Sha256::digest(url.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// The cover of `url` cached in `dir`, if it was downloaded.
+pub fn cached(dir: &Path, url: &str) -> Option<PathBuf> {
+    let name = cache_name(url);
+    EXTENSIONS
+        .iter()
+        .map(|(_, extension)| dir.join(format!("{}.{}", name, extension)))
+        .find(|path| path.is_file())
+}
+
+/// Download the cover at `url` into `dir`, through a temporary file so a cover is either
+/// complete or missing. Returns where it went.
+pub fn fetch(url: &str, dir: &Path) -> io::Result<PathBuf> {
+    let config = ureq::Agent::config_builder()
+        .timeout_global(Some(FETCH_TIMEOUT))
+        .build();
+    let agent = ureq::Agent::new_with_config(config);
+    let mut response = agent.get(url).call().map_err(io::Error::other)?;
+    let mime = response
+        .headers()
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let Some((_, extension)) = EXTENSIONS.iter().find(|(known, _)| *known == mime) else {
+        return Err(io::Error::other(format!("cover is not an image: {}", mime)));
+    };
+    let mut data = vec![];
+    response
+        .body_mut()
+        .as_reader()
+        .take(MAX_BYTES + 1)
+        .read_to_end(&mut data)?;
+    if data.len() as u64 > MAX_BYTES {
+        return Err(io::Error::other(format!("cover over {} bytes", MAX_BYTES)));
+    }
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.{}", cache_name(url), extension));
+    let partial = path.with_extension("part");
+    fs::write(&partial, data)?;
+    fs::rename(&partial, &path)?;
+    Ok(path)
+}
@@ -0,0 +1,113 @@
+use glimpse_sdk::matcher::Matcher;
+
+use crate::player::{Player, Status};
+
+/// Keywords count for a little less than the title.
+const KEYWORD_WEIGHT: f64 = 0.9;
+
+/// A control of an MPRIS player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    PlayPause,
+    Next,
+    Previous,
+    Stop,
+}
+
+impl Command {
+    pub const ALL: [Command; 4] = [
+        Command::PlayPause,
+        Command::Next,
+        Command::Previous,
+        Command::Stop,
+    ];
+
+    /// The name callbacks carry the command by.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Command::PlayPause => "play_pause",
+            Command::Next => "next",
+            Command::Previous => "previous",
+            Command::Stop => "stop",
+        }
+    }
+
+    pub fn parse(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|command| command.key() == key)
+    }
+
+    /// The method of `org.mpris.MediaPlayer2.Player` carrying out the command.
+    pub fn method(&self) -> &'static str {
+        match self {
+            Command::PlayPause => "PlayPause",
+            Command::Next => "Next",
+            Command::Previous => "Previous",
+            Command::Stop => "Stop",
+        }
+    }
+
+    /// The title with `player` in its current state, play/pause toggling.
+    pub fn title(&self, player: &Player) -> &'static str {
+        match self {
+            Command::PlayPause if player.status == Status::Playing => "Pause",
+            Command::PlayPause => "Play",
+            Command::Next => "Next track",
+            Command::Previous => "Previous track",
+            Command::Stop => "Stop",
+        }
+    }
+
+    /// Other words people ask for it by.
+    fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            Command::PlayPause => &["play", "pause", "resume", "toggle"],
+            Command::Next => &["next", "skip"],
+            Command::Previous => &["previous", "prev", "back"],
+            Command::Stop => &["stop"],
+        }
+    }
+
+    pub fn icon(&self, player: &Player) -> &'static str {
+        match self {
+            Command::PlayPause if player.status == Status::Playing => "media-playback-pause",
+            Command::PlayPause => "media-playback-start",
+            Command::Next => "media-skip-forward",
+            Command::Previous => "media-skip-backward",
+            Command::Stop => "media-playback-stop",
+        }
+    }
+
+    /// Whether `player` takes the command now, as it tells.
+    pub fn available(&self, player: &Player) -> bool {
+        match self {
+            Command::PlayPause if player.status == Status::Playing => player.can_pause,
+            Command::PlayPause => player.can_play,
+            Command::Next => player.can_go_next,
+            Command::Previous => player.can_go_previous,
+            Command::Stop => player.can_control && player.status != Status::Stopped,
+        }
+    }
+}
+
+/// Commands `player` takes matching `query` by title or keyword, best first. An empty
+/// query finds them all.
+pub fn search(player: &Player, query: &str) -> Vec<(Command, f64)> {
+    let matcher = Matcher::new(query.trim());
+    let mut found = Command::ALL
+        .into_iter()
+        .filter(|command| command.available(player))
+        .filter_map(|command| {
+            let title = matcher.score(command.title(player));
+            let keywords = command
+                .keywords()
+                .iter()
+                .filter_map(|keyword| matcher.score(keyword))
+                .map(|score| score * KEYWORD_WEIGHT);
+            let score = title.into_iter().chain(keywords).reduce(f64::max)?;
+            Some((command, score))
+        })
+        .collect::<Vec<_>>();
+    // stable, so equal scores keep the order of `ALL`
+    found.sort_by(|a, b| b.1.total_cmp(&a.1));
+    found
+}
@@ -0,0 +1,4 @@
+pub mod art;
+pub mod commands;
+pub mod mpris;
+pub mod player;
@@ -0,0 +1,243 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use glimpse_plugins_media::{
+    art,
+    commands::{self, Command},
+    mpris::Mpris,
+    player::{self, Player, Track},
+};
+use glimpse_sdk::{
+    Action, AlternateAction, GlimpsePlugin, Icon, Match, MatchAction, Metadata, Modifiers, Plugin,
+    PluginError, PluginMetadata, Progress, matcher::Matcher,
+};
+
+/// Keywords count for a little less than the track and the player.
+const KEYWORD_WEIGHT: f64 = 0.9;
+const NOW_PLAYING_KEYWORDS: &[&str] = &["now playing", "music", "song"];
+
+#[derive(GlimpsePlugin)]
+#[glimpse(
+    id = "me.aresa.glimpse.media",
+    name = "Media",
+    description = "Shows what media players are playing and plays, pauses or skips tracks.",
+    author = "Alex Oleshkevich <alex.oleshkevich@gmail.com>",
+    prefix = "media ",
+    triggers("play", "pause", "next", "previous", "prev", "stop"),
+    keep_triggers,
+    prefix_only,
+    permissions(Network)
+)]
+struct MediaPlugin {
+    /// None without a session bus to find players on.
+    mpris: Option<Mpris>,
+    /// Remote covers being downloaded, or that failed to, so each is tried once.
+    fetching: Arc<Mutex<HashSet<String>>>,
+}
+
+impl MediaPlugin {
+    fn control(player: &Player, command: Command) -> Action {
+        Action::Callback {
+            key: "control".to_string(),
+            params: HashMap::from([
+                ("player".to_string(), player.bus_name.clone()),
+                ("command".to_string(), command.key().to_string()),
+            ]),
+        }
+    }
+
+    /// The cover of `track` through the daemon's thumbnails. Remote covers are downloaded in
+    /// the background, hence `Network`, and shown from the next search on.
+    fn cover(&self, track: &Track) -> Option<Icon> {
+        let url = track.art_url.as_deref()?;
+        if let Some(path) = art::file_path(url) {
+            return art::is_image(&path).then(|| Icon::thumbnail(path.to_string_lossy()));
+        }
+        if !art::is_remote(url) {
+            return None;
+        }
+        let dir = art::cache_dir()?;
+        if let Some(path) = art::cached(&dir, url) {
+            return Some(Icon::thumbnail(path.to_string_lossy()));
+        }
+        self.fetch(url, dir);
+        None
+    }
+
+    fn fetch(&self, url: &str, dir: PathBuf) {
+        if !self.fetching.lock().unwrap().insert(url.to_string()) {
+            return;
+        }
+        let fetching = self.fetching.clone();
+        let url = url.to_string();
+        tokio::task::spawn_blocking(move || match art::fetch(&url, &dir) {
+            Ok(path) => {
+                tracing::debug!("downloaded cover {} to {}", url, path.display());
+                fetching.lock().unwrap().remove(&url);
+            }
+            Err(e) => tracing::warn!("failed to download cover {}: {}", url, e),
+        });
+    }
+
+    fn now_playing(&self, player: &Player, track: &Track, score: f64) -> Match {
+        let mut description = vec![track.byline(), player.identity.clone()];
+        description.retain(|part| !part.is_empty());
+        let alternates = Command::Next
+            .available(player)
+            .then(|| AlternateAction {
+                modifiers: Modifiers {
+                    shift: true,
+                    ..Default::default()
+                },
+                title: Command::Next.title(player).to_string(),
+                action: Self::control(player, Command::Next),
+            })
+            .into_iter()
+            .collect();
+        Match {
+            title: track.title.clone(),
+            description: format!("{} · {}", description.join(" · "), player.status.name()),
+            icon: self
+                .cover(track)
+                .or_else(|| Some(Icon::freedesktop(player.icon()))),
+            actions: vec![MatchAction {
+                title: Command::PlayPause.title(player).to_string(),
+                action: Self::control(player, Command::PlayPause),
+                close_on_action: true,
+                alternates,
+                requires_confirmation: false,
+                confirmation_prompt: None,
+                expand: vec![],
+            }],
+            score,
+            ..Default::default()
+        }
+    }
+
+    fn command(player: &Player, command: Command, score: f64) -> Match {
+        let playing = match &player.track {
+            Some(track) => track.title.clone(),
+            None => player.status.name().to_string(),
+        };
+        Match {
+            title: command.title(player).to_string(),
+            description: format!("{} · {}", player.identity, playing),
+            icon: Some(Icon::freedesktop(command.icon(player))),
+            actions: vec![MatchAction {
+                title: command.title(player).to_string(),
+                action: Self::control(player, command),
+                close_on_action: true,
+                alternates: vec![],
+                requires_confirmation: false,
+                confirmation_prompt: None,
+                expand: vec![],
+            }],
+            score,
+            ..Default::default()
+        }
+    }
+}
+
+/// How well `query` matches what `player` is playing, by track, artist, album or player.
+fn now_playing_score(matcher: &Matcher, player: &Player, track: &Track) -> Option<f64> {
+    let texts = [track.title.as_str(), &track.byline(), &player.identity];
+    let keywords = NOW_PLAYING_KEYWORDS
+        .iter()
+        .filter_map(|keyword| matcher.score(keyword))
+        .map(|score| score * KEYWORD_WEIGHT);
+    texts
+        .iter()
+        .filter_map(|text| matcher.score(text))
+        .chain(keywords)
+        .reduce(f64::max)
+}
+
+#[async_trait]
+impl Plugin for MediaPlugin {
+    fn metadata(&self) -> Metadata {
+        Self::plugin_metadata()
+    }
+
+    /// What the players play, then the controls of the one playing. Trigger words come
+    /// along, so `next` finds the next track control.
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        let Some(mpris) = &self.mpris else {
+            return Ok(vec![]);
+        };
+        let mut players = mpris
+            .players()
+            .await
+            .map_err(|e| PluginError::Other(e.to_string()))?;
+        players.sort_by_key(|player| player.status);
+
+        let matcher = Matcher::new(query.trim());
+        let mut found = vec![];
+        if let Some(active) = player::active(&players) {
+            if let Some(track) = &active.track
+                && let Some(score) = now_playing_score(&matcher, active, track)
+            {
+                found.push(self.now_playing(active, track, score));
+            }
+            found.extend(
+                commands::search(active, &query)
+                    .into_iter()
+                    .map(|(command, score)| Self::command(active, command, score)),
+            );
+        }
+        for player in players.iter().skip(1) {
+            if let Some(track) = &player.track
+                && let Some(score) = now_playing_score(&matcher, player, track)
+            {
+                found.push(self.now_playing(player, track, score));
+            }
+        }
+        // stable, so equal scores keep the order above
+        found.sort_by(|a, b| b.score.total_cmp(&a.score));
+        Ok(found)
+    }
+
+    async fn call_action(
+        &self,
+        action: String,
+        params: HashMap<String, String>,
+        _progress: &Progress,
+    ) -> Result<(), PluginError> {
+        if action != "control" {
+            return Err(PluginError::Other(format!("unknown action: {}", action)));
+        }
+        let (Some(player), Some(command)) = (
+            params.get("player"),
+            params.get("command").and_then(|key| Command::parse(key)),
+        ) else {
+            return Err(PluginError::Other(format!(
+                "invalid media control: {:?}",
+                params
+            )));
+        };
+        let Some(mpris) = &self.mpris else {
+            return Err(PluginError::Other("no session bus".to_string()));
+        };
+        tracing::info!("sending {:?} to {}", command, player);
+        mpris
+            .control(player, command)
+            .await
+            .map_err(|e| PluginError::Other(format!("failed to control {}: {}", player, e)))
+    }
+}
+
+#[glimpse_sdk::main]
+async fn main() -> Result<MediaPlugin, Box<dyn Error>> {
+    let mpris = Mpris::connect()
+        .await
+        .inspect_err(|e| tracing::warn!("no session bus, no players to find: {}", e))
+        .ok();
+    Ok(MediaPlugin {
+        mpris,
+        fetching: Arc::new(Mutex::new(HashSet::new())),
+    })
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use zbus::{
+    Connection, Proxy,
+    fdo::DBusProxy,
+    proxy::{Builder, CacheProperties},
+    zvariant::OwnedValue,
+};
+
+use crate::{
+    commands::Command,
+    player::{Player, Status, Track},
+};
+
+/// Players own a name starting with this on the session bus.
+pub const BUS_NAME_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const PATH: &str = "/org/mpris/MediaPlayer2";
+const ROOT_INTERFACE: &str = "org.mpris.MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+/// Whether `name` is the bus name of an MPRIS player.
+pub fn is_player(name: &str) -> bool {
+    name.strip_prefix(BUS_NAME_PREFIX)
+        .is_some_and(|rest| !rest.is_empty())
+}
+
+/// Client of the MPRIS players running in the session.
+pub struct Mpris {
+    connection: Connection,
+}
+
+impl Mpris {
+    pub async fn connect() -> zbus::Result<Self> {
+        Ok(Self {
+            connection: Connection::session().await?,
+        })
+    }
+
+    /// Every running player, those failing to answer left out.
+    pub async fn players(&self) -> zbus::Result<Vec<Player>> {
+        let names = DBusProxy::new(&self.connection).await?.list_names().await?;
+        let mut players = vec![];
+        for name in names.iter().filter(|name| is_player(name.as_str())) {
+            match self.player(name.as_str()).await {
+                Ok(player) => players.push(player),
+                Err(e) => tracing::debug!("failed to read player {}: {}", name, e),
+            }
+        }
+        Ok(players)
+    }
+
+    async fn player(&self, bus_name: &str) -> zbus::Result<Player> {
+        let root = proxy(&self.connection, bus_name, ROOT_INTERFACE).await?;
+        let player = proxy(&self.connection, bus_name, PLAYER_INTERFACE).await?;
+        let identity: String = root.get_property("Identity").await.unwrap_or_default();
+        let desktop_entry: Option<String> = root.get_property("DesktopEntry").await.ok();
+        let status: String = player.get_property("PlaybackStatus").await?;
+        let metadata: HashMap<String, OwnedValue> =
+            player.get_property("Metadata").await.unwrap_or_default();
+        let flag = async |name: &str| player.get_property::<bool>(name).await.unwrap_or(false);
+        Ok(Player {
+            bus_name: bus_name.to_string(),
+            identity: match identity.is_empty() {
+                true => bus_name[BUS_NAME_PREFIX.len()..].to_string(),
+                false => identity,
+            },
+            desktop_entry: desktop_entry.filter(|entry| !entry.is_empty()),
+            status: Status::parse(&status),
+            track: Track::from_metadata(&metadata),
+            can_play: flag("CanPlay").await,
+            can_pause: flag("CanPause").await,
+            can_go_next: flag("CanGoNext").await,
+            can_go_previous: flag("CanGoPrevious").await,
+            can_control: flag("CanControl").await,
+        })
+    }
+
+    /// Have the player owning `bus_name` carry out `command`.
+    pub async fn control(&self, bus_name: &str, command: Command) -> zbus::Result<()> {
+        if !is_player(bus_name) {
+            return Err(zbus::Error::Failure(format!(
+                "not a media player: {}",
+                bus_name
+            )));
+        }
+        let player = proxy(&self.connection, bus_name, PLAYER_INTERFACE).await?;
+        player.call_method(command.method(), &()).await?;
+        Ok(())
+    }
+}
+
+/// A proxy reading properties when asked for, players change tracks between searches.
+async fn proxy(
+    connection: &Connection,
+    bus_name: &str,
+    interface: &'static str,
+) -> zbus::Result<Proxy<'static>> {
+    Builder::new(connection)
+        .destination(bus_name.to_string())?
+        .path(PATH)?
+        .interface(interface)?
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await
+}
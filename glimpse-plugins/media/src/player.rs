@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use zbus::zvariant::OwnedValue;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+impl Status {
+    /// The MPRIS `PlaybackStatus`, players reporting something else count as stopped.
+    pub fn parse(status: &str) -> Self {
+        match status {
+            "Playing" => Status::Playing,
+            "Paused" => Status::Paused,
+            _ => Status::Stopped,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Playing => "Playing",
+            Status::Paused => "Paused",
+            Status::Stopped => "Stopped",
+        }
+    }
+}
+
+/// What a player is playing, from its MPRIS `Metadata`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Track {
+    pub title: String,
+    pub artists: Vec<String>,
+    pub album: Option<String>,
+    /// `mpris:artUrl`, a `file://` or `https://` address.
+    pub art_url: Option<String>,
+}
+
+impl Track {
+    /// The track of MPRIS `Metadata`, None for players with nothing loaded.
+    pub fn from_metadata(metadata: &HashMap<String, OwnedValue>) -> Option<Self> {
+        let text = |key: &str| {
+            metadata
+                .get(key)
+                .and_then(|value| <&str>::try_from(value).ok())
+                .map(str::trim)
+                .filter(|text| !text.is_empty())
+                .map(str::to_string)
+        };
+        let artists = metadata
+            .get("xesam:artist")
+            .and_then(|value| value.try_clone().ok())
+            .and_then(|value| Vec::<String>::try_from(value).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|artist| !artist.trim().is_empty())
+            .collect();
+        Some(Self {
+            title: text("xesam:title")?,
+            artists,
+            album: text("xesam:album"),
+            art_url: text("mpris:artUrl"),
+        })
+    }
+
+    /// Artists and album, like `Miles Davis — Kind of Blue`.
+    pub fn byline(&self) -> String {
+        let artists = self.artists.join(", ");
+        match (&self.album, artists.is_empty()) {
+            (Some(album), false) => format!("{} — {}", artists, album),
+            (Some(album), true) => album.clone(),
+            (None, false) => artists,
+            (None, true) => String::new(),
+        }
+    }
+}
+
+/// A running MPRIS media player.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Player {
+    /// The well-known name it owns on the session bus, like `org.mpris.MediaPlayer2.spotify`.
+    pub bus_name: String,
+    /// Its name for people, like `Spotify`.
+    pub identity: String,
+    /// The desktop entry without `.desktop`, doubling as its icon name.
+    pub desktop_entry: Option<String>,
+    pub status: Status,
+    pub track: Option<Track>,
+    pub can_play: bool,
+    pub can_pause: bool,
+    pub can_go_next: bool,
+    pub can_go_previous: bool,
+    pub can_control: bool,
+}
+
+impl Player {
+    pub fn icon(&self) -> &str {
+        self.desktop_entry.as_deref().unwrap_or("audio-x-generic")
+    }
+}
+
+/// The player controls act on: the first playing, else the first paused, else the first.
+pub fn active(players: &[Player]) -> Option<&Player> {
+    players.iter().min_by_key(|player| player.status)
+}
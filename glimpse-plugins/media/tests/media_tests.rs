@@ -0,0 +1,202 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use glimpse_plugins_media::{
+    art,
+    commands::{self, Command},
+    mpris,
+    player::{self, Player, Status, Track},
+};
+use sha2::{Digest, Sha256};
+use zbus::zvariant::{OwnedValue, Str};
+
+fn text(value: &str) -> OwnedValue {
+    OwnedValue::from(Str::from(value.to_string()))
+}
+
+fn player(name: &str, status: Status) -> Player {
+    Player {
+        bus_name: format!("org.mpris.MediaPlayer2.{}", name),
+        identity: name.to_string(),
+        desktop_entry: Some(name.to_lowercase()),
+        status,
+        track: Some(Track {
+            title: "So What".to_string(),
+            artists: vec!["Miles Davis".to_string()],
+            album: Some("Kind of Blue".to_string()),
+            art_url: None,
+        }),
+        can_play: true,
+        can_pause: true,
+        can_go_next: true,
+        can_go_previous: true,
+        can_control: true,
+    }
+}
+
+#[test]
+fn reads_tracks_from_metadata() {
+    let metadata = HashMap::from([
+        ("xesam:title".to_string(), text("So What")),
+        (
+            "xesam:artist".to_string(),
+            OwnedValue::try_from(zbus::zvariant::Value::from(vec!["Miles Davis"])).unwrap(),
+        ),
+        ("xesam:album".to_string(), text("Kind of Blue")),
+        (
+            "mpris:artUrl".to_string(),
+            text("https://example.com/cover.jpg"),
+        ),
+        ("mpris:length".to_string(), OwnedValue::from(562_000_000i64)),
+    ]);
+
+    let track = Track::from_metadata(&metadata).unwrap();
+    assert_eq!(track.title, "So What");
+    assert_eq!(track.artists, vec!["Miles Davis"]);
+    assert_eq!(track.album.as_deref(), Some("Kind of Blue"));
+    assert_eq!(
+        track.art_url.as_deref(),
+        Some("https://example.com/cover.jpg")
+    );
+    assert_eq!(track.byline(), "Miles Davis — Kind of Blue");
+}
+
+#[test]
+fn players_without_a_track_have_none() {
+    assert_eq!(Track::from_metadata(&HashMap::new()), None);
+    let untitled = HashMap::from([("xesam:title".to_string(), text("  "))]);
+    assert_eq!(Track::from_metadata(&untitled), None);
+}
+
+#[test]
+fn bylines_skip_what_is_missing() {
+    let track = Track {
+        title: "Stream".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(track.byline(), "");
+    let album = Track {
+        album: Some("Live".to_string()),
+        ..track
+    };
+    assert_eq!(album.byline(), "Live");
+}
+
+#[test]
+fn parses_playback_status() {
+    assert_eq!(Status::parse("Playing"), Status::Playing);
+    assert_eq!(Status::parse("Paused"), Status::Paused);
+    assert_eq!(Status::parse("Stopped"), Status::Stopped);
+    assert_eq!(Status::parse("Buffering"), Status::Stopped);
+}
+
+#[test]
+fn controls_go_to_the_player_playing() {
+    let players = vec![
+        player("vlc", Status::Paused),
+        player("Spotify", Status::Playing),
+        player("mpv", Status::Stopped),
+    ];
+    assert_eq!(player::active(&players).unwrap().identity, "Spotify");
+    assert_eq!(player::active(&players[..1]).unwrap().identity, "vlc");
+    assert_eq!(player::active(&[]), None);
+}
+
+#[test]
+fn play_pause_follows_the_status() {
+    let playing = player("Spotify", Status::Playing);
+    let paused = player("Spotify", Status::Paused);
+    assert_eq!(Command::PlayPause.title(&playing), "Pause");
+    assert_eq!(Command::PlayPause.icon(&playing), "media-playback-pause");
+    assert_eq!(Command::PlayPause.title(&paused), "Play");
+    assert_eq!(Command::PlayPause.method(), "PlayPause");
+}
+
+#[test]
+fn searches_commands_by_trigger_word() {
+    let spotify = player("Spotify", Status::Playing);
+    let first = |query| commands::search(&spotify, query)[0].0;
+
+    assert_eq!(first("next"), Command::Next);
+    assert_eq!(first("prev"), Command::Previous);
+    assert_eq!(first("pause"), Command::PlayPause);
+    assert_eq!(first("play"), Command::PlayPause);
+    assert_eq!(first("stop"), Command::Stop);
+
+    let all = commands::search(&spotify, "")
+        .into_iter()
+        .map(|(command, _)| command)
+        .collect::<Vec<_>>();
+    assert_eq!(all, Command::ALL.to_vec());
+}
+
+#[test]
+fn leaves_out_commands_the_player_refuses() {
+    let radio = Player {
+        can_go_next: false,
+        can_go_previous: false,
+        ..player("Radio", Status::Stopped)
+    };
+    let found = commands::search(&radio, "")
+        .into_iter()
+        .map(|(command, _)| command)
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec![Command::PlayPause]);
+}
+
+#[test]
+fn commands_round_trip_their_keys() {
+    for command in Command::ALL {
+        assert_eq!(Command::parse(command.key()), Some(command));
+    }
+    assert_eq!(Command::parse("rewind"), None);
+}
+
+#[test]
+fn recognizes_player_bus_names() {
+    assert!(mpris::is_player("org.mpris.MediaPlayer2.spotify"));
+    assert!(mpris::is_player(
+        "org.mpris.MediaPlayer2.firefox.instance_1_84"
+    ));
+    assert!(!mpris::is_player("org.mpris.MediaPlayer2."));
+    assert!(!mpris::is_player("org.freedesktop.Notifications"));
+}
+
+#[test]
+fn decodes_local_covers() {
+    assert_eq!(
+        art::file_path("file:///home/me/Music/Kind%20of%20Blue/cover.jpg"),
+        Some(PathBuf::from("/home/me/Music/Kind of Blue/cover.jpg"))
+    );
+    assert_eq!(art::file_path("https://example.com/cover.jpg"), None);
+    assert!(art::is_remote("https://example.com/cover.jpg"));
+    assert!(!art::is_remote("file:///tmp/cover.jpg"));
+}
+
+#[test]
+fn thumbnails_only_images() {
+    assert!(art::is_image(&PathBuf::from("/music/cover.JPEG")));
+    assert!(art::is_image(&PathBuf::from("/music/cover.png")));
+    // browsers keep covers without an extension
+    assert!(!art::is_image(&PathBuf::from(
+        "/tmp/.com.google.Chrome.x8Kq2z"
+    )));
+}
+
+#[test]
+fn finds_cached_covers() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = "https://example.com/cover";
+    assert_eq!(art::cached(dir.path(), url), None);
+
+    fs::write(dir.path().join("other.jpg"), b"jpeg").unwrap();
+    assert_eq!(art::cached(dir.path(), url), None);
+
+    // named by the hash of the address, with the extension of its type
+    let name = Sha256::digest(url.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    let cover = dir.path().join(format!("{}.png", name));
+    fs::write(&cover, b"png").unwrap();
+    assert_eq!(art::cached(dir.path(), url), Some(cover));
+}
@@ -0,0 +1,210 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use async_trait::async_trait;
+use glimpse_plugins_processes::procs::{self, CpuSampler, Process};
+use glimpse_sdk::{
+    Action, ConfigField, ConfigKind, ConfigSchema, Icon, Match, MatchAction, Metadata, Plugin,
+    PluginError, Progress, Settings, run_plugin, setup_logging,
+};
+use nix::{sys::signal::Signal, unistd::Pid};
+use serde::Deserialize;
+
+const DEFAULT_MAX_RESULTS: usize = 20;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+struct ProcessesSettings {
+    max_results: usize,
+}
+
+impl Default for ProcessesSettings {
+    fn default() -> Self {
+        Self {
+            max_results: DEFAULT_MAX_RESULTS,
+        }
+    }
+}
+
+struct ProcessesPlugin {
+    proc_dir: PathBuf,
+    /// Only processes of the user running the plugin are listed, others cannot be signalled.
+    uid: Option<u32>,
+    sampler: Arc<Mutex<CpuSampler>>,
+    settings: Settings<ProcessesSettings>,
+}
+
+impl ProcessesPlugin {
+    fn new(proc_dir: PathBuf) -> Self {
+        let uid = procs::read(&proc_dir, std::process::id()).map(|process| process.uid);
+        Self {
+            proc_dir,
+            uid,
+            sampler: Arc::new(Mutex::new(CpuSampler::new())),
+            settings: Settings::default(),
+        }
+    }
+
+    fn signal_action(
+        process: &Process,
+        title: &str,
+        signal: Signal,
+        prompt: String,
+    ) -> MatchAction {
+        MatchAction {
+            title: title.to_string(),
+            action: Action::Callback {
+                key: "signal".to_string(),
+                params: HashMap::from([
+                    ("pid".to_string(), process.pid.to_string()),
+                    ("started".to_string(), process.started.to_string()),
+                    ("signal".to_string(), signal.as_str().to_string()),
+                ]),
+            },
+            close_on_action: true,
+            alternates: vec![],
+            requires_confirmation: true,
+            confirmation_prompt: Some(prompt),
+            expand: vec![],
+        }
+    }
+
+    fn to_match(process: &Process, cpu: f64, score: f64) -> Match {
+        let label = format!("{} (pid {})", process.name, process.pid);
+        Match {
+            title: process.name.clone(),
+            description: format!(
+                "pid {} · {:.1}% CPU · {} · {}",
+                process.pid,
+                cpu,
+                procs::format_bytes(process.rss),
+                process.cmdline
+            ),
+            icon: Some(Icon::freedesktop("utilities-system-monitor")),
+            actions: vec![
+                Self::signal_action(
+                    process,
+                    "End process",
+                    Signal::SIGTERM,
+                    format!("End {}? Unsaved work may be lost.", label),
+                ),
+                Self::signal_action(
+                    process,
+                    "Kill process",
+                    Signal::SIGKILL,
+                    format!("Kill {}? It cannot save anything before it stops.", label),
+                ),
+            ],
+            score,
+            ..Default::default()
+        }
+    }
+
+    /// Send the signal named in `params` to the process in them, unless the pid went to
+    /// another process since it was listed.
+    fn signal(&self, params: &HashMap<String, String>) -> Result<(), PluginError> {
+        let number = |key: &str| params.get(key).and_then(|value| value.parse::<u64>().ok());
+        let (Some(pid), Some(started)) = (number("pid"), number("started")) else {
+            return Err(PluginError::Other(format!("invalid process: {:?}", params)));
+        };
+        let signal = params
+            .get("signal")
+            .and_then(|signal| signal.parse::<Signal>().ok())
+            .filter(|signal| matches!(signal, Signal::SIGTERM | Signal::SIGKILL))
+            .ok_or_else(|| PluginError::Other(format!("invalid signal: {:?}", params)))?;
+
+        let process = u32::try_from(pid)
+            .ok()
+            .and_then(|pid| procs::read(&self.proc_dir, pid))
+            .filter(|process| process.started == started)
+            .ok_or_else(|| PluginError::Other(format!("process {} is gone", pid)))?;
+        tracing::info!("sending {} to {} ({})", signal, process.name, process.pid);
+        nix::sys::signal::kill(Pid::from_raw(process.pid as i32), signal)
+            .map_err(|e| PluginError::Other(format!("failed to signal {}: {}", process.name, e)))
+    }
+}
+
+#[async_trait]
+impl Plugin for ProcessesPlugin {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            id: "me.aresa.glimpse.processes".to_string(),
+            name: "Processes".to_string(),
+            version: "0.1.0".to_string(),
+            description: "Finds running processes and ends them.".to_string(),
+            author: "Alex Oleshkevich <alex.oleshkevich@gmail.com>".to_string(),
+            prefix: Some("kill ".to_string()),
+            prefix_only: true,
+            config_schema: Some(
+                ConfigSchema::new().field(
+                    ConfigField::new("max_results", ConfigKind::Integer)
+                        .default_value(DEFAULT_MAX_RESULTS)
+                        .description("Maximum number of processes returned per search"),
+                ),
+            ),
+            ..Default::default()
+        }
+    }
+
+    async fn configure(&self, config: serde_json::Value) -> Result<(), PluginError> {
+        self.settings.apply(config)
+    }
+
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        let settings = self.settings.get();
+        let proc_dir = self.proc_dir.clone();
+        let own = std::process::id();
+        let uid = self.uid;
+        let (processes, uptime) = tokio::task::spawn_blocking(move || {
+            let processes = procs::list(&proc_dir)
+                .into_iter()
+                // kernel threads have no command line
+                .filter(|process| process.pid != own && !process.cmdline.is_empty())
+                .filter(|process| uid.is_none_or(|uid| process.uid == uid))
+                .collect::<Vec<_>>();
+            (processes, procs::uptime(&proc_dir).unwrap_or_default())
+        })
+        .await
+        .map_err(|e| PluginError::Other(e.to_string()))?;
+
+        let cpu = self
+            .sampler
+            .lock()
+            .unwrap()
+            .sample(&processes, uptime, Instant::now());
+        Ok(procs::search(&processes, &query, &cpu)
+            .into_iter()
+            .take(settings.max_results)
+            .map(|(process, score)| {
+                let usage = cpu.get(&process.pid).copied().unwrap_or_default();
+                Self::to_match(process, usage, score)
+            })
+            .collect())
+    }
+
+    async fn call_action(
+        &self,
+        action: String,
+        params: HashMap<String, String>,
+        _progress: &Progress,
+    ) -> Result<(), PluginError> {
+        match action.as_str() {
+            "signal" => self.signal(&params),
+            _ => Err(PluginError::Other(format!("unknown action: {}", action))),
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    setup_logging(tracing::Level::INFO);
+    if let Err(err) = run_plugin(ProcessesPlugin::new(PathBuf::from("/proc"))).await {
+        tracing::error!("error running plugin: {}", err);
+    }
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use std::{collections::HashMap, path::Path, time::Instant};
+
+/// `/proc` counts CPU time in ticks of `USER_HZ`, 100 on every architecture Linux runs on.
+pub const TICKS_PER_SECOND: f64 = 100.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Process {
+    pub pid: u32,
+    pub name: String,
+    /// Arguments joined by spaces, empty for kernel threads.
+    pub cmdline: String,
+    /// CPU time used so far, in ticks.
+    pub cpu_ticks: u64,
+    /// Ticks since boot the process started at. With the pid it tells the process apart from
+    /// a later one given the same pid.
+    pub started: u64,
+    /// Resident memory in bytes.
+    pub rss: u64,
+    pub uid: u32,
+}
+
+/// The fields of `/proc/<pid>/stat` processes are listed with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stat {
+    pub name: String,
+    pub cpu_ticks: u64,
+    pub started: u64,
+}
+
+/// Parse `/proc/<pid>/stat`. The name is in parentheses and may hold spaces and parentheses
+/// itself, the fields follow the last `)`.
+pub fn parse_stat(content: &str) -> Option<Stat> {
+    let open = content.find('(')?;
+    let close = content.rfind(')')?;
+    let name = content.get(open + 1..close)?.to_string();
+    // fields from the state on, the third of the file
+    let fields = content
+        .get(close + 1..)?
+        .split_whitespace()
+        .collect::<Vec<_>>();
+    let field = |number: usize| fields.get(number - 3)?.parse::<u64>().ok();
+    Some(Stat {
+        name,
+        cpu_ticks: field(14)? + field(15)?,
+        started: field(22)?,
+    })
+}
+
+/// The real uid and the resident memory in bytes of `/proc/<pid>/status`. Kernel threads
+/// have no memory of their own.
+pub fn parse_status(content: &str) -> Option<(u32, u64)> {
+    let mut uid = None;
+    let mut rss = 0;
+    for line in content.lines() {
+        if let Some(ids) = line.strip_prefix("Uid:") {
+            uid = ids.split_whitespace().next()?.parse().ok();
+        } else if let Some(kilobytes) = line.strip_prefix("VmRSS:") {
+            let kilobytes = kilobytes.trim().trim_end_matches("kB").trim();
+            rss = kilobytes.parse::<u64>().ok()? * 1024;
+        }
+    }
+    Some((uid?, rss))
+}
+
+/// The process `pid` as `proc_dir` describes it, `None` once it exited.
+pub fn read(proc_dir: &Path, pid: u32) -> Option<Process> {
+    let dir = proc_dir.join(pid.to_string());
+    let stat = parse_stat(&std::fs::read_to_string(dir.join("stat")).ok()?)?;
+    let (uid, rss) = parse_status(&std::fs::read_to_string(dir.join("status")).ok()?)?;
+    let cmdline = std::fs::read(dir.join("cmdline")).unwrap_or_default();
+    let cmdline = String::from_utf8_lossy(&cmdline)
+        .split('\0')
+        .filter(|arg| !arg.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(Process {
+        pid,
+        name: stat.name,
+        cmdline,
+        cpu_ticks: stat.cpu_ticks,
+        started: stat.started,
+        rss,
+        uid,
+    })
+}
+
+/// Every process in `proc_dir`, in no particular order. Processes exiting while listed are
+/// left out.
+pub fn list(proc_dir: &Path) -> Vec<Process> {
+    let Ok(entries) = std::fs::read_dir(proc_dir) else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+        .filter_map(|pid| read(proc_dir, pid))
+        .collect()
+}
+
+/// Seconds since boot, from `/proc/uptime`.
+pub fn uptime(proc_dir: &Path) -> Option<f64> {
+    std::fs::read_to_string(proc_dir.join("uptime"))
+        .ok()?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// CPU usage of processes between consecutive listings.
+#[derive(Debug, Default)]
+pub struct CpuSampler {
+    /// CPU ticks of the last listing by pid and start time.
+    last: HashMap<(u32, u64), u64>,
+    at: Option<Instant>,
+}
+
+impl CpuSampler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// CPU usage of `processes` in percent of one core by pid, measured since the previous
+    /// sample. Processes without one, such as on the first sample, get their average over
+    /// their lifetime, from `uptime` seconds since boot.
+    pub fn sample(
+        &mut self,
+        processes: &[Process],
+        uptime: f64,
+        now: Instant,
+    ) -> HashMap<u32, f64> {
+        let elapsed = self
+            .at
+            .map(|at| now.saturating_duration_since(at).as_secs_f64())
+            .unwrap_or_default();
+        let usage = processes
+            .iter()
+            .map(|process| {
+                let previous = self
+                    .last
+                    .get(&(process.pid, process.started))
+                    .filter(|_| elapsed > 0.0);
+                let (ticks, seconds) = match previous {
+                    Some(previous) => (process.cpu_ticks.saturating_sub(*previous), elapsed),
+                    None => (
+                        process.cpu_ticks,
+                        uptime - process.started as f64 / TICKS_PER_SECOND,
+                    ),
+                };
+                if seconds <= 0.0 {
+                    return (process.pid, 0.0);
+                }
+                (
+                    process.pid,
+                    ticks as f64 / TICKS_PER_SECOND / seconds * 100.0,
+                )
+            })
+            .collect();
+        self.last = processes
+            .iter()
+            .map(|process| ((process.pid, process.started), process.cpu_ticks))
+            .collect();
+        self.at = Some(now);
+        usage
+    }
+}
+
+/// Processes matching `query` with their scores, best first: names starting with it, then
+/// names containing it, then command lines containing it. A number also matches the pid. An
+/// empty query matches everything. Busier processes go first among equals.
+pub fn search<'a>(
+    processes: &'a [Process],
+    query: &str,
+    cpu: &HashMap<u32, f64>,
+) -> Vec<(&'a Process, f64)> {
+    let query = query.trim().to_lowercase();
+    let usage = |process: &Process| cpu.get(&process.pid).copied().unwrap_or_default();
+    let mut found = processes
+        .iter()
+        .filter_map(|process| {
+            let name = process.name.to_lowercase();
+            let score = if query.is_empty() {
+                0.5
+            } else if name.starts_with(&query) || process.pid.to_string() == query {
+                1.0
+            } else if name.contains(&query) {
+                0.7
+            } else if process.cmdline.to_lowercase().contains(&query) {
+                0.4
+            } else {
+                return None;
+            };
+            // the daemon ranks by score, usage orders processes matching alike
+            Some((process, score + usage(process).clamp(0.0, 100.0) / 1000.0))
+        })
+        .collect::<Vec<_>>();
+    found.sort_by(|a, b| b.1.total_cmp(&a.1));
+    found
+}
+
+/// `bytes` for people, e.g. `12.3 MB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
@@ -0,0 +1,144 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use glimpse_plugins_processes::procs::{self, CpuSampler, Process, Stat};
+
+fn process(pid: u32, name: &str, cmdline: &str, cpu_ticks: u64) -> Process {
+    Process {
+        pid,
+        name: name.to_string(),
+        cmdline: cmdline.to_string(),
+        cpu_ticks,
+        started: 1000,
+        rss: 0,
+        uid: 1000,
+    }
+}
+
+fn write_process(proc_dir: &Path, pid: u32, stat: &str, status: &str, cmdline: &[u8]) {
+    let dir = proc_dir.join(pid.to_string());
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("stat"), stat).unwrap();
+    std::fs::write(dir.join("status"), status).unwrap();
+    std::fs::write(dir.join("cmdline"), cmdline).unwrap();
+}
+
+#[test]
+fn test_parse_stat() {
+    let stat = "4242 (Web Content (x)) S 1 4242 4242 0 -1 4194560 100 0 0 0 150 50 0 0 20 0 30 0 98765 1000 200";
+    assert_eq!(
+        procs::parse_stat(stat),
+        Some(Stat {
+            name: "Web Content (x)".to_string(),
+            cpu_ticks: 200,
+            started: 98765,
+        })
+    );
+    assert_eq!(procs::parse_stat("4242 (truncated) S 1"), None);
+    assert_eq!(procs::parse_stat(""), None);
+}
+
+#[test]
+fn test_parse_status() {
+    let status = "Name:\tfirefox\nUid:\t1000\t1000\t1000\t1000\nVmRSS:\t  2048 kB\n";
+    assert_eq!(procs::parse_status(status), Some((1000, 2048 * 1024)));
+    // kernel threads have no resident memory
+    assert_eq!(procs::parse_status("Uid:\t0\t0\t0\t0\n"), Some((0, 0)));
+    assert_eq!(procs::parse_status("Name:\tbroken\n"), None);
+}
+
+#[test]
+fn test_list_processes() {
+    let dir = tempfile::tempdir().unwrap();
+    let stat = "7 (bash) S 1 7 7 0 -1 0 0 0 0 0 12 3 0 0 20 0 1 0 500 0 0";
+    write_process(
+        dir.path(),
+        7,
+        stat,
+        "Uid:\t1000\t1000\t1000\t1000\nVmRSS:\t4 kB\n",
+        b"/bin/bash\0--login\0",
+    );
+    // a process exiting while listed
+    std::fs::create_dir(dir.path().join("8")).unwrap();
+    std::fs::create_dir(dir.path().join("self")).unwrap();
+    std::fs::write(dir.path().join("uptime"), "12.50 40.00\n").unwrap();
+
+    assert_eq!(
+        procs::list(dir.path()),
+        vec![Process {
+            pid: 7,
+            name: "bash".to_string(),
+            cmdline: "/bin/bash --login".to_string(),
+            cpu_ticks: 15,
+            started: 500,
+            rss: 4096,
+            uid: 1000,
+        }]
+    );
+    assert_eq!(procs::uptime(dir.path()), Some(12.5));
+}
+
+#[test]
+fn test_cpu_sampler() {
+    let mut sampler = CpuSampler::new();
+    let start = Instant::now();
+
+    // started 10 seconds after boot, busy for half of the 20 seconds since
+    let usage = sampler.sample(&[process(1, "busy", "busy", 1000)], 30.0, start);
+    assert_eq!(usage[&1], 50.0);
+
+    let processes = [
+        process(1, "busy", "busy", 1200),
+        process(2, "new", "new", 0),
+    ];
+    let usage = sampler.sample(&processes, 32.0, start + Duration::from_secs(2));
+    assert_eq!(usage[&1], 100.0);
+    assert_eq!(usage[&2], 0.0);
+
+    // a pid given to another process starts over
+    let mut reused = process(1, "other", "other", 100);
+    reused.started = 3000;
+    let usage = sampler.sample(&[reused], 40.0, start + Duration::from_secs(10));
+    assert_eq!(usage[&1], 10.0);
+}
+
+#[test]
+fn test_search() {
+    let processes = [
+        process(10, "firefox", "/usr/lib/firefox/firefox", 0),
+        process(
+            11,
+            "Web Content",
+            "/usr/lib/firefox/firefox -contentproc",
+            0,
+        ),
+        process(12, "fish", "/usr/bin/fish", 0),
+        process(13, "firefox-bin", "/opt/firefox-bin", 0),
+    ];
+    let cpu = HashMap::from([(13, 40.0)]);
+
+    let pids = |query: &str| {
+        procs::search(&processes, query, &cpu)
+            .into_iter()
+            .map(|(process, _)| process.pid)
+            .collect::<Vec<_>>()
+    };
+    // busier processes first among equals, then name matches before command lines
+    assert_eq!(pids("FIRE"), vec![13, 10, 11]);
+    assert_eq!(pids("content"), vec![11]);
+    assert_eq!(pids("12"), vec![12]);
+    assert_eq!(pids("chrome"), Vec::<u32>::new());
+    assert_eq!(pids("").len(), 4);
+    assert_eq!(pids("")[0], 13);
+}
+
+#[test]
+fn test_format_bytes() {
+    assert_eq!(procs::format_bytes(512), "512 B");
+    assert_eq!(procs::format_bytes(2048), "2.0 KB");
+    assert_eq!(procs::format_bytes(150 * 1024 * 1024), "150.0 MB");
+    assert_eq!(procs::format_bytes(3 * 1024 * 1024 * 1024), "3.0 GB");
+}
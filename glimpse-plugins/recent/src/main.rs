@@ -0,0 +1,221 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+};
+
+use async_trait::async_trait;
+use glimpse_plugins_recent::recent::{self, RecentDocument};
+use glimpse_sdk::{
+    Action, AlternateAction, ConfigField, ConfigKind, ConfigSchema, Context, GlimpsePlugin, Icon,
+    Match, MatchAction, Metadata, Modifiers, Plugin, PluginError, PluginMetadata, Settings,
+};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+
+const DEFAULT_MAX_RESULTS: usize = 10;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+struct RecentSettings {
+    max_results: usize,
+}
+
+impl Default for RecentSettings {
+    fn default() -> Self {
+        Self {
+            max_results: DEFAULT_MAX_RESULTS,
+        }
+    }
+}
+
+#[derive(GlimpsePlugin)]
+#[glimpse(
+    id = "me.aresa.glimpse.recent",
+    name = "Recent documents",
+    description = "Reopens recently used documents.",
+    author = "Alex Oleshkevich <alex.oleshkevich@gmail.com>",
+    prefix = "recent ",
+    permissions(HomeRead)
+)]
+struct RecentPlugin {
+    /// The `recently-used.xbel` file.
+    path: PathBuf,
+    home: PathBuf,
+    documents: Arc<RwLock<Vec<RecentDocument>>>,
+    /// Watches for as long as the plugin runs.
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    settings: Settings<RecentSettings>,
+}
+
+impl RecentPlugin {
+    fn new(path: PathBuf, home: PathBuf) -> Self {
+        Self {
+            path,
+            home,
+            documents: Arc::new(RwLock::new(vec![])),
+            watcher: Mutex::new(None),
+            settings: Settings::default(),
+        }
+    }
+
+    /// Documents of the xbel file still on disk. A missing file lists none.
+    fn load(path: &Path) -> Vec<RecentDocument> {
+        let xml = match std::fs::read_to_string(path) {
+            Ok(xml) => xml,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return vec![],
+            Err(err) => {
+                tracing::warn!("failed to read {}: {}", path.display(), err);
+                return vec![];
+            }
+        };
+        match recent::parse(&xml) {
+            Ok(documents) => documents
+                .into_iter()
+                .filter(|document| document.path.exists())
+                .collect(),
+            Err(err) => {
+                tracing::warn!("failed to parse {}: {}", path.display(), err);
+                vec![]
+            }
+        }
+    }
+
+    /// Read the list again whenever the file is written. Applications replace it rather than
+    /// write in place, so its directory is watched.
+    fn watch(
+        path: PathBuf,
+        documents: Arc<RwLock<Vec<RecentDocument>>>,
+    ) -> Result<RecommendedWatcher, PluginError> {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let watched = path.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(err) => {
+                        tracing::warn!("watch error: {}", err);
+                        return;
+                    }
+                };
+                let relevant = matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                );
+                if relevant && event.paths.contains(&watched) {
+                    let _ = tx.send(());
+                }
+            })
+            .map_err(|e| PluginError::Other(e.to_string()))?;
+        let dir = path.parent().unwrap_or(Path::new("/"));
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| PluginError::Other(e.to_string()))?;
+
+        tokio::spawn(async move {
+            while rx.recv().await.is_some() {
+                // one reload for a burst of events
+                while rx.try_recv().is_ok() {}
+                let load_path = path.clone();
+                if let Ok(loaded) =
+                    tokio::task::spawn_blocking(move || Self::load(&load_path)).await
+                {
+                    tracing::debug!("reloaded {} recent documents", loaded.len());
+                    *documents.write().unwrap() = loaded;
+                }
+            }
+        });
+        Ok(watcher)
+    }
+
+    fn to_match(&self, document: &RecentDocument, score: f64) -> Match {
+        let folder = document.path.parent().unwrap_or(Path::new("/"));
+        let display_folder = match folder.strip_prefix(&self.home) {
+            Ok(relative) => format!("~/{}", relative.display()),
+            Err(_) => folder.display().to_string(),
+        };
+        let description = match &document.application {
+            Some(application) => format!("{} · {}", display_folder, application),
+            None => display_folder,
+        };
+
+        Match {
+            title: document.name(),
+            description,
+            icon: Some(Icon::freedesktop(document.icon_name())),
+            actions: vec![MatchAction {
+                title: "Open".to_string(),
+                action: Action::Open {
+                    uri: document.uri.clone(),
+                },
+                close_on_action: true,
+                alternates: vec![AlternateAction {
+                    modifiers: Modifiers {
+                        shift: true,
+                        ..Default::default()
+                    },
+                    title: "Open folder".to_string(),
+                    action: Action::open_file(folder),
+                }],
+                requires_confirmation: false,
+                confirmation_prompt: None,
+                expand: vec![],
+            }],
+            score,
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for RecentPlugin {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            config_schema: Some(
+                ConfigSchema::new().field(
+                    ConfigField::new("max_results", ConfigKind::Integer)
+                        .default_value(DEFAULT_MAX_RESULTS)
+                        .description("Maximum number of documents returned per search"),
+                ),
+            ),
+            ..Self::plugin_metadata()
+        }
+    }
+
+    async fn configure(&self, config: serde_json::Value) -> Result<(), PluginError> {
+        self.settings.apply(config)
+    }
+
+    async fn initialize(&self, _context: &Context) -> Result<(), PluginError> {
+        let path = self.path.clone();
+        let loaded = tokio::task::spawn_blocking(move || Self::load(&path))
+            .await
+            .map_err(|e| PluginError::Other(e.to_string()))?;
+        tracing::info!("loaded {} recent documents", loaded.len());
+        *self.documents.write().unwrap() = loaded;
+
+        match Self::watch(self.path.clone(), self.documents.clone()) {
+            Ok(watcher) => *self.watcher.lock().unwrap() = Some(watcher),
+            Err(e) => tracing::warn!("failed to watch {}: {}", self.path.display(), e),
+        }
+        Ok(())
+    }
+
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        let settings = self.settings.get();
+        let documents = self.documents.read().unwrap();
+        Ok(recent::search(&documents, &query)
+            .into_iter()
+            .take(settings.max_results)
+            .map(|(document, score)| self.to_match(document, score))
+            .collect())
+    }
+}
+
+#[glimpse_sdk::main]
+async fn main() -> Result<RecentPlugin, Box<dyn Error>> {
+    let home = dirs::home_dir().ok_or("cannot determine the home directory")?;
+    let data_dir = dirs::data_dir().ok_or("cannot determine the data directory")?;
+    Ok(RecentPlugin::new(data_dir.join("recently-used.xbel"), home))
+}
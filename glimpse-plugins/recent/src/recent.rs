@@ -0,0 +1,140 @@
+use std::path::{Path, PathBuf};
+
+use glimpse_sdk::matcher::Matcher;
+
+const BOOKMARK_NAMESPACE: &str = "http://www.freedesktop.org/standards/desktop-bookmarks";
+const MIME_NAMESPACE: &str = "http://www.freedesktop.org/standards/shared-mime-info";
+
+/// Words of an application's command line that launch it rather than name it.
+const LAUNCHERS: &[&str] = &["env", "flatpak", "run", "snap"];
+
+/// A document from `recently-used.xbel`, the list GTK and KDE applications add the files
+/// they open to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecentDocument {
+    pub path: PathBuf,
+    pub uri: String,
+    pub mime_type: Option<String>,
+    /// Name of the application that opened it last, e.g. `Document Viewer`.
+    pub application: Option<String>,
+    /// Command line of that application, e.g. `evince %u`.
+    pub exec: Option<String>,
+    /// When it was last opened, added or changed, as written in the file: ISO 8601 in UTC,
+    /// which sorts as text.
+    pub used: String,
+}
+
+impl RecentDocument {
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.to_string_lossy().to_string())
+    }
+
+    /// Theme icon of the application that opened the document, guessed from its command
+    /// line, or of the document's type.
+    pub fn icon_name(&self) -> String {
+        if let Some(program) = self.exec.as_deref().and_then(program) {
+            return program;
+        }
+        match &self.mime_type {
+            Some(mime_type) => mime_type.replace('/', "-"),
+            None => "text-x-generic".to_string(),
+        }
+    }
+}
+
+/// The program an application's command line runs, `evince` of `'evince %u'` or
+/// `org.gnome.TextEditor` of `flatpak run org.gnome.TextEditor %U`.
+pub fn program(exec: &str) -> Option<String> {
+    exec.trim_matches(|c| c == '\'' || c == '"')
+        .split_whitespace()
+        .filter(|word| !word.starts_with(['-', '%']) && !word.contains('='))
+        .find(|word| !LAUNCHERS.contains(word))
+        .and_then(|word| Path::new(word).file_name())
+        .map(|name| name.to_string_lossy().to_string())
+}
+
+/// Local documents of an xbel file, most recently used first. Bookmarks of other than
+/// `file://` addresses are left out.
+pub fn parse(xml: &str) -> Result<Vec<RecentDocument>, roxmltree::Error> {
+    let document = roxmltree::Document::parse(xml)?;
+    let mut documents = document
+        .root_element()
+        .children()
+        .filter(|node| node.has_tag_name("bookmark"))
+        .filter_map(|bookmark| {
+            let uri = bookmark.attribute("href")?;
+            let path = file_path(uri)?;
+            let used = ["visited", "modified", "added"]
+                .iter()
+                .filter_map(|name| bookmark.attribute(*name))
+                .max()
+                .unwrap_or_default();
+            let mime_type = bookmark
+                .descendants()
+                .find(|node| node.has_tag_name((MIME_NAMESPACE, "mime-type")))
+                .and_then(|node| node.attribute("type"));
+            // the application to use it last, applications list in no particular order
+            let application = bookmark
+                .descendants()
+                .filter(|node| node.has_tag_name((BOOKMARK_NAMESPACE, "application")))
+                .max_by_key(|node| node.attribute("modified").unwrap_or_default());
+            Some(RecentDocument {
+                path,
+                uri: uri.to_string(),
+                mime_type: mime_type.map(str::to_string),
+                application: application
+                    .and_then(|node| node.attribute("name"))
+                    .map(str::to_string),
+                exec: application
+                    .and_then(|node| node.attribute("exec"))
+                    .map(str::to_string),
+                used: used.to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+    documents.sort_by(|a, b| b.used.cmp(&a.used));
+    Ok(documents)
+}
+
+/// The local path of a `file://` URI, percent escapes decoded.
+pub fn file_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match bytes[i] {
+            b'%' => path
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    let path = PathBuf::from(String::from_utf8_lossy(&decoded).into_owned());
+    path.is_absolute().then_some(path)
+}
+
+/// Documents matching `query` by file name, best first and the most recent of equals first.
+/// An empty query lists them all, most recent first.
+pub fn search<'a>(documents: &'a [RecentDocument], query: &str) -> Vec<(&'a RecentDocument, f64)> {
+    let matcher = Matcher::new(query);
+    let mut found = documents
+        .iter()
+        .filter_map(|document| Some((document, matcher.score(&document.name())?)))
+        .collect::<Vec<_>>();
+    found.sort_by(|a, b| b.1.total_cmp(&a.1));
+    found
+}
@@ -0,0 +1,131 @@
+use std::path::PathBuf;
+
+use glimpse_plugins_recent::recent::{self, RecentDocument};
+
+const XBEL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/alex/Documents/Annual%20report.pdf" added="2025-03-01T09:00:00.000000Z" modified="2025-03-02T10:00:00.000000Z" visited="2025-03-02T10:00:00.000000Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="application/pdf"/>
+        <bookmark:applications>
+          <bookmark:application name="Document Viewer" exec="&apos;evince %u&apos;" modified="2025-03-02T10:00:00.000000Z" count="2"/>
+          <bookmark:application name="Firefox" exec="&apos;firefox %u&apos;" modified="2025-03-01T09:00:00.000000Z" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/alex/notes.txt" added="2025-03-05T12:00:00Z" modified="2025-03-05T12:00:00Z" visited="2025-03-05T12:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="sftp://server/etc/hosts" added="2025-03-06T12:00:00Z" modified="2025-03-06T12:00:00Z" visited="2025-03-06T12:00:00Z"/>
+</xbel>
+"#;
+
+fn document(path: &str, used: &str) -> RecentDocument {
+    RecentDocument {
+        path: PathBuf::from(path),
+        uri: format!("file://{}", path),
+        mime_type: None,
+        application: None,
+        exec: None,
+        used: used.to_string(),
+    }
+}
+
+#[test]
+fn test_parse_local_documents_most_recent_first() {
+    let documents = recent::parse(XBEL).unwrap();
+
+    assert_eq!(
+        documents,
+        vec![
+            RecentDocument {
+                path: PathBuf::from("/home/alex/notes.txt"),
+                uri: "file:///home/alex/notes.txt".to_string(),
+                mime_type: Some("text/plain".to_string()),
+                application: None,
+                exec: None,
+                used: "2025-03-05T12:00:00Z".to_string(),
+            },
+            RecentDocument {
+                path: PathBuf::from("/home/alex/Documents/Annual report.pdf"),
+                uri: "file:///home/alex/Documents/Annual%20report.pdf".to_string(),
+                mime_type: Some("application/pdf".to_string()),
+                application: Some("Document Viewer".to_string()),
+                exec: Some("'evince %u'".to_string()),
+                used: "2025-03-02T10:00:00.000000Z".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_rejects_invalid_xml() {
+    assert!(recent::parse("<xbel>").is_err());
+    assert!(recent::parse("<xbel version=\"1.0\"/>").unwrap().is_empty());
+}
+
+#[test]
+fn test_file_path() {
+    assert_eq!(
+        recent::file_path("file:///tmp/a%20b%C3%A9.txt"),
+        Some(PathBuf::from("/tmp/a bé.txt"))
+    );
+    assert_eq!(
+        recent::file_path("file:///tmp/100%"),
+        Some(PathBuf::from("/tmp/100%"))
+    );
+    assert_eq!(recent::file_path("https://example.com/a.pdf"), None);
+    assert_eq!(recent::file_path("file://relative"), None);
+}
+
+#[test]
+fn test_program() {
+    assert_eq!(recent::program("'evince %u'"), Some("evince".to_string()));
+    assert_eq!(
+        recent::program("'/usr/bin/gnome-text-editor %U'"),
+        Some("gnome-text-editor".to_string())
+    );
+    assert_eq!(
+        recent::program("'flatpak run --branch=stable org.gnome.TextEditor %U'"),
+        Some("org.gnome.TextEditor".to_string())
+    );
+    assert_eq!(recent::program("''"), None);
+}
+
+#[test]
+fn test_icon_name() {
+    let mut document = document("/tmp/a.pdf", "");
+    assert_eq!(document.icon_name(), "text-x-generic");
+    document.mime_type = Some("application/pdf".to_string());
+    assert_eq!(document.icon_name(), "application-pdf");
+    document.exec = Some("'evince %u'".to_string());
+    assert_eq!(document.icon_name(), "evince");
+}
+
+#[test]
+fn test_search_by_name_keeps_recency_among_equals() {
+    let documents = vec![
+        document("/home/alex/report.pdf", "2025-03-05T00:00:00Z"),
+        document("/home/alex/old/report.pdf", "2025-01-01T00:00:00Z"),
+        document("/home/alex/reports/notes.txt", "2025-03-01T00:00:00Z"),
+    ];
+
+    let found = recent::search(&documents, "report");
+    assert_eq!(
+        found
+            .iter()
+            .map(|(document, _)| document.path.to_str().unwrap())
+            .collect::<Vec<_>>(),
+        vec!["/home/alex/report.pdf", "/home/alex/old/report.pdf"]
+    );
+    assert_eq!(recent::search(&documents, "").len(), 3);
+}
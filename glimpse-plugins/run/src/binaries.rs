@@ -0,0 +1,37 @@
+use std::{collections::BTreeSet, ffi::OsStr, os::unix::fs::PermissionsExt};
+
+/// Names of the executables found in a `$PATH`-style list of directories.
+pub fn scan(path: &OsStr) -> Vec<String> {
+    let mut names = BTreeSet::new();
+    for dir in std::env::split_paths(path) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            // follow symlinks, most of /usr/bin is links
+            let Ok(metadata) = std::fs::metadata(entry.path()) else {
+                continue;
+            };
+            if !metadata.is_file() || metadata.permissions().mode() & 0o111 == 0 {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str() {
+                names.insert(name.to_string());
+            }
+        }
+    }
+    names.into_iter().collect()
+}
+
+/// Names in sorted `binaries` starting with `prefix`, shortest first.
+pub fn complete<'a>(binaries: &'a [String], prefix: &str, limit: usize) -> Vec<&'a str> {
+    let start = binaries.partition_point(|name| name.as_str() < prefix);
+    let mut found = binaries[start..]
+        .iter()
+        .take_while(|name| name.starts_with(prefix))
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    found.sort_by_key(|name| name.len());
+    found.truncate(limit);
+    found
+}
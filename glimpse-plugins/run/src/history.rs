@@ -0,0 +1,117 @@
+use std::{io, os::unix::fs::PermissionsExt, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+const DAY: u64 = 24 * 60 * 60;
+/// Runs kept per command, older ones no longer change its rank much.
+const MAX_RUNS: usize = 10;
+/// Commands kept overall, the least used are forgotten first.
+const MAX_COMMANDS: usize = 200;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RunCommand {
+    pub line: String,
+    /// Unix timestamps of the latest runs in seconds, oldest first.
+    pub runs: Vec<u64>,
+}
+
+impl RunCommand {
+    pub fn frecency(&self, now: u64) -> f64 {
+        self.runs
+            .iter()
+            .map(|ran_at| weight(now.saturating_sub(*ran_at)))
+            .sum()
+    }
+}
+
+/// Same buckets the daemon ranks activations with: recent runs count the most.
+fn weight(age_seconds: u64) -> f64 {
+    match age_seconds / DAY {
+        0..4 => 100.0,
+        4..14 => 70.0,
+        14..31 => 50.0,
+        31..90 => 30.0,
+        _ => 10.0,
+    }
+}
+
+/// Shell commands run through the plugin.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CommandHistory {
+    commands: Vec<RunCommand>,
+}
+
+impl CommandHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let data = std::fs::read(path)?;
+        serde_json::from_slice(&data).map_err(io::Error::other)
+    }
+
+    /// Write the history atomically, readable by the owner only since commands may hold secrets.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(self).map_err(io::Error::other)?)?;
+        std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(tmp, path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn record(&mut self, line: &str, now: u64) {
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        match self
+            .commands
+            .iter_mut()
+            .find(|command| command.line == line)
+        {
+            Some(command) => {
+                command.runs.push(now);
+                if command.runs.len() > MAX_RUNS {
+                    command.runs.remove(0);
+                }
+            }
+            None => self.commands.push(RunCommand {
+                line: line.to_string(),
+                runs: vec![now],
+            }),
+        }
+
+        if self.commands.len() > MAX_COMMANDS {
+            self.sort(now);
+            self.commands.truncate(MAX_COMMANDS);
+        }
+    }
+
+    /// Commands containing `query`, most frecent first. An empty query lists them all.
+    pub fn search(&self, query: &str, now: u64, limit: usize) -> Vec<&RunCommand> {
+        let mut found = self
+            .commands
+            .iter()
+            .filter(|command| command.line.contains(query))
+            .collect::<Vec<_>>();
+        found.sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+        found.truncate(limit);
+        found
+    }
+
+    fn sort(&mut self, now: u64) {
+        self.commands
+            .sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+    }
+}
@@ -0,0 +1,2 @@
+pub mod binaries;
+pub mod history;
@@ -0,0 +1,264 @@
+use std::{
+    error::Error,
+    os::unix::process::CommandExt,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use glimpse_plugins_run::{
+    binaries,
+    history::{CommandHistory, RunCommand},
+};
+use glimpse_sdk::{
+    Action, ConfigField, ConfigKind, ConfigSchema, Context, Match, MatchAction, Metadata,
+    Permission, Plugin, PluginError, PowerProfile, Settings, run_plugin, setup_logging,
+};
+use serde::Deserialize;
+use tokio::sync::watch;
+
+const DEFAULT_MAX_RESULTS: usize = 20;
+/// New binaries show up when packages are installed, no need to look often.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(300);
+/// Argument of the plugin binary that records a command and runs it.
+const EXEC_FLAG: &str = "--exec";
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+struct RunSettings {
+    max_results: usize,
+}
+
+impl Default for RunSettings {
+    fn default() -> Self {
+        Self {
+            max_results: DEFAULT_MAX_RESULTS,
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+fn history_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("glimpse")
+        .join("run-history.json")
+}
+
+/// Commands are run by the daemon through this binary, which records them first:
+/// `glimpse-plugins-run --exec <line>`.
+fn record_and_exec(line: &str) -> Result<(), Box<dyn Error>> {
+    let path = history_path();
+    let mut history = match CommandHistory::load(&path) {
+        Ok(history) => history,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => CommandHistory::new(),
+        Err(err) => return Err(err.into()),
+    };
+    history.record(line, now());
+    if let Err(err) = history.save(&path) {
+        eprintln!("failed to save run history: {}", err);
+    }
+    // only returns on failure
+    Err(std::process::Command::new("sh")
+        .args(["-c", line])
+        .exec()
+        .into())
+}
+
+/// The history file as last read, reloaded when `--exec` changed it.
+struct StoredHistory {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    history: CommandHistory,
+}
+
+impl StoredHistory {
+    fn refresh(&mut self) {
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if modified.is_none() || modified == self.modified {
+            return;
+        }
+        match CommandHistory::load(&self.path) {
+            Ok(history) => self.history = history,
+            Err(err) => tracing::warn!("failed to load run history: {}", err),
+        }
+        self.modified = modified;
+    }
+}
+
+struct RunPlugin {
+    exe: Option<PathBuf>,
+    history: Mutex<StoredHistory>,
+    binaries: Arc<RwLock<Vec<String>>>,
+    settings: Settings<RunSettings>,
+    power: watch::Sender<PowerProfile>,
+}
+
+impl RunPlugin {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            exe: std::env::current_exe().ok(),
+            history: Mutex::new(StoredHistory {
+                path,
+                modified: None,
+                history: CommandHistory::new(),
+            }),
+            binaries: Arc::new(RwLock::new(vec![])),
+            settings: Settings::default(),
+            power: watch::Sender::new(PowerProfile::Normal),
+        }
+    }
+
+    /// Run `line` through this binary so it gets remembered, or straight in a shell if the
+    /// binary cannot be found.
+    fn exec_action(exe: Option<&Path>, line: &str) -> Action {
+        match exe {
+            Some(exe) => Action::Exec {
+                command: exe.to_string_lossy().to_string(),
+                args: vec![EXEC_FLAG.to_string(), line.to_string()],
+            },
+            None => Action::Exec {
+                command: "sh".to_string(),
+                args: vec!["-c".to_string(), line.to_string()],
+            },
+        }
+    }
+
+    fn to_match(&self, line: &str, description: String, score: f64) -> Match {
+        Match {
+            title: line.to_string(),
+            description,
+            actions: vec![MatchAction {
+                title: "Run".to_string(),
+                close_on_action: true,
+                action: Self::exec_action(self.exe.as_deref(), line),
+                alternates: vec![],
+                requires_confirmation: false,
+                confirmation_prompt: None,
+                expand: vec![],
+            }],
+            score,
+            ..Default::default()
+        }
+    }
+
+    fn describe(command: &RunCommand) -> String {
+        match command.runs.len() {
+            1 => "Ran once".to_string(),
+            runs => format!("Ran {} times", runs),
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for RunPlugin {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            id: "me.aresa.glimpse.run".to_string(),
+            name: "Run".to_string(),
+            version: "0.1.0".to_string(),
+            description: "Runs shell commands and remembers the ones used most.".to_string(),
+            author: "Alex Oleshkevich <alex.oleshkevich@gmail.com>".to_string(),
+            permissions: vec![Permission::Exec],
+            prefix: Some(">".to_string()),
+            prefix_only: true,
+            config_schema: Some(
+                ConfigSchema::new().field(
+                    ConfigField::new("max_results", ConfigKind::Integer)
+                        .default_value(DEFAULT_MAX_RESULTS)
+                        .description("Maximum number of commands returned per search"),
+                ),
+            ),
+            ..Default::default()
+        }
+    }
+
+    async fn configure(&self, config: serde_json::Value) -> Result<(), PluginError> {
+        self.settings.apply(config)
+    }
+
+    async fn power_profile_changed(&self, profile: PowerProfile) -> Result<(), PluginError> {
+        self.power.send_replace(profile);
+        Ok(())
+    }
+
+    async fn initialize(&self, _context: &Context) -> Result<(), PluginError> {
+        let binaries = self.binaries.clone();
+        let power = self.power.subscribe();
+        tokio::spawn(async move {
+            let mut rescan = tokio::time::interval(RESCAN_INTERVAL);
+            loop {
+                rescan.tick().await;
+                if *power.borrow() == PowerProfile::LowPower && !binaries.read().unwrap().is_empty()
+                {
+                    continue;
+                }
+                let path = std::env::var_os("PATH").unwrap_or_default();
+                if let Ok(found) = tokio::task::spawn_blocking(move || binaries::scan(&path)).await
+                {
+                    tracing::debug!("found {} binaries in PATH", found.len());
+                    *binaries.write().unwrap() = found;
+                }
+            }
+        });
+        Ok(())
+    }
+
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        let limit = self.settings.get().max_results;
+        let query = query.trim();
+        let now = now();
+
+        let mut stored = self.history.lock().unwrap();
+        stored.refresh();
+        let mut matches = vec![];
+        if !query.is_empty() {
+            matches.push(self.to_match(query, "Run in a shell".to_string(), 1.0));
+        }
+        for command in stored.history.search(query, now, limit) {
+            if command.line != query {
+                let score = 0.9 / (1 + matches.len()) as f64;
+                matches.push(self.to_match(&command.line, Self::describe(command), score));
+            }
+        }
+
+        // complete the program name until arguments follow
+        if !query.is_empty() && !query.contains(char::is_whitespace) {
+            let binaries = self.binaries.read().unwrap();
+            for name in binaries::complete(&binaries, query, limit) {
+                if matches.iter().any(|item| item.title == name) {
+                    continue;
+                }
+                let score = 0.5 / (1 + matches.len()) as f64;
+                matches.push(self.to_match(name, "Program in PATH".to_string(), score));
+            }
+        }
+        matches.truncate(limit);
+        Ok(matches)
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let [flag, line] = args.as_slice()
+        && flag == EXEC_FLAG
+    {
+        return record_and_exec(line);
+    }
+
+    setup_logging(tracing::Level::INFO);
+    if let Err(err) = run_plugin(RunPlugin::new(history_path())).await {
+        tracing::error!("error running plugin: {}", err);
+    }
+    Ok(())
+}
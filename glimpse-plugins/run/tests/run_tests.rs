@@ -0,0 +1,88 @@
+use std::{ffi::OsString, os::unix::fs::PermissionsExt, path::Path};
+
+use glimpse_plugins_run::{binaries, history::CommandHistory};
+
+const DAY: u64 = 24 * 60 * 60;
+
+fn lines(history: &CommandHistory, query: &str, now: u64) -> Vec<String> {
+    history
+        .search(query, now, 10)
+        .into_iter()
+        .map(|command| command.line.clone())
+        .collect()
+}
+
+fn create_binary(dir: &Path, name: &str, mode: u32) {
+    let path = dir.join(name);
+    std::fs::write(&path, "#!/bin/sh\n").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+}
+
+#[test]
+fn test_search_ranks_by_frecency() {
+    let now = 100 * DAY;
+    let mut history = CommandHistory::new();
+    // run often, but months ago
+    for _ in 0..3 {
+        history.record("make build", now - 60 * DAY);
+    }
+    history.record("  make test ", now - DAY);
+    history.record("make test", now);
+    history.record("htop", now);
+
+    assert_eq!(
+        lines(&history, "make", now),
+        vec!["make test", "make build"]
+    );
+    assert_eq!(lines(&history, "", now)[0], "make test");
+    assert_eq!(history.len(), 3);
+}
+
+#[test]
+fn test_record_ignores_blank_commands() {
+    let mut history = CommandHistory::new();
+    history.record("   ", 1);
+    assert!(history.is_empty());
+}
+
+#[test]
+fn test_save_and_load() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("glimpse").join("run-history.json");
+    let mut history = CommandHistory::new();
+    history.record("ls -la", 1);
+
+    history.save(&path).unwrap();
+
+    assert_eq!(CommandHistory::load(&path).unwrap(), history);
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[test]
+fn test_scan_finds_executables() {
+    let first = tempfile::tempdir().unwrap();
+    let second = tempfile::tempdir().unwrap();
+    create_binary(first.path(), "firefox", 0o755);
+    create_binary(first.path(), "notes.txt", 0o644);
+    create_binary(second.path(), "firefox", 0o755);
+    create_binary(second.path(), "fish", 0o700);
+    std::fs::create_dir(second.path().join("fdir")).unwrap();
+
+    let path =
+        std::env::join_paths([first.path(), second.path(), Path::new("/nonexistent")]).unwrap();
+    assert_eq!(binaries::scan(&path), vec!["firefox", "fish"]);
+    assert!(binaries::scan(&OsString::new()).is_empty());
+}
+
+#[test]
+fn test_complete_prefers_short_names() {
+    let binaries = ["cat", "fd", "firefox", "fish", "fishd", "git"].map(String::from);
+
+    assert_eq!(
+        binaries::complete(&binaries, "fi", 10),
+        vec!["fish", "fishd", "firefox"]
+    );
+    assert_eq!(binaries::complete(&binaries, "fi", 1), vec!["fish"]);
+    assert!(binaries::complete(&binaries, "zsh", 10).is_empty());
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use glimpse_sdk::matcher::Matcher;
+
+/// Attributes naming the account of a stored secret, in the order they are preferred.
+/// Applications store it under names of their own, these cover the common ones.
+const USERNAME_ATTRIBUTES: &[&str] = &[
+    "username",
+    "user",
+    "login",
+    "account",
+    "username_value",
+    "email",
+];
+
+/// A secret stored in the Secret Service, without the secret itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Credential {
+    /// D-Bus object path of the item.
+    pub path: String,
+    pub label: String,
+    pub username: Option<String>,
+    /// Locked secrets are unlocked by the user before they can be read.
+    pub locked: bool,
+}
+
+impl Credential {
+    pub fn new(
+        path: impl Into<String>,
+        label: impl Into<String>,
+        attributes: &HashMap<String, String>,
+        locked: bool,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            label: label.into(),
+            username: username(attributes),
+            locked,
+        }
+    }
+}
+
+/// The account a secret belongs to, from the first of the usual attributes that is set.
+pub fn username(attributes: &HashMap<String, String>) -> Option<String> {
+    USERNAME_ATTRIBUTES
+        .iter()
+        .filter_map(|name| attributes.get(*name))
+        .find(|value| !value.trim().is_empty())
+        .cloned()
+}
+
+/// Credentials matching `query` by label, or counting half by username, best first. An empty
+/// query finds nothing, listing every secret is not what a launcher is for.
+pub fn search<'a>(credentials: &'a [Credential], query: &str) -> Vec<(&'a Credential, f64)> {
+    if query.trim().is_empty() {
+        return vec![];
+    }
+    let matcher = Matcher::new(query);
+    let mut found = credentials
+        .iter()
+        .filter_map(|credential| {
+            let label = matcher.score(&credential.label);
+            let username = credential
+                .username
+                .as_deref()
+                .and_then(|username| matcher.score(username))
+                .map(|score| score / 2.0);
+            let score = label.into_iter().chain(username).reduce(f64::max)?;
+            Some((credential, score))
+        })
+        .collect::<Vec<_>>();
+    found.sort_by(|a, b| b.1.total_cmp(&a.1));
+    found
+}
@@ -0,0 +1,231 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use glimpse_plugins_secrets::{
+    credentials::{self, Credential},
+    secret_service::SecretService,
+};
+use glimpse_sdk::{
+    Action, AlternateAction, ConfigField, ConfigKind, ConfigSchema, GlimpsePlugin, Icon, Match,
+    MatchAction, Metadata, Modifiers, Plugin, PluginError, PluginMetadata, Progress, Settings,
+};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+const DEFAULT_MAX_RESULTS: usize = 5;
+const DEFAULT_CLEAR_AFTER_SECS: u64 = 30;
+
+/// How long the list of stored secrets is reused before it is read again. Reading it takes a
+/// few calls per secret, too many for every key press.
+const LIST_TTL: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+struct SecretsSettings {
+    max_results: usize,
+    /// Seconds before a copied password or username is cleared from the clipboard.
+    clear_after_secs: u64,
+}
+
+impl Default for SecretsSettings {
+    fn default() -> Self {
+        Self {
+            max_results: DEFAULT_MAX_RESULTS,
+            clear_after_secs: DEFAULT_CLEAR_AFTER_SECS,
+        }
+    }
+}
+
+#[derive(GlimpsePlugin)]
+#[glimpse(
+    id = "me.aresa.glimpse.secrets",
+    name = "Passwords",
+    description = "Copies passwords and usernames stored in the Secret Service.",
+    author = "Alex Oleshkevich <alex.oleshkevich@gmail.com>",
+    prefix = "pass ",
+    permissions(Clipboard, Secrets),
+    sensitive
+)]
+struct SecretsPlugin {
+    service: SecretService,
+    listed: Mutex<Option<(Instant, Vec<Credential>)>>,
+    settings: Settings<SecretsSettings>,
+}
+
+impl SecretsPlugin {
+    fn new(service: SecretService) -> Self {
+        Self {
+            service,
+            listed: Mutex::new(None),
+            settings: Settings::default(),
+        }
+    }
+
+    async fn credentials(&self) -> Result<Vec<Credential>, PluginError> {
+        let mut listed = self.listed.lock().await;
+        if let Some((at, credentials)) = listed.as_ref()
+            && at.elapsed() < LIST_TTL
+        {
+            return Ok(credentials.clone());
+        }
+        let credentials = self
+            .service
+            .credentials()
+            .await
+            .map_err(|e| PluginError::Other(e.to_string()))?;
+        *listed = Some((Instant::now(), credentials.clone()));
+        Ok(credentials)
+    }
+
+    /// Copy actions for an unlocked secret. Its password is read only once the match is
+    /// activated, so search results never carry it.
+    fn copy_action(settings: &SecretsSettings, credential: &Credential) -> MatchAction {
+        let alternates = credential
+            .username
+            .iter()
+            .map(|username| AlternateAction {
+                modifiers: Modifiers {
+                    shift: true,
+                    ..Default::default()
+                },
+                title: "Copy username".to_string(),
+                action: Action::ExpiringClipboard {
+                    text: username.as_str().into(),
+                    clear_after_secs: settings.clear_after_secs,
+                },
+            })
+            .collect();
+        MatchAction {
+            title: "Copy password".to_string(),
+            action: Action::Callback {
+                key: "copy".to_string(),
+                params: HashMap::from([("item".to_string(), credential.path.clone())]),
+            },
+            close_on_action: true,
+            alternates,
+            requires_confirmation: false,
+            confirmation_prompt: None,
+            expand: vec![],
+        }
+    }
+
+    /// A locked secret can only be unlocked from here, its matches offer copying once it is.
+    fn unlock_action(credential: &Credential) -> MatchAction {
+        MatchAction {
+            title: "Unlock".to_string(),
+            action: Action::Callback {
+                key: "unlock".to_string(),
+                params: HashMap::from([("item".to_string(), credential.path.clone())]),
+            },
+            close_on_action: false,
+            alternates: vec![],
+            requires_confirmation: false,
+            confirmation_prompt: None,
+            expand: vec![],
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for SecretsPlugin {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            config_schema: Some(
+                ConfigSchema::new()
+                    .field(
+                        ConfigField::new("max_results", ConfigKind::Integer)
+                            .default_value(DEFAULT_MAX_RESULTS)
+                            .description("Maximum number of secrets returned per search"),
+                    )
+                    .field(
+                        ConfigField::new("clear_after_secs", ConfigKind::Integer)
+                            .default_value(DEFAULT_CLEAR_AFTER_SECS)
+                            .description("Seconds before a copied secret is cleared"),
+                    ),
+            ),
+            ..Self::plugin_metadata()
+        }
+    }
+
+    async fn configure(&self, config: serde_json::Value) -> Result<(), PluginError> {
+        self.settings.apply(config)
+    }
+
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        let settings = self.settings.get();
+        let credentials = self.credentials().await?;
+        let mut matches = vec![];
+        for (credential, score) in credentials::search(&credentials, &query)
+            .into_iter()
+            .take(settings.max_results)
+        {
+            let action = match credential.locked {
+                true => Self::unlock_action(credential),
+                false => Self::copy_action(&settings, credential),
+            };
+            let description = match (&credential.username, credential.locked) {
+                (Some(username), true) => format!("{} · locked", username),
+                (Some(username), false) => username.clone(),
+                (None, true) => "Locked".to_string(),
+                (None, false) => String::new(),
+            };
+            matches.push(Match {
+                title: credential.label.clone(),
+                description,
+                icon: Some(Icon::freedesktop("dialog-password")),
+                actions: vec![action],
+                score,
+                ..Default::default()
+            });
+        }
+        Ok(matches)
+    }
+
+    async fn call_action(
+        &self,
+        action: String,
+        params: HashMap<String, String>,
+        progress: &Progress,
+    ) -> Result<(), PluginError> {
+        let Some(item) = params.get("item").filter(|_| action == "copy") else {
+            self.handle_action(action, params).await;
+            return Ok(());
+        };
+        let password = self
+            .service
+            .secret(item)
+            .await
+            .map_err(|e| PluginError::Other(e.to_string()))?;
+        progress
+            .run(Action::ExpiringClipboard {
+                text: password,
+                clear_after_secs: self.settings.get().clear_after_secs,
+            })
+            .await
+    }
+
+    async fn handle_action(&self, action: String, params: HashMap<String, String>) {
+        let Some(item) = params.get("item").filter(|_| action == "unlock") else {
+            tracing::warn!("unknown action: {} {:?}", action, params);
+            return;
+        };
+        match self.service.unlock(item).await {
+            Ok(true) => {
+                // the listing says the secret is locked, read it again next search
+                *self.listed.lock().await = None;
+            }
+            Ok(false) => tracing::debug!("unlocking {} was dismissed", item),
+            Err(e) => tracing::warn!("failed to unlock {}: {}", item, e),
+        }
+    }
+}
+
+#[glimpse_sdk::main]
+async fn main() -> Result<SecretsPlugin, Box<dyn Error>> {
+    let service = SecretService::connect().await?;
+    Ok(SecretsPlugin::new(service))
+}
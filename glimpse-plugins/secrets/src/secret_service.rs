@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use futures::StreamExt;
+use glimpse_sdk::Sensitive;
+use zbus::{
+    Connection, Proxy,
+    proxy::{Builder, CacheProperties},
+    zvariant::{OwnedObjectPath, OwnedValue, Value},
+};
+use zeroize::Zeroize;
+
+use crate::credentials::Credential;
+
+const SERVICE: &str = "org.freedesktop.secrets";
+const SERVICE_PATH: &str = "/org/freedesktop/secrets";
+const SERVICE_INTERFACE: &str = "org.freedesktop.Secret.Service";
+const COLLECTION_INTERFACE: &str = "org.freedesktop.Secret.Collection";
+const ITEM_INTERFACE: &str = "org.freedesktop.Secret.Item";
+const PROMPT_INTERFACE: &str = "org.freedesktop.Secret.Prompt";
+
+/// The path standing for "no object", returned where no prompt is needed.
+const NO_OBJECT: &str = "/";
+
+/// Client of the freedesktop Secret Service, served by GNOME Keyring, KWallet and KeePassXC.
+///
+/// Secrets travel in the clear over the session bus, which only the user's processes reach,
+/// so no encrypted session is negotiated.
+pub struct SecretService {
+    connection: Connection,
+    service: Proxy<'static>,
+    session: OwnedObjectPath,
+}
+
+impl SecretService {
+    pub async fn connect() -> zbus::Result<Self> {
+        let connection = Connection::session().await?;
+        let service = proxy(&connection, SERVICE_PATH, SERVICE_INTERFACE).await?;
+        let (_, session): (OwnedValue, OwnedObjectPath) = service
+            .call("OpenSession", &("plain", Value::from("")))
+            .await?;
+        Ok(Self {
+            connection,
+            service,
+            session,
+        })
+    }
+
+    /// Every item of every collection, locked ones included; their labels stay readable.
+    pub async fn credentials(&self) -> zbus::Result<Vec<Credential>> {
+        let collections: Vec<OwnedObjectPath> = self.service.get_property("Collections").await?;
+        let mut credentials = vec![];
+        for collection in collections {
+            let collection =
+                proxy(&self.connection, collection.as_str(), COLLECTION_INTERFACE).await?;
+            let items: Vec<OwnedObjectPath> = collection.get_property("Items").await?;
+            for item in items {
+                match self.credential(&item).await {
+                    Ok(credential) => credentials.push(credential),
+                    Err(e) => tracing::warn!("failed to read secret {}: {}", item.as_str(), e),
+                }
+            }
+        }
+        Ok(credentials)
+    }
+
+    async fn credential(&self, path: &OwnedObjectPath) -> zbus::Result<Credential> {
+        let item = proxy(&self.connection, path.as_str(), ITEM_INTERFACE).await?;
+        let label: String = item.get_property("Label").await?;
+        let attributes: HashMap<String, String> = item.get_property("Attributes").await?;
+        let locked: bool = item.get_property("Locked").await?;
+        Ok(Credential::new(path.as_str(), label, &attributes, locked))
+    }
+
+    /// The secret of the item at `path`, which must be unlocked.
+    pub async fn secret(&self, path: &str) -> zbus::Result<Sensitive<String>> {
+        let item = proxy(&self.connection, path, ITEM_INTERFACE).await?;
+        let (_, _, value, _): (OwnedObjectPath, Vec<u8>, Vec<u8>, String) =
+            item.call("GetSecret", &(&self.session,)).await?;
+        String::from_utf8(value).map(Sensitive::new).map_err(|err| {
+            err.into_bytes().zeroize();
+            zbus::Error::Failure(format!("the secret of {} is not text", path))
+        })
+    }
+
+    /// Unlock the item at `path`, the service asking the user for the keyring password.
+    /// Returns whether it got unlocked, false if the user dismissed the prompt.
+    pub async fn unlock(&self, path: &str) -> zbus::Result<bool> {
+        let objects = vec![zbus::zvariant::ObjectPath::try_from(path)?];
+        let (unlocked, prompt): (Vec<OwnedObjectPath>, OwnedObjectPath) =
+            self.service.call("Unlock", &(objects,)).await?;
+        if prompt.as_str() == NO_OBJECT {
+            return Ok(!unlocked.is_empty());
+        }
+
+        let prompt = proxy(&self.connection, prompt.as_str(), PROMPT_INTERFACE).await?;
+        let mut completed = prompt.receive_signal("Completed").await?;
+        prompt.call_method("Prompt", &("",)).await?;
+        let Some(message) = completed.next().await else {
+            return Err(zbus::Error::Failure(
+                "the prompt closed without completing".to_string(),
+            ));
+        };
+        let (dismissed, _): (bool, OwnedValue) = message.body().deserialize()?;
+        Ok(!dismissed)
+    }
+}
+
+/// A proxy of the Secret Service object at `path`. Properties are read when asked for, not
+/// cached, as items are looked at once each.
+async fn proxy(
+    connection: &Connection,
+    path: &str,
+    interface: &'static str,
+) -> zbus::Result<Proxy<'static>> {
+    Builder::new(connection)
+        .destination(SERVICE)?
+        .path(path.to_string())?
+        .interface(interface)?
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use glimpse_plugins_secrets::credentials::{self, Credential, username};
+
+fn attributes(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+fn credential(label: &str, user: Option<&str>) -> Credential {
+    let attributes = attributes(
+        &user
+            .map(|user| ("username", user))
+            .into_iter()
+            .collect::<Vec<_>>(),
+    );
+    Credential::new(format!("/item/{}", label), label, &attributes, false)
+}
+
+fn labels<'a>(found: &[(&'a Credential, f64)]) -> Vec<&'a str> {
+    found
+        .iter()
+        .map(|(credential, _)| credential.label.as_str())
+        .collect()
+}
+
+#[test]
+fn test_username_from_the_preferred_attribute() {
+    let stored = attributes(&[("email", "alice@example.com"), ("user", "alice")]);
+    assert_eq!(username(&stored), Some("alice".to_string()));
+
+    // blank values are skipped for the next attribute
+    let chrome = attributes(&[("username_value", "bob"), ("username", " ")]);
+    assert_eq!(username(&chrome), Some("bob".to_string()));
+
+    assert_eq!(
+        username(&attributes(&[("xdg:schema", "org.gnome.keyring.Note")])),
+        None
+    );
+}
+
+#[test]
+fn test_search_by_label_before_username() {
+    let stored = vec![
+        credential("Work VPN", Some("github-bot")),
+        credential("GitHub", Some("alice")),
+        credential("Bank", None),
+    ];
+
+    let found = credentials::search(&stored, "github");
+
+    assert_eq!(labels(&found), vec!["GitHub", "Work VPN"]);
+    assert!(found[0].1 > found[1].1);
+}
+
+#[test]
+fn test_empty_query_lists_nothing() {
+    let stored = vec![credential("GitHub", Some("alice"))];
+
+    assert!(credentials::search(&stored, "  ").is_empty());
+    assert!(credentials::search(&stored, "bank").is_empty());
+}
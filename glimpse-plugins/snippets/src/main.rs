@@ -0,0 +1,146 @@
+use std::{error::Error, path::PathBuf};
+
+use async_trait::async_trait;
+use glimpse_plugins_snippets::snippets::{self, Snippet};
+use glimpse_sdk::{
+    Action, AlternateAction, ConfigField, ConfigKind, ConfigSchema, GlimpsePlugin, Icon, Match,
+    MatchAction, Metadata, Modifiers, Plugin, PluginError, PluginMetadata, Settings,
+};
+use serde::Deserialize;
+
+const DEFAULT_MAX_RESULTS: usize = 10;
+const PREVIEW_CHARS: usize = 80;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+struct SnippetsSettings {
+    max_results: usize,
+    /// Type snippets into the focused window on Enter, and copy them with Shift+Enter.
+    type_by_default: bool,
+}
+
+impl Default for SnippetsSettings {
+    fn default() -> Self {
+        Self {
+            max_results: DEFAULT_MAX_RESULTS,
+            type_by_default: false,
+        }
+    }
+}
+
+#[derive(GlimpsePlugin)]
+#[glimpse(
+    id = "me.aresa.glimpse.snippets",
+    name = "Snippets",
+    description = "Pastes or types text snippets from snippets.toml or snippets.json.",
+    author = "Alex Oleshkevich <alex.oleshkevich@gmail.com>",
+    prefix = "snip ",
+    permissions(Clipboard, Keyboard)
+)]
+struct SnippetsPlugin {
+    config_dir: PathBuf,
+    settings: Settings<SnippetsSettings>,
+}
+
+impl SnippetsPlugin {
+    fn new(config_dir: PathBuf) -> Self {
+        Self {
+            config_dir,
+            settings: Settings::default(),
+        }
+    }
+
+    fn to_match(settings: &SnippetsSettings, snippet: &Snippet, score: f64) -> Match {
+        let copy = (
+            "Copy",
+            Action::Clipboard {
+                text: snippet.text.as_str().into(),
+            },
+        );
+        let type_text = (
+            "Type",
+            Action::TypeText {
+                text: snippet.text.as_str().into(),
+            },
+        );
+        let ((title, action), (alternate_title, alternate)) = match settings.type_by_default {
+            true => (type_text, copy),
+            false => (copy, type_text),
+        };
+        let mut description = snippet.preview(PREVIEW_CHARS);
+        if !snippet.tags.is_empty() {
+            description = format!("{} · {}", description, snippet.tags.join(", "));
+        }
+
+        Match {
+            title: snippet.name.clone(),
+            description,
+            icon: Some(Icon::freedesktop("edit-paste")),
+            actions: vec![MatchAction {
+                title: title.to_string(),
+                action,
+                close_on_action: true,
+                alternates: vec![AlternateAction {
+                    modifiers: Modifiers {
+                        shift: true,
+                        ..Default::default()
+                    },
+                    title: alternate_title.to_string(),
+                    action: alternate,
+                }],
+                requires_confirmation: false,
+                confirmation_prompt: None,
+                expand: vec![],
+            }],
+            score,
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for SnippetsPlugin {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            config_schema: Some(
+                ConfigSchema::new()
+                    .field(
+                        ConfigField::new("max_results", ConfigKind::Integer)
+                            .default_value(DEFAULT_MAX_RESULTS)
+                            .description("Maximum number of snippets returned per search"),
+                    )
+                    .field(
+                        ConfigField::new("type_by_default", ConfigKind::Boolean)
+                            .default_value(false)
+                            .description("Type snippets on Enter instead of copying them"),
+                    ),
+            ),
+            ..Self::plugin_metadata()
+        }
+    }
+
+    async fn configure(&self, config: serde_json::Value) -> Result<(), PluginError> {
+        self.settings.apply(config)
+    }
+
+    /// The files are read again on every search, so edits show up right away.
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        let settings = self.settings.get();
+        let config_dir = self.config_dir.clone();
+        let snippets = tokio::task::spawn_blocking(move || snippets::load(&config_dir))
+            .await
+            .map_err(|e| PluginError::Other(e.to_string()))?;
+
+        Ok(snippets::search(&snippets, &query)
+            .into_iter()
+            .take(settings.max_results)
+            .map(|(snippet, score)| Self::to_match(&settings, snippet, score))
+            .collect())
+    }
+}
+
+#[glimpse_sdk::main]
+async fn main() -> Result<SnippetsPlugin, Box<dyn Error>> {
+    let config_dir = dirs::config_dir().ok_or("cannot determine the config directory")?;
+    Ok(SnippetsPlugin::new(config_dir.join("glimpse")))
+}
@@ -0,0 +1,114 @@
+use std::{error::Error, fmt::Display, path::Path};
+
+use glimpse_sdk::matcher::Matcher;
+use serde::Deserialize;
+
+/// Snippets are read from these files of the config directory, in this order.
+pub const FILES: &[&str] = &["snippets.toml", "snippets.json"];
+
+#[derive(Debug)]
+pub enum SnippetsError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+}
+
+impl Display for SnippetsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnippetsError::Io(err) => write!(f, "io: {}", err),
+            SnippetsError::Toml(err) => write!(f, "toml: {}", err),
+            SnippetsError::Json(err) => write!(f, "json: {}", err),
+        }
+    }
+}
+impl Error for SnippetsError {}
+
+/// A named piece of text, pasted or typed in place of its name.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Snippet {
+    pub name: String,
+    pub text: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl Snippet {
+    /// The first line of the text, shortened to `max_chars`.
+    pub fn preview(&self, max_chars: usize) -> String {
+        let line = self.text.lines().next().unwrap_or_default();
+        let mut preview: String = line.chars().take(max_chars).collect();
+        if line.chars().count() > max_chars || self.text.trim_end().contains('\n') {
+            preview.push('…');
+        }
+        preview
+    }
+}
+
+/// The TOML file holds `[[snippet]]` tables.
+#[derive(Deserialize, Debug, Default)]
+struct SnippetsToml {
+    #[serde(default)]
+    snippet: Vec<Snippet>,
+}
+
+/// Snippets of a `snippets.toml` file.
+pub fn parse_toml(text: &str) -> Result<Vec<Snippet>, SnippetsError> {
+    toml::from_str::<SnippetsToml>(text)
+        .map(|file| file.snippet)
+        .map_err(SnippetsError::Toml)
+}
+
+/// Snippets of a `snippets.json` file, an array of snippets.
+pub fn parse_json(text: &str) -> Result<Vec<Snippet>, SnippetsError> {
+    serde_json::from_str(text).map_err(SnippetsError::Json)
+}
+
+/// The snippets of every file in `dir`. Missing files hold none, a file that fails to read
+/// is skipped with a warning so the others still load.
+pub fn load(dir: &Path) -> Vec<Snippet> {
+    let mut snippets = vec![];
+    for name in FILES {
+        let path = dir.join(name);
+        let loaded = match std::fs::read_to_string(&path) {
+            Ok(text) if name.ends_with(".toml") => parse_toml(&text),
+            Ok(text) => parse_json(&text),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => Err(SnippetsError::Io(err)),
+        };
+        match loaded {
+            Ok(loaded) => snippets.extend(loaded),
+            Err(e) => tracing::warn!("failed to load snippets {}: {}", path.display(), e),
+        }
+    }
+    snippets
+}
+
+/// Snippets matching `query` by name or, counting less, by a tag, best first. An empty query
+/// lists them all by name.
+pub fn search<'a>(snippets: &'a [Snippet], query: &str) -> Vec<(&'a Snippet, f64)> {
+    if query.trim().is_empty() {
+        let mut all = snippets
+            .iter()
+            .map(|snippet| (snippet, 1.0))
+            .collect::<Vec<_>>();
+        all.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+        return all;
+    }
+    let matcher = Matcher::new(query);
+    let mut found = snippets
+        .iter()
+        .filter_map(|snippet| {
+            let name = matcher.score(&snippet.name);
+            let tags = snippet
+                .tags
+                .iter()
+                .filter_map(|tag| matcher.score(tag))
+                .map(|score| score * 0.75);
+            let score = name.into_iter().chain(tags).reduce(f64::max)?;
+            Some((snippet, score))
+        })
+        .collect::<Vec<_>>();
+    found.sort_by(|a, b| b.1.total_cmp(&a.1));
+    found
+}
@@ -0,0 +1,116 @@
+use glimpse_plugins_snippets::snippets::{self, Snippet};
+
+fn snippet(name: &str, text: &str, tags: &[&str]) -> Snippet {
+    Snippet {
+        name: name.to_string(),
+        text: text.to_string(),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+    }
+}
+
+fn names<'a>(found: &[(&'a Snippet, f64)]) -> Vec<&'a str> {
+    found
+        .iter()
+        .map(|(snippet, _)| snippet.name.as_str())
+        .collect()
+}
+
+#[test]
+fn test_parse_toml() {
+    let text = r#"
+[[snippet]]
+name = "shrug"
+text = '¯\_(ツ)_/¯'
+tags = ["kaomoji"]
+
+[[snippet]]
+name = "signature"
+text = """
+Best regards,
+Alex
+"""
+"#;
+
+    assert_eq!(
+        snippets::parse_toml(text).unwrap(),
+        vec![
+            snippet("shrug", "¯\\_(ツ)_/¯", &["kaomoji"]),
+            snippet("signature", "Best regards,\nAlex\n", &[]),
+        ]
+    );
+    assert!(snippets::parse_toml("").unwrap().is_empty());
+    assert!(snippets::parse_toml("[[snippet]]\nname = \"no text\"").is_err());
+}
+
+#[test]
+fn test_parse_json() {
+    let text = r#"[{"name": "flip", "text": "(╯°□°)╯︵ ┻━┻", "tags": ["kaomoji", "table"]}]"#;
+
+    assert_eq!(
+        snippets::parse_json(text).unwrap(),
+        vec![snippet("flip", "(╯°□°)╯︵ ┻━┻", &["kaomoji", "table"])]
+    );
+}
+
+#[test]
+fn test_load_merges_files_and_skips_broken_ones() {
+    let dir = tempfile::tempdir().unwrap();
+    assert!(snippets::load(dir.path()).is_empty());
+
+    std::fs::write(
+        dir.path().join("snippets.toml"),
+        "[[snippet]]\nname = \"shrug\"\ntext = \"¯\\\\_(ツ)_/¯\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("snippets.json"),
+        r#"[{"name": "flip", "text": "(╯°□°)╯︵ ┻━┻"}]"#,
+    )
+    .unwrap();
+    let loaded = snippets::load(dir.path());
+    assert_eq!(
+        loaded.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(),
+        vec!["shrug", "flip"]
+    );
+
+    std::fs::write(dir.path().join("snippets.toml"), "not toml [").unwrap();
+    assert_eq!(snippets::load(dir.path()).len(), 1);
+}
+
+#[test]
+fn test_search_by_name_and_tag() {
+    let all = vec![
+        snippet("shrug", "¯\\_(ツ)_/¯", &["kaomoji"]),
+        snippet("signature", "Best regards", &["email"]),
+        snippet("kaomoji flip", "(╯°□°)╯︵ ┻━┻", &[]),
+    ];
+
+    assert_eq!(names(&snippets::search(&all, "sig")), vec!["signature"]);
+    // the name matches better than the tag
+    assert_eq!(
+        names(&snippets::search(&all, "kaomoji")),
+        vec!["kaomoji flip", "shrug"]
+    );
+    assert!(snippets::search(&all, "zzz").is_empty());
+}
+
+#[test]
+fn test_search_empty_lists_all_by_name() {
+    let all = vec![
+        snippet("signature", "Best regards", &[]),
+        snippet("flip", "(╯°□°)╯︵ ┻━┻", &[]),
+    ];
+
+    assert_eq!(
+        names(&snippets::search(&all, " ")),
+        vec!["flip", "signature"]
+    );
+}
+
+#[test]
+fn test_preview() {
+    assert_eq!(snippet("a", "short", &[]).preview(10), "short");
+    assert_eq!(snippet("a", "a long line", &[]).preview(6), "a long…");
+    assert_eq!(snippet("a", "first\nsecond", &[]).preview(10), "first…");
+    assert_eq!(snippet("a", "line\n", &[]).preview(10), "line");
+}
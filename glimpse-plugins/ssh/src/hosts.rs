@@ -0,0 +1,221 @@
+use std::path::{Path, PathBuf};
+
+/// Nested `Include`s deeper than this are ignored, like ssh does past its own limit.
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Host {
+    /// Alias from `~/.ssh/config`, or the host name from `known_hosts`.
+    pub name: String,
+    pub hostname: Option<String>,
+    pub user: Option<String>,
+    /// Only set for `known_hosts` entries, config aliases carry their port themselves.
+    pub port: Option<u16>,
+    pub from_config: bool,
+}
+
+impl Host {
+    /// Arguments of the `ssh` invocation connecting to this host.
+    pub fn ssh_args(&self) -> Vec<String> {
+        let mut args = vec!["ssh".to_string()];
+        if let Some(port) = self.port {
+            args.extend(["-p".to_string(), port.to_string()]);
+        }
+        args.push(self.name.clone());
+        args
+    }
+
+    pub fn describe(&self) -> String {
+        match (&self.user, &self.hostname) {
+            (Some(user), Some(hostname)) => format!("{}@{}", user, hostname),
+            (Some(user), None) => format!("{}@{}", user, self.name),
+            (None, Some(hostname)) => hostname.clone(),
+            (None, None) if self.from_config => format!("ssh {}", self.name),
+            (None, None) => "From known_hosts".to_string(),
+        }
+    }
+}
+
+/// Aliases declared by `Host` lines in an ssh config, wildcard patterns left out.
+/// `ssh_dir` resolves relative `Include` paths.
+pub fn parse_config(text: &str, ssh_dir: &Path) -> Vec<Host> {
+    let mut hosts = vec![];
+    parse_config_into(text, ssh_dir, 0, &mut hosts);
+    hosts
+}
+
+fn parse_config_into(text: &str, ssh_dir: &Path, depth: usize, hosts: &mut Vec<Host>) {
+    // hosts of the block being read, their options follow the `Host` line
+    let mut block = hosts.len()..hosts.len();
+    for line in text.lines() {
+        let Some((keyword, value)) = split_option(line) else {
+            continue;
+        };
+        match keyword.to_ascii_lowercase().as_str() {
+            "host" => {
+                let start = hosts.len();
+                for alias in value.split_whitespace() {
+                    if alias.contains(['*', '?', '!']) || hosts.iter().any(|h| h.name == alias) {
+                        continue;
+                    }
+                    hosts.push(Host {
+                        name: alias.to_string(),
+                        from_config: true,
+                        ..Default::default()
+                    });
+                }
+                block = start..hosts.len();
+            }
+            // options of a `Match` block do not belong to the preceding hosts
+            "match" => block = hosts.len()..hosts.len(),
+            "hostname" => {
+                for host in &mut hosts[block.clone()] {
+                    host.hostname.get_or_insert_with(|| value.to_string());
+                }
+            }
+            "user" => {
+                for host in &mut hosts[block.clone()] {
+                    host.user.get_or_insert_with(|| value.to_string());
+                }
+            }
+            "include" if depth < MAX_INCLUDE_DEPTH => {
+                for pattern in value.split_whitespace() {
+                    for path in expand_include(pattern, ssh_dir) {
+                        if let Ok(text) = std::fs::read_to_string(&path) {
+                            parse_config_into(&text, ssh_dir, depth + 1, hosts);
+                        }
+                    }
+                }
+                block = hosts.len()..hosts.len();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `Keyword value` or `Keyword=value`, comments and blank lines skipped.
+fn split_option(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let split = line.find(|c: char| c.is_whitespace() || c == '=')?;
+    let (keyword, rest) = line.split_at(split);
+    let value = rest
+        .trim_start()
+        .strip_prefix('=')
+        .unwrap_or(rest)
+        .trim()
+        .trim_matches('"');
+    Some((keyword, value))
+}
+
+/// Files an `Include` pattern names. Wildcards are supported in the file name only,
+/// which covers the usual `Include config.d/*`.
+fn expand_include(pattern: &str, ssh_dir: &Path) -> Vec<PathBuf> {
+    let path = match pattern.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => ssh_dir.join(pattern),
+    };
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return vec![];
+    };
+    if !name.contains('*') {
+        return vec![path];
+    }
+    let (prefix, suffix) = name.split_once('*').unwrap();
+    let Some(dir) = path.parent() else {
+        return vec![];
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut paths = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(prefix) && name.ends_with(suffix))
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+/// Hosts from a `known_hosts` file. Hashed entries cannot be read back and are skipped.
+pub fn parse_known_hosts(text: &str) -> Vec<Host> {
+    let mut hosts: Vec<Host> = vec![];
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('@') {
+            continue;
+        }
+        let Some(names) = line.split_whitespace().next() else {
+            continue;
+        };
+        for name in names.split(',') {
+            if name.starts_with('|') || name.contains(['*', '?', '!']) {
+                continue;
+            }
+            let (name, port) = match name
+                .strip_prefix('[')
+                .and_then(|rest| rest.split_once("]:"))
+            {
+                Some((name, port)) => (name, port.parse().ok()),
+                None => (name, None),
+            };
+            if hosts.iter().any(|h| h.name == name && h.port == port) {
+                continue;
+            }
+            hosts.push(Host {
+                name: name.to_string(),
+                port,
+                ..Default::default()
+            });
+        }
+    }
+    hosts
+}
+
+/// Config aliases first, then known hosts not already reachable through an alias.
+pub fn merge(config: Vec<Host>, known: Vec<Host>) -> Vec<Host> {
+    let mut hosts = config;
+    for host in known {
+        let covered = hosts.iter().any(|alias| {
+            alias.port.is_none()
+                && host.port.is_none()
+                && (alias.name == host.name || alias.hostname.as_deref() == Some(&host.name))
+        });
+        if !covered {
+            hosts.push(host);
+        }
+    }
+    hosts
+}
+
+/// Hosts whose name contains `query`, ignoring case, with a score favouring prefixes and
+/// config aliases.
+pub fn search<'a>(hosts: &'a [Host], query: &str) -> Vec<(&'a Host, f64)> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return vec![];
+    }
+    let mut found = hosts
+        .iter()
+        .filter_map(|host| {
+            let name = host.name.to_lowercase();
+            let position = name.find(&query)?;
+            let mut score: f64 = if position == 0 { 1.0 } else { 0.6 };
+            if name.len() == query.len() {
+                score += 0.2;
+            }
+            if !host.from_config {
+                score *= 0.8;
+            }
+            Some((host, score))
+        })
+        .collect::<Vec<_>>();
+    found.sort_by(|a, b| b.1.total_cmp(&a.1));
+    found
+}
@@ -0,0 +1,158 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use glimpse_plugins_ssh::hosts::{self, Host};
+use glimpse_sdk::{
+    Action, AlternateAction, ConfigField, ConfigKind, ConfigSchema, GlimpsePlugin, Match,
+    MatchAction, Metadata, Modifiers, Plugin, PluginError, PluginMetadata, Settings,
+};
+use serde::Deserialize;
+
+const DEFAULT_MAX_RESULTS: usize = 10;
+const DEFAULT_TERMINAL: &str = "x-terminal-emulator";
+const DEFAULT_TERMINAL_ARGS: &[&str] = &["-e"];
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+struct SshSettings {
+    max_results: usize,
+    /// Terminal emulator running ssh.
+    terminal: String,
+    /// Arguments placed before the ssh command, `-e` for most terminals.
+    terminal_args: Vec<String>,
+}
+
+impl Default for SshSettings {
+    fn default() -> Self {
+        Self {
+            max_results: DEFAULT_MAX_RESULTS,
+            terminal: DEFAULT_TERMINAL.to_string(),
+            terminal_args: DEFAULT_TERMINAL_ARGS
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect(),
+        }
+    }
+}
+
+#[derive(GlimpsePlugin)]
+#[glimpse(
+    id = "me.aresa.glimpse.ssh",
+    name = "SSH",
+    description = "Connects to hosts from the ssh config and known_hosts.",
+    author = "Alex Oleshkevich <alex.oleshkevich@gmail.com>",
+    prefix = "ssh ",
+    permissions(Clipboard, Exec)
+)]
+struct SshPlugin {
+    ssh_dir: PathBuf,
+    settings: Settings<SshSettings>,
+}
+
+impl SshPlugin {
+    fn new(ssh_dir: PathBuf) -> Self {
+        Self {
+            ssh_dir,
+            settings: Settings::default(),
+        }
+    }
+
+    /// Read the config and known hosts again on every search, so edits show up right away.
+    fn load_hosts(ssh_dir: &Path) -> Vec<Host> {
+        let config = std::fs::read_to_string(ssh_dir.join("config"))
+            .map(|text| hosts::parse_config(&text, ssh_dir))
+            .unwrap_or_default();
+        let known = std::fs::read_to_string(ssh_dir.join("known_hosts"))
+            .map(|text| hosts::parse_known_hosts(&text))
+            .unwrap_or_default();
+        hosts::merge(config, known)
+    }
+
+    fn to_match(settings: &SshSettings, host: &Host, score: f64) -> Match {
+        let ssh_args = host.ssh_args();
+        let mut args = settings.terminal_args.clone();
+        args.extend(ssh_args.iter().cloned());
+
+        Match {
+            title: host.name.clone(),
+            description: host.describe(),
+            actions: vec![MatchAction {
+                title: "Connect".to_string(),
+                close_on_action: true,
+                action: Action::Exec {
+                    command: settings.terminal.clone(),
+                    args,
+                },
+                alternates: vec![AlternateAction {
+                    modifiers: Modifiers {
+                        shift: true,
+                        ..Default::default()
+                    },
+                    title: "Copy ssh command".to_string(),
+                    action: Action::Clipboard {
+                        text: ssh_args.join(" ").into(),
+                    },
+                }],
+                requires_confirmation: false,
+                confirmation_prompt: None,
+                expand: vec![],
+            }],
+            score,
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for SshPlugin {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            config_schema: Some(
+                ConfigSchema::new()
+                    .field(
+                        ConfigField::new("max_results", ConfigKind::Integer)
+                            .default_value(DEFAULT_MAX_RESULTS)
+                            .description("Maximum number of hosts returned per search"),
+                    )
+                    .field(
+                        ConfigField::new("terminal", ConfigKind::String)
+                            .default_value(DEFAULT_TERMINAL)
+                            .description("Terminal emulator to run ssh in"),
+                    )
+                    .field(
+                        ConfigField::new("terminal_args", ConfigKind::StringList)
+                            .default_value(DEFAULT_TERMINAL_ARGS.to_vec())
+                            .description("Terminal arguments placed before the ssh command"),
+                    ),
+            ),
+            ..Self::plugin_metadata()
+        }
+    }
+
+    async fn configure(&self, config: serde_json::Value) -> Result<(), PluginError> {
+        self.settings.apply(config)
+    }
+
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        let settings = self.settings.get();
+        let ssh_dir = self.ssh_dir.clone();
+        let hosts = tokio::task::spawn_blocking(move || Self::load_hosts(&ssh_dir))
+            .await
+            .map_err(|e| PluginError::Other(e.to_string()))?;
+
+        Ok(hosts::search(&hosts, &query)
+            .into_iter()
+            .take(settings.max_results)
+            .map(|(host, score)| Self::to_match(&settings, host, score))
+            .collect())
+    }
+}
+
+#[glimpse_sdk::main]
+async fn main() -> Result<SshPlugin, Box<dyn Error>> {
+    let home = dirs::home_dir().ok_or("cannot determine the home directory")?;
+    Ok(SshPlugin::new(home.join(".ssh")))
+}
@@ -0,0 +1,96 @@
+use std::path::Path;
+
+use glimpse_plugins_ssh::hosts::{self, Host};
+
+fn names(hosts: &[Host]) -> Vec<&str> {
+    hosts.iter().map(|host| host.name.as_str()).collect()
+}
+
+#[test]
+fn test_parse_config_aliases_and_options() {
+    let config = "
+# personal
+Host web web-staging
+    HostName web.example.com
+    User deploy
+Host=db
+  hostname = 10.0.0.5
+Host * !bastion
+    User root
+Host bastion
+    Port 2222
+";
+    let hosts = hosts::parse_config(config, Path::new("/nonexistent"));
+
+    assert_eq!(names(&hosts), vec!["web", "web-staging", "db", "bastion"]);
+    assert_eq!(hosts[1].describe(), "deploy@web.example.com");
+    assert_eq!(hosts[2].describe(), "10.0.0.5");
+    // the wildcard block does not leak into the next host
+    assert_eq!(hosts[3].user, None);
+    assert_eq!(hosts[3].ssh_args(), vec!["ssh", "bastion"]);
+}
+
+#[test]
+fn test_parse_config_follows_includes() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("config.d")).unwrap();
+    std::fs::write(dir.path().join("config.d/work.conf"), "Host work\n").unwrap();
+    std::fs::write(dir.path().join("config.d/home.conf"), "Host nas\n").unwrap();
+    std::fs::write(dir.path().join("extra"), "Host extra\n").unwrap();
+
+    let config = "Include config.d/*.conf extra\nHost main\n";
+    let hosts = hosts::parse_config(config, dir.path());
+
+    assert_eq!(names(&hosts), vec!["nas", "work", "extra", "main"]);
+}
+
+#[test]
+fn test_parse_known_hosts() {
+    let known = "
+github.com,140.82.121.4 ssh-ed25519 AAAA
+|1|hashed= ssh-ed25519 AAAA
+[git.example.com]:2222 ssh-rsa AAAA
+@cert-authority *.example.com ssh-rsa AAAA
+github.com ssh-rsa AAAA
+";
+    let hosts = hosts::parse_known_hosts(known);
+
+    assert_eq!(
+        names(&hosts),
+        vec!["github.com", "140.82.121.4", "git.example.com"]
+    );
+    assert_eq!(
+        hosts[2].ssh_args(),
+        vec!["ssh", "-p", "2222", "git.example.com"]
+    );
+    assert_eq!(hosts[0].describe(), "From known_hosts");
+}
+
+#[test]
+fn test_merge_skips_hosts_behind_aliases() {
+    let config = hosts::parse_config(
+        "Host web\n  HostName web.example.com\n",
+        Path::new("/nonexistent"),
+    );
+    let known =
+        hosts::parse_known_hosts("web.example.com ssh-ed25519 A\nother.org ssh-ed25519 A\n");
+
+    assert_eq!(
+        names(&hosts::merge(config, known)),
+        vec!["web", "other.org"]
+    );
+}
+
+#[test]
+fn test_search_prefers_aliases_and_prefixes() {
+    let config = hosts::parse_config("Host prod-web\nHost web\n", Path::new("/nonexistent"));
+    let known = hosts::parse_known_hosts("webmail.org ssh-ed25519 A\n");
+    let hosts = hosts::merge(config, known);
+
+    let found = hosts::search(&hosts, "WEB")
+        .into_iter()
+        .map(|(host, _)| host.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec!["web", "webmail.org", "prod-web"]);
+    assert!(hosts::search(&hosts, " ").is_empty());
+}
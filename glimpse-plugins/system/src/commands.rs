@@ -0,0 +1,78 @@
+use glimpse_sdk::{SystemCommand, matcher::Matcher};
+
+/// Keywords count for a little less than the title.
+const KEYWORD_WEIGHT: f64 = 0.9;
+
+/// A system command as offered to the user.
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    pub command: SystemCommand,
+    pub title: &'static str,
+    /// Other words people search for it by.
+    pub keywords: &'static [&'static str],
+    pub icon: &'static str,
+    /// Asked before running commands that close applications, `None` for the others.
+    pub confirmation: Option<&'static str>,
+}
+
+pub const ENTRIES: &[Entry] = &[
+    Entry {
+        command: SystemCommand::PowerOff,
+        title: "Shut down",
+        keywords: &["power off", "shutdown", "turn off", "halt"],
+        icon: "system-shutdown",
+        confirmation: Some("Shut down the computer? Unsaved work will be lost."),
+    },
+    Entry {
+        command: SystemCommand::Reboot,
+        title: "Reboot",
+        keywords: &["restart"],
+        icon: "system-reboot",
+        confirmation: Some("Restart the computer? Unsaved work will be lost."),
+    },
+    Entry {
+        command: SystemCommand::Suspend,
+        title: "Suspend",
+        keywords: &["sleep"],
+        icon: "system-suspend",
+        confirmation: None,
+    },
+    Entry {
+        command: SystemCommand::LockScreen,
+        title: "Lock screen",
+        keywords: &["lock"],
+        icon: "system-lock-screen",
+        confirmation: None,
+    },
+    Entry {
+        command: SystemCommand::LogOut,
+        title: "Log out",
+        keywords: &["logout", "sign out", "end session"],
+        icon: "system-log-out",
+        confirmation: Some("Log out? Unsaved work will be lost."),
+    },
+];
+
+/// Entries matching `query` by title or keyword, best first. An empty query finds nothing,
+/// so the commands only show up when asked for.
+pub fn search(query: &str) -> Vec<(&'static Entry, f64)> {
+    if query.trim().is_empty() {
+        return vec![];
+    }
+    let matcher = Matcher::new(query);
+    let mut found = ENTRIES
+        .iter()
+        .filter_map(|entry| {
+            let title = matcher.score(entry.title);
+            let keywords = entry
+                .keywords
+                .iter()
+                .filter_map(|keyword| matcher.score(keyword))
+                .map(|score| score * KEYWORD_WEIGHT);
+            let score = title.into_iter().chain(keywords).reduce(f64::max)?;
+            Some((entry, score))
+        })
+        .collect::<Vec<_>>();
+    found.sort_by(|a, b| b.1.total_cmp(&a.1));
+    found
+}
@@ -0,0 +1,60 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use glimpse_plugins_system::commands::{self, Entry};
+use glimpse_sdk::{
+    Action, GlimpsePlugin, Icon, Match, MatchAction, Metadata, Plugin, PluginError, PluginMetadata,
+};
+
+#[derive(GlimpsePlugin)]
+#[glimpse(
+    id = "me.aresa.glimpse.system",
+    name = "System",
+    description = "Shuts down, reboots or suspends the computer, locks the screen and logs out.",
+    author = "Alex Oleshkevich <alex.oleshkevich@gmail.com>",
+    permissions(Power)
+)]
+struct SystemPlugin;
+
+impl SystemPlugin {
+    /// The daemon runs the command, the plugin has no business with logind.
+    fn to_match(entry: &Entry, score: f64) -> Match {
+        Match {
+            title: entry.title.to_string(),
+            description: "System".to_string(),
+            icon: Some(Icon::freedesktop(entry.icon)),
+            actions: vec![MatchAction {
+                title: entry.title.to_string(),
+                action: Action::System {
+                    command: entry.command,
+                },
+                close_on_action: true,
+                alternates: vec![],
+                requires_confirmation: entry.confirmation.is_some(),
+                confirmation_prompt: entry.confirmation.map(str::to_string),
+                expand: vec![],
+            }],
+            score,
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for SystemPlugin {
+    fn metadata(&self) -> Metadata {
+        Self::plugin_metadata()
+    }
+
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        Ok(commands::search(&query)
+            .into_iter()
+            .map(|(entry, score)| Self::to_match(entry, score))
+            .collect())
+    }
+}
+
+#[glimpse_sdk::main]
+async fn main() -> Result<SystemPlugin, Box<dyn Error>> {
+    Ok(SystemPlugin)
+}
@@ -0,0 +1,42 @@
+use glimpse_plugins_system::commands::{self, ENTRIES};
+use glimpse_sdk::SystemCommand;
+
+fn found(query: &str) -> Vec<SystemCommand> {
+    commands::search(query)
+        .into_iter()
+        .map(|(entry, _)| entry.command)
+        .collect()
+}
+
+#[test]
+fn test_search_by_title() {
+    assert_eq!(found("lock")[0], SystemCommand::LockScreen);
+    assert_eq!(found("suspend"), vec![SystemCommand::Suspend]);
+    assert_eq!(found("Shut down")[0], SystemCommand::PowerOff);
+}
+
+#[test]
+fn test_search_by_keyword() {
+    assert_eq!(found("restart"), vec![SystemCommand::Reboot]);
+    assert_eq!(found("sleep"), vec![SystemCommand::Suspend]);
+    assert_eq!(found("power off")[0], SystemCommand::PowerOff);
+    assert_eq!(found("sign out")[0], SystemCommand::LogOut);
+}
+
+#[test]
+fn test_search_empty_finds_nothing() {
+    assert!(found("").is_empty());
+    assert!(found("   ").is_empty());
+    assert!(found("firefox").is_empty());
+}
+
+#[test]
+fn test_commands_closing_applications_need_confirmation() {
+    for entry in ENTRIES {
+        let closes = matches!(
+            entry.command,
+            SystemCommand::PowerOff | SystemCommand::Reboot | SystemCommand::LogOut
+        );
+        assert_eq!(entry.confirmation.is_some(), closes, "{}", entry.title);
+    }
+}
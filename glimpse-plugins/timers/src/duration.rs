@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+/// Longest timer accepted, a week.
+pub const MAX_DURATION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// A timer asked for by a query like `10m tea`, `1h 30m stretch` or `in 25 min pomodoro`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimerQuery {
+    pub duration: Duration,
+    /// What the timer is for, empty when the query names nothing.
+    pub label: String,
+}
+
+/// The timer `query` asks for: an optional `in`, one or more amounts with a unit, then the
+/// label. Units are `s`, `m`, `h` and their longer spellings, and may be separated from the
+/// amount by a space. None if the query does not start with a duration, or one past
+/// [`MAX_DURATION`].
+pub fn parse(query: &str) -> Option<TimerQuery> {
+    let words: Vec<&str> = query.split_whitespace().collect();
+    let mut at = match words.first() {
+        Some(word) if word.eq_ignore_ascii_case("in") => 1,
+        _ => 0,
+    };
+
+    let mut duration = Duration::ZERO;
+    let start = at;
+    // a label may start with a number too, as in `10m 2 eggs`
+    while let Some((secs, used)) = amount(&words[at..]) {
+        duration = duration.checked_add(Duration::from_secs(secs))?;
+        at += used;
+    }
+
+    if at == start || duration.is_zero() || duration > MAX_DURATION {
+        return None;
+    }
+    Some(TimerQuery {
+        duration,
+        label: words[at..].join(" "),
+    })
+}
+
+/// Seconds of the amount with a unit `words` start with, `10m` or `10 min`, and how many
+/// words it took.
+fn amount(words: &[&str]) -> Option<(u64, usize)> {
+    let word = words.first()?;
+    let digits = word
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(word.len());
+    let amount: u64 = word[..digits].parse().ok()?;
+    let (unit, used) = match &word[digits..] {
+        "" => (*words.get(1)?, 2),
+        unit => (unit, 1),
+    };
+    Some((amount.checked_mul(unit_secs(unit)?)?, used))
+}
+
+fn unit_secs(unit: &str) -> Option<u64> {
+    match unit.to_lowercase().as_str() {
+        "s" | "sec" | "secs" | "second" | "seconds" => Some(1),
+        "m" | "min" | "mins" | "minute" | "minutes" => Some(60),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Some(60 * 60),
+        _ => None,
+    }
+}
+
+/// `duration` for people, e.g. `1 h 30 min` or `45 s`.
+pub fn describe(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let parts = [
+        (secs / 3600, "h"),
+        (secs / 60 % 60, "min"),
+        (secs % 60, "s"),
+    ];
+    parts
+        .iter()
+        .filter(|(amount, _)| *amount > 0)
+        .map(|(amount, unit)| format!("{} {}", amount, unit))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
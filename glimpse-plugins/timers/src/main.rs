@@ -0,0 +1,84 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use glimpse_plugins_timers::duration::{self, TimerQuery};
+use glimpse_sdk::{
+    Action, AlternateAction, GlimpsePlugin, Icon, Match, MatchAction, Metadata, Modifiers, Plugin,
+    PluginError, PluginMetadata,
+};
+
+/// Timers set without a label are called this.
+const DEFAULT_TITLE: &str = "Timer";
+
+#[derive(GlimpsePlugin)]
+#[glimpse(
+    id = "me.aresa.glimpse.timers",
+    name = "Timers",
+    description = "Sets timers and reminders, like `10m tea`, notifying when they are up.",
+    author = "Alex Oleshkevich <alex.oleshkevich@gmail.com>",
+    permissions(Notify)
+)]
+struct TimersPlugin;
+
+impl TimersPlugin {
+    fn title(query: &TimerQuery) -> String {
+        match query.label.is_empty() {
+            true => DEFAULT_TITLE.to_string(),
+            false => query.label.clone(),
+        }
+    }
+
+    fn schedule(query: &TimerQuery, open_launcher: bool) -> Action {
+        Action::Schedule {
+            after_secs: query.duration.as_secs(),
+            title: Self::title(query),
+            body: Some(format!("{} are up", duration::describe(query.duration))),
+            open_launcher,
+        }
+    }
+
+    fn to_match(query: &TimerQuery) -> Match {
+        Match {
+            title: Self::title(query),
+            description: format!("Notifies in {}", duration::describe(query.duration)),
+            icon: Some(Icon::freedesktop("alarm-symbolic")),
+            actions: vec![MatchAction {
+                title: "Start timer".to_string(),
+                action: Self::schedule(query, false),
+                close_on_action: true,
+                alternates: vec![AlternateAction {
+                    modifiers: Modifiers {
+                        shift: true,
+                        ..Default::default()
+                    },
+                    title: "Start timer and open Glimpse when up".to_string(),
+                    action: Self::schedule(query, true),
+                }],
+                requires_confirmation: false,
+                confirmation_prompt: None,
+                expand: vec![],
+            }],
+            score: 1.0,
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for TimersPlugin {
+    fn metadata(&self) -> Metadata {
+        Self::plugin_metadata()
+    }
+
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        Ok(duration::parse(&query)
+            .map(|query| Self::to_match(&query))
+            .into_iter()
+            .collect())
+    }
+}
+
+#[glimpse_sdk::main]
+async fn main() -> Result<TimersPlugin, Box<dyn Error>> {
+    Ok(TimersPlugin)
+}
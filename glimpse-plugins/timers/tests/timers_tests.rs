@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use glimpse_plugins_timers::duration::{self, MAX_DURATION, TimerQuery};
+
+fn query(secs: u64, label: &str) -> Option<TimerQuery> {
+    Some(TimerQuery {
+        duration: Duration::from_secs(secs),
+        label: label.to_string(),
+    })
+}
+
+#[test]
+fn test_parse_attached_and_spaced_units() {
+    assert_eq!(duration::parse("10m tea"), query(600, "tea"));
+    assert_eq!(duration::parse("10 min tea"), query(600, "tea"));
+    assert_eq!(duration::parse("45s"), query(45, ""));
+    assert_eq!(duration::parse("2 Hours"), query(7200, ""));
+}
+
+#[test]
+fn test_parse_combined_amounts_and_in() {
+    assert_eq!(duration::parse("1h 30m stretch"), query(5400, "stretch"));
+    assert_eq!(
+        duration::parse("in 25 min pomodoro break"),
+        query(1500, "pomodoro break")
+    );
+}
+
+#[test]
+fn test_parse_label_starting_with_a_number() {
+    assert_eq!(duration::parse("10m 2 eggs"), query(600, "2 eggs"));
+}
+
+#[test]
+fn test_parse_rejects_non_durations() {
+    assert_eq!(duration::parse(""), None);
+    assert_eq!(duration::parse("tea"), None);
+    assert_eq!(duration::parse("in"), None);
+    assert_eq!(duration::parse("10 tea"), None);
+    assert_eq!(duration::parse("0m"), None);
+    assert_eq!(duration::parse("10x"), None);
+}
+
+#[test]
+fn test_parse_rejects_past_max_duration() {
+    assert!(duration::parse("168h").is_some());
+    assert_eq!(duration::parse("169h"), None);
+    assert_eq!(duration::parse("99999999999999999999h"), None);
+    assert_eq!(MAX_DURATION, Duration::from_secs(168 * 3600));
+}
+
+#[test]
+fn test_describe() {
+    assert_eq!(duration::describe(Duration::from_secs(45)), "45 s");
+    assert_eq!(duration::describe(Duration::from_secs(600)), "10 min");
+    assert_eq!(duration::describe(Duration::from_secs(5400)), "1 h 30 min");
+    assert_eq!(duration::describe(Duration::from_secs(3601)), "1 h 1 s");
+}
@@ -0,0 +1,52 @@
+/// Stands for the query in an engine's URL template.
+pub const PLACEHOLDER: &str = "%s";
+
+/// Engines offered unless configured otherwise, as `name=template`.
+pub const DEFAULT_ENGINES: &[&str] = &[
+    "DuckDuckGo=https://duckduckgo.com/?q=%s",
+    "Google=https://www.google.com/search?q=%s",
+];
+
+/// A search engine, by the URL of its results with `%s` in place of the query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Engine {
+    pub name: String,
+    pub template: String,
+}
+
+impl Engine {
+    /// An engine configured as `name=template`, e.g.
+    /// `Wikipedia=https://en.wikipedia.org/w/index.php?search=%s`. None if the name is empty
+    /// or the template is not a web address holding `%s`.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (name, template) = spec.split_once('=')?;
+        let (name, template) = (name.trim(), template.trim());
+        let web = template.starts_with("https://") || template.starts_with("http://");
+        if name.is_empty() || !web || !template.contains(PLACEHOLDER) {
+            return None;
+        }
+        Some(Self {
+            name: name.to_string(),
+            template: template.to_string(),
+        })
+    }
+
+    /// The address of the results for `query`.
+    pub fn url(&self, query: &str) -> String {
+        self.template.replace(PLACEHOLDER, &encode(query.trim()))
+    }
+}
+
+/// `query` percent encoded for a URL, everything but unreserved characters escaped.
+pub fn encode(query: &str) -> String {
+    let mut encoded = String::with_capacity(query.len());
+    for byte in query.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
@@ -0,0 +1,124 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use glimpse_plugins_websearch::engines::{DEFAULT_ENGINES, Engine};
+use glimpse_sdk::{
+    Action, ConfigField, ConfigKind, ConfigSchema, GlimpsePlugin, Icon, Match, MatchAction,
+    Metadata, Plugin, PluginError, PluginMetadata, Settings,
+};
+use serde::Deserialize;
+
+/// Below about any match of another plugin, in the order of the engines.
+const BASE_SCORE: f64 = 0.1;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+struct WebSearchSettings {
+    /// Engines as `name=template`, `%s` standing for the query.
+    engines: Vec<String>,
+}
+
+impl Default for WebSearchSettings {
+    fn default() -> Self {
+        Self {
+            engines: DEFAULT_ENGINES
+                .iter()
+                .map(|engine| engine.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl WebSearchSettings {
+    fn engines(&self) -> Vec<Engine> {
+        self.engines
+            .iter()
+            .filter_map(|spec| {
+                let engine = Engine::parse(spec);
+                if engine.is_none() {
+                    tracing::warn!("invalid search engine, expected name=template: {}", spec);
+                }
+                engine
+            })
+            .collect()
+    }
+}
+
+#[derive(GlimpsePlugin)]
+#[glimpse(
+    id = "me.aresa.glimpse.websearch",
+    name = "Web search",
+    description = "Searches the web for queries nothing else found.",
+    author = "Alex Oleshkevich <alex.oleshkevich@gmail.com>",
+    prefix = "web ",
+    permissions(Network),
+    fallback
+)]
+struct WebSearchPlugin {
+    settings: Settings<WebSearchSettings>,
+}
+
+impl WebSearchPlugin {
+    fn to_match(engine: &Engine, query: &str, position: usize) -> Match {
+        Match {
+            title: format!("Search {} for “{}”", engine.name, query),
+            description: "Web search".to_string(),
+            icon: Some(Icon::freedesktop("web-browser")),
+            actions: vec![MatchAction {
+                title: "Search".to_string(),
+                action: Action::Open {
+                    uri: engine.url(query),
+                },
+                close_on_action: true,
+                alternates: vec![],
+                requires_confirmation: false,
+                confirmation_prompt: None,
+                expand: vec![],
+            }],
+            score: BASE_SCORE / (position + 1) as f64,
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for WebSearchPlugin {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            config_schema: Some(
+                ConfigSchema::new().field(
+                    ConfigField::new("engines", ConfigKind::StringList)
+                        .default_value(DEFAULT_ENGINES.to_vec())
+                        .description("Search engines as name=URL, %s standing for the query"),
+                ),
+            ),
+            ..Self::plugin_metadata()
+        }
+    }
+
+    async fn configure(&self, config: serde_json::Value) -> Result<(), PluginError> {
+        self.settings.apply(config)
+    }
+
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(self
+            .settings
+            .get()
+            .engines()
+            .iter()
+            .enumerate()
+            .map(|(position, engine)| Self::to_match(engine, query, position))
+            .collect())
+    }
+}
+
+#[glimpse_sdk::main]
+async fn main() -> Result<WebSearchPlugin, Box<dyn Error>> {
+    Ok(WebSearchPlugin {
+        settings: Settings::default(),
+    })
+}
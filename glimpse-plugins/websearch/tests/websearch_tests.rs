@@ -0,0 +1,40 @@
+use glimpse_plugins_websearch::engines::{self, DEFAULT_ENGINES, Engine};
+
+#[test]
+fn test_parse_engine() {
+    assert_eq!(
+        Engine::parse(" Wikipedia = https://en.wikipedia.org/w/index.php?search=%s "),
+        Some(Engine {
+            name: "Wikipedia".to_string(),
+            template: "https://en.wikipedia.org/w/index.php?search=%s".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_parse_rejects_invalid_engines() {
+    assert_eq!(Engine::parse("https://duckduckgo.com/?q=%s"), None);
+    assert_eq!(Engine::parse("=https://duckduckgo.com/?q=%s"), None);
+    assert_eq!(Engine::parse("DuckDuckGo=https://duckduckgo.com/"), None);
+    assert_eq!(Engine::parse("Local=file:///tmp/%s"), None);
+}
+
+#[test]
+fn test_default_engines_parse() {
+    assert!(
+        DEFAULT_ENGINES
+            .iter()
+            .all(|spec| Engine::parse(spec).is_some())
+    );
+}
+
+#[test]
+fn test_url_encodes_the_query() {
+    let engine = Engine::parse("DuckDuckGo=https://duckduckgo.com/?q=%s").unwrap();
+
+    assert_eq!(
+        engine.url(" rust & café?  "),
+        "https://duckduckgo.com/?q=rust%20%26%20caf%C3%A9%3F"
+    );
+    assert_eq!(engines::encode("a-b_c.d~e"), "a-b_c.d~e");
+}
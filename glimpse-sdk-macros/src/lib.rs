@@ -0,0 +1,175 @@
+//! Macros re-exported by `glimpse_sdk`, documented there.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    DeriveInput, Ident, ItemFn, LitStr, Token, meta::ParseNestedMeta, parenthesized,
+    parse_macro_input,
+};
+
+/// What `#[glimpse(...)]` attributes set.
+#[derive(Default)]
+struct PluginAttributes {
+    id: Option<LitStr>,
+    name: Option<LitStr>,
+    version: Option<LitStr>,
+    description: Option<LitStr>,
+    author: Option<LitStr>,
+    prefix: Option<LitStr>,
+    triggers: Vec<LitStr>,
+    keep_triggers: bool,
+    permissions: Vec<Ident>,
+    prefix_only: bool,
+    sensitive: bool,
+    fallback: bool,
+}
+
+impl PluginAttributes {
+    fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut attributes = Self::default();
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("glimpse"))
+        {
+            attr.parse_nested_meta(|meta| attributes.parse_one(meta))?;
+        }
+        if attributes.id.is_none() {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "a plugin needs an id, as in `#[glimpse(id = \"com.example.plugin\")]`",
+            ));
+        }
+        Ok(attributes)
+    }
+
+    fn parse_one(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        let Some(key) = meta.path.get_ident().map(Ident::to_string) else {
+            return Err(meta.error("expected a glimpse attribute"));
+        };
+        match key.as_str() {
+            "id" => self.id = Some(meta.value()?.parse()?),
+            "name" => self.name = Some(meta.value()?.parse()?),
+            "version" => self.version = Some(meta.value()?.parse()?),
+            "description" => self.description = Some(meta.value()?.parse()?),
+            "author" => self.author = Some(meta.value()?.parse()?),
+            "prefix" => self.prefix = Some(meta.value()?.parse()?),
+            "triggers" => {
+                let content;
+                parenthesized!(content in meta.input);
+                let triggers =
+                    content.parse_terminated(|input| input.parse::<LitStr>(), Token![,])?;
+                self.triggers.extend(triggers);
+            }
+            "permissions" => meta.parse_nested_meta(|permission| {
+                let ident = permission
+                    .path
+                    .get_ident()
+                    .ok_or_else(|| permission.error("expected a permission, as in `Clipboard`"))?;
+                self.permissions.push(ident.clone());
+                Ok(())
+            })?,
+            "keep_triggers" => self.keep_triggers = true,
+            "prefix_only" => self.prefix_only = true,
+            "sensitive" => self.sensitive = true,
+            "fallback" => self.fallback = true,
+            _ => return Err(meta.error(format!("unknown glimpse attribute `{}`", key))),
+        }
+        Ok(())
+    }
+}
+
+/// Implements `glimpse_sdk::PluginMetadata` from `#[glimpse(...)]` attributes.
+#[proc_macro_derive(GlimpsePlugin, attributes(glimpse))]
+pub fn derive_glimpse_plugin(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let attributes = match PluginAttributes::parse(&input) {
+        Ok(attributes) => attributes,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let or_package = |value: &Option<LitStr>, variable: &str| match value {
+        Some(value) => quote!(#value),
+        None => quote!(env!(#variable)),
+    };
+    let id = &attributes.id;
+    let name = match &attributes.name {
+        Some(name) => quote!(#name),
+        None => {
+            let name = LitStr::new(&ident.to_string(), ident.span());
+            quote!(#name)
+        }
+    };
+    let version = or_package(&attributes.version, "CARGO_PKG_VERSION");
+    let description = or_package(&attributes.description, "CARGO_PKG_DESCRIPTION");
+    let author = or_package(&attributes.author, "CARGO_PKG_AUTHORS");
+    let prefix = match &attributes.prefix {
+        Some(prefix) => quote!(Some(#prefix.to_string())),
+        None => quote!(None),
+    };
+    let triggers = &attributes.triggers;
+    let keep_triggers = attributes.keep_triggers;
+    let permissions = &attributes.permissions;
+    let prefix_only = attributes.prefix_only;
+    let sensitive = attributes.sensitive;
+    let fallback = attributes.fallback;
+
+    quote! {
+        impl #impl_generics ::glimpse_sdk::PluginMetadata for #ident #type_generics #where_clause {
+            fn plugin_metadata() -> ::glimpse_sdk::Metadata {
+                ::glimpse_sdk::Metadata {
+                    id: #id.to_string(),
+                    name: #name.to_string(),
+                    version: #version.to_string(),
+                    description: #description.to_string(),
+                    author: #author.to_string(),
+                    prefix: #prefix,
+                    triggers: vec![#(#triggers.to_string()),*],
+                    keep_triggers: #keep_triggers,
+                    permissions: vec![#(::glimpse_sdk::Permission::#permissions),*],
+                    prefix_only: #prefix_only,
+                    sensitive: #sensitive,
+                    fallback: #fallback,
+                    ..::std::default::Default::default()
+                }
+            }
+        }
+    }
+    .into()
+}
+
+/// Turns an `async fn main` returning the plugin, or a `Result` of it, into the plugin's
+/// entry point.
+#[proc_macro_attribute]
+pub fn main(_args: TokenStream, input: TokenStream) -> TokenStream {
+    let function = parse_macro_input!(input as ItemFn);
+    if function.sig.asyncness.is_none() {
+        return syn::Error::new_spanned(function.sig.fn_token, "the main function must be async")
+            .to_compile_error()
+            .into();
+    }
+    if !function.sig.inputs.is_empty() {
+        return syn::Error::new_spanned(
+            &function.sig.inputs,
+            "the main function takes no arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let attrs = &function.attrs;
+    let visibility = &function.vis;
+    let name = &function.sig.ident;
+    let output = &function.sig.output;
+    let body = &function.block;
+    quote! {
+        #(#attrs)*
+        #visibility fn #name() -> ::std::process::ExitCode {
+            async fn __glimpse_plugin() #output #body
+            ::glimpse_sdk::serve_main(__glimpse_plugin())
+        }
+    }
+    .into()
+}
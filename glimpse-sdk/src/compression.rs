@@ -0,0 +1,150 @@
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::Display,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use base64::Engine;
+
+use crate::MAX_MESSAGE_BYTES;
+
+/// Marks a compressed line: the rest is the message, zstd compressed and base64 encoded.
+/// JSON never starts with it.
+pub const COMPRESSED_PREFIX: char = '~';
+
+/// Messages are worth compressing from this size on, smaller ones go out as they are.
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 16 * 1024;
+
+/// zstd level, fast enough not to be noticed on the search path.
+const LEVEL: i32 = 3;
+
+#[derive(Debug)]
+pub enum CompressionError {
+    Base64(base64::DecodeError),
+    /// The data is no zstd frame or inflates past [`MAX_MESSAGE_BYTES`].
+    Zstd(std::io::Error),
+}
+
+impl Display for CompressionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompressionError::Base64(err) => write!(f, "base64: {}", err),
+            CompressionError::Zstd(err) => write!(f, "zstd: {}", err),
+        }
+    }
+}
+impl Error for CompressionError {}
+
+/// `line` compressed if it is longer than `threshold` bytes and compressing makes it shorter.
+pub fn compress_line(line: &str, threshold: usize) -> Option<String> {
+    if line.len() <= threshold {
+        return None;
+    }
+    let compressed = zstd::bulk::compress(line.as_bytes(), LEVEL)
+        .inspect_err(|err| tracing::warn!("failed to compress a message: {}", err))
+        .ok()?;
+    let mut encoded = String::with_capacity(compressed.len() * 4 / 3 + 5);
+    encoded.push(COMPRESSED_PREFIX);
+    base64::engine::general_purpose::STANDARD.encode_string(compressed, &mut encoded);
+    (encoded.len() < line.len()).then_some(encoded)
+}
+
+/// The message of a line read from a peer, decompressed if it was sent compressed.
+pub fn decompress_line(line: &str) -> Result<Cow<'_, str>, CompressionError> {
+    let Some(encoded) = line.trim_end().strip_prefix(COMPRESSED_PREFIX) else {
+        return Ok(Cow::Borrowed(line));
+    };
+    let compressed = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(CompressionError::Base64)?;
+    let bytes =
+        zstd::bulk::decompress(&compressed, MAX_MESSAGE_BYTES).map_err(CompressionError::Zstd)?;
+    Ok(Cow::Owned(String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+/// What compression saved and cost on one side of a connection, to check it pays off.
+#[derive(Default, Debug)]
+pub struct CompressionStats {
+    messages: AtomicU64,
+    /// Bytes of the messages as JSON.
+    raw_bytes: AtomicU64,
+    /// Bytes of the compressed lines.
+    wire_bytes: AtomicU64,
+    nanos: AtomicU64,
+}
+
+impl CompressionStats {
+    pub fn record(&self, raw_bytes: usize, wire_bytes: usize, elapsed: Duration) {
+        self.messages.fetch_add(1, Ordering::Relaxed);
+        self.raw_bytes
+            .fetch_add(raw_bytes as u64, Ordering::Relaxed);
+        self.wire_bytes
+            .fetch_add(wire_bytes as u64, Ordering::Relaxed);
+        self.nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Compress `line` like [`compress_line`], recording the outcome.
+    pub fn compress(&self, line: &str, threshold: usize) -> Option<String> {
+        if line.len() <= threshold {
+            return None;
+        }
+        let started = Instant::now();
+        let compressed = compress_line(line, threshold);
+        let wire_bytes = compressed.as_ref().map_or(line.len(), String::len);
+        self.record(line.len(), wire_bytes, started.elapsed());
+        compressed
+    }
+
+    /// Decompress `line` like [`decompress_line`], recording lines that were compressed.
+    pub fn decompress<'a>(&self, line: &'a str) -> Result<Cow<'a, str>, CompressionError> {
+        let started = Instant::now();
+        let decompressed = decompress_line(line)?;
+        if let Cow::Owned(message) = &decompressed {
+            self.record(message.len(), line.trim_end().len(), started.elapsed());
+        }
+        Ok(decompressed)
+    }
+
+    /// Messages compressed or decompressed.
+    pub fn messages(&self) -> u64 {
+        self.messages.load(Ordering::Relaxed)
+    }
+
+    pub fn raw_bytes(&self) -> u64 {
+        self.raw_bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn wire_bytes(&self) -> u64 {
+        self.wire_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Time spent compressing or decompressing.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
+    }
+
+    /// Size on the wire relative to the messages, below 1 when compression saves bytes.
+    pub fn ratio(&self) -> f64 {
+        match self.raw_bytes() {
+            0 => 1.0,
+            raw => self.wire_bytes() as f64 / raw as f64,
+        }
+    }
+}
+
+impl Display for CompressionStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} messages, {} bytes as {} ({:.0}%), {:?} spent",
+            self.messages(),
+            self.raw_bytes(),
+            self.wire_bytes(),
+            self.ratio() * 100.0,
+            self.elapsed()
+        )
+    }
+}
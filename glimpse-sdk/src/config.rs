@@ -0,0 +1,183 @@
+use std::sync::{Arc, Mutex, RwLock};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+
+use crate::PluginError;
+
+/// Settings a plugin accepts, declared in its [`Metadata`](crate::Metadata).
+///
+/// The daemon validates `~/.config/glimpse/plugins/<plugin-id>.toml` against it and
+/// fills in defaults before sending the result with `Method::Configure`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ConfigSchema {
+    pub fields: Vec<ConfigField>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ConfigField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: ConfigKind,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigKind {
+    String,
+    Integer,
+    Float,
+    Boolean,
+    StringList,
+}
+
+impl ConfigKind {
+    fn accepts(&self, value: &Value) -> bool {
+        match self {
+            ConfigKind::String => value.is_string(),
+            ConfigKind::Integer => value.is_i64() || value.is_u64(),
+            ConfigKind::Float => value.is_number(),
+            ConfigKind::Boolean => value.is_boolean(),
+            ConfigKind::StringList => value
+                .as_array()
+                .is_some_and(|items| items.iter().all(Value::is_string)),
+        }
+    }
+}
+
+impl ConfigField {
+    pub fn new(name: &str, kind: ConfigKind) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            required: false,
+            default: None,
+            description: String::new(),
+        }
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    pub fn default_value(mut self, value: impl Into<Value>) -> Self {
+        self.default = Some(value.into());
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+}
+
+impl ConfigSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field(mut self, field: ConfigField) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// Check `config` against the schema and fill in defaults.
+    ///
+    /// Collects every problem instead of stopping at the first one, so users can fix
+    /// their file in one go.
+    pub fn validate(&self, config: &Value) -> Result<Value, Vec<String>> {
+        let empty = Map::new();
+        let Some(values) = config.as_object().or(config.is_null().then_some(&empty)) else {
+            return Err(vec!["configuration must be a table".to_string()]);
+        };
+
+        let mut errors = vec![];
+        let mut validated = Map::new();
+        for field in &self.fields {
+            match values.get(&field.name).or(field.default.as_ref()) {
+                Some(value) if field.kind.accepts(value) => {
+                    validated.insert(field.name.clone(), value.clone());
+                }
+                Some(value) => errors.push(format!(
+                    "{}: expected {:?}, got {}",
+                    field.name, field.kind, value
+                )),
+                None if field.required => errors.push(format!("{}: missing", field.name)),
+                None => {}
+            }
+        }
+
+        for key in values.keys() {
+            if !self.fields.iter().any(|field| &field.name == key) {
+                errors.push(format!("{}: unknown setting", key));
+            }
+        }
+
+        match errors.is_empty() {
+            true => Ok(Value::Object(validated)),
+            false => Err(errors),
+        }
+    }
+}
+
+type ChangeCallback<T> = Box<dyn Fn(&T) + Send + Sync>;
+
+/// Typed view of the settings delivered with `Method::Configure` and `Method::ConfigChanged`.
+///
+/// Clones share state, so a plugin can keep one in its struct and hand others to
+/// background tasks that need the current values.
+pub struct Settings<T> {
+    current: Arc<RwLock<T>>,
+    callbacks: Arc<Mutex<Vec<ChangeCallback<T>>>>,
+}
+
+impl<T> Clone for Settings<T> {
+    fn clone(&self) -> Self {
+        Self {
+            current: self.current.clone(),
+            callbacks: self.callbacks.clone(),
+        }
+    }
+}
+
+impl<T: Default + DeserializeOwned + Clone + Send + Sync + 'static> Default for Settings<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: DeserializeOwned + Clone + Send + Sync + 'static> Settings<T> {
+    pub fn new(initial: T) -> Self {
+        Self {
+            current: Arc::new(RwLock::new(initial)),
+            callbacks: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    pub fn get(&self) -> T {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Run `callback` with the new values after every successful [`Settings::apply`].
+    pub fn on_change(&self, callback: impl Fn(&T) + Send + Sync + 'static) {
+        self.callbacks.lock().unwrap().push(Box::new(callback));
+    }
+
+    /// Replace the settings with `config`, keeping the old values if it does not deserialize.
+    pub fn apply(&self, config: Value) -> Result<(), PluginError> {
+        let settings: T = serde_json::from_value(config).map_err(PluginError::Json)?;
+        *self.current.write().unwrap() = settings.clone();
+
+        for callback in self.callbacks.lock().unwrap().iter() {
+            callback(&settings);
+        }
+        Ok(())
+    }
+}
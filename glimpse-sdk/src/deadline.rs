@@ -0,0 +1,63 @@
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+/// The time a plugin has to answer a request, from the `deadline_ms` the daemon sends along.
+///
+/// Plugins size their work by it, e.g. a shallow search when little time is left. Searches
+/// still running when it passes are ended by `run_plugin`, the matches sent so far stand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Deadline {
+    at: Option<Instant>,
+}
+
+impl Deadline {
+    /// No deadline, the plugin takes the time it needs.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    pub fn after(budget: Duration) -> Self {
+        Self {
+            at: Some(Instant::now() + budget),
+        }
+    }
+
+    /// The deadline of a request received now with `deadline_ms`.
+    pub fn from_millis(deadline_ms: Option<u64>) -> Self {
+        match deadline_ms {
+            Some(ms) => Self::after(Duration::from_millis(ms)),
+            None => Self::none(),
+        }
+    }
+
+    pub fn instant(&self) -> Option<Instant> {
+        self.at
+    }
+
+    /// Time left, zero once the deadline passed. `None` without a deadline.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.at
+            .map(|at| at.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn is_exceeded(&self) -> bool {
+        self.remaining()
+            .is_some_and(|remaining| remaining.is_zero())
+    }
+
+    /// Whether work expected to take `estimate` finishes in time.
+    pub fn allows(&self, estimate: Duration) -> bool {
+        self.remaining()
+            .is_none_or(|remaining| estimate <= remaining)
+    }
+
+    /// Run `future` until the deadline, `None` if it passed first.
+    pub async fn run<F: Future>(&self, future: F) -> Option<F::Output> {
+        match self.at {
+            Some(at) => tokio::time::timeout_at(at.into(), future).await.ok(),
+            None => Some(future.await),
+        }
+    }
+}
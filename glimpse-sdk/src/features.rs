@@ -0,0 +1,125 @@
+use std::{error::Error, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+/// Experimental protocol subsystems, announced by plugins in their metadata and turned on
+/// in the daemon's `[features]` settings. A subsystem is used with a plugin only when both
+/// have it on, and only in builds compiled with its cargo feature.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct Features {
+    /// Matches reach clients as plugins send them, rather than once their search is done.
+    pub streaming: bool,
+    /// Messages framed in a binary encoding rather than JSON lines. Negotiated, nothing is
+    /// sent in it yet.
+    pub binary_encoding: bool,
+    /// Plugins publish topics clients subscribe to.
+    pub subscriptions: bool,
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Self {
+            streaming: true,
+            binary_encoding: false,
+            subscriptions: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeatureError {
+    /// Turned on in a build compiled without it.
+    NotCompiled { feature: &'static str },
+    /// Turned on without a feature it works on top of.
+    Requires {
+        feature: &'static str,
+        requires: &'static str,
+    },
+}
+
+impl Display for FeatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeatureError::NotCompiled { feature } => {
+                write!(f, "{} is not compiled into this build", feature)
+            }
+            FeatureError::Requires { feature, requires } => {
+                write!(f, "{} requires {} to be on as well", feature, requires)
+            }
+        }
+    }
+}
+impl Error for FeatureError {}
+
+impl Features {
+    /// Every subsystem this build may turn on, by the cargo features it was compiled with.
+    pub fn compiled() -> Self {
+        Self {
+            streaming: cfg!(feature = "streaming"),
+            binary_encoding: cfg!(feature = "binary-encoding"),
+            subscriptions: cfg!(feature = "subscriptions"),
+        }
+    }
+
+    /// Everything off, as for peers that cannot use experimental subsystems.
+    pub fn none() -> Self {
+        Self {
+            streaming: false,
+            binary_encoding: false,
+            subscriptions: false,
+        }
+    }
+
+    /// The subsystems both sides have on.
+    pub fn intersect(&self, other: &Features) -> Self {
+        Self {
+            streaming: self.streaming && other.streaming,
+            binary_encoding: self.binary_encoding && other.binary_encoding,
+            subscriptions: self.subscriptions && other.subscriptions,
+        }
+    }
+
+    /// Names of the subsystems turned on.
+    pub fn enabled(&self) -> Vec<&'static str> {
+        [
+            ("streaming", self.streaming),
+            ("binary_encoding", self.binary_encoding),
+            ("subscriptions", self.subscriptions),
+        ]
+        .into_iter()
+        .filter_map(|(name, on)| on.then_some(name))
+        .collect()
+    }
+
+    /// Refuse subsystems missing from this build and combinations that cannot work.
+    pub fn check(&self) -> Result<(), FeatureError> {
+        let compiled = Self::compiled();
+        for (feature, on, available) in [
+            ("streaming", self.streaming, compiled.streaming),
+            (
+                "binary_encoding",
+                self.binary_encoding,
+                compiled.binary_encoding,
+            ),
+            ("subscriptions", self.subscriptions, compiled.subscriptions),
+        ] {
+            if on && !available {
+                return Err(FeatureError::NotCompiled { feature });
+            }
+        }
+        self.validate()
+    }
+
+    /// Refuse combinations that cannot work, whatever the build.
+    pub fn validate(&self) -> Result<(), FeatureError> {
+        // binary frames carry the chunks of a streamed search
+        if self.binary_encoding && !self.streaming {
+            return Err(FeatureError::Requires {
+                feature: "binary_encoding",
+                requires: "streaming",
+            });
+        }
+        Ok(())
+    }
+}
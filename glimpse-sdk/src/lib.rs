@@ -1,17 +1,46 @@
+pub mod compression;
+pub mod config;
+pub mod deadline;
+pub mod features;
+pub mod limits;
+pub mod logging;
+pub mod matcher;
 pub mod plugin;
 pub mod protocol;
+pub mod requests;
+pub mod sensitive;
+#[cfg(feature = "testing")]
+pub mod testing;
 
-use std::{error::Error, fmt::Display, path::PathBuf, sync::Arc};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    future::Future,
+    path::PathBuf,
+    process::ExitCode,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
 
 use tokio_util::sync::CancellationToken;
 
-use tokio::{
-    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, stdin, stdout},
-    task::JoinHandle,
-};
+pub use glimpse_sdk_macros::{GlimpsePlugin, main};
+
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, stdin, stdout};
 
+pub use compression::*;
+pub use config::*;
+pub use deadline::*;
+pub use features::*;
+pub use limits::*;
+pub use logging::*;
 pub use plugin::*;
 pub use protocol::*;
+pub use requests::*;
+pub use sensitive::*;
 
 #[derive(Debug)]
 pub enum PluginError {
@@ -19,6 +48,8 @@ pub enum PluginError {
     Io(std::io::Error),
     Json(serde_json::Error),
     Cancelled(String),
+    /// A plugin method panicked, the plugin keeps serving other requests.
+    Panicked(String),
     Other(String),
 }
 
@@ -33,6 +64,7 @@ impl Clone for PluginError {
                 serde_json::from_str::<()>(&format!("invalid: {}", err)).unwrap_err(),
             ),
             PluginError::Cancelled(msg) => PluginError::Cancelled(msg.clone()),
+            PluginError::Panicked(msg) => PluginError::Panicked(msg.clone()),
             PluginError::Other(msg) => PluginError::Other(msg.clone()),
         }
     }
@@ -46,39 +78,227 @@ impl Display for PluginError {
             PluginError::Json(err) => write!(f, "json: {}", err),
             PluginError::Other(msg) => write!(f, "error: {}", msg),
             PluginError::Cancelled(msg) => write!(f, "cancelled: {}", msg),
+            PluginError::Panicked(msg) => write!(f, "panicked: {}", msg),
         }
     }
 }
 impl Error for PluginError {}
 
-pub fn setup_logging(log_level: tracing::Level) {
-    let subscriber = tracing_subscriber::fmt()
-        .with_max_level(log_level)
-        .with_file(true)
-        .with_writer(std::io::stderr)
-        .with_target(false)
-        .finish();
+/// Unix socket the daemon accepts clients on, `$GLIMPSE_SOCKET` or
+/// `$XDG_RUNTIME_DIR/glimpse/glimpsed.sock`.
+#[cfg(unix)]
+pub fn get_client_socket_path() -> PathBuf {
+    if let Some(path) = std::env::var_os("GLIMPSE_SOCKET") {
+        return PathBuf::from(path);
+    }
+    dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("glimpse")
+        .join("glimpsed.sock")
+}
 
-    let _ = tracing::subscriber::set_global_default(subscriber);
+/// Named pipe the daemon accepts clients on, `$GLIMPSE_SOCKET` or `\\.\pipe\glimpse-$USERNAME`.
+/// Pipes share one namespace for the whole machine, the user name keeps them apart.
+#[cfg(windows)]
+pub fn get_client_socket_path() -> PathBuf {
+    if let Some(path) = std::env::var_os("GLIMPSE_SOCKET") {
+        return PathBuf::from(path);
+    }
+    let user = std::env::var("USERNAME").unwrap_or_else(|_| "default".to_string());
+    PathBuf::from(format!(r"\\.\pipe\glimpse-{}", user))
 }
 
-pub async fn run_plugin<P: Plugin>(plugin: P) -> Result<(), PluginError> {
-    let stdin = stdin();
-    let mut stdout = stdout();
-    let mut reader = BufReader::new(stdin);
+/// Searches and their pages stream matches through a sink and callback actions report
+/// progress, all finish with `MethodResult::Done`; other methods answer with a single response.
+///
+/// Searches still running at their deadline are cut short, answering with the matches sent so
+/// far and a truncated `Done`.
+async fn handle_request<P: Plugin>(
+    plugin: &P,
+    id: usize,
+    plugin_id: &str,
+    method: Method,
+    deadline: Deadline,
+    response_tx: &tokio::sync::mpsc::Sender<Message>,
+    queries: &Mutex<RecentQueries>,
+) -> Result<MethodResult, PluginError> {
+    match method {
+        Method::Search(query) => {
+            let sink = SearchSink::new(id, plugin_id.to_string(), response_tx.clone())
+                .with_deadline(deadline);
+            let searched = deadline
+                .run(async {
+                    match plugin.page_size() {
+                        Some(limit) => {
+                            queries.lock().unwrap().remember(id, query.clone());
+                            plugin.search_page(query, 0, limit, &sink).await
+                        }
+                        None => plugin.search(query, &sink).await,
+                    }
+                })
+                .await;
+            finish_search(id, searched)
+        }
+        Method::More {
+            request_id,
+            offset,
+            limit,
+        } => {
+            let query = queries.lock().unwrap().get(request_id).map(str::to_string);
+            let Some(query) = query else {
+                return Err(PluginError::Other(format!(
+                    "unknown search: {}",
+                    request_id
+                )));
+            };
+            let sink = SearchSink::new(id, plugin_id.to_string(), response_tx.clone())
+                .with_deadline(deadline);
+            let searched = deadline
+                .run(plugin.search_page(query, offset, limit, &sink))
+                .await;
+            finish_search(id, searched)
+        }
+        Method::CallAction(action, params) => {
+            let progress = Progress::new(id, plugin_id.to_string(), response_tx.clone());
+            plugin.call_action(action, params, &progress).await?;
+            Ok(MethodResult::Done { truncated: false })
+        }
+        Method::Describe(item) => Ok(MethodResult::Details {
+            detail: plugin.details(*item).await?,
+        }),
+        method => plugin.handle(method).await,
+    }
+}
 
-    let (response_tx, mut response_rx) = tokio::sync::mpsc::channel::<Message>(10);
+async fn write_line<W: AsyncWrite + Unpin>(output: &mut W, line: &str) -> std::io::Result<()> {
+    output.write_all(line.as_bytes()).await?;
+    output.write_all(b"\n").await?;
+    output.flush().await
+}
 
+/// The end of a search run with [`Deadline::run`], `None` if the deadline passed.
+fn finish_search(
+    id: usize,
+    searched: Option<Result<(), PluginError>>,
+) -> Result<MethodResult, PluginError> {
+    match searched {
+        Some(result) => result.map(|_| MethodResult::Done { truncated: false }),
+        None => {
+            tracing::debug!("search {} ran out of time, its matches are partial", id);
+            Ok(MethodResult::Done { truncated: true })
+        }
+    }
+}
 
-    let context = Context{
-        config_dir: dirs::config_dir()
+pub async fn run_plugin<P: Plugin>(plugin: P) -> Result<(), PluginError> {
+    let config_dir = dirs::config_dir()
         .unwrap_or_else(|| PathBuf::from("."))
-        .join("glimpse"),
+        .join("glimpse");
+    serve_plugin(plugin, stdin(), stdout(), config_dir).await
+}
+
+/// What the main function of `#[glimpse_sdk::main]` returns: the plugin, or a `Result` of it
+/// failing to start.
+pub trait IntoPlugin {
+    type Plugin: Plugin;
+
+    fn into_plugin(self) -> Result<Self::Plugin, String>;
+}
+
+impl<P: Plugin> IntoPlugin for P {
+    type Plugin = P;
+
+    fn into_plugin(self) -> Result<P, String> {
+        Ok(self)
+    }
+}
+
+impl<P: Plugin, E: Display> IntoPlugin for Result<P, E> {
+    type Plugin = P;
+
+    fn into_plugin(self) -> Result<P, String> {
+        self.map_err(|err| err.to_string())
+    }
+}
+
+/// The entry point `#[glimpse_sdk::main]` wraps the plugin's main function in: logging to
+/// the daemon at info level, then running the plugin `plugin` resolves to on a tokio runtime.
+///
+/// ```ignore
+/// #[glimpse_sdk::main]
+/// async fn main() -> Result<SshPlugin, Box<dyn Error>> {
+///     let home = dirs::home_dir().ok_or("cannot determine the home directory")?;
+///     Ok(SshPlugin::new(home.join(".ssh")))
+/// }
+/// ```
+pub fn serve_main<F>(plugin: F) -> ExitCode
+where
+    F: Future,
+    F::Output: IntoPlugin,
+{
+    setup_logging(tracing::Level::INFO);
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            tracing::error!("failed to start the runtime: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    runtime.block_on(async {
+        let plugin = match plugin.await.into_plugin() {
+            Ok(plugin) => plugin,
+            Err(err) => {
+                tracing::error!("failed to start the plugin: {}", err);
+                return ExitCode::FAILURE;
+            }
+        };
+        match run_plugin(plugin).await {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                tracing::error!("error running plugin: {}", err);
+                ExitCode::FAILURE
+            }
+        }
+    })
+}
+
+/// Serve the daemon's messages read from `input`, answering on `output`, until the input
+/// closes or the daemon says `Quit`.
+pub(crate) async fn serve_plugin<P, R, W>(
+    plugin: P,
+    input: R,
+    mut output: W,
+    config_dir: PathBuf,
+) -> Result<(), PluginError>
+where
+    P: Plugin,
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let mut reader = BufReader::new(input);
+
+    let (response_tx, mut response_rx) = tokio::sync::mpsc::channel::<Message>(10);
+
+    let context = Context {
+        config_dir,
+        tx: response_tx.clone(),
     };
     plugin.initialize(&context).await?;
 
-    // authenticate
-    let metadata = plugin.metadata();
+    // authenticate, announcing the protocol version and capabilities
+    let mut metadata = plugin.metadata();
+    metadata.protocol_version = PROTOCOL_VERSION;
+    metadata.capabilities = SDK_CAPABILITIES.to_vec();
+    if plugin.page_size().is_some() {
+        metadata.capabilities.push(Capability::Paginated);
+    }
+    if plugin.lazy_details() {
+        metadata.capabilities.push(Capability::Details);
+    }
+    metadata.features = plugin.features().intersect(&Features::compiled());
     let plugin_id = metadata.id.clone();
 
     tracing::debug!(
@@ -92,118 +312,219 @@ pub async fn run_plugin<P: Plugin>(plugin: P) -> Result<(), PluginError> {
         id: 0,
         error: None,
         plugin_id: Some(plugin_id.clone()),
-        result: Some(MethodResult::Authenticate(metadata)),
+        result: Some(MethodResult::Authenticate(Box::new(metadata))),
     };
     response_tx
         .send(auth_message)
         .await
         .map_err(|e| PluginError::Authenticate(e.to_string()))?;
 
-    // task cancellation
-    let mut current_cancel_token: Option<CancellationToken> = None;
-    let mut current_task: Option<JoinHandle<()>> = None;
+    // requests run concurrently, the daemon may serve several clients at once
+    let mut requests = RequestTable::new(
+        plugin_id.clone(),
+        response_tx.clone(),
+        plugin.max_concurrent_requests(),
+    );
+
+    // paginated plugins serve later pages of the searches they remember
+    let queries = Arc::new(Mutex::new(RecentQueries::new()));
+
+    // subscriptions live independently of search requests
+    let mut subscriptions: HashMap<String, CancellationToken> = HashMap::new();
+
+    // messages go out plain until the daemon asks for compression, 0 meaning off
+    let threshold = Arc::new(AtomicUsize::new(0));
+    let compression = Arc::new(CompressionStats::default());
 
     let self_ref = Arc::new(plugin);
     let response_tx_clone = response_tx.clone();
+    let enabled = threshold.clone();
+
+    // settings and power profiles apply one at a time in the order they were sent, so an
+    // older one never overwrites what the daemon sent last
+    let (updates_tx, mut updates_rx) = tokio::sync::mpsc::unbounded_channel::<Method>();
+    let updated = self_ref.clone();
+    tokio::spawn(async move {
+        while let Some(method) = updates_rx.recv().await {
+            if let Err(err) = catch_panic(updated.handle(method)).await {
+                tracing::warn!("notification failed: {}", err);
+            }
+        }
+    });
 
     let stdin_handle = tokio::spawn(async move {
         let mut line = String::new();
-        loop {
+        'read: loop {
             line.clear();
-            let bytes_read = reader.read_line(&mut line).await.unwrap();
-            if bytes_read == 0 {
-                break;
+            match reader.read_line(&mut line).await {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(err) => {
+                    tracing::warn!("failed to read a message: {}", err);
+                    break;
+                }
             }
-            let message: Message = match serde_json::from_str(&line) {
-                Ok(msg) => msg,
+            let line = match decompress_line(&line) {
+                Ok(line) => line,
+                Err(err) => {
+                    tracing::warn!("failed to decompress a message: {}", err);
+                    continue;
+                }
+            };
+            let frame = match Frame::parse(&line) {
+                Ok(frame) => frame,
                 Err(err) => {
                     tracing::warn!("failed to parse JSON: {}", err);
                     continue;
                 }
             };
 
-            tracing::debug!("request: {:?}", &message);
-            match message {
-                Message::Request { id, method, .. } => {
-                    if let Some(cancel_token) = current_cancel_token.take() {
-                        tracing::debug!("cancelling previous request");
-                        cancel_token.cancel();
-                    }
-
-                    if let Some(task) = current_task.take() {
-                        task.abort();
-                    }
-
-                    // new cancellation token
-                    let cancel_token = CancellationToken::new();
-                    current_cancel_token = Some(cancel_token.clone());
-
-                    let plugin_clone = self_ref.clone();
-                    let response_tx = response_tx_clone.clone();
-
-                    let plugin_id = plugin_id.clone();
-                    let task = tokio::spawn(async move {
-                        let result = tokio::select! {
-                            result = plugin_clone.handle(method) => result,
-                            _ = cancel_token.cancelled() => {
-                                tracing::debug!("request {} was cancelled", id);
-                                Err(PluginError::Cancelled("request cancelled".into()))
-                            },
-                        };
-
-                        let response = match result {
-                            Ok(method_result) => Message::Response {
-                                id,
-                                error: None,
-                                plugin_id: Some(plugin_id.clone()),
-                                result: Some(method_result),
-                            },
-                            Err(err) => Message::Response {
-                                id,
-                                error: Some(err.to_string()),
-                                plugin_id: Some(plugin_id.clone()),
-                                result: None,
-                            },
-                        };
-
-                        if let Err(err) = response_tx.send(response).await {
-                            tracing::warn!("error sending response: {}", err);
-                        }
-                    });
-                    current_task = Some(task);
-                }
-                Message::Notification { method, .. } => match method {
-                    Method::Cancel => {
-                        if let Some(cancel_token) = current_cancel_token.take() {
-                            cancel_token.cancel();
-                            tracing::debug!("request cancelled");
+            for message in frame.into_messages() {
+                tracing::debug!("request: {:?}", &message);
+                match message {
+                    Message::Request {
+                        id,
+                        method: Method::Subscribe { topic, .. },
+                        ..
+                    } => {
+                        let token = CancellationToken::new();
+                        if let Some(previous) = subscriptions.insert(topic.clone(), token.clone()) {
+                            previous.cancel();
                         }
-                    }
-                    Method::CallAction(..) => {
+
+                        let publisher = Publisher::new(
+                            id,
+                            plugin_id.clone(),
+                            topic.clone(),
+                            response_tx_clone.clone(),
+                            token.clone(),
+                        );
                         let plugin_clone = self_ref.clone();
-                        let method_clone = method.clone();
+                        let response_tx = response_tx_clone.clone();
+                        let plugin_id = plugin_id.clone();
                         tokio::spawn(async move {
-                            let _ = plugin_clone.handle(method_clone).await;
+                            let result = tokio::select! {
+                                result = catch_panic(plugin_clone.subscribe(topic.clone(), publisher)) => result,
+                                _ = token.cancelled() => Ok(()),
+                            };
+
+                            if let Err(err) = result {
+                                tracing::warn!("subscription to {} failed: {}", topic, err);
+                                let response = Message::Response {
+                                    id,
+                                    error: Some(RpcError::plugin(err.to_string())),
+                                    plugin_id: Some(plugin_id),
+                                    result: None,
+                                };
+                                let _ = response_tx.send(response).await;
+                            }
                         });
                     }
-                    Method::Quit => {
-                        tracing::debug!("quitting");
-                        break;
+                    Message::Request {
+                        method: Method::Unsubscribe { topic, .. },
+                        ..
+                    } => {
+                        if let Some(token) = subscriptions.remove(&topic) {
+                            tracing::debug!("unsubscribed from {}", topic);
+                            token.cancel();
+                        }
+                    }
+                    Message::Request {
+                        id,
+                        method: Method::Cancel(target),
+                        ..
+                    } => {
+                        requests.cancel(target.unwrap_or(id));
+                    }
+                    Message::Request {
+                        id,
+                        method,
+                        deadline_ms,
+                        ..
+                    } => {
+                        let deadline = Deadline::from_millis(deadline_ms);
+                        let plugin = self_ref.clone();
+                        let plugin_id = plugin_id.clone();
+                        let response_tx = response_tx_clone.clone();
+                        let queries = queries.clone();
+                        requests.start(id, async move {
+                            handle_request(
+                                plugin.as_ref(),
+                                id,
+                                &plugin_id,
+                                method,
+                                deadline,
+                                &response_tx,
+                                &queries,
+                            )
+                            .await
+                        });
                     }
+                    Message::Notification { method, .. } => match method {
+                        Method::Cancel(Some(id)) => {
+                            requests.cancel(id);
+                        }
+                        Method::Cancel(None) => {
+                            requests.cancel_all();
+                            tracing::debug!("requests cancelled");
+                        }
+                        Method::Configure(..)
+                        | Method::ConfigChanged(..)
+                        | Method::PowerProfile(..) => {
+                            let _ = updates_tx.send(method);
+                        }
+                        Method::CallAction(..) => {
+                            let plugin_clone = self_ref.clone();
+                            let method_clone = method.clone();
+                            tokio::spawn(async move {
+                                if let Err(err) =
+                                    catch_panic(plugin_clone.handle(method_clone)).await
+                                {
+                                    tracing::warn!("notification failed: {}", err);
+                                }
+                            });
+                        }
+                        Method::EnableCompression { threshold } => {
+                            tracing::debug!("compressing messages over {} bytes", threshold);
+                            enabled.store(threshold.max(1), Ordering::Relaxed);
+                        }
+                        Method::Quit => {
+                            tracing::debug!("quitting");
+                            break 'read;
+                        }
+                        _ => {}
+                    },
                     _ => {}
-                },
-                _ => {}
+                }
             }
         }
+
+        for (_, token) in subscriptions.drain() {
+            token.cancel();
+        }
     });
 
+    let stats = compression.clone();
     let stdout_handle = tokio::spawn(async move {
         while let Some(message) = response_rx.recv().await {
-            let response = serde_json::to_string(&message).unwrap();
             tracing::debug!("response: {:?}", &message);
-            stdout.write_all(response.as_bytes()).await.unwrap();
-            stdout.write_all(b"\n").await.unwrap();
-            stdout.flush().await.unwrap();
+            let (response, oversized) = encode_bounded(&message, MAX_MESSAGE_BYTES);
+            if let Some(oversized) = oversized {
+                tracing::warn!("{}", oversized);
+            }
+            let Some(mut response) = response else {
+                continue;
+            };
+            let threshold = threshold.load(Ordering::Relaxed);
+            if threshold > 0
+                && let Some(compressed) = stats.compress(&response, threshold)
+            {
+                response = compressed;
+            }
+            if let Err(err) = write_line(&mut output, &response).await {
+                tracing::warn!("failed to write a message: {}", err);
+                break;
+            }
         }
     });
 
@@ -215,6 +536,9 @@ pub async fn run_plugin<P: Plugin>(plugin: P) -> Result<(), PluginError> {
             tracing::debug!("stdout write completed, exiting");
         },
     }
+    if compression.messages() > 0 {
+        tracing::info!("compressed {}", compression);
+    }
 
     Ok(())
 }
@@ -0,0 +1,191 @@
+use std::{error::Error, fmt::Display};
+
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use crate::{Message, MethodResult, RpcError};
+
+/// Largest message a peer writes or reads, newline excluded. Writers shorten larger
+/// responses, readers skip larger lines without buffering them.
+pub const MAX_MESSAGE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Outcome of [`read_line_bounded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineRead {
+    Eof,
+    Line,
+    /// The line was longer than the limit and skipped, holds its length in bytes.
+    TooLong(usize),
+}
+
+/// Read a line into `line` without its newline, like `read_line` but skipping lines longer
+/// than `limit` bytes instead of growing the buffer with them. Invalid UTF-8 is replaced.
+pub async fn read_line_bounded<R>(
+    reader: &mut R,
+    line: &mut String,
+    limit: usize,
+) -> std::io::Result<LineRead>
+where
+    R: AsyncBufRead + Unpin,
+{
+    line.clear();
+    let mut bytes = Vec::new();
+    let mut total = 0;
+    let mut too_long = false;
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            break;
+        }
+        let (chunk, done) = match available.iter().position(|byte| *byte == b'\n') {
+            Some(end) => (&available[..end], true),
+            None => (available, false),
+        };
+        total += chunk.len();
+        if total > limit {
+            too_long = true;
+            bytes = Vec::new();
+        } else {
+            bytes.extend_from_slice(chunk);
+        }
+        let consumed = chunk.len() + usize::from(done);
+        reader.consume(consumed);
+        if done {
+            return Ok(finish(line, bytes, total, too_long));
+        }
+    }
+    if total == 0 {
+        return Ok(LineRead::Eof);
+    }
+    Ok(finish(line, bytes, total, too_long))
+}
+
+fn finish(line: &mut String, bytes: Vec<u8>, total: usize, too_long: bool) -> LineRead {
+    if too_long {
+        return LineRead::TooLong(total);
+    }
+    line.push_str(&String::from_utf8_lossy(&bytes));
+    LineRead::Line
+}
+
+/// What [`encode_bounded`] did to a message over the limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Oversized {
+    /// The matches of response `id` past the first `kept` were left out.
+    Truncated {
+        id: usize,
+        bytes: usize,
+        kept: usize,
+        dropped: usize,
+    },
+    /// Response `id` could not be shortened and was answered with an error instead.
+    Replaced { id: usize, bytes: usize },
+    /// A request or notification too large to send was left out.
+    Dropped { bytes: usize },
+}
+
+impl Display for Oversized {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Oversized::Truncated {
+                id,
+                bytes,
+                kept,
+                dropped,
+            } => write!(
+                f,
+                "response {} of {} bytes is over the limit, sent {} matches and left out {}",
+                id, bytes, kept, dropped
+            ),
+            Oversized::Replaced { id, bytes } => write!(
+                f,
+                "response {} of {} bytes is over the limit, answered with an error",
+                id, bytes
+            ),
+            Oversized::Dropped { bytes } => {
+                write!(f, "message of {} bytes is over the limit, not sent", bytes)
+            }
+        }
+    }
+}
+impl Error for Oversized {}
+
+/// Serialize `message` into a line of at most `limit` bytes.
+///
+/// Responses carrying matches keep as many of them as fit, plugins send their best matches
+/// first. Other responses become an error response. Requests and notifications over the limit
+/// are not sent.
+pub fn encode_bounded(message: &Message, limit: usize) -> (Option<String>, Option<Oversized>) {
+    let line = serde_json::to_string(message).expect("messages serialize");
+    if line.len() <= limit {
+        return (Some(line), None);
+    }
+    let bytes = line.len();
+
+    let Message::Response {
+        id,
+        result,
+        plugin_id,
+        ..
+    } = message
+    else {
+        return (None, Some(Oversized::Dropped { bytes }));
+    };
+
+    if let Some(total) = match_count(result.as_ref()) {
+        // the largest prefix of the matches that fits
+        let with = |kept: usize| {
+            let mut shortened = message.clone();
+            if let Message::Response {
+                result: Some(MethodResult::Matches { items } | MethodResult::Update { items, .. }),
+                ..
+            } = &mut shortened
+            {
+                items.truncate(kept);
+            }
+            serde_json::to_string(&shortened).expect("messages serialize")
+        };
+        let (mut low, mut high) = (0, total);
+        let mut fitting = None;
+        while low < high {
+            let mid = (low + high).div_ceil(2);
+            let line = with(mid);
+            if line.len() <= limit {
+                low = mid;
+                fitting = Some(line);
+            } else {
+                high = mid - 1;
+            }
+        }
+        let line = fitting.or_else(|| Some(with(0)).filter(|line| line.len() <= limit));
+        if let Some(line) = line {
+            let oversized = Oversized::Truncated {
+                id: *id,
+                bytes,
+                kept: low,
+                dropped: total - low,
+            };
+            return (Some(line), Some(oversized));
+        }
+    }
+
+    let replacement = Message::Response {
+        id: *id,
+        error: Some(RpcError::plugin(format!(
+            "response of {} bytes exceeds the {} byte limit",
+            bytes, limit
+        ))),
+        result: None,
+        plugin_id: plugin_id.clone(),
+    };
+    let line = serde_json::to_string(&replacement).expect("messages serialize");
+    (Some(line), Some(Oversized::Replaced { id: *id, bytes }))
+}
+
+fn match_count(result: Option<&MethodResult>) -> Option<usize> {
+    match result {
+        Some(MethodResult::Matches { items } | MethodResult::Update { items, .. }) => {
+            Some(items.len())
+        }
+        _ => None,
+    }
+}
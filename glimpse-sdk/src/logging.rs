@@ -0,0 +1,207 @@
+use std::{
+    fmt::Display,
+    io::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::{
+    Event, Subscriber,
+    field::{Field, Visit},
+};
+use tracing_subscriber::{
+    filter::LevelFilter,
+    layer::{Context, Layer, SubscriberExt},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl From<&tracing::Level> for LogLevel {
+    fn from(level: &tracing::Level) -> Self {
+        match *level {
+            tracing::Level::TRACE => LogLevel::Trace,
+            tracing::Level::DEBUG => LogLevel::Debug,
+            tracing::Level::INFO => LogLevel::Info,
+            tracing::Level::WARN => LogLevel::Warn,
+            tracing::Level::ERROR => LogLevel::Error,
+        }
+    }
+}
+
+impl Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let level = match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        };
+        f.pad(level)
+    }
+}
+
+/// One log event, written by plugins to stderr as a JSON line.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LogRecord {
+    /// Unix time in milliseconds.
+    pub ts: u64,
+    pub level: LogLevel,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub target: String,
+    pub message: String,
+    /// `file:line` of the event, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Structured fields of the event other than its message.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub fields: Map<String, Value>,
+}
+
+impl LogRecord {
+    /// The record of a stderr line read at `at`. Lines not logged through the SDK, such as
+    /// panic messages, are kept as warnings.
+    pub fn parse_line(line: &str, at: u64) -> Self {
+        let line = line.trim_end();
+        serde_json::from_str(line).unwrap_or_else(|_| LogRecord {
+            ts: at,
+            level: LogLevel::Warn,
+            target: String::new(),
+            message: line.to_string(),
+            location: None,
+            fields: Map::new(),
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("log records always serialize")
+    }
+}
+
+impl Display for LogRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:>5} ", self.level)?;
+        if !self.target.is_empty() {
+            write!(f, "{}: ", self.target)?;
+        }
+        write!(f, "{}", self.message)?;
+        for (name, value) in &self.fields {
+            match value {
+                Value::String(value) => write!(f, " {}={}", name, value)?,
+                value => write!(f, " {}={}", name, value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Milliseconds since the Unix epoch.
+pub fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Collects the message and fields of an event.
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: Map<String, Value>,
+}
+
+impl FieldVisitor {
+    fn record(&mut self, field: &Field, value: Value) {
+        match (field.name(), value) {
+            ("message", Value::String(message)) => self.message = message,
+            (name, value) => {
+                self.fields.insert(name.to_string(), value);
+            }
+        }
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.record(field, Value::String(format!("{:?}", value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, Value::String(value.to_string()));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record(field, Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record(field, Value::from(value));
+    }
+}
+
+/// A tracing layer handing every event to `write` as a [`LogRecord`].
+pub struct JsonLayer<F> {
+    write: F,
+}
+
+impl<F> JsonLayer<F>
+where
+    F: Fn(&LogRecord) + Send + Sync + 'static,
+{
+    pub fn new(write: F) -> Self {
+        Self { write }
+    }
+}
+
+impl<S, F> Layer<S> for JsonLayer<F>
+where
+    S: Subscriber,
+    F: Fn(&LogRecord) + Send + Sync + 'static,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let record = LogRecord {
+            ts: unix_millis(),
+            level: LogLevel::from(metadata.level()),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+            location: metadata
+                .file()
+                .map(|file| format!("{}:{}", file, metadata.line().unwrap_or(0))),
+            fields: visitor.fields,
+        };
+        (self.write)(&record);
+    }
+}
+
+/// Log events at `log_level` and above to stderr, one JSON [`LogRecord`] per line. The
+/// daemon keeps them in the plugin's log file.
+pub fn setup_logging(log_level: tracing::Level) {
+    let subscriber = tracing_subscriber::registry()
+        .with(LevelFilter::from_level(log_level))
+        .with(JsonLayer::new(|record| {
+            let mut stderr = std::io::stderr().lock();
+            let _ = writeln!(stderr, "{}", record.to_json());
+        }));
+
+    let _ = tracing::subscriber::set_global_default(subscriber);
+}
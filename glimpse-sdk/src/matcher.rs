@@ -0,0 +1,280 @@
+use crate::{Highlights, Match};
+
+const SCORE_MATCH: i32 = 16;
+const PENALTY_GAP_START: i32 = -3;
+const PENALTY_GAP_EXTENSION: i32 = -1;
+/// At the start of the text or after a separator.
+const BONUS_BOUNDARY: i32 = 8;
+/// At a lowercase to uppercase or a letter to digit change.
+const BONUS_CAMEL: i32 = 7;
+/// Added to every character right after another matched one.
+const BONUS_CONSECUTIVE: i32 = 4;
+/// The bonus of the query's first character counts this many times.
+const FIRST_CHAR_MULTIPLIER: i32 = 2;
+
+/// Fuzzy matching for plugins, so matches of every plugin are scored alike.
+///
+/// Texts are scored in the manner of fzf: the query's characters must appear in order, and
+/// the best alignment wins, found with a Smith-Waterman style pass. Characters at the start of
+/// words, after a separator or at a camelCase hump earn a bonus, runs of adjacent characters
+/// keep it, and gaps cost a penalty. Texts and queries are compared case folded and with the
+/// accents of Latin letters removed, so `cafe` finds `Café`. An empty query matches every
+/// text with the top score.
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    pattern: Vec<char>,
+}
+
+impl Matcher {
+    /// Whitespace around `query` is ignored.
+    pub fn new(query: &str) -> Self {
+        Self {
+            pattern: query.trim().chars().map(fold).collect(),
+        }
+    }
+
+    /// How well the query matches `text`, from 0 to 1, or None when it does not.
+    pub fn score(&self, text: &str) -> Option<f64> {
+        self.indices(text).map(|(score, _)| score)
+    }
+
+    /// The score and the positions in `text` the query's characters matched at, counted in
+    /// chars.
+    pub fn indices(&self, text: &str) -> Option<(f64, Vec<usize>)> {
+        if self.pattern.is_empty() {
+            return Some((1.0, vec![]));
+        }
+        let chars: Vec<char> = text.chars().collect();
+        let folded: Vec<char> = chars.iter().map(|&c| fold(c)).collect();
+        if !is_subsequence(&self.pattern, &folded) {
+            return None;
+        }
+        let bonuses: Vec<i32> = (0..chars.len())
+            .map(|j| bonus(j.checked_sub(1).map(|i| chars[i]), chars[j]))
+            .collect();
+        let (score, positions) = align(&self.pattern, &folded, &bonuses)?;
+        let best = perfect_score(self.pattern.len());
+        Some(((score as f64 / best as f64).clamp(0.0, 1.0), positions))
+    }
+
+    /// How well the query matches `item`, by its title or, counting half, its description.
+    /// The highlights are those of the better of the two.
+    pub fn match_item(&self, item: &Match) -> Option<(f64, Highlights)> {
+        let title = self.indices(&item.title).map(|(score, indices)| {
+            let highlights = Highlights {
+                title: ranges(&indices),
+                description: vec![],
+            };
+            (score, highlights)
+        });
+        let description = self.indices(&item.description).map(|(score, indices)| {
+            let highlights = Highlights {
+                title: vec![],
+                description: ranges(&indices),
+            };
+            (score / 2.0, highlights)
+        });
+        match (title, description) {
+            (Some(title), Some(description)) if description.0 > title.0 => Some(description),
+            (Some(title), _) => Some(title),
+            (None, description) => description,
+        }
+    }
+}
+
+/// The matches `query` matches, scored and highlighted by [`Matcher::match_item`] and
+/// sorted best first. Highlights the plugin set are kept.
+pub fn filter_matches(query: &str, items: Vec<Match>) -> Vec<Match> {
+    let matcher = Matcher::new(query);
+    let mut found: Vec<Match> = items
+        .into_iter()
+        .filter_map(|mut item| {
+            let (score, highlights) = matcher.match_item(&item)?;
+            item.score = score;
+            if item.highlights.is_none()
+                && (!highlights.title.is_empty() || !highlights.description.is_empty())
+            {
+                item.highlights = Some(highlights);
+            }
+            Some(item)
+        })
+        .collect();
+    found.sort_by(|a, b| b.score.total_cmp(&a.score));
+    found
+}
+
+/// Runs of adjacent positions as `(start, end)` ranges, for [`Highlights`].
+pub fn ranges(indices: &[usize]) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = vec![];
+    for &index in indices {
+        match ranges.last_mut() {
+            Some((_, end)) if *end == index => *end += 1,
+            _ => ranges.push((index, index + 1)),
+        }
+    }
+    ranges
+}
+
+/// `c` lowercased and without its accent, for comparing. Characters lowercasing to several,
+/// like `İ`, are compared by the first.
+fn fold(c: char) -> char {
+    c.to_lowercase().next().map_or(c, strip_accent)
+}
+
+const ACCENTED: [(char, &str); 19] = [
+    ('a', "àáâãäåāăąǎǟǡǻȁȃȧ"),
+    ('c', "çćĉċč"),
+    ('d', "ďđ"),
+    ('e', "èéêëēĕėęěȅȇȩ"),
+    ('g', "ĝğġģǧǵ"),
+    ('h', "ĥħȟ"),
+    ('i', "ìíîïĩīĭįıǐȉȋ"),
+    ('j', "ĵǰ"),
+    ('k', "ķǩ"),
+    ('l', "ĺļľŀł"),
+    ('n', "ñńņňǹ"),
+    ('o', "òóôõöøōŏőơǒǫǭȍȏȫȭȯȱ"),
+    ('r', "ŕŗřȑȓ"),
+    ('s', "śŝşšſș"),
+    ('t', "ţťț"),
+    ('u', "ùúûüũūŭůűųưǔǖǘǚǜȕȗ"),
+    ('w', "ŵ"),
+    ('y', "ýÿŷȳ"),
+    ('z', "źżž"),
+];
+
+/// The base letter of a lowercase Latin letter with an accent.
+fn strip_accent(c: char) -> char {
+    if c.is_ascii() {
+        return c;
+    }
+    ACCENTED
+        .iter()
+        .find(|(_, accented)| accented.contains(c))
+        .map_or(c, |&(base, _)| base)
+}
+
+fn is_subsequence(pattern: &[char], text: &[char]) -> bool {
+    let mut text = text.iter();
+    pattern.iter().all(|p| text.any(|c| c == p))
+}
+
+#[derive(PartialEq)]
+enum CharClass {
+    Separator,
+    Lower,
+    Upper,
+    Digit,
+    Other,
+}
+
+fn class(c: char) -> CharClass {
+    if c.is_whitespace() || matches!(c, '/' | '\\' | '-' | '_' | '.' | ',' | ':' | ';' | '|') {
+        CharClass::Separator
+    } else if c.is_lowercase() {
+        CharClass::Lower
+    } else if c.is_uppercase() {
+        CharClass::Upper
+    } else if c.is_numeric() {
+        CharClass::Digit
+    } else {
+        CharClass::Other
+    }
+}
+
+/// Bonus for matching `current`, which follows `previous`.
+fn bonus(previous: Option<char>, current: char) -> i32 {
+    let current = class(current);
+    if current == CharClass::Separator {
+        return 0;
+    }
+    let Some(previous) = previous else {
+        return BONUS_BOUNDARY;
+    };
+    match (class(previous), current) {
+        (CharClass::Separator, _) => BONUS_BOUNDARY,
+        (CharClass::Lower, CharClass::Upper) => BONUS_CAMEL,
+        (CharClass::Lower | CharClass::Upper, CharClass::Digit) => BONUS_CAMEL,
+        _ => 0,
+    }
+}
+
+/// The score of a query of `len` characters matched at the start of a word in one run.
+fn perfect_score(len: usize) -> i32 {
+    let len = len as i32;
+    SCORE_MATCH * len
+        + BONUS_BOUNDARY * FIRST_CHAR_MULTIPLIER
+        + (BONUS_BOUNDARY + BONUS_CONSECUTIVE) * (len - 1)
+}
+
+/// The best alignment of `pattern` in `text` by score, with the positions it matched at.
+///
+/// `scores[i][j]` is the best score of `pattern[..=i]` with `pattern[i]` matched at `text[j]`.
+/// A match either follows the one of the previous character right away, earning the
+/// consecutive bonus and at least the bonus its run started with, or after a gap, whose
+/// penalty grows with its length.
+fn align(pattern: &[char], text: &[char], bonuses: &[i32]) -> Option<(i32, Vec<usize>)> {
+    let (m, n) = (pattern.len(), text.len());
+    let mut scores = vec![vec![None::<i32>; n]; m];
+    // for consecutive matches, the bonus of the character their run started at
+    let mut run_bonus = vec![vec![0; n]; m];
+    let mut from = vec![vec![0usize; n]; m];
+
+    for (j, &c) in text.iter().enumerate() {
+        if c == pattern[0] {
+            scores[0][j] = Some(SCORE_MATCH + bonuses[j] * FIRST_CHAR_MULTIPLIER);
+            run_bonus[0][j] = bonuses[j];
+        }
+    }
+    for i in 1..m {
+        // best score of pattern[..i] ending at least one character before j - 1, with the
+        // penalty of the gap up to j
+        let mut gapped: Option<(i32, usize)> = None;
+        for j in i..n {
+            if j >= 2 {
+                let extended = gapped.map(|(score, k)| (score + PENALTY_GAP_EXTENSION, k));
+                let started = scores[i - 1][j - 2].map(|score| (score + PENALTY_GAP_START, j - 2));
+                gapped = match (extended, started) {
+                    (Some(e), Some(s)) if s.0 >= e.0 => Some(s),
+                    (Some(e), _) => Some(e),
+                    (None, s) => s,
+                };
+            }
+            if text[j] != pattern[i] {
+                continue;
+            }
+            let consecutive = scores[i - 1][j - 1].map(|score| {
+                let bonus = bonuses[j].max(run_bonus[i - 1][j - 1]);
+                (
+                    score + SCORE_MATCH + bonus + BONUS_CONSECUTIVE,
+                    bonus,
+                    j - 1,
+                )
+            });
+            let after_gap =
+                gapped.map(|(score, k)| (score + SCORE_MATCH + bonuses[j], bonuses[j], k));
+            let best = match (consecutive, after_gap) {
+                (Some(c), Some(g)) if g.0 > c.0 => Some(g),
+                (Some(c), _) => Some(c),
+                (None, g) => g,
+            };
+            if let Some((score, bonus, k)) = best {
+                scores[i][j] = Some(score);
+                run_bonus[i][j] = bonus;
+                from[i][j] = k;
+            }
+        }
+    }
+
+    let (mut j, score) = scores[m - 1]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, score)| score.map(|score| (j, score)))
+        .max_by_key(|&(j, score)| (score, std::cmp::Reverse(j)))?;
+    let mut positions = vec![j; m];
+    for i in (1..m).rev() {
+        j = from[i][j];
+        positions[i - 1] = j;
+    }
+    Some((score, positions))
+}
@@ -2,16 +2,215 @@ use std::{collections::HashMap, path::PathBuf};
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
-use crate::{Match, Method, MethodResult, PluginError};
+use crate::{
+    Action, ActionProgress, ConfigSchema, Deadline, Detail, Features, Icon, Match, Message, Method,
+    MethodResult, PluginError, PowerProfile,
+};
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// Version of the plugin protocol this SDK speaks. Bumped when messages change in ways
+/// older daemons or plugins cannot parse.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Requests a plugin runs at once unless it overrides [`Plugin::max_concurrent_requests`].
+pub const DEFAULT_CONCURRENT_REQUESTS: usize = 8;
+
+/// Optional parts of the protocol a plugin implements, announced when it authenticates.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Answers searches with any number of `Matches` chunks followed by `Done`.
+    StreamingSearch,
+    /// Runs `CallAction` requests and may report `ActionProgress` while they run.
+    ActionProgress,
+    /// Publishes topics clients subscribe to.
+    Subscriptions,
+    /// Runs requests concurrently and accepts `Cancel` notifications naming the one to stop.
+    TargetedCancel,
+    /// Declares in `Metadata::permissions` what its actions need. Plugins without it declare
+    /// nothing, their actions needing a permission are refused.
+    Permissions,
+    /// Serves searches a page at a time and answers `More` with the following pages.
+    /// Announced by plugins with a [`Plugin::page_size`].
+    Paginated,
+    /// Answers `Describe` with the details of its matches, which it sends without them.
+    /// Announced by plugins with [`Plugin::lazy_details`].
+    Details,
+    /// Reads compressed lines and compresses large messages once the daemon sends
+    /// `EnableCompression`.
+    Compression,
+    /// Announced by a newer plugin, ignored.
+    #[serde(other)]
+    Unknown,
+}
+
+/// What every plugin served by [`crate::run_plugin`] supports.
+pub const SDK_CAPABILITIES: &[Capability] = &[
+    Capability::StreamingSearch,
+    Capability::ActionProgress,
+    Capability::Subscriptions,
+    Capability::TargetedCancel,
+    Capability::Permissions,
+    Capability::Compression,
+];
+
+/// What a plugin's actions may do, declared in its metadata. The daemon rejects actions
+/// needing a permission the plugin did not declare.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// Copy text to the clipboard.
+    Clipboard,
+    /// Run commands.
+    Exec,
+    /// Open web addresses.
+    Network,
+    /// Open files and directories.
+    HomeRead,
+    /// Show desktop notifications, such as those of scheduled timers.
+    Notify,
+    /// Type text into other windows, as if on the keyboard.
+    Keyboard,
+    /// Shut down, reboot or suspend the machine, lock the screen or log out.
+    Power,
+    /// Connect and disconnect Bluetooth devices and networks.
+    Devices,
+    /// Handle passwords, keys or tokens. Not tied to an action, such plugins are kept out of
+    /// everything the daemon writes to disk.
+    Secrets,
+    /// Declared by a newer plugin, ignored.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Permission {
+    /// Sensitive permissions also need the user's consent, asked for on first use.
+    pub fn is_sensitive(&self) -> bool {
+        matches!(
+            self,
+            Permission::Exec | Permission::Secrets | Permission::Power
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct Metadata {
     pub id: String,
     pub name: String,
     pub version: String,
     pub description: String,
     pub author: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_schema: Option<ConfigSchema>,
+    /// Queries starting with this prefix go to this plugin alone, with the prefix stripped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// Words that route a query to this plugin alone when it starts with one of them, on
+    /// its own or followed by a space: `clip` takes `clip foo` but not `clipboard`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<String>,
+    /// Queries routed by a trigger word reach the plugin with the word left in, for trigger
+    /// words that are commands themselves, like `next`.
+    #[serde(default)]
+    pub keep_triggers: bool,
+    /// Only search this plugin through its prefix or triggers, leaving it out of queries for
+    /// everyone.
+    #[serde(default)]
+    pub prefix_only: bool,
+    /// Matches may hold secrets, such as clipboard contents, and are never written to disk.
+    #[serde(default)]
+    pub sensitive: bool,
+    /// Matches are only shown once the search completed without a good match from another
+    /// plugin, like offers to search the web for the query.
+    #[serde(default)]
+    pub fallback: bool,
+    /// Filled in by `run_plugin`. 0 for plugins predating protocol versions.
+    #[serde(default)]
+    pub protocol_version: u32,
+    /// Filled in by `run_plugin`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capabilities: Vec<Capability>,
+    /// Experimental subsystems the plugin has on, filled in by `run_plugin` from
+    /// [`Plugin::features`]. Plugins predating them get the defaults.
+    #[serde(default)]
+    pub features: Features,
+    /// Checked by daemons supporting `Capability::Permissions`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub permissions: Vec<Permission>,
+    /// Directories `Permission::HomeRead` is limited to, `~` standing for the home
+    /// directory. Only files in the home directory may be opened while it is empty. The
+    /// directories of a `roots` setting in `config_schema` count as declared.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    /// Listed in the command palette. Running one sends the plugin a callback by its key.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<PaletteCommand>,
+    /// Ids of plugins this one works best with once they are up, e.g. the indexer a file
+    /// search reads. The daemon holds back searches until they authenticate, for a while.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+}
+
+impl Metadata {
+    pub fn supports(&self, capability: Capability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+}
+
+/// Something a plugin does on request rather than for a match, e.g. clearing its history.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct PaletteCommand {
+    /// Callback key the plugin receives when the command runs.
+    pub key: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<Icon>,
+}
+
+impl PaletteCommand {
+    pub fn new(key: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            title: title.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+}
+
+/// Metadata declared with `#[derive(GlimpsePlugin)]` and `#[glimpse(...)]` attributes:
+///
+/// ```ignore
+/// #[derive(GlimpsePlugin)]
+/// #[glimpse(id = "me.aresa.glimpse.ssh", name = "SSH", prefix = "ssh ", triggers("ssh"))]
+/// #[glimpse(permissions(Clipboard, Exec))]
+/// struct SshPlugin;
+///
+/// impl Plugin for SshPlugin {
+///     fn metadata(&self) -> Metadata {
+///         Self::plugin_metadata()
+///     }
+/// }
+/// ```
+///
+/// `name` defaults to the type's name, `version`, `description` and `author` to the
+/// package's. `prefix_only`, `sensitive` and `fallback` set the flags of the same name. Fields without
+/// an attribute, such as the config schema, are added over it.
+pub trait PluginMetadata {
+    fn plugin_metadata() -> Metadata;
 }
 
 #[async_trait]
@@ -32,7 +231,9 @@ pub trait Plugin: Send + Sync + 'static {
             Method::Search(query) => {
                 let results = self.handle_search(query).await;
                 match results {
-                    Err(e) => Ok(MethodResult::Error(e.to_string())),
+                    Err(e) => Ok(MethodResult::Error {
+                        message: e.to_string(),
+                    }),
                     Ok(results) => Ok(MethodResult::Matches { items: results }),
                 }
             }
@@ -40,17 +241,305 @@ pub trait Plugin: Send + Sync + 'static {
                 self.handle_action(action, params).await;
                 Ok(MethodResult::None)
             }
+            Method::Configure(config) => {
+                self.configure(config).await?;
+                Ok(MethodResult::None)
+            }
+            Method::ConfigChanged(config) => {
+                self.config_changed(config).await?;
+                Ok(MethodResult::None)
+            }
+            Method::PowerProfile(profile) => {
+                self.power_profile_changed(profile).await?;
+                Ok(MethodResult::None)
+            }
             _ => Ok(MethodResult::None),
         }
     }
 
+    /// Receive settings validated against `Metadata::config_schema`.
+    async fn configure(&self, config: serde_json::Value) -> Result<(), PluginError> {
+        tracing::debug!("ignoring configuration: {}", config);
+        Ok(())
+    }
+
+    /// Receive settings changed while the plugin is running. Applies them like `configure`
+    /// unless overridden.
+    async fn config_changed(&self, config: serde_json::Value) -> Result<(), PluginError> {
+        self.configure(config).await
+    }
+
+    /// The daemon switched power profiles. Plugins doing background work, such as indexing,
+    /// should pause it in `PowerProfile::LowPower`. Plugins start out in `PowerProfile::Normal`.
+    async fn power_profile_changed(&self, _profile: PowerProfile) -> Result<(), PluginError> {
+        Ok(())
+    }
+
+    /// Stream matches for `query` through `sink`; returning ends this plugin's part of the search.
+    ///
+    /// The default sends everything `handle` returns as one chunk. Slow providers override this
+    /// to send results as soon as they have them, sizing their work by `sink.deadline()`: the
+    /// search is ended when it passes, keeping the chunks sent before.
+    async fn search(&self, query: String, sink: &SearchSink) -> Result<(), PluginError> {
+        match self.handle(Method::Search(query)).await? {
+            MethodResult::Matches { items } => sink.send(items).await,
+            MethodResult::Error { message } => Err(PluginError::Other(message)),
+            _ => Ok(()),
+        }
+    }
+
     async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError>;
 
+    /// Size of the first page of a search for plugins serving pages themselves, such as ones
+    /// with large indexes. Searches then go to `search_page` and the plugin announces
+    /// `Capability::Paginated`. `None`, the default, answers searches with every match.
+    fn page_size(&self) -> Option<usize> {
+        None
+    }
+
+    /// Stream matches `offset..offset + limit` of `query` through `sink`, best first.
+    ///
+    /// The default slices what `handle_search` returns, plugins override this to look up the
+    /// page alone.
+    async fn search_page(
+        &self,
+        query: String,
+        offset: usize,
+        limit: usize,
+        sink: &SearchSink,
+    ) -> Result<(), PluginError> {
+        let items = self.handle_search(query).await?;
+        sink.send(items.into_iter().skip(offset).take(limit).collect())
+            .await
+    }
+
+    /// Whether matches are sent without their detail, which is looked up with `details` once
+    /// a client shows it. Plugins with costly details, such as file previews, announce
+    /// `Capability::Details` with it. `false` by default.
+    fn lazy_details(&self) -> bool {
+        false
+    }
+
+    /// Experimental subsystems the plugin turns on, those the SDK was not compiled with
+    /// stay off. [`Features::default`] unless overridden.
+    fn features(&self) -> Features {
+        Features::default()
+    }
+
+    /// The detail of `item`, a match this plugin sent. The default keeps the one it came with.
+    async fn details(&self, item: Match) -> Result<Option<Detail>, PluginError> {
+        Ok(item.detail)
+    }
+
     async fn handle_action(&self, action: String, params: HashMap<String, String>) {
         tracing::warn!("unhandled action: {} {:?}", action, params);
     }
+
+    /// Run a callback action, reporting progress of slow work through `progress`. The future
+    /// is dropped when the user cancels the action.
+    ///
+    /// The default hands the action to `handle_action`.
+    async fn call_action(
+        &self,
+        action: String,
+        params: HashMap<String, String>,
+        _progress: &Progress,
+    ) -> Result<(), PluginError> {
+        self.handle_action(action, params).await;
+        Ok(())
+    }
+
+    /// Push updates for `topic` until the publisher is closed.
+    async fn subscribe(&self, topic: String, _publisher: Publisher) -> Result<(), PluginError> {
+        Err(PluginError::Other(format!("unknown topic: {}", topic)))
+    }
+
+    /// Requests `run_plugin` runs at once, later ones wait for a running one to finish.
+    /// Subscriptions and notifications are not counted.
+    fn max_concurrent_requests(&self) -> usize {
+        DEFAULT_CONCURRENT_REQUESTS
+    }
 }
 
+/// Sends chunks of matches for one search request.
+#[derive(Clone)]
+pub struct SearchSink {
+    id: usize,
+    plugin_id: String,
+    tx: mpsc::Sender<Message>,
+    deadline: Deadline,
+}
+
+impl SearchSink {
+    /// Responses are written to `tx`, which lets tests collect them without stdio.
+    pub fn new(id: usize, plugin_id: String, tx: mpsc::Sender<Message>) -> Self {
+        Self {
+            id,
+            plugin_id,
+            tx,
+            deadline: Deadline::none(),
+        }
+    }
+
+    pub fn with_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    pub fn request_id(&self) -> usize {
+        self.id
+    }
+
+    /// When the daemon stops waiting for the search.
+    pub fn deadline(&self) -> Deadline {
+        self.deadline
+    }
+
+    pub async fn send(&self, items: Vec<Match>) -> Result<(), PluginError> {
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        let message = Message::Response {
+            id: self.id,
+            error: None,
+            result: Some(MethodResult::Matches { items }),
+            plugin_id: Some(self.plugin_id.clone()),
+        };
+        self.tx
+            .send(message)
+            .await
+            .map_err(|e| PluginError::Other(e.to_string()))
+    }
+}
+
+/// Reports progress of one callback action to the client that started it.
+#[derive(Clone)]
+pub struct Progress {
+    id: usize,
+    plugin_id: String,
+    tx: mpsc::Sender<Message>,
+}
+
+impl Progress {
+    /// Reports are written to `tx`, which lets tests collect them without stdio.
+    pub fn new(id: usize, plugin_id: String, tx: mpsc::Sender<Message>) -> Self {
+        Self { id, plugin_id, tx }
+    }
+
+    pub fn action_id(&self) -> usize {
+        self.id
+    }
+
+    /// Report `pct` percent done, values over 100 are capped.
+    pub async fn report(&self, pct: u8, message: impl Into<String>) -> Result<(), PluginError> {
+        let message = Message::Notification {
+            method: Method::ActionProgress(ActionProgress {
+                action_id: self.id,
+                pct: pct.min(100),
+                message: message.into(),
+            }),
+            plugin_id: Some(self.plugin_id.clone()),
+        };
+        self.tx
+            .send(message)
+            .await
+            .map_err(|e| PluginError::Other(e.to_string()))
+    }
+
+    /// Have the daemon carry out `action`, with the permissions the plugin declared.
+    pub async fn run(&self, action: Action) -> Result<(), PluginError> {
+        let message = Message::Notification {
+            method: Method::RunAction(action),
+            plugin_id: Some(self.plugin_id.clone()),
+        };
+        self.tx
+            .send(message)
+            .await
+            .map_err(|e| PluginError::Other(e.to_string()))
+    }
+}
+
+/// Sends updates of one subscribed topic. Closed when the client unsubscribes.
+#[derive(Clone)]
+pub struct Publisher {
+    id: usize,
+    plugin_id: String,
+    topic: String,
+    tx: mpsc::Sender<Message>,
+    token: CancellationToken,
+}
+
+impl Publisher {
+    pub(crate) fn new(
+        id: usize,
+        plugin_id: String,
+        topic: String,
+        tx: mpsc::Sender<Message>,
+        token: CancellationToken,
+    ) -> Self {
+        Self {
+            id,
+            plugin_id,
+            topic,
+            tx,
+            token,
+        }
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    pub async fn publish(&self, items: Vec<Match>) -> Result<(), PluginError> {
+        if self.is_closed() {
+            return Err(PluginError::Cancelled(format!(
+                "{} unsubscribed",
+                self.topic
+            )));
+        }
+
+        let message = Message::Response {
+            id: self.id,
+            error: None,
+            result: Some(MethodResult::Update {
+                topic: self.topic.clone(),
+                items,
+            }),
+            plugin_id: Some(self.plugin_id.clone()),
+        };
+        self.tx
+            .send(message)
+            .await
+            .map_err(|e| PluginError::Other(e.to_string()))
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Resolves once the subscription ends.
+    pub async fn closed(&self) {
+        self.token.cancelled().await
+    }
+}
+
+#[derive(Clone)]
 pub struct Context {
     pub config_dir: PathBuf,
+    pub(crate) tx: mpsc::Sender<Message>,
+}
+
+impl Context {
+    /// Ask the daemon to send the current settings again with `Method::Configure`.
+    pub async fn request_config(&self) -> Result<(), PluginError> {
+        let message = Message::Notification {
+            method: Method::GetConfig,
+            plugin_id: None,
+        };
+        self.tx
+            .send(message)
+            .await
+            .map_err(|e| PluginError::Other(e.to_string()))
+    }
 }
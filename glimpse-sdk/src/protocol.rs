@@ -1,50 +1,545 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
 
 use serde::{Deserialize, Serialize};
 
-use crate::Metadata;
+use crate::{Features, LogRecord, Metadata, Permission, Sensitive};
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(tag = "method", content = "params", rename_all = "snake_case")]
 pub enum Method {
     Search(String),
-    Activate(usize, usize),                      // match index, action index
+    /// Matches `offset..offset + limit` of search `request_id`. Clients get the first page of
+    /// a search and ask for the following ones with it. The daemon sends it, under the id of
+    /// the search, to plugins with `Capability::Paginated` and serves the pages of other
+    /// plugins from the matches it holds.
+    More {
+        request_id: usize,
+        offset: usize,
+        limit: usize,
+    },
+    Activate {
+        generation: usize, // search request id the match belongs to
+        match_id: usize,
+        action: usize, // action index
+        #[serde(default)]
+        modifiers: Modifiers, // keys held while activating
+        /// Set when the user confirmed an action that requires it, see
+        /// `MatchAction::requires_confirmation`.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        confirmed: bool,
+    },
+    /// The detail of a match, answered with `Details`. Clients ask for the selected match,
+    /// the daemon answers from the match or asks its plugin with `Describe`.
+    Details {
+        generation: usize,
+        match_id: usize,
+    },
+    /// Sent by clients as a notification as the user scrolls: rows `first_index` to
+    /// `last_index` of the ranked matches of search `generation` are on screen. The daemon
+    /// fetches their details and renders their thumbnails ahead of the rest, and sends
+    /// `DetailReady` for each detail a plugin described.
+    Viewport {
+        generation: usize,
+        first_index: usize,
+        last_index: usize,
+    },
+    /// Sent by the daemon to clients once a plugin described a match announced with
+    /// `Viewport`, later `Details` requests for it are answered with `detail` right away.
+    DetailReady {
+        generation: usize,
+        match_id: usize,
+        detail: Detail,
+    },
+    /// Sent by the daemon to plugins with `Capability::Details` for the detail of a match
+    /// they sent without one, answered with `Details`.
+    Describe(Box<Match>),
+    /// Sent by the daemon to run a callback action. As a request its progress is reported
+    /// with `ActionProgress` until the plugin answers.
     CallAction(String, HashMap<String, String>), // action key
-    Cancel,
+    /// Progress of a running callback action. Plugins report it under the id of the
+    /// `CallAction` request, clients receive it under the id of their `Activate` request.
+    ActionProgress(ActionProgress),
+    /// Sent by clients to stop a running callback action, `action_id` is the id of the
+    /// `Activate` request that started it.
+    CancelAction {
+        action_id: usize,
+    },
+    /// Sent by a plugin while running a callback action to have the daemon carry out
+    /// `Action`, checked like the actions of its matches. Lets plugins fetch what a match
+    /// needs, such as a password, only once it is activated.
+    RunAction(Action),
+    Subscribe {
+        plugin_id: String,
+        topic: String,
+    },
+    Unsubscribe {
+        plugin_id: String,
+        topic: String,
+    },
+    /// Validated plugin settings, sent by the daemon on startup and whenever the file changes.
+    Configure(serde_json::Value),
+    /// Settings re-validated after the file changed while the plugin was running.
+    ConfigChanged(serde_json::Value),
+    /// Sent by a plugin to have the daemon deliver its current settings with `Configure`.
+    GetConfig,
+    /// Most frecent previously activated matches, answered by the daemon with `History`.
+    History {
+        limit: usize,
+    },
+    /// Sent by the daemon to clients when plugins appear in or disappear from the plugin
+    /// directories. Lists metadata ids.
+    PluginsChanged {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    /// Sent by clients once the user allowed a plugin a sensitive permission, after an
+    /// activation failed with `RpcError::PERMISSION_REQUIRED`. Grants are kept across restarts.
+    GrantPermission {
+        plugin_id: String,
+        permission: Permission,
+    },
+    /// The last completed search, answered by the daemon with `LastResults` while it is
+    /// fresh. Lets a client restarted after a crash show what the user last saw.
+    LastResults,
+    /// Newer releases of the daemon and installed plugins, answered with `Updates`.
+    /// `check` fetches the release manifest now instead of returning the last known result.
+    Updates {
+        #[serde(default)]
+        check: bool,
+    },
+    /// Metadata of the plugins that authenticated, answered by the daemon with `Plugins`.
+    Plugins,
+    /// Plugin executables the daemon holds back until the user trusts them, answered with
+    /// `UntrustedPlugins`.
+    UntrustedPlugins,
+    /// Sent by the daemon to clients when it finds plugin executables it does not trust, and
+    /// to clients connecting while there are any. They do not run until trusted.
+    PluginsHeldBack {
+        items: Vec<UntrustedPlugin>,
+    },
+    /// Sent by clients once the user trusted the executable at `path`. The daemon pins
+    /// `sha256`, the digest the user was shown, and starts the plugin if it still matches.
+    TrustPlugin {
+        path: String,
+        sha256: String,
+    },
+    /// How each plugin served searches since statistics were first kept, answered by the
+    /// daemon with `PluginStats`.
+    PluginStats,
+    /// Search latency, result counts, failures and restarts of every plugin since the daemon
+    /// started, answered with `Stats`.
+    Stats,
+    /// The experimental subsystems the daemon has on and those negotiated with each plugin,
+    /// answered with `Features`.
+    Features,
+    /// What the plugin with this metadata id or key logged at or after `since`, unix
+    /// milliseconds, answered by the daemon with `Logs`. `glimpsed` names the daemon itself.
+    GetLogs {
+        plugin_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        since: Option<u64>,
+    },
+    /// Sent by the daemon to clients once the thumbnail of the file at `path` is rendered:
+    /// matches with its `Icon::Thumbnail` show `icon` instead.
+    ThumbnailReady {
+        path: String,
+        icon: Icon,
+    },
+    /// Sent by the daemon to plugins and clients when it switches power profiles.
+    PowerProfile(PowerProfile),
+    /// Sent by the daemon to plugins with `Capability::Compression`: messages longer than
+    /// `threshold` bytes may be sent compressed from now on.
+    EnableCompression {
+        threshold: usize,
+    },
+    /// Cancels the plugin's request with the given id. Without one, a request cancels the
+    /// request with its own id and a notification cancels everything the plugin is working on.
+    /// Plugins announce understanding ids with `Capability::TargetedCancel`.
+    Cancel(Option<usize>),
     Quit,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum MethodResult {
-    Authenticate(Metadata),
-    Matches { items: Vec<Match> },
-    Error(String),
+    Authenticate(Box<Metadata>),
+    Matches {
+        items: Vec<Match>,
+    },
+    /// Terminates a search response stream; any number of `Matches` chunks may precede it.
+    /// `truncated` when the plugin ran out of the request's deadline and the matches sent are
+    /// partial.
+    Done {
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        truncated: bool,
+    },
+    Snapshot {
+        items: Vec<SnapshotItem>,
+    },
+    /// Pushed by a plugin for a subscribed topic, replaces the previous update.
+    Update {
+        topic: String,
+        items: Vec<Match>,
+    },
+    History {
+        items: Vec<HistoryEntry>,
+    },
+    /// Matches of the last completed search, ranked, activatable under the id of the
+    /// `LastResults` request. `None` answers when there is no fresh snapshot.
+    LastResults {
+        query: String,
+        items: Vec<Match>,
+    },
+    /// `None` when the match has no detail.
+    Details {
+        detail: Option<Detail>,
+    },
+    Updates {
+        items: Vec<AvailableUpdate>,
+    },
+    Plugins {
+        items: Vec<Metadata>,
+    },
+    UntrustedPlugins {
+        items: Vec<UntrustedPlugin>,
+    },
+    /// Answers the activation of an `Action::Sequence`: how each of its steps went, in the
+    /// order of `Action::steps`.
+    Sequence {
+        steps: Vec<StepStatus>,
+    },
+    /// Busiest plugins first.
+    PluginStats {
+        items: Vec<PluginStats>,
+    },
+    /// Plugins in order of their metadata ids.
+    Stats {
+        items: Vec<PluginMetrics>,
+    },
+    /// `plugins` by metadata id, with what each has on in common with the daemon.
+    Features {
+        daemon: Features,
+        plugins: BTreeMap<String, Features>,
+    },
+    /// Oldest first.
+    Logs {
+        items: Vec<LogRecord>,
+    },
+    Error {
+        message: String,
+    },
     None,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-#[serde(untagged)]
+/// Value of the `jsonrpc` member of every message.
+pub const JSONRPC_VERSION: &str = "2.0";
+
+/// A JSON-RPC 2.0 message. Written with `"jsonrpc": "2.0"`; messages of peers predating
+/// the member are accepted without it.
+#[derive(Debug, Clone, PartialEq)]
 pub enum Message {
     Request {
+        id: usize,
+        method: Method,
+        plugin_id: Option<String>,
+        /// Milliseconds the sender waits for the answer. A hint for plugins to size their
+        /// work by, sent with searches; peers predating it leave it out and ignore it.
+        deadline_ms: Option<u64>,
+    },
+    Response {
+        id: usize,
+        error: Option<RpcError>,
+        result: Option<MethodResult>,
+        plugin_id: Option<String>,
+    },
+    Notification {
+        method: Method,
+        plugin_id: Option<String>,
+    },
+}
+
+/// A JSON-RPC 2.0 error object.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+impl RpcError {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+    /// A plugin failed to answer, or went away before it did.
+    pub const PLUGIN_ERROR: i64 = -32000;
+    /// The daemon refused the request: an unknown plugin, a stale match or the policy.
+    pub const REJECTED: i64 = -32001;
+    /// The activation needs a sensitive permission the user has not granted yet. `data`
+    /// holds the `plugin_id` and `permission` to ask about.
+    pub const PERMISSION_REQUIRED: i64 = -32002;
+    /// The action requires confirmation, clients ask the user the `prompt` in `data` and
+    /// send the `Activate` again with `confirmed` set.
+    pub const CONFIRMATION_REQUIRED: i64 = -32003;
+
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(Self::INTERNAL_ERROR, message)
+    }
+
+    pub fn plugin(message: impl Into<String>) -> Self {
+        Self::new(Self::PLUGIN_ERROR, message)
+    }
+
+    pub fn rejected(message: impl Into<String>) -> Self {
+        Self::new(Self::REJECTED, message)
+    }
+
+    /// Refuses an unconfirmed activation of `action`.
+    pub fn confirmation_required(action: &MatchAction) -> Self {
+        Self {
+            data: Some(serde_json::json!({ "prompt": action.prompt() })),
+            ..Self::new(
+                Self::CONFIRMATION_REQUIRED,
+                format!("{:?} needs confirmation", action.title),
+            )
+        }
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+impl std::error::Error for RpcError {}
+
+impl<'de> Deserialize<'de> for RpcError {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Object {
+                code: i64,
+                message: String,
+                #[serde(default)]
+                data: Option<serde_json::Value>,
+            },
+            // peers predating error objects
+            Message(String),
+        }
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Object {
+                code,
+                message,
+                data,
+            } => RpcError {
+                code,
+                message,
+                data,
+            },
+            Repr::Message(message) => RpcError::internal(message),
+        })
+    }
+}
+
+/// The `jsonrpc` member, refusing versions other than 2.0.
+#[derive(Debug, Clone, Copy)]
+struct Version;
+
+impl Serialize for Version {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(JSONRPC_VERSION)
+    }
+}
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let version = String::deserialize(deserializer)?;
+        if version != JSONRPC_VERSION {
+            return Err(serde::de::Error::custom(format!(
+                "unsupported jsonrpc version {:?}",
+                version
+            )));
+        }
+        Ok(Version)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum MessageRef<'a> {
+    Request {
+        jsonrpc: Version,
+        id: usize,
+        #[serde(flatten)]
+        method: &'a Method,
+        plugin_id: &'a Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        deadline_ms: &'a Option<u64>,
+    },
+    Response {
+        jsonrpc: Version,
+        id: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: &'a Option<RpcError>,
+        // a response carries either a result or an error
+        #[serde(skip_serializing_if = "Option::is_none")]
+        result: Option<&'a Option<MethodResult>>,
+        plugin_id: &'a Option<String>,
+    },
+    Notification {
+        jsonrpc: Version,
+        #[serde(flatten)]
+        method: &'a Method,
+        plugin_id: &'a Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MessageRepr {
+    Request {
+        // only validated
+        #[serde(default, rename = "jsonrpc")]
+        _jsonrpc: Option<Version>,
         id: usize,
         #[serde(flatten)]
         method: Method,
         plugin_id: Option<String>,
+        #[serde(default)]
+        deadline_ms: Option<u64>,
     },
     Response {
+        // only validated
+        #[serde(default, rename = "jsonrpc")]
+        _jsonrpc: Option<Version>,
         id: usize,
-        error: Option<String>,
+        #[serde(default)]
+        error: Option<RpcError>,
+        #[serde(default)]
         result: Option<MethodResult>,
         plugin_id: Option<String>,
     },
     Notification {
+        // only validated
+        #[serde(default, rename = "jsonrpc")]
+        _jsonrpc: Option<Version>,
         #[serde(flatten)]
         method: Method,
         plugin_id: Option<String>,
     },
 }
 
+impl Serialize for Message {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let message = match self {
+            Message::Request {
+                id,
+                method,
+                plugin_id,
+                deadline_ms,
+            } => MessageRef::Request {
+                jsonrpc: Version,
+                id: *id,
+                method,
+                plugin_id,
+                deadline_ms,
+            },
+            Message::Response {
+                id,
+                error,
+                result,
+                plugin_id,
+            } => MessageRef::Response {
+                jsonrpc: Version,
+                id: *id,
+                error,
+                result: (error.is_none() || result.is_some()).then_some(result),
+                plugin_id,
+            },
+            Message::Notification { method, plugin_id } => MessageRef::Notification {
+                jsonrpc: Version,
+                method,
+                plugin_id,
+            },
+        };
+        message.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Message {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match MessageRepr::deserialize(deserializer)? {
+            MessageRepr::Request {
+                id,
+                method,
+                plugin_id,
+                deadline_ms,
+                ..
+            } => Message::Request {
+                id,
+                method,
+                plugin_id,
+                deadline_ms,
+            },
+            MessageRepr::Response {
+                id,
+                error,
+                result,
+                plugin_id,
+                ..
+            } => Message::Response {
+                id,
+                error,
+                result,
+                plugin_id,
+            },
+            MessageRepr::Notification {
+                method, plugin_id, ..
+            } => Message::Notification { method, plugin_id },
+        })
+    }
+}
+
+/// One line of the protocol: a message or a JSON-RPC batch of them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Frame {
+    Single(Box<Message>),
+    Batch(Vec<Message>),
+}
+
+impl Frame {
+    /// Parse a line, refusing empty batches as JSON-RPC does.
+    pub fn parse(line: &str) -> Result<Self, serde_json::Error> {
+        let frame: Frame = serde_json::from_str(line)?;
+        if matches!(&frame, Frame::Batch(messages) if messages.is_empty()) {
+            return Err(serde::de::Error::custom("empty batch"));
+        }
+        Ok(frame)
+    }
+
+    pub fn into_messages(self) -> Vec<Message> {
+        match self {
+            Frame::Single(message) => vec![*message],
+            Frame::Batch(messages) => messages,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Action {
@@ -59,13 +554,218 @@ pub enum Action {
     Open {
         uri: String,
     },
+    /// The text may be a secret, it stays out of logs and is wiped once dropped.
     Clipboard {
-        text: String,
+        text: Sensitive<String>,
+    },
+    /// Copies like `Clipboard`, then clears the clipboard after `clear_after_secs` unless
+    /// something else was copied since. Meant for passwords and other credentials.
+    ExpiringClipboard {
+        text: Sensitive<String>,
+        clear_after_secs: u64,
+    },
+    /// Types `text` into the window focused once the launcher closed, as if on the keyboard.
+    /// The text may be a secret, like that of `Clipboard`.
+    TypeText {
+        text: Sensitive<String>,
     },
     Callback {
         key: String,
         params: HashMap<String, String>,
     },
+    /// Has the daemon show a desktop notification after `after_secs`, kept across restarts
+    /// of the daemon. `open_launcher` brings the launcher up along with it.
+    Schedule {
+        after_secs: u64,
+        title: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        body: Option<String>,
+        #[serde(default)]
+        open_launcher: bool,
+    },
+    /// Has the daemon shut down, reboot or suspend the machine, lock the screen or end the
+    /// session through systemd-logind.
+    System {
+        command: SystemCommand,
+    },
+    /// Has the daemon connect or disconnect a paired Bluetooth device through BlueZ, or a
+    /// saved network through NetworkManager.
+    Connection {
+        target: ConnectionTarget,
+        connect: bool,
+    },
+    /// Runs the actions in order, stopping at the first that fails. The daemon answers the
+    /// activation with `MethodResult::Sequence`. Callbacks count as done once the plugin
+    /// was sent them.
+    Sequence {
+        actions: Vec<Action>,
+    },
+}
+
+/// What an `Action::System` asks of systemd-logind.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemCommand {
+    PowerOff,
+    Reboot,
+    Suspend,
+    LockScreen,
+    LogOut,
+}
+
+/// What an `Action::Connection` connects or disconnects.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConnectionTarget {
+    /// A paired Bluetooth device by its address, like `00:1A:7D:DA:71:13`.
+    Bluetooth { address: String },
+    /// A saved NetworkManager connection by its UUID.
+    Network { uuid: String },
+}
+
+/// Most steps a sequence runs, nested sequences flattened.
+pub const MAX_SEQUENCE_STEPS: usize = 16;
+
+/// Most sequences nested in one another, the outermost included.
+pub const MAX_SEQUENCE_DEPTH: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SequenceError {
+    Empty,
+    TooManySteps { steps: usize },
+    TooDeep,
+}
+
+impl std::fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SequenceError::Empty => write!(f, "sequence has no steps"),
+            SequenceError::TooManySteps { steps } => write!(
+                f,
+                "sequence has {} steps, at most {} are run",
+                steps, MAX_SEQUENCE_STEPS
+            ),
+            SequenceError::TooDeep => write!(
+                f,
+                "sequences are nested more than {} deep",
+                MAX_SEQUENCE_DEPTH
+            ),
+        }
+    }
+}
+impl std::error::Error for SequenceError {}
+
+impl Action {
+    /// The actions an activation runs in order: the steps of a sequence with nested sequences
+    /// flattened, or the action itself.
+    pub fn steps(&self) -> Result<Vec<&Action>, SequenceError> {
+        let mut steps = vec![];
+        collect_steps(self, 0, &mut steps)?;
+        if steps.is_empty() {
+            return Err(SequenceError::Empty);
+        }
+        if steps.len() > MAX_SEQUENCE_STEPS {
+            return Err(SequenceError::TooManySteps { steps: steps.len() });
+        }
+        Ok(steps)
+    }
+
+    /// Open the local file or directory at the absolute `path`.
+    pub fn open_file(path: impl AsRef<Path>) -> Self {
+        Action::Open {
+            uri: file_uri(path.as_ref()),
+        }
+    }
+}
+
+/// The `file://` URI of the absolute `path`. Bytes that may not appear in a URI path, such
+/// as spaces, `#` and `%`, are percent-encoded.
+pub fn file_uri(path: &Path) -> String {
+    #[cfg(unix)]
+    let bytes = std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str()).to_vec();
+    #[cfg(not(unix))]
+    let bytes = path.to_string_lossy().replace('\\', "/").into_bytes();
+
+    let mut uri = String::from("file://");
+    if bytes.first() != Some(&b'/') {
+        uri.push('/');
+    }
+    for byte in bytes {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                uri.push(byte as char)
+            }
+            byte => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+fn collect_steps<'a>(
+    action: &'a Action,
+    depth: usize,
+    steps: &mut Vec<&'a Action>,
+) -> Result<(), SequenceError> {
+    let Action::Sequence { actions } = action else {
+        steps.push(action);
+        return Ok(());
+    };
+    if depth == MAX_SEQUENCE_DEPTH {
+        return Err(SequenceError::TooDeep);
+    }
+    actions
+        .iter()
+        .try_for_each(|action| collect_steps(action, depth + 1, steps))
+}
+
+/// How a step of an `Action::Sequence` went.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum StepStatus {
+    Done,
+    Failed {
+        message: String,
+    },
+    /// Not run, an earlier step failed.
+    Skipped,
+}
+
+/// Action fields the daemon expands before running them: a leading `~`, `$NAME` and
+/// `${NAME}` from the user and XDG directory variables of its environment, XDG directories
+/// defaulted as in the base directory specification, and `$$` for a literal `$`. Unknown
+/// variables, and any others of the daemon's environment, refuse the activation.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpandField {
+    /// The program of `Action::Exec`.
+    Command,
+    /// Each argument of `Action::Exec`.
+    Args,
+    /// The URI of `Action::Open`, e.g. `file://~/Downloads`.
+    Uri,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(default)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    pub fn is_empty(&self) -> bool {
+        *self == Modifiers::default()
+    }
+}
+
+/// Variant of a [`MatchAction`] triggered when the user holds `modifiers` on activation,
+/// e.g. "open in new window" on Shift+Enter.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AlternateAction {
+    pub modifiers: Modifiers,
+    pub title: String,
+    pub action: Action,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -74,14 +774,312 @@ pub struct MatchAction {
     pub title: String,
     pub action: Action,
     pub close_on_action: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternates: Vec<AlternateAction>,
+    /// Destructive actions, e.g. deleting a file, only run once the user confirmed them.
+    /// The daemon refuses unconfirmed activations with `RpcError::CONFIRMATION_REQUIRED`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub requires_confirmation: bool,
+    /// Question clients ask before running the action, a generic one when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirmation_prompt: Option<String>,
+    /// Fields of the action and its alternates to expand, none unless the plugin opts in.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expand: Vec<ExpandField>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+impl MatchAction {
+    /// The action to run for the given modifier state, falling back to the default action.
+    pub fn action_for(&self, modifiers: &Modifiers) -> &Action {
+        if modifiers.is_empty() {
+            return &self.action;
+        }
+        self.alternates
+            .iter()
+            .find(|alternate| alternate.modifiers == *modifiers)
+            .map(|alternate| &alternate.action)
+            .unwrap_or(&self.action)
+    }
+
+    /// The question to confirm the action with.
+    pub fn prompt(&self) -> String {
+        self.confirmation_prompt
+            .clone()
+            .unwrap_or_else(|| format!("{}?", self.title))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub struct Match {
+    /// Assigned by the daemon, plugins leave it empty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<usize>,
     pub title: String,
     pub description: String,
-    pub icon: Option<String>,
+    pub icon: Option<Icon>,
     pub actions: Vec<MatchAction>,
     pub score: f64,
+    /// Longer content clients show beside the results while the match is selected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<Detail>,
+    /// Section clients group the match under, e.g. `Apps` or `Files`. The daemon fills in
+    /// the plugin's name when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Parts of the title and description that matched the query, for clients to highlight.
+    /// The daemon works them out for plugins that leave them unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlights: Option<Highlights>,
+}
+
+/// Ranges of characters, counted in Unicode scalar values from `start` up to but excluding
+/// `end`, written `[start, end]`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Highlights {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub title: Vec<(usize, usize)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub description: Vec<(usize, usize)>,
+}
+
+/// Where the icon of a match comes from. The daemon resolves icons before matches reach
+/// clients: they get a `Path` to an image they can load as is, small images inline as `Data`,
+/// an `Emoji` to draw, or a `Thumbnail` still being rendered.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Icon {
+    /// An icon of the freedesktop icon theme, e.g. `firefox`.
+    Freedesktop {
+        name: String,
+    },
+    /// A local image, absolute or as a `file://` URI.
+    Path {
+        path: String,
+    },
+    Emoji {
+        emoji: String,
+    },
+    /// A thumbnail of the image or video at `path`. Clients get it while the daemon renders
+    /// the thumbnail, to be replaced with the icon of `ThumbnailReady`.
+    Thumbnail {
+        path: String,
+    },
+    /// An image the plugin ships in the `icons/` folder next to its executable, by file name,
+    /// e.g. `trash.svg`. The extension may be left out for SVG and PNG icons.
+    Bundled {
+        name: String,
+    },
+    /// An image sent inline, base64 encoded.
+    Data {
+        mime: String,
+        data: String,
+    },
+}
+
+impl Icon {
+    pub fn freedesktop(name: impl Into<String>) -> Self {
+        Icon::Freedesktop { name: name.into() }
+    }
+
+    pub fn path(path: impl Into<String>) -> Self {
+        Icon::Path { path: path.into() }
+    }
+
+    pub fn emoji(emoji: impl Into<String>) -> Self {
+        Icon::Emoji {
+            emoji: emoji.into(),
+        }
+    }
+
+    pub fn thumbnail(path: impl Into<String>) -> Self {
+        Icon::Thumbnail { path: path.into() }
+    }
+
+    pub fn bundled(name: impl Into<String>) -> Self {
+        Icon::Bundled { name: name.into() }
+    }
+
+    pub fn data(mime: impl Into<String>, data: impl Into<String>) -> Self {
+        Icon::Data {
+            mime: mime.into(),
+            data: data.into(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Icon {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(tag = "type", rename_all = "snake_case")]
+        enum Tagged {
+            Freedesktop { name: String },
+            Path { path: String },
+            Emoji { emoji: String },
+            Thumbnail { path: String },
+            Bundled { name: String },
+            Data { mime: String, data: String },
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Tagged(Tagged),
+            // plugins predating icon objects send a path or a theme icon name
+            Plain(String),
+        }
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Tagged(Tagged::Freedesktop { name }) => Icon::Freedesktop { name },
+            Repr::Tagged(Tagged::Path { path }) => Icon::Path { path },
+            Repr::Tagged(Tagged::Emoji { emoji }) => Icon::Emoji { emoji },
+            Repr::Tagged(Tagged::Thumbnail { path }) => Icon::Thumbnail { path },
+            Repr::Tagged(Tagged::Bundled { name }) => Icon::Bundled { name },
+            Repr::Tagged(Tagged::Data { mime, data }) => Icon::Data { mime, data },
+            Repr::Plain(path) if path.starts_with('/') || path.starts_with("file://") => {
+                Icon::Path { path }
+            }
+            Repr::Plain(name) => Icon::Freedesktop { name },
+        })
+    }
+}
+
+/// How clients render [`Detail::content`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DetailFormat {
+    #[default]
+    Text,
+    Markdown,
+}
+
+/// The detail pane of a match, e.g. a worked calculation or a dictionary entry.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Detail {
+    #[serde(default)]
+    pub format: DetailFormat,
+    pub content: String,
+    /// Image shown above the content, a local path or `file://` URI.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+}
+
+impl Detail {
+    pub fn text(content: impl Into<String>) -> Self {
+        Self {
+            format: DetailFormat::Text,
+            content: content.into(),
+            image: None,
+        }
+    }
+
+    pub fn markdown(content: impl Into<String>) -> Self {
+        Self {
+            format: DetailFormat::Markdown,
+            content: content.into(),
+            image: None,
+        }
+    }
+}
+
+/// How far along a long-running action is.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ActionProgress {
+    pub action_id: usize,
+    /// Percent done, 0 to 100.
+    pub pct: u8,
+    pub message: String,
+}
+
+/// A match the user activated before, aggregated over all its activations.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub plugin_id: String,
+    pub title: String,
+    pub count: usize,
+    /// Unix timestamp of the latest activation, in seconds.
+    pub last_used: u64,
+    pub score: f64,
+}
+
+/// How much work the daemon, its plugins and clients should do in the background.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerProfile {
+    #[default]
+    Normal,
+    /// Running on a low battery: pause indexing and avoid speculative work.
+    LowPower,
+}
+
+/// A release newer than the installed version of the daemon or a plugin.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AvailableUpdate {
+    /// `glimpsed` for the daemon, the metadata id for plugins.
+    pub component: String,
+    pub installed: String,
+    pub latest: String,
+    /// Where to get the release, it is never installed automatically.
+    pub url: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// A plugin executable the daemon found but does not run, as it was never trusted or
+/// changed since.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UntrustedPlugin {
+    pub path: String,
+    /// SHA-256 digest of the executable, hex encoded.
+    pub sha256: String,
+    /// The executable was trusted with a different digest.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub changed: bool,
+}
+
+/// How a plugin served searches, summed across daemon restarts.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PluginStats {
+    pub plugin_id: String,
+    /// Searches the plugin was sent.
+    pub searches: u64,
+    /// Mean time to the plugin's last match of a search, over the searches it answered.
+    pub average_latency_ms: u64,
+    pub activations: u64,
+    /// Fraction of all activations that picked a match of this plugin.
+    pub activation_share: f64,
+    /// Searches the plugin answered with an error.
+    pub errors: u64,
+    /// Searches the plugin did not answer in time.
+    pub timeouts: u64,
+    /// Fraction of searches that failed or timed out.
+    pub error_rate: f64,
+}
+
+/// How a plugin served searches since the daemon started.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PluginMetrics {
+    pub plugin_id: String,
+    /// Searches the plugin was sent.
+    pub searches: u64,
+    /// Matches the plugin sent over all searches.
+    pub results: u64,
+    /// Searches the plugin answered with an error.
+    pub errors: u64,
+    /// Searches the plugin did not answer in time.
+    pub timeouts: u64,
+    /// Times the plugin came back after exiting.
+    pub restarts: u64,
+    /// Fraction of searches that failed or timed out.
+    pub error_rate: f64,
+    /// Mean time to the plugin's last match, over the searches it answered.
+    pub average_latency_ms: u64,
+    /// Upper bound of the latency bucket holding the 95th percentile.
+    pub p95_latency_ms: u64,
+    pub max_latency_ms: u64,
+}
+
+/// Final ordering of a completed search, sent by the daemon.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SnapshotItem {
+    pub id: usize,
+    pub score: f64,
 }
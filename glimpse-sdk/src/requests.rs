@@ -0,0 +1,172 @@
+use std::{
+    any::Any,
+    collections::{HashMap, VecDeque},
+    future::Future,
+    panic::AssertUnwindSafe,
+    sync::Arc,
+};
+
+use futures::FutureExt;
+use tokio::{
+    sync::{Semaphore, mpsc},
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{Message, MethodResult, PluginError, RpcError};
+
+/// Requests a plugin is working on, keyed by request id.
+///
+/// Each request runs in its own task and answers with a response carrying its id, so a slow
+/// search does not hold up the requests after it. At most `limit` requests run at once, the
+/// rest wait in their tasks and can be cancelled while waiting.
+pub struct RequestTable {
+    plugin_id: String,
+    response_tx: mpsc::Sender<Message>,
+    permits: Arc<Semaphore>,
+    running: HashMap<usize, (CancellationToken, JoinHandle<()>)>,
+}
+
+impl RequestTable {
+    /// A `limit` of 0 is taken as 1.
+    pub fn new(plugin_id: String, response_tx: mpsc::Sender<Message>, limit: usize) -> Self {
+        Self {
+            plugin_id,
+            response_tx,
+            permits: Arc::new(Semaphore::new(limit.max(1))),
+            running: HashMap::new(),
+        }
+    }
+
+    /// Run `request` as request `id`, cancelling a request still running under the same id.
+    pub fn start<F>(&mut self, id: usize, request: F)
+    where
+        F: Future<Output = Result<MethodResult, PluginError>> + Send + 'static,
+    {
+        self.running.retain(|_, (_, task)| !task.is_finished());
+        self.cancel(id);
+
+        let token = CancellationToken::new();
+        let cancelled = token.clone();
+        let permits = self.permits.clone();
+        let response_tx = self.response_tx.clone();
+        let plugin_id = self.plugin_id.clone();
+        let task = tokio::spawn(async move {
+            let result = tokio::select! {
+                result = async {
+                    let _permit = permits.acquire_owned().await;
+                    catch_panic(request).await
+                } => result,
+                _ = cancelled.cancelled() => {
+                    tracing::debug!("request {} was cancelled", id);
+                    Err(PluginError::Cancelled("request cancelled".into()))
+                },
+            };
+
+            let response = match result {
+                Ok(method_result) => Message::Response {
+                    id,
+                    error: None,
+                    plugin_id: Some(plugin_id),
+                    result: Some(method_result),
+                },
+                Err(err) => Message::Response {
+                    id,
+                    error: Some(RpcError::plugin(err.to_string())),
+                    plugin_id: Some(plugin_id),
+                    result: None,
+                },
+            };
+
+            if let Err(err) = response_tx.send(response).await {
+                tracing::warn!("error sending response: {}", err);
+            }
+        });
+        self.running.insert(id, (token, task));
+    }
+
+    /// Cancel request `id`, false if it is not running. The request answers with an error.
+    pub fn cancel(&mut self, id: usize) -> bool {
+        match self.running.remove(&id) {
+            Some((token, _)) => {
+                tracing::debug!("cancelling request {}", id);
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn cancel_all(&mut self) {
+        for (_, (token, _)) in self.running.drain() {
+            token.cancel();
+        }
+    }
+
+    /// Requests started and not yet finished, running or waiting for their turn.
+    pub fn len(&mut self) -> usize {
+        self.running.retain(|_, (_, task)| !task.is_finished());
+        self.running.len()
+    }
+
+    pub fn is_empty(&mut self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Queries of the latest searches by request id, so `More` requests naming a search can be
+/// served. Only the last [`RecentQueries::CAPACITY`] are kept, clients page the search they
+/// are showing.
+#[derive(Debug, Default)]
+pub struct RecentQueries {
+    queries: VecDeque<(usize, String)>,
+}
+
+impl RecentQueries {
+    pub const CAPACITY: usize = 16;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn remember(&mut self, id: usize, query: String) {
+        self.queries.retain(|(known, _)| *known != id);
+        if self.queries.len() == Self::CAPACITY {
+            self.queries.pop_front();
+        }
+        self.queries.push_back((id, query));
+    }
+
+    pub fn get(&self, id: usize) -> Option<&str> {
+        self.queries
+            .iter()
+            .find(|(known, _)| *known == id)
+            .map(|(_, query)| query.as_str())
+    }
+}
+
+/// Run `future`, turning a panic into `PluginError::Panicked` so a request still gets its
+/// response and the plugin keeps serving the others.
+pub async fn catch_panic<T>(
+    future: impl Future<Output = Result<T, PluginError>>,
+) -> Result<T, PluginError> {
+    match AssertUnwindSafe(future).catch_unwind().await {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = panic_message(payload.as_ref());
+            tracing::error!("plugin panicked: {}", message);
+            Err(PluginError::Panicked(message))
+        }
+    }
+}
+
+/// The message a panic was raised with.
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
@@ -0,0 +1,62 @@
+use std::fmt::{Debug, Display};
+
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+/// What [`Sensitive`] values print as.
+pub const REDACTED: &str = "[redacted]";
+
+/// A secret passing through the protocol, such as a password a plugin copies to the clipboard.
+///
+/// Serialized as the bare value, so peers without the wrapper read it as usual. It prints as
+/// [`REDACTED`] in `Debug` and `Display` output, keeping it out of logs, and its memory is
+/// wiped when dropped. Read it with [`Sensitive::expose`] where it is needed.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+#[serde(transparent)]
+pub struct Sensitive<T: Zeroize>(T);
+
+impl<T: Zeroize> Sensitive<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> From<T> for Sensitive<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Sensitive<String> {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl<T: Zeroize> Zeroize for Sensitive<T> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> Drop for Sensitive<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> Debug for Sensitive<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T: Zeroize> Display for Sensitive<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
@@ -0,0 +1,355 @@
+use std::{collections::VecDeque, error::Error, fmt::Display, path::PathBuf, time::Duration};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream, Lines, duplex},
+    task::JoinHandle,
+    time::Instant,
+};
+
+use crate::{
+    CompressionError, Frame, Match, Message, Metadata, Method, MethodResult, Plugin, PluginError,
+    RpcError, decompress_line, serve_plugin,
+};
+
+/// Bytes buffered each way between the harness and the plugin.
+const PIPE_CAPACITY: usize = 1024 * 1024;
+
+#[derive(Debug)]
+pub enum HarnessError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Compression(CompressionError),
+    /// The plugin failed to start or stopped with an error.
+    Plugin(PluginError),
+    /// No message arrived within the harness timeout.
+    Timeout(Duration),
+    /// The plugin stopped, closing its output.
+    Closed,
+    /// The plugin answered with an error.
+    Rpc(RpcError),
+    /// A message the test did not expect.
+    Unexpected(Box<Message>),
+}
+
+impl Display for HarnessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HarnessError::Io(err) => write!(f, "io: {}", err),
+            HarnessError::Json(err) => write!(f, "json: {}", err),
+            HarnessError::Compression(err) => write!(f, "compression: {}", err),
+            HarnessError::Plugin(err) => write!(f, "plugin: {}", err),
+            HarnessError::Timeout(timeout) => write!(f, "no message within {:?}", timeout),
+            HarnessError::Closed => write!(f, "the plugin closed its output"),
+            HarnessError::Rpc(err) => write!(f, "the plugin answered with an error: {}", err),
+            HarnessError::Unexpected(message) => write!(f, "unexpected message: {:?}", message),
+        }
+    }
+}
+impl Error for HarnessError {}
+
+impl From<std::io::Error> for HarnessError {
+    fn from(err: std::io::Error) -> Self {
+        HarnessError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for HarnessError {
+    fn from(err: serde_json::Error) -> Self {
+        HarnessError::Json(err)
+    }
+}
+
+/// What a search answered, its matches in the order the plugin sent them.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SearchOutcome {
+    pub matches: Vec<Match>,
+    /// The search ran out of its deadline, the matches are partial.
+    pub truncated: bool,
+}
+
+/// Runs a [`Plugin`] the way the daemon does, talking JSON-RPC lines over an in-memory pipe
+/// in place of its stdin and stdout, so plugins can be tested end to end without a daemon.
+///
+/// ```ignore
+/// #[tokio::test]
+/// async fn test_search() {
+///     let mut harness = PluginHarness::start(MyPlugin::default()).await.unwrap();
+///     let outcome = harness.search("fire").await.unwrap();
+///     assert_eq!(outcome.matches[0].title, "Firefox");
+///     harness.quit().await.unwrap();
+/// }
+/// ```
+///
+/// Waiting for a message gives up after a timeout on the tokio clock, so tests of deadlines
+/// run instantly with a [`FakeClock`].
+pub struct PluginHarness {
+    input: DuplexStream,
+    output: Lines<BufReader<DuplexStream>>,
+    metadata: Metadata,
+    next_id: usize,
+    timeout: Duration,
+    /// Messages read while waiting for those of another request, handed out first.
+    queued: VecDeque<Message>,
+    task: JoinHandle<Result<(), PluginError>>,
+}
+
+impl PluginHarness {
+    /// Start `plugin` and wait for it to authenticate. Its config dir is a `glimpse-harness`
+    /// folder in the temp dir.
+    pub async fn start<P: Plugin>(plugin: P) -> Result<Self, HarnessError> {
+        Self::start_in(plugin, std::env::temp_dir().join("glimpse-harness")).await
+    }
+
+    /// Start `plugin` with `config_dir` as its [`crate::Context::config_dir`].
+    pub async fn start_in<P: Plugin>(
+        plugin: P,
+        config_dir: impl Into<PathBuf>,
+    ) -> Result<Self, HarnessError> {
+        let (input, plugin_input) = duplex(PIPE_CAPACITY);
+        let (plugin_output, output) = duplex(PIPE_CAPACITY);
+        let task = tokio::spawn(serve_plugin(
+            plugin,
+            plugin_input,
+            plugin_output,
+            config_dir.into(),
+        ));
+
+        let mut harness = Self {
+            input,
+            output: BufReader::new(output).lines(),
+            metadata: Metadata::default(),
+            next_id: 1,
+            timeout: Duration::from_secs(5),
+            queued: VecDeque::new(),
+            task,
+        };
+        match harness.next_message().await? {
+            Message::Response {
+                id: 0,
+                result: Some(MethodResult::Authenticate(metadata)),
+                ..
+            } => harness.metadata = *metadata,
+            message => return Err(HarnessError::Unexpected(Box::new(message))),
+        }
+        Ok(harness)
+    }
+
+    /// What the plugin authenticated with, including the capabilities the SDK announced.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// How long to wait for a message before failing, 5 seconds by default.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Write `message` to the plugin as the daemon would.
+    pub async fn send(&mut self, message: &Message) -> Result<(), HarnessError> {
+        let line = serde_json::to_string(message)?;
+        self.input.write_all(line.as_bytes()).await?;
+        self.input.write_all(b"\n").await?;
+        self.input.flush().await?;
+        Ok(())
+    }
+
+    /// Send `method` as a request under a new id, which the answers refer to. `deadline` is
+    /// the time the plugin has to answer.
+    pub async fn request(
+        &mut self,
+        method: Method,
+        deadline: Option<Duration>,
+    ) -> Result<usize, HarnessError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let message = Message::Request {
+            id,
+            method,
+            plugin_id: Some(self.metadata.id.clone()),
+            deadline_ms: deadline.map(|deadline| deadline.as_millis() as u64),
+        };
+        self.send(&message).await?;
+        Ok(id)
+    }
+
+    pub async fn notify(&mut self, method: Method) -> Result<(), HarnessError> {
+        let message = Message::Notification {
+            method,
+            plugin_id: Some(self.metadata.id.clone()),
+        };
+        self.send(&message).await
+    }
+
+    /// The next message the plugin wrote, unpacking batches and compressed lines.
+    pub async fn next_message(&mut self) -> Result<Message, HarnessError> {
+        if let Some(message) = self.queued.pop_front() {
+            return Ok(message);
+        }
+        let timeout = self.timeout;
+        loop {
+            let line = tokio::time::timeout(timeout, self.output.next_line())
+                .await
+                .map_err(|_| HarnessError::Timeout(timeout))??
+                .ok_or(HarnessError::Closed)?;
+            let line = decompress_line(&line).map_err(HarnessError::Compression)?;
+            self.queued.extend(Frame::parse(&line)?.into_messages());
+            if let Some(message) = self.queued.pop_front() {
+                return Ok(message);
+            }
+        }
+    }
+
+    /// The messages about request `id` up to its final response: matches and progress
+    /// reports, then the answer. Messages about other requests are kept for later calls.
+    pub async fn responses(&mut self, id: usize) -> Result<Vec<Message>, HarnessError> {
+        let mut others = VecDeque::new();
+        let mut responses = vec![];
+        let found = loop {
+            let message = match self.next_message().await {
+                Ok(message) => message,
+                Err(err) => break Err(err),
+            };
+            let done = match &message {
+                Message::Response {
+                    id: response_id,
+                    result,
+                    ..
+                } if *response_id == id => !matches!(result, Some(MethodResult::Matches { .. })),
+                Message::Notification {
+                    method: Method::ActionProgress(progress),
+                    ..
+                } if progress.action_id == id => false,
+                _ => {
+                    others.push_back(message);
+                    continue;
+                }
+            };
+            responses.push(message);
+            if done {
+                break Ok(responses);
+            }
+        };
+        others.append(&mut self.queued);
+        self.queued = others;
+        found
+    }
+
+    /// Search for `query` without a deadline.
+    pub async fn search(&mut self, query: &str) -> Result<SearchOutcome, HarnessError> {
+        self.search_within(query, None).await
+    }
+
+    /// Search for `query`, giving the plugin `deadline` to answer when set.
+    pub async fn search_within(
+        &mut self,
+        query: &str,
+        deadline: Option<Duration>,
+    ) -> Result<SearchOutcome, HarnessError> {
+        let id = self
+            .request(Method::Search(query.to_string()), deadline)
+            .await?;
+        let mut outcome = SearchOutcome::default();
+        for message in self.responses(id).await? {
+            match answer(message)? {
+                MethodResult::Matches { items } => outcome.matches.extend(items),
+                MethodResult::Done { truncated } => outcome.truncated = truncated,
+                result => {
+                    return Err(HarnessError::Unexpected(Box::new(Message::Response {
+                        id,
+                        error: None,
+                        result: Some(result),
+                        plugin_id: None,
+                    })));
+                }
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// Send `method` as a request and wait for its answer, progress reports left out.
+    pub async fn call(&mut self, method: Method) -> Result<MethodResult, HarnessError> {
+        let id = self.request(method, None).await?;
+        let responses = self.responses(id).await?;
+        let last = responses.into_iter().last().ok_or(HarnessError::Closed)?;
+        answer(last)
+    }
+
+    /// Fail with the first message the plugin writes within `duration`.
+    pub async fn expect_silence(&mut self, duration: Duration) -> Result<(), HarnessError> {
+        let timeout = std::mem::replace(&mut self.timeout, duration);
+        let received = self.next_message().await;
+        self.timeout = timeout;
+        match received {
+            Ok(message) => Err(HarnessError::Unexpected(Box::new(message))),
+            Err(HarnessError::Timeout(_)) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Tell the plugin to quit and wait for it to stop.
+    pub async fn quit(mut self) -> Result<(), HarnessError> {
+        self.notify(Method::Quit).await?;
+        match (&mut self.task).await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(err)) => Err(HarnessError::Plugin(err)),
+            Err(_) => Err(HarnessError::Closed),
+        }
+    }
+}
+
+impl Drop for PluginHarness {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// The result a response carries, or its error.
+fn answer(message: Message) -> Result<MethodResult, HarnessError> {
+    match message {
+        Message::Response {
+            error: Some(error), ..
+        } => Err(HarnessError::Rpc(error)),
+        Message::Response {
+            result: Some(MethodResult::Error { message }),
+            ..
+        } => Err(HarnessError::Rpc(RpcError::plugin(message))),
+        Message::Response {
+            result: Some(result),
+            ..
+        } => Ok(result),
+        message => Err(HarnessError::Unexpected(Box::new(message))),
+    }
+}
+
+/// The tokio clock, stopped so tests of deadlines and timeouts need not wait for them.
+///
+/// While stopped, time moves only when advanced, or skips ahead to the next timer once every
+/// task is waiting, so a plugin sleeping past its deadline is cut short at once. Needs the
+/// current-thread runtime `#[tokio::test]` starts.
+pub struct FakeClock {
+    started: Instant,
+}
+
+impl FakeClock {
+    pub fn pause() -> Self {
+        tokio::time::pause();
+        Self {
+            started: Instant::now(),
+        }
+    }
+
+    /// Move the clock forward, firing the timers due by then.
+    pub async fn advance(&self, by: Duration) {
+        tokio::time::advance(by).await;
+    }
+
+    /// Time passed on the clock since it stopped.
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Let the clock run with real time again.
+    pub fn resume(self) {
+        tokio::time::resume();
+    }
+}
@@ -0,0 +1,98 @@
+use glimpse_sdk::{
+    COMPRESSED_PREFIX, CompressionStats, Frame, Match, Message, Method, MethodResult,
+    compress_line, decompress_line,
+};
+
+fn matches_line(count: usize) -> String {
+    let message = Message::Response {
+        id: 7,
+        error: None,
+        result: Some(MethodResult::Matches {
+            items: (0..count)
+                .map(|i| Match {
+                    title: format!("match {}", i),
+                    description: "a rather repetitive description".to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+        }),
+        plugin_id: Some("test".to_string()),
+    };
+    serde_json::to_string(&message).unwrap()
+}
+
+#[test]
+fn test_compressed_lines_round_trip() {
+    let line = matches_line(500);
+    let compressed = compress_line(&line, 1024).unwrap();
+    assert!(compressed.starts_with(COMPRESSED_PREFIX));
+    assert!(compressed.len() < line.len() / 4);
+
+    // lines are read with their newline
+    let read = format!("{}\n", compressed);
+    let decompressed = decompress_line(&read).unwrap();
+    assert_eq!(decompressed, line);
+    assert!(Frame::parse(&decompressed).is_ok());
+}
+
+#[test]
+fn test_small_and_incompressible_lines_stay_plain() {
+    let line = matches_line(2);
+    assert_eq!(compress_line(&line, 16 * 1024), None);
+
+    // base64 of random bytes barely compresses, the encoded result would be longer
+    let mut state = 42u64;
+    let noise = (0..20_000)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            char::from(b'!' + ((state >> 33) % 90) as u8)
+        })
+        .collect::<String>();
+    assert_eq!(compress_line(&noise, 1024), None);
+
+    // plain lines pass through untouched
+    assert_eq!(decompress_line(&line).unwrap(), line);
+}
+
+#[test]
+fn test_invalid_compressed_lines_fail() {
+    assert!(decompress_line("~not base64!").is_err());
+    assert!(decompress_line("~aGVsbG8=").is_err());
+}
+
+#[test]
+fn test_stats_record_ratio() {
+    let stats = CompressionStats::default();
+    assert_eq!(stats.ratio(), 1.0);
+
+    let line = matches_line(500);
+    assert_eq!(stats.compress(&line, line.len()), None);
+    assert_eq!(stats.messages(), 0);
+
+    let compressed = stats.compress(&line, 1024).unwrap();
+    assert_eq!(stats.messages(), 1);
+    assert_eq!(stats.raw_bytes(), line.len() as u64);
+    assert_eq!(stats.wire_bytes(), compressed.len() as u64);
+    assert!(stats.ratio() < 0.25);
+
+    let receiver = CompressionStats::default();
+    assert_eq!(receiver.decompress(&line).unwrap(), line);
+    assert_eq!(receiver.messages(), 0);
+    assert_eq!(receiver.decompress(&compressed).unwrap(), line);
+    assert_eq!(receiver.messages(), 1);
+    assert_eq!(receiver.ratio(), stats.ratio());
+}
+
+#[test]
+fn test_enable_compression_notification() {
+    let message = Message::Notification {
+        method: Method::EnableCompression { threshold: 16384 },
+        plugin_id: None,
+    };
+    let json = serde_json::to_value(&message).unwrap();
+    assert_eq!(json["method"], "enable_compression");
+    assert_eq!(json["params"]["threshold"], 16384);
+    assert_eq!(serde_json::from_value::<Message>(json).unwrap(), message);
+}
@@ -0,0 +1,64 @@
+use glimpse_sdk::{ConfigField, ConfigKind, ConfigSchema, Metadata};
+use serde_json::json;
+
+fn create_schema() -> ConfigSchema {
+    ConfigSchema::new()
+        .field(ConfigField::new("max_results", ConfigKind::Integer).default_value(20))
+        .field(ConfigField::new("roots", ConfigKind::StringList).required())
+        .field(ConfigField::new("show_hidden", ConfigKind::Boolean))
+}
+
+#[test]
+fn test_validate_fills_defaults() {
+    let config = create_schema()
+        .validate(&json!({"roots": ["~/src"]}))
+        .unwrap();
+
+    assert_eq!(config, json!({"max_results": 20, "roots": ["~/src"]}));
+}
+
+#[test]
+fn test_validate_keeps_user_values() {
+    let config = create_schema()
+        .validate(&json!({"roots": [], "max_results": 5, "show_hidden": true}))
+        .unwrap();
+
+    assert_eq!(
+        config,
+        json!({"max_results": 5, "roots": [], "show_hidden": true})
+    );
+}
+
+#[test]
+fn test_validate_collects_all_errors() {
+    let errors = create_schema()
+        .validate(&json!({"max_results": "many", "colour": "red"}))
+        .unwrap_err();
+
+    assert_eq!(errors.len(), 3);
+    assert!(errors.iter().any(|e| e.starts_with("max_results:")));
+    assert!(errors.iter().any(|e| e == "roots: missing"));
+    assert!(errors.iter().any(|e| e == "colour: unknown setting"));
+}
+
+#[test]
+fn test_validate_treats_null_as_empty_table() {
+    let schema = ConfigSchema::new().field(ConfigField::new("limit", ConfigKind::Integer));
+
+    assert_eq!(schema.validate(&json!(null)).unwrap(), json!({}));
+    assert!(schema.validate(&json!([1, 2])).is_err());
+}
+
+#[test]
+fn test_metadata_without_schema_deserializes() {
+    let metadata: Metadata = serde_json::from_value(json!({
+        "id": "a",
+        "name": "A",
+        "version": "1",
+        "description": "",
+        "author": "",
+    }))
+    .unwrap();
+
+    assert_eq!(metadata.config_schema, None);
+}
@@ -0,0 +1,72 @@
+use glimpse_sdk::{Action, MatchAction, Method, RpcError};
+
+fn delete_action(prompt: Option<&str>) -> MatchAction {
+    MatchAction {
+        title: "Delete".to_string(),
+        action: Action::Exec {
+            command: "rm".to_string(),
+            args: vec!["/tmp/report.pdf".to_string()],
+        },
+        close_on_action: true,
+        alternates: vec![],
+        requires_confirmation: true,
+        confirmation_prompt: prompt.map(str::to_string),
+        expand: vec![],
+    }
+}
+
+#[test]
+fn test_confirmation_fields_default_to_off() {
+    let json = r#"{"title":"Open","action":{"type":"open","uri":"https://example.com"},"close_on_action":true}"#;
+    let action: MatchAction = serde_json::from_str(json).unwrap();
+    assert!(!action.requires_confirmation);
+    assert_eq!(action.confirmation_prompt, None);
+
+    let json = serde_json::to_string(&action).unwrap();
+    assert!(!json.contains("requires_confirmation"));
+    assert!(!json.contains("confirmation_prompt"));
+}
+
+#[test]
+fn test_confirmation_roundtrip() {
+    let action = delete_action(Some("Delete report.pdf?"));
+    let json = serde_json::to_string(&action).unwrap();
+    assert!(json.contains(r#""requires_confirmation":true"#));
+    assert_eq!(serde_json::from_str::<MatchAction>(&json).unwrap(), action);
+}
+
+#[test]
+fn test_confirmation_prompt() {
+    assert_eq!(
+        delete_action(Some("Delete report.pdf?")).prompt(),
+        "Delete report.pdf?"
+    );
+    assert_eq!(delete_action(None).prompt(), "Delete?");
+
+    let error = RpcError::confirmation_required(&delete_action(None));
+    assert_eq!(error.code, RpcError::CONFIRMATION_REQUIRED);
+    assert_eq!(error.data.unwrap()["prompt"], "Delete?");
+}
+
+#[test]
+fn test_activate_confirmed_is_optional() {
+    let json = r#"{"method":"activate","params":{"generation":3,"match_id":1,"action":0}}"#;
+    let method: Method = serde_json::from_str(json).unwrap();
+    assert!(matches!(
+        method,
+        Method::Activate {
+            confirmed: false,
+            ..
+        }
+    ));
+
+    let json = r#"{"method":"activate","params":{"generation":3,"match_id":1,"action":0,"confirmed":true}}"#;
+    let method: Method = serde_json::from_str(json).unwrap();
+    assert!(matches!(
+        method,
+        Method::Activate {
+            confirmed: true,
+            ..
+        }
+    ));
+}
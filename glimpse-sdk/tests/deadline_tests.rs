@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use glimpse_sdk::{Deadline, Message, Method, MethodResult, SearchSink};
+use serde_json::json;
+use tokio::sync::mpsc;
+
+#[test]
+fn test_deadline_without_budget() {
+    let deadline = Deadline::from_millis(None);
+    assert_eq!(deadline, Deadline::none());
+    assert_eq!(deadline.remaining(), None);
+    assert!(!deadline.is_exceeded());
+    assert!(deadline.allows(Duration::from_secs(3600)));
+}
+
+#[test]
+fn test_deadline_budget() {
+    let deadline = Deadline::from_millis(Some(60_000));
+    let remaining = deadline.remaining().unwrap();
+    assert!(remaining <= Duration::from_secs(60));
+    assert!(remaining > Duration::from_secs(50));
+    assert!(!deadline.is_exceeded());
+    assert!(deadline.allows(Duration::from_secs(1)));
+    assert!(!deadline.allows(Duration::from_secs(120)));
+
+    let passed = Deadline::after(Duration::ZERO);
+    assert_eq!(passed.remaining(), Some(Duration::ZERO));
+    assert!(passed.is_exceeded());
+    assert!(!passed.allows(Duration::from_millis(1)));
+}
+
+#[tokio::test]
+async fn test_deadline_run() {
+    assert_eq!(Deadline::none().run(async { 1 }).await, Some(1));
+    assert_eq!(
+        Deadline::after(Duration::from_secs(5))
+            .run(async { 2 })
+            .await,
+        Some(2)
+    );
+
+    let slow = tokio::time::sleep(Duration::from_secs(5));
+    assert_eq!(
+        Deadline::after(Duration::from_millis(20)).run(slow).await,
+        None
+    );
+}
+
+#[test]
+fn test_search_sink_deadline() {
+    let (tx, _rx) = mpsc::channel(1);
+    let sink = SearchSink::new(1, "test".to_string(), tx);
+    assert_eq!(sink.deadline(), Deadline::none());
+
+    let deadline = Deadline::after(Duration::from_secs(1));
+    assert_eq!(sink.with_deadline(deadline).deadline(), deadline);
+}
+
+#[test]
+fn test_deadline_wire_format() {
+    let request = Message::Request {
+        id: 4,
+        method: Method::Search("fire".to_string()),
+        plugin_id: None,
+        deadline_ms: Some(2700),
+    };
+    let value = serde_json::to_value(&request).unwrap();
+    assert_eq!(value["deadline_ms"], json!(2700));
+    assert_eq!(serde_json::from_value::<Message>(value).unwrap(), request);
+
+    // requests from peers predating deadlines have none
+    let legacy = json!({"jsonrpc": "2.0", "id": 4, "method": "search", "params": "fire"});
+    match serde_json::from_value::<Message>(legacy).unwrap() {
+        Message::Request { deadline_ms, .. } => assert_eq!(deadline_ms, None),
+        message => panic!("expected a request, got {:?}", message),
+    }
+
+    let truncated = MethodResult::Done { truncated: true };
+    let value = serde_json::to_value(&truncated).unwrap();
+    assert_eq!(value, json!({"type": "done", "truncated": true}));
+    assert_eq!(
+        serde_json::from_value::<MethodResult>(value).unwrap(),
+        truncated
+    );
+}
@@ -0,0 +1,73 @@
+use async_trait::async_trait;
+use glimpse_sdk::{
+    GlimpsePlugin, IntoPlugin, Match, Metadata, Permission, Plugin, PluginError, PluginMetadata,
+};
+
+#[derive(GlimpsePlugin)]
+#[glimpse(id = "test.clipboard", name = "Clipboard", prefix = "clip ")]
+#[glimpse(
+    triggers("clip", "paste"),
+    keep_triggers,
+    permissions(Clipboard, Secrets),
+    prefix_only,
+    sensitive,
+    fallback
+)]
+struct FullPlugin;
+
+#[derive(GlimpsePlugin, Default)]
+#[glimpse(id = "test.minimal")]
+struct MinimalPlugin;
+
+#[async_trait]
+impl Plugin for MinimalPlugin {
+    fn metadata(&self) -> Metadata {
+        Self::plugin_metadata()
+    }
+
+    async fn handle_search(&self, _query: String) -> Result<Vec<Match>, PluginError> {
+        Ok(vec![])
+    }
+}
+
+#[test]
+fn test_metadata_from_attributes() {
+    let metadata = FullPlugin::plugin_metadata();
+
+    assert_eq!(metadata.id, "test.clipboard");
+    assert_eq!(metadata.name, "Clipboard");
+    assert_eq!(metadata.prefix.as_deref(), Some("clip "));
+    assert_eq!(metadata.triggers, vec!["clip", "paste"]);
+    assert!(metadata.keep_triggers);
+    assert_eq!(
+        metadata.permissions,
+        vec![Permission::Clipboard, Permission::Secrets]
+    );
+    assert!(metadata.prefix_only);
+    assert!(metadata.sensitive);
+    assert!(metadata.fallback);
+}
+
+#[test]
+fn test_metadata_defaults_to_the_package() {
+    let metadata = MinimalPlugin.metadata();
+
+    assert_eq!(metadata.name, "MinimalPlugin");
+    assert_eq!(metadata.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(metadata.prefix, None);
+    assert!(metadata.triggers.is_empty());
+    assert!(!metadata.keep_triggers);
+    assert!(!metadata.prefix_only);
+    assert!(!metadata.fallback);
+}
+
+#[test]
+fn test_main_may_return_a_result() {
+    assert!(MinimalPlugin.into_plugin().is_ok());
+
+    let failed: Result<MinimalPlugin, &str> = Err("no home directory");
+    assert_eq!(
+        failed.into_plugin().err().as_deref(),
+        Some("no home directory")
+    );
+}
@@ -0,0 +1,73 @@
+use glimpse_sdk::{FeatureError, Features, Metadata};
+
+#[test]
+fn test_defaults_are_compiled_in() {
+    let defaults = Features::default();
+    assert!(defaults.streaming && defaults.subscriptions);
+    assert!(!defaults.binary_encoding);
+    assert_eq!(defaults.check(), Ok(()));
+    assert_eq!(defaults.intersect(&Features::compiled()), defaults);
+}
+
+#[test]
+fn test_check_refuses_features_missing_from_the_build() {
+    let binary = Features {
+        binary_encoding: true,
+        ..Features::default()
+    };
+    assert_eq!(
+        binary.check(),
+        Err(FeatureError::NotCompiled {
+            feature: "binary_encoding"
+        })
+    );
+    assert_eq!(binary.validate(), Ok(()));
+}
+
+#[test]
+fn test_validate_refuses_binary_encoding_without_streaming() {
+    let features = Features {
+        streaming: false,
+        binary_encoding: true,
+        subscriptions: false,
+    };
+    let err = features.validate().unwrap_err();
+    assert_eq!(
+        err,
+        FeatureError::Requires {
+            feature: "binary_encoding",
+            requires: "streaming"
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "binary_encoding requires streaming to be on as well"
+    );
+}
+
+#[test]
+fn test_intersect_keeps_what_both_have_on() {
+    let plugin = Features {
+        subscriptions: false,
+        ..Features::default()
+    };
+    let negotiated = Features::default().intersect(&plugin);
+    assert_eq!(negotiated.enabled(), vec!["streaming"]);
+    assert!(Features::none().intersect(&plugin).enabled().is_empty());
+}
+
+#[test]
+fn test_metadata_without_features_gets_the_defaults() {
+    let metadata: Metadata = serde_json::from_str(
+        r#"{"id":"old","name":"Old","version":"1","description":"","author":""}"#,
+    )
+    .unwrap();
+    assert_eq!(metadata.features, Features::default());
+
+    let metadata: Metadata = serde_json::from_str(
+        r#"{"id":"new","name":"New","version":"1","description":"","author":"","features":{"subscriptions":false}}"#,
+    )
+    .unwrap();
+    assert!(metadata.features.streaming);
+    assert!(!metadata.features.subscriptions);
+}
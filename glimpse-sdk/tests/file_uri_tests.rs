@@ -0,0 +1,29 @@
+use std::path::Path;
+
+use glimpse_sdk::{Action, file_uri};
+
+#[test]
+fn test_file_uri_encodes_reserved_bytes() {
+    assert_eq!(
+        file_uri(Path::new("/home/alice/Documents/report.pdf")),
+        "file:///home/alice/Documents/report.pdf"
+    );
+    assert_eq!(
+        file_uri(Path::new("/home/alice/100% #1?.txt")),
+        "file:///home/alice/100%25%20%231%3F.txt"
+    );
+    assert_eq!(
+        file_uri(Path::new("/media/Фото/été.jpg")),
+        "file:///media/%D0%A4%D0%BE%D1%82%D0%BE/%C3%A9t%C3%A9.jpg"
+    );
+}
+
+#[test]
+fn test_open_file_action() {
+    assert_eq!(
+        Action::open_file("/mnt/data/my notes"),
+        Action::Open {
+            uri: "file:///mnt/data/my%20notes".to_string(),
+        }
+    );
+}
@@ -0,0 +1,185 @@
+use std::{collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+use glimpse_sdk::{
+    Match, Message, Metadata, Method, MethodResult, PROTOCOL_VERSION, Plugin, PluginError,
+    Progress, SearchSink,
+    testing::{FakeClock, HarnessError, PluginHarness},
+};
+use tokio::sync::watch;
+
+struct TestPlugin;
+
+#[async_trait]
+impl Plugin for TestPlugin {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            id: "test.harness".to_string(),
+            name: "Harness".to_string(),
+            version: "0.1.0".to_string(),
+            ..Default::default()
+        }
+    }
+
+    async fn search(&self, query: String, sink: &SearchSink) -> Result<(), PluginError> {
+        match query.as_str() {
+            "fail" => Err(PluginError::Other("no results today".to_string())),
+            "slow" => {
+                sink.send(vec![create_match("quick")]).await?;
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                sink.send(vec![create_match("late")]).await
+            }
+            _ => {
+                sink.send(vec![create_match("first")]).await?;
+                sink.send(vec![create_match("second")]).await
+            }
+        }
+    }
+
+    async fn handle_search(&self, _query: String) -> Result<Vec<Match>, PluginError> {
+        unreachable!("answered through search()")
+    }
+
+    async fn call_action(
+        &self,
+        _action: String,
+        _params: HashMap<String, String>,
+        progress: &Progress,
+    ) -> Result<(), PluginError> {
+        progress.report(50, "halfway").await
+    }
+}
+
+/// Records the settings versions it applied, taking `delay_ms` to apply each.
+struct ConfiguredPlugin {
+    applied: watch::Sender<Vec<u64>>,
+}
+
+#[async_trait]
+impl Plugin for ConfiguredPlugin {
+    fn metadata(&self) -> Metadata {
+        TestPlugin.metadata()
+    }
+
+    async fn configure(&self, config: serde_json::Value) -> Result<(), PluginError> {
+        let delay = config["delay_ms"].as_u64().unwrap_or_default();
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        let version = config["version"].as_u64().unwrap_or_default();
+        self.applied.send_modify(|applied| applied.push(version));
+        Ok(())
+    }
+
+    async fn handle_search(&self, _query: String) -> Result<Vec<Match>, PluginError> {
+        Ok(vec![])
+    }
+}
+
+fn create_match(title: &str) -> Match {
+    Match {
+        title: title.to_string(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_harness_waits_for_authentication() {
+    let harness = PluginHarness::start(TestPlugin).await.unwrap();
+
+    assert_eq!(harness.metadata().id, "test.harness");
+    assert_eq!(harness.metadata().protocol_version, PROTOCOL_VERSION);
+    harness.quit().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_search_collects_streamed_matches() {
+    let mut harness = PluginHarness::start(TestPlugin).await.unwrap();
+
+    let outcome = harness.search("fire").await.unwrap();
+
+    let titles: Vec<_> = outcome.matches.iter().map(|m| m.title.as_str()).collect();
+    assert_eq!(titles, vec!["first", "second"]);
+    assert!(!outcome.truncated);
+    harness
+        .expect_silence(Duration::from_millis(50))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_search_errors_are_returned() {
+    let mut harness = PluginHarness::start(TestPlugin).await.unwrap();
+
+    let err = harness.search("fail").await.unwrap_err();
+
+    assert!(matches!(err, HarnessError::Rpc(_)), "{}", err);
+    assert!(err.to_string().contains("no results today"));
+}
+
+#[tokio::test]
+async fn test_deadlines_pass_on_a_fake_clock() {
+    let clock = FakeClock::pause();
+    let mut harness = PluginHarness::start(TestPlugin).await.unwrap();
+
+    let outcome = harness
+        .search_within("slow", Some(Duration::from_millis(200)))
+        .await
+        .unwrap();
+
+    assert!(outcome.truncated);
+    assert_eq!(outcome.matches, vec![create_match("quick")]);
+    assert!(clock.elapsed() < Duration::from_secs(60));
+}
+
+#[tokio::test]
+async fn test_responses_include_progress_reports() {
+    let mut harness = PluginHarness::start(TestPlugin).await.unwrap();
+
+    let id = harness
+        .request(Method::CallAction("sync".to_string(), HashMap::new()), None)
+        .await
+        .unwrap();
+    let responses = harness.responses(id).await.unwrap();
+
+    assert_eq!(responses.len(), 2);
+    assert!(matches!(
+        &responses[0],
+        Message::Notification { method: Method::ActionProgress(progress), .. } if progress.pct == 50
+    ));
+    assert!(matches!(
+        &responses[1],
+        Message::Response {
+            result: Some(MethodResult::Done { truncated: false }),
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn test_settings_apply_in_the_order_sent() {
+    let (applied, mut versions) = watch::channel(vec![]);
+    let mut harness = PluginHarness::start(ConfiguredPlugin { applied })
+        .await
+        .unwrap();
+
+    // the first settings take longer to apply, the later ones still win
+    harness
+        .notify(Method::Configure(
+            serde_json::json!({"version": 1, "delay_ms": 100}),
+        ))
+        .await
+        .unwrap();
+    harness
+        .notify(Method::ConfigChanged(serde_json::json!({"version": 2})))
+        .await
+        .unwrap();
+
+    let applied = tokio::time::timeout(
+        Duration::from_secs(5),
+        versions.wait_for(|applied| applied.len() == 2),
+    )
+    .await
+    .unwrap()
+    .unwrap()
+    .clone();
+    assert_eq!(applied, vec![1, 2]);
+}
@@ -0,0 +1,101 @@
+use glimpse_sdk::{Frame, Message, Method, MethodResult, RpcError};
+use serde_json::json;
+
+#[test]
+fn test_messages_are_written_as_jsonrpc_2() {
+    let request = Message::Request {
+        id: 1,
+        method: Method::Search("fire".to_string()),
+        plugin_id: None,
+        deadline_ms: None,
+    };
+    assert_eq!(
+        serde_json::to_value(&request).unwrap(),
+        json!({"jsonrpc": "2.0", "id": 1, "method": "search", "params": "fire", "plugin_id": null})
+    );
+
+    let notification = Message::Notification {
+        method: Method::Cancel(None),
+        plugin_id: None,
+    };
+    assert_eq!(
+        serde_json::to_value(&notification).unwrap(),
+        json!({"jsonrpc": "2.0", "method": "cancel", "params": null, "plugin_id": null})
+    );
+}
+
+#[test]
+fn test_response_carries_result_or_error() {
+    let done = Message::Response {
+        id: 2,
+        error: None,
+        result: Some(MethodResult::Done { truncated: false }),
+        plugin_id: Some("test".to_string()),
+    };
+    assert_eq!(
+        serde_json::to_value(&done).unwrap(),
+        json!({"jsonrpc": "2.0", "id": 2, "result": {"type": "done"}, "plugin_id": "test"})
+    );
+
+    let failed = Message::Response {
+        id: 3,
+        error: Some(RpcError::rejected("unknown match")),
+        result: None,
+        plugin_id: None,
+    };
+    let json = serde_json::to_value(&failed).unwrap();
+    assert_eq!(
+        json,
+        json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "error": {"code": RpcError::REJECTED, "message": "unknown match"},
+            "plugin_id": null,
+        })
+    );
+    assert_eq!(serde_json::from_value::<Message>(json).unwrap(), failed);
+}
+
+#[test]
+fn test_messages_of_older_peers_are_accepted() {
+    let message: Message =
+        serde_json::from_str(r#"{"id": 4, "error": "boom", "result": null, "plugin_id": "test"}"#)
+            .unwrap();
+    assert_eq!(
+        message,
+        Message::Response {
+            id: 4,
+            error: Some(RpcError::internal("boom")),
+            result: None,
+            plugin_id: Some("test".to_string()),
+        }
+    );
+}
+
+#[test]
+fn test_other_jsonrpc_versions_are_refused() {
+    let line = r#"{"jsonrpc": "1.0", "id": 1, "method": "quit", "plugin_id": null}"#;
+    assert!(serde_json::from_str::<Message>(line).is_err());
+}
+
+#[test]
+fn test_parse_batch() {
+    let frame = Frame::parse(
+        r#"[{"jsonrpc": "2.0", "id": 1, "method": "search", "params": "a", "plugin_id": null},
+            {"jsonrpc": "2.0", "method": "cancel", "plugin_id": null}]"#,
+    )
+    .unwrap();
+    let messages = frame.into_messages();
+    assert_eq!(messages.len(), 2);
+    assert!(matches!(
+        messages[1],
+        Message::Notification {
+            method: Method::Cancel(None),
+            ..
+        }
+    ));
+
+    let single = Frame::parse(r#"{"jsonrpc": "2.0", "method": "quit", "plugin_id": null}"#);
+    assert_eq!(single.unwrap().into_messages().len(), 1);
+    assert!(Frame::parse("[]").is_err());
+}
@@ -0,0 +1,123 @@
+use glimpse_sdk::{
+    LineRead, Match, Message, Method, MethodResult, Oversized, encode_bounded, read_line_bounded,
+};
+use tokio::io::BufReader;
+
+fn matches_response(count: usize) -> Message {
+    Message::Response {
+        id: 7,
+        error: None,
+        result: Some(MethodResult::Matches {
+            items: (0..count)
+                .map(|i| Match {
+                    title: format!("match {}", i),
+                    description: "x".repeat(100),
+                    ..Default::default()
+                })
+                .collect(),
+        }),
+        plugin_id: Some("test".to_string()),
+    }
+}
+
+#[tokio::test]
+async fn test_long_lines_are_skipped() {
+    let input = format!("short\n{}\nafter\nlast", "x".repeat(100));
+    // a tiny buffer makes the reader see the long line in pieces
+    let mut reader = BufReader::with_capacity(8, input.as_bytes());
+    let mut line = String::new();
+
+    let mut read = vec![];
+    loop {
+        let result = read_line_bounded(&mut reader, &mut line, 10).await.unwrap();
+        if result == LineRead::Eof {
+            break;
+        }
+        read.push((result, line.clone()));
+    }
+
+    assert_eq!(
+        read,
+        vec![
+            (LineRead::Line, "short".to_string()),
+            (LineRead::TooLong(100), String::new()),
+            (LineRead::Line, "after".to_string()),
+            (LineRead::Line, "last".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_small_messages_are_sent_as_is() {
+    let message = matches_response(3);
+    let (line, oversized) = encode_bounded(&message, 1024 * 1024);
+    assert_eq!(line.unwrap(), serde_json::to_string(&message).unwrap());
+    assert!(oversized.is_none());
+}
+
+#[test]
+fn test_oversized_matches_are_truncated() {
+    let limit = 2_000;
+    let (line, oversized) = encode_bounded(&matches_response(100), limit);
+    let line = line.unwrap();
+    assert!(line.len() <= limit);
+
+    let Some(Oversized::Truncated {
+        id, kept, dropped, ..
+    }) = oversized
+    else {
+        panic!("expected truncation, got {:?}", oversized);
+    };
+    assert_eq!(id, 7);
+    assert_eq!(kept + dropped, 100);
+    assert!(kept > 0);
+
+    // the best matches come first and are the ones kept
+    let Message::Response {
+        result: Some(MethodResult::Matches { items }),
+        ..
+    } = serde_json::from_str(&line).unwrap()
+    else {
+        panic!("expected matches");
+    };
+    assert_eq!(items.len(), kept);
+    assert_eq!(items[0].title, "match 0");
+    // one more would not have fitted
+    assert!(
+        serde_json::to_string(&matches_response(kept + 1))
+            .unwrap()
+            .len()
+            > limit
+    );
+}
+
+#[test]
+fn test_responses_that_cannot_shrink_become_errors() {
+    let message = Message::Response {
+        id: 3,
+        error: None,
+        result: Some(MethodResult::Error {
+            message: "x".repeat(5_000),
+        }),
+        plugin_id: None,
+    };
+    let (line, oversized) = encode_bounded(&message, 1_000);
+
+    assert!(matches!(oversized, Some(Oversized::Replaced { id: 3, .. })));
+    let Message::Response { id, error, .. } = serde_json::from_str(&line.unwrap()).unwrap() else {
+        panic!("expected a response");
+    };
+    assert_eq!(id, 3);
+    assert!(error.unwrap().message.contains("exceeds"));
+}
+
+#[test]
+fn test_oversized_notifications_are_dropped() {
+    let message = Message::Notification {
+        method: Method::Search("x".repeat(5_000)),
+        plugin_id: None,
+    };
+    let (line, oversized) = encode_bounded(&message, 1_000);
+    assert!(line.is_none());
+    assert!(matches!(oversized, Some(Oversized::Dropped { .. })));
+}
@@ -0,0 +1,66 @@
+use std::sync::{Arc, Mutex};
+
+use glimpse_sdk::{JsonLayer, LogLevel, LogRecord, Method};
+use tracing_subscriber::layer::SubscriberExt;
+
+#[test]
+fn test_parse_line() {
+    let line = r#"{"ts":1700000000000,"level":"info","target":"files","message":"indexed","fields":{"count":3}}"#;
+    let record = LogRecord::parse_line(&format!("{}\n", line), 5);
+    assert_eq!(record.ts, 1_700_000_000_000);
+    assert_eq!(record.level, LogLevel::Info);
+    assert_eq!(record.fields["count"], 3);
+    assert_eq!(record.to_json(), line);
+    assert_eq!(record.to_string(), " INFO files: indexed count=3");
+
+    // anything else a plugin prints is kept as a warning
+    let record = LogRecord::parse_line("thread 'main' panicked at src/main.rs:3:5\n", 5);
+    assert_eq!(record.ts, 5);
+    assert_eq!(record.level, LogLevel::Warn);
+    assert_eq!(record.message, "thread 'main' panicked at src/main.rs:3:5");
+    assert_eq!(
+        record.to_json(),
+        r#"{"ts":5,"level":"warn","message":"thread 'main' panicked at src/main.rs:3:5"}"#
+    );
+}
+
+#[test]
+fn test_json_layer_records_events() {
+    let records = Arc::new(Mutex::new(vec![]));
+    let written = records.clone();
+    let subscriber = tracing_subscriber::registry().with(JsonLayer::new(move |record| {
+        written.lock().unwrap().push(record.clone());
+    }));
+
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::warn!(path = "/tmp/a", retries = 2, "failed to read {}", "a");
+    });
+
+    let records = records.lock().unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].level, LogLevel::Warn);
+    assert_eq!(records[0].message, "failed to read a");
+    assert_eq!(records[0].fields["path"], "/tmp/a");
+    assert_eq!(records[0].fields["retries"], 2);
+    assert!(
+        records[0]
+            .location
+            .as_deref()
+            .unwrap()
+            .contains("logging_tests.rs:")
+    );
+}
+
+#[test]
+fn test_get_logs_serde() {
+    let method = Method::GetLogs {
+        plugin_id: "me.aresa.glimpse.files".to_string(),
+        since: None,
+    };
+    let json = serde_json::to_value(&method).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"method": "get_logs", "params": {"plugin_id": "me.aresa.glimpse.files"}})
+    );
+    assert_eq!(serde_json::from_value::<Method>(json).unwrap(), method);
+}
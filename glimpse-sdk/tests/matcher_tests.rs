@@ -0,0 +1,105 @@
+use glimpse_sdk::{
+    Highlights, Match,
+    matcher::{Matcher, filter_matches, ranges},
+};
+
+fn create_match(title: &str, description: &str) -> Match {
+    Match {
+        title: title.to_string(),
+        description: description.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_characters_must_appear_in_order() {
+    let matcher = Matcher::new("ffx");
+
+    assert!(matcher.score("Firefox").is_some());
+    assert_eq!(matcher.score("Files"), None);
+    assert_eq!(matcher.score("xff"), None);
+}
+
+#[test]
+fn test_word_starts_beat_the_middle_of_words() {
+    let matcher = Matcher::new("ff");
+
+    let firefox = matcher.score("Firefox").unwrap();
+    let diff = matcher.score("Diff viewer").unwrap();
+    assert!(firefox > diff, "{} <= {}", firefox, diff);
+
+    let camel = Matcher::new("fb").score("FooBar").unwrap();
+    let plain = Matcher::new("fb").score("Foobar").unwrap();
+    assert!(camel > plain, "{} <= {}", camel, plain);
+}
+
+#[test]
+fn test_consecutive_characters_beat_scattered_ones() {
+    let matcher = Matcher::new("term");
+
+    let run = matcher.score("Terminal").unwrap();
+    let scattered = matcher.score("The Rust Manual").unwrap();
+    assert!(run > scattered, "{} <= {}", run, scattered);
+    assert_eq!(matcher.score("term"), Some(1.0));
+}
+
+#[test]
+fn test_best_alignment_is_found() {
+    // the first `c` of the text is not the best start
+    let (_, indices) = Matcher::new("code").indices("cat code").unwrap();
+
+    assert_eq!(indices, vec![4, 5, 6, 7]);
+}
+
+#[test]
+fn test_case_and_accents_are_folded() {
+    assert_eq!(Matcher::new("CAFE").score("café"), Some(1.0));
+    assert_eq!(Matcher::new("café").score("Cafe"), Some(1.0));
+    assert!(Matcher::new("zurich").score("Zürich").is_some());
+}
+
+#[test]
+fn test_indices_count_characters() {
+    let (_, indices) = Matcher::new("ab").indices("ää ab").unwrap();
+
+    assert_eq!(indices, vec![3, 4]);
+}
+
+#[test]
+fn test_empty_query_matches_everything() {
+    assert_eq!(Matcher::new("  ").score("anything"), Some(1.0));
+}
+
+#[test]
+fn test_filter_matches_scores_and_sorts() {
+    let items = vec![
+        create_match("Settings", "Change the firefox profile"),
+        create_match("Calculator", ""),
+        create_match("Firefox", "Web browser"),
+    ];
+
+    let found = filter_matches("firefox", items);
+
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].title, "Firefox");
+    assert_eq!(found[0].score, 1.0);
+    assert_eq!(
+        found[0].highlights,
+        Some(Highlights {
+            title: vec![(0, 7)],
+            description: vec![],
+        })
+    );
+    assert_eq!(found[1].title, "Settings");
+    assert!(found[1].score <= 0.5);
+    assert_eq!(
+        found[1].highlights.as_ref().unwrap().description,
+        vec![(11, 18)]
+    );
+}
+
+#[test]
+fn test_ranges_merge_adjacent_positions() {
+    assert_eq!(ranges(&[0, 1, 2, 5, 7, 8]), vec![(0, 3), (5, 6), (7, 9)]);
+    assert_eq!(ranges(&[]), vec![]);
+}
@@ -0,0 +1,134 @@
+use glimpse_sdk::{Detail, DetailFormat, Icon, Match, Message, Method, MethodResult};
+
+#[test]
+fn test_error_result_serialization() {
+    let result = MethodResult::Error {
+        message: "index not ready".to_string(),
+    };
+    let json = serde_json::to_string(&result).unwrap();
+    assert_eq!(json, r#"{"type":"error","message":"index not ready"}"#);
+    assert_eq!(serde_json::from_str::<MethodResult>(&json).unwrap(), result);
+}
+
+#[test]
+fn test_error_response_roundtrip() {
+    let message = Message::Response {
+        id: 4,
+        error: None,
+        result: Some(MethodResult::Error {
+            message: "boom".to_string(),
+        }),
+        plugin_id: Some("me.aresa.glimpse.files".to_string()),
+    };
+    let json = serde_json::to_string(&message).unwrap();
+    assert_eq!(serde_json::from_str::<Message>(&json).unwrap(), message);
+}
+
+#[test]
+fn test_done_result_serialization() {
+    let json = serde_json::to_string(&MethodResult::Done { truncated: false }).unwrap();
+    assert_eq!(json, r#"{"type":"done"}"#);
+    assert_eq!(
+        serde_json::from_str::<MethodResult>(&json).unwrap(),
+        MethodResult::Done { truncated: false }
+    );
+}
+
+#[test]
+fn test_match_detail_serialization() {
+    let item = Match {
+        title: "2 + 2".to_string(),
+        detail: Some(Detail {
+            image: Some("/tmp/plot.png".to_string()),
+            ..Detail::markdown("**4**")
+        }),
+        ..Default::default()
+    };
+    let json = serde_json::to_value(&item).unwrap();
+    assert_eq!(
+        json["detail"],
+        serde_json::json!({"format": "markdown", "content": "**4**", "image": "/tmp/plot.png"})
+    );
+    assert_eq!(serde_json::from_value::<Match>(json).unwrap(), item);
+
+    // matches without a detail and details without a format still parse
+    let plain = serde_json::to_value(Match::default()).unwrap();
+    assert!(plain.get("detail").is_none());
+    let detail: Detail = serde_json::from_str(r#"{"content":"4"}"#).unwrap();
+    assert_eq!(detail.format, DetailFormat::Text);
+    assert_eq!(detail, Detail::text("4"));
+}
+
+#[test]
+fn test_details_serialization() {
+    let method = Method::Details {
+        generation: 3,
+        match_id: 1,
+    };
+    let json = serde_json::to_value(&method).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"method": "details", "params": {"generation": 3, "match_id": 1}})
+    );
+    assert_eq!(serde_json::from_value::<Method>(json).unwrap(), method);
+
+    let describe = Method::Describe(Box::new(Match {
+        title: "notes.md".to_string(),
+        ..Default::default()
+    }));
+    let json = serde_json::to_string(&describe).unwrap();
+    assert_eq!(serde_json::from_str::<Method>(&json).unwrap(), describe);
+
+    let result = MethodResult::Details {
+        detail: Some(Detail::markdown("# Notes")),
+    };
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["type"], "details");
+    assert_eq!(json["detail"]["format"], "markdown");
+    assert_eq!(
+        serde_json::from_value::<MethodResult>(json).unwrap(),
+        result
+    );
+}
+
+#[test]
+fn test_icon_serialization() {
+    let icon = Icon::freedesktop("firefox");
+    let json = serde_json::to_string(&icon).unwrap();
+    assert_eq!(json, r#"{"type":"freedesktop","name":"firefox"}"#);
+    assert_eq!(serde_json::from_str::<Icon>(&json).unwrap(), icon);
+    assert_eq!(
+        serde_json::from_str::<Icon>(r#"{"type":"emoji","emoji":"📋"}"#).unwrap(),
+        Icon::emoji("📋")
+    );
+    assert_eq!(
+        serde_json::to_string(&Icon::thumbnail("/home/me/photo.jpg")).unwrap(),
+        r#"{"type":"thumbnail","path":"/home/me/photo.jpg"}"#
+    );
+    assert_eq!(
+        serde_json::from_str::<Icon>(r#"{"type":"thumbnail","path":"/a.mp4"}"#).unwrap(),
+        Icon::thumbnail("/a.mp4")
+    );
+    assert_eq!(
+        serde_json::from_str::<Icon>(r#"{"type":"bundled","name":"trash.svg"}"#).unwrap(),
+        Icon::bundled("trash.svg")
+    );
+    assert_eq!(
+        serde_json::to_string(&Icon::data("image/png", "iVBORw0KGgo=")).unwrap(),
+        r#"{"type":"data","mime":"image/png","data":"iVBORw0KGgo="}"#
+    );
+
+    // plugins predating icon objects send a path or a theme icon name
+    assert_eq!(
+        serde_json::from_str::<Icon>(r#""/usr/share/pixmaps/app.png""#).unwrap(),
+        Icon::path("/usr/share/pixmaps/app.png")
+    );
+    assert_eq!(
+        serde_json::from_str::<Icon>(r#""file:///tmp/thumb.png""#).unwrap(),
+        Icon::path("file:///tmp/thumb.png")
+    );
+    assert_eq!(
+        serde_json::from_str::<Icon>(r#""software-update-available""#).unwrap(),
+        Icon::freedesktop("software-update-available")
+    );
+}
@@ -0,0 +1,107 @@
+use glimpse_sdk::{Action, AlternateAction, MatchAction, Method, Modifiers};
+
+fn create_action() -> MatchAction {
+    MatchAction {
+        title: "Open".to_string(),
+        action: Action::Open {
+            uri: "file:///tmp/report.pdf".to_string(),
+        },
+        close_on_action: true,
+        alternates: vec![AlternateAction {
+            modifiers: Modifiers {
+                shift: true,
+                ..Default::default()
+            },
+            title: "Copy path".to_string(),
+            action: Action::Clipboard {
+                text: "/tmp/report.pdf".into(),
+            },
+        }],
+        requires_confirmation: false,
+        confirmation_prompt: None,
+        expand: vec![],
+    }
+}
+
+#[test]
+fn test_activate_without_modifiers_deserialization() {
+    let json = r#"{"method":"activate","params":{"generation":3,"match_id":1,"action":0}}"#;
+    let method: Method = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        method,
+        Method::Activate {
+            generation: 3,
+            match_id: 1,
+            action: 0,
+            modifiers: Modifiers::default(),
+            confirmed: false,
+        }
+    );
+}
+
+#[test]
+fn test_activate_with_modifiers_roundtrip() {
+    let method = Method::Activate {
+        generation: 3,
+        match_id: 1,
+        action: 0,
+        modifiers: Modifiers {
+            shift: true,
+            ctrl: true,
+            alt: false,
+        },
+        confirmed: false,
+    };
+    let json = serde_json::to_string(&method).unwrap();
+    assert!(json.contains(r#""modifiers":{"shift":true,"ctrl":true,"alt":false}"#));
+    let deserialized: Method = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, method);
+}
+
+#[test]
+fn test_partial_modifiers_deserialization() {
+    let modifiers: Modifiers = serde_json::from_str(r#"{"alt":true}"#).unwrap();
+    assert_eq!(
+        modifiers,
+        Modifiers {
+            alt: true,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_action_for_picks_matching_alternate() {
+    let action = create_action();
+    let shift = Modifiers {
+        shift: true,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        action.action_for(&shift),
+        &Action::Clipboard {
+            text: "/tmp/report.pdf".into()
+        }
+    );
+}
+
+#[test]
+fn test_action_for_falls_back_to_default() {
+    let action = create_action();
+    let ctrl = Modifiers {
+        ctrl: true,
+        ..Default::default()
+    };
+
+    assert_eq!(action.action_for(&Modifiers::default()), &action.action);
+    assert_eq!(action.action_for(&ctrl), &action.action);
+}
+
+#[test]
+fn test_match_action_without_alternates_omits_field() {
+    let mut action = create_action();
+    action.alternates.clear();
+    let json = serde_json::to_string(&action).unwrap();
+    assert!(!json.contains("alternates"));
+}
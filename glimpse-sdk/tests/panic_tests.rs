@@ -0,0 +1,42 @@
+use glimpse_sdk::{Message, MethodResult, PluginError, RequestTable, catch_panic};
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn test_panicking_request_is_answered_with_an_error() {
+    let (tx, mut rx) = mpsc::channel(10);
+    let mut table = RequestTable::new("test".to_string(), tx, 1);
+
+    table.start(1, async { panic!("index out of bounds") });
+    match rx.recv().await.unwrap() {
+        Message::Response {
+            id, error, result, ..
+        } => {
+            assert_eq!(id, 1);
+            assert!(result.is_none());
+            assert!(error.unwrap().message.contains("index out of bounds"));
+        }
+        other => panic!("expected a response, got {:?}", other),
+    }
+
+    // the panic released its slot, later requests still run
+    table.start(2, async { Ok(MethodResult::Done { truncated: false }) });
+    match rx.recv().await.unwrap() {
+        Message::Response { id, error, .. } => {
+            assert_eq!(id, 2);
+            assert!(error.is_none());
+        }
+        other => panic!("expected a response, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_catch_panic() {
+    let value = catch_panic(async { Ok::<_, PluginError>(3) }).await;
+    assert_eq!(value.unwrap(), 3);
+
+    let formatted = catch_panic::<()>(async { panic!("bad query {}", 7) }).await;
+    assert!(matches!(formatted, Err(PluginError::Panicked(message)) if message == "bad query 7"));
+
+    let error = catch_panic::<()>(async { Err(PluginError::Other("failed".to_string())) }).await;
+    assert!(matches!(error, Err(PluginError::Other(_))));
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use glimpse_sdk::{
+    Action, ActionProgress, Match, Message, Metadata, Method, Plugin, PluginError, Progress,
+};
+use tokio::sync::mpsc;
+
+struct ExtractingPlugin;
+
+#[async_trait]
+impl Plugin for ExtractingPlugin {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            id: "test.extracting".to_string(),
+            name: "Extracting".to_string(),
+            version: "0.1.0".to_string(),
+            ..Default::default()
+        }
+    }
+
+    async fn handle_search(&self, _query: String) -> Result<Vec<Match>, PluginError> {
+        Ok(vec![])
+    }
+
+    async fn call_action(
+        &self,
+        _action: String,
+        params: HashMap<String, String>,
+        progress: &Progress,
+    ) -> Result<(), PluginError> {
+        for pct in [0, 50, 250] {
+            progress.report(pct, &params["file"]).await?;
+        }
+        Ok(())
+    }
+}
+
+struct QuietPlugin;
+
+#[async_trait]
+impl Plugin for QuietPlugin {
+    fn metadata(&self) -> Metadata {
+        ExtractingPlugin.metadata()
+    }
+
+    async fn handle_search(&self, _query: String) -> Result<Vec<Match>, PluginError> {
+        Ok(vec![])
+    }
+}
+
+fn create_progress() -> (Progress, mpsc::Receiver<Message>) {
+    let (tx, rx) = mpsc::channel(10);
+    (Progress::new(5, "test.extracting".to_string(), tx), rx)
+}
+
+fn reported(message: Message) -> ActionProgress {
+    match message {
+        Message::Notification {
+            method: Method::ActionProgress(progress),
+            plugin_id,
+        } => {
+            assert_eq!(plugin_id.as_deref(), Some("test.extracting"));
+            progress
+        }
+        other => panic!("expected action progress, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_progress_is_reported_under_action_id() {
+    let (progress, mut rx) = create_progress();
+    let params = HashMap::from([("file".to_string(), "movie.mkv".to_string())]);
+
+    ExtractingPlugin
+        .call_action("extract".to_string(), params, &progress)
+        .await
+        .unwrap();
+    drop(progress);
+
+    for pct in [0, 50, 100] {
+        assert_eq!(
+            reported(rx.recv().await.unwrap()),
+            ActionProgress {
+                action_id: 5,
+                pct,
+                message: "movie.mkv".to_string(),
+            }
+        );
+    }
+    assert!(rx.recv().await.is_none());
+}
+
+#[tokio::test]
+async fn test_default_call_action_reports_nothing() {
+    let (progress, mut rx) = create_progress();
+
+    QuietPlugin
+        .call_action("noop".to_string(), HashMap::new(), &progress)
+        .await
+        .unwrap();
+    drop(progress);
+
+    assert!(rx.recv().await.is_none());
+}
+
+#[test]
+fn test_action_progress_wire_format() {
+    let message = Message::Notification {
+        method: Method::ActionProgress(ActionProgress {
+            action_id: 3,
+            pct: 40,
+            message: "Extracting".to_string(),
+        }),
+        plugin_id: None,
+    };
+    let json = serde_json::to_value(&message).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "action_progress",
+            "params": {"action_id": 3, "pct": 40, "message": "Extracting"},
+            "plugin_id": null,
+        })
+    );
+
+    let cancel: Message = serde_json::from_str(
+        r#"{"id": 9, "method": "cancel_action", "params": {"action_id": 3}, "plugin_id": null}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        cancel,
+        Message::Request {
+            id: 9,
+            method: Method::CancelAction { action_id: 3 },
+            plugin_id: None,
+            deadline_ms: None,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_run_asks_the_daemon_to_carry_out_an_action() {
+    let (progress, mut rx) = create_progress();
+
+    progress
+        .run(Action::ExpiringClipboard {
+            text: "hunter2".into(),
+            clear_after_secs: 30,
+        })
+        .await
+        .unwrap();
+
+    match rx.recv().await.unwrap() {
+        Message::Notification {
+            method: Method::RunAction(Action::ExpiringClipboard { text, .. }),
+            plugin_id,
+        } => {
+            assert_eq!(plugin_id.as_deref(), Some("test.extracting"));
+            assert_eq!(text.expose(), "hunter2");
+        }
+        other => panic!("expected an action to run, got {:?}", other),
+    }
+}
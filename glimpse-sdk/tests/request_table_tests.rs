@@ -0,0 +1,129 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use glimpse_sdk::{Message, Method, MethodResult, RequestTable};
+use tokio::sync::{mpsc, oneshot};
+
+fn response_id(message: &Message) -> (usize, bool) {
+    match message {
+        Message::Response { id, error, .. } => (*id, error.is_some()),
+        other => panic!("expected a response, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_requests_run_concurrently() {
+    let (tx, mut rx) = mpsc::channel(10);
+    let mut table = RequestTable::new("test".to_string(), tx, 4);
+
+    // the first request waits for the second, which only finishes if both run at once
+    let (done_tx, done_rx) = oneshot::channel();
+    table.start(1, async move {
+        done_rx.await.unwrap();
+        Ok(MethodResult::Done { truncated: false })
+    });
+    table.start(2, async move {
+        done_tx.send(()).unwrap();
+        Ok(MethodResult::Done { truncated: false })
+    });
+
+    let first = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await;
+    let second = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await;
+    assert_eq!(response_id(&first.unwrap().unwrap()), (2, false));
+    assert_eq!(response_id(&second.unwrap().unwrap()), (1, false));
+}
+
+#[tokio::test]
+async fn test_concurrency_limit() {
+    let (tx, mut rx) = mpsc::channel(10);
+    let mut table = RequestTable::new("test".to_string(), tx, 2);
+    let active = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+
+    for id in 0..6 {
+        let active = active.clone();
+        let peak = peak.clone();
+        table.start(id, async move {
+            let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            active.fetch_sub(1, Ordering::SeqCst);
+            Ok(MethodResult::Done { truncated: false })
+        });
+    }
+
+    for _ in 0..6 {
+        let response = rx.recv().await.unwrap();
+        assert!(!response_id(&response).1);
+    }
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+    assert!(table.is_empty());
+}
+
+#[tokio::test]
+async fn test_cancel_targets_one_request() {
+    let (tx, mut rx) = mpsc::channel(10);
+    let mut table = RequestTable::new("test".to_string(), tx, 4);
+    let (release_tx, release_rx) = oneshot::channel::<()>();
+
+    table.start(1, async move {
+        std::future::pending::<()>().await;
+        Ok(MethodResult::Done { truncated: false })
+    });
+    table.start(2, async move {
+        release_rx.await.unwrap();
+        Ok(MethodResult::Done { truncated: false })
+    });
+
+    assert!(table.cancel(1));
+    assert!(!table.cancel(1));
+    assert_eq!(response_id(&rx.recv().await.unwrap()), (1, true));
+
+    release_tx.send(()).unwrap();
+    assert_eq!(response_id(&rx.recv().await.unwrap()), (2, false));
+}
+
+#[tokio::test]
+async fn test_waiting_requests_can_be_cancelled() {
+    let (tx, mut rx) = mpsc::channel(10);
+    let mut table = RequestTable::new("test".to_string(), tx, 1);
+    let ran = Arc::new(AtomicUsize::new(0));
+
+    table.start(1, async move {
+        std::future::pending::<()>().await;
+        Ok(MethodResult::Done { truncated: false })
+    });
+    let counter = ran.clone();
+    table.start(2, async move {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Ok(MethodResult::Done { truncated: false })
+    });
+    assert_eq!(table.len(), 2);
+
+    assert!(table.cancel(2));
+    assert_eq!(response_id(&rx.recv().await.unwrap()), (2, true));
+    assert_eq!(ran.load(Ordering::SeqCst), 0);
+
+    table.cancel_all();
+    assert_eq!(response_id(&rx.recv().await.unwrap()), (1, true));
+}
+
+#[test]
+fn test_cancel_wire_format() {
+    let targeted = serde_json::to_value(Method::Cancel(Some(7))).unwrap();
+    assert_eq!(
+        targeted,
+        serde_json::json!({"method": "cancel", "params": 7})
+    );
+
+    // peers predating targeted cancels send no params
+    let bare = serde_json::from_str::<Method>(r#"{"method": "cancel"}"#).unwrap();
+    assert_eq!(bare, Method::Cancel(None));
+    let null = serde_json::from_str::<Method>(r#"{"method": "cancel", "params": null}"#).unwrap();
+    assert_eq!(null, Method::Cancel(None));
+}
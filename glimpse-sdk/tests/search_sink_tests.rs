@@ -0,0 +1,170 @@
+use async_trait::async_trait;
+use glimpse_sdk::{
+    Detail, Match, Message, Metadata, MethodResult, Plugin, PluginError, RecentQueries, SearchSink,
+};
+use tokio::sync::mpsc;
+
+struct StaticPlugin;
+
+#[async_trait]
+impl Plugin for StaticPlugin {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            id: "test.static".to_string(),
+            name: "Static".to_string(),
+            version: "0.1.0".to_string(),
+            ..Default::default()
+        }
+    }
+
+    async fn handle_search(&self, query: String) -> Result<Vec<Match>, PluginError> {
+        match query.as_str() {
+            "fail" => Err(PluginError::Other("no results today".to_string())),
+            "none" => Ok(vec![]),
+            _ => Ok(vec![create_match("a"), create_match("b")]),
+        }
+    }
+}
+
+struct StreamingPlugin;
+
+#[async_trait]
+impl Plugin for StreamingPlugin {
+    fn metadata(&self) -> Metadata {
+        StaticPlugin.metadata()
+    }
+
+    async fn search(&self, _query: String, sink: &SearchSink) -> Result<(), PluginError> {
+        for title in ["first", "second", "third"] {
+            sink.send(vec![create_match(title)]).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_search(&self, _query: String) -> Result<Vec<Match>, PluginError> {
+        unreachable!("streaming plugins answer through search()")
+    }
+}
+
+fn create_match(title: &str) -> Match {
+    Match {
+        title: title.to_string(),
+        ..Default::default()
+    }
+}
+
+fn create_sink() -> (SearchSink, mpsc::Receiver<Message>) {
+    let (tx, rx) = mpsc::channel(10);
+    (SearchSink::new(3, "test.static".to_string(), tx), rx)
+}
+
+fn chunk_titles(message: Message) -> Vec<String> {
+    match message {
+        Message::Response {
+            id,
+            result: Some(MethodResult::Matches { items }),
+            plugin_id,
+            ..
+        } => {
+            assert_eq!(id, 3);
+            assert_eq!(plugin_id.as_deref(), Some("test.static"));
+            items.into_iter().map(|m| m.title).collect()
+        }
+        other => panic!("expected matches chunk, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_default_search_sends_single_chunk() {
+    let (sink, mut rx) = create_sink();
+
+    StaticPlugin.search("x".to_string(), &sink).await.unwrap();
+    drop(sink);
+
+    assert_eq!(chunk_titles(rx.recv().await.unwrap()), vec!["a", "b"]);
+    assert!(rx.recv().await.is_none());
+}
+
+#[tokio::test]
+async fn test_default_search_skips_empty_chunk() {
+    let (sink, mut rx) = create_sink();
+
+    StaticPlugin
+        .search("none".to_string(), &sink)
+        .await
+        .unwrap();
+    drop(sink);
+
+    assert!(rx.recv().await.is_none());
+}
+
+#[tokio::test]
+async fn test_default_search_propagates_errors() {
+    let (sink, _rx) = create_sink();
+
+    let err = StaticPlugin
+        .search("fail".to_string(), &sink)
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("no results today"));
+}
+
+#[tokio::test]
+async fn test_streaming_search_sends_chunks_in_order() {
+    let (sink, mut rx) = create_sink();
+
+    StreamingPlugin
+        .search("x".to_string(), &sink)
+        .await
+        .unwrap();
+    drop(sink);
+
+    for expected in ["first", "second", "third"] {
+        assert_eq!(chunk_titles(rx.recv().await.unwrap()), vec![expected]);
+    }
+    assert!(rx.recv().await.is_none());
+}
+
+#[tokio::test]
+async fn test_default_search_page_slices_matches() {
+    let (sink, mut rx) = create_sink();
+
+    StaticPlugin
+        .search_page("x".to_string(), 1, 5, &sink)
+        .await
+        .unwrap();
+    drop(sink);
+
+    assert_eq!(chunk_titles(rx.recv().await.unwrap()), vec!["b"]);
+    assert!(rx.recv().await.is_none());
+}
+
+#[test]
+fn test_recent_queries_keep_the_latest() {
+    let mut queries = RecentQueries::new();
+    for id in 0..=RecentQueries::CAPACITY {
+        queries.remember(id, format!("query {}", id));
+    }
+
+    assert_eq!(queries.get(0), None);
+    assert_eq!(queries.get(1), Some("query 1"));
+    assert_eq!(
+        queries.get(RecentQueries::CAPACITY),
+        Some(format!("query {}", RecentQueries::CAPACITY).as_str())
+    );
+}
+
+#[tokio::test]
+async fn test_default_details_keep_the_match_detail() {
+    assert!(!StaticPlugin.lazy_details());
+    let item = Match {
+        detail: Some(Detail::text("full text")),
+        ..create_match("a")
+    };
+    assert_eq!(
+        StaticPlugin.details(item).await.unwrap(),
+        Some(Detail::text("full text"))
+    );
+    assert_eq!(StaticPlugin.details(create_match("b")).await.unwrap(), None);
+}
@@ -0,0 +1,48 @@
+use glimpse_sdk::{Action, REDACTED, Sensitive};
+use tracing_test::traced_test;
+use zeroize::Zeroize;
+
+#[test]
+fn test_debug_and_display_are_redacted() {
+    let secret = Sensitive::new("hunter2".to_string());
+
+    assert_eq!(format!("{:?}", secret), REDACTED);
+    assert_eq!(secret.to_string(), REDACTED);
+    assert_eq!(secret.expose(), "hunter2");
+}
+
+#[test]
+fn test_serialized_as_bare_value() {
+    let action = Action::Clipboard {
+        text: "hunter2".into(),
+    };
+
+    let json = serde_json::to_value(&action).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"type": "clipboard", "text": "hunter2"})
+    );
+    assert_eq!(serde_json::from_value::<Action>(json).unwrap(), action);
+}
+
+#[test]
+fn test_zeroize_wipes_value() {
+    let mut secret = Sensitive::new("hunter2".to_string());
+
+    secret.zeroize();
+
+    assert!(secret.expose().is_empty());
+}
+
+#[test]
+#[traced_test]
+fn test_actions_log_without_secret() {
+    let action = Action::Clipboard {
+        text: "hunter2".into(),
+    };
+
+    tracing::info!("dispatching {:?}", action);
+
+    assert!(logs_contain(REDACTED));
+    assert!(!logs_contain("hunter2"));
+}
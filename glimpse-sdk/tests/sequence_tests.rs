@@ -0,0 +1,104 @@
+use glimpse_sdk::{
+    Action, MAX_SEQUENCE_DEPTH, MAX_SEQUENCE_STEPS, MethodResult, SequenceError, StepStatus,
+};
+
+fn open(uri: &str) -> Action {
+    Action::Open {
+        uri: uri.to_string(),
+    }
+}
+
+fn sequence(actions: Vec<Action>) -> Action {
+    Action::Sequence { actions }
+}
+
+#[test]
+fn test_sequence_serde() {
+    let action = sequence(vec![
+        Action::Clipboard {
+            text: "hello".into(),
+        },
+        open("https://example.com"),
+    ]);
+    let json = serde_json::to_value(&action).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "type": "sequence",
+            "actions": [
+                {"type": "clipboard", "text": "hello"},
+                {"type": "open", "uri": "https://example.com"},
+            ],
+        })
+    );
+    assert_eq!(serde_json::from_value::<Action>(json).unwrap(), action);
+}
+
+#[test]
+fn test_steps_flatten_nested_sequences() {
+    let action = sequence(vec![
+        open("a"),
+        sequence(vec![open("b"), sequence(vec![open("c")])]),
+        open("d"),
+    ]);
+    let steps = action.steps().unwrap();
+    assert_eq!(steps, vec![&open("a"), &open("b"), &open("c"), &open("d")]);
+
+    // other actions are a step of their own
+    assert_eq!(open("a").steps().unwrap(), vec![&open("a")]);
+}
+
+#[test]
+fn test_steps_limits() {
+    assert_eq!(sequence(vec![]).steps(), Err(SequenceError::Empty));
+    assert_eq!(
+        sequence(vec![sequence(vec![])]).steps(),
+        Err(SequenceError::Empty)
+    );
+
+    let longest = sequence(vec![open("a"); MAX_SEQUENCE_STEPS]);
+    assert_eq!(longest.steps().unwrap().len(), MAX_SEQUENCE_STEPS);
+    let nested = sequence(vec![longest.clone(), open("b")]);
+    assert_eq!(
+        nested.steps(),
+        Err(SequenceError::TooManySteps {
+            steps: MAX_SEQUENCE_STEPS + 1,
+        })
+    );
+
+    let mut deepest = open("a");
+    for _ in 0..MAX_SEQUENCE_DEPTH {
+        deepest = sequence(vec![deepest]);
+    }
+    assert_eq!(deepest.steps().unwrap(), vec![&open("a")]);
+    assert_eq!(sequence(vec![deepest]).steps(), Err(SequenceError::TooDeep));
+}
+
+#[test]
+fn test_step_statuses_serde() {
+    let result = MethodResult::Sequence {
+        steps: vec![
+            StepStatus::Done,
+            StepStatus::Failed {
+                message: "io: not found".to_string(),
+            },
+            StepStatus::Skipped,
+        ],
+    };
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "type": "sequence",
+            "steps": [
+                {"status": "done"},
+                {"status": "failed", "message": "io: not found"},
+                {"status": "skipped"},
+            ],
+        })
+    );
+    assert_eq!(
+        serde_json::from_value::<MethodResult>(json).unwrap(),
+        result
+    );
+}
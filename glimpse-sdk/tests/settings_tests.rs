@@ -0,0 +1,75 @@
+use std::sync::{Arc, Mutex};
+
+use glimpse_sdk::{Method, Settings};
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+struct TestSettings {
+    limit: usize,
+    roots: Vec<String>,
+}
+
+#[test]
+fn test_apply_replaces_values() {
+    let settings = Settings::<TestSettings>::default();
+
+    settings
+        .apply(json!({"limit": 5, "roots": ["~/src"]}))
+        .unwrap();
+
+    assert_eq!(
+        settings.get(),
+        TestSettings {
+            limit: 5,
+            roots: vec!["~/src".to_string()],
+        }
+    );
+}
+
+#[test]
+fn test_apply_keeps_values_on_invalid_config() {
+    let settings = Settings::new(TestSettings {
+        limit: 3,
+        roots: vec![],
+    });
+
+    assert!(settings.apply(json!({"limit": "many"})).is_err());
+    assert_eq!(settings.get().limit, 3);
+}
+
+#[test]
+fn test_on_change_runs_callbacks_with_new_values() {
+    let settings = Settings::<TestSettings>::default();
+    let seen = Arc::new(Mutex::new(vec![]));
+    let seen_clone = seen.clone();
+    settings.on_change(move |settings| seen_clone.lock().unwrap().push(settings.limit));
+
+    settings.apply(json!({"limit": 1})).unwrap();
+    let _ = settings.apply(json!({"limit": -1}));
+    settings.apply(json!({"limit": 2})).unwrap();
+
+    assert_eq!(*seen.lock().unwrap(), vec![1, 2]);
+}
+
+#[test]
+fn test_clones_share_state() {
+    let settings = Settings::<TestSettings>::default();
+    let clone = settings.clone();
+
+    settings.apply(json!({"limit": 7})).unwrap();
+
+    assert_eq!(clone.get().limit, 7);
+}
+
+#[test]
+fn test_config_methods_round_trip() {
+    for method in [
+        Method::ConfigChanged(json!({"limit": 7})),
+        Method::GetConfig,
+    ] {
+        let encoded = serde_json::to_string(&method).unwrap();
+        assert_eq!(serde_json::from_str::<Method>(&encoded).unwrap(), method);
+    }
+}
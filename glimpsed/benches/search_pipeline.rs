@@ -0,0 +1,105 @@
+//! Daemon-side cost of answering one query, from routing to the final snapshot.
+//!
+//! A broadcast query goes through every plugin's results and ranks them together, while a
+//! prefixed query only touches the one plugin it was routed to. Wall-clock latency improves
+//! further than shown here, as the prefixed search does not wait for the slowest plugin.
+
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use glimpse_devtools::synthetic::SyntheticSpec;
+use glimpse_sdk::{Match, Message, Metadata, MethodResult};
+use glimpsed::{
+    matches::MatchStore,
+    routing::{Route, route},
+};
+
+const PLUGINS: usize = 8;
+const MATCHES_PER_PLUGIN: usize = 50;
+
+fn create_plugins() -> Vec<Metadata> {
+    (0..PLUGINS)
+        .map(|i| Metadata {
+            id: format!("plugin.{}", i),
+            prefix: (i == 0).then(|| "=".to_string()),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn create_matches(plugin: usize) -> Vec<Match> {
+    let spec = SyntheticSpec {
+        seed: plugin as u64,
+        results: MATCHES_PER_PLUGIN,
+        description_len: 11,
+        ..Default::default()
+    };
+    spec.matches("2+2")
+}
+
+/// Store every chunk, serialize it like the stdout task does and finish with a snapshot.
+fn answer(store: &mut MatchStore, chunks: &[(String, Vec<Match>)], passthrough: bool) -> usize {
+    store.reset(1);
+    store.set_passthrough(passthrough);
+    for (plugin_id, _) in chunks {
+        store.expect(plugin_id);
+    }
+
+    let mut written = 0;
+    for (plugin_id, items) in chunks {
+        let items = store.extend(1, plugin_id, items).unwrap();
+        let chunk = Message::Response {
+            id: 1,
+            error: None,
+            result: Some(MethodResult::Matches { items }),
+            plugin_id: Some(plugin_id.clone()),
+        };
+        written += serde_json::to_string(&chunk).unwrap().len();
+        store.finish(1, plugin_id);
+    }
+
+    let snapshot = Message::Response {
+        id: 1,
+        error: None,
+        result: Some(MethodResult::Snapshot {
+            items: store.snapshot(),
+        }),
+        plugin_id: None,
+    };
+    written + serde_json::to_string(&snapshot).unwrap().len()
+}
+
+fn bench_search_pipeline(c: &mut Criterion) {
+    let plugins = create_plugins();
+    let chunks = plugins
+        .iter()
+        .enumerate()
+        .map(|(i, metadata)| (metadata.id.clone(), create_matches(i)))
+        .collect::<Vec<_>>();
+    let mut store = MatchStore::new();
+
+    let mut group = c.benchmark_group("search_pipeline");
+    group.bench_function("broadcast", |b| {
+        b.iter(|| {
+            assert_eq!(route(black_box("2+2"), &plugins), Route::Broadcast);
+            answer(&mut store, black_box(&chunks), false)
+        })
+    });
+    group.bench_function("prefixed", |b| {
+        b.iter(|| {
+            let Route::Prefixed { plugin_id, .. } = route(black_box("=2+2"), &plugins) else {
+                panic!("expected the prefixed route");
+            };
+            let routed = chunks
+                .iter()
+                .filter(|(id, _)| *id == plugin_id)
+                .cloned()
+                .collect::<Vec<_>>();
+            answer(&mut store, black_box(&routed), true)
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_search_pipeline);
+criterion_main!(benches);
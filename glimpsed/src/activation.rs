@@ -0,0 +1,156 @@
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+#[cfg(unix)]
+use std::os::{
+    fd::{FromRawFd, RawFd},
+    unix::process::CommandExt,
+};
+
+use serde::Deserialize;
+
+use crate::transport::ClientListener;
+
+/// The first descriptor systemd passes, see sd_listen_fds(3).
+#[cfg(unix)]
+const LISTEN_FDS_START: RawFd = 3;
+
+/// Process creation flags starting the daemon without a console, in a group of its own.
+#[cfg(windows)]
+const DETACHED_PROCESS: u32 = 0x0000_0008;
+#[cfg(windows)]
+const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+
+/// How long `--daemonize` waits for the started daemon to accept clients.
+const START_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ActivationConfig {
+    /// Seconds without a connected client before a daemon started on demand, by socket
+    /// activation or `--daemonize`, exits. 0 keeps it running.
+    pub idle_exit_secs: u64,
+}
+
+impl Default for ActivationConfig {
+    fn default() -> Self {
+        Self {
+            idle_exit_secs: 600,
+        }
+    }
+}
+
+impl ActivationConfig {
+    /// `None` when the daemon is kept running.
+    pub fn idle_exit(&self) -> Option<Duration> {
+        (self.idle_exit_secs > 0).then(|| Duration::from_secs(self.idle_exit_secs))
+    }
+}
+
+/// Descriptors systemd passed to the process `pid`, from the values of `LISTEN_PID` and
+/// `LISTEN_FDS`. Those meant for another process, such as the parent, are not ours.
+pub fn passed_fds(pid: u32, listen_pid: Option<&str>, listen_fds: Option<&str>) -> usize {
+    if listen_pid.and_then(|listen_pid| listen_pid.parse::<u32>().ok()) != Some(pid) {
+        return 0;
+    }
+    listen_fds
+        .and_then(|listen_fds| listen_fds.parse().ok())
+        .unwrap_or(0)
+}
+
+/// The client socket systemd listens on for the daemon, if it was started by socket
+/// activation. Only the first passed descriptor is used.
+#[cfg(unix)]
+pub fn activated_listener() -> std::io::Result<Option<ClientListener>> {
+    let passed = passed_fds(
+        std::process::id(),
+        std::env::var("LISTEN_PID").ok().as_deref(),
+        std::env::var("LISTEN_FDS").ok().as_deref(),
+    );
+    if passed == 0 {
+        return Ok(None);
+    }
+    if passed > 1 {
+        tracing::warn!("systemd passed {} sockets, using the first", passed);
+    }
+    // SAFETY: systemd hands the descriptors over to this process, nothing else owns them.
+    let listener = unsafe { std::os::unix::net::UnixListener::from_raw_fd(LISTEN_FDS_START) };
+    // plugins are not meant to inherit the socket
+    // SAFETY: fcntl only changes the flags of a descriptor this process owns.
+    if unsafe { libc::fcntl(LISTEN_FDS_START, libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    listener.set_nonblocking(true)?;
+    tokio::net::UnixListener::from_std(listener).map(|listener| Some(listener.into()))
+}
+
+/// Windows has no socket activation, the daemon always binds its pipe itself.
+#[cfg(windows)]
+pub fn activated_listener() -> std::io::Result<Option<ClientListener>> {
+    Ok(None)
+}
+
+/// Start `daemon` listening on `socket` in a session of its own, detached from the terminal,
+/// and wait until it accepts clients. A daemon already listening there is left alone.
+pub fn daemonize(daemon: &Path, socket: &Path) -> std::io::Result<()> {
+    if is_listening(socket) {
+        tracing::info!("a daemon already listens on {:?}", socket);
+        return Ok(());
+    }
+    let mut command = Command::new(daemon);
+    command
+        .args(["--listen", "--exit-when-idle"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // SAFETY: setsid is async-signal-safe and touches no memory of the parent.
+    #[cfg(unix)]
+    unsafe {
+        command.pre_exec(|| match libc::setsid() {
+            -1 => Err(std::io::Error::last_os_error()),
+            _ => Ok(()),
+        });
+    }
+    #[cfg(windows)]
+    command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    let mut child = command.spawn()?;
+
+    let started = Instant::now();
+    while started.elapsed() < START_TIMEOUT {
+        if is_listening(socket) {
+            tracing::info!("started daemon {} on {:?}", child.id(), socket);
+            return Ok(());
+        }
+        if let Some(status) = child.try_wait()? {
+            return Err(std::io::Error::other(format!(
+                "daemon exited before listening: {}",
+                status
+            )));
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::TimedOut,
+        format!("daemon did not listen on {:?} in time", socket),
+    ))
+}
+
+#[cfg(unix)]
+fn is_listening(socket: &Path) -> bool {
+    std::os::unix::net::UnixStream::connect(socket).is_ok()
+}
+
+/// Opening a named pipe takes one of its free instances, a listening daemon always has one.
+#[cfg(windows)]
+fn is_listening(socket: &Path) -> bool {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(socket)
+        .is_ok()
+}
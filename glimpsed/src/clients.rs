@@ -0,0 +1,404 @@
+use std::{collections::HashMap, sync::Arc};
+
+use glimpse_sdk::Message;
+use tokio::sync::Mutex;
+
+use crate::{
+    janitor::Reclaim,
+    matches::{MatchId, MatchStore},
+    outbox::{Outbox, OutboxConfig},
+    subscriptions::SubscriptionRegistry,
+};
+
+pub type ClientId = usize;
+
+/// Request ids as plugins see them.
+///
+/// Every client numbers its requests on its own, so requests forwarded to plugins get an id
+/// that is unique across clients, and answers are mapped back to the client's own id.
+/// Id 0 is never assigned, the daemon uses it for requests of its own.
+#[derive(Debug, Default)]
+pub struct RequestIds {
+    last: usize,
+    routes: HashMap<usize, (ClientId, usize)>,
+}
+
+impl RequestIds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    /// Daemon-wide id for request `id` of `client`.
+    pub fn assign(&mut self, client: ClientId, id: usize) -> usize {
+        self.last += 1;
+        self.routes.insert(self.last, (client, id));
+        self.last
+    }
+
+    /// Daemon-wide id for a request the daemon makes on a client's behalf, routed to no
+    /// client request.
+    pub fn reserve(&mut self) -> usize {
+        self.last += 1;
+        self.last
+    }
+
+    /// The client and its request id behind a daemon-wide id.
+    pub fn resolve(&self, global: usize) -> Option<(ClientId, usize)> {
+        self.routes.get(&global).copied()
+    }
+
+    /// Daemon-wide id of request `id` of `client`, if it was forwarded to plugins.
+    pub fn find(&self, client: ClientId, id: usize) -> Option<usize> {
+        self.routes
+            .iter()
+            .find(|(_, route)| **route == (client, id))
+            .map(|(global, _)| *global)
+    }
+
+    pub fn release(&mut self, global: usize) -> Option<(ClientId, usize)> {
+        self.routes.remove(&global)
+    }
+
+    /// Forget every request of a client that went away, returning their daemon-wide ids.
+    pub fn release_client(&mut self, client: ClientId) -> Vec<usize> {
+        let mut released = vec![];
+        self.routes.retain(|global, (owner, _)| {
+            let keep = *owner != client;
+            if !keep {
+                released.push(*global);
+            }
+            keep
+        });
+        released
+    }
+}
+
+/// What the daemon keeps for a connected client.
+pub struct Session {
+    pub outbox: Arc<Outbox>,
+    pub matches: Arc<Mutex<MatchStore>>,
+    pub subscriptions: SubscriptionRegistry,
+    /// Daemon-wide id of the client's running search.
+    pub search: Option<usize>,
+    /// Callback actions still running, by request id, with the key of the plugin running them.
+    pub actions: HashMap<usize, String>,
+    /// Details requests forwarded to plugins, by request id, with the key of the plugin.
+    pub details: HashMap<usize, String>,
+    /// Details the daemon asked plugins for ahead of the client, by daemon-wide id, with the
+    /// key of the plugin and the generation and id of the match.
+    pub prefetches: HashMap<usize, (String, usize, MatchId)>,
+}
+
+impl Session {
+    pub fn new(outbox: OutboxConfig) -> Self {
+        Self {
+            outbox: Arc::new(Outbox::new(outbox)),
+            matches: Arc::new(Mutex::new(MatchStore::new())),
+            subscriptions: SubscriptionRegistry::new(),
+            search: None,
+            actions: HashMap::new(),
+            details: HashMap::new(),
+            prefetches: HashMap::new(),
+        }
+    }
+}
+
+/// Connected clients and the requests they forwarded to plugins.
+#[derive(Default)]
+pub struct Sessions {
+    last: ClientId,
+    sessions: HashMap<ClientId, Session>,
+    ids: RequestIds,
+}
+
+impl Sessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    pub fn open(&mut self, outbox: OutboxConfig) -> (ClientId, &Session) {
+        self.last += 1;
+        let session = self
+            .sessions
+            .entry(self.last)
+            .or_insert_with(|| Session::new(outbox));
+        (self.last, session)
+    }
+
+    /// End a session, forgetting the requests the client left behind.
+    pub fn close(&mut self, client: ClientId) -> Option<Session> {
+        self.ids.release_client(client);
+        self.sessions.remove(&client)
+    }
+
+    pub fn clients(&self) -> Vec<ClientId> {
+        self.sessions.keys().copied().collect()
+    }
+
+    pub fn get(&self, client: ClientId) -> Option<&Session> {
+        self.sessions.get(&client)
+    }
+
+    pub fn ids(&self) -> &RequestIds {
+        &self.ids
+    }
+
+    /// The client request id and session a daemon-wide id belongs to.
+    pub fn route(&self, global: usize) -> Option<(usize, &Session)> {
+        let (client, id) = self.ids.resolve(global)?;
+        Some((id, self.sessions.get(&client)?))
+    }
+
+    /// Make search `id` the client's running search. Returns its daemon-wide id and that of
+    /// the search it supersedes, `None` if the client is gone.
+    pub fn start_search(&mut self, client: ClientId, id: usize) -> Option<(usize, Option<usize>)> {
+        let session = self.sessions.get_mut(&client)?;
+        let global = self.ids.assign(client, id);
+        let previous = session.search.replace(global);
+        if let Some(previous) = previous {
+            self.ids.release(previous);
+        }
+        Some((global, previous))
+    }
+
+    /// Stop tracking the client's running search, returning its daemon-wide id.
+    pub fn end_search(&mut self, client: ClientId) -> Option<usize> {
+        let search = self.sessions.get_mut(&client)?.search.take()?;
+        self.ids.release(search);
+        Some(search)
+    }
+
+    /// Track callback action `id` of the client run by the plugin, returning the daemon-wide
+    /// id to run it with.
+    pub fn start_action(&mut self, client: ClientId, id: usize, plugin_key: &str) -> Option<usize> {
+        let session = self.sessions.get_mut(&client)?;
+        session.actions.insert(id, plugin_key.to_string());
+        Some(self.ids.assign(client, id))
+    }
+
+    /// The client action id and session of an action the plugin is running.
+    pub fn route_action(&self, plugin_key: &str, global: usize) -> Option<(usize, &Session)> {
+        let (id, session) = self.route(global)?;
+        (session.actions.get(&id)? == plugin_key).then_some((id, session))
+    }
+
+    /// Stop tracking an action the plugin finished. Returns the client and its action id.
+    pub fn end_action(&mut self, plugin_key: &str, global: usize) -> Option<(ClientId, usize)> {
+        let (client, id) = self.ids.resolve(global)?;
+        let session = self.sessions.get_mut(&client)?;
+        if session.actions.get(&id)? != plugin_key {
+            return None;
+        }
+        session.actions.remove(&id);
+        self.ids.release(global);
+        Some((client, id))
+    }
+
+    /// Track details request `id` of the client forwarded to the plugin, returning the
+    /// daemon-wide id to forward it with.
+    pub fn start_details(
+        &mut self,
+        client: ClientId,
+        id: usize,
+        plugin_key: &str,
+    ) -> Option<usize> {
+        let session = self.sessions.get_mut(&client)?;
+        session.details.insert(id, plugin_key.to_string());
+        Some(self.ids.assign(client, id))
+    }
+
+    /// Stop tracking a details request the plugin answered. Returns the client and its
+    /// request id.
+    pub fn end_details(&mut self, plugin_key: &str, global: usize) -> Option<(ClientId, usize)> {
+        let (client, id) = self.ids.resolve(global)?;
+        let session = self.sessions.get_mut(&client)?;
+        if session.details.get(&id)? != plugin_key {
+            return None;
+        }
+        session.details.remove(&id);
+        self.ids.release(global);
+        Some((client, id))
+    }
+
+    /// Track the detail of a match the client shows asked of the plugin ahead of the client,
+    /// returning the daemon-wide id to ask with.
+    pub fn start_prefetch(
+        &mut self,
+        client: ClientId,
+        plugin_key: &str,
+        generation: usize,
+        match_id: MatchId,
+    ) -> Option<usize> {
+        let session = self.sessions.get_mut(&client)?;
+        let global = self.ids.reserve();
+        session
+            .prefetches
+            .insert(global, (plugin_key.to_string(), generation, match_id));
+        Some(global)
+    }
+
+    /// Stop tracking a prefetched detail the plugin answered. Returns the client and the
+    /// generation and id of the match.
+    pub fn end_prefetch(
+        &mut self,
+        plugin_key: &str,
+        global: usize,
+    ) -> Option<(ClientId, usize, MatchId)> {
+        self.sessions.iter_mut().find_map(|(client, session)| {
+            let (key, generation, match_id) = session.prefetches.get(&global)?;
+            if key != plugin_key {
+                return None;
+            }
+            let found = (*client, *generation, *match_id);
+            session.prefetches.remove(&global);
+            Some(found)
+        })
+    }
+
+    /// The daemon-wide id and plugin key of the client's running action `id`, to cancel it.
+    /// The action stays tracked until the plugin answers.
+    pub fn find_action(&self, client: ClientId, id: usize) -> Option<(usize, String)> {
+        let plugin_key = self.sessions.get(&client)?.actions.get(&id)?.clone();
+        Some((self.ids.find(client, id)?, plugin_key))
+    }
+
+    /// Route the plugin's topic to subscription request `id` of the client, returning the
+    /// daemon-wide id to subscribe with.
+    pub fn subscribe(
+        &mut self,
+        client: ClientId,
+        plugin_key: &str,
+        topic: &str,
+        id: usize,
+    ) -> Option<usize> {
+        let session = self.sessions.get_mut(&client)?;
+        if let Some(replaced) = session.subscriptions.subscribe(plugin_key, topic, id)
+            && let Some(global) = self.ids.find(client, replaced)
+        {
+            self.ids.release(global);
+        }
+        Some(self.ids.assign(client, id))
+    }
+
+    /// Drop the client's subscription, returns true if no other client wants the topic
+    /// and the plugin can stop publishing it.
+    pub fn unsubscribe(&mut self, client: ClientId, plugin_key: &str, topic: &str) -> bool {
+        let Some(session) = self.sessions.get_mut(&client) else {
+            return false;
+        };
+        let Some(id) = session.subscriptions.unsubscribe(plugin_key, topic) else {
+            return false;
+        };
+        if let Some(global) = self.ids.find(client, id) {
+            self.ids.release(global);
+        }
+        !self.is_subscribed(plugin_key, topic)
+    }
+
+    /// Drop the subscription behind daemon-wide id `global`, e.g. after the plugin rejected it.
+    /// Returns the client, its request id and the topic.
+    pub fn remove_request(
+        &mut self,
+        plugin_key: &str,
+        global: usize,
+    ) -> Option<(ClientId, usize, String)> {
+        let (client, id) = self.ids.resolve(global)?;
+        let session = self.sessions.get_mut(&client)?;
+        let topic = session.subscriptions.remove_request(plugin_key, id)?;
+        self.ids.release(global);
+        Some((client, id, topic))
+    }
+
+    /// Drop every subscription to, action of and details request to a plugin that went away,
+    /// returning (client, request id) pairs to notify.
+    pub fn remove_plugin(&mut self, plugin_key: &str) -> Vec<(ClientId, usize)> {
+        let mut removed = vec![];
+        for (client, session) in self.sessions.iter_mut() {
+            for id in session.subscriptions.remove_plugin(plugin_key) {
+                removed.push((*client, id));
+            }
+            for requests in [&mut session.actions, &mut session.details] {
+                requests.retain(|id, key| {
+                    let keep = key != plugin_key;
+                    if !keep {
+                        removed.push((*client, *id));
+                    }
+                    keep
+                });
+            }
+            // nobody waits for these
+            session
+                .prefetches
+                .retain(|_, (key, _, _)| key != plugin_key);
+        }
+        for (client, id) in &removed {
+            if let Some(global) = self.ids.find(*client, *id) {
+                self.ids.release(global);
+            }
+        }
+        removed
+    }
+
+    /// Whether any client still wants updates from the plugin's topic.
+    pub fn is_subscribed(&self, plugin_key: &str, topic: &str) -> bool {
+        self.sessions
+            .values()
+            .any(|session| session.subscriptions.route(plugin_key, topic).is_some())
+    }
+
+    /// Queue a plugin's update for every client subscribed to its topic, under the client's
+    /// request id. Returns false if nobody is subscribed.
+    pub fn publish(&self, plugin_key: &str, topic: &str, message: &Message) -> bool {
+        let mut delivered = false;
+        for session in self.sessions.values() {
+            if let Some(id) = session.subscriptions.route(plugin_key, topic) {
+                let _ = session.outbox.push(with_id(message, id));
+                delivered = true;
+            }
+        }
+        delivered
+    }
+
+    /// Queue a message for every client, e.g. a daemon notification.
+    pub fn broadcast(&self, message: &Message) {
+        for session in self.sessions.values() {
+            let _ = session.outbox.push(message.clone());
+        }
+    }
+}
+
+/// The message with its response id replaced by a client's request id.
+pub fn with_id(message: &Message, client_id: usize) -> Message {
+    let mut message = message.clone();
+    if let Message::Response { id, .. } = &mut message {
+        *id = client_id;
+    }
+    message
+}
+
+impl Reclaim for Sessions {
+    fn reclaim(&mut self) -> usize {
+        // stores in use belong to a busy client, the next sweep gets them
+        self.sessions
+            .values()
+            .filter_map(|session| session.matches.try_lock().ok())
+            .map(|mut matches| matches.reclaim())
+            .sum()
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use glimpse_sdk::{Action, Icon, Match, MatchAction, Metadata, PaletteCommand, PowerProfile};
+use serde::Deserialize;
+
+/// Plugin key of the commands the daemon runs itself.
+pub const PROVIDER_KEY: &str = "glimpsed/commands";
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct CommandConfig {
+    pub enabled: bool,
+    /// Queries starting with it search commands only, e.g. `> reload`.
+    pub prefix: String,
+}
+
+impl Default for CommandConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            prefix: ">".to_string(),
+        }
+    }
+}
+
+impl CommandConfig {
+    /// The command query of a palette search, `None` for other searches.
+    pub fn strip<'a>(&self, query: &'a str) -> Option<&'a str> {
+        if !self.enabled || self.prefix.is_empty() {
+            return None;
+        }
+        query
+            .strip_prefix(&self.prefix)
+            .map(|rest| rest.trim_start())
+    }
+}
+
+/// Commands of the daemon itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuiltinCommand {
+    /// Send every plugin its settings again, as if their files changed.
+    ReloadSettings,
+    /// Stop the plugin with this id, it is started again right away.
+    RestartPlugin(String),
+    PowerProfile(PowerProfile),
+    CheckUpdates,
+}
+
+impl BuiltinCommand {
+    /// The commands on offer: restarts of the given plugins, the power profile not in use
+    /// and update checks if they are configured.
+    pub fn available(plugins: &[&Metadata], profile: PowerProfile, updates: bool) -> Vec<Self> {
+        let other = match profile {
+            PowerProfile::Normal => PowerProfile::LowPower,
+            PowerProfile::LowPower => PowerProfile::Normal,
+        };
+        let mut commands = vec![BuiltinCommand::ReloadSettings];
+        commands.push(BuiltinCommand::PowerProfile(other));
+        if updates {
+            commands.push(BuiltinCommand::CheckUpdates);
+        }
+        commands.extend(
+            plugins
+                .iter()
+                .map(|metadata| BuiltinCommand::RestartPlugin(metadata.id.clone())),
+        );
+        commands
+    }
+
+    /// The callback a palette row runs the command with.
+    pub fn action(&self) -> Action {
+        let (key, params) = match self {
+            BuiltinCommand::ReloadSettings => ("reload_settings", HashMap::new()),
+            BuiltinCommand::RestartPlugin(plugin_id) => (
+                "restart_plugin",
+                HashMap::from([("plugin_id".to_string(), plugin_id.clone())]),
+            ),
+            BuiltinCommand::PowerProfile(profile) => (
+                "power_profile",
+                HashMap::from([(
+                    "profile".to_string(),
+                    match profile {
+                        PowerProfile::Normal => "normal",
+                        PowerProfile::LowPower => "low_power",
+                    }
+                    .to_string(),
+                )]),
+            ),
+            BuiltinCommand::CheckUpdates => ("check_updates", HashMap::new()),
+        };
+        Action::Callback {
+            key: key.to_string(),
+            params,
+        }
+    }
+
+    /// The command a palette row was activated with, `None` for anything else.
+    pub fn from_action(action: &Action) -> Option<Self> {
+        let Action::Callback { key, params } = action else {
+            return None;
+        };
+        match key.as_str() {
+            "reload_settings" => Some(BuiltinCommand::ReloadSettings),
+            "restart_plugin" => params
+                .get("plugin_id")
+                .map(|plugin_id| BuiltinCommand::RestartPlugin(plugin_id.clone())),
+            "power_profile" => match params.get("profile").map(String::as_str) {
+                Some("normal") => Some(BuiltinCommand::PowerProfile(PowerProfile::Normal)),
+                Some("low_power") => Some(BuiltinCommand::PowerProfile(PowerProfile::LowPower)),
+                _ => None,
+            },
+            "check_updates" => Some(BuiltinCommand::CheckUpdates),
+            _ => None,
+        }
+    }
+
+    fn command(&self, plugins: &[&Metadata]) -> PaletteCommand {
+        let (title, description, icon) = match self {
+            BuiltinCommand::ReloadSettings => (
+                "Reload plugin settings".to_string(),
+                "Send every plugin its settings again".to_string(),
+                "view-refresh",
+            ),
+            BuiltinCommand::RestartPlugin(plugin_id) => {
+                let name = plugins
+                    .iter()
+                    .find(|metadata| &metadata.id == plugin_id)
+                    .map_or(plugin_id.as_str(), |metadata| metadata.name.as_str());
+                (
+                    format!("Restart {}", name),
+                    format!("Stop and start plugin {}", plugin_id),
+                    "system-reboot",
+                )
+            }
+            BuiltinCommand::PowerProfile(PowerProfile::LowPower) => (
+                "Switch to low power mode".to_string(),
+                "Pause background work until the battery state changes".to_string(),
+                "battery-caution",
+            ),
+            BuiltinCommand::PowerProfile(PowerProfile::Normal) => (
+                "Switch to normal power mode".to_string(),
+                "Resume background work".to_string(),
+                "battery-full-charged",
+            ),
+            BuiltinCommand::CheckUpdates => (
+                "Check for updates".to_string(),
+                "Look for newer releases of the daemon and its plugins".to_string(),
+                "software-update-available",
+            ),
+        };
+        PaletteCommand::new("", title)
+            .description(description)
+            .icon(Icon::freedesktop(icon))
+    }
+}
+
+/// How well `query` matches a command, `None` if it does not. Title prefixes rank above
+/// word prefixes, then text anywhere in the title, then the description.
+pub fn score(query: &str, command: &PaletteCommand) -> Option<f64> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Some(1.0);
+    }
+    let title = command.title.to_lowercase();
+    if title.starts_with(&query) {
+        Some(1.0)
+    } else if title
+        .split_whitespace()
+        .any(|word| word.starts_with(&query))
+    {
+        Some(0.8)
+    } else if title.contains(&query) {
+        Some(0.6)
+    } else if command.description.to_lowercase().contains(&query) {
+        Some(0.4)
+    } else {
+        None
+    }
+}
+
+fn command_match(command: &PaletteCommand, action: Action, score: f64) -> Match {
+    Match {
+        title: command.title.clone(),
+        description: command.description.clone(),
+        icon: command.icon.clone(),
+        actions: vec![MatchAction {
+            title: "Run".to_string(),
+            action,
+            close_on_action: true,
+            alternates: vec![],
+            requires_confirmation: false,
+            confirmation_prompt: None,
+            expand: vec![],
+        }],
+        score,
+        ..Default::default()
+    }
+}
+
+/// Commands matching `query`, grouped by the key of the plugin running them: the
+/// daemon's own under [`PROVIDER_KEY`], those of a plugin under its key so activating one
+/// sends the plugin its callback. `plugins` holds plugin keys with their metadata.
+pub fn palette(
+    query: &str,
+    builtins: &[BuiltinCommand],
+    plugins: &[(&str, &Metadata)],
+) -> Vec<(String, Vec<Match>)> {
+    let metadata = plugins
+        .iter()
+        .map(|(_, metadata)| *metadata)
+        .collect::<Vec<_>>();
+    let own = builtins
+        .iter()
+        .filter_map(|builtin| {
+            let command = builtin.command(&metadata);
+            let score = score(query, &command)?;
+            Some(command_match(&command, builtin.action(), score))
+        })
+        .collect::<Vec<_>>();
+
+    let mut found = vec![(PROVIDER_KEY.to_string(), own)];
+    for (key, metadata) in plugins {
+        let rows = metadata
+            .commands
+            .iter()
+            .filter_map(|command| {
+                let score = score(query, command)?;
+                let action = Action::Callback {
+                    key: command.key.clone(),
+                    params: HashMap::new(),
+                };
+                Some(command_match(command, action, score))
+            })
+            .collect::<Vec<_>>();
+        found.push((key.to_string(), rows));
+    }
+    found.retain(|(_, rows)| !rows.is_empty());
+    found
+}
@@ -0,0 +1,32 @@
+use glimpse_sdk::{DEFAULT_COMPRESSION_THRESHOLD, Message, Method};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct CompressionConfig {
+    /// Let plugins announcing `Capability::Compression` compress large messages.
+    pub enabled: bool,
+    /// Bytes a message must exceed before it is compressed.
+    pub threshold: usize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: DEFAULT_COMPRESSION_THRESHOLD,
+        }
+    }
+}
+
+impl CompressionConfig {
+    /// The notification switching compression on for a plugin that supports it.
+    pub fn negotiate(&self, supported: bool) -> Option<Message> {
+        (self.enabled && supported).then(|| Message::Notification {
+            method: Method::EnableCompression {
+                threshold: self.threshold,
+            },
+            plugin_id: None,
+        })
+    }
+}
@@ -0,0 +1,79 @@
+use std::path::{Path, PathBuf};
+
+use glimpse_sdk::Features;
+use serde::Deserialize;
+
+use crate::{
+    activation::ActivationConfig, commands::CommandConfig, compression::CompressionConfig,
+    dbus::DbusConfig, dependencies::DependencyConfig, icons::IconConfig, janitor::JanitorConfig,
+    last_results::LastResultsConfig, logs::LogConfig, metrics::MetricsConfig, outbox::OutboxConfig,
+    policy::PolicyConfig, power::PowerConfig, prefetch::PrefetchConfig, ranking::RankingConfig,
+    requests::RequestConfig, sandbox::SandboxConfig, shortcut::ShortcutConfig,
+    supervisor::SupervisorConfig, trust::TrustConfig, updates::UpdateConfig,
+};
+
+/// Daemon settings, read from `~/.config/glimpse/glimpsed.toml`.
+/// Every section is optional and falls back to its defaults.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct DaemonConfig {
+    pub janitor: JanitorConfig,
+    pub outbox: OutboxConfig,
+    pub requests: RequestConfig,
+    pub updates: UpdateConfig,
+    pub power: PowerConfig,
+    pub policy: PolicyConfig,
+    pub last_results: LastResultsConfig,
+    pub ranking: RankingConfig,
+    pub icons: IconConfig,
+    pub compression: CompressionConfig,
+    pub supervisor: SupervisorConfig,
+    pub sandbox: SandboxConfig,
+    pub commands: CommandConfig,
+    pub trust: TrustConfig,
+    pub logs: LogConfig,
+    pub metrics: MetricsConfig,
+    pub prefetch: PrefetchConfig,
+    pub dependencies: DependencyConfig,
+    pub activation: ActivationConfig,
+    pub dbus: DbusConfig,
+    pub shortcut: ShortcutConfig,
+    /// Experimental protocol subsystems, see [`Features`].
+    pub features: Features,
+}
+
+impl DaemonConfig {
+    pub fn path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("glimpse")
+            .join("glimpsed.toml")
+    }
+
+    pub fn load() -> Self {
+        Self::load_from(&Self::path())
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(err) => {
+                tracing::warn!("failed to read config {}: {}", path.display(), err);
+                return Self::default();
+            }
+        };
+
+        match Self::from_toml(&content) {
+            Ok(config) => config,
+            Err(err) => {
+                tracing::warn!("invalid config {}: {}", path.display(), err);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+}
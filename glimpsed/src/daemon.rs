@@ -1,36 +1,121 @@
 use std::{
-    collections::HashMap,
-    sync::{
-        Arc,
-        atomic::{AtomicUsize, Ordering},
-    },
+    collections::{HashMap, VecDeque, hash_map::Entry},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
 };
 
-use glimpse_sdk::{Action, Match, Message, Metadata, Method, MethodResult};
+use glimpse_client::Client;
+use glimpse_sdk::{
+    Action, ActionProgress, AvailableUpdate, Capability, CompressionStats, Frame, Icon, Match,
+    Message, Metadata, Method, MethodResult, PROTOCOL_VERSION, PowerProfile, RpcError,
+    UntrustedPlugin, get_client_socket_path, panic_message,
+};
 use tokio::{
-    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, stdin, stdout},
-    sync::{Mutex, mpsc},
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, Lines, stdin, stdout},
+    sync::{Mutex, Notify, mpsc},
+    task::JoinSet,
 };
 
 use crate::{
-    dispatchers,
-    plugins::{PluginResponse, discover_plugins, spawn_plugin},
+    clients::{ClientId, Sessions, with_id},
+    commands::{self, BuiltinCommand},
+    config::DaemonConfig,
+    dbus,
+    dependencies::Dependencies,
+    dispatchers::{Dispatcher, SystemDispatcher, dispatch_action, dispatch_sequence},
+    expand::Environment,
+    handshake,
+    history::History,
+    icons::Icons,
+    janitor::Janitor,
+    last_results::{LastResults, SavedMatch, SearchSnapshot},
+    logs::{self, LogStore, log_name},
+    matches::{MatchStore, categorize, highlight},
+    metrics::{self, Metrics},
+    outbox::Outbox,
+    permissions::{Grants, with_configured_roots},
+    plugin_config,
+    plugins::{PluginResponse, discover_plugins, plugin_dirs, spawn_plugin, watch_plugin_dirs},
+    power::{self, PowerMode, PowerStats, Upower},
+    prefetch,
+    ranking::{Features, RankingEvent, RankingLog, RankingStrategy},
+    requests::RequestTracker,
+    routing::{self, Route},
+    sandbox::SandboxConfig,
+    shortcut,
+    stats::{self, Outcome, StatsStore},
+    supervisor::{Restart, Supervisor, SupervisorStats},
+    thumbnails::{self, Thumbnails},
+    transport::{self, ClientListener, ClientReader, ClientWriter},
+    trust::{Admitted, TrustError, TrustStore},
+    updates::{self, UpdateError},
 };
 
+/// Delay of the first update check after startup.
+const UPDATE_CHECK_DELAY: Duration = Duration::from_secs(30);
+
+/// How long plugin directory events settle before the directories are rescanned.
+const DISCOVERY_DEBOUNCE: Duration = Duration::from_millis(500);
+
 struct ConnectedPlugin {
     metadata: Option<Metadata>,
+    /// Experimental features negotiated when the plugin authenticated.
+    features: glimpse_sdk::Features,
+    /// When the plugin last authenticated, it waits on its prerequisites for a while after.
+    authenticated_at: Option<Instant>,
     tx: mpsc::Sender<Message>,
-}
-
-struct MatchHolder {
-    plugin_id: String,
-    match_: Match,
+    handle: tokio::task::JoinHandle<()>,
+    /// Tell clients about the plugin once it authenticates, set for plugins added at runtime.
+    announce: bool,
 }
 
 pub struct Daemon {
-    current_request: Arc<AtomicUsize>,
-    current_matches: Arc<Mutex<Vec<MatchHolder>>>,
+    requests: Arc<Mutex<RequestTracker>>,
+    sessions: Arc<Mutex<Sessions>>,
     stop_channel: Option<tokio::sync::oneshot::Sender<()>>,
+    dispatcher: Arc<dyn Dispatcher>,
+    janitor: Arc<Janitor>,
+    power: Arc<PowerStats>,
+    compression: Arc<CompressionStats>,
+    tasks: Arc<SupervisorStats>,
+    logs: Arc<LogStore>,
+    metrics: Arc<Metrics>,
+    /// Exit after this long without a connected client, for daemons started on demand.
+    idle_exit: Option<Duration>,
+    config: DaemonConfig,
+}
+
+/// What a client connection shares with the rest of the daemon.
+#[derive(Clone)]
+struct ClientContext {
+    plugins: Arc<Mutex<HashMap<String, ConnectedPlugin>>>,
+    sessions: Arc<Mutex<Sessions>>,
+    requests: Arc<Mutex<RequestTracker>>,
+    request_tracked: Arc<Notify>,
+    history: Option<Arc<Mutex<History>>>,
+    stats: Arc<Mutex<StatsStore>>,
+    metrics: Arc<Metrics>,
+    logs: Arc<LogStore>,
+    ranking: Arc<Mutex<Box<dyn RankingStrategy>>>,
+    ranking_log: Option<Arc<RankingLog>>,
+    icons: Arc<Icons>,
+    last_results: Arc<LastResults>,
+    grants: Arc<Mutex<Grants>>,
+    available_updates: Arc<Mutex<Vec<AvailableUpdate>>>,
+    trust: Arc<Mutex<TrustStore>>,
+    /// Plugins found but held back until the user trusts them.
+    untrusted: Arc<Mutex<Vec<UntrustedPlugin>>>,
+    /// Rescans the plugin directories.
+    discovery: mpsc::UnboundedSender<()>,
+    dispatcher: Arc<dyn Dispatcher>,
+    janitor: Arc<Janitor>,
+    power: Arc<PowerStats>,
+    /// Files whose thumbnails clients show, rendered ahead of the others.
+    previews: mpsc::UnboundedSender<PathBuf>,
+    config: DaemonConfig,
+    shutdown: Arc<Notify>,
 }
 
 impl Default for Daemon {
@@ -41,277 +126,2715 @@ impl Default for Daemon {
 
 impl Daemon {
     pub fn new() -> Self {
+        Self::with_dispatcher(Arc::new(SystemDispatcher::default()))
+    }
+
+    pub fn with_dispatcher(dispatcher: Arc<dyn Dispatcher>) -> Self {
+        Self::with_config(DaemonConfig::default(), dispatcher)
+    }
+
+    pub fn with_config(mut config: DaemonConfig, dispatcher: Arc<dyn Dispatcher>) -> Self {
+        if let Err(err) = config.features.check() {
+            tracing::error!(
+                "experimental features {:?}: {}",
+                config.features.enabled(),
+                err
+            );
+            config.features =
+                glimpse_sdk::Features::default().intersect(&glimpse_sdk::Features::compiled());
+        }
         let (stop_channel, _) = tokio::sync::oneshot::channel();
+        let sessions = Arc::new(Mutex::new(Sessions::new()));
+
+        let mut janitor = Janitor::new(config.janitor.clone());
+        janitor.register(sessions.clone());
+
         Daemon {
-            current_request: Arc::new(AtomicUsize::new(0)),
+            requests: Arc::new(Mutex::new(RequestTracker::new())),
             stop_channel: Some(stop_channel),
-            current_matches: Arc::new(Mutex::new(vec![])),
+            sessions,
+            dispatcher,
+            janitor: Arc::new(janitor),
+            power: Arc::new(PowerStats::default()),
+            compression: Arc::new(CompressionStats::default()),
+            tasks: Arc::new(SupervisorStats::default()),
+            logs: Arc::new(LogStore::new(&LogStore::dir(), config.logs.clone())),
+            metrics: Arc::new(Metrics::default()),
+            idle_exit: None,
+            config,
         }
     }
 
+    pub fn janitor(&self) -> Arc<Janitor> {
+        self.janitor.clone()
+    }
+
+    pub fn power(&self) -> Arc<PowerStats> {
+        self.power.clone()
+    }
+
+    pub fn compression(&self) -> Arc<CompressionStats> {
+        self.compression.clone()
+    }
+
+    /// Search metrics of the plugins since the daemon started.
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.metrics.clone()
+    }
+
+    /// Health of the daemon's background tasks.
+    pub fn tasks(&self) -> Arc<SupervisorStats> {
+        self.tasks.clone()
+    }
+
     pub async fn stop(&mut self) {
         if let Some(stop_channel) = self.stop_channel.take() {
             let _ = stop_channel.send(());
         }
     }
 
+    /// Exit once no client was connected for `idle_exit_secs` under `[activation]`, the
+    /// daemon being started again on demand. Daemons with a stdio client exit with it.
+    pub fn exit_when_idle(&mut self) {
+        self.idle_exit = self.config.activation.idle_exit();
+    }
+
+    /// Serve the client on stdin/stdout until it leaves. Clients connecting to the socket are
+    /// served alongside, unless another daemon already listens there.
     pub async fn run(&mut self) {
-        let stdin = stdin();
-        let mut stdout = stdout();
-        let mut reader = BufReader::new(stdin);
-        let current_request = Arc::clone(&self.current_request);
+        let path = get_client_socket_path();
+        let listener = transport::bind(&path)
+            .inspect_err(|e| tracing::warn!("not accepting clients on {:?}: {}", path, e))
+            .ok();
+        self.serve(true, listener).await;
+        let _ = std::fs::remove_file(&path);
+    }
+
+    /// Serve clients connecting to the socket until the daemon is stopped or one of them
+    /// sends `Quit`.
+    pub async fn listen(&mut self) -> std::io::Result<()> {
+        let path = get_client_socket_path();
+        let listener = transport::bind(&path)?;
+        tracing::info!("accepting clients on {:?}", path);
+        self.serve(false, Some(listener)).await;
+        let _ = std::fs::remove_file(&path);
+        Ok(())
+    }
+
+    /// Serve clients connecting to a socket someone else bound, such as systemd, until the
+    /// daemon is stopped or one of them sends `Quit`. The socket is left for its owner.
+    pub async fn listen_on(&mut self, listener: ClientListener) {
+        tracing::info!("accepting clients on the activated socket");
+        self.serve(false, Some(listener)).await;
+    }
 
-        let (response_tx, mut response_rx) = mpsc::channel::<Message>(10);
-        let (plugin_tx, mut plugin_rx) = mpsc::channel::<PluginResponse>(10);
+    async fn serve(&mut self, stdio: bool, mut listener: Option<ClientListener>) {
+        let (plugin_tx, plugin_rx) = mpsc::channel::<PluginResponse>(10);
 
         let plugin_paths = discover_plugins();
         tracing::info!("discovered plugins: {:?}", &plugin_paths);
 
-        let mut handles = vec![];
-        let plugins: HashMap<String, ConnectedPlugin> = plugin_paths
+        // executables nobody trusted yet wait for the user, everything else starts
+        let trust = TrustStore::load(&TrustStore::path(), self.config.trust.clone());
+        let (admitted, held) = trust.admit(plugin_paths);
+        if !held.is_empty() {
+            tracing::warn!("holding back untrusted plugins: {:?}", held);
+        }
+        let trust = Arc::new(Mutex::new(trust));
+        let untrusted = Arc::new(Mutex::new(held));
+        let logs = self.logs.clone();
+
+        let plugins: HashMap<String, ConnectedPlugin> = admitted
             .into_iter()
-            .map(|path| {
-                tracing::debug!("starting plugin {:?}", &path);
-                let (tx, rx) = mpsc::channel::<Message>(10);
-                let plugin_tx = plugin_tx.clone();
-                let path_copy = path.clone();
-                let handle = tokio::spawn(async move {
-                    spawn_plugin(path_copy, plugin_tx, rx).await;
-                });
-                handles.push(handle);
-                let plugin_name = path.to_string();
-                (plugin_name, ConnectedPlugin { metadata: None, tx })
+            .map(|admitted| {
+                let plugin = start_plugin(
+                    &admitted,
+                    &plugin_tx,
+                    &self.compression,
+                    &self.config.sandbox,
+                    &logs,
+                    false,
+                );
+                (admitted.path, plugin)
             })
             .collect();
 
-        let response_tx = response_tx.clone();
-        let current_request_clone = Arc::clone(&current_request);
+        let mut supervisor = Supervisor::new(self.config.supervisor.clone(), self.tasks.clone());
+        if self.config.janitor.enabled {
+            let janitor = self.janitor.clone();
+            supervisor.spawn("janitor", Restart::OnPanic, move || janitor.clone().run());
+        }
 
         let plugins_arc = Arc::new(Mutex::new(plugins));
+
+        let history = History::open(&History::path())
+            .inspect_err(|e| tracing::warn!("usage history disabled: {}", e))
+            .ok()
+            .map(|history| Arc::new(Mutex::new(history)));
+        let stats = Arc::new(Mutex::new(StatsStore::load(&StatsStore::path())));
+        let saved_stats = stats.clone();
+        supervisor.spawn("plugin stats", Restart::OnPanic, move || {
+            let saved_stats = saved_stats.clone();
+            async move {
+                let mut ticker = tokio::time::interval(stats::SAVE_INTERVAL);
+                loop {
+                    ticker.tick().await;
+                    if let Err(e) = saved_stats.lock().await.save() {
+                        tracing::warn!("failed to save plugin stats: {}", e);
+                    }
+                }
+            }
+        });
+        if let Some(address) = self.config.metrics.prometheus {
+            match metrics::bind(address).await {
+                Ok(listener) => {
+                    tracing::info!("serving metrics on {}", address);
+                    let listener = Arc::new(listener);
+                    let metrics = self.metrics.clone();
+                    supervisor.spawn("metrics exporter", Restart::OnPanic, move || {
+                        metrics::serve_prometheus(listener.clone(), metrics.clone())
+                    });
+                }
+                Err(e) => tracing::warn!("not serving metrics on {}: {}", address, e),
+            }
+        }
+        let ranking = Arc::new(Mutex::new(self.config.ranking.strategy.build()));
+        let (render_tx, render_rx) = mpsc::unbounded_channel::<PathBuf>();
+        let thumbnail_cache = Thumbnails::user();
+        let icons = Arc::new(
+            Icons::new(&self.config.icons, &Icons::cache_dir()).with_thumbnails(
+                thumbnail_cache.clone(),
+                self.config.icons.thumbnails.then_some(render_tx),
+            ),
+        );
+        tracing::debug!("resolving icons in the {} theme", icons.theme());
+
+        // thumbnails are rendered one at a time off the search path, clients swap them in;
+        // those of matches on screen go first
+        let (preview_tx, preview_rx) = mpsc::unbounded_channel::<PathBuf>();
+        let thumbnail_sessions = self.sessions.clone();
+        let thumbnail_power = self.power.clone();
+        let render_rx = Arc::new(Mutex::new(render_rx));
+        let preview_rx = Arc::new(Mutex::new(preview_rx));
+        supervisor.spawn("thumbnails", Restart::OnPanic, move || {
+            let render_rx = render_rx.clone();
+            let preview_rx = preview_rx.clone();
+            let thumbnail_sessions = thumbnail_sessions.clone();
+            let thumbnail_power = thumbnail_power.clone();
+            let thumbnail_cache = thumbnail_cache.clone();
+            async move {
+                let mut render_rx = render_rx.lock().await;
+                let mut preview_rx = preview_rx.lock().await;
+                loop {
+                    let path = tokio::select! {
+                        biased;
+                        Some(path) = preview_rx.recv() => path,
+                        Some(path) = render_rx.recv() => path,
+                        else => break,
+                    };
+                    // a later search asks again
+                    if thumbnail_power.profile() == PowerProfile::LowPower {
+                        continue;
+                    }
+                    let rendered = tokio::time::timeout(
+                        thumbnails::RENDER_TIMEOUT,
+                        thumbnail_cache.render(&path),
+                    )
+                    .await;
+                    match rendered {
+                        Ok(Ok(Some(thumbnail))) => {
+                            thumbnail_sessions
+                                .lock()
+                                .await
+                                .broadcast(&thumbnail_ready(&path, &thumbnail));
+                        }
+                        Ok(Ok(None)) => {}
+                        Ok(Err(e)) => tracing::debug!("{}", e),
+                        Err(_) => tracing::debug!("timed out thumbnailing {}", path.display()),
+                    }
+                }
+            }
+        });
+        let ranking_log = self
+            .config
+            .ranking
+            .log
+            .then(|| Arc::new(RankingLog::new(&RankingLog::path())));
+        let last_results = Arc::new(LastResults::new(
+            &LastResults::path(),
+            self.config.last_results.clone(),
+        ));
+
+        // re-deliver plugin settings whenever their files change
+        let (config_tx, config_rx) = mpsc::unbounded_channel::<String>();
+        let config_dir = plugin_config::config_dir();
+        let _config_watcher = plugin_config::watch(&config_dir, config_tx)
+            .inspect_err(|e| tracing::warn!("not watching plugin settings: {}", e))
+            .ok();
         let plugins_copy = plugins_arc.clone();
-        let current_matches = self.current_matches.clone();
-        let plugin_handle = tokio::spawn(async move {
-            while let Some(ref plugin_message) = plugin_rx.recv().await {
-                match plugin_message {
-                    PluginResponse::Response(plugin_id, message) => {
-                        match message {
-                            Message::Response { id, result, .. } => {
-                                if *id != current_request_clone.load(Ordering::SeqCst) {
-                                    continue;
-                                }
+        let config_rx = Arc::new(Mutex::new(config_rx));
+        supervisor.spawn("plugin settings", Restart::OnPanic, move || {
+            let config_rx = config_rx.clone();
+            let plugins_copy = plugins_copy.clone();
+            let config_dir = config_dir.clone();
+            async move {
+                let mut config_rx = config_rx.lock().await;
+                while let Some(plugin_id) = config_rx.recv().await {
+                    let plugins = plugins_copy.lock().await;
+                    let Some(key) = find_plugin_key(&plugins, &plugin_id) else {
+                        continue;
+                    };
+                    tracing::info!("settings of {} changed, reconfiguring", plugin_id);
+                    configure_plugin(&config_dir, &plugins[&key], Method::ConfigChanged);
+                }
+            }
+        });
 
-                                if result.is_none() {
-                                    let _ = response_tx.send(message.clone()).await;
-                                    continue;
-                                }
+        // start executables dropped into the plugin directories, stop removed ones
+        let (discovery_tx, discovery_rx) = mpsc::unbounded_channel::<()>();
+        let _plugin_watcher = watch_plugin_dirs(&plugin_dirs(), discovery_tx.clone())
+            .inspect_err(|e| tracing::warn!("not watching plugin directories: {}", e))
+            .ok();
+        let plugins_copy = plugins_arc.clone();
+        let sessions = self.sessions.clone();
+        let discovery_plugin_tx = plugin_tx.clone();
+        let discovery_requests = self.requests.clone();
+        let discovery_power = self.power.clone();
+        let discovery_compression = self.compression.clone();
+        let discovery_sandbox = self.config.sandbox.clone();
+        let discovery_logs = logs.clone();
+        let discovery_last_results = last_results.clone();
+        let discovery_trust = trust.clone();
+        let discovery_untrusted = untrusted.clone();
+        let rescan_tx = discovery_tx;
+        let discovery_rx = Arc::new(Mutex::new(discovery_rx));
+        supervisor.spawn("plugin discovery", Restart::OnPanic, move || {
+            let discovery_rx = discovery_rx.clone();
+            let plugins_copy = plugins_copy.clone();
+            let sessions = sessions.clone();
+            let discovery_plugin_tx = discovery_plugin_tx.clone();
+            let discovery_requests = discovery_requests.clone();
+            let discovery_power = discovery_power.clone();
+            let discovery_compression = discovery_compression.clone();
+            let discovery_sandbox = discovery_sandbox.clone();
+            let discovery_logs = discovery_logs.clone();
+            let discovery_last_results = discovery_last_results.clone();
+            let discovery_trust = discovery_trust.clone();
+            let discovery_untrusted = discovery_untrusted.clone();
+            async move {
+                let mut discovery_rx = discovery_rx.lock().await;
+                while discovery_rx.recv().await.is_some() {
+                    // copying an executable in takes several events, rescan once they settle
+                    let profile = discovery_power.profile();
+                    tokio::time::sleep(power::debounce(profile, DISCOVERY_DEBOUNCE)).await;
+                    while discovery_rx.try_recv().is_ok() {}
 
-                                let result = result.as_ref().unwrap();
-                                match result {
-                                    MethodResult::Authenticate(metadata) => {
-                                        plugins_copy.lock().await.get_mut(plugin_id).map(
-                                            |plugin| {
-                                                plugin.metadata.replace(metadata.clone());
-                                            },
-                                        );
-                                        tracing::info!(
-                                            "authenticated plugin {} v{}",
-                                            metadata.name,
-                                            metadata.version
-                                        );
-                                    }
-                                    MethodResult::Matches { items } => {
-                                        let new_items = items
-                                            .iter()
-                                            .map(|m| MatchHolder {
-                                                plugin_id: plugin_id.clone(),
-                                                match_: m.clone(),
-                                            })
-                                            .collect::<Vec<_>>();
-                                        current_matches.lock().await.extend(new_items);
-                                        let _ = response_tx.send(message.clone()).await;
-                                    }
-                                    _ => {
-                                        let _ = response_tx.send(message.clone()).await;
-                                    }
-                                }
+                    // replaced executables are stopped and asked about again
+                    let (found, held) = discovery_trust.lock().await.admit(discover_plugins());
+                    let newly_held = {
+                        let mut untrusted = discovery_untrusted.lock().await;
+                        let newly_held = held.iter().any(|plugin| !untrusted.contains(plugin));
+                        *untrusted = held.clone();
+                        newly_held
+                    };
+                    if newly_held {
+                        tracing::warn!("holding back untrusted plugins: {:?}", held);
+                        sessions.lock().await.broadcast(&plugins_held_back(held));
+                    }
+
+                    let mut plugins = plugins_copy.lock().await;
+                    let gone = plugins
+                        .keys()
+                        .filter(|key| !found.iter().any(|admitted| &admitted.path == *key))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    let gone = gone
+                        .into_iter()
+                        .filter_map(|key| plugins.remove_entry(&key))
+                        .collect::<Vec<_>>();
+                    for admitted in found {
+                        if let Entry::Vacant(entry) = plugins.entry(admitted.path.clone()) {
+                            tracing::info!("plugin {:?} added", entry.key());
+                            let plugin = start_plugin(
+                                &admitted,
+                                &discovery_plugin_tx,
+                                &discovery_compression,
+                                &discovery_sandbox,
+                                &discovery_logs,
+                                true,
+                            );
+                            entry.insert(plugin);
+                        }
+                    }
+                    // searching locks matches before plugins, never hold both the other way round
+                    drop(plugins);
+
+                    let mut removed = vec![];
+                    for (key, plugin) in gone {
+                        tracing::info!("plugin {:?} removed", key);
+                        {
+                            let mut sessions = sessions.lock().await;
+                            for (client, client_id) in sessions.remove_plugin(&key) {
+                                let Some(session) = sessions.get(client) else {
+                                    continue;
+                                };
+                                let _ = session.outbox.push(Message::Response {
+                                    id: client_id,
+                                    error: Some(RpcError::plugin("plugin removed")),
+                                    result: None,
+                                    plugin_id: None,
+                                });
                             }
-                            _ => {
-                                let _ = response_tx.send(message.clone()).await;
+                        }
+                        let unanswered = discovery_requests.lock().await.forget_plugin(&key);
+                        for id in unanswered {
+                            if let Some((client_id, outbox, matches)) =
+                                route_search(&sessions, id).await
+                            {
+                                finish_search(
+                                    &matches,
+                                    &outbox,
+                                    client_id,
+                                    &key,
+                                    &discovery_last_results,
+                                )
+                                .await;
                             }
-                        };
+                        }
+                        if let Some(metadata) = &plugin.metadata {
+                            removed.push(metadata.id.clone());
+                        }
+                        stop_plugin(plugin);
+                    }
+
+                    // added plugins are announced once they authenticate and have an id
+                    if !removed.is_empty() {
+                        sessions
+                            .lock()
+                            .await
+                            .broadcast(&plugins_changed(vec![], removed));
                     }
                 }
             }
         });
 
-        let plugins_copy = plugins_arc.clone();
-        let current_matches = self.current_matches.clone();
-        let stdin_handle = tokio::spawn(async move {
-            let mut line = String::new();
-            loop {
-                line.clear();
-                let bytes_read = reader.read_line(&mut line).await.unwrap();
-                if bytes_read == 0 {
-                    break;
+        // low power on a draining battery, unless the config forces a profile
+        let power_config = self.config.power.clone();
+        self.power.switch(power_config.profile_for(None));
+        if power_config.mode == PowerMode::Auto {
+            let power = self.power.clone();
+            let plugins = plugins_arc.clone();
+            let sessions = self.sessions.clone();
+            supervisor.spawn("power", Restart::OnPanic, move || {
+                let power = power.clone();
+                let plugins = plugins.clone();
+                let sessions = sessions.clone();
+                let power_config = power_config.clone();
+                async move {
+                    let upower = match Upower::connect().await {
+                        Ok(upower) => upower,
+                        Err(e) => {
+                            tracing::warn!("battery state unavailable: {}", e);
+                            return;
+                        }
+                    };
+                    let mut ticker = tokio::time::interval(power_config.poll_interval());
+                    loop {
+                        ticker.tick().await;
+                        let state = match upower.state().await {
+                            Ok(state) => state,
+                            Err(e) => {
+                                tracing::warn!("failed to read battery state: {}", e);
+                                continue;
+                            }
+                        };
+                        power.record_state(state);
+                        let profile = power_config.profile_for(Some(state));
+                        if !power.switch(profile) {
+                            continue;
+                        }
+                        tracing::info!(
+                            "switching to {:?} power profile, battery at {}%",
+                            profile,
+                            state.percentage
+                        );
+                        for plugin in plugins.lock().await.values() {
+                            send_to_plugin(plugin, power_profile(profile));
+                        }
+                        sessions.lock().await.broadcast(&power_profile(profile));
+                    }
+                }
+            });
+        }
+
+        // opt-in: look for newer releases, they are only reported, never installed
+        let updates_arc = Arc::new(Mutex::new(Vec::new()));
+        if let Some(url) = self.config.updates.manifest_url() {
+            let url = url.to_string();
+            let interval = self.config.updates.interval();
+            let plugins = plugins_arc.clone();
+            let available_updates = updates_arc.clone();
+            supervisor.spawn("updates", Restart::OnPanic, move || {
+                let url = url.clone();
+                let plugins = plugins.clone();
+                let available_updates = available_updates.clone();
+                async move {
+                    let mut ticker = tokio::time::interval(interval);
+                    loop {
+                        ticker.tick().await;
+                        // give plugins a moment to authenticate so their versions are known
+                        tokio::time::sleep(UPDATE_CHECK_DELAY).await;
+                        match check_updates(&url, &plugins, &available_updates).await {
+                            Ok(found) if !found.is_empty() => {
+                                tracing::info!("{} updates available", found.len())
+                            }
+                            Ok(_) => tracing::debug!("everything is up to date"),
+                            Err(e) => tracing::warn!("update check failed: {}", e),
+                        }
+                    }
                 }
+            });
+        }
 
-                let message: Message = match serde_json::from_str(&line) {
-                    Ok(msg) => msg,
-                    Err(err) => {
-                        tracing::warn!("failed to parse JSON: {}", err);
+        // plugins that miss the deadline are left out, the client gets what arrived so far
+        let timeout = self.config.requests.timeout();
+        let request_tracked = Arc::new(Notify::new());
+        let requests = self.requests.clone();
+        let sessions = self.sessions.clone();
+        let tracked = request_tracked.clone();
+        let timeout_last_results = last_results.clone();
+        let timeout_plugins = plugins_arc.clone();
+        let timeout_stats = stats.clone();
+        let timeout_metrics = self.metrics.clone();
+        supervisor.spawn("timeouts", Restart::OnPanic, move || {
+            let requests = requests.clone();
+            let sessions = sessions.clone();
+            let tracked = tracked.clone();
+            let timeout_last_results = timeout_last_results.clone();
+            let timeout_plugins = timeout_plugins.clone();
+            let timeout_stats = timeout_stats.clone();
+            let timeout_metrics = timeout_metrics.clone();
+            async move {
+                loop {
+                    let next_deadline = requests.lock().await.next_deadline();
+                    let Some(deadline) = next_deadline else {
+                        tracked.notified().await;
                         continue;
+                    };
+                    tokio::select! {
+                        _ = tokio::time::sleep_until(deadline.into()) => {}
+                        // a new search may come with an earlier deadline
+                        _ = tracked.notified() => continue,
                     }
-                };
-                tracing::debug!("client request -> plugins: {:?}", &message);
 
-                match message {
-                    Message::Request {
-                        id,
-                        method,
-                        ref plugin_id,
-                    } => match method {
-                        Method::Search(query) => {
-                            current_request.store(id, Ordering::SeqCst);
-                            current_matches.lock().await.clear();
-
-                            for plugin in plugins_copy.lock().await.values() {
-                                if plugin_id.is_some() {
-                                    if plugin.metadata.is_none() {
+                    let expired = requests.lock().await.expire(Instant::now());
+                    for (id, plugin_id) in expired {
+                        tracing::warn!(
+                            "plugin {} did not answer search {} within {:?}",
+                            plugin_id,
+                            id,
+                            timeout
+                        );
+                        let stats_id = plugin_id_of(&*timeout_plugins.lock().await, &plugin_id);
+                        timeout_stats
+                            .lock()
+                            .await
+                            .record(&stats_id, Outcome::TimedOut);
+                        timeout_metrics.search(&stats_id, Outcome::TimedOut);
+                        if let Some((client_id, outbox, matches)) =
+                            route_search(&sessions, id).await
+                        {
+                            finish_search(
+                                &matches,
+                                &outbox,
+                                client_id,
+                                &plugin_id,
+                                &timeout_last_results,
+                            )
+                            .await;
+                        }
+                    }
+                }
+            }
+        });
+
+        let plugins_copy = plugins_arc.clone();
+        let sessions = self.sessions.clone();
+        let janitor = self.janitor.clone();
+        let plugin_history = history.clone();
+        let plugin_stats = stats.clone();
+        let plugin_metrics = self.metrics.clone();
+        let plugin_ranking = ranking.clone();
+        let plugin_icons = icons.clone();
+        let plugin_last_results = last_results.clone();
+        let requests = self.requests.clone();
+        let plugin_power = self.power.clone();
+        let policy = self.config.policy.clone();
+        let grants = Arc::new(Mutex::new(Grants::load(&Grants::path())));
+        let plugin_grants = grants.clone();
+        let plugin_dispatcher = self.dispatcher.clone();
+        let compression = self.config.compression.clone();
+        let daemon_features = self.config.features;
+        // a panic handling one plugin message loses that message only
+        let plugin_rx = Arc::new(Mutex::new(plugin_rx));
+        supervisor.spawn("plugin messages", Restart::Critical, move || {
+            let plugin_rx = plugin_rx.clone();
+            let plugins_copy = plugins_copy.clone();
+            let sessions = sessions.clone();
+            let janitor = janitor.clone();
+            let plugin_history = plugin_history.clone();
+            let plugin_stats = plugin_stats.clone();
+            let plugin_metrics = plugin_metrics.clone();
+            let plugin_ranking = plugin_ranking.clone();
+            let plugin_icons = plugin_icons.clone();
+            let plugin_last_results = plugin_last_results.clone();
+            let requests = requests.clone();
+            let plugin_power = plugin_power.clone();
+            let policy = policy.clone();
+            let plugin_grants = plugin_grants.clone();
+            let plugin_dispatcher = plugin_dispatcher.clone();
+            let compression = compression.clone();
+            async move {
+                let mut plugin_rx = plugin_rx.lock().await;
+                while let Some(ref plugin_message) = plugin_rx.recv().await {
+                    janitor.touch();
+                    match plugin_message {
+                        PluginResponse::Response(plugin_id, message) => {
+                            match message {
+                                Message::Response {
+                                    id, result, error, ..
+                                } => {
+                                    if let Some(MethodResult::Update { topic, .. }) = result {
+                                        let mut message = message.clone();
+                                        if let Message::Response {
+                                            result: Some(MethodResult::Update { items, .. }),
+                                            ..
+                                        } = &mut message
+                                        {
+                                            let allowed = policy.filter(std::mem::take(items));
+                                            *items =
+                                                resolve_icons(&plugin_icons, Some(plugin_id), allowed)
+                                                    .await;
+                                        }
+                                        if !sessions.lock().await.publish(plugin_id, topic, &message) {
+                                            tracing::debug!(
+                                                "dropping update for {}: not subscribed",
+                                                topic
+                                            );
+                                        }
                                         continue;
                                     }
 
-                                    let connected_plugin_id = plugin.metadata.clone().unwrap().id;
-                                    if plugin_id.clone().unwrap() != connected_plugin_id {
+                                    // a callback action finished, failed or was cancelled
+                                    let ended = sessions.lock().await.end_action(plugin_id, *id);
+                                    if let Some((client, client_id)) = ended {
+                                        if let Some(session) = sessions.lock().await.get(client) {
+                                            let _ = session.outbox.push(with_id(message, client_id));
+                                        }
                                         continue;
                                     }
-                                }
 
-                                let tx = plugin.tx.clone();
-                                let request = Message::Request {
-                                    id,
-                                    method: Method::Search(query.clone()),
-                                    plugin_id: None,
-                                };
-                                tokio::spawn(async move {
-                                    if let Err(e) = tx.send(request).await {
-                                        tracing::error!("failed to send request to plugin: {}", e);
+                                    // a detail fetched ahead of the client is kept for it
+                                    let prefetched = sessions.lock().await.end_prefetch(plugin_id, *id);
+                                    if let Some((client, generation, match_id)) = prefetched {
+                                        let Some(MethodResult::Details {
+                                            detail: Some(detail),
+                                        }) = result
+                                        else {
+                                            continue;
+                                        };
+                                        let session = sessions.lock().await.get(client).map(|session| {
+                                            (session.matches.clone(), session.outbox.clone())
+                                        });
+                                        if let Some((matches, outbox)) = session
+                                            && matches.lock().await.set_detail(
+                                                generation,
+                                                match_id,
+                                                detail.clone(),
+                                            )
+                                        {
+                                            let _ = outbox.push(prefetch::detail_ready(
+                                                generation,
+                                                match_id,
+                                                detail.clone(),
+                                            ));
+                                        }
+                                        continue;
                                     }
-                                });
-                            }
-                        }
-                        Method::Activate(match_index, action_index) => {
-                            let matches = current_matches.lock().await;
-                            if match_index >= matches.len() {
-                                tracing::warn!("invalid match index: {}", &match_index);
-                                continue;
-                            }
 
-                            if action_index >= matches[match_index].match_.actions.len() {
-                                tracing::warn!("invalid action index: {}", &action_index);
-                                continue;
-                            }
+                                    let described = sessions.lock().await.end_details(plugin_id, *id);
+                                    if let Some((client, client_id)) = described {
+                                        if let Some(session) = sessions.lock().await.get(client) {
+                                            let _ = session.outbox.push(with_id(message, client_id));
+                                        }
+                                        continue;
+                                    }
+
+                                    if error.is_some() {
+                                        let mut sessions = sessions.lock().await;
+                                        if let Some((client, client_id, topic)) =
+                                            sessions.remove_request(plugin_id, *id)
+                                        {
+                                            tracing::warn!("subscription to {} rejected", topic);
+                                            if let Some(session) = sessions.get(client) {
+                                                let _ =
+                                                    session.outbox.push(with_id(message, client_id));
+                                            }
+                                            continue;
+                                        }
+                                    }
 
-                            let action = &matches[match_index].match_.actions[action_index].action;
-                            match action {
-                                Action::Exec { command, args } => {
-                                    dispatchers::shell_exec(&command, args).await
+                                    // plugins may come up in the middle of a search
+                                    if let Some(MethodResult::Authenticate(metadata)) = result {
+                                        let negotiated = handshake::negotiate(metadata).and_then(|_| {
+                                            handshake::negotiate_features(metadata, &daemon_features)
+                                        });
+                                        let features = match negotiated {
+                                            Ok(features) => features,
+                                            Err(err) => {
+                                                tracing::error!(
+                                                    "refusing plugin {}: {}",
+                                                    metadata.id,
+                                                    err
+                                                );
+                                                if let Some(plugin) =
+                                                    plugins_copy.lock().await.remove(plugin_id)
+                                                {
+                                                    stop_plugin(plugin);
+                                                }
+                                                continue;
+                                            }
+                                        };
+                                        if metadata.protocol_version < PROTOCOL_VERSION {
+                                            tracing::warn!(
+                                                "plugin {} speaks protocol version {}, capabilities: {:?}",
+                                                metadata.id,
+                                                metadata.protocol_version,
+                                                metadata.capabilities
+                                            );
+                                        }
+                                        if !metadata.supports(Capability::Permissions) {
+                                            tracing::warn!(
+                                                "plugin {} declares no permissions, its actions needing one are refused",
+                                                metadata.id
+                                            );
+                                        } else {
+                                            tracing::debug!(
+                                                "plugin {} declares permissions {:?}, paths {:?}",
+                                                metadata.id,
+                                                metadata.permissions,
+                                                metadata.paths
+                                            );
+                                        }
+                                        let announce =
+                                            match plugins_copy.lock().await.get_mut(plugin_id) {
+                                                Some(plugin) => {
+                                                    // plugins authenticate again once restarted
+                                                    if plugin
+                                                        .metadata
+                                                        .replace((**metadata).clone())
+                                                        .is_some()
+                                                    {
+                                                        plugin_metrics.restarted(&metadata.id);
+                                                    }
+                                                    plugin.features = features;
+                                                    plugin.authenticated_at = Some(Instant::now());
+                                                    configure_plugin(
+                                                        &plugin_config::config_dir(),
+                                                        plugin,
+                                                        Method::Configure,
+                                                    );
+                                                    // plugins start out in the normal profile
+                                                    if plugin_power.profile() == PowerProfile::LowPower
+                                                    {
+                                                        send_to_plugin(
+                                                            plugin,
+                                                            power_profile(PowerProfile::LowPower),
+                                                        );
+                                                    }
+                                                    if let Some(enable) = compression.negotiate(
+                                                        metadata.supports(Capability::Compression),
+                                                    ) {
+                                                        send_to_plugin(plugin, enable);
+                                                    }
+                                                    std::mem::take(&mut plugin.announce)
+                                                }
+                                                None => false,
+                                            };
+                                        report_dependencies(&*plugins_copy.lock().await, &metadata.id);
+                                        if announce {
+                                            sessions.lock().await.broadcast(&plugins_changed(
+                                                vec![metadata.id.clone()],
+                                                vec![],
+                                            ));
+                                        }
+                                        tracing::info!(
+                                            "authenticated plugin {} v{}",
+                                            metadata.name,
+                                            metadata.version
+                                        );
+                                        continue;
+                                    }
+
+                                    if let Some(MethodResult::Matches { items }) = result {
+                                        if !requests.lock().await.is_pending(*id, plugin_id) {
+                                            tracing::debug!(
+                                                "dropping late matches of {} for search {}",
+                                                plugin_id,
+                                                id
+                                            );
+                                            continue;
+                                        }
+                                        let Some((client_id, outbox, matches)) =
+                                            route_search(&sessions, *id).await
+                                        else {
+                                            continue;
+                                        };
+                                        // a chunk of a streamed search, more may follow
+                                        let mut items = resolve_icons(
+                                            &plugin_icons,
+                                            Some(plugin_id),
+                                            policy.filter(items.clone()),
+                                        )
+                                        .await;
+                                        let (metadata, streaming) = plugins_copy
+                                            .lock()
+                                            .await
+                                            .get(plugin_id)
+                                            .map_or((None, false), |p| {
+                                                (p.metadata.clone(), p.features.streaming)
+                                            });
+                                        // older plugins answer with all their matches at once
+                                        let streams = metadata.as_ref().is_some_and(|metadata| {
+                                            metadata.supports(Capability::StreamingSearch)
+                                        });
+                                        let frecency = match (&plugin_history, &metadata) {
+                                            (Some(history), Some(metadata)) => history
+                                                .lock()
+                                                .await
+                                                .frecency(&metadata.id, SystemTime::now())
+                                                .inspect_err(|e| {
+                                                    tracing::warn!("failed to rank by history: {}", e)
+                                                })
+                                                .unwrap_or_default(),
+                                            _ => HashMap::new(),
+                                        };
+                                        if let Some(metadata) = &metadata {
+                                            categorize(&mut items, &metadata.name);
+                                        }
+                                        let stats_id = metadata.as_ref().map_or(plugin_id, |m| &m.id);
+                                        plugin_metrics.results(stats_id, items.len());
+                                        let features = Features::of(&items, &frecency);
+                                        {
+                                            let strategy = plugin_ranking.lock().await;
+                                            for (item, features) in items.iter_mut().zip(&features) {
+                                                item.score = strategy.score(features);
+                                            }
+                                        }
+                                        let stamped = {
+                                            let mut matches = matches.lock().await;
+                                            highlight(&mut items, matches.query());
+                                            matches
+                                                .extend_ranked(client_id, plugin_id, &items, &features)
+                                                // without streaming, the page is sent once the
+                                                // search completes
+                                                .map(|stamped| match streaming {
+                                                    true => matches.deliver(stamped),
+                                                    false => vec![],
+                                                })
+                                        };
+                                        let Some(items) = stamped else {
+                                            tracing::debug!(
+                                                "dropping matches for stale search {}",
+                                                client_id
+                                            );
+                                            continue;
+                                        };
+                                        if !items.is_empty() {
+                                            let _ = outbox.push(Message::Response {
+                                                id: client_id,
+                                                error: None,
+                                                result: Some(MethodResult::Matches { items }),
+                                                plugin_id: Some(plugin_id.clone()),
+                                            });
+                                        }
+                                        let answered = match streams {
+                                            true => None,
+                                            false => requests.lock().await.finish(*id, plugin_id),
+                                        };
+                                        if let Some(elapsed) = answered {
+                                            plugin_stats
+                                                .lock()
+                                                .await
+                                                .record(stats_id, Outcome::Answered(elapsed));
+                                            plugin_metrics.search(stats_id, Outcome::Answered(elapsed));
+                                            finish_search(
+                                                &matches,
+                                                &outbox,
+                                                client_id,
+                                                plugin_id,
+                                                &plugin_last_results,
+                                            )
+                                            .await;
+                                        }
+                                        continue;
+                                    }
+
+                                    let Some(elapsed) = requests.lock().await.finish(*id, plugin_id)
+                                    else {
+                                        continue;
+                                    };
+                                    let outcome = match result {
+                                        Some(MethodResult::Done { .. }) => Outcome::Answered(elapsed),
+                                        _ => Outcome::Failed(elapsed),
+                                    };
+                                    let stats_id = plugin_id_of(&*plugins_copy.lock().await, plugin_id);
+                                    plugin_stats.lock().await.record(&stats_id, outcome);
+                                    plugin_metrics.search(&stats_id, outcome);
+                                    let Some((client_id, outbox, matches)) =
+                                        route_search(&sessions, *id).await
+                                    else {
+                                        continue;
+                                    };
+                                    if let Some(MethodResult::Done { truncated: true }) = result {
+                                        tracing::debug!(
+                                            "plugin {} ran out of time for search {}, its matches are partial",
+                                            plugin_id,
+                                            id
+                                        );
+                                    }
+                                    if !matches!(result, Some(MethodResult::Done { .. })) {
+                                        // errors end the plugin's part of the search too
+                                        let _ = outbox.push(with_id(message, client_id));
+                                    }
+                                    finish_search(
+                                        &matches,
+                                        &outbox,
+                                        client_id,
+                                        plugin_id,
+                                        &plugin_last_results,
+                                    )
+                                    .await;
                                 }
-                                Action::Launch { app_id, action } => {
-                                    dispatchers::launch_app(&app_id, &action.as_deref()).await
+                                Message::Notification {
+                                    method: Method::GetConfig,
+                                    ..
+                                } => {
+                                    if let Some(plugin) = plugins_copy.lock().await.get(plugin_id) {
+                                        configure_plugin(
+                                            &plugin_config::config_dir(),
+                                            plugin,
+                                            Method::Configure,
+                                        );
+                                    }
                                 }
-                                Action::Clipboard { text } => {
-                                    dispatchers::copy_to_clipboard(&text).await
+                                Message::Notification {
+                                    method: Method::ActionProgress(progress),
+                                    ..
+                                } => {
+                                    let sessions = sessions.lock().await;
+                                    let Some((client_id, session)) =
+                                        sessions.route_action(plugin_id, progress.action_id)
+                                    else {
+                                        tracing::debug!(
+                                            "dropping progress of finished action {}",
+                                            progress.action_id
+                                        );
+                                        continue;
+                                    };
+                                    let _ = session.outbox.push(Message::Notification {
+                                        method: Method::ActionProgress(ActionProgress {
+                                            action_id: client_id,
+                                            ..progress.clone()
+                                        }),
+                                        plugin_id: None,
+                                    });
                                 }
-                                Action::Open { uri } => dispatchers::open_url(&uri).await,
-                                Action::Callback { key, params } => {
-                                    let source_plugin_id = matches[match_index].plugin_id.clone();
-                                    let plugin_tx = plugins_copy
+                                Message::Notification {
+                                    method: Method::RunAction(action),
+                                    ..
+                                } => {
+                                    let (metadata, plugin) = match plugins_copy
                                         .lock()
                                         .await
-                                        .get(&source_plugin_id)
-                                        .map(|p| p.tx.clone());
-                                    if let Some(tx) = plugin_tx {
-                                        dispatchers::plugin_callback(tx, &key, &params).await;
-                                    } else {
+                                        .get(plugin_id)
+                                    {
+                                        Some(p) => (declared_metadata(p), Some((p.tx.clone(), 0))),
+                                        None => (None, None),
+                                    };
+                                    let checked = match action.steps() {
+                                        Err(err) => Err(err.to_string()),
+                                        Ok(_) => policy.check(action).map_err(|e| e.to_string()),
+                                    };
+                                    let checked = match checked {
+                                        Ok(()) => plugin_grants
+                                            .lock()
+                                            .await
+                                            .check_plugin(plugin_id, metadata.as_ref(), action)
+                                            .map_err(|e| e.to_string()),
+                                        err => err,
+                                    };
+                                    if let Err(err) = checked {
                                         tracing::warn!(
-                                            "failed to find plugin for callback: {}",
-                                            source_plugin_id
+                                            "refusing action of plugin {}: {}",
+                                            plugin_id,
+                                            err
                                         );
+                                        continue;
                                     }
+                                    let dispatcher = plugin_dispatcher.clone();
+                                    let action = action.clone();
+                                    tokio::spawn(async move {
+                                        if let Err(e) =
+                                            dispatch_action(dispatcher.as_ref(), &action, plugin)
+                                                .await
+                                        {
+                                            tracing::error!("failed to run plugin action: {}", e);
+                                        }
+                                    });
                                 }
-                            }
+                                _ => {
+                                    sessions.lock().await.broadcast(message);
+                                }
+                            };
                         }
-                        Method::Cancel => {
-                            current_request.store(0, Ordering::SeqCst);
-                            current_matches.lock().await.clear();
-                            for plugin in plugins_copy.lock().await.values() {
-                                let tx = plugin.tx.clone();
-                                let request = Message::Request {
+                    }
+                }
+            }
+        });
+
+        let context = ClientContext {
+            plugins: plugins_arc.clone(),
+            sessions: self.sessions.clone(),
+            requests: self.requests.clone(),
+            request_tracked,
+            history,
+            stats: stats.clone(),
+            metrics: self.metrics.clone(),
+            logs,
+            ranking,
+            ranking_log,
+            icons,
+            last_results,
+            grants,
+            available_updates: updates_arc,
+            trust,
+            untrusted,
+            discovery: rescan_tx,
+            dispatcher: self.dispatcher.clone(),
+            janitor: self.janitor.clone(),
+            power: self.power.clone(),
+            previews: preview_tx,
+            config: self.config.clone(),
+            shutdown: Arc::new(Notify::new()),
+        };
+
+        // the stdio client owns the daemon, with it gone there is nobody to serve
+        let mut stdio_handle = match stdio {
+            true => tokio::spawn(serve_client(context.clone(), stdin(), stdout(), true)),
+            false => tokio::spawn(std::future::pending()),
+        };
+        let (dbus, dbus_session) = match self.config.dbus.enabled {
+            true => serve_dbus(&context).await,
+            false => (None, None),
+        };
+        let shortcut = self
+            .config
+            .shortcut
+            .enabled
+            .then(|| tokio::spawn(shortcut::serve(self.config.shortcut.clone())));
+        let mut connections = JoinSet::new();
+        let mut last_client = Instant::now();
+        loop {
+            let idle_exit = self
+                .idle_exit
+                .filter(|_| !stdio && connections.is_empty())
+                .map(|after| tokio::time::Instant::from_std(last_client + after));
+            tokio::select! {
+                _ = &mut stdio_handle => {
+                    tracing::debug!("stdio client left, shutting down");
+                    break;
+                }
+                accepted = accept(listener.as_mut()) => match accepted {
+                    Ok((reader, writer)) => {
+                        // without a stdio client, socket clients may shut the daemon down
+                        connections.spawn(serve_client(context.clone(), reader, writer, !stdio));
+                    }
+                    Err(e) => tracing::warn!("failed to accept client: {}", e),
+                },
+                Some(joined) = connections.join_next() => {
+                    last_client = Instant::now();
+                    if let Err(e) = joined
+                        && e.is_panic()
+                    {
+                        tracing::error!(
+                            "client session panicked: {}",
+                            panic_message(e.into_panic().as_ref())
+                        );
+                    }
+                }
+                _ = context.shutdown.notified() => break,
+                _ = tokio::time::sleep_until(idle_exit.unwrap_or_else(tokio::time::Instant::now)),
+                    if idle_exit.is_some() =>
+                {
+                    tracing::info!(
+                        "no client for {}s, shutting down",
+                        self.config.activation.idle_exit_secs
+                    );
+                    break;
+                }
+                name = supervisor.watch() => {
+                    tracing::error!("{} task is gone, shutting down", name);
+                    break;
+                }
+            }
+        }
+
+        stdio_handle.abort();
+        drop(dbus);
+        if let Some(dbus_session) = dbus_session {
+            dbus_session.abort();
+        }
+        if let Some(shortcut) = shortcut {
+            shortcut.abort();
+        }
+        connections.shutdown().await;
+        supervisor.shutdown().await;
+        if let Err(e) = stats.lock().await.save() {
+            tracing::warn!("failed to save plugin stats: {}", e);
+        }
+
+        // the sessions are over, plugins can stop pushing updates and searching
+        let clients = self.sessions.lock().await.clients();
+        for client in clients {
+            end_session(&context, client).await;
+        }
+        drop(context);
+        self.requests.lock().await.clear();
+        let plugins = plugins_arc.lock().await;
+        for plugin in plugins.values() {
+            send_to_plugin(
+                plugin,
+                Message::Request {
+                    id: 0,
+                    method: Method::Quit,
+                    plugin_id: None,
+                    deadline_ms: None,
+                },
+            );
+        }
+        drop(plugins);
+        let handles = plugins_arc
+            .lock()
+            .await
+            .drain()
+            .map(|(_, plugin)| plugin.handle)
+            .collect::<Vec<_>>();
+        drop(plugins_arc);
+
+        tracing::debug!("shutting down, waiting for plugins to exit");
+        for handle in handles {
+            let _ = handle.await;
+        }
+
+        if self.compression.messages() > 0 {
+            tracing::info!("decompressed {}", self.compression);
+        }
+        tracing::debug!("all plugins exited, daemon shutting down");
+    }
+}
+
+/// Offer searches on the session bus through a client session of the daemon's own, which
+/// lasts as long as the returned connection.
+async fn serve_dbus(
+    context: &ClientContext,
+) -> (
+    Option<zbus::Connection>,
+    Option<tokio::task::JoinHandle<()>>,
+) {
+    let (daemon_end, client_end) = tokio::io::duplex(64 * 1024);
+    let (client_reader, client_writer) = tokio::io::split(client_end);
+    match dbus::serve(Client::from_io(client_reader, client_writer)).await {
+        Ok(connection) => {
+            tracing::info!("serving {} on the session bus", dbus::SERVICE);
+            let (reader, writer) = tokio::io::split(daemon_end);
+            let session = tokio::spawn(serve_client(context.clone(), reader, writer, false));
+            (Some(connection), Some(session))
+        }
+        Err(e) => {
+            tracing::warn!("not serving {} on the session bus: {}", dbus::SERVICE, e);
+            (None, None)
+        }
+    }
+}
+
+async fn accept(
+    listener: Option<&mut ClientListener>,
+) -> std::io::Result<(ClientReader, ClientWriter)> {
+    match listener {
+        Some(listener) => listener.accept().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Serve one client until it disconnects, stops reading or quits.
+async fn serve_client<R, W>(context: ClientContext, reader: R, mut writer: W, owner: bool)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let (client, outbox, matches) = {
+        let mut sessions = context.sessions.lock().await;
+        let (client, session) = sessions.open(context.config.outbox.clone());
+        (client, session.outbox.clone(), session.matches.clone())
+    };
+    tracing::info!("client {} connected", client);
+    if context.power.profile() == PowerProfile::LowPower {
+        let _ = outbox.push(power_profile(PowerProfile::LowPower));
+    }
+    let untrusted = context.untrusted.lock().await.clone();
+    if !untrusted.is_empty() {
+        let _ = outbox.push(plugins_held_back(untrusted));
+    }
+
+    tokio::select! {
+        _ = read_requests(&context, client, &outbox, &matches, reader, owner) => {
+            tracing::debug!("client {} closed the session", client);
+        }
+        _ = write_responses(&outbox, &mut writer) => {
+            tracing::info!("client {} stopped reading, ending the session", client);
+        }
+    }
+
+    end_session(&context, client).await;
+}
+
+async fn write_responses<W>(outbox: &Outbox, writer: &mut W)
+where
+    W: AsyncWrite + Unpin,
+{
+    while let Some(message) = outbox.pop().await {
+        let response = serde_json::to_string(&message).unwrap();
+        tracing::debug!("plugin response -> client: {:?}", &message);
+        tokio::select! {
+            written = write_line(writer, &response) => {
+                if let Err(e) = written {
+                    tracing::warn!("client disconnected: {}", e);
+                    break;
+                }
+            }
+            // a client that stopped reading blocks the write forever
+            _ = outbox.closed() => break,
+        }
+    }
+    outbox.close();
+}
+
+async fn read_requests<R>(
+    context: &ClientContext,
+    client: ClientId,
+    outbox: &Arc<Outbox>,
+    current_matches: &Mutex<MatchStore>,
+    reader: R,
+    owner: bool,
+) where
+    R: AsyncRead + Unpin,
+{
+    let mut lines = BufReader::new(reader).lines();
+    let timeout = context.config.requests.timeout();
+    let budget_ms = context.config.requests.plugin_budget_ms();
+    // messages of a batch are handled one by one, as if sent on their own lines
+    let mut queued = VecDeque::new();
+    loop {
+        let Some(message) = queued.pop_front() else {
+            match lines.next_line().await {
+                Ok(Some(line)) => queue_frame(&line, &mut queued),
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::warn!("failed to read from client {}: {}", client, e);
+                    break;
+                }
+            }
+            continue;
+        };
+        tracing::debug!("client {} request -> plugins: {:?}", client, &message);
+        context.janitor.touch();
+
+        match message {
+            Message::Request {
+                id,
+                method,
+                ref plugin_id,
+                ..
+            } => match method {
+                Method::Search(query) => {
+                    // clients search on every keystroke, only the query typing pauses on
+                    // reaches plugins
+                    let quiet = power::debounce(
+                        context.power.profile(),
+                        context.config.requests.debounce(),
+                    );
+                    if !quiet.is_zero()
+                        && !dispatches_at_once(context, &query, plugin_id.as_deref()).await
+                        && superseded(&mut lines, &mut queued, quiet).await
+                    {
+                        tracing::debug!("search {} superseded while typing", id);
+                        continue;
+                    }
+                    let started = context.sessions.lock().await.start_search(client, id);
+                    let Some((search, previous)) = started else {
+                        break;
+                    };
+                    outbox.supersede(id);
+                    if let Some(previous) = previous {
+                        cancel_search(context, previous).await;
+                    }
+                    let mut matches = current_matches.lock().await;
+                    matches.set_page_size(context.config.requests.page_size());
+                    matches.set_fallback_score(context.config.ranking.fallback_score);
+                    matches.reset(id);
+                    matches.set_query(&query);
+
+                    // the command palette is answered by the daemon, plugins are not searched
+                    if plugin_id.is_none()
+                        && let Some(query) = context.config.commands.strip(&query)
+                    {
+                        let found = {
+                            let plugins = context.plugins.lock().await;
+                            let plugins = plugins
+                                .iter()
+                                .filter_map(|(key, plugin)| {
+                                    Some((key.as_str(), plugin.metadata.as_ref()?))
+                                })
+                                .collect::<Vec<_>>();
+                            let installed = plugins.iter().map(|(_, m)| *m).collect::<Vec<_>>();
+                            let builtins = BuiltinCommand::available(
+                                &installed,
+                                context.power.profile(),
+                                context.config.updates.manifest_url().is_some(),
+                            );
+                            commands::palette(query, &builtins, &plugins)
+                        };
+                        for (key, rows) in found {
+                            let plugin =
+                                Some(key.as_str()).filter(|key| *key != commands::PROVIDER_KEY);
+                            let rows = resolve_icons(&context.icons, plugin, rows).await;
+                            if let Some(stamped) = matches.extend(id, &key, &rows)
+                                && let items = matches.deliver(stamped)
+                                && !items.is_empty()
+                            {
+                                let _ = outbox.push(Message::Response {
                                     id,
-                                    method: Method::Cancel,
+                                    error: None,
+                                    result: Some(MethodResult::Matches { items }),
                                     plugin_id: None,
-                                };
-                                tokio::spawn(async move {
-                                    if let Err(e) = tx.send(request).await {
-                                        tracing::error!("failed to send cancel to plugin: {}", e);
-                                    }
                                 });
                             }
                         }
-                        Method::Quit => {
-                            tracing::info!("received quit command, shutting down");
-                            for plugin in plugins_copy.lock().await.values() {
-                                let tx = plugin.tx.clone();
-                                let request = Message::Request {
-                                    id,
-                                    method: Method::Quit,
-                                    plugin_id: None,
-                                };
-                                tokio::spawn(async move {
-                                    if let Err(e) = tx.send(request).await {
-                                        tracing::error!("failed to send cancel to plugin: {}", e);
-                                    }
-                                });
+                        let _ = outbox.push(snapshot_message(id, &matches));
+                        continue;
+                    }
+
+                    let mut tracked = context.requests.lock().await;
+                    let deadline = Instant::now() + timeout;
+
+                    let plugins = context.plugins.lock().await;
+                    let route = match plugin_id {
+                        Some(_) => Route::Broadcast,
+                        None => routing::route(
+                            &query,
+                            plugins.values().filter_map(|p| p.metadata.as_ref()),
+                        ),
+                    };
+                    if route == Route::Broadcast && updates::is_update_query(&query) {
+                        let rows = updates::update_matches(&context.available_updates.lock().await);
+                        let rows = resolve_icons(&context.icons, None, rows).await;
+                        if let Some(stamped) = matches.extend(id, updates::PROVIDER_KEY, &rows)
+                            && let items = matches.deliver(stamped)
+                            && !items.is_empty()
+                        {
+                            let _ = outbox.push(Message::Response {
+                                id,
+                                error: None,
+                                result: Some(MethodResult::Matches { items }),
+                                plugin_id: Some(updates::DAEMON_COMPONENT.to_string()),
+                            });
+                        }
+                    }
+
+                    // a single prefixed plugin skips ranking, its results pass through
+                    let mut literal = false;
+                    let (target, query) = match route {
+                        Route::Prefixed {
+                            plugin_id: target,
+                            query,
+                        } => {
+                            tracing::debug!("routing search {} to {}", id, target);
+                            matches.set_passthrough(true);
+                            (Some(target), query)
+                        }
+                        Route::Literal { query } => {
+                            literal = true;
+                            (None, query)
+                        }
+                        Route::Broadcast => (plugin_id.clone(), query),
+                    };
+
+                    let dependencies =
+                        Dependencies::of(plugins.values().filter_map(|p| p.metadata.as_ref()));
+                    for (key, plugin) in dispatch_order(&plugins, &dependencies) {
+                        if target.is_none()
+                            && !routing::takes_unrouted(plugin.metadata.as_ref(), literal)
+                        {
+                            continue;
+                        }
+                        if waits_on_dependencies(plugin, &dependencies, &context.config) {
+                            continue;
+                        }
+                        if let Some(target) = &target {
+                            let Some(metadata) = &plugin.metadata else {
+                                continue;
+                            };
+                            if &metadata.id != target {
+                                continue;
                             }
-                            break;
                         }
-                        Method::CallAction(key, params) => {
-                            tracing::warn!(
-                                "unexpected CallAction method from client: {} {:?}",
-                                key,
-                                params
+
+                        let excluded = plugin.metadata.as_ref().is_some_and(|metadata| {
+                            context.last_results.config().is_excluded(metadata)
+                        });
+                        if excluded {
+                            matches.expect_private(key);
+                        } else {
+                            matches.expect(key);
+                        }
+                        let paginated = plugin
+                            .metadata
+                            .as_ref()
+                            .is_some_and(|metadata| metadata.supports(Capability::Paginated));
+                        if paginated {
+                            matches.expect_pages(key);
+                        }
+                        // a search routed to it by prefix is all its own
+                        let fallback = plugin
+                            .metadata
+                            .as_ref()
+                            .is_some_and(|metadata| metadata.fallback);
+                        if fallback && target.is_none() {
+                            matches.expect_fallback(key);
+                        }
+                        tracked.track(search, key, deadline);
+                        send_to_plugin(
+                            plugin,
+                            Message::Request {
+                                id: search,
+                                method: Method::Search(query.clone()),
+                                plugin_id: None,
+                                deadline_ms: Some(budget_ms),
+                            },
+                        );
+                    }
+
+                    drop(tracked);
+                    context.request_tracked.notify_one();
+
+                    if matches.is_complete() {
+                        let _ = outbox.push(snapshot_message(id, &matches));
+                    }
+                }
+                Method::More {
+                    request_id,
+                    offset,
+                    limit,
+                } => {
+                    let mut matches = current_matches.lock().await;
+                    let Some(ask) = matches.more(request_id, offset, limit) else {
+                        let _ = outbox.push(Message::Response {
+                            id,
+                            error: Some(RpcError::rejected(format!(
+                                "stale search: {}",
+                                request_id
+                            ))),
+                            result: None,
+                            plugin_id: None,
+                        });
+                        continue;
+                    };
+                    // the page goes out under the search, a search still running sends it
+                    // once it completes
+                    let search = context
+                        .sessions
+                        .lock()
+                        .await
+                        .get(client)
+                        .and_then(|session| session.search);
+                    if let Some(search) = search
+                        && !ask.is_empty()
+                    {
+                        let mut tracked = context.requests.lock().await;
+                        let deadline = Instant::now() + timeout;
+                        let plugins = context.plugins.lock().await;
+                        for (key, offset) in ask {
+                            let Some(plugin) = plugins.get(&key) else {
+                                matches.finish(request_id, &key);
+                                continue;
+                            };
+                            tracked.track(search, &key, deadline);
+                            send_to_plugin(
+                                plugin,
+                                Message::Request {
+                                    id: search,
+                                    method: Method::More {
+                                        request_id: search,
+                                        offset,
+                                        limit,
+                                    },
+                                    plugin_id: None,
+                                    deadline_ms: Some(budget_ms),
+                                },
                             );
                         }
-                    },
-                    Message::Notification { method, .. } => match method {
-                        _ => {}
-                    },
-                    Message::Response { .. } => {}
+                        drop(tracked);
+                        context.request_tracked.notify_one();
+                    }
+                    if matches.is_complete() {
+                        send_page(outbox, request_id, &mut matches);
+                    }
+                }
+                Method::Activate {
+                    generation,
+                    match_id,
+                    action,
+                    modifiers,
+                    confirmed,
+                } => {
+                    let matches = current_matches.lock().await;
+                    let (holder, match_action) = match matches.action(generation, match_id, action)
+                    {
+                        Ok(found) => found,
+                        Err(err) => {
+                            tracing::warn!("rejected activation: {}", err);
+                            let _ = outbox.push(Message::Response {
+                                id,
+                                error: Some(RpcError::rejected(err.to_string())),
+                                result: None,
+                                plugin_id: None,
+                            });
+                            continue;
+                        }
+                    };
+                    // plugins opt in to expanding `~` and variables in the fields they name
+                    let expanded = Environment::current()
+                        .expand_action(match_action.action_for(&modifiers), &match_action.expand);
+                    let action = match &expanded {
+                        Ok(action) => action,
+                        Err(err) => {
+                            tracing::warn!("rejected activation: {}", err);
+                            let _ = outbox.push(Message::Response {
+                                id,
+                                error: Some(RpcError::rejected(err.to_string())),
+                                result: None,
+                                plugin_id: None,
+                            });
+                            continue;
+                        }
+                    };
+                    if let Err(err) = action.steps() {
+                        tracing::warn!("rejected activation: {}", err);
+                        let _ = outbox.push(Message::Response {
+                            id,
+                            error: Some(RpcError::rejected(err.to_string())),
+                            result: None,
+                            plugin_id: None,
+                        });
+                        continue;
+                    }
+                    if let Err(err) = context.config.policy.check(action) {
+                        tracing::warn!("rejected activation: {}", err);
+                        let _ = outbox.push(Message::Response {
+                            id,
+                            error: Some(RpcError::rejected(err.to_string())),
+                            result: None,
+                            plugin_id: None,
+                        });
+                        continue;
+                    }
+                    // nothing is recorded or run until the user confirmed
+                    if match_action.requires_confirmation && !confirmed {
+                        let _ = outbox.push(Message::Response {
+                            id,
+                            error: Some(RpcError::confirmation_required(match_action)),
+                            result: None,
+                            plugin_id: None,
+                        });
+                        continue;
+                    }
+                    if holder.plugin_id == commands::PROVIDER_KEY {
+                        match BuiltinCommand::from_action(action) {
+                            Some(command) => run_command(context, command).await,
+                            None => tracing::warn!("unknown daemon command: {:?}", action),
+                        }
+                        continue;
+                    }
+                    let plugins = context.plugins.lock().await;
+                    let plugin = plugins.get(&holder.plugin_id);
+                    if let Err(err) = context.grants.lock().await.check_plugin(
+                        &holder.plugin_id,
+                        plugin.and_then(declared_metadata).as_ref(),
+                        action,
+                    ) {
+                        tracing::warn!("rejected activation: {}", err);
+                        let _ = outbox.push(Message::Response {
+                            id,
+                            error: Some(err.to_rpc()),
+                            result: None,
+                            plugin_id: None,
+                        });
+                        continue;
+                    }
+                    if let Some((chosen, shown)) = matches.impression(generation, match_id) {
+                        context.ranking.lock().await.learn(&shown[chosen], &shown);
+                        if let Some(log) = &context.ranking_log
+                            && let Err(e) =
+                                log.append(&RankingEvent::new(chosen, shown, SystemTime::now()))
+                        {
+                            tracing::warn!("failed to log activation for ranking: {}", e);
+                        }
+                    }
+                    let plugin_tx = plugin.map(|p| p.tx.clone());
+                    let reports_progress = plugin
+                        .and_then(|p| p.metadata.as_ref())
+                        .is_some_and(|metadata| metadata.supports(Capability::ActionProgress));
+                    if let Some(history) = &context.history
+                        && let Some(metadata) = plugin.and_then(|p| p.metadata.as_ref())
+                        && let Err(e) = history.lock().await.record(
+                            &metadata.id,
+                            &holder.match_.title,
+                            SystemTime::now(),
+                        )
+                    {
+                        tracing::warn!("failed to record activation: {}", e);
+                    }
+                    if let Some(metadata) = plugin.and_then(|p| p.metadata.as_ref()) {
+                        context.stats.lock().await.activated(&metadata.id);
+                    }
+                    drop(plugins);
+                    // callbacks may take a while, progress and the outcome go back under `id`;
+                    // older plugins run them as requests of the daemon nobody waits for
+                    let action_id = match action {
+                        Action::Callback { .. } if reports_progress => context
+                            .sessions
+                            .lock()
+                            .await
+                            .start_action(client, id, &holder.plugin_id),
+                        _ => None,
+                    };
+                    let plugin = plugin_tx.map(|tx| (tx, action_id.unwrap_or_default()));
+                    if !matches!(action, Action::Sequence { .. }) {
+                        if let Err(e) =
+                            dispatch_action(context.dispatcher.as_ref(), action, plugin).await
+                        {
+                            tracing::error!("failed to run action: {}", e);
+                        }
+                        continue;
+                    }
+                    // the client learns how far the sequence got
+                    let response = match dispatch_sequence(
+                        context.dispatcher.as_ref(),
+                        action,
+                        plugin,
+                    )
+                    .await
+                    {
+                        Ok(steps) => Message::Response {
+                            id,
+                            error: None,
+                            result: Some(MethodResult::Sequence { steps }),
+                            plugin_id: None,
+                        },
+                        Err(e) => Message::Response {
+                            id,
+                            error: Some(RpcError::rejected(e.to_string())),
+                            result: None,
+                            plugin_id: None,
+                        },
+                    };
+                    let _ = outbox.push(response);
+                }
+                Method::Details {
+                    generation,
+                    match_id,
+                } => {
+                    let matches = current_matches.lock().await;
+                    let holder = match matches.get(generation, match_id) {
+                        Ok(holder) => holder,
+                        Err(err) => {
+                            let _ = outbox.push(Message::Response {
+                                id,
+                                error: Some(RpcError::rejected(err.to_string())),
+                                result: None,
+                                plugin_id: None,
+                            });
+                            continue;
+                        }
+                    };
+                    let plugins = context.plugins.lock().await;
+                    let lazy = plugins
+                        .get(&holder.plugin_id)
+                        .filter(|plugin| {
+                            plugin
+                                .metadata
+                                .as_ref()
+                                .is_some_and(|metadata| metadata.supports(Capability::Details))
+                        })
+                        .filter(|_| holder.match_.detail.is_none());
+                    let Some(plugin) = lazy else {
+                        let _ = outbox.push(Message::Response {
+                            id,
+                            error: None,
+                            result: Some(MethodResult::Details {
+                                detail: holder.match_.detail.clone(),
+                            }),
+                            plugin_id: None,
+                        });
+                        continue;
+                    };
+                    let forwarded =
+                        context
+                            .sessions
+                            .lock()
+                            .await
+                            .start_details(client, id, &holder.plugin_id);
+                    if let Some(forwarded) = forwarded {
+                        send_to_plugin(
+                            plugin,
+                            Message::Request {
+                                id: forwarded,
+                                method: Method::Describe(Box::new(holder.match_.clone())),
+                                plugin_id: None,
+                                deadline_ms: Some(budget_ms),
+                            },
+                        );
+                    }
+                }
+                Method::Viewport {
+                    generation,
+                    first_index,
+                    last_index,
+                } => {
+                    prefetch_visible(
+                        context,
+                        client,
+                        current_matches,
+                        generation,
+                        first_index..=last_index,
+                    )
+                    .await;
+                }
+                Method::Updates { check } => {
+                    let Some(url) = context.config.updates.manifest_url() else {
+                        let _ = outbox.push(Message::Response {
+                            id,
+                            error: Some(RpcError::rejected(UpdateError::Disabled.to_string())),
+                            result: None,
+                            plugin_id: None,
+                        });
+                        continue;
+                    };
+                    if !check {
+                        let items = context.available_updates.lock().await.clone();
+                        let _ = outbox.push(Message::Response {
+                            id,
+                            error: None,
+                            result: Some(MethodResult::Updates { items }),
+                            plugin_id: None,
+                        });
+                        continue;
+                    }
+
+                    let url = url.to_string();
+                    let plugins = context.plugins.clone();
+                    let available_updates = context.available_updates.clone();
+                    let outbox = outbox.clone();
+                    tokio::spawn(async move {
+                        let response = match check_updates(&url, &plugins, &available_updates).await
+                        {
+                            Ok(items) => Message::Response {
+                                id,
+                                error: None,
+                                result: Some(MethodResult::Updates { items }),
+                                plugin_id: None,
+                            },
+                            Err(e) => Message::Response {
+                                id,
+                                error: Some(RpcError::internal(e.to_string())),
+                                result: None,
+                                plugin_id: None,
+                            },
+                        };
+                        let _ = outbox.push(response);
+                    });
+                }
+                Method::Plugins => {
+                    let mut items = context
+                        .plugins
+                        .lock()
+                        .await
+                        .values()
+                        .filter_map(|plugin| plugin.metadata.clone())
+                        .collect::<Vec<_>>();
+                    items.sort_by(|a, b| a.id.cmp(&b.id));
+                    let _ = outbox.push(Message::Response {
+                        id,
+                        error: None,
+                        result: Some(MethodResult::Plugins { items }),
+                        plugin_id: None,
+                    });
+                }
+                Method::UntrustedPlugins => {
+                    let items = context.untrusted.lock().await.clone();
+                    let _ = outbox.push(Message::Response {
+                        id,
+                        error: None,
+                        result: Some(MethodResult::UntrustedPlugins { items }),
+                        plugin_id: None,
+                    });
+                }
+                Method::TrustPlugin { path, sha256 } => {
+                    let held = context
+                        .untrusted
+                        .lock()
+                        .await
+                        .iter()
+                        .any(|plugin| plugin.path == path);
+                    let trusted = match held {
+                        true => context.trust.lock().await.trust(&path, &sha256),
+                        false => Err(TrustError::NotHeld { path: path.clone() }),
+                    };
+                    let response = match trusted {
+                        Ok(()) => {
+                            tracing::info!("trusting plugin {}", path);
+                            context
+                                .untrusted
+                                .lock()
+                                .await
+                                .retain(|plugin| plugin.path != path);
+                            // the rescan starts it
+                            let _ = context.discovery.send(());
+                            Message::Response {
+                                id,
+                                error: None,
+                                result: Some(MethodResult::None),
+                                plugin_id: None,
+                            }
+                        }
+                        Err(e) => {
+                            tracing::warn!("not trusting plugin {}: {}", path, e);
+                            Message::Response {
+                                id,
+                                error: Some(RpcError::rejected(e.to_string())),
+                                result: None,
+                                plugin_id: None,
+                            }
+                        }
+                    };
+                    let _ = outbox.push(response);
+                }
+                Method::PluginStats => {
+                    let items = context.stats.lock().await.summary();
+                    let _ = outbox.push(Message::Response {
+                        id,
+                        error: None,
+                        result: Some(MethodResult::PluginStats { items }),
+                        plugin_id: None,
+                    });
+                }
+                Method::Stats => {
+                    let items = context.metrics.summary();
+                    let _ = outbox.push(Message::Response {
+                        id,
+                        error: None,
+                        result: Some(MethodResult::Stats { items }),
+                        plugin_id: None,
+                    });
+                }
+                Method::Features => {
+                    let plugins = context
+                        .plugins
+                        .lock()
+                        .await
+                        .values()
+                        .filter_map(|plugin| {
+                            let metadata = plugin.metadata.as_ref()?;
+                            Some((metadata.id.clone(), plugin.features))
+                        })
+                        .collect();
+                    let _ = outbox.push(Message::Response {
+                        id,
+                        error: None,
+                        result: Some(MethodResult::Features {
+                            daemon: context.config.features,
+                            plugins,
+                        }),
+                        plugin_id: None,
+                    });
+                }
+                Method::GetLogs { plugin_id, since } => {
+                    let name = match plugin_id.as_str() {
+                        logs::DAEMON_LOG => plugin_id.clone(),
+                        _ => {
+                            let plugins = context.plugins.lock().await;
+                            match plugins.contains_key(&plugin_id) {
+                                true => log_name(&plugin_id),
+                                false => find_plugin_key(&plugins, &plugin_id)
+                                    .map_or(plugin_id.clone(), |key| log_name(&key)),
+                            }
+                        }
+                    };
+                    let read = match context.logs.enabled() {
+                        true => context.logs.read(&name, since),
+                        false => Err(std::io::Error::other("plugin logs are disabled")),
+                    };
+                    let response = match read {
+                        Ok(items) => Message::Response {
+                            id,
+                            error: None,
+                            result: Some(MethodResult::Logs { items }),
+                            plugin_id: None,
+                        },
+                        Err(e) => Message::Response {
+                            id,
+                            error: Some(RpcError::rejected(format!(
+                                "no logs of {}: {}",
+                                plugin_id, e
+                            ))),
+                            result: None,
+                            plugin_id: None,
+                        },
+                    };
+                    let _ = outbox.push(response);
+                }
+                Method::GrantPermission {
+                    plugin_id,
+                    permission,
+                } => {
+                    tracing::info!("granting {:?} to plugin {}", permission, plugin_id);
+                    let granted = context.grants.lock().await.grant(&plugin_id, permission);
+                    let response = match granted {
+                        Ok(()) => Message::Response {
+                            id,
+                            error: None,
+                            result: Some(MethodResult::None),
+                            plugin_id: None,
+                        },
+                        Err(e) => Message::Response {
+                            id,
+                            error: Some(RpcError::internal(e.to_string())),
+                            result: None,
+                            plugin_id: None,
+                        },
+                    };
+                    let _ = outbox.push(response);
+                }
+                Method::LastResults => {
+                    outbox.supersede(id);
+                    let search = context.sessions.lock().await.end_search(client);
+                    if let Some(search) = search {
+                        cancel_search(context, search).await;
+                    }
+                    let result = match context.last_results.load(SystemTime::now()) {
+                        Some(snapshot) => {
+                            // restored matches belong to this request, activations refer to it
+                            let mut matches = current_matches.lock().await;
+                            matches.set_page_size(context.config.requests.page_size());
+                            matches.reset(id);
+                            matches.set_query(&snapshot.query);
+                            matches.set_passthrough(true);
+                            let mut items = vec![];
+                            for saved in snapshot.items {
+                                let allowed = context.config.policy.filter(vec![saved.match_]);
+                                if let Some(stamped) =
+                                    matches.extend(id, &saved.plugin_key, &allowed)
+                                {
+                                    items.extend(matches.deliver(stamped));
+                                }
+                            }
+                            MethodResult::LastResults {
+                                query: snapshot.query,
+                                items,
+                            }
+                        }
+                        None => MethodResult::None,
+                    };
+                    let _ = outbox.push(Message::Response {
+                        id,
+                        error: None,
+                        result: Some(result),
+                        plugin_id: None,
+                    });
+                }
+                Method::History { limit } => {
+                    let recent = match &context.history {
+                        Some(history) => history
+                            .lock()
+                            .await
+                            .recent(limit, SystemTime::now())
+                            .map_err(|e| e.to_string()),
+                        None => Ok(vec![]),
+                    };
+                    let response = match recent {
+                        Ok(items) => Message::Response {
+                            id,
+                            error: None,
+                            result: Some(MethodResult::History { items }),
+                            plugin_id: None,
+                        },
+                        Err(e) => Message::Response {
+                            id,
+                            error: Some(RpcError::internal(e)),
+                            result: None,
+                            plugin_id: None,
+                        },
+                    };
+                    let _ = outbox.push(response);
+                }
+                Method::Cancel(_) => {
+                    outbox.supersede(id);
+                    current_matches.lock().await.reset(0);
+                    let search = context.sessions.lock().await.end_search(client);
+                    if let Some(search) = search {
+                        cancel_search(context, search).await;
+                    }
+                }
+                Method::Quit => {
+                    // other clients may not shut down a daemon that serves a stdio client
+                    if owner {
+                        tracing::info!("received quit command, shutting down");
+                        context.shutdown.notify_one();
+                    } else {
+                        tracing::debug!("client {} quit", client);
+                    }
+                    break;
+                }
+                Method::Subscribe {
+                    plugin_id: target,
+                    topic,
+                } => {
+                    let key = find_plugin_key(&*context.plugins.lock().await, &target);
+                    let Some(key) = key else {
+                        let _ = outbox.push(Message::Response {
+                            id,
+                            error: Some(RpcError::rejected(format!("unknown plugin: {}", target))),
+                            result: None,
+                            plugin_id: None,
+                        });
+                        continue;
+                    };
+                    let (supported, negotiated) =
+                        context
+                            .plugins
+                            .lock()
+                            .await
+                            .get(&key)
+                            .map_or((false, false), |p| {
+                                let supported = p.metadata.as_ref().is_some_and(|metadata| {
+                                    metadata.supports(Capability::Subscriptions)
+                                });
+                                (supported, p.features.subscriptions)
+                            });
+                    if supported && !negotiated {
+                        let _ = outbox.push(Message::Response {
+                            id,
+                            error: Some(RpcError::rejected(format!(
+                                "subscriptions are not enabled with plugin {}",
+                                target
+                            ))),
+                            result: None,
+                            plugin_id: None,
+                        });
+                        continue;
+                    }
+                    if !supported {
+                        let _ = outbox.push(Message::Response {
+                            id,
+                            error: Some(RpcError::new(
+                                RpcError::METHOD_NOT_FOUND,
+                                format!("plugin does not support subscriptions: {}", target),
+                            )),
+                            result: None,
+                            plugin_id: None,
+                        });
+                        continue;
+                    }
+
+                    let subscribed = context
+                        .sessions
+                        .lock()
+                        .await
+                        .subscribe(client, &key, &topic, id);
+                    let Some(subscription) = subscribed else {
+                        break;
+                    };
+                    // a plugin publishes each topic once, every subscribed client gets it
+                    if let Some(plugin) = context.plugins.lock().await.get(&key) {
+                        send_to_plugin(
+                            plugin,
+                            Message::Request {
+                                id: subscription,
+                                method: Method::Subscribe {
+                                    plugin_id: target,
+                                    topic,
+                                },
+                                plugin_id: None,
+                                deadline_ms: None,
+                            },
+                        );
+                    }
+                }
+                Method::Unsubscribe {
+                    plugin_id: target,
+                    topic,
+                } => {
+                    let key = find_plugin_key(&*context.plugins.lock().await, &target);
+                    let Some(key) = key else {
+                        continue;
+                    };
+                    let unused = context
+                        .sessions
+                        .lock()
+                        .await
+                        .unsubscribe(client, &key, &topic);
+                    if unused && let Some(plugin) = context.plugins.lock().await.get(&key) {
+                        send_to_plugin(
+                            plugin,
+                            Message::Request {
+                                id: 0,
+                                method: Method::Unsubscribe {
+                                    plugin_id: target,
+                                    topic,
+                                },
+                                plugin_id: None,
+                                deadline_ms: None,
+                            },
+                        );
+                    }
+                }
+                Method::CancelAction { action_id } => {
+                    let running = context.sessions.lock().await.find_action(client, action_id);
+                    let Some((action, key)) = running else {
+                        tracing::debug!("action {} is not running", action_id);
+                        continue;
+                    };
+                    // the plugin answers the cancelled request, which ends the action
+                    if let Some(plugin) = context.plugins.lock().await.get(&key) {
+                        send_to_plugin(plugin, cancel_request(plugin, action));
+                    }
+                }
+                Method::CallAction(key, params) => {
+                    tracing::warn!(
+                        "unexpected CallAction method from client: {} {:?}",
+                        key,
+                        params
+                    );
+                }
+                Method::Describe(_) => {
+                    tracing::warn!("unexpected Describe method from client");
+                }
+                Method::Configure(_) | Method::ConfigChanged(_) | Method::GetConfig => {
+                    tracing::warn!("unexpected configuration method from client");
                 }
+                Method::PluginsChanged { .. }
+                | Method::ThumbnailReady { .. }
+                | Method::DetailReady { .. }
+                | Method::PowerProfile(_)
+                | Method::PluginsHeldBack { .. }
+                | Method::EnableCompression { .. }
+                | Method::ActionProgress(_)
+                | Method::RunAction(_) => {
+                    tracing::warn!("unexpected daemon notification from client");
+                }
+            },
+            // scrolling sends a hint per frame, there is nothing to answer
+            Message::Notification {
+                method:
+                    Method::Viewport {
+                        generation,
+                        first_index,
+                        last_index,
+                    },
+                ..
+            } => {
+                prefetch_visible(
+                    context,
+                    client,
+                    current_matches,
+                    generation,
+                    first_index..=last_index,
+                )
+                .await;
             }
+            Message::Notification { .. } => {}
+            Message::Response { .. } => {}
+        }
+    }
+}
+
+/// Fetch the details and render the thumbnails of the matches on the client's screen, the
+/// `rows` of its ranked results, ahead of the rest. Matches scrolled past are described once
+/// asked for with `Details`.
+async fn prefetch_visible(
+    context: &ClientContext,
+    client: ClientId,
+    current_matches: &Mutex<MatchStore>,
+    generation: usize,
+    rows: RangeInclusive<usize>,
+) {
+    if !context.config.prefetch.enabled || context.power.profile() == PowerProfile::LowPower {
+        return;
+    }
+    let mut matches = current_matches.lock().await;
+    let visible = matches.rows(generation, rows);
+    let visible = context.config.prefetch.visible(&visible);
+    for match_id in visible {
+        if let Ok(holder) = matches.get(generation, *match_id)
+            && let Some(Icon::Thumbnail { path }) = &holder.match_.icon
+        {
+            let _ = context.previews.send(PathBuf::from(path));
+        }
+    }
+    let wanted = matches.prefetch(generation, visible);
+    let plugins = context.plugins.lock().await;
+    for match_id in wanted {
+        let Ok(holder) = matches.get(generation, match_id) else {
+            continue;
+        };
+        let Some(plugin) = plugins.get(&holder.plugin_id).filter(|plugin| {
+            plugin
+                .metadata
+                .as_ref()
+                .is_some_and(|metadata| metadata.supports(Capability::Details))
+        }) else {
+            continue;
+        };
+        let forwarded = context.sessions.lock().await.start_prefetch(
+            client,
+            &holder.plugin_id,
+            generation,
+            match_id,
+        );
+        if let Some(forwarded) = forwarded {
+            send_to_plugin(
+                plugin,
+                Message::Request {
+                    id: forwarded,
+                    method: Method::Describe(Box::new(holder.match_.clone())),
+                    plugin_id: None,
+                    deadline_ms: Some(context.config.requests.plugin_budget_ms()),
+                },
+            );
+        }
+    }
+}
+
+/// Tell the plugins still working on search `id` (daemon-wide) to stop.
+fn queue_frame(line: &str, queued: &mut VecDeque<Message>) {
+    match Frame::parse(line) {
+        Ok(frame) => queued.extend(frame.into_messages()),
+        Err(err) => tracing::warn!("failed to parse JSON: {}", err),
+    }
+}
+
+/// Whether a search skips the quiet period: one routed to a single plugin, by id or
+/// prefix, or to the command palette.
+async fn dispatches_at_once(context: &ClientContext, query: &str, plugin_id: Option<&str>) -> bool {
+    if plugin_id.is_some() || context.config.commands.strip(query).is_some() {
+        return true;
+    }
+    let plugins = context.plugins.lock().await;
+    let route = routing::route(query, plugins.values().filter_map(|p| p.metadata.as_ref()));
+    matches!(route, Route::Prefixed { .. })
+}
+
+/// Wait `quiet` for the client to send a newer search, queueing whatever else it sends
+/// meanwhile. Whether one came in.
+async fn superseded<R>(
+    lines: &mut Lines<BufReader<R>>,
+    queued: &mut VecDeque<Message>,
+    quiet: Duration,
+) -> bool
+where
+    R: AsyncRead + Unpin,
+{
+    let deadline = tokio::time::Instant::now() + quiet;
+    loop {
+        let search = queued.iter().any(|message| {
+            matches!(
+                message,
+                Message::Request {
+                    method: Method::Search(_),
+                    ..
+                }
+            )
         });
+        if search {
+            return true;
+        }
+        match tokio::time::timeout_at(deadline, lines.next_line()).await {
+            Ok(Ok(Some(line))) => queue_frame(&line, queued),
+            // the end of the stream or a read error is seen again by the next read
+            Ok(Ok(None) | Err(_)) | Err(_) => return false,
+        }
+    }
+}
 
-        let stdout_handle = tokio::spawn(async move {
-            while let Some(message) = response_rx.recv().await {
-                let response = serde_json::to_string(&message).unwrap();
-                tracing::debug!("plugin response -> client: {:?}", &message);
-                stdout.write_all(response.as_bytes()).await.unwrap();
-                stdout.write_all(b"\n").await.unwrap();
-                stdout.flush().await.unwrap();
+async fn cancel_search(context: &ClientContext, id: usize) {
+    let working = context.requests.lock().await.cancel(id);
+    let plugins = context.plugins.lock().await;
+    for key in working {
+        if let Some(plugin) = plugins.get(&key) {
+            send_to_plugin(plugin, cancel_request(plugin, id));
+        }
+    }
+}
+
+/// Forget a client: stop its search and the subscriptions no other client shares.
+async fn end_session(context: &ClientContext, client: ClientId) {
+    let (session, unused) = {
+        let mut sessions = context.sessions.lock().await;
+        let Some(mut session) = sessions.close(client) else {
+            return;
+        };
+        let unused = session
+            .subscriptions
+            .drain()
+            .into_iter()
+            .filter(|(key, topic)| !sessions.is_subscribed(key, topic))
+            .collect::<Vec<_>>();
+        (session, unused)
+    };
+    session.outbox.close();
+    if let Some(search) = session.search {
+        cancel_search(context, search).await;
+    }
+
+    let plugins = context.plugins.lock().await;
+    for (key, topic) in unused {
+        let Some(plugin) = plugins.get(&key) else {
+            continue;
+        };
+        let Some(metadata) = &plugin.metadata else {
+            continue;
+        };
+        send_to_plugin(
+            plugin,
+            Message::Request {
+                id: 0,
+                method: Method::Unsubscribe {
+                    plugin_id: metadata.id.clone(),
+                    topic,
+                },
+                plugin_id: None,
+                deadline_ms: None,
+            },
+        );
+    }
+    if session.outbox.dropped() > 0 {
+        tracing::info!(
+            "dropped {} messages client {} was too slow for",
+            session.outbox.dropped(),
+            client
+        );
+    }
+    tracing::info!("client {} disconnected", client);
+}
+
+/// Outbox and match store of the client that started search `id` (daemon-wide), along with
+/// the client's own id for the search.
+async fn route_search(
+    sessions: &Mutex<Sessions>,
+    id: usize,
+) -> Option<(usize, Arc<Outbox>, Arc<Mutex<MatchStore>>)> {
+    let sessions = sessions.lock().await;
+    let (client_id, session) = sessions.route(id)?;
+    Some((client_id, session.outbox.clone(), session.matches.clone()))
+}
+
+async fn write_line<W>(writer: &mut W, line: &str) -> std::io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    writer.write_all(line.as_bytes()).await?;
+    writer.write_all(b"\n").await?;
+    writer.flush().await
+}
+
+fn start_plugin(
+    plugin: &Admitted,
+    plugin_tx: &mpsc::Sender<PluginResponse>,
+    compression: &Arc<CompressionStats>,
+    sandbox: &SandboxConfig,
+    logs: &Arc<LogStore>,
+    announce: bool,
+) -> ConnectedPlugin {
+    tracing::debug!("starting plugin {:?}", plugin.path);
+    let (tx, rx) = mpsc::channel::<Message>(10);
+    let handle = tokio::spawn(spawn_plugin(
+        plugin.path.clone(),
+        plugin_tx.clone(),
+        rx,
+        compression.clone(),
+        sandbox.for_plugin(Path::new(&plugin.path)),
+        plugin.sha256.clone(),
+        logs.clone(),
+    ));
+    ConnectedPlugin {
+        metadata: None,
+        features: glimpse_sdk::Features::none(),
+        authenticated_at: None,
+        tx,
+        handle,
+        announce,
+    }
+}
+
+/// Ask the plugin to quit. Dropping its sender stops the restart loop once Quit is written.
+fn stop_plugin(plugin: ConnectedPlugin) {
+    send_to_plugin(
+        &plugin,
+        Message::Request {
+            id: 0,
+            method: Method::Quit,
+            plugin_id: None,
+            deadline_ms: None,
+        },
+    );
+}
+
+fn power_profile(profile: PowerProfile) -> Message {
+    Message::Notification {
+        method: Method::PowerProfile(profile),
+        plugin_id: None,
+    }
+}
+
+fn plugins_held_back(items: Vec<UntrustedPlugin>) -> Message {
+    Message::Notification {
+        method: Method::PluginsHeldBack { items },
+        plugin_id: None,
+    }
+}
+
+fn plugins_changed(added: Vec<String>, removed: Vec<String>) -> Message {
+    Message::Notification {
+        method: Method::PluginsChanged { added, removed },
+        plugin_id: None,
+    }
+}
+
+/// Run a command of the palette the daemon serves itself.
+async fn run_command(context: &ClientContext, command: BuiltinCommand) {
+    tracing::info!("running command {:?}", command);
+    match command {
+        BuiltinCommand::ReloadSettings => {
+            let dir = plugin_config::config_dir();
+            for plugin in context.plugins.lock().await.values() {
+                configure_plugin(&dir, plugin, Method::ConfigChanged);
+            }
+        }
+        BuiltinCommand::RestartPlugin(plugin_id) => {
+            let plugins = context.plugins.lock().await;
+            let Some(key) = find_plugin_key(&plugins, &plugin_id) else {
+                tracing::warn!("cannot restart unknown plugin {}", plugin_id);
+                return;
+            };
+            // the plugin exits on Quit, its sender stays so it is started again
+            send_to_plugin(
+                &plugins[&key],
+                Message::Request {
+                    id: 0,
+                    method: Method::Quit,
+                    plugin_id: None,
+                    deadline_ms: None,
+                },
+            );
+        }
+        BuiltinCommand::PowerProfile(profile) => {
+            if !context.power.switch(profile) {
+                return;
             }
+            for plugin in context.plugins.lock().await.values() {
+                send_to_plugin(plugin, power_profile(profile));
+            }
+            context
+                .sessions
+                .lock()
+                .await
+                .broadcast(&power_profile(profile));
+        }
+        BuiltinCommand::CheckUpdates => {
+            let Some(url) = context.config.updates.manifest_url() else {
+                return;
+            };
+            let url = url.to_string();
+            let plugins = context.plugins.clone();
+            let available_updates = context.available_updates.clone();
+            tokio::spawn(async move {
+                match check_updates(&url, &plugins, &available_updates).await {
+                    Ok(found) => tracing::info!("{} updates available", found.len()),
+                    Err(e) => tracing::warn!("update check failed: {}", e),
+                }
+            });
+        }
+    }
+}
+
+/// Fetch the release manifest and remember which installed components are behind it.
+async fn check_updates(
+    url: &str,
+    plugins: &Mutex<HashMap<String, ConnectedPlugin>>,
+    available_updates: &Mutex<Vec<AvailableUpdate>>,
+) -> Result<Vec<AvailableUpdate>, UpdateError> {
+    let manifest = updates::fetch_manifest(url).await?;
+    let installed = plugins
+        .lock()
+        .await
+        .values()
+        .filter_map(|plugin| plugin.metadata.clone())
+        .collect::<Vec<_>>();
+    let found = manifest.available_updates(env!("CARGO_PKG_VERSION"), &installed);
+    *available_updates.lock().await = found.clone();
+    Ok(found)
+}
+
+/// End the plugin's part of search `id`, sending the snapshot if it was the last one.
+async fn finish_search(
+    matches: &Mutex<MatchStore>,
+    outbox: &Outbox,
+    id: usize,
+    plugin_key: &str,
+    last_results: &LastResults,
+) {
+    let mut matches = matches.lock().await;
+    if matches.finish(id, plugin_key) {
+        send_page(outbox, id, &mut matches);
+        save_last_results(last_results, &matches);
+    }
+}
+
+/// Send the client the matches its page lacks and the ranking of the page.
+fn thumbnail_ready(path: &Path, thumbnail: &Path) -> Message {
+    Message::Notification {
+        method: Method::ThumbnailReady {
+            path: path.to_string_lossy().to_string(),
+            icon: Icon::path(thumbnail.to_string_lossy()),
+        },
+        plugin_id: None,
+    }
+}
+
+/// Resolve the icons of `items` sent by the plugin `plugin_id` off the runtime, theme lookups
+/// and rasterizing SVGs block. Plugins are keyed by their executable, which bundled icons
+/// are found next to.
+async fn resolve_icons(
+    icons: &Arc<Icons>,
+    plugin_id: Option<&str>,
+    mut items: Vec<Match>,
+) -> Vec<Match> {
+    let icons = icons.clone();
+    let plugin = plugin_id.map(PathBuf::from);
+    tokio::task::spawn_blocking(move || {
+        icons.resolve_all(plugin.as_deref(), &mut items);
+        items
+    })
+    .await
+    .unwrap_or_else(|e| {
+        tracing::error!("failed to resolve icons: {}", e);
+        vec![]
+    })
+}
+
+fn send_page(outbox: &Outbox, id: usize, matches: &mut MatchStore) {
+    let items = matches.fill_page();
+    if !items.is_empty() {
+        let _ = outbox.push(Message::Response {
+            id,
+            error: None,
+            result: Some(MethodResult::Matches { items }),
+            plugin_id: None,
         });
+    }
+    let _ = outbox.push(snapshot_message(id, matches));
+}
 
-        tokio::select! {
-            _ = stdin_handle => {},
-            _ = stdout_handle => {},
-            _ = plugin_handle => {},
+/// Keep a completed search for clients restarted after a crash. A search only private plugins
+/// answered drops the previous snapshot instead, it is no longer what the user last saw.
+fn save_last_results(last_results: &LastResults, matches: &MatchStore) {
+    let saved = match matches.persistable() {
+        Some(items) => {
+            let items = items
+                .into_iter()
+                .map(|holder| SavedMatch {
+                    plugin_key: holder.plugin_id,
+                    match_: holder.match_,
+                })
+                .collect();
+            last_results.save(&SearchSnapshot::new(
+                matches.query().to_string(),
+                items,
+                SystemTime::now(),
+            ))
         }
+        None => last_results.clear(),
+    };
+    if let Err(e) = saved {
+        tracing::warn!("failed to save last results: {}", e);
+    }
+}
 
-        tracing::debug!("shutting down, waiting for plugins to exit");
-        for handle in handles {
-            let _ = handle.await;
+fn snapshot_message(id: usize, matches: &MatchStore) -> Message {
+    Message::Response {
+        id,
+        error: None,
+        result: Some(MethodResult::Snapshot {
+            items: matches.snapshot(),
+        }),
+        plugin_id: None,
+    }
+}
+
+/// Plugins in the order searches reach them: authenticated ones after those they depend
+/// on, then the rest by path.
+fn dispatch_order<'a>(
+    plugins: &'a HashMap<String, ConnectedPlugin>,
+    dependencies: &Dependencies,
+) -> Vec<(&'a String, &'a ConnectedPlugin)> {
+    let order = dependencies.order();
+    let mut ordered = plugins.iter().collect::<Vec<_>>();
+    ordered.sort_by_key(|(key, plugin)| {
+        let position = plugin
+            .metadata
+            .as_ref()
+            .and_then(|metadata| order.iter().position(|id| *id == metadata.id));
+        (position.unwrap_or(order.len()), *key)
+    });
+    ordered
+}
+
+/// Whether `plugin` is held back from searches while plugins it depends on come up.
+fn waits_on_dependencies(
+    plugin: &ConnectedPlugin,
+    dependencies: &Dependencies,
+    config: &DaemonConfig,
+) -> bool {
+    let (Some(metadata), Some(authenticated_at)) = (&plugin.metadata, plugin.authenticated_at)
+    else {
+        return false;
+    };
+    let pending = dependencies.pending(&metadata.id);
+    if pending.is_empty() || authenticated_at.elapsed() >= config.dependencies.wait() {
+        return false;
+    }
+    tracing::debug!("{} waits on {:?}, leaving it out", metadata.id, pending);
+    true
+}
+
+/// Log what the plugin `plugin_id` just authenticated into: prerequisites still missing,
+/// or dependencies going around in a circle.
+fn report_dependencies(plugins: &HashMap<String, ConnectedPlugin>, plugin_id: &str) {
+    let dependencies = Dependencies::of(plugins.values().filter_map(|p| p.metadata.as_ref()));
+    if let Err(err) = dependencies.check() {
+        tracing::error!("invalid plugin dependencies: {}", err);
+    }
+    let pending = dependencies.pending(plugin_id);
+    if !pending.is_empty() {
+        tracing::info!("{} waits on {:?} before searching", plugin_id, pending);
+    }
+}
+
+fn find_plugin_key(plugins: &HashMap<String, ConnectedPlugin>, plugin_id: &str) -> Option<String> {
+    plugins
+        .iter()
+        .find(|(_, plugin)| {
+            plugin
+                .metadata
+                .as_ref()
+                .is_some_and(|metadata| metadata.id == plugin_id)
+        })
+        .map(|(key, _)| key.clone())
+}
+
+/// The metadata id of the plugin at `key`, the key itself before it authenticates or once
+/// it is gone.
+fn plugin_id_of(plugins: &HashMap<String, ConnectedPlugin>, key: &str) -> String {
+    plugins
+        .get(key)
+        .and_then(|plugin| plugin.metadata.as_ref())
+        .map_or_else(|| key.to_string(), |metadata| metadata.id.clone())
+}
+
+/// Send the plugin its settings, wrapped in `method` to tell first delivery from a change.
+fn configure_plugin(dir: &Path, plugin: &ConnectedPlugin, method: fn(serde_json::Value) -> Method) {
+    let Some(metadata) = &plugin.metadata else {
+        return;
+    };
+    match plugin_config::load(dir, &metadata.id, metadata.config_schema.as_ref()) {
+        Ok(Some(config)) => send_to_plugin(
+            plugin,
+            Message::Notification {
+                method: method(config),
+                plugin_id: None,
+            },
+        ),
+        Ok(None) => {}
+        Err(e) => tracing::warn!("invalid settings for {}: {}", metadata.id, e),
+    }
+}
+
+/// The metadata of `plugin` with the roots it is configured to search among its declared
+/// paths, read from its validated settings.
+fn declared_metadata(plugin: &ConnectedPlugin) -> Option<Metadata> {
+    let metadata = plugin.metadata.as_ref()?;
+    let Some(schema) = &metadata.config_schema else {
+        return Some(metadata.clone());
+    };
+    let config = match plugin_config::load(&plugin_config::config_dir(), &metadata.id, Some(schema))
+    {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::warn!("invalid settings for {}: {}", metadata.id, e);
+            None
         }
+    };
+    Some(with_configured_roots(metadata, config.as_ref()))
+}
 
-        tracing::debug!("all plugins exited, daemon shutting down");
+/// Asks a plugin to stop request `id`. Plugins without `Capability::TargetedCancel` get a
+/// cancel request sharing the id instead, which is what they understand.
+fn cancel_request(plugin: &ConnectedPlugin, id: usize) -> Message {
+    let targeted = plugin
+        .metadata
+        .as_ref()
+        .is_some_and(|metadata| metadata.supports(Capability::TargetedCancel));
+    if targeted {
+        Message::Notification {
+            method: Method::Cancel(Some(id)),
+            plugin_id: None,
+        }
+    } else {
+        Message::Request {
+            id,
+            method: Method::Cancel(None),
+            plugin_id: None,
+            deadline_ms: None,
+        }
     }
 }
+
+fn send_to_plugin(plugin: &ConnectedPlugin, message: Message) {
+    let tx = plugin.tx.clone();
+    tokio::spawn(async move {
+        if let Err(e) = tx.send(message).await {
+            tracing::error!("failed to send request to plugin: {}", e);
+        }
+    });
+}
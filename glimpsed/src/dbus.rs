@@ -0,0 +1,156 @@
+use glimpse_client::{Client, SearchEvent};
+use glimpse_sdk::{Icon, Match, Metadata, Modifiers};
+use serde::{Deserialize, Serialize};
+use zbus::{fdo, object_server::SignalEmitter, zvariant::Type};
+
+/// Well-known name the daemon takes on the session bus.
+pub const SERVICE: &str = "org.glimpse.Daemon1";
+/// Object path of [`DaemonInterface`].
+pub const PATH: &str = "/org/glimpse/Daemon1";
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct DbusConfig {
+    /// Offer searches on the session bus, for desktop shells and KRunner bridges.
+    pub enabled: bool,
+}
+
+impl Default for DbusConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// A match as sent over D-Bus, signature `(tsssas)`.
+#[derive(Serialize, Deserialize, Type, Debug, Clone, PartialEq)]
+pub struct DbusMatch {
+    /// Passed back to `Activate` with the generation of its search.
+    pub id: u64,
+    pub title: String,
+    pub description: String,
+    /// A freedesktop icon name or an image path, empty for icons only glimpse can draw.
+    pub icon: String,
+    /// Action titles, indexed by `Activate`.
+    pub actions: Vec<String>,
+}
+
+impl From<&Match> for DbusMatch {
+    fn from(item: &Match) -> Self {
+        let icon = match &item.icon {
+            Some(Icon::Freedesktop { name }) => name.clone(),
+            Some(Icon::Path { path } | Icon::Thumbnail { path }) => path.clone(),
+            _ => String::new(),
+        };
+        Self {
+            id: item.id.unwrap_or_default() as u64,
+            title: item.title.clone(),
+            description: item.description.clone(),
+            icon,
+            actions: item
+                .actions
+                .iter()
+                .map(|action| action.title.clone())
+                .collect(),
+        }
+    }
+}
+
+/// A plugin as listed over D-Bus, signature `(ssss)`: id, name, version and description.
+pub fn plugin_row(metadata: &Metadata) -> (String, String, String, String) {
+    (
+        metadata.id.clone(),
+        metadata.name.clone(),
+        metadata.version.clone(),
+        metadata.description.clone(),
+    )
+}
+
+/// `org.glimpse.Daemon1`, searching through a client session of the daemon. A search
+/// supersedes the one before it, as typing does in a launcher.
+pub struct DaemonInterface {
+    client: Client,
+}
+
+impl DaemonInterface {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+#[zbus::interface(name = "org.glimpse.Daemon1")]
+impl DaemonInterface {
+    /// Search every plugin, answering with the generation of the search and its matches
+    /// in the daemon's order once every plugin answered. `ResultsChanged` tells how many
+    /// matches arrived while it runs.
+    async fn search(
+        &self,
+        query: String,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) -> fdo::Result<(u64, Vec<DbusMatch>)> {
+        let mut search = self.client.search(&query).await.map_err(failed)?;
+        let generation = search.generation() as u64;
+        let mut matches = Vec::new();
+        while let Some(event) = search.next().await {
+            match event {
+                SearchEvent::Matches { items, .. } => {
+                    matches.extend(items);
+                    let _ = Self::results_changed(&emitter, generation, matches.len() as u32).await;
+                }
+                SearchEvent::Error { .. } => {}
+                SearchEvent::Completed(snapshot) => {
+                    let ordered = snapshot
+                        .iter()
+                        .filter_map(|item| matches.iter().find(|m| m.id == Some(item.id)))
+                        .map(DbusMatch::from)
+                        .collect();
+                    return Ok((generation, ordered));
+                }
+            }
+        }
+        Err(fdo::Error::Failed(format!(
+            "search {} was superseded",
+            generation
+        )))
+    }
+
+    /// Run the action at index `action` of a match of the search `generation`.
+    async fn activate(&self, generation: u64, match_id: u64, action: u32) -> fdo::Result<()> {
+        self.client
+            .activate(
+                generation as usize,
+                match_id as usize,
+                action as usize,
+                Modifiers::default(),
+            )
+            .await
+            .map_err(failed)
+    }
+
+    /// The plugins running, as (id, name, version, description).
+    async fn list_plugins(&self) -> fdo::Result<Vec<(String, String, String, String)>> {
+        let plugins = self.client.plugins().await.map_err(failed)?;
+        Ok(plugins.iter().map(plugin_row).collect())
+    }
+
+    /// Matches of the search `generation` so far.
+    #[zbus(signal)]
+    async fn results_changed(
+        emitter: &SignalEmitter<'_>,
+        generation: u64,
+        count: u32,
+    ) -> zbus::Result<()>;
+}
+
+fn failed(err: glimpse_client::ClientError) -> fdo::Error {
+    fdo::Error::Failed(err.to_string())
+}
+
+/// Take [`SERVICE`] on the session bus and serve [`DaemonInterface`] until the returned
+/// connection is dropped.
+pub async fn serve(client: Client) -> zbus::Result<zbus::Connection> {
+    zbus::connection::Builder::session()?
+        .name(SERVICE)?
+        .serve_at(PATH, DaemonInterface::new(client))?
+        .build()
+        .await
+}
@@ -0,0 +1,162 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fmt::Display,
+    time::Duration,
+};
+
+use glimpse_sdk::Metadata;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct DependencyConfig {
+    /// Milliseconds an authenticated plugin is held back from searches while plugins it
+    /// depends on have not authenticated. Prerequisites that are not installed are given
+    /// up on after this.
+    pub wait_ms: u64,
+}
+
+impl Default for DependencyConfig {
+    fn default() -> Self {
+        Self { wait_ms: 5000 }
+    }
+}
+
+impl DependencyConfig {
+    pub fn wait(&self) -> Duration {
+        Duration::from_millis(self.wait_ms)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyError {
+    /// The plugins depend on each other in a circle, the first one repeated at the end.
+    Cycle { plugins: Vec<String> },
+}
+
+impl Display for DependencyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DependencyError::Cycle { plugins } => {
+                write!(f, "plugins depend on each other: {}", plugins.join(" -> "))
+            }
+        }
+    }
+}
+impl Error for DependencyError {}
+
+/// Soft dependencies declared by authenticated plugins, by metadata id.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dependencies {
+    graph: BTreeMap<String, Vec<String>>,
+}
+
+impl Dependencies {
+    pub fn of<'a>(plugins: impl IntoIterator<Item = &'a Metadata>) -> Self {
+        let graph = plugins
+            .into_iter()
+            .map(|metadata| (metadata.id.clone(), metadata.depends_on.clone()))
+            .collect();
+        Self { graph }
+    }
+
+    /// Prerequisites of `plugin_id` that have not authenticated.
+    pub fn pending(&self, plugin_id: &str) -> Vec<&str> {
+        self.graph
+            .get(plugin_id)
+            .into_iter()
+            .flatten()
+            .filter(|dependency| !self.graph.contains_key(*dependency))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Every plugin after those it depends on, ties going by id. Cycles are broken at
+    /// their lowest id, see [`Dependencies::check`].
+    pub fn order(&self) -> Vec<&str> {
+        let mut order = Vec::with_capacity(self.graph.len());
+        let mut placed = BTreeSet::new();
+        while placed.len() < self.graph.len() {
+            let unplaced = self
+                .graph
+                .keys()
+                .filter(|plugin_id| !placed.contains(plugin_id.as_str()))
+                .collect::<Vec<_>>();
+            let next = match unplaced
+                .iter()
+                .find(|plugin_id| self.ready(plugin_id, &placed))
+            {
+                Some(next) => next.as_str(),
+                None => self.break_cycle(unplaced[0], &placed),
+            };
+            placed.insert(next);
+            order.push(next);
+        }
+        order
+    }
+
+    /// Refuse dependencies that go around in a circle, naming the first one found.
+    pub fn check(&self) -> Result<(), DependencyError> {
+        let mut placed = BTreeSet::new();
+        loop {
+            let unplaced = self
+                .graph
+                .keys()
+                .filter(|plugin_id| !placed.contains(plugin_id.as_str()))
+                .collect::<Vec<_>>();
+            let Some(first) = unplaced.first() else {
+                return Ok(());
+            };
+            match unplaced
+                .iter()
+                .find(|plugin_id| self.ready(plugin_id, &placed))
+            {
+                Some(next) => {
+                    placed.insert(next.as_str());
+                }
+                None => return Err(self.cycle_from(first, &placed)),
+            }
+        }
+    }
+
+    /// Whether every authenticated prerequisite of `plugin_id` is in `placed`.
+    fn ready(&self, plugin_id: &str, placed: &BTreeSet<&str>) -> bool {
+        self.graph[plugin_id].iter().all(|dependency| {
+            placed.contains(dependency.as_str()) || !self.graph.contains_key(dependency)
+        })
+    }
+
+    /// The plugin to place first when every unplaced one waits on another: the lowest id
+    /// in the cycle reached from `start`.
+    fn break_cycle(&self, start: &str, placed: &BTreeSet<&str>) -> &str {
+        let DependencyError::Cycle { plugins } = self.cycle_from(start, placed);
+        let lowest = plugins.iter().min().expect("a cycle has plugins");
+        self.graph
+            .get_key_value(lowest)
+            .map(|(plugin_id, _)| plugin_id.as_str())
+            .expect("cycles are made of known plugins")
+    }
+
+    /// Follow unplaced prerequisites from `start` until one repeats. Every unplaced plugin
+    /// waits on another, so the walk always comes back around.
+    fn cycle_from(&self, start: &str, placed: &BTreeSet<&str>) -> DependencyError {
+        let mut path = vec![start.to_string()];
+        let mut current = start;
+        loop {
+            let next = self.graph[current]
+                .iter()
+                .find(|dependency| {
+                    self.graph.contains_key(*dependency) && !placed.contains(dependency.as_str())
+                })
+                .expect("an unplaced plugin waits on another");
+            if let Some(seen) = path.iter().position(|plugin_id| plugin_id == next) {
+                let mut plugins = path.split_off(seen);
+                plugins.push(next.clone());
+                return DependencyError::Cycle { plugins };
+            }
+            path.push(next.clone());
+            current = next;
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use glimpse_sdk::ConnectionTarget;
+use zbus::{
+    Connection, Proxy,
+    fdo::{ManagedObjects, ObjectManagerProxy},
+    proxy::{Builder, CacheProperties},
+    zvariant::{ObjectPath, OwnedObjectPath},
+};
+
+const BLUEZ_SERVICE: &str = "org.bluez";
+const BLUEZ_DEVICE_INTERFACE: &str = "org.bluez.Device1";
+
+const NM_SERVICE: &str = "org.freedesktop.NetworkManager";
+const NM_PATH: &str = "/org/freedesktop/NetworkManager";
+const NM_INTERFACE: &str = "org.freedesktop.NetworkManager";
+const NM_SETTINGS_PATH: &str = "/org/freedesktop/NetworkManager/Settings";
+const NM_SETTINGS_INTERFACE: &str = "org.freedesktop.NetworkManager.Settings";
+const NM_ACTIVE_INTERFACE: &str = "org.freedesktop.NetworkManager.Connection.Active";
+
+/// The path standing for "no object", letting NetworkManager pick the device.
+const NO_OBJECT: &str = "/";
+
+/// The object of the Bluetooth device with `address` among the objects BlueZ manages, on
+/// whichever adapter it is paired with.
+pub fn bluez_device(objects: &ManagedObjects, address: &str) -> Option<OwnedObjectPath> {
+    objects.iter().find_map(|(path, interfaces)| {
+        let device = interfaces
+            .iter()
+            .find(|(interface, _)| interface.as_str() == BLUEZ_DEVICE_INTERFACE)?
+            .1;
+        let found = <&str>::try_from(device.get("Address")?).ok()?;
+        found.eq_ignore_ascii_case(address).then(|| path.clone())
+    })
+}
+
+/// Connects and disconnects Bluetooth devices through BlueZ and networks through
+/// NetworkManager, both on the system bus.
+pub struct Devices {
+    connection: Connection,
+}
+
+impl Devices {
+    pub async fn connect() -> zbus::Result<Self> {
+        Ok(Self {
+            connection: Connection::system().await?,
+        })
+    }
+
+    /// Done once connected or disconnected, which for Bluetooth may take seconds.
+    pub async fn set(&self, target: &ConnectionTarget, connect: bool) -> zbus::Result<()> {
+        match target {
+            ConnectionTarget::Bluetooth { address } => self.bluetooth(address, connect).await,
+            ConnectionTarget::Network { uuid } if connect => self.activate(uuid).await,
+            ConnectionTarget::Network { uuid } => self.deactivate(uuid).await,
+        }
+    }
+
+    async fn bluetooth(&self, address: &str, connect: bool) -> zbus::Result<()> {
+        let objects = ObjectManagerProxy::builder(&self.connection)
+            .destination(BLUEZ_SERVICE)?
+            .path("/")?
+            .build()
+            .await?
+            .get_managed_objects()
+            .await?;
+        let Some(path) = bluez_device(&objects, address) else {
+            return Err(zbus::Error::Failure(format!(
+                "no bluetooth device {}",
+                address
+            )));
+        };
+        let device = proxy(
+            &self.connection,
+            BLUEZ_SERVICE,
+            path,
+            BLUEZ_DEVICE_INTERFACE,
+        )
+        .await?;
+        match connect {
+            true => device.call_method("Connect", &()).await?,
+            false => device.call_method("Disconnect", &()).await?,
+        };
+        Ok(())
+    }
+
+    async fn activate(&self, uuid: &str) -> zbus::Result<()> {
+        let settings = proxy(
+            &self.connection,
+            NM_SERVICE,
+            NM_SETTINGS_PATH,
+            NM_SETTINGS_INTERFACE,
+        )
+        .await?;
+        let connection: OwnedObjectPath = settings.call("GetConnectionByUuid", &(uuid,)).await?;
+        let manager = proxy(&self.connection, NM_SERVICE, NM_PATH, NM_INTERFACE).await?;
+        let no_object = ObjectPath::from_static_str_unchecked(NO_OBJECT);
+        let _: OwnedObjectPath = manager
+            .call("ActivateConnection", &(connection, &no_object, &no_object))
+            .await?;
+        Ok(())
+    }
+
+    async fn deactivate(&self, uuid: &str) -> zbus::Result<()> {
+        let manager = proxy(&self.connection, NM_SERVICE, NM_PATH, NM_INTERFACE).await?;
+        let active: Vec<OwnedObjectPath> = manager.get_property("ActiveConnections").await?;
+        for path in active {
+            let connection = proxy(
+                &self.connection,
+                NM_SERVICE,
+                path.clone(),
+                NM_ACTIVE_INTERFACE,
+            )
+            .await?;
+            let active_uuid: String = connection.get_property("Uuid").await?;
+            if active_uuid == uuid {
+                manager
+                    .call_method("DeactivateConnection", &(path,))
+                    .await?;
+                return Ok(());
+            }
+        }
+        // already disconnected
+        Ok(())
+    }
+}
+
+/// A proxy reading properties when asked for, they change as devices come and go.
+async fn proxy(
+    connection: &Connection,
+    service: &'static str,
+    path: impl TryInto<ObjectPath<'static>, Error: Into<zbus::Error>>,
+    interface: &'static str,
+) -> zbus::Result<Proxy<'static>> {
+    Builder::new(connection)
+        .destination(service)?
+        .path(path)?
+        .interface(interface)?
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await
+}
@@ -1,72 +1,574 @@
-use std::collections::HashMap;
-
-use glimpse_sdk::{Message, Method};
-use tokio::{process::Command, sync::mpsc};
-
-pub async fn shell_exec(command: &str, args: &Vec<String>) {
-    tracing::debug!("executing command: {} {:?}", command, args);
-    let command = command.to_string();
-    let args = args.clone();
-    tokio::spawn(async move {
-        if let Err(err) = Command::new(&command).args(&args).spawn() {
-            tracing::error!("failed to execute command: {}", err);
-        } else {
-            tracing::debug!("executed command: {} {:?}", command, args);
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    process::{ExitStatus, Stdio},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use glimpse_sdk::{
+    Action, ConnectionTarget, Message, Method, Sensitive, SequenceError, StepStatus, SystemCommand,
+};
+use tokio::{io::AsyncWriteExt, process::Command, sync::mpsc, task::JoinHandle};
+
+use crate::{
+    devices::Devices,
+    logind::Logind,
+    timers::{Reminder, TimerError, TimerStore, Timers},
+};
+
+/// How long typing waits for the launcher to hide, so the text goes to the window focused
+/// before it.
+const TYPE_DELAY: Duration = Duration::from_millis(200);
+
+#[derive(Debug)]
+pub enum DispatchError {
+    /// The command could not be started.
+    Io(std::io::Error),
+    /// The command ran and failed.
+    Status {
+        command: String,
+        status: ExitStatus,
+    },
+    /// There is no plugin to run the callback, or it went away.
+    NoPlugin {
+        key: String,
+    },
+    Sequence(SequenceError),
+    Timer(TimerError),
+    /// systemd-logind refused the command or could not be reached.
+    Logind(zbus::Error),
+    /// BlueZ or NetworkManager refused to connect or disconnect, or could not be reached.
+    Devices(zbus::Error),
+}
+
+impl Display for DispatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DispatchError::Io(err) => write!(f, "io: {}", err),
+            DispatchError::Status { command, status } => {
+                write!(f, "{} failed: {}", command, status)
+            }
+            DispatchError::NoPlugin { key } => write!(f, "no plugin to run callback {}", key),
+            DispatchError::Sequence(err) => write!(f, "{}", err),
+            DispatchError::Timer(err) => write!(f, "timer: {}", err),
+            DispatchError::Logind(err) => write!(f, "logind: {}", err),
+            DispatchError::Devices(err) => write!(f, "devices: {}", err),
         }
-    });
+    }
 }
+impl Error for DispatchError {}
+
+/// Side effects performed by the daemon when the client activates a match action.
+#[async_trait]
+pub trait Dispatcher: Send + Sync + 'static {
+    async fn exec(&self, command: &str, args: &[String]) -> Result<(), DispatchError>;
+
+    async fn launch(&self, app_id: &str, action: Option<&str>) -> Result<(), DispatchError>;
+
+    async fn clipboard(&self, text: &Sensitive<String>) -> Result<(), DispatchError>;
+
+    /// Copy `text`, then clear the clipboard after `clear_after` unless it was replaced.
+    async fn expiring_clipboard(
+        &self,
+        text: &Sensitive<String>,
+        clear_after: Duration,
+    ) -> Result<(), DispatchError>;
 
-pub async fn launch_app(app: &str, action: &Option<&str>) {
-    tracing::debug!("launching app: {} {:?}", app, action);
-    // if let Err(err) = Command::new(app).args(args).spawn() {
-    //     tracing::error!("failed to launch app: {}", err);
-    // } else {
-    //     tracing::debug!("launched app: {} {:?}", app, args);
-    // }
+    async fn open(&self, uri: &str) -> Result<(), DispatchError>;
+
+    /// Type `text` into the focused window.
+    async fn type_text(&self, text: &Sensitive<String>) -> Result<(), DispatchError>;
+
+    /// Show `reminder` as a notification after `after`.
+    async fn schedule(&self, after: Duration, reminder: Reminder) -> Result<(), DispatchError>;
+
+    /// Power off, lock the screen or the like, see [`SystemCommand`].
+    async fn system(&self, command: SystemCommand) -> Result<(), DispatchError>;
+
+    /// Connect or disconnect a Bluetooth device or a network.
+    async fn connection(
+        &self,
+        target: &ConnectionTarget,
+        connect: bool,
+    ) -> Result<(), DispatchError>;
+
+    /// Have the plugin that owns the match run a callback action as request `id`.
+    async fn notify(
+        &self,
+        plugin_tx: mpsc::Sender<Message>,
+        id: usize,
+        key: &str,
+        params: &HashMap<String, String>,
+    ) -> Result<(), DispatchError>;
 }
 
-pub async fn copy_to_clipboard(text: &str) {
-    let text = text.to_string();
-    tokio::spawn(async move {
-        tracing::debug!("copying to clipboard: {}", text);
-        if let Err(err) = Command::new("wl-copy").arg(&text).spawn() {
-            tracing::error!("failed to copy to clipboard: {}", err);
-        } else {
-            tracing::debug!("copied to clipboard: {}", text);
+/// Clears expiring copies from the clipboard once their time is up. A new expiring copy
+/// replaces the clear still pending for the previous one, which it overwrote.
+#[derive(Default)]
+pub struct DelayedClipboard {
+    pending: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl DelayedClipboard {
+    /// Run `clear` after `delay`, in place of the clear pending.
+    pub fn schedule<F>(&self, delay: Duration, clear: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let task = tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            clear.await;
+        });
+        if let Some(previous) = self.pending.lock().unwrap().replace(task) {
+            previous.abort();
         }
-    });
+    }
 }
 
-pub async fn open_url(uri: &str) {
-    tracing::debug!("opening uri: {}", uri);
-    let uri = uri.to_string();
-    tokio::spawn(async move {
-        if let Err(err) = Command::new("xdg-open").arg(&uri).spawn() {
-            tracing::error!("failed to open uri: {}", err);
-        } else {
-            tracing::debug!("opened uri: {}", uri);
+/// Dispatcher that talks to the real system.
+pub struct SystemDispatcher {
+    expiring: DelayedClipboard,
+    timers: Arc<Timers>,
+}
+
+/// Timers are kept in memory, nothing fires them. Use [`SystemDispatcher::with_timers`]
+/// for timers the daemon runs.
+impl Default for SystemDispatcher {
+    fn default() -> Self {
+        Self::with_timers(Arc::new(Timers::new(TimerStore::in_memory())))
+    }
+}
+
+impl SystemDispatcher {
+    pub fn with_timers(timers: Arc<Timers>) -> Self {
+        Self {
+            expiring: DelayedClipboard::default(),
+            timers,
         }
-    });
-}
-
-pub async fn plugin_callback(
-    plugin_tx: mpsc::Sender<Message>,
-    key: &str,
-    params: &HashMap<String, String>,
-) {
-    tracing::debug!("call plugin callback: {} {:?}", key, params);
-    let key = key.to_string();
-    let params = params.clone();
-    let plugin_tx = plugin_tx.clone();
-    tokio::spawn(async move {
-        if let Err(err) = plugin_tx
-            .send(Message::Notification {
-                method: Method::CallAction(key.clone(), params),
+    }
+}
+
+#[async_trait]
+impl Dispatcher for SystemDispatcher {
+    /// Done once the command started, it is not waited for.
+    async fn exec(&self, command: &str, args: &[String]) -> Result<(), DispatchError> {
+        tracing::debug!("executing command: {} {:?}", command, args);
+        Command::new(command)
+            .args(args)
+            .spawn()
+            .map_err(DispatchError::Io)?;
+        tracing::debug!("executed command: {} {:?}", command, args);
+        Ok(())
+    }
+
+    async fn launch(&self, app_id: &str, action: Option<&str>) -> Result<(), DispatchError> {
+        tracing::debug!("launching app: {} {:?}", app_id, action);
+        // if let Err(err) = Command::new(app).args(args).spawn() {
+        //     tracing::error!("failed to launch app: {}", err);
+        // } else {
+        //     tracing::debug!("launched app: {} {:?}", app, args);
+        // }
+        Ok(())
+    }
+
+    /// Done once wl-copy owns the text, it forks to serve it and returns right away.
+    async fn clipboard(&self, text: &Sensitive<String>) -> Result<(), DispatchError> {
+        tracing::debug!("copying to clipboard: {}", text);
+        pipe_to("wl-copy", &[], text).await?;
+        tracing::debug!("copied to clipboard: {}", text);
+        Ok(())
+    }
+
+    /// Done once copied, the clipboard is cleared in the background.
+    async fn expiring_clipboard(
+        &self,
+        text: &Sensitive<String>,
+        clear_after: Duration,
+    ) -> Result<(), DispatchError> {
+        self.clipboard(text).await?;
+        let text = text.clone();
+        self.expiring.schedule(clear_after, async move {
+            match clipboard_holds(&text).await {
+                Ok(true) => match clear_clipboard().await {
+                    Ok(()) => tracing::debug!("cleared expired copy from the clipboard"),
+                    Err(e) => tracing::warn!("failed to clear the clipboard: {}", e),
+                },
+                Ok(false) => tracing::debug!("clipboard changed since the copy, leaving it"),
+                Err(e) => tracing::warn!("failed to read the clipboard: {}", e),
+            }
+        });
+        Ok(())
+    }
+
+    async fn open(&self, uri: &str) -> Result<(), DispatchError> {
+        tracing::debug!("opening uri: {}", uri);
+        Command::new("xdg-open")
+            .arg(uri)
+            .spawn()
+            .map_err(DispatchError::Io)?;
+        tracing::debug!("opened uri: {}", uri);
+        Ok(())
+    }
+
+    /// Done once typed, with wtype on Wayland and xdotool on X11, both reading the text from
+    /// standard input.
+    async fn type_text(&self, text: &Sensitive<String>) -> Result<(), DispatchError> {
+        tokio::time::sleep(TYPE_DELAY).await;
+        let (command, args) = match std::env::var_os("WAYLAND_DISPLAY") {
+            Some(_) => ("wtype", &["-"][..]),
+            None => ("xdotool", &["type", "--clearmodifiers", "--file", "-"][..]),
+        };
+        tracing::debug!("typing text with {}: {}", command, text);
+        pipe_to(command, args, text).await?;
+        tracing::debug!("typed text: {}", text);
+        Ok(())
+    }
+
+    async fn schedule(&self, after: Duration, reminder: Reminder) -> Result<(), DispatchError> {
+        let title = reminder.title.clone();
+        let id = self
+            .timers
+            .schedule(after, reminder)
+            .await
+            .map_err(DispatchError::Timer)?;
+        tracing::debug!("scheduled timer {} in {:?}: {}", id, after, title);
+        Ok(())
+    }
+
+    async fn system(&self, command: SystemCommand) -> Result<(), DispatchError> {
+        tracing::info!("running system command: {:?}", command);
+        let logind = Logind::connect().await.map_err(DispatchError::Logind)?;
+        logind.run(command).await.map_err(DispatchError::Logind)
+    }
+
+    async fn connection(
+        &self,
+        target: &ConnectionTarget,
+        connect: bool,
+    ) -> Result<(), DispatchError> {
+        tracing::info!("setting connection of {:?}: {}", target, connect);
+        let devices = Devices::connect().await.map_err(DispatchError::Devices)?;
+        devices
+            .set(target, connect)
+            .await
+            .map_err(DispatchError::Devices)
+    }
+
+    async fn notify(
+        &self,
+        plugin_tx: mpsc::Sender<Message>,
+        id: usize,
+        key: &str,
+        params: &HashMap<String, String>,
+    ) -> Result<(), DispatchError> {
+        tracing::debug!("call plugin callback {}: {} {:?}", id, key, params);
+        plugin_tx
+            .send(Message::Request {
+                id,
+                method: Method::CallAction(key.to_string(), params.clone()),
                 plugin_id: None,
+                deadline_ms: None,
+            })
+            .await
+            .map_err(|_| DispatchError::NoPlugin {
+                key: key.to_string(),
             })
+    }
+}
+
+/// Run `command` with `text` on its standard input, where, unlike in its arguments, other
+/// users cannot read it from the process list.
+pub async fn pipe_to(
+    command: &str,
+    args: &[&str],
+    text: &Sensitive<String>,
+) -> Result<(), DispatchError> {
+    let mut child = Command::new(command)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(DispatchError::Io)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.expose().as_bytes())
             .await
-        {
-            tracing::error!("failed to send plugin callback: {}", err);
+            .map_err(DispatchError::Io)?;
+        // dropped, so the command sees the end of the text
+    }
+    let status = child.wait().await.map_err(DispatchError::Io)?;
+    if !status.success() {
+        return Err(DispatchError::Status {
+            command: command.to_string(),
+            status,
+        });
+    }
+    Ok(())
+}
+
+/// Whether the clipboard still holds `text`, as wl-paste reads it.
+async fn clipboard_holds(text: &Sensitive<String>) -> std::io::Result<bool> {
+    let output = Command::new("wl-paste")
+        .arg("--no-newline")
+        .output()
+        .await?;
+    let pasted = Sensitive::new(output.stdout);
+    Ok(output.status.success() && pasted.expose() == text.expose().as_bytes())
+}
+
+async fn clear_clipboard() -> Result<(), DispatchError> {
+    let status = Command::new("wl-copy")
+        .arg("--clear")
+        .status()
+        .await
+        .map_err(DispatchError::Io)?;
+    if !status.success() {
+        return Err(DispatchError::Status {
+            command: "wl-copy --clear".to_string(),
+            status,
+        });
+    }
+    Ok(())
+}
+
+/// A side effect captured by [`RecordingDispatcher`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Dispatched {
+    Exec {
+        command: String,
+        args: Vec<String>,
+    },
+    Launch {
+        app_id: String,
+        action: Option<String>,
+    },
+    Clipboard {
+        text: String,
+    },
+    ExpiringClipboard {
+        text: String,
+        clear_after: Duration,
+    },
+    Open {
+        uri: String,
+    },
+    TypeText {
+        text: String,
+    },
+    Schedule {
+        after: Duration,
+        reminder: Reminder,
+    },
+    System {
+        command: SystemCommand,
+    },
+    Connection {
+        target: ConnectionTarget,
+        connect: bool,
+    },
+    Notify {
+        id: usize,
+        key: String,
+        params: HashMap<String, String>,
+    },
+}
+
+/// Dispatcher that records calls instead of performing them, for tests.
+#[derive(Default, Clone)]
+pub struct RecordingDispatcher {
+    calls: Arc<Mutex<Vec<Dispatched>>>,
+    failing: Arc<Mutex<Vec<Dispatched>>>,
+}
+
+impl RecordingDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn calls(&self) -> Vec<Dispatched> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Fail calls equal to `call`, they are recorded all the same.
+    pub fn fail_on(&self, call: Dispatched) {
+        self.failing.lock().unwrap().push(call);
+    }
+
+    fn record(&self, call: Dispatched) -> Result<(), DispatchError> {
+        let fails = self.failing.lock().unwrap().contains(&call);
+        self.calls.lock().unwrap().push(call);
+        match fails {
+            true => Err(DispatchError::Io(std::io::Error::other("recorded failure"))),
+            false => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl Dispatcher for RecordingDispatcher {
+    async fn exec(&self, command: &str, args: &[String]) -> Result<(), DispatchError> {
+        self.record(Dispatched::Exec {
+            command: command.to_string(),
+            args: args.to_vec(),
+        })
+    }
+
+    async fn launch(&self, app_id: &str, action: Option<&str>) -> Result<(), DispatchError> {
+        self.record(Dispatched::Launch {
+            app_id: app_id.to_string(),
+            action: action.map(str::to_string),
+        })
+    }
+
+    async fn clipboard(&self, text: &Sensitive<String>) -> Result<(), DispatchError> {
+        self.record(Dispatched::Clipboard {
+            text: text.expose().clone(),
+        })
+    }
+
+    async fn expiring_clipboard(
+        &self,
+        text: &Sensitive<String>,
+        clear_after: Duration,
+    ) -> Result<(), DispatchError> {
+        self.record(Dispatched::ExpiringClipboard {
+            text: text.expose().clone(),
+            clear_after,
+        })
+    }
+
+    async fn open(&self, uri: &str) -> Result<(), DispatchError> {
+        self.record(Dispatched::Open {
+            uri: uri.to_string(),
+        })
+    }
+
+    async fn type_text(&self, text: &Sensitive<String>) -> Result<(), DispatchError> {
+        self.record(Dispatched::TypeText {
+            text: text.expose().clone(),
+        })
+    }
+
+    async fn schedule(&self, after: Duration, reminder: Reminder) -> Result<(), DispatchError> {
+        self.record(Dispatched::Schedule { after, reminder })
+    }
+
+    async fn system(&self, command: SystemCommand) -> Result<(), DispatchError> {
+        self.record(Dispatched::System { command })
+    }
+
+    async fn connection(
+        &self,
+        target: &ConnectionTarget,
+        connect: bool,
+    ) -> Result<(), DispatchError> {
+        self.record(Dispatched::Connection {
+            target: target.clone(),
+            connect,
+        })
+    }
+
+    async fn notify(
+        &self,
+        _plugin_tx: mpsc::Sender<Message>,
+        id: usize,
+        key: &str,
+        params: &HashMap<String, String>,
+    ) -> Result<(), DispatchError> {
+        self.record(Dispatched::Notify {
+            id,
+            key: key.to_string(),
+            params: params.clone(),
+        })
+    }
+}
+
+/// Route a match action to the matching dispatcher call.
+/// `plugin` is the channel of the plugin that produced the match and the daemon-wide id to
+/// run a callback under, required for callbacks. A sequence fails with its first failing
+/// step.
+pub async fn dispatch_action(
+    dispatcher: &dyn Dispatcher,
+    action: &Action,
+    plugin: Option<(mpsc::Sender<Message>, usize)>,
+) -> Result<(), DispatchError> {
+    for step in action.steps().map_err(DispatchError::Sequence)? {
+        dispatch_step(dispatcher, step, plugin.clone()).await?;
+    }
+    Ok(())
+}
+
+/// Run the steps of a sequence in order, skipping the rest once one fails. Returns how each
+/// step went, refuses sequences over the limits without running any step.
+pub async fn dispatch_sequence(
+    dispatcher: &dyn Dispatcher,
+    action: &Action,
+    plugin: Option<(mpsc::Sender<Message>, usize)>,
+) -> Result<Vec<StepStatus>, SequenceError> {
+    let mut statuses = vec![];
+    let mut failed = false;
+    for step in action.steps()? {
+        if failed {
+            statuses.push(StepStatus::Skipped);
+            continue;
+        }
+        match dispatch_step(dispatcher, step, plugin.clone()).await {
+            Ok(()) => statuses.push(StepStatus::Done),
+            Err(err) => {
+                tracing::warn!("step {} of sequence failed: {}", statuses.len(), err);
+                statuses.push(StepStatus::Failed {
+                    message: err.to_string(),
+                });
+                failed = true;
+            }
+        }
+    }
+    Ok(statuses)
+}
+
+async fn dispatch_step(
+    dispatcher: &dyn Dispatcher,
+    action: &Action,
+    plugin: Option<(mpsc::Sender<Message>, usize)>,
+) -> Result<(), DispatchError> {
+    match action {
+        Action::Exec { command, args } => dispatcher.exec(command, args).await,
+        Action::Launch { app_id, action } => dispatcher.launch(app_id, action.as_deref()).await,
+        Action::Clipboard { text } => dispatcher.clipboard(text).await,
+        Action::ExpiringClipboard {
+            text,
+            clear_after_secs,
+        } => {
+            dispatcher
+                .expiring_clipboard(text, Duration::from_secs(*clear_after_secs))
+                .await
+        }
+        Action::Open { uri } => dispatcher.open(uri).await,
+        Action::TypeText { text } => dispatcher.type_text(text).await,
+        Action::Schedule {
+            after_secs,
+            title,
+            body,
+            open_launcher,
+        } => {
+            let reminder = Reminder {
+                title: title.clone(),
+                body: body.clone(),
+                open_launcher: *open_launcher,
+            };
+            dispatcher
+                .schedule(Duration::from_secs(*after_secs), reminder)
+                .await
         }
-    });
+        Action::System { command } => dispatcher.system(*command).await,
+        Action::Connection { target, connect } => dispatcher.connection(target, *connect).await,
+        Action::Callback { key, params } => match plugin {
+            Some((tx, id)) => dispatcher.notify(tx, id, key, params).await,
+            None => Err(DispatchError::NoPlugin { key: key.clone() }),
+        },
+        // flattened by `Action::steps`
+        Action::Sequence { .. } => Err(DispatchError::Sequence(SequenceError::TooDeep)),
+    }
 }
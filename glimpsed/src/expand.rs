@@ -0,0 +1,196 @@
+use std::{collections::HashMap, error::Error, fmt::Display, path::PathBuf};
+
+use glimpse_sdk::{Action, ExpandField};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpandError {
+    /// `$NAME` names a variable that is not set.
+    Undefined { name: String },
+    /// `${` without its closing brace.
+    Unterminated { value: String },
+    /// `~` with no home directory to expand it to.
+    NoHome,
+}
+
+impl Display for ExpandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExpandError::Undefined { name } => write!(f, "${} is not set", name),
+            ExpandError::Unterminated { value } => write!(f, "unterminated ${{ in {:?}", value),
+            ExpandError::NoHome => write!(f, "no home directory to expand ~ to"),
+        }
+    }
+}
+impl Error for ExpandError {}
+
+/// Variables of the daemon's environment action fields may expand, anything else, like
+/// tokens and keys the daemon was started with, stays out of reach of plugins.
+const ALLOWED_VARS: &[&str] = &["HOME", "USER", "LOGNAME"];
+
+/// Variables action fields expand with: the user and directory variables of the daemon's
+/// environment, with the XDG base directories filled in where unset and the user
+/// directories known to `dirs`.
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    home: Option<String>,
+    vars: HashMap<String, String>,
+}
+
+fn is_allowed(name: &str) -> bool {
+    ALLOWED_VARS.contains(&name)
+        || name.strip_prefix("XDG_").is_some_and(|rest| {
+            rest.ends_with("_HOME") || rest.ends_with("_DIR") || rest.ends_with("_DIRS")
+        })
+}
+
+impl Environment {
+    pub fn current() -> Self {
+        Self::allowed(dirs::home_dir(), std::env::vars())
+            .with_user_dir("XDG_DESKTOP_DIR", dirs::desktop_dir())
+            .with_user_dir("XDG_DOCUMENTS_DIR", dirs::document_dir())
+            .with_user_dir("XDG_DOWNLOAD_DIR", dirs::download_dir())
+            .with_user_dir("XDG_MUSIC_DIR", dirs::audio_dir())
+            .with_user_dir("XDG_PICTURES_DIR", dirs::picture_dir())
+            .with_user_dir("XDG_VIDEOS_DIR", dirs::video_dir())
+            .with_user_dir("XDG_RUNTIME_DIR", dirs::runtime_dir())
+    }
+
+    /// Like [`Environment::new`] with only the variables of `vars` naming the user and the
+    /// XDG directories.
+    pub fn allowed(
+        home: Option<PathBuf>,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        Self::new(home, vars.into_iter().filter(|(name, _)| is_allowed(name)))
+    }
+
+    /// `vars` with the XDG base directories under `home` for those missing.
+    pub fn new(home: Option<PathBuf>, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        let home = home.map(|home| home.to_string_lossy().to_string());
+        let mut vars = vars.into_iter().collect::<HashMap<_, _>>();
+        if let Some(home) = &home {
+            vars.entry("HOME".to_string())
+                .or_insert_with(|| home.clone());
+            for (name, dir) in [
+                ("XDG_CONFIG_HOME", ".config"),
+                ("XDG_DATA_HOME", ".local/share"),
+                ("XDG_STATE_HOME", ".local/state"),
+                ("XDG_CACHE_HOME", ".cache"),
+            ] {
+                vars.entry(name.to_string())
+                    .or_insert_with(|| format!("{}/{}", home, dir));
+            }
+        }
+        Self { home, vars }
+    }
+
+    fn with_user_dir(mut self, name: &str, dir: Option<PathBuf>) -> Self {
+        if let Some(dir) = dir {
+            self.vars
+                .entry(name.to_string())
+                .or_insert_with(|| dir.to_string_lossy().to_string());
+        }
+        self
+    }
+
+    /// `value` with a leading `~` or `~/` replaced by the home directory and `$NAME` or
+    /// `${NAME}` by the variable, `$$` standing for `$`. A `$` followed by anything else,
+    /// and `~user`, are kept as they are.
+    pub fn expand(&self, value: &str) -> Result<String, ExpandError> {
+        let (mut expanded, rest) = match value.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+                (self.home.clone().ok_or(ExpandError::NoHome)?, rest)
+            }
+            _ => (String::new(), value),
+        };
+
+        let mut chars = rest.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                expanded.push(c);
+                continue;
+            }
+            let name = match chars.peek() {
+                Some('$') => {
+                    chars.next();
+                    expanded.push('$');
+                    continue;
+                }
+                Some('{') => {
+                    chars.next();
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break name,
+                            Some(c) => name.push(c),
+                            None => {
+                                return Err(ExpandError::Unterminated {
+                                    value: value.to_string(),
+                                });
+                            }
+                        }
+                    }
+                }
+                _ => {
+                    let mut name = String::new();
+                    while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                        name.push(c);
+                    }
+                    name
+                }
+            };
+            if name.is_empty() {
+                expanded.push('$');
+                continue;
+            }
+            let found = self
+                .vars
+                .get(&name)
+                .ok_or(ExpandError::Undefined { name })?;
+            expanded.push_str(found);
+        }
+        Ok(expanded)
+    }
+
+    /// `action` with the opted-in `fields` expanded, in every step of a sequence.
+    pub fn expand_action(
+        &self,
+        action: &Action,
+        fields: &[ExpandField],
+    ) -> Result<Action, ExpandError> {
+        let expand = |field, value: &str| match fields.contains(&field) {
+            true => self.expand(value),
+            false => Ok(value.to_string()),
+        };
+        Ok(match action {
+            Action::Exec { command, args } => Action::Exec {
+                command: expand(ExpandField::Command, command)?,
+                args: args
+                    .iter()
+                    .map(|arg| expand(ExpandField::Args, arg))
+                    .collect::<Result<_, _>>()?,
+            },
+            Action::Open { uri } => Action::Open {
+                uri: expand_uri(uri, |path| expand(ExpandField::Uri, path))?,
+            },
+            Action::Sequence { actions } => Action::Sequence {
+                actions: actions
+                    .iter()
+                    .map(|action| self.expand_action(action, fields))
+                    .collect::<Result<_, _>>()?,
+            },
+            action => action.clone(),
+        })
+    }
+}
+
+/// A `file://` URI expands its path, so `file://~/Downloads` works like `~/Downloads`.
+fn expand_uri(
+    uri: &str,
+    expand: impl Fn(&str) -> Result<String, ExpandError>,
+) -> Result<String, ExpandError> {
+    match uri.strip_prefix("file://") {
+        Some(path) => Ok(format!("file://{}", expand(path)?)),
+        None => expand(uri),
+    }
+}
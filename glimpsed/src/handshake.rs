@@ -0,0 +1,60 @@
+use std::{error::Error, fmt::Display};
+
+use glimpse_sdk::{FeatureError, Features, Metadata, PROTOCOL_VERSION};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandshakeError {
+    /// The plugin may send messages this daemon cannot parse.
+    Newer { plugin: u32, daemon: u32 },
+    /// The plugin turned on experimental features that cannot work together.
+    Features(FeatureError),
+}
+
+impl Display for HandshakeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HandshakeError::Newer { plugin, daemon } => write!(
+                f,
+                "plugin speaks protocol version {}, newer than version {} of the daemon",
+                plugin, daemon
+            ),
+            HandshakeError::Features(err) => write!(f, "invalid features: {}", err),
+        }
+    }
+}
+impl Error for HandshakeError {}
+
+/// Decide whether the daemon can talk to an authenticating plugin.
+///
+/// Plugins on the same or an older protocol are accepted; the daemon works around the
+/// capabilities they lack, see [`Metadata::supports`]. Plugins on a newer protocol are refused.
+pub fn negotiate(metadata: &Metadata) -> Result<(), HandshakeError> {
+    if metadata.protocol_version > PROTOCOL_VERSION {
+        return Err(HandshakeError::Newer {
+            plugin: metadata.protocol_version,
+            daemon: PROTOCOL_VERSION,
+        });
+    }
+    Ok(())
+}
+
+/// The experimental features used with an authenticating plugin: those it and the daemon
+/// both have on. Plugins announcing a combination that cannot work are refused.
+pub fn negotiate_features(
+    metadata: &Metadata,
+    daemon: &Features,
+) -> Result<Features, HandshakeError> {
+    metadata
+        .features
+        .validate()
+        .map_err(HandshakeError::Features)?;
+    Ok(daemon.intersect(&metadata.features))
+}
+
+/// The protocol version announced in a message that failed to parse, if it differs from the
+/// daemon's. Explains why a plugin on another protocol never seems to answer.
+pub fn mismatched_version(line: &str) -> Option<u64> {
+    let value = serde_json::from_str::<serde_json::Value>(line).ok()?;
+    let version = value.pointer("/result/protocol_version")?.as_u64()?;
+    (version != u64::from(PROTOCOL_VERSION)).then_some(version)
+}
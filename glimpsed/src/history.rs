@@ -0,0 +1,204 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use glimpse_sdk::{HistoryEntry, Match};
+use rusqlite::{Connection, params};
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Activations older than this are dropped when the database is opened.
+pub const RETENTION: Duration = Duration::from_secs(365 * DAY);
+
+/// Upper bound of the score boost frecency gives a match.
+const MAX_BOOST: f64 = 1.0;
+
+/// Frecency at which a match gets half of [`MAX_BOOST`], about one activation this week.
+const HALF_BOOST_AT: f64 = 100.0;
+
+#[derive(Debug)]
+pub enum HistoryError {
+    Io(std::io::Error),
+    Sqlite(rusqlite::Error),
+}
+
+impl Display for HistoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HistoryError::Io(err) => write!(f, "io: {}", err),
+            HistoryError::Sqlite(err) => write!(f, "sqlite: {}", err),
+        }
+    }
+}
+impl Error for HistoryError {}
+
+impl From<rusqlite::Error> for HistoryError {
+    fn from(err: rusqlite::Error) -> Self {
+        HistoryError::Sqlite(err)
+    }
+}
+
+/// Activated matches, used to rank frequently and recently chosen results higher.
+pub struct History {
+    conn: Connection,
+}
+
+impl History {
+    pub fn path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("glimpse")
+            .join("history.sqlite3")
+    }
+
+    pub fn open(path: &Path) -> Result<Self, HistoryError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(HistoryError::Io)?;
+        }
+        let history = Self::init(Connection::open(path)?)?;
+        history.prune(SystemTime::now() - RETENTION)?;
+        Ok(history)
+    }
+
+    pub fn open_in_memory() -> Result<Self, HistoryError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, HistoryError> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS activations (
+                id INTEGER PRIMARY KEY,
+                plugin_id TEXT NOT NULL,
+                title TEXT NOT NULL,
+                activated_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS activations_plugin ON activations (plugin_id, title);",
+        )?;
+        Ok(Self { conn })
+    }
+
+    pub fn record(&self, plugin_id: &str, title: &str, at: SystemTime) -> Result<(), HistoryError> {
+        self.conn.execute(
+            "INSERT INTO activations (plugin_id, title, activated_at) VALUES (?1, ?2, ?3)",
+            params![plugin_id, title, unix_seconds(at)],
+        )?;
+        Ok(())
+    }
+
+    /// Drop activations older than `before`, returns how many were removed.
+    pub fn prune(&self, before: SystemTime) -> Result<usize, HistoryError> {
+        Ok(self.conn.execute(
+            "DELETE FROM activations WHERE activated_at < ?1",
+            params![unix_seconds(before)],
+        )?)
+    }
+
+    /// Frecency of every title activated from `plugin_id`.
+    pub fn frecency(
+        &self,
+        plugin_id: &str,
+        now: SystemTime,
+    ) -> Result<HashMap<String, f64>, HistoryError> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT title, activated_at FROM activations WHERE plugin_id = ?1")?;
+        let rows = stmt.query_map(params![plugin_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+
+        let now = unix_seconds(now);
+        let mut scores = HashMap::new();
+        for row in rows {
+            let (title, activated_at) = row?;
+            *scores.entry(title).or_default() += weight(now - activated_at);
+        }
+        Ok(scores)
+    }
+
+    /// Raise the score of matches the user picked before.
+    pub fn boost(
+        &self,
+        plugin_id: &str,
+        items: &mut [Match],
+        now: SystemTime,
+    ) -> Result<(), HistoryError> {
+        let scores = self.frecency(plugin_id, now)?;
+        if scores.is_empty() {
+            return Ok(());
+        }
+        for item in items {
+            if let Some(frecency) = scores.get(&item.title) {
+                item.score += boost_for(*frecency);
+            }
+        }
+        Ok(())
+    }
+
+    /// Previously activated matches, highest frecency first.
+    pub fn recent(&self, limit: usize, now: SystemTime) -> Result<Vec<HistoryEntry>, HistoryError> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT plugin_id, title, activated_at FROM activations")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+
+        let now = unix_seconds(now);
+        let mut entries: HashMap<(String, String), HistoryEntry> = HashMap::new();
+        for row in rows {
+            let (plugin_id, title, activated_at) = row?;
+            let entry = entries
+                .entry((plugin_id.clone(), title.clone()))
+                .or_insert_with(|| HistoryEntry {
+                    plugin_id,
+                    title,
+                    count: 0,
+                    last_used: 0,
+                    score: 0.0,
+                });
+            entry.count += 1;
+            entry.last_used = entry.last_used.max(activated_at.max(0) as u64);
+            entry.score += weight(now - activated_at);
+        }
+
+        let mut entries = entries.into_values().collect::<Vec<_>>();
+        entries.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(b.last_used.cmp(&a.last_used))
+        });
+        entries.truncate(limit);
+        Ok(entries)
+    }
+}
+
+/// Recent activations count more, the way browsers rank their address bar suggestions.
+fn weight(age_seconds: i64) -> f64 {
+    let days = age_seconds.max(0) as u64 / DAY;
+    match days {
+        0..4 => 100.0,
+        4..14 => 70.0,
+        14..31 => 50.0,
+        31..90 => 30.0,
+        _ => 10.0,
+    }
+}
+
+/// Map unbounded frecency to a score boost below [`MAX_BOOST`].
+pub fn boost_for(frecency: f64) -> f64 {
+    MAX_BOOST * frecency / (frecency + HALF_BOOST_AT)
+}
+
+fn unix_seconds(at: SystemTime) -> i64 {
+    at.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,361 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Component, Path, PathBuf},
+    sync::Mutex,
+    time::UNIX_EPOCH,
+};
+
+use base64::Engine;
+use glimpse_sdk::{Icon, Match};
+use resvg::{tiny_skia, usvg};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+
+use crate::thumbnails::{self, MediaKind, Thumbnails};
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct IconConfig {
+    /// Icon theme to resolve names in, the active GTK theme when unset.
+    pub theme: Option<String>,
+    /// Size in pixels icons are looked up and SVGs rasterized at.
+    pub size: u16,
+    /// Render the thumbnails plugins ask for with `Thumbnail` icons. Without, such matches
+    /// show the thumbnails file managers left in the shared cache alone.
+    pub thumbnails: bool,
+    /// Icons plugins bundle up to this many bytes are sent inline, so clients that cannot
+    /// read the daemon's files show them too. 0 sends paths alone.
+    pub inline_bytes: u64,
+}
+
+impl Default for IconConfig {
+    fn default() -> Self {
+        Self {
+            theme: None,
+            size: 48,
+            thumbnails: true,
+            inline_bytes: 16 * 1024,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum IconError {
+    Io(std::io::Error),
+    Svg(usvg::Error),
+    /// The SVG has no area to draw or could not be encoded.
+    Render(String),
+}
+
+impl Display for IconError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IconError::Io(err) => write!(f, "io: {}", err),
+            IconError::Svg(err) => write!(f, "svg: {}", err),
+            IconError::Render(message) => write!(f, "render: {}", message),
+        }
+    }
+}
+impl Error for IconError {}
+
+/// Resolves match icons into images clients load without knowing about icon themes.
+///
+/// Theme names are looked up in the icon theme, SVGs are rasterized into PNGs kept in the
+/// cache directory. Theme lookups are remembered, plugins send the same icons over and over.
+pub struct Icons {
+    theme: String,
+    size: u16,
+    cache_dir: PathBuf,
+    inline_bytes: u64,
+    /// Theme icons by name, the theme does not change while the daemon runs.
+    resolved: Mutex<HashMap<String, Option<Icon>>>,
+    /// Bundled icons by plugin executable and name.
+    bundled: Mutex<HashMap<(PathBuf, String), Option<Icon>>>,
+    thumbnails: Option<Thumbnails>,
+    /// Files to render thumbnails of, `None` when rendering is off.
+    render: Option<mpsc::UnboundedSender<PathBuf>>,
+}
+
+impl Icons {
+    pub fn cache_dir() -> PathBuf {
+        dirs::cache_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("glimpse")
+            .join("icons")
+    }
+
+    pub fn new(config: &IconConfig, cache_dir: &Path) -> Self {
+        Self {
+            theme: config.theme.clone().unwrap_or_else(active_theme),
+            size: config.size.max(1),
+            cache_dir: cache_dir.to_path_buf(),
+            inline_bytes: config.inline_bytes,
+            resolved: Mutex::new(HashMap::new()),
+            bundled: Mutex::new(HashMap::new()),
+            thumbnails: None,
+            render: None,
+        }
+    }
+
+    /// Resolve `Thumbnail` icons from `cache`, sending the files missing one to `render`.
+    pub fn with_thumbnails(
+        mut self,
+        cache: Thumbnails,
+        render: Option<mpsc::UnboundedSender<PathBuf>>,
+    ) -> Self {
+        self.thumbnails = Some(cache);
+        self.render = render;
+        self
+    }
+
+    pub fn theme(&self) -> &str {
+        &self.theme
+    }
+
+    /// The icon clients get in place of `icon`: a `Path` to a raster image or an `Emoji`.
+    /// `None` when the icon does not exist or cannot be drawn.
+    pub fn resolve(&self, icon: &Icon) -> Option<Icon> {
+        self.resolve_from(None, icon)
+    }
+
+    /// Like [`Icons::resolve`] for an icon of the plugin with the executable `plugin`, whose
+    /// `Bundled` icons are looked up next to it. Small ones are sent inline as `Data`.
+    pub fn resolve_from(&self, plugin: Option<&Path>, icon: &Icon) -> Option<Icon> {
+        match icon {
+            Icon::Emoji { .. } | Icon::Data { .. } => Some(icon.clone()),
+            Icon::Path { path } => {
+                self.load(Path::new(path.strip_prefix("file://").unwrap_or(path)))
+            }
+            // desktop entries name their icon by path at times
+            Icon::Freedesktop { name } if name.starts_with('/') => self.load(Path::new(name)),
+            Icon::Freedesktop { name } => {
+                if let Some(resolved) = self.resolved.lock().unwrap().get(name) {
+                    return resolved.clone();
+                }
+                let resolved =
+                    themed(name, &self.theme, self.size).and_then(|path| self.load(&path));
+                self.resolved
+                    .lock()
+                    .unwrap()
+                    .insert(name.clone(), resolved.clone());
+                resolved
+            }
+            Icon::Thumbnail { path } => {
+                self.thumbnail(Path::new(path.strip_prefix("file://").unwrap_or(path)))
+            }
+            Icon::Bundled { name } => self.bundled(plugin?, name),
+        }
+    }
+
+    fn bundled(&self, plugin: &Path, name: &str) -> Option<Icon> {
+        let key = (plugin.to_path_buf(), name.to_string());
+        if let Some(resolved) = self.bundled.lock().unwrap().get(&key) {
+            return resolved.clone();
+        }
+        let resolved = bundled_path(plugin, name)
+            .and_then(|path| self.load(&path))
+            .map(|icon| self.inline(icon));
+        self.bundled.lock().unwrap().insert(key, resolved.clone());
+        resolved
+    }
+
+    /// `icon` as `Data` if it is a `Path` to an image of at most `inline_bytes`.
+    fn inline(&self, icon: Icon) -> Icon {
+        let Icon::Path { path } = &icon else {
+            return icon;
+        };
+        let path = Path::new(path);
+        let small = std::fs::metadata(path).is_ok_and(|meta| meta.len() <= self.inline_bytes);
+        let Some(mime) = mime_type(path).filter(|_| small) else {
+            return icon;
+        };
+        match std::fs::read(path) {
+            Ok(data) => Icon::data(mime, base64::engine::general_purpose::STANDARD.encode(data)),
+            Err(err) => {
+                tracing::warn!("failed to inline {}: {}", path.display(), err);
+                icon
+            }
+        }
+    }
+
+    /// The cached thumbnail of `path`. A missing one is queued for rendering and the icon
+    /// stays a `Thumbnail` until clients are told it is ready. Files no thumbnailer handles or
+    /// whose thumbnail failed get no icon.
+    fn thumbnail(&self, path: &Path) -> Option<Icon> {
+        let cache = self.thumbnails.as_ref()?;
+        MediaKind::of(path)?;
+        let mtime = thumbnails::mtime(path)?;
+        if let Some(cached) = cache.lookup(path, mtime) {
+            return Some(Icon::path(cached.to_string_lossy()));
+        }
+        if cache.failed(path, mtime) {
+            return None;
+        }
+        let render = self.render.as_ref()?;
+        // a file queued twice is rendered once, the second request finds it cached
+        render.send(path.to_path_buf()).ok()?;
+        Some(Icon::thumbnail(path.to_string_lossy()))
+    }
+
+    /// Resolve the icons of `items` sent by the plugin with the executable `plugin` in place,
+    /// dropping those that cannot be shown.
+    pub fn resolve_all(&self, plugin: Option<&Path>, items: &mut [Match]) {
+        for item in items {
+            item.icon = item
+                .icon
+                .as_ref()
+                .and_then(|icon| self.resolve_from(plugin, icon));
+        }
+    }
+
+    fn load(&self, path: &Path) -> Option<Icon> {
+        if !path.is_file() {
+            return None;
+        }
+        let is_svg = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"));
+        if !is_svg {
+            return Some(Icon::path(path.to_string_lossy()));
+        }
+        match rasterize(path, self.size, &self.cache_dir) {
+            Ok(png) => Some(Icon::path(png.to_string_lossy())),
+            Err(err) => {
+                tracing::warn!("failed to rasterize {}: {}", path.display(), err);
+                None
+            }
+        }
+    }
+}
+
+/// The file of the icon `name` bundled with the plugin at `plugin`, in the `icons/` folder
+/// next to the executable. Names are plain file names: anything reaching outside the folder,
+/// by separators, `..` or symlinks, resolves to nothing.
+pub fn bundled_path(plugin: &Path, name: &str) -> Option<PathBuf> {
+    let mut components = Path::new(name).components();
+    let (Some(Component::Normal(_)), None) = (components.next(), components.next()) else {
+        return None;
+    };
+    let dir = plugin.parent()?.join("icons").canonicalize().ok()?;
+    [
+        name.to_string(),
+        format!("{}.svg", name),
+        format!("{}.png", name),
+    ]
+    .iter()
+    .find_map(|candidate| {
+        let path = dir.join(candidate).canonicalize().ok()?;
+        (path.starts_with(&dir) && path.is_file()).then_some(path)
+    })
+}
+
+/// The MIME type of the raster image at `path`, by extension.
+fn mime_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// The icon theme GTK applications use: the `gtk-icon-theme-name` of the GTK settings, then
+/// the GNOME setting, then `hicolor` every theme falls back to.
+pub fn active_theme() -> String {
+    let settings = dirs::config_dir()
+        .map(|dir| {
+            ["gtk-4.0", "gtk-3.0"]
+                .map(|version| dir.join(version).join("settings.ini"))
+                .to_vec()
+        })
+        .unwrap_or_default();
+    settings
+        .iter()
+        .find_map(|path| {
+            let content = std::fs::read_to_string(path).ok()?;
+            theme_from_settings(&content)
+        })
+        .or_else(default_theme)
+        .unwrap_or_else(|| "hicolor".to_string())
+}
+
+/// The file of the icon `name` in `theme` or the themes it inherits.
+#[cfg(unix)]
+fn themed(name: &str, theme: &str, size: u16) -> Option<PathBuf> {
+    freedesktop_icons::lookup(name)
+        .with_theme(theme)
+        .with_size(size)
+        .with_cache()
+        .find()
+}
+
+/// Windows has no icon themes, named icons are left to clients.
+#[cfg(not(unix))]
+fn themed(_name: &str, _theme: &str, _size: u16) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(unix)]
+fn default_theme() -> Option<String> {
+    freedesktop_icons::default_theme_gtk()
+}
+
+#[cfg(not(unix))]
+fn default_theme() -> Option<String> {
+    None
+}
+
+/// The `gtk-icon-theme-name` of a GTK `settings.ini`.
+pub fn theme_from_settings(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "gtk-icon-theme-name")
+            .then(|| value.trim().trim_matches('"').to_string())
+            .filter(|theme| !theme.is_empty())
+    })
+}
+
+/// Render the SVG at `svg` into a `size` pixel square PNG in `cache_dir`, keeping its aspect
+/// ratio. Renders are reused until the SVG changes.
+pub fn rasterize(svg: &Path, size: u16, cache_dir: &Path) -> Result<PathBuf, IconError> {
+    let modified = std::fs::metadata(svg)
+        .and_then(|meta| meta.modified())
+        .map_err(IconError::Io)?;
+    let mut hasher = DefaultHasher::new();
+    svg.hash(&mut hasher);
+    modified
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    let png = cache_dir.join(format!("{:016x}-{}.png", hasher.finish(), size));
+    if png.is_file() {
+        return Ok(png);
+    }
+
+    let data = std::fs::read(svg).map_err(IconError::Io)?;
+    let tree = usvg::Tree::from_data(&data, &usvg::Options::default()).map_err(IconError::Svg)?;
+    let mut pixmap = tiny_skia::Pixmap::new(size.into(), size.into())
+        .ok_or_else(|| IconError::Render("empty icon size".to_string()))?;
+    let view = tree.size();
+    let scale = f32::from(size) / view.width().max(view.height());
+    let offset_x = (f32::from(size) - view.width() * scale) / 2.0;
+    let offset_y = (f32::from(size) - view.height() * scale) / 2.0;
+    let transform =
+        tiny_skia::Transform::from_scale(scale, scale).post_translate(offset_x, offset_y);
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+    let encoded = pixmap
+        .encode_png()
+        .map_err(|err| IconError::Render(err.to_string()))?;
+
+    // written next to the cache entry and renamed, a reader never sees half a PNG
+    std::fs::create_dir_all(cache_dir).map_err(IconError::Io)?;
+    let partial = png.with_extension("png.partial");
+    std::fs::write(&partial, encoded).map_err(IconError::Io)?;
+    std::fs::rename(&partial, &png).map_err(IconError::Io)?;
+    Ok(png)
+}
@@ -0,0 +1,143 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use serde::Deserialize;
+use tokio::{sync::Mutex, time::Instant};
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct JanitorConfig {
+    pub enabled: bool,
+    /// Seconds without client or plugin traffic before memory is reclaimed.
+    pub idle_secs: u64,
+    /// Ask the allocator to return freed pages to the OS (glibc only).
+    pub malloc_trim: bool,
+}
+
+impl Default for JanitorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            idle_secs: 300,
+            malloc_trim: true,
+        }
+    }
+}
+
+/// State that can release memory it keeps around for reuse (caches, buffers, match stores).
+pub trait Reclaim: Send + Sync {
+    /// Release what can be released, returning an estimate of the freed bytes.
+    fn reclaim(&mut self) -> usize;
+}
+
+#[derive(Default, Debug)]
+pub struct JanitorStats {
+    runs: AtomicU64,
+    reclaimed_bytes: AtomicU64,
+}
+
+impl JanitorStats {
+    pub fn runs(&self) -> u64 {
+        self.runs.load(Ordering::Relaxed)
+    }
+
+    pub fn reclaimed_bytes(&self) -> u64 {
+        self.reclaimed_bytes.load(Ordering::Relaxed)
+    }
+}
+
+/// Reclaims memory from registered targets once the daemon has been idle for a while.
+pub struct Janitor {
+    config: JanitorConfig,
+    last_activity: std::sync::Mutex<Instant>,
+    swept: AtomicBool,
+    targets: Vec<Arc<Mutex<dyn Reclaim>>>,
+    stats: Arc<JanitorStats>,
+}
+
+impl Janitor {
+    pub fn new(config: JanitorConfig) -> Self {
+        Self {
+            config,
+            last_activity: std::sync::Mutex::new(Instant::now()),
+            swept: AtomicBool::new(false),
+            targets: vec![],
+            stats: Arc::new(JanitorStats::default()),
+        }
+    }
+
+    pub fn register(&mut self, target: Arc<Mutex<dyn Reclaim>>) {
+        self.targets.push(target);
+    }
+
+    pub fn stats(&self) -> Arc<JanitorStats> {
+        self.stats.clone()
+    }
+
+    pub fn idle_after(&self) -> Duration {
+        Duration::from_secs(self.config.idle_secs)
+    }
+
+    /// Record client or plugin traffic, postponing the next sweep.
+    pub fn touch(&self) {
+        *self.last_activity.lock().unwrap() = Instant::now();
+        self.swept.store(false, Ordering::Relaxed);
+    }
+
+    /// Sweep once per idle period: returns `None` if the daemon is busy or was already swept.
+    pub async fn sweep_if_idle(&self, now: Instant) -> Option<usize> {
+        let last_activity = *self.last_activity.lock().unwrap();
+        if now.saturating_duration_since(last_activity) < self.idle_after() {
+            return None;
+        }
+        if self.swept.swap(true, Ordering::Relaxed) {
+            return None;
+        }
+        Some(self.sweep().await)
+    }
+
+    pub async fn sweep(&self) -> usize {
+        let mut reclaimed = 0;
+        for target in &self.targets {
+            reclaimed += target.lock().await.reclaim();
+        }
+        if self.config.malloc_trim {
+            trim_allocator();
+        }
+
+        self.stats.runs.fetch_add(1, Ordering::Relaxed);
+        self.stats
+            .reclaimed_bytes
+            .fetch_add(reclaimed as u64, Ordering::Relaxed);
+        tracing::debug!(
+            "janitor reclaimed {} bytes ({} total)",
+            reclaimed,
+            self.stats.reclaimed_bytes()
+        );
+        reclaimed
+    }
+
+    pub async fn run(self: Arc<Self>) {
+        let interval = (self.idle_after() / 2).max(Duration::from_secs(1));
+        loop {
+            tokio::time::sleep(interval).await;
+            self.sweep_if_idle(Instant::now()).await;
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+fn trim_allocator() {
+    // SAFETY: malloc_trim only walks the allocator's own free lists.
+    unsafe {
+        libc::malloc_trim(0);
+    }
+}
+
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+fn trim_allocator() {}
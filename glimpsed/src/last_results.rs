@@ -0,0 +1,197 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use glimpse_sdk::{Match, Metadata, Permission};
+use serde::{Deserialize, Serialize};
+
+/// Version of the snapshot file layout. Snapshots of another version are ignored, so a
+/// daemon never hands clients matches in a shape it no longer writes.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct LastResultsConfig {
+    /// Keep the last completed search on disk for clients that reconnect.
+    pub enabled: bool,
+    /// Seconds a snapshot is shown after its search completed.
+    pub ttl_secs: u64,
+    /// Plugin ids whose matches are never written, in addition to plugins marked sensitive
+    /// or declaring `Permission::Secrets`.
+    pub exclude: Vec<String>,
+}
+
+impl Default for LastResultsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_secs: 300,
+            exclude: vec![],
+        }
+    }
+}
+
+impl LastResultsConfig {
+    pub fn ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_secs)
+    }
+
+    /// Whether the matches of a plugin stay out of snapshots.
+    pub fn is_excluded(&self, metadata: &Metadata) -> bool {
+        metadata.sensitive
+            || metadata.permissions.contains(&Permission::Secrets)
+            || self.exclude.contains(&metadata.id)
+    }
+}
+
+#[derive(Debug)]
+pub enum LastResultsError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl Display for LastResultsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LastResultsError::Io(err) => write!(f, "io: {}", err),
+            LastResultsError::Json(err) => write!(f, "json: {}", err),
+        }
+    }
+}
+impl Error for LastResultsError {}
+
+/// A match of the snapshot with the key of the plugin that sent it, so it can be activated.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedMatch {
+    pub plugin_key: String,
+    #[serde(rename = "match")]
+    pub match_: Match,
+}
+
+/// A completed search as the user last saw it, matches ranked best first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SearchSnapshot {
+    pub version: u32,
+    pub query: String,
+    /// Unix seconds.
+    pub saved_at: u64,
+    pub items: Vec<SavedMatch>,
+}
+
+impl SearchSnapshot {
+    pub fn new(query: String, items: Vec<SavedMatch>, at: SystemTime) -> Self {
+        Self {
+            version: SNAPSHOT_VERSION,
+            query,
+            saved_at: unix_seconds(at),
+            items,
+        }
+    }
+
+    pub fn is_fresh(&self, ttl: Duration, now: SystemTime) -> bool {
+        unix_seconds(now).saturating_sub(self.saved_at) <= ttl.as_secs()
+    }
+}
+
+/// The last completed search, kept on disk so a client restarted after a crash shows what the
+/// user was looking at right away.
+pub struct LastResults {
+    path: PathBuf,
+    config: LastResultsConfig,
+}
+
+impl LastResults {
+    pub fn path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("glimpse")
+            .join("last_results.json")
+    }
+
+    pub fn new(path: &Path, config: LastResultsConfig) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &LastResultsConfig {
+        &self.config
+    }
+
+    /// Replace the stored snapshot. The file is written next to the old one and renamed over
+    /// it, a crash while saving leaves the previous snapshot.
+    pub fn save(&self, snapshot: &SearchSnapshot) -> Result<(), LastResultsError> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(LastResultsError::Io)?;
+        }
+        let content = serde_json::to_vec(snapshot).map_err(LastResultsError::Json)?;
+        let partial = self.path.with_extension("json.partial");
+        std::fs::write(&partial, content).map_err(LastResultsError::Io)?;
+        std::fs::rename(&partial, &self.path).map_err(LastResultsError::Io)
+    }
+
+    /// The stored snapshot, `None` if there is none, it expired or was written in another
+    /// version.
+    pub fn load(&self, now: SystemTime) -> Option<SearchSnapshot> {
+        if !self.config.enabled {
+            return None;
+        }
+        let content = match std::fs::read(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(err) => {
+                tracing::warn!("failed to read {}: {}", self.path.display(), err);
+                return None;
+            }
+        };
+        // read the version alone first, other versions may not parse
+        #[derive(Deserialize)]
+        struct Versioned {
+            version: u32,
+        }
+        match serde_json::from_slice::<Versioned>(&content) {
+            Ok(Versioned { version }) if version == SNAPSHOT_VERSION => {}
+            Ok(Versioned { version }) => {
+                tracing::debug!("ignoring snapshot of version {}", version);
+                return None;
+            }
+            Err(err) => {
+                tracing::warn!("invalid snapshot {}: {}", self.path.display(), err);
+                return None;
+            }
+        }
+        let snapshot = match serde_json::from_slice::<SearchSnapshot>(&content) {
+            Ok(snapshot) => snapshot,
+            Err(err) => {
+                tracing::warn!("invalid snapshot {}: {}", self.path.display(), err);
+                return None;
+            }
+        };
+        snapshot
+            .is_fresh(self.config.ttl(), now)
+            .then_some(snapshot)
+    }
+
+    /// Drop the stored snapshot.
+    pub fn clear(&self) -> Result<(), LastResultsError> {
+        match std::fs::remove_file(&self.path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                Err(LastResultsError::Io(err))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn unix_seconds(at: SystemTime) -> u64 {
+    at.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -1,3 +1,40 @@
+pub mod activation;
+pub mod clients;
+pub mod commands;
+pub mod compression;
+pub mod config;
 pub mod daemon;
-pub mod plugins;
+pub mod dbus;
+pub mod dependencies;
+pub mod devices;
 pub mod dispatchers;
+pub mod expand;
+pub mod handshake;
+pub mod history;
+pub mod icons;
+pub mod janitor;
+pub mod last_results;
+pub mod logind;
+pub mod logs;
+pub mod matches;
+pub mod metrics;
+pub mod outbox;
+pub mod permissions;
+pub mod plugin_config;
+pub mod plugins;
+pub mod policy;
+pub mod power;
+pub mod prefetch;
+pub mod ranking;
+pub mod requests;
+pub mod routing;
+pub mod sandbox;
+pub mod shortcut;
+pub mod stats;
+pub mod subscriptions;
+pub mod supervisor;
+pub mod thumbnails;
+pub mod timers;
+pub mod transport;
+pub mod trust;
+pub mod updates;
@@ -0,0 +1,48 @@
+use glimpse_sdk::SystemCommand;
+
+const LOGIND_SERVICE: &str = "org.freedesktop.login1";
+const LOGIND_PATH: &str = "/org/freedesktop/login1";
+const MANAGER_INTERFACE: &str = "org.freedesktop.login1.Manager";
+/// The session of the caller, or the display session of its user when the caller runs
+/// outside of one, as a daemon started by systemd does.
+const SESSION_PATH: &str = "/org/freedesktop/login1/session/auto";
+const SESSION_INTERFACE: &str = "org.freedesktop.login1.Session";
+
+/// The logind method carrying out `command` and whether it is called on the session rather
+/// than the manager.
+pub fn method(command: SystemCommand) -> (&'static str, bool) {
+    match command {
+        SystemCommand::PowerOff => ("PowerOff", false),
+        SystemCommand::Reboot => ("Reboot", false),
+        SystemCommand::Suspend => ("Suspend", false),
+        SystemCommand::LockScreen => ("Lock", true),
+        SystemCommand::LogOut => ("Terminate", true),
+    }
+}
+
+/// Runs system commands through systemd-logind on the system bus, polkit deciding whether
+/// the user may.
+pub struct Logind {
+    manager: zbus::Proxy<'static>,
+    session: zbus::Proxy<'static>,
+}
+
+impl Logind {
+    pub async fn connect() -> zbus::Result<Self> {
+        let connection = zbus::Connection::system().await?;
+        let manager =
+            zbus::Proxy::new(&connection, LOGIND_SERVICE, LOGIND_PATH, MANAGER_INTERFACE).await?;
+        let session =
+            zbus::Proxy::new(&connection, LOGIND_SERVICE, SESSION_PATH, SESSION_INTERFACE).await?;
+        Ok(Self { manager, session })
+    }
+
+    pub async fn run(&self, command: SystemCommand) -> zbus::Result<()> {
+        match method(command) {
+            (method, true) => self.session.call_method(method, &()).await?,
+            // interactive, polkit may ask for a password
+            (method, false) => self.manager.call_method(method, &(true,)).await?,
+        };
+        Ok(())
+    }
+}
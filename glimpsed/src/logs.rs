@@ -0,0 +1,180 @@
+use std::{
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use glimpse_sdk::LogRecord;
+use serde::Deserialize;
+
+/// Name of the daemon's own log file.
+pub const DAEMON_LOG: &str = "glimpsed";
+
+/// Most records a log query answers with, the newest ones.
+pub const MAX_RECORDS: usize = 1000;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct LogConfig {
+    /// Keep what plugins and the daemon log in files, one per plugin.
+    pub enabled: bool,
+    /// Size a log file grows to before it is rotated.
+    pub max_bytes: u64,
+    /// Rotated files kept next to the current one.
+    pub keep: usize,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_bytes: 1024 * 1024,
+            keep: 3,
+        }
+    }
+}
+
+/// The log file name of the plugin at `path`, its executable name.
+pub fn log_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Log files by name under one directory, `<name>.log` with `<name>.log.1` and on
+/// for older records.
+#[derive(Debug, Clone)]
+pub struct LogStore {
+    dir: PathBuf,
+    config: LogConfig,
+}
+
+impl LogStore {
+    pub fn dir() -> PathBuf {
+        dirs::state_dir()
+            .or_else(dirs::data_dir)
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("glimpse")
+            .join("logs")
+    }
+
+    pub fn new(dir: &Path, config: LogConfig) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            config,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// The log file `name` for appending, created if missing.
+    pub fn open(&self, name: &str) -> std::io::Result<LogFile> {
+        let path = self.path(name)?;
+        std::fs::create_dir_all(&self.dir)?;
+        LogFile::open(path, self.config.clone())
+    }
+
+    /// Records of `name` logged at or after `since`, unix milliseconds, oldest first. Only
+    /// the newest [`MAX_RECORDS`] are kept, lines that are not records are skipped.
+    pub fn read(&self, name: &str, since: Option<u64>) -> std::io::Result<Vec<LogRecord>> {
+        let path = self.path(name)?;
+        let mut records = VecDeque::new();
+        for generation in (0..=self.config.keep).rev() {
+            let file = match File::open(rotated(&path, generation)) {
+                Ok(file) => file,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            };
+            for line in BufReader::new(file).lines() {
+                let Ok(record) = serde_json::from_str::<LogRecord>(&line?) else {
+                    continue;
+                };
+                if since.is_some_and(|since| record.ts < since) {
+                    continue;
+                }
+                if records.len() == MAX_RECORDS {
+                    records.pop_front();
+                }
+                records.push_back(record);
+            }
+        }
+        Ok(records.into())
+    }
+
+    /// Names are file names, never paths out of the log directory.
+    fn path(&self, name: &str) -> std::io::Result<PathBuf> {
+        if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("invalid log name: {:?}", name),
+            ));
+        }
+        Ok(self.dir.join(format!("{}.log", name)))
+    }
+}
+
+/// `path` for the current generation, `path.N` for rotated ones.
+fn rotated(path: &Path, generation: usize) -> PathBuf {
+    match generation {
+        0 => path.to_path_buf(),
+        generation => {
+            let mut name = path.as_os_str().to_owned();
+            name.push(format!(".{}", generation));
+            PathBuf::from(name)
+        }
+    }
+}
+
+/// A log file records are appended to, rotated once it grows over the configured size.
+#[derive(Debug)]
+pub struct LogFile {
+    path: PathBuf,
+    config: LogConfig,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(path: PathBuf, config: LogConfig) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            config,
+            file,
+            size,
+        })
+    }
+
+    pub fn append(&mut self, record: &LogRecord) -> std::io::Result<()> {
+        let line = format!("{}\n", record.to_json());
+        if self.size > 0 && self.size + line.len() as u64 > self.config.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Shift every generation one up, dropping the oldest, and start an empty file.
+    fn rotate(&mut self) -> std::io::Result<()> {
+        if self.config.keep == 0 {
+            std::fs::remove_file(&self.path)?;
+        }
+        for generation in (0..self.config.keep).rev() {
+            match std::fs::rename(
+                rotated(&self.path, generation),
+                rotated(&self.path, generation + 1),
+            ) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+        }
+        *self = Self::open(self.path.clone(), self.config.clone())?;
+        Ok(())
+    }
+}
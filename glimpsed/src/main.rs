@@ -1,33 +1,151 @@
-use crate::daemon::Daemon;
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use glimpse_sdk::{JsonLayer, get_client_socket_path};
+
+use glimpsed::{
+    activation,
+    config::DaemonConfig,
+    daemon::Daemon,
+    dispatchers::SystemDispatcher,
+    logs::{DAEMON_LOG, LogConfig, LogStore},
+    ranking::{RankingLog, evaluate},
+    timers::{self, TimerStore, Timers},
+    transport::ClientListener,
+};
 use tokio::signal;
-mod daemon;
-mod plugins;
-mod dispatchers;
+use tracing_subscriber::{filter::LevelFilter, layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
-    tracing_subscriber::fmt()
-        .with_writer(std::io::stderr)
-        .with_max_level(tracing::Level::DEBUG)
-        .init();
+    // the config says where the daemon logs, problems reading it go to stderr only
+    let config = tracing::subscriber::with_default(
+        tracing_subscriber::fmt()
+            .with_writer(std::io::stderr)
+            .finish(),
+        DaemonConfig::load,
+    );
+    setup_logging(&config.logs);
 
-    let mut daemon = Daemon::new();
-    let mut sigterm = signal::unix::signal(signal::unix::SignalKind::terminate())?;
-    let mut sigint = signal::unix::signal(signal::unix::SignalKind::interrupt())?;
+    // `eval-ranking [LOG]` replays the ranking log through every built-in strategy
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.first().is_some_and(|arg| arg == "eval-ranking") {
+        let path = args
+            .get(1)
+            .map(PathBuf::from)
+            .unwrap_or_else(RankingLog::path);
+        return eval_ranking(&path);
+    }
+
+    // `--daemonize` starts a daemon in the background unless one runs, for clients to use
+    if args.iter().any(|arg| arg == "--daemonize") {
+        let daemon = std::env::current_exe()?;
+        activation::daemonize(&daemon, &get_client_socket_path())?;
+        return Ok(());
+    }
+
+    // `--listen` serves socket clients only, without a client on stdio, and
+    // `--exit-when-idle` stops serving once no client is left for a while. Daemons started
+    // by systemd socket activation do both.
+    let activated = activation::activated_listener()?;
+    let listen = activated.is_some() || args.iter().any(|arg| arg == "--listen");
+    let exit_when_idle = activated.is_some() || args.iter().any(|arg| arg == "--exit-when-idle");
 
+    // timers set before a restart fire once the daemon is back
+    let timers = Arc::new(Timers::new(TimerStore::load(&TimerStore::path())));
+    let launcher = config.shortcut.launcher();
+    tokio::spawn(
+        timers
+            .clone()
+            .run(move |timer| timers::fire(timer, launcher.clone())),
+    );
+    let dispatcher = SystemDispatcher::with_timers(timers);
+    let mut daemon = Daemon::with_config(config, Arc::new(dispatcher));
+    if exit_when_idle {
+        daemon.exit_when_idle();
+    }
     tokio::select! {
-        _ = sigterm.recv() => {
-            tracing::debug!("received SIGTERM, shutting down gracefully");
-            daemon.stop().await;
-        },
-        _ = sigint.recv() => {
-            tracing::debug!("received SIGINT, shutting down gracefully");
+        signal = shutdown_signal() => {
+            tracing::debug!("received {}, shutting down gracefully", signal?);
             daemon.stop().await;
         },
-        _ = daemon.run() => {
+        served = serve(&mut daemon, listen, activated) => {
+            served?;
             tracing::debug!("daemon finished");
         }
     }
 
     Ok(())
 }
+
+async fn serve(
+    daemon: &mut Daemon,
+    listen: bool,
+    activated: Option<ClientListener>,
+) -> std::io::Result<()> {
+    match (activated, listen) {
+        (Some(listener), _) => {
+            daemon.listen_on(listener).await;
+            Ok(())
+        }
+        (None, true) => daemon.listen().await,
+        (None, false) => {
+            daemon.run().await;
+            Ok(())
+        }
+    }
+}
+
+/// The name of the first signal asking the daemon to stop.
+#[cfg(unix)]
+async fn shutdown_signal() -> std::io::Result<&'static str> {
+    let mut sigterm = signal::unix::signal(signal::unix::SignalKind::terminate())?;
+    let mut sigint = signal::unix::signal(signal::unix::SignalKind::interrupt())?;
+    tokio::select! {
+        _ = sigterm.recv() => Ok("SIGTERM"),
+        _ = sigint.recv() => Ok("SIGINT"),
+    }
+}
+
+/// The name of the first console event asking the daemon to stop.
+#[cfg(windows)]
+async fn shutdown_signal() -> std::io::Result<&'static str> {
+    let mut ctrl_close = signal::windows::ctrl_close()?;
+    tokio::select! {
+        _ = ctrl_close.recv() => Ok("CTRL_CLOSE"),
+        ctrl_c = signal::ctrl_c() => ctrl_c.map(|_| "CTRL_C"),
+    }
+}
+
+/// Log to stderr and, unless disabled, to the daemon's own log file.
+fn setup_logging(config: &LogConfig) {
+    let file = match config.enabled {
+        true => LogStore::new(&LogStore::dir(), config.clone())
+            .open(DAEMON_LOG)
+            .inspect_err(|e| eprintln!("not keeping daemon logs: {}", e))
+            .ok(),
+        false => None,
+    };
+    let json = file.map(|file| {
+        let file = Mutex::new(file);
+        JsonLayer::new(move |record| {
+            let _ = file.lock().unwrap().append(record);
+        })
+    });
+    tracing_subscriber::registry()
+        .with(LevelFilter::DEBUG)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(json)
+        .init();
+}
+
+fn eval_ranking(path: &Path) -> Result<(), anyhow::Error> {
+    let events = RankingLog::new(path).read()?;
+    println!("{} activations in {}", events.len(), path.display());
+    for strategy in DaemonConfig::load().ranking.strategy.builtins() {
+        println!("{}", evaluate(strategy.build().as_mut(), &events));
+    }
+    Ok(())
+}
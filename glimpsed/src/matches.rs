@@ -0,0 +1,502 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt::Display,
+    ops::RangeInclusive,
+};
+
+use glimpse_sdk::{
+    Detail, Highlights, Match, MatchAction, SnapshotItem,
+    matcher::{Matcher, ranges},
+};
+
+use crate::{janitor::Reclaim, ranking::Features};
+
+/// Daemon-assigned match identifier, stable for the lifetime of a search generation.
+pub type MatchId = usize;
+
+#[derive(Debug, Clone)]
+pub struct MatchHolder {
+    pub plugin_id: String,
+    pub match_: Match,
+    /// What the ranking strategy scored the match on.
+    pub features: Features,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActivationError {
+    StaleGeneration { requested: usize, current: usize },
+    UnknownMatch(MatchId),
+    UnknownAction { match_id: MatchId, action: usize },
+}
+
+impl Display for ActivationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActivationError::StaleGeneration { requested, current } => write!(
+                f,
+                "stale generation: requested {}, current {}",
+                requested, current
+            ),
+            ActivationError::UnknownMatch(match_id) => write!(f, "unknown match: {}", match_id),
+            ActivationError::UnknownAction { match_id, action } => {
+                write!(f, "unknown action {} for match {}", action, match_id)
+            }
+        }
+    }
+}
+impl Error for ActivationError {}
+
+/// Matches collected for the current search.
+///
+/// Every search starts a new generation (the client request id). Matches are stored in a slab
+/// keyed by their [`MatchId`], so ids handed to the client stay valid while later batches
+/// arrive; batches and activations that refer to another generation are rejected.
+///
+/// With a page size the client is sent the best matches up to its page alone, the rest are
+/// held until it asks for [`MatchStore::more`].
+///
+/// Matches of fallback plugins are held back until the search completes, and only shown if
+/// no other match scored at least the fallback score by then.
+#[derive(Default)]
+pub struct MatchStore {
+    generation: usize,
+    slab: Vec<MatchHolder>,
+    pending: HashSet<String>,
+    passthrough: bool,
+    query: String,
+    /// Plugin keys whose matches stay out of [`MatchStore::persistable`].
+    private: HashSet<String>,
+    /// Whether the search went to a plugin not in `private`.
+    public: bool,
+    /// Matches sent before the client asks for more, 0 sends all of them.
+    page_size: usize,
+    /// Ranked matches the client asked for so far.
+    visible: usize,
+    /// Matches the client holds.
+    sent: HashSet<MatchId>,
+    /// Plugins serving pages themselves, with the matches received from them and how many
+    /// they had sent when last asked for more.
+    pages: HashMap<String, (usize, usize)>,
+    /// Matches whose detail was asked of their plugin ahead of the client.
+    prefetched: HashSet<MatchId>,
+    /// Plugin keys whose matches are held back, see [`MatchStore::expect_fallback`].
+    fallback: HashSet<String>,
+    /// Score of another match that keeps fallback matches hidden.
+    fallback_score: f64,
+}
+
+impl MatchStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn len(&self) -> usize {
+        self.slab.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slab.is_empty()
+    }
+
+    /// Drop all matches and start a new generation.
+    pub fn reset(&mut self, generation: usize) {
+        self.generation = generation;
+        self.slab.clear();
+        self.pending.clear();
+        self.passthrough = false;
+        self.query.clear();
+        self.private.clear();
+        self.public = false;
+        self.visible = self.page_size;
+        self.sent.clear();
+        self.pages.clear();
+        self.prefetched.clear();
+        self.fallback.clear();
+    }
+
+    /// Page size of the searches that follow, 0 sends every match. Kept across resets.
+    pub fn set_page_size(&mut self, page_size: usize) {
+        self.page_size = page_size;
+        self.visible = page_size;
+    }
+
+    /// Score a match of another plugin needs to keep fallback matches hidden. Kept across
+    /// resets.
+    pub fn set_fallback_score(&mut self, score: f64) {
+        self.fallback_score = score;
+    }
+
+    /// The query of the current generation, as the user typed it.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn set_query(&mut self, query: &str) {
+        self.query = query.to_string();
+    }
+
+    /// Keep the arrival order in snapshots instead of ranking by score.
+    /// Used when a single plugin answers the search and already sends its results in order.
+    pub fn set_passthrough(&mut self, passthrough: bool) {
+        self.passthrough = passthrough;
+    }
+
+    /// Register a plugin the current search was dispatched to.
+    pub fn expect(&mut self, plugin_id: &str) {
+        self.pending.insert(plugin_id.to_string());
+        self.public = true;
+    }
+
+    /// Register a plugin whose matches must not be persisted, see [`MatchStore::persistable`].
+    pub fn expect_private(&mut self, plugin_id: &str) {
+        self.pending.insert(plugin_id.to_string());
+        self.private.insert(plugin_id.to_string());
+    }
+
+    /// Register a plugin serving pages itself, see [`MatchStore::more`].
+    pub fn expect_pages(&mut self, plugin_id: &str) {
+        self.pages.insert(plugin_id.to_string(), (0, 0));
+    }
+
+    /// Register a fallback plugin, whose matches are only shown once the search completed
+    /// without a good match of another plugin.
+    pub fn expect_fallback(&mut self, plugin_id: &str) {
+        self.fallback.insert(plugin_id.to_string());
+    }
+
+    /// Whether matches of fallback plugins are shown: the search completed and no other
+    /// match reached the fallback score.
+    fn shows_fallback(&self) -> bool {
+        self.pending.is_empty()
+            && !self.slab.iter().any(|holder| {
+                !self.fallback.contains(&holder.plugin_id)
+                    && holder.match_.score >= self.fallback_score
+            })
+    }
+
+    fn held_back(&self, holder: &MatchHolder, shows_fallback: bool) -> bool {
+        !shows_fallback && self.fallback.contains(&holder.plugin_id)
+    }
+
+    /// Mark a plugin as done with `generation`.
+    /// Returns true when this was the last pending plugin, i.e. the search has completed.
+    pub fn finish(&mut self, generation: usize, plugin_id: &str) -> bool {
+        if generation != self.generation {
+            return false;
+        }
+        self.pending.remove(plugin_id) && self.pending.is_empty()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Match ids of the current generation's page ordered by score, best first.
+    /// Matches with equal scores keep their arrival order, as do all matches in passthrough mode.
+    pub fn snapshot(&self) -> Vec<SnapshotItem> {
+        let shows_fallback = self.shows_fallback();
+        let mut items = self
+            .slab
+            .iter()
+            .enumerate()
+            .filter(|(_, holder)| !self.held_back(holder, shows_fallback))
+            .map(|(id, holder)| SnapshotItem {
+                id,
+                score: holder.match_.score,
+            })
+            .collect::<Vec<_>>();
+        if !self.passthrough {
+            items.sort_by(|a, b| b.score.total_cmp(&a.score));
+        }
+        if self.page_size > 0 {
+            items.truncate(self.visible);
+        }
+        items
+    }
+
+    /// Of freshly stamped matches, the ones to send the client now: while the search runs,
+    /// matches are sent until the page is full. Fallback matches wait for the page sent once
+    /// the search completes.
+    pub fn deliver(&mut self, stamped: Vec<Match>) -> Vec<Match> {
+        stamped
+            .into_iter()
+            .filter(|item| {
+                let Some(id) = item.id else {
+                    return false;
+                };
+                if self
+                    .slab
+                    .get(id)
+                    .is_some_and(|holder| self.fallback.contains(&holder.plugin_id))
+                {
+                    return false;
+                }
+                if self.page_size > 0 && self.sent.len() >= self.visible {
+                    return false;
+                }
+                self.sent.insert(id)
+            })
+            .collect()
+    }
+
+    /// Matches of the page the client lacks, to send before the snapshot. Matches sent while
+    /// the search ran that ranked out of the page are dropped by the client along with the
+    /// snapshot, a later page holding them sends them again.
+    pub fn fill_page(&mut self) -> Vec<Match> {
+        let page = self.snapshot();
+        let missing = page
+            .iter()
+            .filter(|item| !self.sent.contains(&item.id))
+            .map(|item| self.slab[item.id].match_.clone())
+            .collect();
+        self.sent = page.into_iter().map(|item| item.id).collect();
+        missing
+    }
+
+    /// Grow the page of `generation` to hold the ranked matches `offset..offset + limit`.
+    ///
+    /// Matches at hand fill the page first. When there are too few, plugins serving pages
+    /// themselves that sent matches since they were last asked are expected again; they are
+    /// returned along with the offset to ask them for. `None` if the generation is stale.
+    pub fn more(
+        &mut self,
+        generation: usize,
+        offset: usize,
+        limit: usize,
+    ) -> Option<Vec<(String, usize)>> {
+        if generation != self.generation {
+            return None;
+        }
+        if self.page_size == 0 {
+            return Some(vec![]);
+        }
+        self.visible = self.visible.max(offset.saturating_add(limit));
+        if self.slab.len() >= self.visible {
+            return Some(vec![]);
+        }
+        let mut ask = vec![];
+        for (plugin_id, (received, asked_at)) in self.pages.iter_mut() {
+            if *received > *asked_at {
+                *asked_at = *received;
+                ask.push((plugin_id.clone(), *received));
+            }
+        }
+        for (plugin_id, _) in &ask {
+            self.pending.insert(plugin_id.clone());
+        }
+        Some(ask)
+    }
+
+    /// Store a batch of plugin matches for `generation`.
+    /// Returns the matches stamped with their ids, or `None` if the generation is stale.
+    pub fn extend(
+        &mut self,
+        generation: usize,
+        plugin_id: &str,
+        items: &[Match],
+    ) -> Option<Vec<Match>> {
+        let features = items
+            .iter()
+            .map(|item| Features::from_score(item.score))
+            .collect::<Vec<_>>();
+        self.extend_ranked(generation, plugin_id, items, &features)
+    }
+
+    /// Like [`MatchStore::extend`], keeping the features each match was scored on so
+    /// activations can be learned from. `features` holds one entry per item.
+    pub fn extend_ranked(
+        &mut self,
+        generation: usize,
+        plugin_id: &str,
+        items: &[Match],
+        features: &[Features],
+    ) -> Option<Vec<Match>> {
+        if generation != self.generation {
+            return None;
+        }
+
+        if let Some((received, _)) = self.pages.get_mut(plugin_id) {
+            *received += items.len();
+        }
+        let mut stamped = Vec::with_capacity(items.len());
+        for (index, item) in items.iter().enumerate() {
+            let mut item = item.clone();
+            item.id = Some(self.slab.len());
+            stamped.push(item.clone());
+            let features = features
+                .get(index)
+                .copied()
+                .unwrap_or_else(|| Features::from_score(item.score));
+            self.slab.push(MatchHolder {
+                plugin_id: plugin_id.to_string(),
+                match_: item,
+                features,
+            });
+        }
+        Some(stamped)
+    }
+
+    pub fn get(
+        &self,
+        generation: usize,
+        match_id: MatchId,
+    ) -> Result<&MatchHolder, ActivationError> {
+        if generation != self.generation {
+            return Err(ActivationError::StaleGeneration {
+                requested: generation,
+                current: self.generation,
+            });
+        }
+        self.slab
+            .get(match_id)
+            .ok_or(ActivationError::UnknownMatch(match_id))
+    }
+
+    /// Look up the action the client wants to activate, along with the match that owns it.
+    pub fn action(
+        &self,
+        generation: usize,
+        match_id: MatchId,
+        action: usize,
+    ) -> Result<(&MatchHolder, &MatchAction), ActivationError> {
+        let holder = self.get(generation, match_id)?;
+        holder
+            .match_
+            .actions
+            .get(action)
+            .map(|match_action| (holder, match_action))
+            .ok_or(ActivationError::UnknownAction { match_id, action })
+    }
+
+    /// Ids of the matches at `rows` of the client's list, in rank order. None for another
+    /// generation.
+    pub fn rows(&self, generation: usize, rows: RangeInclusive<usize>) -> Vec<MatchId> {
+        if generation != self.generation {
+            return vec![];
+        }
+        let page = self.snapshot();
+        let (first, last) = rows.into_inner();
+        page.iter()
+            .skip(first)
+            .take(last.saturating_add(1).saturating_sub(first))
+            .map(|item| item.id)
+            .collect()
+    }
+
+    /// Matches of `match_ids` whose detail is still to be asked of their plugin, marked as
+    /// asked. Those of another generation, or that came with a detail, are left out.
+    pub fn prefetch(&mut self, generation: usize, match_ids: &[MatchId]) -> Vec<MatchId> {
+        if generation != self.generation {
+            return vec![];
+        }
+        match_ids
+            .iter()
+            .copied()
+            .filter(|match_id| {
+                self.slab
+                    .get(*match_id)
+                    .is_some_and(|holder| holder.match_.detail.is_none())
+            })
+            .filter(|match_id| self.prefetched.insert(*match_id))
+            .collect()
+    }
+
+    /// Keep the detail a plugin described a match with, for the client's `Details` requests.
+    /// Returns false if the match is gone with its generation.
+    pub fn set_detail(&mut self, generation: usize, match_id: MatchId, detail: Detail) -> bool {
+        if generation != self.generation {
+            return false;
+        }
+        let Some(holder) = self.slab.get_mut(match_id) else {
+            return false;
+        };
+        holder.match_.detail = Some(detail);
+        true
+    }
+
+    /// The page the client showed when `match_id` was activated: the features of its matches
+    /// in rank order and the position of the activated one. `None` if the match is not on it.
+    pub fn impression(
+        &self,
+        generation: usize,
+        match_id: MatchId,
+    ) -> Option<(usize, Vec<Features>)> {
+        if generation != self.generation {
+            return None;
+        }
+        let page = self.snapshot();
+        let chosen = page.iter().position(|item| item.id == match_id)?;
+        let shown = page
+            .iter()
+            .map(|item| self.slab[item.id].features)
+            .collect();
+        Some((chosen, shown))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &MatchHolder> {
+        self.slab.iter()
+    }
+
+    /// Matches of the current generation that may be written to disk, ranked like
+    /// [`MatchStore::snapshot`]. `None` when the search only went to private plugins, its
+    /// query may be as private as their matches.
+    pub fn persistable(&self) -> Option<Vec<MatchHolder>> {
+        if !self.public && !self.private.is_empty() {
+            return None;
+        }
+        let items = self
+            .snapshot()
+            .into_iter()
+            .map(|item| &self.slab[item.id])
+            .filter(|holder| !self.private.contains(&holder.plugin_id))
+            .cloned()
+            .collect();
+        Some(items)
+    }
+}
+
+impl Reclaim for MatchStore {
+    fn reclaim(&mut self) -> usize {
+        let before = self.slab.capacity() * size_of::<MatchHolder>();
+        self.slab.shrink_to_fit();
+        self.pending.shrink_to_fit();
+        self.sent.shrink_to_fit();
+        self.prefetched.shrink_to_fit();
+        self.fallback.shrink_to_fit();
+        before - self.slab.capacity() * size_of::<MatchHolder>()
+    }
+}
+
+/// Put the matches a plugin left without a category under `plugin_name`, so clients can
+/// group every match.
+pub fn categorize(items: &mut [Match], plugin_name: &str) {
+    for item in items.iter_mut().filter(|item| item.category.is_none()) {
+        item.category = Some(plugin_name.to_string());
+    }
+}
+
+/// Mark where `query` fuzzy matches the title and description of the matches a plugin left
+/// without highlights. Matches the query does not fuzzy match, like calculator answers, get
+/// none.
+pub fn highlight(items: &mut [Match], query: &str) {
+    let matcher = Matcher::new(query);
+    let ranges = |text: &str| {
+        matcher
+            .indices(text)
+            .map_or_else(Vec::new, |(_, indices)| ranges(&indices))
+    };
+    for item in items.iter_mut().filter(|item| item.highlights.is_none()) {
+        let highlights = Highlights {
+            title: ranges(&item.title),
+            description: ranges(&item.description),
+        };
+        if !highlights.title.is_empty() || !highlights.description.is_empty() {
+            item.highlights = Some(highlights);
+        }
+    }
+}
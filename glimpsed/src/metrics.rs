@@ -0,0 +1,274 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use glimpse_sdk::PluginMetrics;
+use serde::Deserialize;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use crate::stats::Outcome;
+
+/// Upper bounds of the latency histogram buckets, in milliseconds.
+pub const LATENCY_BUCKETS_MS: [u64; 10] = [5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000];
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct MetricsConfig {
+    /// Loopback address to serve the metrics on in the Prometheus text format, e.g.
+    /// `127.0.0.1:9464`. Not served unless set.
+    pub prometheus: Option<SocketAddr>,
+}
+
+/// Name suffix, help text and value of a counter exported to Prometheus.
+type Counter = (&'static str, &'static str, fn(&SearchCounters) -> u64);
+
+/// Counters of one plugin since the daemon started.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchCounters {
+    pub searches: u64,
+    pub results: u64,
+    pub errors: u64,
+    pub timeouts: u64,
+    pub restarts: u64,
+    /// Answered searches per latency bucket, the last one counting those over every bound.
+    pub buckets: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    pub latency_sum_ms: u64,
+    pub max_latency_ms: u64,
+}
+
+impl SearchCounters {
+    fn answered(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Bound of the bucket the `quantile` of answered searches falls in, the longest
+    /// latency seen for those over every bound.
+    fn quantile_ms(&self, quantile: f64) -> u64 {
+        let rank = (self.answered() as f64 * quantile).ceil() as u64;
+        let mut seen = 0;
+        for (bound, count) in LATENCY_BUCKETS_MS.iter().zip(&self.buckets) {
+            seen += count;
+            if seen >= rank && seen > 0 {
+                return (*bound).min(self.max_latency_ms);
+            }
+        }
+        self.max_latency_ms
+    }
+}
+
+/// Search metrics of every plugin by metadata id, kept in memory while the daemon runs.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    plugins: Mutex<BTreeMap<String, SearchCounters>>,
+}
+
+impl Metrics {
+    /// Count a search of `plugin_id` that ended with `outcome`.
+    pub fn search(&self, plugin_id: &str, outcome: Outcome) {
+        let elapsed = match outcome {
+            Outcome::Answered(elapsed) | Outcome::Failed(elapsed) => Some(elapsed),
+            Outcome::TimedOut => None,
+        };
+        tracing::debug!(
+            plugin = plugin_id,
+            elapsed_ms = elapsed.map(|elapsed| elapsed.as_millis() as u64),
+            failed = !matches!(outcome, Outcome::Answered(_)),
+            "search finished"
+        );
+        let mut plugins = self.plugins.lock().unwrap();
+        let counters = plugins.entry(plugin_id.to_string()).or_default();
+        counters.searches += 1;
+        match outcome {
+            Outcome::Failed(_) => counters.errors += 1,
+            Outcome::TimedOut => counters.timeouts += 1,
+            Outcome::Answered(_) => {}
+        }
+        if let Some(elapsed) = elapsed {
+            let elapsed = elapsed.as_millis() as u64;
+            let bucket = LATENCY_BUCKETS_MS
+                .iter()
+                .position(|bound| elapsed <= *bound)
+                .unwrap_or(LATENCY_BUCKETS_MS.len());
+            counters.buckets[bucket] += 1;
+            counters.latency_sum_ms += elapsed;
+            counters.max_latency_ms = counters.max_latency_ms.max(elapsed);
+        }
+    }
+
+    /// Count matches `plugin_id` sent for a search.
+    pub fn results(&self, plugin_id: &str, count: usize) {
+        let mut plugins = self.plugins.lock().unwrap();
+        plugins.entry(plugin_id.to_string()).or_default().results += count as u64;
+    }
+
+    /// Count a restart of `plugin_id` after it exited.
+    pub fn restarted(&self, plugin_id: &str) {
+        let mut plugins = self.plugins.lock().unwrap();
+        plugins.entry(plugin_id.to_string()).or_default().restarts += 1;
+    }
+
+    pub fn counters(&self, plugin_id: &str) -> Option<SearchCounters> {
+        self.plugins.lock().unwrap().get(plugin_id).cloned()
+    }
+
+    pub fn summary(&self) -> Vec<PluginMetrics> {
+        let plugins = self.plugins.lock().unwrap();
+        plugins
+            .iter()
+            .map(|(plugin_id, counters)| PluginMetrics {
+                plugin_id: plugin_id.clone(),
+                searches: counters.searches,
+                results: counters.results,
+                errors: counters.errors,
+                timeouts: counters.timeouts,
+                restarts: counters.restarts,
+                error_rate: match counters.searches {
+                    0 => 0.0,
+                    searches => (counters.errors + counters.timeouts) as f64 / searches as f64,
+                },
+                average_latency_ms: counters
+                    .latency_sum_ms
+                    .checked_div(counters.answered())
+                    .unwrap_or(0),
+                p95_latency_ms: counters.quantile_ms(0.95),
+                max_latency_ms: counters.max_latency_ms,
+            })
+            .collect()
+    }
+
+    /// The metrics in the Prometheus text exposition format.
+    pub fn prometheus(&self) -> String {
+        let plugins = self.plugins.lock().unwrap();
+        let mut text = String::new();
+        let counters: [Counter; 5] = [
+            ("searches", "Searches sent to the plugin.", |c| c.searches),
+            ("results", "Matches the plugin sent.", |c| c.results),
+            (
+                "errors",
+                "Searches the plugin answered with an error.",
+                |c| c.errors,
+            ),
+            (
+                "timeouts",
+                "Searches the plugin did not answer in time.",
+                |c| c.timeouts,
+            ),
+            (
+                "restarts",
+                "Times the plugin came back after exiting.",
+                |c| c.restarts,
+            ),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(text, "# HELP glimpse_plugin_{}_total {}", name, help);
+            let _ = writeln!(text, "# TYPE glimpse_plugin_{}_total counter", name);
+            for (plugin_id, counters) in plugins.iter() {
+                let _ = writeln!(
+                    text,
+                    "glimpse_plugin_{}_total{{plugin=\"{}\"}} {}",
+                    name,
+                    escape_label(plugin_id),
+                    value(counters)
+                );
+            }
+        }
+
+        let histogram = "glimpse_plugin_search_latency_seconds";
+        let _ = writeln!(
+            text,
+            "# HELP {} Time to the plugin's last match of a search.",
+            histogram
+        );
+        let _ = writeln!(text, "# TYPE {} histogram", histogram);
+        for (plugin_id, counters) in plugins.iter() {
+            let plugin = escape_label(plugin_id);
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS_MS.iter().zip(&counters.buckets) {
+                cumulative += count;
+                let _ = writeln!(
+                    text,
+                    "{}_bucket{{plugin=\"{}\",le=\"{}\"}} {}",
+                    histogram,
+                    plugin,
+                    seconds(*bound),
+                    cumulative
+                );
+            }
+            let answered = counters.answered();
+            let _ = writeln!(
+                text,
+                "{}_bucket{{plugin=\"{}\",le=\"+Inf\"}} {}",
+                histogram, plugin, answered
+            );
+            let _ = writeln!(
+                text,
+                "{}_sum{{plugin=\"{}\"}} {}",
+                histogram,
+                plugin,
+                seconds(counters.latency_sum_ms)
+            );
+            let _ = writeln!(
+                text,
+                "{}_count{{plugin=\"{}\"}} {}",
+                histogram, plugin, answered
+            );
+        }
+        text
+    }
+}
+
+fn seconds(millis: u64) -> f64 {
+    Duration::from_millis(millis).as_secs_f64()
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Listen for scrapes on `address`, which must be a loopback address.
+pub async fn bind(address: SocketAddr) -> std::io::Result<TcpListener> {
+    if !address.ip().is_loopback() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "metrics are only served on loopback addresses",
+        ));
+    }
+    TcpListener::bind(address).await
+}
+
+/// Answer every HTTP request on `listener` with the metrics, whatever its path.
+pub async fn serve_prometheus(listener: Arc<TcpListener>, metrics: Arc<Metrics>) {
+    loop {
+        let mut stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                tracing::warn!("failed to accept a metrics scrape: {}", e);
+                continue;
+            }
+        };
+        let body = metrics.prometheus();
+        tokio::spawn(async move {
+            // the request itself does not matter, only that it arrived
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            if let Err(e) = stream.write_all(response.as_bytes()).await {
+                tracing::debug!("failed to answer a metrics scrape: {}", e);
+            }
+        });
+    }
+}
@@ -0,0 +1,222 @@
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::Display,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use glimpse_sdk::{Message, MethodResult};
+use serde::Deserialize;
+use tokio::sync::Notify;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct OutboxConfig {
+    /// Messages queued for a client before the oldest search results are dropped.
+    pub capacity: usize,
+    /// Seconds the queue may stay full before the client is considered gone.
+    pub stall_secs: u64,
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 256,
+            stall_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutboxError {
+    Closed,
+}
+
+impl Display for OutboxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutboxError::Closed => write!(f, "client disconnected"),
+        }
+    }
+}
+impl Error for OutboxError {}
+
+#[derive(Default)]
+struct State {
+    queue: VecDeque<Message>,
+    generation: usize,
+    closed: bool,
+    full_since: Option<Instant>,
+    dropped: u64,
+}
+
+/// Bounded queue of messages waiting to be written to one client.
+///
+/// Pushing never blocks, so a client that stops reading cannot stall plugins or searches.
+/// Results of superseded searches are dropped instead of delivered late, subscription
+/// updates replace their queued predecessor, and a queue that stays full is closed.
+pub struct Outbox {
+    config: OutboxConfig,
+    state: Mutex<State>,
+    ready: Notify,
+    closed: Notify,
+}
+
+impl Outbox {
+    pub fn new(config: OutboxConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(State::default()),
+            ready: Notify::new(),
+            closed: Notify::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Messages dropped so far because they went stale or the queue overflowed.
+    pub fn dropped(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+
+    pub fn push(&self, message: Message) -> Result<(), OutboxError> {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return Err(OutboxError::Closed);
+        }
+
+        if let Some(id) = search_result_id(&message)
+            && state.generation != 0
+            && id != state.generation
+        {
+            state.dropped += 1;
+            return Ok(());
+        }
+
+        if let Some(key) = update_key(&message)
+            && let Some(queued) = state
+                .queue
+                .iter_mut()
+                .find(|queued| update_key(queued) == Some(key))
+        {
+            *queued = message;
+            return Ok(());
+        }
+
+        if state.queue.len() >= self.config.capacity {
+            let since = *state.full_since.get_or_insert_with(|| {
+                tracing::warn!("client is not reading, dropping old results");
+                Instant::now()
+            });
+            if since.elapsed() >= Duration::from_secs(self.config.stall_secs) {
+                tracing::warn!("client stalled for {:?}, disconnecting", since.elapsed());
+                drop(state);
+                self.close();
+                return Err(OutboxError::Closed);
+            }
+
+            let evicted = state
+                .queue
+                .iter()
+                .position(|queued| search_result_id(queued).is_some())
+                .unwrap_or(0);
+            state.queue.remove(evicted);
+            state.dropped += 1;
+        }
+
+        state.queue.push_back(message);
+        drop(state);
+        self.ready.notify_one();
+        Ok(())
+    }
+
+    /// A new search started: results of earlier searches are no longer worth sending.
+    pub fn supersede(&self, generation: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.generation = generation;
+
+        let before = state.queue.len();
+        state
+            .queue
+            .retain(|queued| search_result_id(queued).is_none_or(|id| id == generation));
+        let removed = before - state.queue.len();
+        state.dropped += removed as u64;
+        if removed > 0 {
+            tracing::debug!("dropped {} stale result batches", removed);
+        }
+    }
+
+    /// Next message to write, `None` once the outbox is closed.
+    pub async fn pop(&self) -> Option<Message> {
+        loop {
+            {
+                let mut state = self.state.lock().unwrap();
+                if state.closed {
+                    return None;
+                }
+                if let Some(message) = state.queue.pop_front() {
+                    if state.queue.len() < self.config.capacity {
+                        state.full_since = None;
+                    }
+                    return Some(message);
+                }
+            }
+            self.ready.notified().await;
+        }
+    }
+
+    /// Discard queued messages and reject new ones.
+    pub fn close(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        state.queue.clear();
+        drop(state);
+        self.ready.notify_one();
+        self.closed.notify_waiters();
+    }
+
+    /// Resolves once the outbox is closed.
+    pub async fn closed(&self) {
+        loop {
+            let notified = self.closed.notified();
+            if self.is_closed() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Request id of a search result batch or snapshot.
+fn search_result_id(message: &Message) -> Option<usize> {
+    match message {
+        Message::Response {
+            id,
+            result: Some(MethodResult::Matches { .. } | MethodResult::Snapshot { .. }),
+            ..
+        } => Some(*id),
+        _ => None,
+    }
+}
+
+fn update_key(message: &Message) -> Option<(usize, &str)> {
+    match message {
+        Message::Response {
+            id,
+            result: Some(MethodResult::Update { topic, .. }),
+            ..
+        } => Some((*id, topic.as_str())),
+        _ => None,
+    }
+}
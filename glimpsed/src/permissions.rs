@@ -0,0 +1,385 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    error::Error,
+    fmt::Display,
+    path::{Component, Path, PathBuf},
+};
+
+use glimpse_sdk::{Action, Capability, Metadata, Permission, RpcError};
+use serde_json::Value;
+
+/// The permission the daemon needs from a plugin to carry out `action`.
+///
+/// Launching installed applications and plugin callbacks need none, callbacks run inside the
+/// plugin. Opening anything but a local file counts as network access, whatever handles the
+/// scheme may reach out.
+pub fn required(action: &Action) -> Option<Permission> {
+    match action {
+        Action::Exec { .. } => Some(Permission::Exec),
+        Action::Clipboard { .. } | Action::ExpiringClipboard { .. } => Some(Permission::Clipboard),
+        Action::Open { uri } => match scheme(uri) {
+            Some(scheme) if !scheme.eq_ignore_ascii_case("file") => Some(Permission::Network),
+            _ => Some(Permission::HomeRead),
+        },
+        Action::TypeText { .. } => Some(Permission::Keyboard),
+        Action::Schedule { .. } => Some(Permission::Notify),
+        Action::System { .. } => Some(Permission::Power),
+        Action::Connection { .. } => Some(Permission::Devices),
+        Action::Launch { .. } | Action::Callback { .. } => None,
+        // checked step by step
+        Action::Sequence { .. } => None,
+    }
+}
+
+/// The local file an `Open` action opens, `None` for other addresses and files on other
+/// hosts. `..` is resolved without looking at the file system, symlinks are not followed.
+pub fn opened_path(uri: &str) -> Option<PathBuf> {
+    let path = match scheme(uri) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("file") => {
+            local_file(&uri[scheme.len() + 1..])?
+        }
+        Some(_) => return None,
+        None => uri.to_string(),
+    };
+    expand_home(&path).map(|path| normalize(&path))
+}
+
+/// The scheme of `uri`, `None` for plain paths. A single letter is a Windows drive.
+fn scheme(uri: &str) -> Option<&str> {
+    let (scheme, _) = uri.split_once(':')?;
+    let mut chars = scheme.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    (valid && scheme.len() > 1).then_some(scheme)
+}
+
+/// The decoded path of a `file:` URI from what follows the scheme, with however many
+/// slashes it starts.
+fn local_file(rest: &str) -> Option<String> {
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let path = match rest.strip_prefix("//") {
+        Some(authority) => {
+            let (host, path) = authority.split_at(authority.find('/').unwrap_or(authority.len()));
+            if !host.is_empty() && !host.eq_ignore_ascii_case("localhost") {
+                return None;
+            }
+            path
+        }
+        None => rest,
+    };
+    path.starts_with('/').then(|| percent_decode(path))
+}
+
+/// Whether `path` is within one of the directories in `roots`, as declared in
+/// `Metadata::paths`.
+pub fn is_within(path: &Path, roots: &[String]) -> bool {
+    roots
+        .iter()
+        .filter_map(|root| expand_home(root))
+        .any(|root| path.starts_with(normalize(&root)))
+}
+
+/// `metadata` with the directories of the `roots` setting in `config` among its declared
+/// paths, so plugins searching configurable roots may open what they find there. Roots resolve
+/// as those plugins resolve them, relative ones within the home directory, which plugins
+/// declaring no paths keep.
+pub fn with_configured_roots(metadata: &Metadata, config: Option<&Value>) -> Metadata {
+    let roots: Vec<String> = config
+        .and_then(|config| config.get("roots"))
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(|root| match root.strip_prefix('~') {
+            Some(rest) => format!("~/{}", rest.trim_start_matches('/')),
+            None if Path::new(root).is_absolute() => root.to_string(),
+            None => format!("~/{}", root),
+        })
+        .collect();
+    let mut metadata = metadata.clone();
+    if roots.is_empty() {
+        return metadata;
+    }
+    if metadata.paths.is_empty() {
+        metadata.paths.push("~".to_string());
+    }
+    metadata.paths.extend(roots);
+    metadata
+}
+
+fn expand_home(path: &str) -> Option<PathBuf> {
+    if path == "~" {
+        return dirs::home_dir();
+    }
+    if let Some(rest) = path.strip_prefix("~/") {
+        return dirs::home_dir().map(|home| home.join(rest));
+    }
+    let path = PathBuf::from(path);
+    path.is_absolute().then_some(path)
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = text
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// What the user sees when asked about a permission.
+pub fn describe(permission: Permission) -> &'static str {
+    match permission {
+        Permission::Clipboard => "copy to the clipboard",
+        Permission::Exec => "run commands",
+        Permission::Network => "open web addresses",
+        Permission::HomeRead => "open files",
+        Permission::Notify => "show notifications",
+        Permission::Keyboard => "type into other windows",
+        Permission::Power => "shut down, lock the screen or log out",
+        Permission::Devices => "connect devices and networks",
+        Permission::Secrets => "handle secrets",
+        Permission::Unknown => "do something unknown",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermissionError {
+    /// The plugin's manifest does not list the permission, the action is refused.
+    Undeclared {
+        plugin_id: String,
+        permission: Permission,
+    },
+    /// The plugin declares the sensitive permission but the user has not granted it yet.
+    NotGranted {
+        plugin_id: String,
+        permission: Permission,
+    },
+    /// The file is outside the directories the plugin declares.
+    OutsidePaths { plugin_id: String, path: PathBuf },
+}
+
+impl Display for PermissionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PermissionError::Undeclared {
+                plugin_id,
+                permission,
+            } => write!(
+                f,
+                "plugin {} may not {}, it does not declare the {:?} permission",
+                plugin_id,
+                describe(*permission),
+                permission
+            ),
+            PermissionError::NotGranted {
+                plugin_id,
+                permission,
+            } => write!(f, "plugin {} asks to {}", plugin_id, describe(*permission)),
+            PermissionError::OutsidePaths { plugin_id, path } => write!(
+                f,
+                "plugin {} may not open {}, it is outside the paths it declares",
+                plugin_id,
+                path.display()
+            ),
+        }
+    }
+}
+impl Error for PermissionError {}
+
+impl PermissionError {
+    /// The error clients receive. Missing grants carry what to ask the user about.
+    pub fn to_rpc(&self) -> RpcError {
+        match self {
+            PermissionError::Undeclared { .. } | PermissionError::OutsidePaths { .. } => {
+                RpcError::rejected(self.to_string())
+            }
+            PermissionError::NotGranted {
+                plugin_id,
+                permission,
+            } => RpcError {
+                data: Some(serde_json::json!({
+                    "plugin_id": plugin_id,
+                    "permission": permission,
+                })),
+                ..RpcError::new(RpcError::PERMISSION_REQUIRED, self.to_string())
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GrantsError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl Display for GrantsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GrantsError::Io(err) => write!(f, "io: {}", err),
+            GrantsError::Json(err) => write!(f, "json: {}", err),
+        }
+    }
+}
+impl Error for GrantsError {}
+
+/// Sensitive permissions the user granted, by plugin metadata id.
+#[derive(Debug, Default)]
+pub struct Grants {
+    /// Where grants are saved, `None` keeps them in memory.
+    path: Option<PathBuf>,
+    granted: HashMap<String, BTreeSet<Permission>>,
+}
+
+impl Grants {
+    pub fn path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("glimpse")
+            .join("permissions.json")
+    }
+
+    /// Grants saved at `path`. A missing or unreadable file grants nothing.
+    pub fn load(path: &Path) -> Self {
+        let granted = match std::fs::read(path) {
+            Ok(content) => serde_json::from_slice(&content)
+                .inspect_err(|e| tracing::warn!("invalid grants {}: {}", path.display(), e))
+                .unwrap_or_default(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(err) => {
+                tracing::warn!("failed to read grants {}: {}", path.display(), err);
+                HashMap::new()
+            }
+        };
+        Self {
+            path: Some(path.to_path_buf()),
+            granted,
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    pub fn is_granted(&self, plugin_id: &str, permission: Permission) -> bool {
+        self.granted
+            .get(plugin_id)
+            .is_some_and(|granted| granted.contains(&permission))
+    }
+
+    /// Remember that the user allowed `plugin_id` the permission, saving the grants.
+    pub fn grant(&mut self, plugin_id: &str, permission: Permission) -> Result<(), GrantsError> {
+        if !self
+            .granted
+            .entry(plugin_id.to_string())
+            .or_default()
+            .insert(permission)
+        {
+            return Ok(());
+        }
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(GrantsError::Io)?;
+        }
+        let content = serde_json::to_vec_pretty(&self.granted).map_err(GrantsError::Json)?;
+        std::fs::write(path, content).map_err(GrantsError::Io)
+    }
+
+    /// Whether the plugin may carry out `action`, every step of a sequence. Plugins predating
+    /// permission manifests declare nothing.
+    pub fn check(&self, metadata: &Metadata, action: &Action) -> Result<(), PermissionError> {
+        if let Action::Sequence { actions } = action {
+            return actions
+                .iter()
+                .try_for_each(|action| self.check(metadata, action));
+        }
+        let Some(permission) = required(action) else {
+            return Ok(());
+        };
+        if !metadata.supports(Capability::Permissions)
+            || !metadata.permissions.contains(&permission)
+        {
+            return Err(PermissionError::Undeclared {
+                plugin_id: metadata.id.clone(),
+                permission,
+            });
+        }
+        if permission == Permission::HomeRead
+            && let Action::Open { uri } = action
+        {
+            // the home directory unless the plugin declares others
+            let home = ["~".to_string()];
+            let roots = match metadata.paths.is_empty() {
+                true => &home[..],
+                false => &metadata.paths[..],
+            };
+            match opened_path(uri) {
+                Some(path) if is_within(&path, roots) => {}
+                path => {
+                    return Err(PermissionError::OutsidePaths {
+                        plugin_id: metadata.id.clone(),
+                        path: path.unwrap_or_else(|| PathBuf::from(uri)),
+                    });
+                }
+            }
+        }
+        if permission.is_sensitive() && !self.is_granted(&metadata.id, permission) {
+            return Err(PermissionError::NotGranted {
+                plugin_id: metadata.id.clone(),
+                permission,
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether the plugin `plugin_id` may carry out `action`. Without metadata it declares
+    /// nothing.
+    pub fn check_plugin(
+        &self,
+        plugin_id: &str,
+        metadata: Option<&Metadata>,
+        action: &Action,
+    ) -> Result<(), PermissionError> {
+        match metadata {
+            Some(metadata) => self.check(metadata, action),
+            None => self.check(
+                &Metadata {
+                    id: plugin_id.to_string(),
+                    ..Default::default()
+                },
+                action,
+            ),
+        }
+    }
+}
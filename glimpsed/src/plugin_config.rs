@@ -0,0 +1,95 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use glimpse_sdk::ConfigSchema;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+#[derive(Debug)]
+pub enum PluginConfigError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    Invalid(Vec<String>),
+}
+
+impl Display for PluginConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginConfigError::Io(err) => write!(f, "io: {}", err),
+            PluginConfigError::Toml(err) => write!(f, "toml: {}", err),
+            PluginConfigError::Invalid(errors) => write!(f, "invalid: {}", errors.join("; ")),
+        }
+    }
+}
+impl Error for PluginConfigError {}
+
+/// Directory with one `<plugin-id>.toml` per plugin.
+pub fn config_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("glimpse")
+        .join("plugins")
+}
+
+pub fn config_path(dir: &Path, plugin_id: &str) -> PathBuf {
+    dir.join(format!("{}.toml", plugin_id))
+}
+
+/// Read and validate the settings of a plugin.
+///
+/// Returns `None` when there is nothing to deliver: no file and no schema to take defaults from.
+pub fn load(
+    dir: &Path,
+    plugin_id: &str,
+    schema: Option<&ConfigSchema>,
+) -> Result<Option<Value>, PluginConfigError> {
+    let config = match std::fs::read_to_string(config_path(dir, plugin_id)) {
+        Ok(content) => {
+            let table: toml::Table = toml::from_str(&content).map_err(PluginConfigError::Toml)?;
+            serde_json::to_value(table)
+                .map_err(|e| PluginConfigError::Invalid(vec![e.to_string()]))?
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            if schema.is_none() {
+                return Ok(None);
+            }
+            Value::Object(Default::default())
+        }
+        Err(err) => return Err(PluginConfigError::Io(err)),
+    };
+
+    match schema {
+        Some(schema) => schema
+            .validate(&config)
+            .map(Some)
+            .map_err(PluginConfigError::Invalid),
+        None => Ok(Some(config)),
+    }
+}
+
+/// Report ids of plugins whose settings file was written, created or removed.
+pub fn watch(dir: &Path, tx: mpsc::UnboundedSender<String>) -> notify::Result<RecommendedWatcher> {
+    std::fs::create_dir_all(dir).map_err(notify::Error::io)?;
+
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        for path in event.paths {
+            if path.extension().is_some_and(|ext| ext == "toml")
+                && let Some(plugin_id) = path.file_stem().and_then(|stem| stem.to_str())
+            {
+                let _ = tx.send(plugin_id.to_string());
+            }
+        }
+    })?;
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    Ok(watcher)
+}
@@ -1,20 +1,35 @@
 use std::env;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 
-use glimpse_sdk::Message;
+use glimpse_sdk::{
+    CompressionStats, Frame, LineRead, LogRecord, MAX_MESSAGE_BYTES, Message, PROTOCOL_VERSION,
+    read_line_bounded, unix_millis,
+};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, stderr as sys_stderr};
 use tokio::sync::Mutex;
 use tokio::sync::mpsc;
 use tokio::time;
 
+use crate::handshake;
+use crate::logs::{LogStore, log_name};
+use crate::sandbox::Sandbox;
+use crate::trust;
+
 pub enum PluginResponse {
     Response(String, Message),
 }
 
-pub fn discover_plugins() -> Vec<String> {
+/// How long a stopped plugin gets to exit on its own before it is killed.
+const STOP_GRACE: Duration = Duration::from_secs(2);
+
+/// Directories searched for plugin executables, in order.
+pub fn plugin_dirs() -> Vec<PathBuf> {
     let directories = vec![
         env::var("GLIMPSE_PLUGIN_DIR").unwrap_or_default(),
         dirs::data_dir()
@@ -28,25 +43,34 @@ pub fn discover_plugins() -> Vec<String> {
         "/usr/lib/glimpsed/plugins".to_owned(),
         "/usr/local/lib/glimpsed/plugins".to_owned(),
     ];
+    directories
+        .into_iter()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+pub fn discover_plugins() -> Vec<String> {
+    discover_plugins_in(&plugin_dirs())
+}
+
+/// Plugin executables in `directories`, by directory and then by path.
+pub fn discover_plugins_in(directories: &[PathBuf]) -> Vec<String> {
     tracing::debug!("plugin directories: {:?}", directories);
 
     let mut plugins = Vec::new();
     for dir in directories {
-        if !std::path::Path::new(&dir).exists() {
+        if !dir.exists() {
             continue;
         }
 
-        if dir.is_empty() {
-            continue;
-        }
-
-        let entries = std::fs::read_dir(&dir);
+        let entries = std::fs::read_dir(dir);
         if let Err(err) = entries {
-            tracing::warn!("failed to read plugin directory {}: {}", dir, err);
+            tracing::warn!("failed to read plugin directory {}: {}", dir.display(), err);
             continue;
         }
-        let entries = entries.unwrap();
-        for entry in entries.into_iter() {
+        let mut found = Vec::new();
+        for entry in entries.unwrap() {
             if let Err(err) = entry {
                 tracing::warn!("failed to read plugin entry: {}", err);
                 continue;
@@ -54,60 +78,114 @@ pub fn discover_plugins() -> Vec<String> {
             let entry = entry.unwrap();
 
             let path = entry.path();
-            if !path.is_file() {
+            if !is_plugin(&path) {
                 continue;
             }
 
-            #[cfg(unix)]
-            {
-                let metadata = match path.metadata() {
-                    Ok(metadata) => metadata,
-                    Err(err) => {
-                        tracing::warn!("failed to read metadata for {}: {}", path.display(), err);
-                        continue;
-                    }
-                };
-                let permissions = metadata.permissions();
-                if permissions.mode() & 0o111 == 0 {
-                    continue;
-                }
-            }
+            found.push(path.to_string_lossy().to_string());
+        }
+        // directory order is arbitrary, plugins start in the same order every time
+        found.sort();
+        plugins.extend(found);
+    }
 
-            #[cfg(windows)]
-            {
-                // On Windows, check if it's a .exe or .dll file
-                if let Some(ext) = path.extension() {
-                    let ext = ext.to_string_lossy().to_lowercase();
-                    if ext != "exe" && ext != "dll" {
-                        continue;
-                    }
-                } else {
-                    continue;
-                }
+    plugins
+}
+
+fn is_plugin(path: &Path) -> bool {
+    if !path.is_file() {
+        return false;
+    }
+
+    #[cfg(unix)]
+    {
+        let metadata = match path.metadata() {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                tracing::warn!("failed to read metadata for {}: {}", path.display(), err);
+                return false;
             }
+        };
+        let permissions = metadata.permissions();
+        if permissions.mode() & 0o111 == 0 {
+            return false;
+        }
+    }
 
-            let path_str = path.to_string_lossy().to_string();
-            plugins.push(path_str);
+    #[cfg(windows)]
+    {
+        // Windows has no executable bit, what runs is told by the extension
+        let runnable = path.extension().is_some_and(|ext| {
+            let ext = ext.to_string_lossy().to_lowercase();
+            ext == "exe" || ext == "bat" || ext == "cmd"
+        });
+        if !runnable {
+            return false;
         }
     }
 
-    plugins
+    true
 }
 
+/// Signal `tx` whenever something changes in one of the existing plugin directories.
+///
+/// Directories created after startup are not picked up.
+pub fn watch_plugin_dirs(
+    directories: &[PathBuf],
+    tx: mpsc::UnboundedSender<()>,
+) -> notify::Result<RecommendedWatcher> {
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event
+            && !matches!(event.kind, EventKind::Access(_))
+        {
+            let _ = tx.send(());
+        }
+    })?;
+    for dir in directories.iter().filter(|dir| dir.is_dir()) {
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    }
+    Ok(watcher)
+}
+
+/// Run the plugin at `path`, restarting it when it exits, until the daemon drops the
+/// sending half of `plugin_rx`.
 pub async fn spawn_plugin(
     path: String,
     response_tx: mpsc::Sender<PluginResponse>,
     plugin_rx: mpsc::Receiver<Message>,
+    compression: Arc<CompressionStats>,
+    sandbox: Option<Sandbox>,
+    pinned: Option<String>,
+    logs: Arc<LogStore>,
 ) {
     let plugin_rx = Arc::new(Mutex::new(plugin_rx));
 
     loop {
         let path = path.clone();
-        let status = tokio::process::Command::new(&path)
+        let mut command = tokio::process::Command::new(&path);
+        command
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
-            .spawn();
+            .kill_on_drop(true);
+        if let Some(sandbox) = &sandbox
+            && let Err(e) = sandbox.apply(&mut command)
+        {
+            // never start a plugin with more than it was meant to get
+            tracing::error!("failed to sandbox plugin {:?}: {}", path, e);
+            return;
+        }
+        // the executable may have been replaced since it was trusted
+        if let Some(pinned) = &pinned
+            && trust::digest(Path::new(&path)).ok().as_ref() != Some(pinned)
+        {
+            tracing::error!(
+                "plugin {:?} changed since it was trusted, not starting it",
+                path
+            );
+            return;
+        }
+        let status = command.spawn();
         if let Err(e) = status {
             tracing::error!("failed to start plugin {:?}: {}", path, e);
             time::sleep(time::Duration::from_secs(5)).await;
@@ -144,33 +222,78 @@ pub async fn spawn_plugin(
         let response_tx = response_tx.clone();
 
         let plugin_id = path.clone();
+        let compression = compression.clone();
         let stdout_handle = tokio::spawn(async move {
             let mut line = String::new();
-            loop {
-                line.clear();
-                let bytes_read = reader.read_line(&mut line).await.unwrap();
-                if bytes_read == 0 {
-                    break;
+            'read: loop {
+                match read_line_bounded(&mut reader, &mut line, MAX_MESSAGE_BYTES).await {
+                    Ok(LineRead::Line) => {}
+                    Ok(LineRead::Eof) => break,
+                    Ok(LineRead::TooLong(bytes)) => {
+                        // the plugin's answer is lost, its request times out
+                        tracing::warn!(
+                            "plugin {:?} sent a message of {} bytes, over the {} byte limit, skipping it",
+                            plugin_id,
+                            bytes,
+                            MAX_MESSAGE_BYTES
+                        );
+                        continue;
+                    }
+                    Err(e) => {
+                        tracing::error!("failed to read from plugin {:?}: {}", plugin_id, e);
+                        break;
+                    }
                 }
 
-                let message: Message = match serde_json::from_str(&line) {
-                    Ok(msg) => msg,
+                let decompressed = match compression.decompress(&line) {
+                    Ok(decompressed) => decompressed,
                     Err(err) => {
-                        tracing::warn!("failed to parse plugin JSON: {}", err);
+                        tracing::warn!(
+                            "failed to decompress a message of {:?}: {}",
+                            plugin_id,
+                            err
+                        );
                         continue;
                     }
                 };
-                tracing::debug!("plugin response: {:?}", &message);
-                if let Err(e) = response_tx
-                    .send(PluginResponse::Response(plugin_id.clone(), message))
-                    .await
-                {
-                    tracing::error!("failed to send plugin response: {}", e);
-                    break;
+                let frame = match Frame::parse(&decompressed) {
+                    Ok(frame) => frame,
+                    Err(err) => {
+                        match handshake::mismatched_version(&line) {
+                            Some(version) => tracing::error!(
+                                "plugin {:?} speaks protocol version {}, the daemon speaks {}: {}",
+                                plugin_id,
+                                version,
+                                PROTOCOL_VERSION,
+                                err
+                            ),
+                            None => tracing::warn!("failed to parse plugin JSON: {}", err),
+                        }
+                        continue;
+                    }
+                };
+                for message in frame.into_messages() {
+                    tracing::debug!("plugin response: {:?}", &message);
+                    if let Err(e) = response_tx
+                        .send(PluginResponse::Response(plugin_id.clone(), message))
+                        .await
+                    {
+                        tracing::error!("failed to send plugin response: {}", e);
+                        break 'read;
+                    }
                 }
             }
         });
 
+        // plugins log JSON records, kept in their log file and echoed readable
+        let name = log_name(&path);
+        let mut log_file = match logs.enabled() {
+            true => logs
+                .open(&name)
+                .inspect_err(|e| tracing::warn!("not keeping logs of plugin {:?}: {}", path, e))
+                .ok(),
+            false => None,
+        };
         let stderr_handle = tokio::spawn(async move {
             let mut reader = BufReader::new(stderr);
             let mut line = String::new();
@@ -181,7 +304,15 @@ pub async fn spawn_plugin(
                     break;
                 }
 
-                let _ = sys_stderr().write_all(line.as_bytes()).await;
+                let record = LogRecord::parse_line(&line, unix_millis());
+                if let Some(file) = &mut log_file
+                    && let Err(e) = file.append(&record)
+                {
+                    tracing::warn!("failed to write the log of {}: {}", name, e);
+                    log_file = None;
+                }
+                let echoed = format!("{} {}\n", name, record);
+                let _ = sys_stderr().write_all(echoed.as_bytes()).await;
                 let _ = sys_stderr().flush().await;
             }
         });
@@ -195,20 +326,30 @@ pub async fn spawn_plugin(
                 tracing::debug!("plugin request: {:?}", &message);
                 if let Err(e) = writer.write_all(request.as_bytes()).await {
                     tracing::error!("failed to write to plugin stdin: {}", e);
-                    break;
+                    return false;
                 }
                 if let Err(e) = writer.write_all(b"\n").await {
                     tracing::error!("failed to write newline to plugin stdin: {}", e);
-                    break;
+                    return false;
                 }
                 if let Err(e) = writer.flush().await {
                     tracing::error!("failed to flush plugin stdin: {}", e);
-                    break;
+                    return false;
                 }
             }
+            // the daemon let go of the plugin, closing stdin asks it to exit
+            true
         });
         tokio::select! {
-            _ = stdin_handle => {},
+            stopped = stdin_handle => {
+                if matches!(stopped, Ok(true)) {
+                    if time::timeout(STOP_GRACE, process.wait()).await.is_err() {
+                        tracing::warn!("plugin {:?} did not exit in time, killing it", path);
+                    }
+                    tracing::info!("stopped plugin {:?}", path);
+                    return;
+                }
+            },
             _ = stdout_handle => {},
             _ = stderr_handle => {},
             status = process.wait() => {
@@ -0,0 +1,82 @@
+use std::{error::Error, fmt::Display};
+
+use glimpse_sdk::{Action, Match};
+use serde::Deserialize;
+
+/// Restrictions on what activating a match may do, for managed machines.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct PolicyConfig {
+    /// Refuse actions that run commands: `Exec` and plugin callbacks.
+    pub lockdown: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyError {
+    Lockdown { action: &'static str },
+}
+
+impl Display for PolicyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyError::Lockdown { action } => write!(
+                f,
+                "{} actions are disabled in lockdown mode, see lockdown under [policy]",
+                action
+            ),
+        }
+    }
+}
+impl Error for PolicyError {}
+
+/// Actions that end up running a command, directly or through a plugin.
+fn exec_class(action: &Action) -> Option<&'static str> {
+    match action {
+        Action::Exec { .. } => Some("exec"),
+        Action::Callback { .. } => Some("callback"),
+        Action::Launch { .. }
+        | Action::Open { .. }
+        | Action::Clipboard { .. }
+        | Action::ExpiringClipboard { .. }
+        | Action::TypeText { .. }
+        | Action::Schedule { .. }
+        | Action::System { .. }
+        | Action::Connection { .. } => None,
+        // checked step by step
+        Action::Sequence { .. } => None,
+    }
+}
+
+impl PolicyConfig {
+    /// Whether the action may be dispatched.
+    pub fn check(&self, action: &Action) -> Result<(), PolicyError> {
+        if let Action::Sequence { actions } = action {
+            return actions.iter().try_for_each(|action| self.check(action));
+        }
+        match exec_class(action) {
+            Some(action) if self.lockdown => Err(PolicyError::Lockdown { action }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Strip the actions the policy refuses, alternates included, so they are never shown.
+    /// Matches left without actions are dropped.
+    pub fn filter(&self, items: Vec<Match>) -> Vec<Match> {
+        if !self.lockdown {
+            return items;
+        }
+        items
+            .into_iter()
+            .filter_map(|mut item| {
+                item.actions
+                    .retain(|action| self.check(&action.action).is_ok());
+                for action in &mut item.actions {
+                    action
+                        .alternates
+                        .retain(|alternate| self.check(&alternate.action).is_ok());
+                }
+                (!item.actions.is_empty()).then_some(item)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::Duration,
+};
+
+use glimpse_sdk::PowerProfile;
+use serde::Deserialize;
+
+const UPOWER_SERVICE: &str = "org.freedesktop.UPower";
+const UPOWER_PATH: &str = "/org/freedesktop/UPower";
+const DISPLAY_DEVICE_PATH: &str = "/org/freedesktop/UPower/devices/DisplayDevice";
+const DEVICE_INTERFACE: &str = "org.freedesktop.UPower.Device";
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerMode {
+    /// Follow the battery: low power when discharging below the threshold.
+    #[default]
+    Auto,
+    Normal,
+    LowPower,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct PowerConfig {
+    pub mode: PowerMode,
+    /// Battery percentage below which `auto` switches to the low power profile.
+    pub battery_threshold: f64,
+    /// Seconds between battery readings.
+    pub poll_secs: u64,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            mode: PowerMode::Auto,
+            battery_threshold: 30.0,
+            poll_secs: 60,
+        }
+    }
+}
+
+impl PowerConfig {
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_secs.max(1))
+    }
+
+    /// Profile to use in `state`, `None` meaning the battery could not be read.
+    pub fn profile_for(&self, state: Option<PowerState>) -> PowerProfile {
+        match (self.mode, state) {
+            (PowerMode::Normal, _) => PowerProfile::Normal,
+            (PowerMode::LowPower, _) => PowerProfile::LowPower,
+            (PowerMode::Auto, Some(state))
+                if state.on_battery && state.percentage < self.battery_threshold =>
+            {
+                PowerProfile::LowPower
+            }
+            (PowerMode::Auto, _) => PowerProfile::Normal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerState {
+    pub on_battery: bool,
+    pub percentage: f64,
+}
+
+/// Current power state, readable while the daemon runs.
+#[derive(Default, Debug)]
+pub struct PowerStats {
+    low_power: AtomicBool,
+    on_battery: AtomicBool,
+    percentage: AtomicU64,
+    switches: AtomicU64,
+}
+
+impl PowerStats {
+    pub fn profile(&self) -> PowerProfile {
+        match self.low_power.load(Ordering::Relaxed) {
+            true => PowerProfile::LowPower,
+            false => PowerProfile::Normal,
+        }
+    }
+
+    pub fn on_battery(&self) -> bool {
+        self.on_battery.load(Ordering::Relaxed)
+    }
+
+    /// Battery charge in percent, 0 until the battery was read.
+    pub fn percentage(&self) -> f64 {
+        f64::from_bits(self.percentage.load(Ordering::Relaxed))
+    }
+
+    /// How many times the profile changed since startup.
+    pub fn switches(&self) -> u64 {
+        self.switches.load(Ordering::Relaxed)
+    }
+
+    pub fn record_state(&self, state: PowerState) {
+        self.on_battery.store(state.on_battery, Ordering::Relaxed);
+        self.percentage
+            .store(state.percentage.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Switch to `profile`, returns true if it differs from the current one.
+    pub fn switch(&self, profile: PowerProfile) -> bool {
+        let low_power = profile == PowerProfile::LowPower;
+        let changed = self.low_power.swap(low_power, Ordering::Relaxed) != low_power;
+        if changed {
+            self.switches.fetch_add(1, Ordering::Relaxed);
+        }
+        changed
+    }
+}
+
+/// Debounce for bursts of events, longer in the low power profile.
+pub fn debounce(profile: PowerProfile, normal: Duration) -> Duration {
+    match profile {
+        PowerProfile::Normal => normal,
+        PowerProfile::LowPower => normal * 4,
+    }
+}
+
+/// Reads the battery through upower on the system bus.
+pub struct Upower {
+    daemon: zbus::Proxy<'static>,
+    display_device: zbus::Proxy<'static>,
+}
+
+impl Upower {
+    pub async fn connect() -> zbus::Result<Self> {
+        let connection = zbus::Connection::system().await?;
+        let daemon =
+            zbus::Proxy::new(&connection, UPOWER_SERVICE, UPOWER_PATH, UPOWER_SERVICE).await?;
+        let display_device = zbus::Proxy::new(
+            &connection,
+            UPOWER_SERVICE,
+            DISPLAY_DEVICE_PATH,
+            DEVICE_INTERFACE,
+        )
+        .await?;
+        Ok(Self {
+            daemon,
+            display_device,
+        })
+    }
+
+    pub async fn state(&self) -> zbus::Result<PowerState> {
+        Ok(PowerState {
+            on_battery: self.daemon.get_property("OnBattery").await?,
+            percentage: self.display_device.get_property("Percentage").await?,
+        })
+    }
+}
@@ -0,0 +1,43 @@
+use glimpse_sdk::{Detail, Message, Method};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct PrefetchConfig {
+    /// Fetch details and render thumbnails of the matches clients show before they are asked
+    /// for.
+    pub enabled: bool,
+    /// Matches of a viewport hint prefetched, the first ones the client listed.
+    pub max_matches: usize,
+}
+
+impl Default for PrefetchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_matches: 12,
+        }
+    }
+}
+
+impl PrefetchConfig {
+    /// The matches of a viewport hint to prefetch, none when prefetching is off.
+    pub fn visible<'a>(&self, match_ids: &'a [usize]) -> &'a [usize] {
+        match self.enabled {
+            true => &match_ids[..match_ids.len().min(self.max_matches)],
+            false => &[],
+        }
+    }
+}
+
+/// The notification telling a client the detail of a match it shows arrived.
+pub fn detail_ready(generation: usize, match_id: usize, detail: Detail) -> Message {
+    Message::Notification {
+        method: Method::DetailReady {
+            generation,
+            match_id,
+            detail,
+        },
+        plugin_id: None,
+    }
+}
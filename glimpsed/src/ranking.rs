@@ -0,0 +1,394 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use glimpse_sdk::Match;
+use serde::{Deserialize, Serialize};
+
+use crate::history::boost_for;
+
+/// Ranking logs are moved aside to `<name>.1` once they grow past this size.
+pub const MAX_LOG_BYTES: u64 = 4 * 1024 * 1024;
+
+/// What a strategy knows about a match when scoring it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Features {
+    /// Score the plugin gave the match.
+    pub plugin_score: f64,
+    /// Frecency of the match's title in the usage history, 0 if it was never activated.
+    pub frecency: f64,
+}
+
+impl Features {
+    /// Features of a match the usage history knows nothing about.
+    pub fn from_score(plugin_score: f64) -> Self {
+        Self {
+            plugin_score,
+            frecency: 0.0,
+        }
+    }
+
+    /// Features of `items`, with the frecency of their titles taken from `frecency`.
+    pub fn of(items: &[Match], frecency: &HashMap<String, f64>) -> Vec<Self> {
+        items
+            .iter()
+            .map(|item| Self {
+                plugin_score: item.score,
+                frecency: frecency.get(&item.title).copied().unwrap_or(0.0),
+            })
+            .collect()
+    }
+}
+
+/// Orders the matches of a search, higher scores first.
+pub trait RankingStrategy: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn score(&self, features: &Features) -> f64;
+
+    /// The user activated `chosen` out of the page `shown`, which includes it. Strategies
+    /// that learn adjust to rank such matches higher.
+    fn learn(&mut self, _chosen: &Features, _shown: &[Features]) {}
+}
+
+/// Trusts the plugins, ignoring the usage history.
+pub struct PluginScore;
+
+impl RankingStrategy for PluginScore {
+    fn name(&self) -> &'static str {
+        "plugin_score"
+    }
+
+    fn score(&self, features: &Features) -> f64 {
+        features.plugin_score
+    }
+}
+
+/// Adds a boost for matches picked often and recently, `weight` scales it.
+pub struct WeightedFrecency {
+    pub weight: f64,
+}
+
+impl RankingStrategy for WeightedFrecency {
+    fn name(&self) -> &'static str {
+        "weighted_frecency"
+    }
+
+    fn score(&self, features: &Features) -> f64 {
+        features.plugin_score + self.weight * boost_for(features.frecency)
+    }
+}
+
+/// A weighted sum of the plugin score and the frecency boost, the weights adjusted on every
+/// activation the way a pairwise perceptron learns: matches shown above the chosen one pull
+/// the weights towards what sets the chosen one apart.
+pub struct LinearModel {
+    pub score_weight: f64,
+    pub frecency_weight: f64,
+    pub learning_rate: f64,
+}
+
+impl LinearModel {
+    fn inputs(features: &Features) -> [f64; 2] {
+        [features.plugin_score, boost_for(features.frecency)]
+    }
+}
+
+impl RankingStrategy for LinearModel {
+    fn name(&self) -> &'static str {
+        "linear"
+    }
+
+    fn score(&self, features: &Features) -> f64 {
+        let [score, boost] = Self::inputs(features);
+        self.score_weight * score + self.frecency_weight * boost
+    }
+
+    fn learn(&mut self, chosen: &Features, shown: &[Features]) {
+        let target = self.score(chosen);
+        let [chosen_score, chosen_boost] = Self::inputs(chosen);
+        let outranking = shown
+            .iter()
+            .filter(|other| *other != chosen && self.score(other) >= target)
+            .map(Self::inputs)
+            .collect::<Vec<_>>();
+        for [score, boost] in outranking {
+            self.score_weight += self.learning_rate * (chosen_score - score);
+            self.frecency_weight += self.learning_rate * (chosen_boost - boost);
+        }
+    }
+}
+
+/// A built-in strategy with its parameters, as named in the `[ranking.strategy]` section.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "name", rename_all = "snake_case")]
+pub enum Strategy {
+    PluginScore,
+    WeightedFrecency {
+        #[serde(default = "default_weight")]
+        weight: f64,
+    },
+    Linear {
+        #[serde(default = "default_weight")]
+        score_weight: f64,
+        #[serde(default = "default_weight")]
+        frecency_weight: f64,
+        #[serde(default = "default_learning_rate")]
+        learning_rate: f64,
+    },
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn default_learning_rate() -> f64 {
+    0.05
+}
+
+impl Default for Strategy {
+    fn default() -> Self {
+        Strategy::WeightedFrecency {
+            weight: default_weight(),
+        }
+    }
+}
+
+impl Strategy {
+    pub fn build(&self) -> Box<dyn RankingStrategy> {
+        match *self {
+            Strategy::PluginScore => Box::new(PluginScore),
+            Strategy::WeightedFrecency { weight } => Box::new(WeightedFrecency { weight }),
+            Strategy::Linear {
+                score_weight,
+                frecency_weight,
+                learning_rate,
+            } => Box::new(LinearModel {
+                score_weight,
+                frecency_weight,
+                learning_rate,
+            }),
+        }
+    }
+
+    /// Every built-in strategy with its default parameters, except this one which keeps its
+    /// own. Evaluations compare them.
+    pub fn builtins(&self) -> Vec<Strategy> {
+        [
+            Strategy::PluginScore,
+            Strategy::default(),
+            Strategy::Linear {
+                score_weight: default_weight(),
+                frecency_weight: default_weight(),
+                learning_rate: default_learning_rate(),
+            },
+        ]
+        .into_iter()
+        .map(|builtin| {
+            if std::mem::discriminant(&builtin) == std::mem::discriminant(self) {
+                self.clone()
+            } else {
+                builtin
+            }
+        })
+        .collect()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct RankingConfig {
+    pub strategy: Strategy,
+    /// Append every activation with the page it was picked from to the ranking log, which
+    /// `glimpsed eval-ranking` replays. Only scores are logged, no titles or queries.
+    pub log: bool,
+    /// Matches of fallback plugins, like web searches, are shown when no other match scores
+    /// at least this.
+    pub fallback_score: f64,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            strategy: Strategy::default(),
+            log: true,
+            fallback_score: 0.8,
+        }
+    }
+}
+
+/// An activation as the ranking log keeps it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RankingEvent {
+    /// Unix seconds.
+    pub at: u64,
+    /// Index of the activated match in `shown`.
+    pub chosen: usize,
+    /// The page the match was picked from, in the order the user saw it.
+    pub shown: Vec<Features>,
+}
+
+impl RankingEvent {
+    pub fn new(chosen: usize, shown: Vec<Features>, at: SystemTime) -> Self {
+        Self {
+            at: at
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            chosen,
+            shown,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RankingLogError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl Display for RankingLogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RankingLogError::Io(err) => write!(f, "io: {}", err),
+            RankingLogError::Json(err) => write!(f, "json: {}", err),
+        }
+    }
+}
+impl Error for RankingLogError {}
+
+/// Activations in JSON lines, oldest first.
+pub struct RankingLog {
+    path: PathBuf,
+}
+
+impl RankingLog {
+    pub fn path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("glimpse")
+            .join("ranking.log")
+    }
+
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    fn rotated(&self) -> PathBuf {
+        self.path.with_extension("log.1")
+    }
+
+    pub fn append(&self, event: &RankingEvent) -> Result<(), RankingLogError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(RankingLogError::Io)?;
+        }
+        let full = std::fs::metadata(&self.path).is_ok_and(|meta| meta.len() > MAX_LOG_BYTES);
+        if full {
+            std::fs::rename(&self.path, self.rotated()).map_err(RankingLogError::Io)?;
+        }
+        let mut line = serde_json::to_string(event).map_err(RankingLogError::Json)?;
+        line.push('\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(RankingLogError::Io)
+    }
+
+    /// Every logged activation, including those moved aside. Lines that do not parse are
+    /// skipped.
+    pub fn read(&self) -> Result<Vec<RankingEvent>, RankingLogError> {
+        let mut events = vec![];
+        for path in [self.rotated(), self.path.clone()] {
+            let file = match std::fs::File::open(&path) {
+                Ok(file) => file,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(RankingLogError::Io(err)),
+            };
+            for line in BufReader::new(file).lines() {
+                let line = line.map_err(RankingLogError::Io)?;
+                match serde_json::from_str(&line) {
+                    Ok(event) => events.push(event),
+                    Err(err) => tracing::debug!("skipping ranking log line: {}", err),
+                }
+            }
+        }
+        Ok(events)
+    }
+}
+
+/// How well a strategy ranks the matches users went on to activate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    pub strategy: &'static str,
+    pub events: usize,
+    /// Mean reciprocal rank of the activated match.
+    pub mrr: f64,
+    /// Share of activations ranked first, within the top 3 and within the top 5.
+    pub top_1: f64,
+    pub top_3: f64,
+    pub top_5: f64,
+}
+
+impl Display for Evaluation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:<20} events {:>6}  mrr {:.3}  top-1 {:.3}  top-3 {:.3}  top-5 {:.3}",
+            self.strategy, self.events, self.mrr, self.top_1, self.top_3, self.top_5
+        )
+    }
+}
+
+/// Replay `events` in order, ranking each page with `strategy` before letting it learn from
+/// the activation, so learning strategies are only measured on what they have not seen yet.
+pub fn evaluate(strategy: &mut dyn RankingStrategy, events: &[RankingEvent]) -> Evaluation {
+    let mut reciprocal_ranks = 0.0;
+    let mut hits = [0usize; 3];
+    let mut counted = 0;
+    for event in events {
+        let Some(chosen) = event.shown.get(event.chosen) else {
+            continue;
+        };
+        let target = strategy.score(chosen);
+        // ties go against the chosen match, like a stable sort placing it after earlier ones
+        let rank = event
+            .shown
+            .iter()
+            .enumerate()
+            .filter(|(index, other)| {
+                let score = strategy.score(other);
+                score > target || (score == target && *index < event.chosen)
+            })
+            .count();
+        reciprocal_ranks += 1.0 / (rank + 1) as f64;
+        for (hit, k) in hits.iter_mut().zip([1, 3, 5]) {
+            if rank < k {
+                *hit += 1;
+            }
+        }
+        counted += 1;
+        strategy.learn(chosen, &event.shown);
+    }
+
+    let share = |total: f64| match counted {
+        0 => 0.0,
+        _ => total / counted as f64,
+    };
+    Evaluation {
+        strategy: strategy.name(),
+        events: counted,
+        mrr: share(reciprocal_ranks),
+        top_1: share(hits[0] as f64),
+        top_3: share(hits[1] as f64),
+        top_5: share(hits[2] as f64),
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct RequestConfig {
+    /// Milliseconds a plugin gets to finish a search before it is left out of the results.
+    pub timeout_ms: u64,
+    /// Matches of a search clients are sent before asking for more with `More`.
+    pub page_size: usize,
+    /// Milliseconds typing has to pause before a search goes out to plugins, later searches
+    /// replacing it meanwhile. Four times longer in the low power profile, 0 searches at once.
+    pub debounce_ms: u64,
+}
+
+impl Default for RequestConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 3000,
+            page_size: 50,
+            debounce_ms: 50,
+        }
+    }
+}
+
+impl RequestConfig {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms)
+    }
+
+    /// The deadline plugins are sent with searches, a tenth short of the timeout so the partial
+    /// matches of a plugin running out of time still arrive before it is left out.
+    pub fn plugin_budget_ms(&self) -> u64 {
+        self.timeout_ms - self.timeout_ms / 10
+    }
+
+    pub fn debounce(&self) -> Duration {
+        Duration::from_millis(self.debounce_ms)
+    }
+
+    /// At least one match per page.
+    pub fn page_size(&self) -> usize {
+        self.page_size.max(1)
+    }
+}
+
+/// Plugins that still owe an answer, keyed by (request id, plugin id), with their deadlines
+/// and when they were asked.
+///
+/// A plugin's responses are accepted while it is tracked here. Once it finishes, the request is
+/// cancelled or the deadline passes, late responses are dropped.
+#[derive(Default)]
+pub struct RequestTracker {
+    pending: HashMap<(usize, String), (Instant, Instant)>,
+}
+
+impl RequestTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Expect an answer from `plugin_id` to request `id` before `deadline`.
+    pub fn track(&mut self, id: usize, plugin_id: &str, deadline: Instant) {
+        self.pending
+            .insert((id, plugin_id.to_string()), (deadline, Instant::now()));
+    }
+
+    pub fn is_pending(&self, id: usize, plugin_id: &str) -> bool {
+        self.pending.contains_key(&(id, plugin_id.to_string()))
+    }
+
+    /// The plugin answered, returns false if it was not tracked (anymore).
+    pub fn complete(&mut self, id: usize, plugin_id: &str) -> bool {
+        self.finish(id, plugin_id).is_some()
+    }
+
+    /// The plugin answered, returns how long it took, `None` if it was not tracked (anymore).
+    pub fn finish(&mut self, id: usize, plugin_id: &str) -> Option<Duration> {
+        self.pending
+            .remove(&(id, plugin_id.to_string()))
+            .map(|(_, asked)| asked.elapsed())
+    }
+
+    /// Stop waiting for every plugin working on request `id`, returns the plugins it was
+    /// still waiting for.
+    pub fn cancel(&mut self, id: usize) -> Vec<String> {
+        let mut plugins = vec![];
+        self.pending.retain(|(request, plugin), _| {
+            let keep = *request != id;
+            if !keep {
+                plugins.push(plugin.clone());
+            }
+            keep
+        });
+        plugins
+    }
+
+    /// Stop waiting for anything, e.g. when a new search supersedes the current one.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Stop waiting for a plugin that went away, returns the requests it left unanswered.
+    pub fn forget_plugin(&mut self, plugin_id: &str) -> Vec<usize> {
+        let mut requests = vec![];
+        self.pending.retain(|(request, plugin), _| {
+            let keep = plugin != plugin_id;
+            if !keep {
+                requests.push(*request);
+            }
+            keep
+        });
+        requests
+    }
+
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.pending.values().map(|(deadline, _)| *deadline).min()
+    }
+
+    /// Remove and return the (request id, plugin id) pairs whose deadline passed, oldest first.
+    pub fn expire(&mut self, now: Instant) -> Vec<(usize, String)> {
+        let mut expired = self
+            .pending
+            .iter()
+            .filter(|(_, (deadline, _))| *deadline <= now)
+            .map(|(key, (deadline, _))| (*deadline, key.clone()))
+            .collect::<Vec<_>>();
+        expired.sort();
+        expired
+            .into_iter()
+            .map(|(_, key)| {
+                self.pending.remove(&key);
+                key
+            })
+            .collect()
+    }
+}
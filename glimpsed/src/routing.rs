@@ -0,0 +1,92 @@
+use glimpse_sdk::Metadata;
+
+/// Where a search query is dispatched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Route {
+    /// Every plugin gets the query and their results are ranked together.
+    Broadcast,
+    /// The query starts with the prefix of exactly one plugin, which alone gets the rest of it.
+    Prefixed { plugin_id: String, query: String },
+    /// The query was escaped, plugins without a prefix get the unescaped text.
+    Literal { query: String },
+}
+
+/// Pick the route for `query`. Ambiguous prefixes fall back to a broadcast of the full query.
+///
+/// A plugin takes the query when it starts with the plugin's prefix, or with one of its
+/// trigger words followed by a space or nothing. The trigger word is stripped unless the
+/// plugin keeps its triggers.
+///
+/// A leading backslash or surrounding double quotes keep a query from being routed:
+/// `\=5` and `"=5"` both search general plugins for `=5`, and `\\x` searches for `\x`.
+pub fn route<'a>(query: &str, plugins: impl IntoIterator<Item = &'a Metadata>) -> Route {
+    if let Some(literal) = unescape(query) {
+        return Route::Literal {
+            query: literal.to_string(),
+        };
+    }
+
+    let mut found = None;
+    for metadata in plugins {
+        let rest = match strip_prefix(query, metadata) {
+            Some(rest) => rest,
+            None => match strip_trigger(query, metadata) {
+                Some(_) if metadata.keep_triggers => query,
+                Some(rest) => rest,
+                None => continue,
+            },
+        };
+        if found.is_some() {
+            return Route::Broadcast;
+        }
+        found = Some(Route::Prefixed {
+            plugin_id: metadata.id.clone(),
+            query: rest.trim_start().to_string(),
+        });
+    }
+    found.unwrap_or(Route::Broadcast)
+}
+
+/// Whether a plugin gets a query that was not routed to it by prefix, `literal` meaning the
+/// query was escaped. Plugins owning a prefix or trigger words skip escaped queries,
+/// prefix-only plugins skip every query not meant for them.
+pub fn takes_unrouted(metadata: Option<&Metadata>, literal: bool) -> bool {
+    let Some(metadata) = metadata else {
+        return true;
+    };
+    let routed = metadata
+        .prefix
+        .as_deref()
+        .is_some_and(|prefix| !prefix.is_empty())
+        || metadata.triggers.iter().any(|trigger| !trigger.is_empty());
+    !(routed && (literal || metadata.prefix_only))
+}
+
+fn strip_prefix<'q>(query: &'q str, metadata: &Metadata) -> Option<&'q str> {
+    let prefix = metadata
+        .prefix
+        .as_deref()
+        .filter(|prefix| !prefix.is_empty())?;
+    query.strip_prefix(prefix)
+}
+
+/// The rest of `query` after the first of the plugin's trigger words it starts with.
+fn strip_trigger<'q>(query: &'q str, metadata: &Metadata) -> Option<&'q str> {
+    metadata
+        .triggers
+        .iter()
+        .filter(|trigger| !trigger.is_empty())
+        .find_map(|trigger| {
+            let rest = query.strip_prefix(trigger.as_str())?;
+            (rest.is_empty() || rest.starts_with(char::is_whitespace)).then_some(rest)
+        })
+}
+
+fn unescape(query: &str) -> Option<&str> {
+    if let Some(rest) = query.strip_prefix('\\') {
+        return Some(rest);
+    }
+    query
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+}
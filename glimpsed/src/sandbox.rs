@@ -0,0 +1,290 @@
+#[cfg(target_os = "linux")]
+use std::collections::BTreeMap;
+#[cfg(unix)]
+use std::os::unix::fs::DirBuilderExt;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+#[cfg(unix)]
+use nix::sys::resource::{Resource, getrlimit, setrlimit};
+#[cfg(target_os = "linux")]
+use seccompiler::{BpfProgram, SeccompAction, SeccompFilter, TargetArch};
+use serde::Deserialize;
+
+/// Variables plugins need to find the session, the display and their own data.
+const DEFAULT_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "LANG",
+    "LANGUAGE",
+    "LC_*",
+    "TZ",
+    "TMPDIR",
+    "XDG_*",
+    "DISPLAY",
+    "WAYLAND_DISPLAY",
+    "DBUS_SESSION_BUS_ADDRESS",
+    "RUST_LOG",
+    "RUST_BACKTRACE",
+    "GLIMPSE_*",
+];
+
+/// Syscalls no plugin has a use for: debugging other processes, changing the system and
+/// escaping into new namespaces. They fail with `EPERM`.
+#[cfg(target_os = "linux")]
+const BLOCKED_SYSCALLS: &[i64] = &[
+    libc::SYS_ptrace,
+    libc::SYS_process_vm_readv,
+    libc::SYS_process_vm_writev,
+    libc::SYS_mount,
+    libc::SYS_umount2,
+    libc::SYS_pivot_root,
+    libc::SYS_chroot,
+    libc::SYS_unshare,
+    libc::SYS_setns,
+    libc::SYS_reboot,
+    libc::SYS_kexec_load,
+    libc::SYS_init_module,
+    libc::SYS_finit_module,
+    libc::SYS_delete_module,
+    libc::SYS_swapon,
+    libc::SYS_swapoff,
+    libc::SYS_acct,
+    libc::SYS_bpf,
+    libc::SYS_perf_event_open,
+    libc::SYS_userfaultfd,
+    libc::SYS_keyctl,
+    libc::SYS_add_key,
+    libc::SYS_request_key,
+];
+
+/// Resource limits of a plugin process, unset ones are inherited from the daemon. Unix only.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct Limits {
+    /// CPU seconds over the life of the process, it is killed past them.
+    pub cpu_secs: Option<u64>,
+    /// Heap and other private memory in megabytes, allocations fail past it.
+    pub memory_mb: Option<u64>,
+    pub open_files: Option<u64>,
+}
+
+impl Limits {
+    /// These limits, falling back to `defaults` for unset ones.
+    pub fn or(self, defaults: Limits) -> Limits {
+        Limits {
+            cpu_secs: self.cpu_secs.or(defaults.cpu_secs),
+            memory_mb: self.memory_mb.or(defaults.memory_mb),
+            open_files: self.open_files.or(defaults.open_files),
+        }
+    }
+
+    #[cfg(unix)]
+    fn rlimits(&self) -> Vec<(Resource, u64)> {
+        [
+            (Resource::RLIMIT_CPU, self.cpu_secs),
+            (
+                Resource::RLIMIT_DATA,
+                self.memory_mb.map(|mb| mb * 1024 * 1024),
+            ),
+            (Resource::RLIMIT_NOFILE, self.open_files),
+        ]
+        .into_iter()
+        .filter_map(|(resource, limit)| Some((resource, limit?)))
+        .collect()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SandboxConfig {
+    /// Start plugins in a scrubbed environment and a directory of their own, with limits.
+    pub enabled: bool,
+    /// Environment variables plugins get, a trailing `*` matches a prefix. Everything else,
+    /// tokens and keys the daemon was started with included, is dropped.
+    pub env: Vec<String>,
+    pub limits: Limits,
+    /// Block syscalls plugins have no use for, such as `ptrace` and `mount`. Linux only.
+    pub seccomp: bool,
+    /// Overrides by plugin executable name, e.g. `[sandbox.plugins.glimpse-plugin-run]`.
+    pub plugins: HashMap<String, PluginSandboxConfig>,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            env: DEFAULT_ENV.iter().map(|name| name.to_string()).collect(),
+            limits: Limits {
+                cpu_secs: None,
+                memory_mb: Some(2048),
+                open_files: Some(1024),
+            },
+            seccomp: false,
+            plugins: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct PluginSandboxConfig {
+    pub enabled: Option<bool>,
+    /// Variables the plugin gets in addition to those of `[sandbox]`.
+    pub env: Vec<String>,
+    pub limits: Limits,
+    pub seccomp: Option<bool>,
+}
+
+impl SandboxConfig {
+    /// How to run the plugin at `path`, `None` to run it as the daemon runs.
+    pub fn for_plugin(&self, path: &Path) -> Option<Sandbox> {
+        let name = path.file_name()?.to_string_lossy().to_string();
+        let plugin = self.plugins.get(&name).cloned().unwrap_or_default();
+        if !plugin.enabled.unwrap_or(self.enabled) {
+            return None;
+        }
+        Some(Sandbox {
+            env: self.env.iter().chain(&plugin.env).cloned().collect(),
+            limits: plugin.limits.or(self.limits),
+            seccomp: plugin.seccomp.unwrap_or(self.seccomp),
+            work_dir: Sandbox::data_dir().join(name),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum SandboxError {
+    Io(std::io::Error),
+    #[cfg(target_os = "linux")]
+    Seccomp(seccompiler::BackendError),
+}
+
+impl Display for SandboxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SandboxError::Io(err) => write!(f, "io: {}", err),
+            #[cfg(target_os = "linux")]
+            SandboxError::Seccomp(err) => write!(f, "seccomp: {}", err),
+        }
+    }
+}
+impl Error for SandboxError {}
+
+/// What a plugin process is confined to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sandbox {
+    pub env: Vec<String>,
+    pub limits: Limits,
+    pub seccomp: bool,
+    /// Working directory of the plugin, created on start.
+    pub work_dir: PathBuf,
+}
+
+impl Sandbox {
+    /// Directory with the working directories of plugins.
+    pub fn data_dir() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("glimpse")
+            .join("plugin-data")
+    }
+
+    /// Whether the variable `name` is passed to the plugin.
+    pub fn allows(&self, name: &str) -> bool {
+        self.env
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => name == pattern,
+            })
+    }
+
+    /// The variables of `vars` the plugin gets.
+    pub fn filter_env<I>(&self, vars: I) -> Vec<(String, String)>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        vars.into_iter()
+            .filter(|(name, _)| self.allows(name))
+            .collect()
+    }
+
+    /// Set `command` up to start the plugin confined.
+    pub fn apply(&self, command: &mut tokio::process::Command) -> Result<(), SandboxError> {
+        let mut work_dir = std::fs::DirBuilder::new();
+        work_dir.recursive(true);
+        #[cfg(unix)]
+        work_dir.mode(0o700);
+        work_dir.create(&self.work_dir).map_err(SandboxError::Io)?;
+        command
+            .env_clear()
+            .envs(self.filter_env(std::env::vars()))
+            .current_dir(&self.work_dir);
+        self.confine(command)
+    }
+
+    /// Limit the resources of the process and filter its syscalls once it forked.
+    #[cfg(unix)]
+    fn confine(&self, command: &mut tokio::process::Command) -> Result<(), SandboxError> {
+        // everything is prepared up front, between fork and exec nothing may allocate
+        let rlimits = self.limits.rlimits();
+        #[cfg(target_os = "linux")]
+        let filter = self.seccomp.then(seccomp_filter).transpose()?;
+        #[cfg(not(target_os = "linux"))]
+        if self.seccomp {
+            tracing::warn!("seccomp is only available on Linux, syscalls are not filtered");
+        }
+        // SAFETY: the closure only makes async-signal-safe syscalls on data owned by it.
+        unsafe {
+            command.pre_exec(move || {
+                for (resource, limit) in &rlimits {
+                    // limits can only be lowered
+                    let (_, hard) = getrlimit(*resource)?;
+                    let limit = (*limit).min(hard);
+                    setrlimit(*resource, limit, limit)?;
+                }
+                #[cfg(target_os = "linux")]
+                if let Some(filter) = &filter {
+                    seccompiler::apply_filter(filter).map_err(std::io::Error::other)?;
+                }
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+
+    /// Windows has neither rlimits nor seccomp, plugins only get the scrubbed environment.
+    #[cfg(not(unix))]
+    fn confine(&self, _command: &mut tokio::process::Command) -> Result<(), SandboxError> {
+        if self.seccomp || self.limits != Limits::default() {
+            tracing::debug!("resource limits and seccomp are not available on this platform");
+        }
+        Ok(())
+    }
+}
+
+/// A filter failing [`BLOCKED_SYSCALLS`] with `EPERM` and allowing everything else.
+#[cfg(target_os = "linux")]
+pub fn seccomp_filter() -> Result<BpfProgram, SandboxError> {
+    let arch = TargetArch::try_from(std::env::consts::ARCH).map_err(SandboxError::Seccomp)?;
+    let rules = BLOCKED_SYSCALLS
+        .iter()
+        .map(|syscall| (*syscall, vec![]))
+        .collect::<BTreeMap<_, _>>();
+    SeccompFilter::new(
+        rules,
+        SeccompAction::Allow,
+        SeccompAction::Errno(libc::EPERM as u32),
+        arch,
+    )
+    .and_then(BpfProgram::try_from)
+    .map_err(SandboxError::Seccomp)
+}
@@ -0,0 +1,219 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use futures::StreamExt;
+use serde::Deserialize;
+use zbus::{
+    Connection, Proxy,
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value},
+};
+
+const PORTAL_SERVICE: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const SHORTCUTS_INTERFACE: &str = "org.freedesktop.portal.GlobalShortcuts";
+const REQUEST_INTERFACE: &str = "org.freedesktop.portal.Request";
+
+/// Where the GUI answers `toggle` while it runs.
+const GUI_SERVICE: &str = "me.aresa.Glimpse";
+const GUI_PATH: &str = "/me/aresa/Glimpse";
+
+/// Id of the shortcut bound through the portal.
+pub const SHORTCUT_ID: &str = "toggle";
+
+static NEXT_TOKEN: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ShortcutConfig {
+    /// Bind a global shortcut through the XDG desktop portal that shows or hides the
+    /// launcher. It only works while the daemon runs, so it is best used with the daemon
+    /// started on login and `activation.idle_exit_secs = 0`.
+    pub enabled: bool,
+    /// Trigger suggested to the desktop, in the format of the XDG shortcuts spec. The
+    /// desktop may let the user pick another one.
+    pub trigger: String,
+    /// Launcher started when none is running, `$GLIMPSE_BIN` or `/usr/bin/glimpse` when unset.
+    pub launcher: Option<PathBuf>,
+}
+
+impl Default for ShortcutConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            trigger: "LOGO+space".to_string(),
+            launcher: None,
+        }
+    }
+}
+
+impl ShortcutConfig {
+    pub fn launcher(&self) -> PathBuf {
+        self.launcher.clone().unwrap_or_else(|| {
+            std::env::var_os("GLIMPSE_BIN")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("/usr/bin/glimpse"))
+        })
+    }
+}
+
+/// Object path of the portal request made by the connection `unique_name` with
+/// `handle_token`, known before the call so its response cannot be missed.
+pub fn request_path(unique_name: &str, handle_token: &str) -> String {
+    let sender = unique_name.trim_start_matches(':').replace('.', "_");
+    format!("{}/request/{}/{}", PORTAL_PATH, sender, handle_token)
+}
+
+fn next_token() -> String {
+    format!(
+        "glimpse_{}_{}",
+        std::process::id(),
+        NEXT_TOKEN.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Bind the shortcut and toggle the launcher whenever it is pressed, until the portal
+/// goes away. Desktops without the portal leave the shortcut to the compositor config.
+pub async fn serve(config: ShortcutConfig) {
+    let connection = match Connection::session().await {
+        Ok(connection) => connection,
+        Err(e) => {
+            tracing::warn!("no global shortcut, session bus unavailable: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = bind_and_listen(&connection, &config).await {
+        tracing::warn!("no global shortcut: {}", e);
+    }
+}
+
+async fn bind_and_listen(connection: &Connection, config: &ShortcutConfig) -> zbus::Result<()> {
+    let portal = Proxy::new(connection, PORTAL_SERVICE, PORTAL_PATH, SHORTCUTS_INTERFACE).await?;
+    // subscribed before binding, a press right after must not be lost
+    let mut activated = portal.receive_signal("Activated").await?;
+
+    let handle_token = next_token();
+    let session_token = next_token();
+    let options = HashMap::from([
+        ("handle_token", Value::from(handle_token.as_str())),
+        ("session_handle_token", Value::from(session_token.as_str())),
+    ]);
+    let results = request(
+        connection,
+        &portal,
+        "CreateSession",
+        &handle_token,
+        &(options,),
+    )
+    .await?;
+    let session = results
+        .get("session_handle")
+        .and_then(|handle| String::try_from(handle.clone()).ok())
+        .ok_or_else(|| zbus::Error::Failure("the portal gave no session handle".to_string()))?;
+    let session = ObjectPath::try_from(session)?;
+
+    let shortcut = HashMap::from([
+        ("description", Value::from("Show or hide the launcher")),
+        ("preferred_trigger", Value::from(config.trigger.as_str())),
+    ]);
+    let handle_token = next_token();
+    let options = HashMap::from([("handle_token", Value::from(handle_token.as_str()))]);
+    let shortcuts = vec![(SHORTCUT_ID, shortcut)];
+    request(
+        connection,
+        &portal,
+        "BindShortcuts",
+        &handle_token,
+        &(&session, shortcuts, "", options),
+    )
+    .await?;
+    tracing::info!("bound the global shortcut {}", config.trigger);
+
+    let launcher = config.launcher();
+    while let Some(message) = activated.next().await {
+        let (_session, shortcut_id, _timestamp, _options): (
+            OwnedObjectPath,
+            String,
+            u64,
+            HashMap<String, OwnedValue>,
+        ) = message.body().deserialize()?;
+        if shortcut_id == SHORTCUT_ID {
+            call_launcher(connection, &launcher, "toggle").await;
+        }
+    }
+    Ok(())
+}
+
+/// Call a portal method whose answer arrives as the `Response` of a request object, the
+/// one named by the `handle_token` passed in the call's options.
+async fn request<B>(
+    connection: &Connection,
+    portal: &Proxy<'_>,
+    method: &str,
+    handle_token: &str,
+    body: &B,
+) -> zbus::Result<HashMap<String, OwnedValue>>
+where
+    B: serde::Serialize + zbus::zvariant::DynamicType,
+{
+    let unique_name = connection
+        .unique_name()
+        .ok_or_else(|| zbus::Error::Failure("not connected to the session bus".to_string()))?;
+    let path = request_path(unique_name.as_str(), handle_token);
+    let request = Proxy::new(connection, PORTAL_SERVICE, path, REQUEST_INTERFACE).await?;
+    let mut responses = request.receive_signal("Response").await?;
+
+    let _handle: OwnedObjectPath = portal.call(method, body).await?;
+    let message = responses
+        .next()
+        .await
+        .ok_or_else(|| zbus::Error::Failure(format!("{} got no response", method)))?;
+    let (response, results): (u32, HashMap<String, OwnedValue>) = message.body().deserialize()?;
+    match response {
+        0 => Ok(results),
+        response => Err(zbus::Error::Failure(format!(
+            "the portal refused {} (response {})",
+            method, response
+        ))),
+    }
+}
+
+/// Ask the running GUI to show itself, or start it.
+pub async fn show_launcher(launcher: &Path) {
+    match Connection::session().await {
+        Ok(connection) => call_launcher(&connection, launcher, "activate").await,
+        Err(e) => tracing::warn!("not showing the launcher, no session bus: {}", e),
+    }
+}
+
+/// Call `method` of the running GUI, `toggle` or `activate`, or start it.
+async fn call_launcher(connection: &Connection, launcher: &Path, method: &str) {
+    let running = match zbus::fdo::DBusProxy::new(connection).await {
+        Ok(bus) => bus
+            .name_has_owner(GUI_SERVICE.try_into().expect("a valid bus name"))
+            .await
+            .unwrap_or(false),
+        Err(_) => false,
+    };
+    if running {
+        let called = connection
+            .call_method(Some(GUI_SERVICE), GUI_PATH, Some(GUI_SERVICE), method, &())
+            .await;
+        if let Err(e) = called {
+            tracing::warn!("failed to {} the launcher: {}", method, e);
+        }
+        return;
+    }
+
+    match tokio::process::Command::new(launcher).spawn() {
+        Ok(mut child) => {
+            // the launcher outlives the shortcut press, reap it once closed
+            tokio::spawn(async move {
+                let _ = child.wait().await;
+            });
+        }
+        Err(e) => tracing::warn!("failed to start the launcher {:?}: {}", launcher, e),
+    }
+}
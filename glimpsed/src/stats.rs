@@ -0,0 +1,177 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use glimpse_sdk::PluginStats;
+use serde::{Deserialize, Serialize};
+
+/// How often counters are written to disk while the daemon runs.
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+pub enum StatsError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl Display for StatsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatsError::Io(err) => write!(f, "io: {}", err),
+            StatsError::Json(err) => write!(f, "json: {}", err),
+        }
+    }
+}
+impl Error for StatsError {}
+
+/// How a plugin's part of a search ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The plugin sent its last match after the given time.
+    Answered(Duration),
+    /// The plugin answered with an error after the given time.
+    Failed(Duration),
+    /// The plugin missed the deadline and was left out.
+    TimedOut,
+}
+
+/// Cumulative counters of one plugin.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Counters {
+    pub searches: u64,
+    pub errors: u64,
+    pub timeouts: u64,
+    pub activations: u64,
+    /// Summed over the searches the plugin answered, with or without an error.
+    pub latency_ms: u64,
+}
+
+/// Search statistics of every plugin by metadata id, kept across restarts.
+#[derive(Debug, Default)]
+pub struct StatsStore {
+    /// Where the counters are saved, `None` keeps them in memory.
+    path: Option<PathBuf>,
+    plugins: BTreeMap<String, Counters>,
+    /// Counters changed since they were last saved.
+    dirty: bool,
+}
+
+impl StatsStore {
+    pub fn path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("glimpse")
+            .join("plugin_stats.json")
+    }
+
+    /// The counters saved at `path`. Missing or unreadable counters start from zero.
+    pub fn load(path: &Path) -> Self {
+        let plugins = match std::fs::read(path) {
+            Ok(content) => serde_json::from_slice(&content)
+                .inspect_err(|e| tracing::warn!("invalid plugin stats {}: {}", path.display(), e))
+                .unwrap_or_default(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => {
+                tracing::warn!("failed to read plugin stats {}: {}", path.display(), err);
+                BTreeMap::new()
+            }
+        };
+        Self {
+            path: Some(path.to_path_buf()),
+            plugins,
+            dirty: false,
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    pub fn counters(&self, plugin_id: &str) -> Option<&Counters> {
+        self.plugins.get(plugin_id)
+    }
+
+    /// Count a search of `plugin_id` that ended with `outcome`.
+    pub fn record(&mut self, plugin_id: &str, outcome: Outcome) {
+        let counters = self.plugins.entry(plugin_id.to_string()).or_default();
+        counters.searches += 1;
+        match outcome {
+            Outcome::Answered(elapsed) => counters.latency_ms += elapsed.as_millis() as u64,
+            Outcome::Failed(elapsed) => {
+                counters.errors += 1;
+                counters.latency_ms += elapsed.as_millis() as u64;
+            }
+            Outcome::TimedOut => counters.timeouts += 1,
+        }
+        self.dirty = true;
+    }
+
+    /// Count an activation of a match of `plugin_id`.
+    pub fn activated(&mut self, plugin_id: &str) {
+        self.plugins
+            .entry(plugin_id.to_string())
+            .or_default()
+            .activations += 1;
+        self.dirty = true;
+    }
+
+    /// Statistics of every plugin counted so far, most searched first.
+    pub fn summary(&self) -> Vec<PluginStats> {
+        let activations = self
+            .plugins
+            .values()
+            .map(|counters| counters.activations)
+            .sum::<u64>();
+        let mut summary = self
+            .plugins
+            .iter()
+            .map(|(plugin_id, counters)| {
+                let answered = counters.searches - counters.timeouts;
+                PluginStats {
+                    plugin_id: plugin_id.clone(),
+                    searches: counters.searches,
+                    average_latency_ms: counters.latency_ms.checked_div(answered).unwrap_or(0),
+                    activations: counters.activations,
+                    activation_share: ratio(counters.activations, activations),
+                    errors: counters.errors,
+                    timeouts: counters.timeouts,
+                    error_rate: ratio(counters.errors + counters.timeouts, counters.searches),
+                }
+            })
+            .collect::<Vec<_>>();
+        summary.sort_by_key(|stats| std::cmp::Reverse(stats.searches));
+        summary
+    }
+
+    /// Write the counters if they changed since the last save. The file is written next to
+    /// the old one and renamed over it, a crash while saving keeps the previous counters.
+    pub fn save(&mut self) -> Result<(), StatsError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(StatsError::Io)?;
+        }
+        let content = serde_json::to_vec(&self.plugins).map_err(StatsError::Json)?;
+        let partial = path.with_extension("json.partial");
+        std::fs::write(&partial, content).map_err(StatsError::Io)?;
+        std::fs::rename(&partial, path).map_err(StatsError::Io)?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+fn ratio(count: u64, total: u64) -> f64 {
+    match total {
+        0 => 0.0,
+        total => count as f64 / total as f64,
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+/// Client subscriptions to plugin topics.
+///
+/// Each (plugin, topic) pair is owned by the client request that subscribed to it;
+/// pushed updates are forwarded to the client under that request id.
+#[derive(Debug, Default)]
+pub struct SubscriptionRegistry {
+    routes: HashMap<(String, String), usize>,
+}
+
+impl SubscriptionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    /// Register a subscription, returning the request id it replaces.
+    pub fn subscribe(&mut self, plugin_key: &str, topic: &str, request_id: usize) -> Option<usize> {
+        self.routes
+            .insert((plugin_key.to_string(), topic.to_string()), request_id)
+    }
+
+    pub fn unsubscribe(&mut self, plugin_key: &str, topic: &str) -> Option<usize> {
+        self.routes
+            .remove(&(plugin_key.to_string(), topic.to_string()))
+    }
+
+    /// Client request id for an update pushed by a plugin.
+    pub fn route(&self, plugin_key: &str, topic: &str) -> Option<usize> {
+        self.routes
+            .get(&(plugin_key.to_string(), topic.to_string()))
+            .copied()
+    }
+
+    /// Drop the subscription created by `request_id`, e.g. after the plugin rejected it.
+    pub fn remove_request(&mut self, plugin_key: &str, request_id: usize) -> Option<String> {
+        let key = self
+            .routes
+            .iter()
+            .find(|((plugin, _), id)| plugin == plugin_key && **id == request_id)
+            .map(|(key, _)| key.clone())?;
+        self.routes.remove(&key);
+        Some(key.1)
+    }
+
+    /// Drop every subscription to a plugin that went away, returning the request ids to notify.
+    pub fn remove_plugin(&mut self, plugin_key: &str) -> Vec<usize> {
+        let mut removed = vec![];
+        self.routes.retain(|(plugin, _), id| {
+            let keep = plugin != plugin_key;
+            if !keep {
+                removed.push(*id);
+            }
+            keep
+        });
+        removed
+    }
+
+    /// Remove all subscriptions, returning (plugin, topic) pairs to unsubscribe from.
+    pub fn drain(&mut self) -> Vec<(String, String)> {
+        self.routes.drain().map(|(key, _)| key).collect()
+    }
+}
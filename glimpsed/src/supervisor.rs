@@ -0,0 +1,258 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use glimpse_sdk::panic_message;
+use serde::Deserialize;
+use tokio::{
+    task::{Id, JoinSet},
+    time::Instant,
+};
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SupervisorConfig {
+    /// Restarts of one task within `window_secs` before it is given up on.
+    pub max_restarts: usize,
+    pub window_secs: u64,
+    /// Milliseconds before the first restart, doubling with every further one in the window.
+    pub restart_delay_ms: u64,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            window_secs: 60,
+            restart_delay_ms: 100,
+        }
+    }
+}
+
+impl SupervisorConfig {
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs)
+    }
+
+    /// Delay before a restart, `restarts` being those already made in the window.
+    pub fn restart_delay(&self, restarts: usize) -> Duration {
+        Duration::from_millis(self.restart_delay_ms) * 2u32.pow(restarts.min(10) as u32)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Restart {
+    /// Restarted after a panic. Past its restart budget the daemon goes on without it.
+    OnPanic,
+    /// Restarted after a panic, but the daemon cannot work without it: the task ending, or
+    /// panicking past its restart budget, shuts the daemon down.
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskState {
+    Running,
+    /// Ended on its own, e.g. once its channel closed.
+    Finished,
+    /// Panicked past its restart budget.
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskStatus {
+    pub name: &'static str,
+    pub state: TaskState,
+    pub restarts: u64,
+}
+
+/// Health of the supervised tasks, readable while the daemon runs.
+#[derive(Default, Debug)]
+pub struct SupervisorStats {
+    tasks: std::sync::Mutex<Vec<TaskStatus>>,
+    panics: AtomicU64,
+}
+
+impl SupervisorStats {
+    pub fn tasks(&self) -> Vec<TaskStatus> {
+        self.tasks.lock().unwrap().clone()
+    }
+
+    pub fn panics(&self) -> u64 {
+        self.panics.load(Ordering::Relaxed)
+    }
+
+    pub fn restarts(&self) -> u64 {
+        self.tasks().iter().map(|task| task.restarts).sum()
+    }
+
+    /// Whether no task was given up on.
+    pub fn healthy(&self) -> bool {
+        self.tasks()
+            .iter()
+            .all(|task| task.state != TaskState::Failed)
+    }
+
+    fn update(&self, index: usize, update: impl FnOnce(&mut TaskStatus)) {
+        if let Some(task) = self.tasks.lock().unwrap().get_mut(index) {
+            update(task);
+        }
+    }
+}
+
+type Factory = Box<dyn Fn() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+struct Task {
+    name: &'static str,
+    restart: Restart,
+    factory: Factory,
+    /// When the task was restarted within the window.
+    restarted: Vec<Instant>,
+}
+
+/// Runs the daemon's background tasks, restarting those that panic and telling the daemon
+/// when one it cannot do without is gone.
+pub struct Supervisor {
+    config: SupervisorConfig,
+    tasks: Vec<Task>,
+    running: JoinSet<()>,
+    ids: HashMap<Id, usize>,
+    stats: Arc<SupervisorStats>,
+}
+
+impl Supervisor {
+    pub fn new(config: SupervisorConfig, stats: Arc<SupervisorStats>) -> Self {
+        stats.tasks.lock().unwrap().clear();
+        Self {
+            config,
+            tasks: vec![],
+            running: JoinSet::new(),
+            ids: HashMap::new(),
+            stats,
+        }
+    }
+
+    pub fn stats(&self) -> Arc<SupervisorStats> {
+        self.stats.clone()
+    }
+
+    /// Run the future `factory` makes, and a new one each time it panics. Tasks owning a
+    /// channel receiver share it behind a mutex so a restarted task picks it up again.
+    pub fn spawn<F, Fut>(&mut self, name: &'static str, restart: Restart, factory: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.tasks.push(Task {
+            name,
+            restart,
+            factory: Box::new(move || Box::pin(factory())),
+            restarted: vec![],
+        });
+        self.stats.tasks.lock().unwrap().push(TaskStatus {
+            name,
+            state: TaskState::Running,
+            restarts: 0,
+        });
+        self.start(self.tasks.len() - 1, Duration::ZERO);
+    }
+
+    fn start(&mut self, index: usize, delay: Duration) {
+        let task = (self.tasks[index].factory)();
+        let handle = self.running.spawn(async move {
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+            task.await
+        });
+        self.ids.insert(handle.id(), index);
+        self.stats
+            .update(index, |status| status.state = TaskState::Running);
+    }
+
+    /// Restart tasks as they panic until a critical one is gone, returning its name. Waits
+    /// forever without critical tasks. Cancel safe.
+    pub async fn watch(&mut self) -> &'static str {
+        loop {
+            let Some(joined) = self.running.join_next_with_id().await else {
+                return std::future::pending().await;
+            };
+            let (id, panic) = match joined {
+                Ok((id, ())) => (id, None),
+                // only `shutdown` cancels tasks
+                Err(err) if err.is_cancelled() => continue,
+                Err(err) => (err.id(), Some(panic_message(err.into_panic().as_ref()))),
+            };
+            let Some(index) = self.ids.remove(&id) else {
+                continue;
+            };
+            if let Some(name) = self.exited(index, panic, Instant::now()) {
+                return name;
+            }
+        }
+    }
+
+    /// Handle task `index` ending, returns its name if the daemon has to shut down.
+    fn exited(
+        &mut self,
+        index: usize,
+        panic: Option<String>,
+        now: Instant,
+    ) -> Option<&'static str> {
+        let window = self.config.window();
+        let task = &mut self.tasks[index];
+        let name = task.name;
+        let critical = task.restart == Restart::Critical;
+
+        let Some(panic) = panic else {
+            self.stats
+                .update(index, |status| status.state = TaskState::Finished);
+            if critical {
+                tracing::error!("task {} ended", name);
+                return Some(name);
+            }
+            tracing::debug!("task {} finished", name);
+            return None;
+        };
+
+        self.stats.panics.fetch_add(1, Ordering::Relaxed);
+        task.restarted
+            .retain(|at| now.saturating_duration_since(*at) < window);
+        if task.restarted.len() >= self.config.max_restarts {
+            tracing::error!(
+                "task {} panicked {} times within {:?}, giving up: {}",
+                name,
+                task.restarted.len() + 1,
+                window,
+                panic
+            );
+            self.stats
+                .update(index, |status| status.state = TaskState::Failed);
+            return critical.then_some(name);
+        }
+
+        let delay = self.config.restart_delay(task.restarted.len());
+        task.restarted.push(now);
+        tracing::error!(
+            "task {} panicked, restarting in {:?}: {}",
+            name,
+            delay,
+            panic
+        );
+        self.stats.update(index, |status| status.restarts += 1);
+        self.start(index, delay);
+        None
+    }
+
+    /// Stop every task.
+    pub async fn shutdown(&mut self) {
+        self.running.shutdown().await;
+        self.ids.clear();
+    }
+}
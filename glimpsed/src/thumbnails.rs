@@ -0,0 +1,292 @@
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use tokio::process::Command;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+/// Sizes of the shared cache, smallest first, as they fit a result row best.
+const SIZES: &[&str] = &["normal", "large", "x-large", "xx-large"];
+/// Edge of the thumbnails in `normal`, in pixels.
+const NORMAL_SIZE: &str = "128";
+/// Failures are recorded per application, other thumbnailers may still succeed.
+const FAIL_DIR: &str = "glimpse";
+/// Time a thumbnailer may take for one file before it is killed.
+pub const RENDER_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Image,
+    Video,
+}
+
+impl MediaKind {
+    /// Guess from the extension, reading file headers would slow searches down.
+    pub fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "tif" | "tiff" | "svg" | "avif"
+            | "heic" => Some(MediaKind::Image),
+            "mp4" | "mkv" | "webm" | "mov" | "avi" | "m4v" | "wmv" | "flv" | "mpg" | "mpeg" => {
+                Some(MediaKind::Video)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// `file://` URI of `path`, escaped the way the thumbnail spec hashes it.
+pub fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for &byte in path.as_os_str().as_encoded_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => uri.push(byte as char),
+            b'-' | b'.' | b'_' | b'~' | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+'
+            | b',' | b'=' | b':' | b'@' | b'/' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+/// Modification time of `path` in seconds, the value thumbnails are validated against.
+pub fn mtime(path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+/// The freedesktop thumbnail cache, shared with file managers.
+/// See <https://specifications.freedesktop.org/thumbnail-spec/latest/>.
+#[derive(Debug, Clone)]
+pub struct Thumbnails {
+    root: PathBuf,
+}
+
+impl Thumbnails {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// `$XDG_CACHE_HOME/thumbnails`.
+    pub fn user() -> Self {
+        Self::new(
+            dirs::cache_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("thumbnails"),
+        )
+    }
+
+    fn file_name(path: &Path) -> String {
+        format!("{:x}.png", md5::compute(file_uri(path)))
+    }
+
+    /// Where a new thumbnail of `path` goes.
+    pub fn target(&self, path: &Path) -> PathBuf {
+        self.root.join(SIZES[0]).join(Self::file_name(path))
+    }
+
+    fn fail_marker(&self, path: &Path) -> PathBuf {
+        self.root
+            .join("fail")
+            .join(FAIL_DIR)
+            .join(Self::file_name(path))
+    }
+
+    /// An up to date thumbnail of `path` modified at `mtime`, in any size.
+    pub fn lookup(&self, path: &Path, mtime: u64) -> Option<PathBuf> {
+        let name = Self::file_name(path);
+        SIZES
+            .iter()
+            .map(|size| self.root.join(size).join(&name))
+            .find(|thumbnail| {
+                std::fs::read(thumbnail)
+                    .ok()
+                    .and_then(|png| text_chunk(&png, "Thumb::MTime"))
+                    .and_then(|value| value.parse::<u64>().ok())
+                    == Some(mtime)
+            })
+    }
+
+    /// Whether generating a thumbnail of `path` failed before.
+    pub fn failed(&self, path: &Path, mtime: u64) -> bool {
+        std::fs::read(self.fail_marker(path))
+            .ok()
+            .and_then(|png| text_chunk(&png, "Thumb::MTime"))
+            .and_then(|value| value.parse::<u64>().ok())
+            == Some(mtime)
+    }
+
+    /// Render the thumbnail of `path` unless it is up to date or failed before. `None` for
+    /// those and for files no thumbnailer handles.
+    pub async fn render(&self, path: &Path) -> io::Result<Option<PathBuf>> {
+        let (Some(kind), Some(mtime)) = (MediaKind::of(path), mtime(path)) else {
+            return Ok(None);
+        };
+        if self.lookup(path, mtime).is_some() || self.failed(path, mtime) {
+            return Ok(None);
+        }
+        self.generate(path, kind).await.map(Some)
+    }
+
+    /// Render a thumbnail of `path` with the thumbnailer for its kind. A failure is remembered,
+    /// so the file is not tried again until it changes.
+    pub async fn generate(&self, path: &Path, kind: MediaKind) -> io::Result<PathBuf> {
+        let mtime = mtime(path).ok_or_else(|| io::Error::other("cannot read mtime"))?;
+        let target = self.target(path);
+        let parent = target.parent().unwrap();
+        std::fs::create_dir_all(parent)?;
+        set_mode(parent, 0o700)?;
+
+        let rendered = target.with_extension("tmp.png");
+        let mut command = match kind {
+            MediaKind::Image => {
+                let mut command = Command::new("gdk-pixbuf-thumbnailer");
+                command.args(["-s", NORMAL_SIZE]).arg(path).arg(&rendered);
+                command
+            }
+            MediaKind::Video => {
+                let mut command = Command::new("ffmpegthumbnailer");
+                command
+                    .args(["-s", NORMAL_SIZE, "-i"])
+                    .arg(path)
+                    .arg("-o")
+                    .arg(&rendered);
+                command
+            }
+        };
+        let status = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .status()
+            .await;
+
+        let uri = file_uri(path);
+        let png = match status {
+            Ok(status) if status.success() => std::fs::read(&rendered).ok(),
+            _ => None,
+        };
+        let _ = std::fs::remove_file(&rendered);
+        let Some(png) = png.and_then(|png| stamp(&png, &uri, mtime)) else {
+            self.mark_failed(path, &uri, mtime)?;
+            return Err(io::Error::other(format!(
+                "failed to thumbnail {}",
+                path.display()
+            )));
+        };
+        write_private(&target, &png)?;
+        Ok(target)
+    }
+
+    fn mark_failed(&self, path: &Path, uri: &str, mtime: u64) -> io::Result<()> {
+        let marker = self.fail_marker(path);
+        std::fs::create_dir_all(marker.parent().unwrap())?;
+        let png = stamp(&empty_png(), uri, mtime).unwrap();
+        write_private(&marker, &png)
+    }
+}
+
+/// Thumbnails may show private content, the spec asks for owner-only files.
+fn write_private(path: &Path, data: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, data)?;
+    set_mode(&tmp, 0o600)?;
+    std::fs::rename(tmp, path)
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+/// Files under the user's profile are private to them on Windows already.
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> io::Result<()> {
+    Ok(())
+}
+
+/// Value of the `key` text chunk of a PNG.
+pub fn text_chunk(png: &[u8], key: &str) -> Option<String> {
+    chunks(png)?
+        .filter(|(kind, _)| kind == b"tEXt")
+        .find_map(|(_, data)| {
+            let split = data.iter().position(|&byte| byte == 0)?;
+            (&data[..split] == key.as_bytes())
+                .then(|| String::from_utf8_lossy(&data[split + 1..]).to_string())
+        })
+}
+
+/// Add the `Thumb::URI` and `Thumb::MTime` chunks the spec requires after the header.
+pub fn stamp(png: &[u8], uri: &str, mtime: u64) -> Option<Vec<u8>> {
+    let header_end = PNG_SIGNATURE.len() + 8 + chunks(png)?.next()?.1.len() + 4;
+    let mut stamped = png[..header_end].to_vec();
+    for (key, value) in [
+        ("Thumb::URI", uri.to_string()),
+        ("Thumb::MTime", mtime.to_string()),
+    ] {
+        let mut data = key.as_bytes().to_vec();
+        data.push(0);
+        data.extend_from_slice(value.as_bytes());
+        push_chunk(&mut stamped, b"tEXt", &data);
+    }
+    stamped.extend_from_slice(&png[header_end..]);
+    Some(stamped)
+}
+
+fn chunks(png: &[u8]) -> Option<impl Iterator<Item = ([u8; 4], &[u8])>> {
+    let mut rest = png.strip_prefix(PNG_SIGNATURE)?;
+    Some(std::iter::from_fn(move || {
+        let len = u32::from_be_bytes(rest.get(..4)?.try_into().unwrap()) as usize;
+        let kind: [u8; 4] = rest.get(4..8)?.try_into().unwrap();
+        let data = rest.get(8..8 + len)?;
+        rest = rest.get(8 + len + 4..)?;
+        Some((kind, data))
+    }))
+}
+
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// A 1x1 transparent image, the body of failure markers.
+fn empty_png() -> Vec<u8> {
+    let mut png = PNG_SIGNATURE.to_vec();
+    // width, height, bit depth 8, RGBA, default compression, filter and interlacing
+    push_chunk(&mut png, b"IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 6, 0, 0, 0]);
+    // a zlib stream holding the filter byte and one transparent pixel
+    push_chunk(
+        &mut png,
+        b"IDAT",
+        &[
+            0x78, 0x9c, 0x63, 0x60, 0x00, 0x02, 0x00, 0x00, 0x05, 0x00, 0x01,
+        ],
+    );
+    push_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
@@ -0,0 +1,227 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use glimpse_sdk::unix_millis;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, Notify};
+
+use crate::shortcut;
+
+#[derive(Debug)]
+pub enum TimerError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl Display for TimerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimerError::Io(err) => write!(f, "io: {}", err),
+            TimerError::Json(err) => write!(f, "json: {}", err),
+        }
+    }
+}
+impl Error for TimerError {}
+
+/// What the user is told when a timer fires, from an `Action::Schedule`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Reminder {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Bring the launcher up along with the notification.
+    #[serde(default)]
+    pub open_launcher: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Timer {
+    pub id: u64,
+    /// When the timer fires, in unix milliseconds.
+    pub due_ms: u64,
+    #[serde(flatten)]
+    pub reminder: Reminder,
+}
+
+/// Timers waiting to fire, kept across restarts so a timer outlives the daemon that set it.
+#[derive(Debug, Default)]
+pub struct TimerStore {
+    /// Where the timers are saved, `None` keeps them in memory.
+    path: Option<PathBuf>,
+    /// Soonest first.
+    timers: Vec<Timer>,
+}
+
+impl TimerStore {
+    pub fn path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("glimpse")
+            .join("timers.json")
+    }
+
+    /// The timers saved at `path`. Missing or unreadable timers start empty.
+    pub fn load(path: &Path) -> Self {
+        let mut timers: Vec<Timer> = match std::fs::read(path) {
+            Ok(content) => serde_json::from_slice(&content)
+                .inspect_err(|e| tracing::warn!("invalid timers {}: {}", path.display(), e))
+                .unwrap_or_default(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(err) => {
+                tracing::warn!("failed to read timers {}: {}", path.display(), err);
+                vec![]
+            }
+        };
+        timers.sort_by_key(|timer| timer.due_ms);
+        Self {
+            path: Some(path.to_path_buf()),
+            timers,
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    pub fn timers(&self) -> &[Timer] {
+        &self.timers
+    }
+
+    /// Add a timer firing at `due_ms`, returning its id.
+    pub fn add(&mut self, due_ms: u64, reminder: Reminder) -> u64 {
+        let id = self.timers.iter().map(|timer| timer.id).max().unwrap_or(0) + 1;
+        let at = self.timers.partition_point(|timer| timer.due_ms <= due_ms);
+        self.timers.insert(
+            at,
+            Timer {
+                id,
+                due_ms,
+                reminder,
+            },
+        );
+        id
+    }
+
+    pub fn next_due(&self) -> Option<u64> {
+        self.timers.first().map(|timer| timer.due_ms)
+    }
+
+    /// Remove and return the timers due by `now_ms`, including those missed while the daemon
+    /// was not running.
+    pub fn take_due(&mut self, now_ms: u64) -> Vec<Timer> {
+        let due = self.timers.partition_point(|timer| timer.due_ms <= now_ms);
+        self.timers.drain(..due).collect()
+    }
+
+    pub fn save(&self) -> Result<(), TimerError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(TimerError::Io)?;
+        }
+        let content = serde_json::to_vec(&self.timers).map_err(TimerError::Json)?;
+        let partial = path.with_extension("json.partial");
+        std::fs::write(&partial, content).map_err(TimerError::Io)?;
+        std::fs::rename(&partial, path).map_err(TimerError::Io)?;
+        Ok(())
+    }
+}
+
+/// Schedules timers and fires them when due, for as long as the daemon runs.
+pub struct Timers {
+    store: Mutex<TimerStore>,
+    /// Wakes the firing loop when a timer was added, it may be due sooner.
+    changed: Notify,
+}
+
+impl Timers {
+    pub fn new(store: TimerStore) -> Self {
+        Self {
+            store: Mutex::new(store),
+            changed: Notify::new(),
+        }
+    }
+
+    /// Fire `reminder` after `after`, returning the timer's id.
+    pub async fn schedule(&self, after: Duration, reminder: Reminder) -> Result<u64, TimerError> {
+        let due_ms = unix_millis().saturating_add(after.as_millis() as u64);
+        let mut store = self.store.lock().await;
+        let id = store.add(due_ms, reminder);
+        store.save()?;
+        drop(store);
+        self.changed.notify_one();
+        Ok(id)
+    }
+
+    /// Hand timers to `fire` as they come due. Never returns.
+    pub async fn run<F, Fut>(self: Arc<Self>, fire: F)
+    where
+        F: Fn(Timer) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        loop {
+            let (due, next) = {
+                let mut store = self.store.lock().await;
+                let due = store.take_due(unix_millis());
+                if !due.is_empty()
+                    && let Err(e) = store.save()
+                {
+                    tracing::warn!("failed to save timers: {}", e);
+                }
+                (due, store.next_due())
+            };
+            for timer in due {
+                fire(timer).await;
+            }
+            let wait =
+                next.map(|due_ms| Duration::from_millis(due_ms.saturating_sub(unix_millis())));
+            tokio::select! {
+                _ = self.changed.notified() => {}
+                _ = tokio::time::sleep(wait.unwrap_or(Duration::MAX)), if wait.is_some() => {}
+            }
+        }
+    }
+}
+
+/// Through the notification daemon on the session bus.
+#[cfg(all(unix, not(target_os = "macos")))]
+async fn show(notification: notify_rust::Notification) -> Result<(), Box<dyn Error>> {
+    notification.show_async().await?;
+    Ok(())
+}
+
+/// Through the system's notification center, which only has a blocking API.
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+async fn show(notification: notify_rust::Notification) -> Result<(), Box<dyn Error>> {
+    tokio::task::spawn_blocking(move || notification.show().map(drop)).await??;
+    Ok(())
+}
+
+/// Show the desktop notification of `timer`, and the launcher if it asks for it.
+pub async fn fire(timer: Timer, launcher: PathBuf) {
+    tracing::info!("timer {} fired: {}", timer.id, timer.reminder.title);
+    let mut notification = notify_rust::Notification::new();
+    notification
+        .appname("Glimpse")
+        .summary(&timer.reminder.title)
+        .icon("alarm-symbolic");
+    if let Some(body) = &timer.reminder.body {
+        notification.body(body);
+    }
+    if let Err(e) = show(notification).await {
+        tracing::warn!(
+            "failed to show the notification of timer {}: {}",
+            timer.id,
+            e
+        );
+    }
+    if timer.reminder.open_launcher {
+        shortcut::show_launcher(&launcher).await;
+    }
+}
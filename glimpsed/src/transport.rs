@@ -0,0 +1,102 @@
+use std::{io::ErrorKind, path::Path};
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+#[cfg(unix)]
+use tokio::net::UnixListener;
+#[cfg(windows)]
+use tokio::net::windows::named_pipe::{NamedPipeServer, ServerOptions};
+
+pub type ClientReader = Box<dyn AsyncRead + Unpin + Send>;
+pub type ClientWriter = Box<dyn AsyncWrite + Unpin + Send>;
+
+/// Where the daemon accepts clients: a Unix socket, or a named pipe on Windows.
+#[derive(Debug)]
+pub struct ClientListener {
+    #[cfg(unix)]
+    listener: UnixListener,
+    #[cfg(windows)]
+    name: std::ffi::OsString,
+    /// The pipe instance the next client connects to, a named pipe serving one client.
+    #[cfg(windows)]
+    next: NamedPipeServer,
+}
+
+impl ClientListener {
+    /// The next client to connect, split into its reading and writing halves.
+    pub async fn accept(&mut self) -> std::io::Result<(ClientReader, ClientWriter)> {
+        #[cfg(unix)]
+        {
+            let (stream, _) = self.listener.accept().await?;
+            let (reader, writer) = stream.into_split();
+            Ok((Box::new(reader), Box::new(writer)))
+        }
+        #[cfg(windows)]
+        {
+            self.next.connect().await?;
+            let next = ServerOptions::new()
+                .reject_remote_clients(true)
+                .create(&self.name)?;
+            let connected = std::mem::replace(&mut self.next, next);
+            let (reader, writer) = tokio::io::split(connected);
+            Ok((Box::new(reader), Box::new(writer)))
+        }
+    }
+}
+
+#[cfg(unix)]
+impl From<UnixListener> for ClientListener {
+    fn from(listener: UnixListener) -> Self {
+        Self { listener }
+    }
+}
+
+/// Listen on `path`, replacing a socket left behind by a daemon that is gone.
+///
+/// Fails with `AddrInUse` if another daemon still accepts clients there.
+#[cfg(unix)]
+pub fn bind(path: &Path) -> std::io::Result<ClientListener> {
+    if let Some(dir) = path.parent() {
+        std::fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)?;
+    }
+    match std::os::unix::net::UnixStream::connect(path) {
+        Ok(_) => {
+            return Err(std::io::Error::new(
+                ErrorKind::AddrInUse,
+                format!("another daemon is listening on {}", path.display()),
+            ));
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => {}
+        Err(_) => std::fs::remove_file(path)?,
+    }
+
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(listener.into())
+}
+
+/// Listen on the named pipe `path`, such as `\\.\pipe\glimpse-alice`. Pipes vanish with the
+/// daemon that created them, so there is nothing stale to replace.
+///
+/// Fails with `AddrInUse` if another daemon still accepts clients there.
+#[cfg(windows)]
+pub fn bind(path: &Path) -> std::io::Result<ClientListener> {
+    let name = path.as_os_str().to_owned();
+    let next = ServerOptions::new()
+        .first_pipe_instance(true)
+        .reject_remote_clients(true)
+        .create(&name)
+        .map_err(|err| match err.kind() {
+            ErrorKind::PermissionDenied => std::io::Error::new(
+                ErrorKind::AddrInUse,
+                format!("another daemon is listening on {}", path.display()),
+            ),
+            _ => err,
+        })?;
+    Ok(ClientListener { name, next })
+}
@@ -0,0 +1,215 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::Display,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use glimpse_sdk::UntrustedPlugin;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct TrustConfig {
+    /// Only run plugin executables the user trusted, pinned by digest.
+    pub enabled: bool,
+    /// Directories whose plugins run without being trusted, those only root can write to.
+    pub trusted_dirs: Vec<PathBuf>,
+}
+
+impl Default for TrustConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            trusted_dirs: vec![
+                PathBuf::from("/usr/lib/glimpsed/plugins"),
+                PathBuf::from("/usr/local/lib/glimpsed/plugins"),
+            ],
+        }
+    }
+}
+
+impl TrustConfig {
+    /// Whether the plugin at `path` runs without being trusted.
+    pub fn is_exempt(&self, path: &Path) -> bool {
+        !self.enabled
+            || path
+                .parent()
+                .is_some_and(|dir| self.trusted_dirs.iter().any(|trusted| trusted == dir))
+    }
+}
+
+#[derive(Debug)]
+pub enum TrustError {
+    Io(std::io::Error),
+    Serialize(toml::ser::Error),
+    /// The executable is not the one the user was shown.
+    Changed {
+        path: String,
+    },
+    /// The daemon did not find and hold back a plugin at the path.
+    NotHeld {
+        path: String,
+    },
+}
+
+impl Display for TrustError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrustError::Io(err) => write!(f, "io: {}", err),
+            TrustError::Serialize(err) => write!(f, "toml: {}", err),
+            TrustError::Changed { path } => {
+                write!(f, "{} changed since it was reviewed, not trusting it", path)
+            }
+            TrustError::NotHeld { path } => write!(f, "no untrusted plugin at {}", path),
+        }
+    }
+}
+impl Error for TrustError {}
+
+/// SHA-256 digest of the file at `path`, hex encoded.
+pub fn digest(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// A plugin cleared to run, with the digest it was trusted with. Exempt plugins have none.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Admitted {
+    pub path: String,
+    pub sha256: Option<String>,
+}
+
+/// On disk: executable paths with their pinned digests.
+///
+/// ```toml
+/// [plugins]
+/// "/home/me/.local/share/glimpsed/plugins/glimpse-plugin-files" = "9f86d0..."
+/// ```
+#[derive(Serialize, Deserialize, Default)]
+struct TrustFile {
+    #[serde(default)]
+    plugins: BTreeMap<String, String>,
+}
+
+/// Plugin executables the user trusted, pinned by digest so a replaced binary is asked
+/// about again.
+#[derive(Debug, Default)]
+pub struct TrustStore {
+    config: TrustConfig,
+    /// Where the store is saved, `None` keeps it in memory.
+    path: Option<PathBuf>,
+    pinned: BTreeMap<String, String>,
+}
+
+impl TrustStore {
+    pub fn path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("glimpse")
+            .join("trusted-plugins.toml")
+    }
+
+    /// The store saved at `path`. A missing file trusts nothing, an unreadable one is
+    /// reported and trusts nothing either.
+    pub fn load(path: &Path, config: TrustConfig) -> Self {
+        let pinned = match std::fs::read_to_string(path) {
+            Ok(content) => toml::from_str::<TrustFile>(&content)
+                .map(|file| file.plugins)
+                .inspect_err(|e| tracing::warn!("invalid trust store {}: {}", path.display(), e))
+                .unwrap_or_default(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => {
+                tracing::warn!("failed to read trust store {}: {}", path.display(), err);
+                BTreeMap::new()
+            }
+        };
+        Self {
+            config,
+            path: Some(path.to_path_buf()),
+            pinned,
+        }
+    }
+
+    pub fn in_memory(config: TrustConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// The digest the plugin at `path` was trusted with.
+    pub fn pinned(&self, path: &str) -> Option<&str> {
+        self.pinned.get(path).map(String::as_str)
+    }
+
+    /// Whether the plugin at `path` may run: `Ok(None)` if so, what to ask the user about
+    /// otherwise.
+    pub fn check(&self, path: &str) -> std::io::Result<Option<UntrustedPlugin>> {
+        if self.config.is_exempt(Path::new(path)) {
+            return Ok(None);
+        }
+        let sha256 = digest(Path::new(path))?;
+        match self.pinned(path) {
+            Some(pinned) if pinned == sha256 => Ok(None),
+            pinned => Ok(Some(UntrustedPlugin {
+                path: path.to_string(),
+                sha256,
+                changed: pinned.is_some(),
+            })),
+        }
+    }
+
+    /// Split discovered plugins into those cleared to run and those held back until the
+    /// user trusts them. Executables that cannot be read are left out.
+    pub fn admit(&self, paths: Vec<String>) -> (Vec<Admitted>, Vec<UntrustedPlugin>) {
+        let mut admitted = vec![];
+        let mut held = vec![];
+        for path in paths {
+            match self.check(&path) {
+                Ok(None) => admitted.push(Admitted {
+                    sha256: self
+                        .pinned(&path)
+                        .filter(|_| !self.config.is_exempt(Path::new(&path)))
+                        .map(str::to_string),
+                    path,
+                }),
+                Ok(Some(untrusted)) => held.push(untrusted),
+                Err(e) => tracing::warn!("cannot read plugin {}: {}", path, e),
+            }
+        }
+        (admitted, held)
+    }
+
+    /// Trust the executable at `path` if its digest is still `sha256`, saving the store.
+    pub fn trust(&mut self, path: &str, sha256: &str) -> Result<(), TrustError> {
+        if digest(Path::new(path)).map_err(TrustError::Io)? != sha256 {
+            return Err(TrustError::Changed {
+                path: path.to_string(),
+            });
+        }
+        self.pinned.insert(path.to_string(), sha256.to_string());
+        let Some(store) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = store.parent() {
+            std::fs::create_dir_all(parent).map_err(TrustError::Io)?;
+        }
+        let file = TrustFile {
+            plugins: self.pinned.clone(),
+        };
+        let content = toml::to_string(&file).map_err(TrustError::Serialize)?;
+        std::fs::write(store, content).map_err(TrustError::Io)
+    }
+}
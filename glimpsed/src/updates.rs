@@ -0,0 +1,189 @@
+use std::{collections::HashMap, error::Error, fmt::Display, time::Duration};
+
+use glimpse_sdk::{Action, AvailableUpdate, Icon, Match, MatchAction, Metadata};
+use semver::Version;
+use serde::Deserialize;
+
+/// Component name the daemon is listed under in the manifest and in results.
+pub const DAEMON_COMPONENT: &str = "glimpsed";
+
+/// Plugin key of the rows the daemon adds to search results itself.
+pub const PROVIDER_KEY: &str = "glimpsed/updates";
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct UpdateConfig {
+    /// Update checks are off unless enabled and given a manifest URL.
+    pub enabled: bool,
+    pub manifest_url: Option<String>,
+    pub interval_hours: u64,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            manifest_url: None,
+            interval_hours: 24,
+        }
+    }
+}
+
+impl UpdateConfig {
+    /// The manifest to poll, `None` when checks are disabled.
+    pub fn manifest_url(&self) -> Option<&str> {
+        self.manifest_url
+            .as_deref()
+            .filter(|url| self.enabled && !url.is_empty())
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_hours.max(1) * 60 * 60)
+    }
+}
+
+#[derive(Debug)]
+pub enum UpdateError {
+    Disabled,
+    Http(String),
+    Manifest(serde_json::Error),
+}
+
+impl Display for UpdateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpdateError::Disabled => write!(
+                f,
+                "update checks are disabled, set enabled and manifest_url under [updates]"
+            ),
+            UpdateError::Http(err) => write!(f, "http: {}", err),
+            UpdateError::Manifest(err) => write!(f, "invalid manifest: {}", err),
+        }
+    }
+}
+impl Error for UpdateError {}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Release {
+    pub version: String,
+    pub url: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// Latest releases, as published at the manifest URL:
+///
+/// ```json
+/// {"daemon": {"version": "0.2.0", "url": "https://..."},
+///  "plugins": {"files": {"version": "0.3.1"}}}
+/// ```
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct ReleaseManifest {
+    pub daemon: Option<Release>,
+    pub plugins: HashMap<String, Release>,
+}
+
+impl ReleaseManifest {
+    pub fn from_json(content: &str) -> Result<Self, UpdateError> {
+        serde_json::from_str(content).map_err(UpdateError::Manifest)
+    }
+
+    /// Releases newer than what is installed. Plugins missing from the manifest and
+    /// versions that are not semver are skipped.
+    pub fn available_updates(
+        &self,
+        daemon_version: &str,
+        plugins: &[Metadata],
+    ) -> Vec<AvailableUpdate> {
+        let installed = std::iter::once((DAEMON_COMPONENT, daemon_version, self.daemon.as_ref()))
+            .chain(plugins.iter().map(|metadata| {
+                (
+                    metadata.id.as_str(),
+                    metadata.version.as_str(),
+                    self.plugins.get(&metadata.id),
+                )
+            }));
+
+        installed
+            .filter_map(|(component, version, release)| {
+                let release = release?;
+                if !is_newer(version, &release.version) {
+                    return None;
+                }
+                Some(AvailableUpdate {
+                    component: component.to_string(),
+                    installed: version.to_string(),
+                    latest: release.version.clone(),
+                    url: release.url.clone(),
+                    notes: release.notes.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+fn is_newer(installed: &str, latest: &str) -> bool {
+    match (Version::parse(installed), Version::parse(latest)) {
+        (Ok(installed), Ok(latest)) => latest > installed,
+        _ => {
+            tracing::debug!("cannot compare versions {} and {}", installed, latest);
+            false
+        }
+    }
+}
+
+pub async fn fetch_manifest(url: &str) -> Result<ReleaseManifest, UpdateError> {
+    let url = url.to_string();
+    let content = tokio::task::spawn_blocking(move || {
+        let config = ureq::Agent::config_builder()
+            .timeout_global(Some(FETCH_TIMEOUT))
+            .build();
+        let agent = ureq::Agent::new_with_config(config);
+        agent
+            .get(&url)
+            .call()
+            .and_then(|mut response| response.body_mut().read_to_string())
+            .map_err(|err| UpdateError::Http(err.to_string()))
+    })
+    .await
+    .map_err(|err| UpdateError::Http(err.to_string()))??;
+    ReleaseManifest::from_json(&content)
+}
+
+/// Whether a search is asking about updates, e.g. `upd` or `updates`.
+pub fn is_update_query(query: &str) -> bool {
+    let query = query.trim().to_lowercase();
+    query.len() >= 3 && ("updates".starts_with(&query) || query.starts_with("update"))
+}
+
+/// Result rows for the available updates. Activating one opens the release page.
+pub fn update_matches(updates: &[AvailableUpdate]) -> Vec<Match> {
+    updates
+        .iter()
+        .map(|update| Match {
+            title: format!("Update {} to {}", update.component, update.latest),
+            description: match &update.notes {
+                Some(notes) => format!("installed {}, {}", update.installed, notes),
+                None => format!("installed {}", update.installed),
+            },
+            icon: Some(Icon::freedesktop("software-update-available")),
+            actions: update
+                .url
+                .iter()
+                .map(|url| MatchAction {
+                    title: "Open release page".to_string(),
+                    action: Action::Open { uri: url.clone() },
+                    close_on_action: true,
+                    alternates: vec![],
+                    requires_confirmation: false,
+                    confirmation_prompt: None,
+                    expand: vec![],
+                })
+                .collect(),
+            score: 1.0,
+            ..Default::default()
+        })
+        .collect()
+}
@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+use glimpsed::{activation::passed_fds, config::DaemonConfig};
+
+#[test]
+fn test_passed_fds_are_for_this_process_only() {
+    assert_eq!(passed_fds(42, Some("42"), Some("1")), 1);
+    assert_eq!(passed_fds(42, Some("42"), Some("2")), 2);
+    // the parent was activated, the descriptors are not ours
+    assert_eq!(passed_fds(42, Some("41"), Some("1")), 0);
+    assert_eq!(passed_fds(42, None, Some("1")), 0);
+    assert_eq!(passed_fds(42, Some("42"), None), 0);
+    assert_eq!(passed_fds(42, Some("42"), Some("x")), 0);
+}
+
+#[test]
+fn test_idle_exit_is_configurable() {
+    assert_eq!(
+        DaemonConfig::default().activation.idle_exit(),
+        Some(Duration::from_secs(600))
+    );
+    let config = DaemonConfig::from_toml("[activation]\nidle_exit_secs = 0\n").unwrap();
+    assert_eq!(config.activation.idle_exit(), None);
+}
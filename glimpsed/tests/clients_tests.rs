@@ -0,0 +1,247 @@
+use glimpse_sdk::{Match, Message, MethodResult};
+use glimpsed::{
+    clients::{RequestIds, Sessions},
+    outbox::OutboxConfig,
+    transport::bind,
+};
+
+fn update(id: usize, topic: &str) -> Message {
+    Message::Response {
+        id,
+        error: None,
+        result: Some(MethodResult::Update {
+            topic: topic.to_string(),
+            items: vec![Match {
+                title: "Playing".to_string(),
+                ..Default::default()
+            }],
+        }),
+        plugin_id: Some("media".to_string()),
+    }
+}
+
+fn response_id(message: Option<Message>) -> Option<usize> {
+    match message? {
+        Message::Response { id, .. } => Some(id),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_request_ids_are_unique_across_clients() {
+    let mut ids = RequestIds::new();
+    let first = ids.assign(1, 1);
+    let second = ids.assign(2, 1);
+
+    assert_ne!(first, second);
+    assert_ne!(first, 0);
+    assert_eq!(ids.resolve(first), Some((1, 1)));
+    assert_eq!(ids.resolve(second), Some((2, 1)));
+    assert_eq!(ids.find(2, 1), Some(second));
+
+    assert_eq!(ids.release(first), Some((1, 1)));
+    assert_eq!(ids.resolve(first), None);
+    assert_eq!(ids.release_client(2), vec![second]);
+    assert!(ids.is_empty());
+}
+
+#[test]
+fn test_new_search_supersedes_previous() {
+    let mut sessions = Sessions::new();
+    let (client, _) = sessions.open(OutboxConfig::default());
+
+    let (first, previous) = sessions.start_search(client, 1).unwrap();
+    assert_eq!(previous, None);
+    let (second, previous) = sessions.start_search(client, 2).unwrap();
+    assert_eq!(previous, Some(first));
+
+    assert!(sessions.route(first).is_none());
+    assert_eq!(sessions.route(second).map(|(id, _)| id), Some(2));
+
+    assert_eq!(sessions.end_search(client), Some(second));
+    assert!(sessions.route(second).is_none());
+    assert_eq!(sessions.end_search(client), None);
+    assert!(sessions.start_search(client + 1, 1).is_none());
+}
+
+#[test]
+fn test_searches_of_clients_do_not_collide() {
+    let mut sessions = Sessions::new();
+    let (gui, _) = sessions.open(OutboxConfig::default());
+    let (cli, _) = sessions.open(OutboxConfig::default());
+
+    let (gui_search, _) = sessions.start_search(gui, 1).unwrap();
+    let (cli_search, previous) = sessions.start_search(cli, 1).unwrap();
+
+    assert_eq!(previous, None);
+    assert_ne!(gui_search, cli_search);
+    assert_eq!(sessions.ids().resolve(gui_search), Some((gui, 1)));
+    assert_eq!(sessions.ids().resolve(cli_search), Some((cli, 1)));
+}
+
+#[tokio::test]
+async fn test_updates_reach_every_subscribed_client() {
+    let mut sessions = Sessions::new();
+    let (gui, _) = sessions.open(OutboxConfig::default());
+    let (cli, _) = sessions.open(OutboxConfig::default());
+
+    let gui_subscription = sessions.subscribe(gui, "/plugins/media", "player", 3);
+    let cli_subscription = sessions.subscribe(cli, "/plugins/media", "player", 7);
+    assert!(gui_subscription.is_some());
+    assert_ne!(gui_subscription, cli_subscription);
+
+    assert!(sessions.publish("/plugins/media", "player", &update(42, "player")));
+    assert!(!sessions.publish("/plugins/media", "volume", &update(42, "volume")));
+
+    let gui_outbox = sessions.get(gui).unwrap().outbox.clone();
+    let cli_outbox = sessions.get(cli).unwrap().outbox.clone();
+    assert_eq!(response_id(gui_outbox.pop().await), Some(3));
+    assert_eq!(response_id(cli_outbox.pop().await), Some(7));
+
+    // the plugin keeps publishing while any client is subscribed
+    assert!(!sessions.unsubscribe(gui, "/plugins/media", "player"));
+    assert!(sessions.unsubscribe(cli, "/plugins/media", "player"));
+    assert!(sessions.ids().is_empty());
+}
+
+#[test]
+fn test_rejected_subscription_is_routed_back() {
+    let mut sessions = Sessions::new();
+    let (client, _) = sessions.open(OutboxConfig::default());
+    let subscription = sessions
+        .subscribe(client, "/plugins/media", "player", 3)
+        .unwrap();
+
+    assert_eq!(
+        sessions.remove_request("/plugins/other", subscription),
+        None
+    );
+    assert_eq!(
+        sessions.remove_request("/plugins/media", subscription),
+        Some((client, 3, "player".to_string()))
+    );
+    assert!(!sessions.is_subscribed("/plugins/media", "player"));
+    assert!(sessions.ids().is_empty());
+}
+
+#[test]
+fn test_removed_plugin_drops_subscriptions_of_all_clients() {
+    let mut sessions = Sessions::new();
+    let (gui, _) = sessions.open(OutboxConfig::default());
+    let (cli, _) = sessions.open(OutboxConfig::default());
+    sessions.subscribe(gui, "/plugins/media", "player", 3);
+    sessions.subscribe(cli, "/plugins/media", "volume", 7);
+    sessions.subscribe(cli, "/plugins/load", "cpu", 8);
+
+    let mut removed = sessions.remove_plugin("/plugins/media");
+    removed.sort();
+
+    assert_eq!(removed, vec![(gui, 3), (cli, 7)]);
+    assert!(sessions.is_subscribed("/plugins/load", "cpu"));
+    assert_eq!(sessions.ids().len(), 1);
+}
+
+#[test]
+fn test_close_forgets_client_requests() {
+    let mut sessions = Sessions::new();
+    let (client, _) = sessions.open(OutboxConfig::default());
+    let (other, _) = sessions.open(OutboxConfig::default());
+    let (search, _) = sessions.start_search(client, 1).unwrap();
+    sessions.subscribe(client, "/plugins/media", "player", 2);
+    sessions.start_search(other, 1);
+
+    let session = sessions.close(client).unwrap();
+
+    assert_eq!(session.search, Some(search));
+    assert!(sessions.route(search).is_none());
+    assert_eq!(sessions.ids().len(), 1);
+    assert_eq!(sessions.clients(), vec![other]);
+    assert!(sessions.close(client).is_none());
+}
+
+#[tokio::test]
+async fn test_bind_replaces_stale_socket() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("glimpse").join("glimpsed.sock");
+
+    let listener = bind(&path).unwrap();
+    let err = bind(&path).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+
+    // the file outlives the listener, as after a crash
+    drop(listener);
+    assert!(path.exists());
+    assert!(bind(&path).is_ok());
+}
+
+#[test]
+fn test_actions_are_routed_to_their_client_until_finished() {
+    let mut sessions = Sessions::new();
+    let (gui, _) = sessions.open(OutboxConfig::default());
+    let (cli, _) = sessions.open(OutboxConfig::default());
+    let gui_action = sessions.start_action(gui, 4, "archives").unwrap();
+    let cli_action = sessions.start_action(cli, 4, "archives").unwrap();
+
+    assert_ne!(gui_action, cli_action);
+    assert_eq!(sessions.route_action("archives", gui_action).unwrap().0, 4);
+    // progress of another plugin under the same id is not this action's
+    assert!(sessions.route_action("files", gui_action).is_none());
+    assert_eq!(
+        sessions.find_action(cli, 4),
+        Some((cli_action, "archives".to_string()))
+    );
+
+    assert_eq!(sessions.end_action("archives", gui_action), Some((gui, 4)));
+    assert!(sessions.route_action("archives", gui_action).is_none());
+    assert!(sessions.end_action("archives", gui_action).is_none());
+    assert!(sessions.find_action(gui, 4).is_none());
+}
+
+#[test]
+fn test_removed_plugin_ends_its_actions() {
+    let mut sessions = Sessions::new();
+    let (client, _) = sessions.open(OutboxConfig::default());
+    let action = sessions.start_action(client, 2, "archives").unwrap();
+    sessions.start_action(client, 3, "files").unwrap();
+
+    assert_eq!(sessions.remove_plugin("archives"), vec![(client, 2)]);
+    assert!(sessions.route_action("archives", action).is_none());
+    assert!(sessions.find_action(client, 3).is_some());
+}
+
+#[test]
+fn test_details_requests_are_routed_to_their_client() {
+    let mut sessions = Sessions::new();
+    let (client, _) = sessions.open(OutboxConfig::default());
+    let details = sessions.start_details(client, 5, "files").unwrap();
+    sessions.start_details(client, 6, "archives").unwrap();
+
+    assert!(sessions.end_details("archives", details).is_none());
+    assert_eq!(sessions.end_details("files", details), Some((client, 5)));
+    assert!(sessions.end_details("files", details).is_none());
+
+    assert_eq!(sessions.remove_plugin("archives"), vec![(client, 6)]);
+}
+
+#[test]
+fn test_prefetches_route_back_to_their_match() {
+    let mut sessions = Sessions::new();
+    let (client, _) = sessions.open(OutboxConfig::default());
+    let search = sessions.start_search(client, 1).unwrap().0;
+    let prefetch = sessions.start_prefetch(client, "files", 1, 4).unwrap();
+    sessions.start_prefetch(client, "archives", 1, 5).unwrap();
+
+    assert_ne!(prefetch, search);
+    // daemon requests answer no client request
+    assert!(sessions.route(prefetch).is_none());
+    assert!(sessions.end_prefetch("archives", prefetch).is_none());
+    assert_eq!(
+        sessions.end_prefetch("files", prefetch),
+        Some((client, 1, 4))
+    );
+    assert!(sessions.end_prefetch("files", prefetch).is_none());
+
+    // nobody is told about prefetches of a plugin that went away
+    assert!(sessions.remove_plugin("archives").is_empty());
+    assert!(sessions.get(client).unwrap().prefetches.is_empty());
+}
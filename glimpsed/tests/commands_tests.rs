@@ -0,0 +1,165 @@
+use glimpse_sdk::{Action, Icon, Metadata, PaletteCommand, PowerProfile};
+use glimpsed::{
+    commands::{self, BuiltinCommand, CommandConfig, PROVIDER_KEY},
+    config::DaemonConfig,
+};
+
+fn plugin(id: &str, name: &str, commands: Vec<PaletteCommand>) -> Metadata {
+    Metadata {
+        id: id.to_string(),
+        name: name.to_string(),
+        commands,
+        ..Default::default()
+    }
+}
+
+fn titles(found: &[(String, Vec<glimpse_sdk::Match>)], key: &str) -> Vec<String> {
+    found
+        .iter()
+        .filter(|(provider, _)| provider == key)
+        .flat_map(|(_, rows)| rows.iter().map(|row| row.title.clone()))
+        .collect()
+}
+
+#[test]
+fn test_strip_prefix() {
+    let config = CommandConfig::default();
+    assert_eq!(config.strip("> reload"), Some("reload"));
+    assert_eq!(config.strip(">"), Some(""));
+    assert_eq!(config.strip("reload"), None);
+    assert_eq!(config.strip("\\> reload"), None);
+
+    let disabled = CommandConfig {
+        enabled: false,
+        ..Default::default()
+    };
+    assert_eq!(disabled.strip("> reload"), None);
+
+    let config = DaemonConfig::from_toml("[commands]\nprefix = \":\"").unwrap();
+    assert_eq!(config.commands.strip(":clip"), Some("clip"));
+    assert_eq!(config.commands.strip("> clip"), None);
+}
+
+#[test]
+fn test_builtin_actions_round_trip() {
+    let builtins = [
+        BuiltinCommand::ReloadSettings,
+        BuiltinCommand::RestartPlugin("me.aresa.glimpse.apps".to_string()),
+        BuiltinCommand::PowerProfile(PowerProfile::LowPower),
+        BuiltinCommand::PowerProfile(PowerProfile::Normal),
+        BuiltinCommand::CheckUpdates,
+    ];
+    for builtin in builtins {
+        assert_eq!(
+            BuiltinCommand::from_action(&builtin.action()),
+            Some(builtin)
+        );
+    }
+    let unknown = Action::Callback {
+        key: "restart_plugin".to_string(),
+        params: Default::default(),
+    };
+    assert_eq!(BuiltinCommand::from_action(&unknown), None);
+    let open = Action::Open {
+        uri: "https://example.com".to_string(),
+    };
+    assert_eq!(BuiltinCommand::from_action(&open), None);
+}
+
+#[test]
+fn test_available_builtins() {
+    let apps = plugin("me.aresa.glimpse.apps", "Apps", vec![]);
+    assert_eq!(
+        BuiltinCommand::available(&[&apps], PowerProfile::Normal, false),
+        vec![
+            BuiltinCommand::ReloadSettings,
+            BuiltinCommand::PowerProfile(PowerProfile::LowPower),
+            BuiltinCommand::RestartPlugin("me.aresa.glimpse.apps".to_string()),
+        ]
+    );
+    assert!(
+        BuiltinCommand::available(&[], PowerProfile::LowPower, true)
+            .contains(&BuiltinCommand::CheckUpdates)
+    );
+}
+
+#[test]
+fn test_palette_groups_commands_by_provider() {
+    let clipboard = plugin(
+        "me.aresa.glimpse.clipboard",
+        "Clipboard",
+        vec![
+            PaletteCommand::new("clear", "Clear clipboard history")
+                .icon(Icon::freedesktop("edit-clear-history")),
+        ],
+    );
+    let apps = plugin("me.aresa.glimpse.apps", "Apps", vec![]);
+    let plugins = [("/plugins/clipboard", &clipboard), ("/plugins/apps", &apps)];
+    let builtins = BuiltinCommand::available(&[&clipboard, &apps], PowerProfile::Normal, false);
+
+    let found = commands::palette("", &builtins, &plugins);
+    assert_eq!(
+        titles(&found, PROVIDER_KEY),
+        vec![
+            "Reload plugin settings",
+            "Switch to low power mode",
+            "Restart Clipboard",
+            "Restart Apps",
+        ]
+    );
+    assert_eq!(
+        titles(&found, "/plugins/clipboard"),
+        vec!["Clear clipboard history"]
+    );
+    // plugins without commands are left out
+    assert_eq!(found.len(), 2);
+
+    // activating a plugin command sends its callback to the plugin
+    let (_, rows) = &found[1];
+    assert_eq!(
+        rows[0].actions[0].action,
+        Action::Callback {
+            key: "clear".to_string(),
+            params: Default::default(),
+        }
+    );
+
+    let found = commands::palette("clip", &builtins, &plugins);
+    assert_eq!(titles(&found, PROVIDER_KEY), vec!["Restart Clipboard"]);
+    assert_eq!(
+        titles(&found, "/plugins/clipboard"),
+        vec!["Clear clipboard history"]
+    );
+    assert!(commands::palette("nothing like it", &builtins, &plugins).is_empty());
+}
+
+#[test]
+fn test_score() {
+    let command = PaletteCommand::new("reload", "Reload plugin settings")
+        .description("Send every plugin its settings again");
+    let score = |query| commands::score(query, &command);
+    assert_eq!(score("rel"), Some(1.0));
+    assert_eq!(score("PLUG"), Some(0.8));
+    assert_eq!(score("ettings"), Some(0.6));
+    assert_eq!(score("again"), Some(0.4));
+    assert_eq!(score("quit"), None);
+}
+
+#[test]
+fn test_metadata_commands_serde() {
+    let metadata = plugin(
+        "me.aresa.glimpse.clipboard",
+        "Clipboard",
+        vec![PaletteCommand::new("clear", "Clear clipboard history").description("Forget all")],
+    );
+    let json = serde_json::to_value(&metadata).unwrap();
+    assert_eq!(
+        json["commands"],
+        serde_json::json!([{"key": "clear", "title": "Clear clipboard history", "description": "Forget all"}])
+    );
+    assert_eq!(serde_json::from_value::<Metadata>(json).unwrap(), metadata);
+
+    // older plugins declare none
+    let json = serde_json::to_value(plugin("a", "A", vec![])).unwrap();
+    assert!(json.get("commands").is_none());
+}
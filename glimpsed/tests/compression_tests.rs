@@ -0,0 +1,39 @@
+use glimpse_sdk::{Message, Method};
+use glimpsed::config::DaemonConfig;
+
+#[test]
+fn test_compression_is_offered_to_supporting_plugins() {
+    let config = DaemonConfig::default();
+    assert_eq!(
+        config.compression.negotiate(true),
+        Some(Message::Notification {
+            method: Method::EnableCompression { threshold: 16384 },
+            plugin_id: None,
+        })
+    );
+    assert_eq!(config.compression.negotiate(false), None);
+
+    let config = DaemonConfig::from_toml(
+        r#"
+        [compression]
+        enabled = false
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.compression.negotiate(true), None);
+
+    let config = DaemonConfig::from_toml(
+        r#"
+        [compression]
+        threshold = 4096
+        "#,
+    )
+    .unwrap();
+    assert!(matches!(
+        config.compression.negotiate(true),
+        Some(Message::Notification {
+            method: Method::EnableCompression { threshold: 4096 },
+            ..
+        })
+    ));
+}
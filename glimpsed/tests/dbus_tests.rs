@@ -0,0 +1,84 @@
+use glimpse_sdk::{Action, Icon, Match, MatchAction, Metadata};
+use glimpsed::{
+    config::DaemonConfig,
+    dbus::{DbusMatch, plugin_row},
+};
+
+fn create_action(title: &str) -> MatchAction {
+    MatchAction {
+        title: title.to_string(),
+        action: Action::Clipboard { text: title.into() },
+        close_on_action: true,
+        alternates: vec![],
+        requires_confirmation: false,
+        confirmation_prompt: None,
+        expand: vec![],
+    }
+}
+
+#[test]
+fn test_matches_are_sent_with_icon_names_and_action_titles() {
+    let item = Match {
+        id: Some(7),
+        title: "Firefox".to_string(),
+        description: "Web browser".to_string(),
+        icon: Some(Icon::freedesktop("firefox")),
+        actions: vec![create_action("Launch"), create_action("New window")],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        DbusMatch::from(&item),
+        DbusMatch {
+            id: 7,
+            title: "Firefox".to_string(),
+            description: "Web browser".to_string(),
+            icon: "firefox".to_string(),
+            actions: vec!["Launch".to_string(), "New window".to_string()],
+        }
+    );
+}
+
+#[test]
+fn test_icons_only_glimpse_draws_are_left_out() {
+    let icon = |icon| {
+        DbusMatch::from(&Match {
+            icon: Some(icon),
+            ..Default::default()
+        })
+        .icon
+    };
+
+    assert_eq!(icon(Icon::path("/tmp/a.png")), "/tmp/a.png");
+    assert_eq!(icon(Icon::thumbnail("/tmp/b.jpg")), "/tmp/b.jpg");
+    assert_eq!(icon(Icon::emoji("🦀")), "");
+    assert_eq!(DbusMatch::from(&Match::default()).icon, "");
+}
+
+#[test]
+fn test_plugins_are_listed_by_id_name_version_and_description() {
+    let metadata = Metadata {
+        id: "me.aresa.glimpse.apps".to_string(),
+        name: "Apps".to_string(),
+        version: "0.1.0".to_string(),
+        description: "Launch applications".to_string(),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        plugin_row(&metadata),
+        (
+            "me.aresa.glimpse.apps".to_string(),
+            "Apps".to_string(),
+            "0.1.0".to_string(),
+            "Launch applications".to_string()
+        )
+    );
+}
+
+#[test]
+fn test_dbus_can_be_turned_off() {
+    assert!(DaemonConfig::default().dbus.enabled);
+    let config = DaemonConfig::from_toml("[dbus]\nenabled = false\n").unwrap();
+    assert!(!config.dbus.enabled);
+}
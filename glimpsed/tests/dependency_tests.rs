@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use glimpse_sdk::Metadata;
+use glimpsed::{
+    config::DaemonConfig,
+    dependencies::{Dependencies, DependencyError},
+};
+
+fn plugin(id: &str, depends_on: &[&str]) -> Metadata {
+    Metadata {
+        id: id.to_string(),
+        depends_on: depends_on.iter().map(|id| id.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_order_puts_prerequisites_first() {
+    let plugins = [
+        plugin("apps", &[]),
+        plugin("windows", &["compositor"]),
+        plugin("files", &["indexer"]),
+        plugin("indexer", &[]),
+        plugin("compositor", &[]),
+    ];
+    let dependencies = Dependencies::of(&plugins);
+
+    assert_eq!(dependencies.check(), Ok(()));
+    assert_eq!(
+        dependencies.order(),
+        ["apps", "compositor", "indexer", "files", "windows"]
+    );
+}
+
+#[test]
+fn test_pending_lists_prerequisites_not_authenticated() {
+    let plugins = [
+        plugin("files", &["indexer", "thumbnailer"]),
+        plugin("indexer", &[]),
+    ];
+    let dependencies = Dependencies::of(&plugins);
+
+    assert_eq!(dependencies.pending("files"), ["thumbnailer"]);
+    assert!(dependencies.pending("indexer").is_empty());
+    assert!(dependencies.pending("unknown").is_empty());
+    assert_eq!(dependencies.order(), ["indexer", "files"]);
+}
+
+#[test]
+fn test_check_reports_cycles() {
+    let plugins = [
+        plugin("a", &["b"]),
+        plugin("b", &["c"]),
+        plugin("c", &["b"]),
+        plugin("d", &[]),
+    ];
+    let dependencies = Dependencies::of(&plugins);
+
+    let err = dependencies.check().unwrap_err();
+    assert_eq!(
+        err,
+        DependencyError::Cycle {
+            plugins: vec!["b".to_string(), "c".to_string(), "b".to_string()]
+        }
+    );
+    assert_eq!(err.to_string(), "plugins depend on each other: b -> c -> b");
+    // the cycle is broken at its lowest id, everything is still ordered
+    assert_eq!(dependencies.order(), ["d", "b", "a", "c"]);
+}
+
+#[test]
+fn test_self_dependency_is_a_cycle() {
+    let dependencies = Dependencies::of(&[plugin("a", &["a"])]);
+    assert_eq!(
+        dependencies.check(),
+        Err(DependencyError::Cycle {
+            plugins: vec!["a".to_string(), "a".to_string()]
+        })
+    );
+    assert_eq!(dependencies.order(), ["a"]);
+}
+
+#[test]
+fn test_wait_is_configurable() {
+    assert_eq!(
+        DaemonConfig::default().dependencies.wait(),
+        Duration::from_secs(5)
+    );
+    let config = DaemonConfig::from_toml("[dependencies]\nwait_ms = 250\n").unwrap();
+    assert_eq!(config.dependencies.wait(), Duration::from_millis(250));
+}
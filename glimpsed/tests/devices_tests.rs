@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use glimpsed::devices::bluez_device;
+use zbus::{
+    fdo::ManagedObjects,
+    names::OwnedInterfaceName,
+    zvariant::{OwnedObjectPath, OwnedValue, Str},
+};
+
+fn object(
+    interface: &str,
+    address: &str,
+) -> HashMap<OwnedInterfaceName, HashMap<String, OwnedValue>> {
+    let properties = HashMap::from([(
+        "Address".to_string(),
+        OwnedValue::from(Str::from(address.to_string())),
+    )]);
+    HashMap::from([(OwnedInterfaceName::try_from(interface).unwrap(), properties)])
+}
+
+fn path(path: &str) -> OwnedObjectPath {
+    OwnedObjectPath::try_from(path).unwrap()
+}
+
+fn objects() -> ManagedObjects {
+    HashMap::from([
+        (
+            path("/org/bluez/hci0"),
+            object("org.bluez.Adapter1", "5C:F3:70:8B:12:0A"),
+        ),
+        (
+            path("/org/bluez/hci0/dev_00_1A_7D_DA_71_13"),
+            object("org.bluez.Device1", "00:1A:7D:DA:71:13"),
+        ),
+        (
+            path("/org/bluez/hci1/dev_A4_C1_38_0F_22_91"),
+            object("org.bluez.Device1", "A4:C1:38:0F:22:91"),
+        ),
+    ])
+}
+
+#[test]
+fn test_finds_devices_by_address() {
+    assert_eq!(
+        bluez_device(&objects(), "00:1A:7D:DA:71:13"),
+        Some(path("/org/bluez/hci0/dev_00_1A_7D_DA_71_13"))
+    );
+    assert_eq!(
+        bluez_device(&objects(), "a4:c1:38:0f:22:91"),
+        Some(path("/org/bluez/hci1/dev_A4_C1_38_0F_22_91"))
+    );
+}
+
+#[test]
+fn test_ignores_adapters_and_unknown_addresses() {
+    assert_eq!(bluez_device(&objects(), "5C:F3:70:8B:12:0A"), None);
+    assert_eq!(bluez_device(&objects(), "11:22:33:44:55:66"), None);
+}
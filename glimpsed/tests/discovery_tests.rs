@@ -0,0 +1,69 @@
+use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+use glimpse_sdk::{Message, Method};
+use glimpsed::plugins::discover_plugins_in;
+use tempfile::tempdir;
+
+fn write_file(path: &Path, mode: u32) {
+    fs::write(path, "#!/bin/sh\n").unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+}
+
+#[test]
+fn test_discovers_executables_only() {
+    let dir = tempdir().unwrap();
+    write_file(&dir.path().join("apps"), 0o755);
+    write_file(&dir.path().join("README"), 0o644);
+    fs::create_dir(dir.path().join("nested")).unwrap();
+
+    let found = discover_plugins_in(&[dir.path().to_path_buf()]);
+    assert_eq!(found, vec![dir.path().join("apps").to_string_lossy()]);
+}
+
+#[test]
+fn test_rescan_reflects_added_and_removed_plugins() {
+    let dir = tempdir().unwrap();
+    let dirs = [dir.path().to_path_buf(), dir.path().join("missing")];
+    write_file(&dir.path().join("apps"), 0o755);
+    assert_eq!(discover_plugins_in(&dirs).len(), 1);
+
+    write_file(&dir.path().join("files"), 0o755);
+    fs::remove_file(dir.path().join("apps")).unwrap();
+    assert_eq!(
+        discover_plugins_in(&dirs),
+        vec![dir.path().join("files").to_string_lossy()]
+    );
+}
+
+#[test]
+fn test_plugins_changed_is_a_notification() {
+    let message = Message::Notification {
+        method: Method::PluginsChanged {
+            added: vec!["files".to_string()],
+            removed: vec![],
+        },
+        plugin_id: None,
+    };
+    let json = serde_json::to_value(&message).unwrap();
+    assert_eq!(json["method"], "plugins_changed");
+    assert_eq!(json["params"]["added"][0], "files");
+    assert!(json.get("id").is_none());
+
+    let decoded: Message = serde_json::from_value(json).unwrap();
+    assert_eq!(decoded, message);
+}
+
+#[test]
+fn test_discovered_plugins_are_sorted() {
+    let dir = tempdir().unwrap();
+    for name in ["windows", "apps", "files"] {
+        write_file(&dir.path().join(name), 0o755);
+    }
+
+    let found = discover_plugins_in(&[dir.path().to_path_buf()]);
+    let names = found
+        .iter()
+        .map(|path| Path::new(path).file_name().unwrap().to_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["apps", "files", "windows"]);
+}
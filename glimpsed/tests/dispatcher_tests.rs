@@ -0,0 +1,394 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use glimpse_sdk::{
+    Action, ConnectionTarget, MAX_SEQUENCE_STEPS, Message, Sensitive, SequenceError, StepStatus,
+    SystemCommand,
+};
+use glimpsed::dispatchers::{
+    DelayedClipboard, DispatchError, Dispatched, RecordingDispatcher, dispatch_action,
+    dispatch_sequence, pipe_to,
+};
+use glimpsed::timers::Reminder;
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn test_dispatch_exec() {
+    let dispatcher = RecordingDispatcher::new();
+    let action = Action::Exec {
+        command: "ghostty".to_string(),
+        args: vec!["-e".to_string(), "htop".to_string()],
+    };
+
+    dispatch_action(&dispatcher, &action, None).await.unwrap();
+
+    assert_eq!(
+        dispatcher.calls(),
+        vec![Dispatched::Exec {
+            command: "ghostty".to_string(),
+            args: vec!["-e".to_string(), "htop".to_string()],
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_dispatch_launch() {
+    let dispatcher = RecordingDispatcher::new();
+    let action = Action::Launch {
+        app_id: "org.gnome.Nautilus".to_string(),
+        action: Some("new-window".to_string()),
+    };
+
+    dispatch_action(&dispatcher, &action, None).await.unwrap();
+
+    assert_eq!(
+        dispatcher.calls(),
+        vec![Dispatched::Launch {
+            app_id: "org.gnome.Nautilus".to_string(),
+            action: Some("new-window".to_string()),
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_dispatch_clipboard_and_open() {
+    let dispatcher = RecordingDispatcher::new();
+
+    dispatch_action(
+        &dispatcher,
+        &Action::Clipboard {
+            text: "Hello World".into(),
+        },
+        None,
+    )
+    .await
+    .unwrap();
+    dispatch_action(
+        &dispatcher,
+        &Action::Open {
+            uri: "https://www.rust-lang.org".to_string(),
+        },
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        dispatcher.calls(),
+        vec![
+            Dispatched::Clipboard {
+                text: "Hello World".to_string(),
+            },
+            Dispatched::Open {
+                uri: "https://www.rust-lang.org".to_string(),
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_dispatch_expiring_clipboard() {
+    let dispatcher = RecordingDispatcher::new();
+    let action = Action::ExpiringClipboard {
+        text: "hunter2".into(),
+        clear_after_secs: 30,
+    };
+
+    dispatch_action(&dispatcher, &action, None).await.unwrap();
+
+    assert_eq!(
+        dispatcher.calls(),
+        vec![Dispatched::ExpiringClipboard {
+            text: "hunter2".to_string(),
+            clear_after: Duration::from_secs(30),
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_dispatch_type_text() {
+    let dispatcher = RecordingDispatcher::new();
+    let action = Action::TypeText {
+        text: "¯\\_(ツ)_/¯".into(),
+    };
+
+    dispatch_action(&dispatcher, &action, None).await.unwrap();
+
+    assert_eq!(
+        dispatcher.calls(),
+        vec![Dispatched::TypeText {
+            text: "¯\\_(ツ)_/¯".to_string(),
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_dispatch_system() {
+    let dispatcher = RecordingDispatcher::new();
+    let action = Action::System {
+        command: SystemCommand::LockScreen,
+    };
+
+    dispatch_action(&dispatcher, &action, None).await.unwrap();
+
+    assert_eq!(
+        dispatcher.calls(),
+        vec![Dispatched::System {
+            command: SystemCommand::LockScreen,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_dispatch_connection() {
+    let dispatcher = RecordingDispatcher::new();
+    let target = ConnectionTarget::Bluetooth {
+        address: "00:1A:7D:DA:71:13".to_string(),
+    };
+    let action = Action::Connection {
+        target: target.clone(),
+        connect: false,
+    };
+
+    dispatch_action(&dispatcher, &action, None).await.unwrap();
+
+    assert_eq!(
+        dispatcher.calls(),
+        vec![Dispatched::Connection {
+            target,
+            connect: false,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_dispatch_schedule() {
+    let dispatcher = RecordingDispatcher::new();
+    let action = Action::Schedule {
+        after_secs: 600,
+        title: "tea".to_string(),
+        body: Some("10 min are up".to_string()),
+        open_launcher: true,
+    };
+
+    dispatch_action(&dispatcher, &action, None).await.unwrap();
+
+    assert_eq!(
+        dispatcher.calls(),
+        vec![Dispatched::Schedule {
+            after: Duration::from_secs(600),
+            reminder: Reminder {
+                title: "tea".to_string(),
+                body: Some("10 min are up".to_string()),
+                open_launcher: true,
+            },
+        }]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_delayed_clipboard_clears_the_last_copy_only() {
+    let clipboard = DelayedClipboard::default();
+    let cleared = Arc::new(Mutex::new(vec![]));
+    for copy in ["username", "password"] {
+        let cleared = cleared.clone();
+        clipboard.schedule(Duration::from_secs(30), async move {
+            cleared.lock().unwrap().push(copy);
+        });
+    }
+
+    tokio::time::sleep(Duration::from_secs(29)).await;
+    assert!(cleared.lock().unwrap().is_empty());
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(*cleared.lock().unwrap(), vec!["password"]);
+}
+
+#[tokio::test]
+async fn test_dispatch_callback_notifies_plugin() {
+    let dispatcher = RecordingDispatcher::new();
+    let (tx, _rx) = mpsc::channel::<Message>(1);
+    let params = HashMap::from([("example_key".to_string(), "example_value".to_string())]);
+    let action = Action::Callback {
+        key: "example_callback".to_string(),
+        params: params.clone(),
+    };
+
+    dispatch_action(&dispatcher, &action, Some((tx, 7)))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        dispatcher.calls(),
+        vec![Dispatched::Notify {
+            id: 7,
+            key: "example_callback".to_string(),
+            params,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_dispatch_callback_without_plugin_is_skipped() {
+    let dispatcher = RecordingDispatcher::new();
+    let action = Action::Callback {
+        key: "example_callback".to_string(),
+        params: HashMap::new(),
+    };
+
+    assert!(dispatch_action(&dispatcher, &action, None).await.is_err());
+    assert!(dispatcher.calls().is_empty());
+}
+
+fn copy_then_open() -> Action {
+    Action::Sequence {
+        actions: vec![
+            Action::Clipboard {
+                text: "Hello World".into(),
+            },
+            Action::Exec {
+                command: "notify-send".to_string(),
+                args: vec!["Copied".to_string()],
+            },
+            Action::Open {
+                uri: "https://www.rust-lang.org".to_string(),
+            },
+        ],
+    }
+}
+
+#[tokio::test]
+async fn test_dispatch_sequence_runs_steps_in_order() {
+    let dispatcher = RecordingDispatcher::new();
+
+    let steps = dispatch_sequence(&dispatcher, &copy_then_open(), None)
+        .await
+        .unwrap();
+
+    assert_eq!(steps, vec![StepStatus::Done; 3]);
+    assert_eq!(
+        dispatcher.calls(),
+        vec![
+            Dispatched::Clipboard {
+                text: "Hello World".to_string(),
+            },
+            Dispatched::Exec {
+                command: "notify-send".to_string(),
+                args: vec!["Copied".to_string()],
+            },
+            Dispatched::Open {
+                uri: "https://www.rust-lang.org".to_string(),
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_dispatch_sequence_stops_at_failure() {
+    let dispatcher = RecordingDispatcher::new();
+    dispatcher.fail_on(Dispatched::Exec {
+        command: "notify-send".to_string(),
+        args: vec!["Copied".to_string()],
+    });
+
+    let steps = dispatch_sequence(&dispatcher, &copy_then_open(), None)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        steps,
+        vec![
+            StepStatus::Done,
+            StepStatus::Failed {
+                message: "io: recorded failure".to_string(),
+            },
+            StepStatus::Skipped,
+        ]
+    );
+    // nothing runs after the failed step
+    assert_eq!(dispatcher.calls().len(), 2);
+    assert!(matches!(
+        dispatch_action(&dispatcher, &copy_then_open(), None).await,
+        Err(DispatchError::Io(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_dispatch_sequence_callback_needs_plugin() {
+    let dispatcher = RecordingDispatcher::new();
+    let action = Action::Sequence {
+        actions: vec![
+            Action::Callback {
+                key: "pin".to_string(),
+                params: HashMap::new(),
+            },
+            Action::Open {
+                uri: "https://www.rust-lang.org".to_string(),
+            },
+        ],
+    };
+
+    let steps = dispatch_sequence(&dispatcher, &action, None).await.unwrap();
+    assert_eq!(
+        steps,
+        vec![
+            StepStatus::Failed {
+                message: "no plugin to run callback pin".to_string(),
+            },
+            StepStatus::Skipped,
+        ]
+    );
+
+    let (tx, _rx) = mpsc::channel::<Message>(1);
+    let steps = dispatch_sequence(&dispatcher, &action, Some((tx, 3)))
+        .await
+        .unwrap();
+    assert_eq!(steps, vec![StepStatus::Done; 2]);
+}
+
+#[tokio::test]
+async fn test_dispatch_sequence_over_limit_runs_nothing() {
+    let dispatcher = RecordingDispatcher::new();
+    let action = Action::Sequence {
+        actions: vec![
+            Action::Open {
+                uri: "https://www.rust-lang.org".to_string(),
+            };
+            MAX_SEQUENCE_STEPS + 1
+        ],
+    };
+
+    assert_eq!(
+        dispatch_sequence(&dispatcher, &action, None).await,
+        Err(SequenceError::TooManySteps {
+            steps: MAX_SEQUENCE_STEPS + 1,
+        })
+    );
+    assert!(matches!(
+        dispatch_action(&dispatcher, &action, None).await,
+        Err(DispatchError::Sequence(_))
+    ));
+    assert!(dispatcher.calls().is_empty());
+}
+
+#[tokio::test]
+async fn test_pipe_to_passes_text_on_stdin() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("stdin");
+    let output_arg = output.to_string_lossy().to_string();
+    let secret = Sensitive::new("hunter2 with spaces".to_string());
+
+    pipe_to("sh", &["-c", "cat > \"$1\"", "sh", &output_arg], &secret)
+        .await
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&output).unwrap(),
+        "hunter2 with spaces"
+    );
+
+    let failed = pipe_to("sh", &["-c", "cat > /dev/null; exit 3"], &secret).await;
+    assert!(matches!(failed, Err(DispatchError::Status { command, .. }) if command == "sh"));
+}
@@ -0,0 +1,153 @@
+use std::path::PathBuf;
+
+use glimpse_sdk::{Action, ExpandField, MatchAction};
+use glimpsed::expand::{Environment, ExpandError};
+
+fn environment() -> Environment {
+    Environment::new(
+        Some(PathBuf::from("/home/me")),
+        [
+            ("EDITOR".to_string(), "nvim".to_string()),
+            ("XDG_DATA_HOME".to_string(), "/data".to_string()),
+        ],
+    )
+}
+
+#[test]
+fn test_expand() {
+    let env = environment();
+    assert_eq!(env.expand("~").unwrap(), "/home/me");
+    assert_eq!(env.expand("~/notes.md").unwrap(), "/home/me/notes.md");
+    assert_eq!(env.expand("$HOME/bin").unwrap(), "/home/me/bin");
+    assert_eq!(env.expand("${EDITOR}rc").unwrap(), "nvimrc");
+    assert_eq!(env.expand("$EDITOR-qt").unwrap(), "nvim-qt");
+    // set variables win over the XDG defaults
+    assert_eq!(
+        env.expand("$XDG_DATA_HOME/glimpse").unwrap(),
+        "/data/glimpse"
+    );
+    assert_eq!(
+        env.expand("$XDG_CONFIG_HOME/glimpse").unwrap(),
+        "/home/me/.config/glimpse"
+    );
+    // kept as they are
+    assert_eq!(env.expand("costs $$5, $ 3").unwrap(), "costs $5, $ 3");
+    assert_eq!(env.expand("~root/x and a~").unwrap(), "~root/x and a~");
+}
+
+#[test]
+fn test_expand_errors() {
+    let env = environment();
+    assert_eq!(
+        env.expand("$NOPE/x"),
+        Err(ExpandError::Undefined {
+            name: "NOPE".to_string()
+        })
+    );
+    assert!(matches!(
+        env.expand("${HOME"),
+        Err(ExpandError::Unterminated { .. })
+    ));
+    let homeless = Environment::new(None, []);
+    assert_eq!(homeless.expand("~/x"), Err(ExpandError::NoHome));
+    assert_eq!(homeless.expand("plain").unwrap(), "plain");
+}
+
+#[test]
+fn test_daemon_secrets_are_not_expanded() {
+    let env = Environment::allowed(
+        Some(PathBuf::from("/home/me")),
+        [
+            ("USER".to_string(), "me".to_string()),
+            ("XDG_DATA_HOME".to_string(), "/data".to_string()),
+            ("XDG_SESSION_TYPE".to_string(), "wayland".to_string()),
+            ("AWS_SECRET_ACCESS_KEY".to_string(), "hunter2".to_string()),
+        ],
+    );
+    assert_eq!(env.expand("$USER@$XDG_DATA_HOME").unwrap(), "me@/data");
+    assert_eq!(
+        env.expand("https://host/?k=$AWS_SECRET_ACCESS_KEY"),
+        Err(ExpandError::Undefined {
+            name: "AWS_SECRET_ACCESS_KEY".to_string()
+        })
+    );
+    assert!(env.expand("$XDG_SESSION_TYPE").is_err());
+}
+
+#[test]
+fn test_expand_action_opted_in_fields() {
+    let env = environment();
+    let exec = Action::Exec {
+        command: "~/bin/open".to_string(),
+        args: vec!["$EDITOR".to_string()],
+    };
+    assert_eq!(
+        env.expand_action(&exec, &[ExpandField::Args]).unwrap(),
+        Action::Exec {
+            command: "~/bin/open".to_string(),
+            args: vec!["nvim".to_string()],
+        }
+    );
+    // nothing is expanded unless asked for
+    assert_eq!(env.expand_action(&exec, &[]).unwrap(), exec);
+
+    let sequence = Action::Sequence {
+        actions: vec![
+            exec.clone(),
+            Action::Open {
+                uri: "file://~/Downloads".to_string(),
+            },
+        ],
+    };
+    let fields = [ExpandField::Command, ExpandField::Uri];
+    assert_eq!(
+        env.expand_action(&sequence, &fields).unwrap(),
+        Action::Sequence {
+            actions: vec![
+                Action::Exec {
+                    command: "/home/me/bin/open".to_string(),
+                    args: vec!["$EDITOR".to_string()],
+                },
+                Action::Open {
+                    uri: "file:///home/me/Downloads".to_string(),
+                },
+            ],
+        }
+    );
+    assert!(
+        env.expand_action(
+            &Action::Open {
+                uri: "$BROWSER_HOME".to_string()
+            },
+            &fields
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn test_expand_serde() {
+    let json = serde_json::json!({
+        "title": "Edit",
+        "action": {"type": "exec", "command": "$EDITOR", "args": ["~/notes.md"]},
+        "close_on_action": true,
+        "expand": ["command", "args"],
+    });
+    let action = serde_json::from_value::<MatchAction>(json).unwrap();
+    assert_eq!(action.expand, vec![ExpandField::Command, ExpandField::Args]);
+
+    // older plugins send none
+    let json = serde_json::json!({
+        "title": "Edit",
+        "action": {"type": "open", "uri": "https://example.com"},
+        "close_on_action": true,
+    });
+    let action = serde_json::from_value::<MatchAction>(json).unwrap();
+    assert!(action.expand.is_empty());
+    assert!(
+        serde_json::to_value(&action)
+            .unwrap()
+            .get("expand")
+            .is_none()
+    );
+}
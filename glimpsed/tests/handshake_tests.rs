@@ -0,0 +1,124 @@
+use glimpse_sdk::{
+    Capability, FeatureError, Features, Metadata, PROTOCOL_VERSION, SDK_CAPABILITIES,
+};
+use glimpsed::{
+    config::DaemonConfig,
+    handshake::{self, HandshakeError},
+};
+
+fn metadata(protocol_version: u32) -> Metadata {
+    Metadata {
+        id: "test".to_string(),
+        protocol_version,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_negotiate_accepts_same_and_older_versions() {
+    assert_eq!(handshake::negotiate(&metadata(PROTOCOL_VERSION)), Ok(()));
+    assert_eq!(handshake::negotiate(&metadata(0)), Ok(()));
+}
+
+#[test]
+fn test_negotiate_refuses_newer_version() {
+    assert_eq!(
+        handshake::negotiate(&metadata(PROTOCOL_VERSION + 1)),
+        Err(HandshakeError::Newer {
+            plugin: PROTOCOL_VERSION + 1,
+            daemon: PROTOCOL_VERSION,
+        })
+    );
+}
+
+#[test]
+fn test_mismatched_version() {
+    let line = |version: u32| {
+        format!(
+            r#"{{"id":1,"result":{{"protocol_version":{},"unknown":true}}}}"#,
+            version
+        )
+    };
+    assert_eq!(
+        handshake::mismatched_version(&line(PROTOCOL_VERSION + 1)),
+        Some(u64::from(PROTOCOL_VERSION + 1))
+    );
+    assert_eq!(handshake::mismatched_version(&line(PROTOCOL_VERSION)), None);
+    assert_eq!(handshake::mismatched_version("not json"), None);
+}
+
+#[test]
+fn test_metadata_from_older_plugin() {
+    let metadata: Metadata = serde_json::from_str(
+        r#"{"id":"old","name":"Old","version":"0.1.0","description":"","author":""}"#,
+    )
+    .unwrap();
+    assert_eq!(metadata.protocol_version, 0);
+    assert!(!metadata.supports(Capability::StreamingSearch));
+}
+
+#[test]
+fn test_unknown_capabilities_are_ignored() {
+    let metadata: Metadata = serde_json::from_str(
+        r#"{"id":"new","name":"New","version":"0.1.0","description":"","author":"",
+            "protocol_version":1,"capabilities":["streaming_search","teleport"]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        metadata.capabilities,
+        vec![Capability::StreamingSearch, Capability::Unknown]
+    );
+    assert!(metadata.supports(Capability::StreamingSearch));
+    assert!(!metadata.supports(Capability::Subscriptions));
+    for capability in [
+        Capability::StreamingSearch,
+        Capability::ActionProgress,
+        Capability::Subscriptions,
+    ] {
+        assert!(SDK_CAPABILITIES.contains(&capability));
+    }
+}
+
+#[test]
+fn test_negotiate_features_keeps_what_both_have_on() {
+    let mut plugin = metadata(PROTOCOL_VERSION);
+    plugin.features.subscriptions = false;
+
+    let negotiated = handshake::negotiate_features(&plugin, &Features::default()).unwrap();
+    assert!(negotiated.streaming);
+    assert!(!negotiated.subscriptions);
+    assert_eq!(
+        handshake::negotiate_features(&plugin, &Features::none()),
+        Ok(Features::none())
+    );
+}
+
+#[test]
+fn test_negotiate_features_refuses_invalid_combinations() {
+    let mut plugin = metadata(PROTOCOL_VERSION);
+    plugin.features = Features {
+        streaming: false,
+        binary_encoding: true,
+        subscriptions: true,
+    };
+
+    let err = handshake::negotiate_features(&plugin, &Features::default()).unwrap_err();
+    assert_eq!(
+        err,
+        HandshakeError::Features(FeatureError::Requires {
+            feature: "binary_encoding",
+            requires: "streaming"
+        })
+    );
+    assert!(err.to_string().starts_with("invalid features: "));
+}
+
+#[test]
+fn test_features_are_configured_per_daemon() {
+    assert_eq!(DaemonConfig::default().features, Features::default());
+
+    let config = DaemonConfig::from_toml("[features]\nsubscriptions = false\n").unwrap();
+    assert!(config.features.streaming);
+    assert!(!config.features.subscriptions);
+    assert_eq!(config.features.check(), Ok(()));
+}
@@ -0,0 +1,95 @@
+use std::time::{Duration, SystemTime};
+
+use glimpse_sdk::Match;
+use glimpsed::history::{History, boost_for};
+use tempfile::TempDir;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+fn create_match(title: &str, score: f64) -> Match {
+    Match {
+        title: title.to_string(),
+        score,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_recent_activations_weigh_more() {
+    let history = History::open_in_memory().unwrap();
+    let now = SystemTime::now();
+    history.record("test.apps", "Old", now - DAY * 60).unwrap();
+    history.record("test.apps", "New", now - DAY).unwrap();
+
+    let scores = history.frecency("test.apps", now).unwrap();
+
+    assert!(scores["New"] > scores["Old"]);
+    assert!(history.frecency("test.other", now).unwrap().is_empty());
+}
+
+#[test]
+fn test_boost_reorders_matches() {
+    let history = History::open_in_memory().unwrap();
+    let now = SystemTime::now();
+    for _ in 0..3 {
+        history.record("test.apps", "Chosen", now).unwrap();
+    }
+    let mut items = vec![create_match("Best", 0.9), create_match("Chosen", 0.5)];
+
+    history.boost("test.apps", &mut items, now).unwrap();
+
+    assert_eq!(items[0].score, 0.9);
+    assert_eq!(items[1].score, 0.5 + boost_for(300.0));
+    assert!(items[1].score > items[0].score);
+}
+
+#[test]
+fn test_boost_is_bounded() {
+    assert_eq!(boost_for(0.0), 0.0);
+    assert_eq!(boost_for(100.0), 0.5);
+    assert!(boost_for(1e9) < 1.0);
+}
+
+#[test]
+fn test_recent_aggregates_and_orders_by_frecency() {
+    let history = History::open_in_memory().unwrap();
+    let now = SystemTime::now();
+    history
+        .record("test.apps", "Firefox", now - DAY * 40)
+        .unwrap();
+    history.record("test.files", "notes.md", now - DAY).unwrap();
+    history.record("test.files", "notes.md", now).unwrap();
+    history.record("test.apps", "Terminal", now).unwrap();
+
+    let recent = history.recent(10, now).unwrap();
+
+    let titles = recent.iter().map(|e| e.title.as_str()).collect::<Vec<_>>();
+    assert_eq!(titles, vec!["notes.md", "Terminal", "Firefox"]);
+    assert_eq!(recent[0].count, 2);
+    assert_eq!(recent[0].plugin_id, "test.files");
+    assert_eq!(history.recent(1, now).unwrap().len(), 1);
+}
+
+#[test]
+fn test_open_persists_and_prunes_expired_entries() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("nested").join("history.sqlite3");
+    let now = SystemTime::now();
+    {
+        let history = History::open(&path).unwrap();
+        history
+            .record("test.apps", "Ancient", now - DAY * 400)
+            .unwrap();
+        history.record("test.apps", "Firefox", now).unwrap();
+    }
+
+    let history = History::open(&path).unwrap();
+
+    let titles = history
+        .recent(10, now)
+        .unwrap()
+        .into_iter()
+        .map(|e| e.title)
+        .collect::<Vec<_>>();
+    assert_eq!(titles, vec!["Firefox"]);
+}
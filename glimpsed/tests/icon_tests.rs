@@ -0,0 +1,244 @@
+use glimpse_sdk::{Icon, Match};
+use glimpsed::{
+    config::DaemonConfig,
+    icons::{IconConfig, Icons, bundled_path, rasterize, theme_from_settings},
+    thumbnails::{Thumbnails, file_uri, mtime, stamp},
+};
+use tempfile::TempDir;
+use tokio::sync::mpsc;
+
+const SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="16" height="8"><rect width="16" height="8" fill="red"/></svg>"#;
+
+fn icons(dir: &TempDir) -> Icons {
+    let config = IconConfig {
+        theme: Some("hicolor".to_string()),
+        size: 32,
+        thumbnails: true,
+        inline_bytes: 1024,
+    };
+    Icons::new(&config, &dir.path().join("cache"))
+}
+
+#[test]
+fn test_theme_from_settings() {
+    let settings = "[Settings]\ngtk-theme-name=Adwaita\ngtk-icon-theme-name = \"Papirus\"\n";
+    assert_eq!(theme_from_settings(settings).as_deref(), Some("Papirus"));
+    assert_eq!(
+        theme_from_settings("[Settings]\ngtk-icon-theme-name=\n"),
+        None
+    );
+    assert_eq!(theme_from_settings(""), None);
+}
+
+#[test]
+fn test_rasterize_svg_into_cache() {
+    let dir = TempDir::new().unwrap();
+    let svg = dir.path().join("wide.svg");
+    std::fs::write(&svg, SVG).unwrap();
+    let cache = dir.path().join("cache");
+
+    let png = rasterize(&svg, 32, &cache).unwrap();
+    assert!(png.starts_with(&cache));
+    let bytes = std::fs::read(&png).unwrap();
+    assert_eq!(&bytes[1..4], b"PNG");
+    // width and height of the IHDR chunk
+    assert_eq!(&bytes[16..24], &[0, 0, 0, 32, 0, 0, 0, 32]);
+
+    assert_eq!(rasterize(&svg, 32, &cache).unwrap(), png);
+    assert_ne!(rasterize(&svg, 64, &cache).unwrap(), png);
+
+    let other = dir.path().join("broken.svg");
+    std::fs::write(&other, "not svg").unwrap();
+    assert!(rasterize(&other, 32, &cache).is_err());
+}
+
+#[test]
+fn test_resolve_icons() {
+    let dir = TempDir::new().unwrap();
+    let icons = icons(&dir);
+    let png = dir.path().join("app.png");
+    std::fs::write(&png, b"png").unwrap();
+    let svg = dir.path().join("app.svg");
+    std::fs::write(&svg, SVG).unwrap();
+    let png_path = png.to_string_lossy().to_string();
+
+    assert_eq!(icons.resolve(&Icon::emoji("📋")), Some(Icon::emoji("📋")));
+    assert_eq!(
+        icons.resolve(&Icon::path(&png_path)),
+        Some(Icon::path(&png_path))
+    );
+    assert_eq!(
+        icons.resolve(&Icon::path(format!("file://{}", png_path))),
+        Some(Icon::path(&png_path))
+    );
+    // desktop entries name icons by path too
+    assert_eq!(
+        icons.resolve(&Icon::freedesktop(&png_path)),
+        Some(Icon::path(&png_path))
+    );
+    assert_eq!(icons.resolve(&Icon::path("/nonexistent/icon.png")), None);
+    assert_eq!(
+        icons.resolve(&Icon::freedesktop("glimpse-no-such-icon")),
+        None
+    );
+
+    let Some(Icon::Path { path }) = icons.resolve(&Icon::path(svg.to_string_lossy())) else {
+        panic!("svg not rasterized");
+    };
+    assert!(path.starts_with(dir.path().join("cache").to_str().unwrap()));
+    assert!(path.ends_with("-32.png"));
+}
+
+#[test]
+fn test_resolve_all_drops_missing_icons() {
+    let dir = TempDir::new().unwrap();
+    let icons = icons(&dir);
+    let mut items = vec![
+        Match {
+            title: "Clipboard".to_string(),
+            icon: Some(Icon::emoji("📋")),
+            ..Default::default()
+        },
+        Match {
+            title: "Missing".to_string(),
+            icon: Some(Icon::path("/nonexistent/icon.png")),
+            ..Default::default()
+        },
+        Match {
+            title: "Plain".to_string(),
+            ..Default::default()
+        },
+    ];
+    icons.resolve_all(None, &mut items);
+    assert_eq!(items[0].icon, Some(Icon::emoji("📋")));
+    assert_eq!(items[1].icon, None);
+    assert_eq!(items[2].icon, None);
+}
+
+#[test]
+fn test_resolve_thumbnails() {
+    let dir = TempDir::new().unwrap();
+    let photo = dir.path().join("photo.png");
+    let notes = dir.path().join("notes.txt");
+    std::fs::write(&photo, b"not rendered").unwrap();
+    std::fs::write(&notes, b"text").unwrap();
+    let cache = Thumbnails::new(dir.path().join("thumbnails"));
+    let (render_tx, mut render_rx) = mpsc::unbounded_channel();
+    let icons = icons(&dir).with_thumbnails(cache.clone(), Some(render_tx));
+
+    // a missing thumbnail is queued, the match waits for it
+    let pending = Icon::thumbnail(photo.to_string_lossy());
+    assert_eq!(icons.resolve(&pending), Some(pending.clone()));
+    assert_eq!(render_rx.try_recv().unwrap(), photo);
+    // files no thumbnailer handles get no icon
+    assert_eq!(
+        icons.resolve(&Icon::thumbnail(notes.to_string_lossy())),
+        None
+    );
+    assert!(render_rx.try_recv().is_err());
+
+    let thumbnail = cache.target(&photo);
+    std::fs::create_dir_all(thumbnail.parent().unwrap()).unwrap();
+    let rendered = rasterize_png(&dir);
+    std::fs::write(
+        &thumbnail,
+        stamp(&rendered, &file_uri(&photo), mtime(&photo).unwrap()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        icons.resolve(&Icon::thumbnail(format!("file://{}", photo.display()))),
+        Some(Icon::path(thumbnail.to_string_lossy()))
+    );
+    assert!(render_rx.try_recv().is_err());
+}
+
+#[test]
+fn test_thumbnails_are_not_rendered_when_off() {
+    let dir = TempDir::new().unwrap();
+    let photo = dir.path().join("photo.png");
+    std::fs::write(&photo, b"not rendered").unwrap();
+    let icons = icons(&dir).with_thumbnails(Thumbnails::new(dir.path().join("thumbnails")), None);
+
+    assert_eq!(
+        icons.resolve(&Icon::thumbnail(photo.to_string_lossy())),
+        None
+    );
+}
+
+/// A PNG to stand in for a rendered thumbnail.
+fn rasterize_png(dir: &TempDir) -> Vec<u8> {
+    let svg = dir.path().join("thumbnail.svg");
+    std::fs::write(&svg, SVG).unwrap();
+    std::fs::read(rasterize(&svg, 8, &dir.path().join("cache")).unwrap()).unwrap()
+}
+
+#[test]
+fn test_resolve_bundled_icons() {
+    let dir = TempDir::new().unwrap();
+    let icons = icons(&dir);
+    let plugin = dir.path().join("plugins/demo");
+    let bundle = dir.path().join("plugins/icons");
+    std::fs::create_dir_all(&bundle).unwrap();
+    std::fs::write(&plugin, b"").unwrap();
+    std::fs::write(bundle.join("trash.png"), b"png").unwrap();
+    std::fs::write(bundle.join("large.png"), vec![0; 2048]).unwrap();
+    std::fs::write(bundle.join("logo.svg"), SVG).unwrap();
+    std::fs::write(dir.path().join("plugins/secret.png"), b"png").unwrap();
+    std::os::unix::fs::symlink(
+        dir.path().join("plugins/secret.png"),
+        bundle.join("link.png"),
+    )
+    .unwrap();
+
+    // small icons go inline, larger ones by path
+    assert_eq!(
+        icons.resolve_from(Some(&plugin), &Icon::bundled("trash.png")),
+        Some(Icon::data("image/png", "cG5n"))
+    );
+    assert_eq!(
+        icons.resolve_from(Some(&plugin), &Icon::bundled("large")),
+        Some(Icon::path(
+            bundle
+                .join("large.png")
+                .canonicalize()
+                .unwrap()
+                .to_string_lossy()
+        ))
+    );
+    let Some(Icon::Data { mime, .. }) = icons.resolve_from(Some(&plugin), &Icon::bundled("logo"))
+    else {
+        panic!("expected the rasterized SVG inline");
+    };
+    assert_eq!(mime, "image/png");
+
+    // nothing outside the icons folder is reachable
+    for name in [
+        "../secret.png",
+        "/etc/hostname",
+        "link.png",
+        "",
+        ".",
+        "icons/trash.png",
+    ] {
+        assert_eq!(bundled_path(&plugin, name), None, "{}", name);
+        assert_eq!(
+            icons.resolve_from(Some(&plugin), &Icon::bundled(name)),
+            None
+        );
+    }
+    // icons of unknown plugins resolve to nothing
+    assert_eq!(icons.resolve(&Icon::bundled("trash.png")), None);
+}
+
+#[test]
+fn test_icon_config() {
+    let config = DaemonConfig::from_toml("").unwrap();
+    assert_eq!(config.icons, IconConfig::default());
+    assert_eq!(config.icons.size, 48);
+    assert!(config.icons.thumbnails);
+    assert_eq!(config.icons.inline_bytes, 16 * 1024);
+
+    let config = DaemonConfig::from_toml("[icons]\ntheme = \"Papirus\"\nsize = 64\n").unwrap();
+    assert_eq!(config.icons.theme.as_deref(), Some("Papirus"));
+    assert_eq!(config.icons.size, 64);
+}
@@ -0,0 +1,111 @@
+use std::{sync::Arc, time::Duration};
+
+use glimpse_sdk::Match;
+use glimpsed::{
+    config::DaemonConfig,
+    janitor::{Janitor, JanitorConfig},
+    matches::MatchStore,
+};
+use tokio::{sync::Mutex, time::Instant};
+
+fn create_janitor(store: Arc<Mutex<MatchStore>>) -> Janitor {
+    let mut janitor = Janitor::new(JanitorConfig {
+        enabled: true,
+        idle_secs: 60,
+        malloc_trim: false,
+    });
+    janitor.register(store);
+    janitor
+}
+
+async fn fill_and_reset(store: &Arc<Mutex<MatchStore>>) {
+    let items = (0..1000)
+        .map(|i| Match {
+            title: format!("result {}", i),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+    let mut store = store.lock().await;
+    store.reset(1);
+    store.extend(1, "plugin.a", &items).unwrap();
+    store.reset(2);
+}
+
+#[tokio::test]
+async fn test_sweep_reclaims_match_store_capacity() {
+    let store = Arc::new(Mutex::new(MatchStore::new()));
+    fill_and_reset(&store).await;
+    let janitor = create_janitor(store.clone());
+
+    let reclaimed = janitor.sweep().await;
+
+    assert!(reclaimed > 0);
+    assert_eq!(janitor.stats().runs(), 1);
+    assert_eq!(janitor.stats().reclaimed_bytes(), reclaimed as u64);
+    assert_eq!(janitor.sweep().await, 0);
+}
+
+#[tokio::test]
+async fn test_sweep_if_idle_waits_for_idle_period() {
+    let store = Arc::new(Mutex::new(MatchStore::new()));
+    fill_and_reset(&store).await;
+    let janitor = create_janitor(store);
+    janitor.touch();
+
+    assert_eq!(janitor.sweep_if_idle(Instant::now()).await, None);
+    assert_eq!(janitor.stats().runs(), 0);
+
+    let later = Instant::now() + Duration::from_secs(61);
+    assert!(janitor.sweep_if_idle(later).await.unwrap() > 0);
+}
+
+#[tokio::test]
+async fn test_sweep_if_idle_runs_once_per_idle_period() {
+    let store = Arc::new(Mutex::new(MatchStore::new()));
+    let janitor = create_janitor(store);
+    let later = Instant::now() + Duration::from_secs(61);
+
+    assert!(janitor.sweep_if_idle(later).await.is_some());
+    assert!(janitor.sweep_if_idle(later).await.is_none());
+
+    janitor.touch();
+    let much_later = Instant::now() + Duration::from_secs(200);
+    assert!(janitor.sweep_if_idle(much_later).await.is_some());
+    assert_eq!(janitor.stats().runs(), 2);
+}
+
+#[test]
+fn test_config_defaults_when_section_missing() {
+    let config = DaemonConfig::from_toml("").unwrap();
+    assert_eq!(config, DaemonConfig::default());
+    assert!(config.janitor.enabled);
+    assert_eq!(config.janitor.idle_secs, 300);
+}
+
+#[test]
+fn test_config_janitor_section() {
+    let config = DaemonConfig::from_toml(
+        r#"
+        [janitor]
+        idle_secs = 30
+        malloc_trim = false
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.janitor,
+        JanitorConfig {
+            enabled: true,
+            idle_secs: 30,
+            malloc_trim: false,
+        }
+    );
+}
+
+#[test]
+fn test_config_load_missing_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = DaemonConfig::load_from(&dir.path().join("glimpsed.toml"));
+    assert_eq!(config, DaemonConfig::default());
+}
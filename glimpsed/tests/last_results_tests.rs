@@ -0,0 +1,121 @@
+use std::time::{Duration, SystemTime};
+
+use glimpse_sdk::{Match, Metadata};
+use glimpsed::{
+    config::DaemonConfig,
+    last_results::{LastResults, LastResultsConfig, SNAPSHOT_VERSION, SavedMatch, SearchSnapshot},
+};
+use tempfile::TempDir;
+
+fn snapshot(query: &str, at: SystemTime) -> SearchSnapshot {
+    let items = ["Firefox", "Files"]
+        .into_iter()
+        .map(|title| SavedMatch {
+            plugin_key: "/plugins/apps".to_string(),
+            match_: Match {
+                title: title.to_string(),
+                ..Default::default()
+            },
+        })
+        .collect();
+    SearchSnapshot::new(query.to_string(), items, at)
+}
+
+#[test]
+fn test_save_and_load_round_trip() {
+    let dir = TempDir::new().unwrap();
+    let store = LastResults::new(
+        &dir.path().join("glimpse/last_results.json"),
+        LastResultsConfig::default(),
+    );
+    let now = SystemTime::now();
+    assert!(store.load(now).is_none());
+
+    store.save(&snapshot("fi", now)).unwrap();
+    store.save(&snapshot("fire", now)).unwrap();
+
+    let loaded = store.load(now).unwrap();
+    assert_eq!(loaded, snapshot("fire", now));
+    assert_eq!(loaded.version, SNAPSHOT_VERSION);
+    assert_eq!(loaded.items[0].match_.title, "Firefox");
+}
+
+#[test]
+fn test_expired_snapshot_is_not_loaded() {
+    let dir = TempDir::new().unwrap();
+    let config = LastResultsConfig {
+        ttl_secs: 60,
+        ..Default::default()
+    };
+    let store = LastResults::new(&dir.path().join("last_results.json"), config);
+    let saved_at = SystemTime::now() - Duration::from_secs(120);
+    store.save(&snapshot("fire", saved_at)).unwrap();
+
+    assert!(store.load(SystemTime::now()).is_none());
+    assert!(store.load(saved_at + Duration::from_secs(30)).is_some());
+}
+
+#[test]
+fn test_other_versions_and_garbage_are_ignored() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("last_results.json");
+    let store = LastResults::new(&path, LastResultsConfig::default());
+
+    std::fs::write(&path, r#"{"version": 999, "entries": {}}"#).unwrap();
+    assert!(store.load(SystemTime::now()).is_none());
+
+    std::fs::write(&path, "not json").unwrap();
+    assert!(store.load(SystemTime::now()).is_none());
+}
+
+#[test]
+fn test_disabled_store_neither_saves_nor_loads() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("last_results.json");
+    let config = LastResultsConfig {
+        enabled: false,
+        ..Default::default()
+    };
+    let store = LastResults::new(&path, config);
+
+    store.save(&snapshot("fire", SystemTime::now())).unwrap();
+    assert!(!path.exists());
+    assert!(store.load(SystemTime::now()).is_none());
+}
+
+#[test]
+fn test_clear_drops_snapshot() {
+    let dir = TempDir::new().unwrap();
+    let store = LastResults::new(
+        &dir.path().join("last_results.json"),
+        LastResultsConfig::default(),
+    );
+    store.clear().unwrap();
+    store.save(&snapshot("fire", SystemTime::now())).unwrap();
+    store.clear().unwrap();
+    assert!(store.load(SystemTime::now()).is_none());
+}
+
+#[test]
+fn test_sensitive_and_configured_plugins_are_excluded() {
+    let config = DaemonConfig::from_toml(
+        r#"
+        [last_results]
+        ttl_secs = 30
+        exclude = ["me.aresa.glimpse.ssh"]
+        "#,
+    )
+    .unwrap()
+    .last_results;
+    assert_eq!(config.ttl(), Duration::from_secs(30));
+    assert!(config.enabled);
+
+    let plugin = |id: &str, sensitive: bool| Metadata {
+        id: id.to_string(),
+        sensitive,
+        ..Default::default()
+    };
+    assert!(config.is_excluded(&plugin("me.aresa.glimpse.clipboard", true)));
+    assert!(config.is_excluded(&plugin("me.aresa.glimpse.ssh", false)));
+    assert!(!config.is_excluded(&plugin("me.aresa.glimpse.apps", false)));
+}
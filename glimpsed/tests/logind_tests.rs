@@ -0,0 +1,15 @@
+use glimpse_sdk::SystemCommand;
+use glimpsed::logind::method;
+
+#[test]
+fn test_power_commands_go_to_the_manager() {
+    assert_eq!(method(SystemCommand::PowerOff), ("PowerOff", false));
+    assert_eq!(method(SystemCommand::Reboot), ("Reboot", false));
+    assert_eq!(method(SystemCommand::Suspend), ("Suspend", false));
+}
+
+#[test]
+fn test_session_commands_go_to_the_session() {
+    assert_eq!(method(SystemCommand::LockScreen), ("Lock", true));
+    assert_eq!(method(SystemCommand::LogOut), ("Terminate", true));
+}
@@ -0,0 +1,123 @@
+use glimpse_sdk::{LogLevel, LogRecord};
+use glimpsed::{
+    config::DaemonConfig,
+    logs::{LogConfig, LogStore, MAX_RECORDS, log_name},
+};
+use tempfile::TempDir;
+
+fn record(ts: u64, message: &str) -> LogRecord {
+    LogRecord {
+        ts,
+        level: LogLevel::Info,
+        target: "files".to_string(),
+        message: message.to_string(),
+        location: None,
+        fields: Default::default(),
+    }
+}
+
+#[test]
+fn test_log_name() {
+    assert_eq!(
+        log_name("/usr/lib/glimpsed/plugins/glimpse-plugin-files"),
+        "glimpse-plugin-files"
+    );
+}
+
+#[test]
+fn test_read_since() {
+    let dir = TempDir::new().unwrap();
+    let store = LogStore::new(dir.path(), LogConfig::default());
+    let mut file = store.open("glimpse-plugin-files").unwrap();
+    for ts in 1..=3 {
+        file.append(&record(ts, &format!("record {}", ts))).unwrap();
+    }
+
+    let messages = |since| {
+        store
+            .read("glimpse-plugin-files", since)
+            .unwrap()
+            .into_iter()
+            .map(|record| record.message)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(messages(None), vec!["record 1", "record 2", "record 3"]);
+    assert_eq!(messages(Some(2)), vec!["record 2", "record 3"]);
+    // nothing logged yet
+    assert!(store.read("glimpse-plugin-apps", None).unwrap().is_empty());
+}
+
+#[test]
+fn test_rotation_keeps_generations() {
+    let dir = TempDir::new().unwrap();
+    let line = record(0, "record 00").to_json().len() as u64 + 1;
+    let config = LogConfig {
+        enabled: true,
+        max_bytes: line * 2,
+        keep: 2,
+    };
+    let store = LogStore::new(dir.path(), config);
+    let mut file = store.open("glimpsed").unwrap();
+    for ts in 0..10 {
+        file.append(&record(ts, &format!("record {:02}", ts)))
+            .unwrap();
+    }
+
+    // two records per file, the current one and two rotated ones
+    assert!(dir.path().join("glimpsed.log.2").exists());
+    assert!(!dir.path().join("glimpsed.log.3").exists());
+    let kept = store
+        .read("glimpsed", None)
+        .unwrap()
+        .into_iter()
+        .map(|record| record.ts)
+        .collect::<Vec<_>>();
+    assert_eq!(kept, vec![4, 5, 6, 7, 8, 9]);
+
+    // appending continues where the file left off
+    let mut reopened = store.open("glimpsed").unwrap();
+    reopened.append(&record(10, "record 10")).unwrap();
+    assert_eq!(store.read("glimpsed", Some(8)).unwrap().len(), 3);
+}
+
+#[test]
+fn test_read_keeps_newest_records() {
+    let dir = TempDir::new().unwrap();
+    let store = LogStore::new(dir.path(), LogConfig::default());
+    let mut file = store.open("glimpsed").unwrap();
+    for ts in 0..(MAX_RECORDS as u64 + 5) {
+        file.append(&record(ts, "")).unwrap();
+    }
+    std::fs::write(
+        dir.path().join("glimpsed.log.1"),
+        "not a record\n{\"ts\": 1}\n",
+    )
+    .unwrap();
+
+    let records = store.read("glimpsed", None).unwrap();
+    assert_eq!(records.len(), MAX_RECORDS);
+    assert_eq!(records[0].ts, 5);
+}
+
+#[test]
+fn test_names_stay_in_the_log_dir() {
+    let dir = TempDir::new().unwrap();
+    let store = LogStore::new(&dir.path().join("logs"), LogConfig::default());
+    for name in ["", ".", "..", "../glimpsed", "/etc/passwd"] {
+        assert!(store.read(name, None).is_err(), "{:?}", name);
+        assert!(store.open(name).is_err(), "{:?}", name);
+    }
+}
+
+#[test]
+fn test_config() {
+    let config = DaemonConfig::from_toml("[logs]\nmax_bytes = 4096").unwrap();
+    assert_eq!(
+        config.logs,
+        LogConfig {
+            max_bytes: 4096,
+            ..Default::default()
+        }
+    );
+    assert!(DaemonConfig::default().logs.enabled);
+}
@@ -0,0 +1,482 @@
+use glimpse_sdk::{Action, Detail, Highlights, Match, MatchAction, SnapshotItem};
+use glimpsed::{
+    dispatchers::{Dispatched, RecordingDispatcher, dispatch_action},
+    matches::{ActivationError, MatchStore, categorize, highlight},
+};
+
+fn create_match(title: &str) -> Match {
+    Match {
+        title: title.to_string(),
+        description: format!("{} description", title),
+        actions: vec![MatchAction {
+            title: format!("Copy {}", title),
+            action: Action::Clipboard { text: title.into() },
+            close_on_action: true,
+            alternates: vec![],
+            requires_confirmation: false,
+            confirmation_prompt: None,
+            expand: vec![],
+        }],
+        score: 1.0,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_extend_assigns_stable_ids() {
+    let mut store = MatchStore::new();
+    store.reset(1);
+
+    let first = store
+        .extend(1, "plugin.a", &[create_match("a1"), create_match("a2")])
+        .unwrap();
+    let second = store.extend(1, "plugin.b", &[create_match("b1")]).unwrap();
+
+    assert_eq!(
+        first.iter().map(|m| m.id).collect::<Vec<_>>(),
+        vec![Some(0), Some(1)]
+    );
+    assert_eq!(second[0].id, Some(2));
+    assert_eq!(store.len(), 3);
+    assert_eq!(store.get(1, 1).unwrap().match_.title, "a2");
+    assert_eq!(store.get(1, 2).unwrap().plugin_id, "plugin.b");
+}
+
+#[test]
+fn test_extend_rejects_stale_generation() {
+    let mut store = MatchStore::new();
+    store.reset(1);
+    store.reset(2);
+
+    assert!(
+        store
+            .extend(1, "plugin.a", &[create_match("late")])
+            .is_none()
+    );
+    assert!(store.is_empty());
+}
+
+#[test]
+fn test_reset_clears_matches() {
+    let mut store = MatchStore::new();
+    store.reset(1);
+    store.extend(1, "plugin.a", &[create_match("a1")]).unwrap();
+
+    store.reset(2);
+
+    assert_eq!(store.generation(), 2);
+    assert!(store.is_empty());
+}
+
+#[test]
+fn test_action_rejects_stale_generation() {
+    let mut store = MatchStore::new();
+    store.reset(1);
+    store.extend(1, "plugin.a", &[create_match("a1")]).unwrap();
+    store.reset(2);
+    store.extend(2, "plugin.a", &[create_match("a2")]).unwrap();
+
+    let err = store.action(1, 0, 0).unwrap_err();
+    assert_eq!(
+        err,
+        ActivationError::StaleGeneration {
+            requested: 1,
+            current: 2
+        }
+    );
+}
+
+#[test]
+fn test_action_rejects_unknown_match_and_action() {
+    let mut store = MatchStore::new();
+    store.reset(1);
+    store.extend(1, "plugin.a", &[create_match("a1")]).unwrap();
+
+    assert_eq!(
+        store.action(1, 5, 0).unwrap_err(),
+        ActivationError::UnknownMatch(5)
+    );
+    assert_eq!(
+        store.action(1, 0, 3).unwrap_err(),
+        ActivationError::UnknownAction {
+            match_id: 0,
+            action: 3
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_activation_dispatches_stored_action() {
+    let mut store = MatchStore::new();
+    store.reset(7);
+    store
+        .extend(7, "plugin.a", &[create_match("a1"), create_match("a2")])
+        .unwrap();
+    let dispatcher = RecordingDispatcher::new();
+
+    let (holder, match_action) = store.action(7, 1, 0).unwrap();
+    assert_eq!(holder.plugin_id, "plugin.a");
+    dispatch_action(&dispatcher, &match_action.action, None)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        dispatcher.calls(),
+        vec![Dispatched::Clipboard {
+            text: "a2".to_string()
+        }]
+    );
+}
+
+#[test]
+fn test_finish_completes_after_all_expected_plugins() {
+    let mut store = MatchStore::new();
+    store.reset(1);
+    store.expect("plugin.a");
+    store.expect("plugin.b");
+
+    assert!(!store.finish(1, "plugin.a"));
+    assert!(!store.finish(1, "plugin.a"));
+    assert!(!store.is_complete());
+    assert!(store.finish(1, "plugin.b"));
+    assert!(store.is_complete());
+}
+
+#[test]
+fn test_finish_ignores_stale_generation() {
+    let mut store = MatchStore::new();
+    store.reset(1);
+    store.expect("plugin.a");
+    store.reset(2);
+    store.expect("plugin.a");
+
+    assert!(!store.finish(1, "plugin.a"));
+    assert!(store.finish(2, "plugin.a"));
+}
+
+#[test]
+fn test_snapshot_orders_by_score() {
+    let mut store = MatchStore::new();
+    store.reset(1);
+    let mut low = create_match("low");
+    low.score = 0.2;
+    let mut high = create_match("high");
+    high.score = 0.9;
+    let mut tie = create_match("tie");
+    tie.score = 0.9;
+    store.extend(1, "plugin.a", &[low, high]).unwrap();
+    store.extend(1, "plugin.b", &[tie]).unwrap();
+
+    let snapshot = store.snapshot();
+
+    assert_eq!(
+        snapshot,
+        vec![
+            SnapshotItem { id: 1, score: 0.9 },
+            SnapshotItem { id: 2, score: 0.9 },
+            SnapshotItem { id: 0, score: 0.2 },
+        ]
+    );
+}
+
+#[test]
+fn test_snapshot_keeps_arrival_order_in_passthrough() {
+    let mut store = MatchStore::new();
+    store.reset(1);
+    store.set_passthrough(true);
+    let mut low = create_match("low");
+    low.score = 0.2;
+    let mut high = create_match("high");
+    high.score = 0.9;
+    store.extend(1, "plugin.a", &[low, high]).unwrap();
+
+    assert_eq!(
+        store.snapshot(),
+        vec![
+            SnapshotItem { id: 0, score: 0.2 },
+            SnapshotItem { id: 1, score: 0.9 },
+        ]
+    );
+
+    store.reset(2);
+    assert!(store.snapshot().is_empty());
+    store.extend(2, "plugin.a", &[create_match("a")]).unwrap();
+    let mut best = create_match("best");
+    best.score = 2.0;
+    store.extend(2, "plugin.a", &[best]).unwrap();
+    assert_eq!(store.snapshot()[0].id, 1, "reset turns ranking back on");
+}
+
+#[test]
+fn test_persistable_leaves_out_private_plugins() {
+    let mut store = MatchStore::new();
+    store.reset(1);
+    store.set_query("fire");
+    store.expect("plugin.apps");
+    store.expect_private("plugin.clipboard");
+    store.extend(1, "plugin.apps", &[create_match("Firefox")]);
+    store.extend(1, "plugin.clipboard", &[create_match("hunter2")]);
+
+    let persistable = store.persistable().unwrap();
+    assert_eq!(persistable.len(), 1);
+    assert_eq!(persistable[0].match_.title, "Firefox");
+    assert_eq!(store.query(), "fire");
+
+    // a search routed to a private plugin alone is not persisted at all
+    store.reset(2);
+    store.expect_private("plugin.clipboard");
+    store.extend(2, "plugin.clipboard", &[create_match("hunter2")]);
+    assert!(store.persistable().is_none());
+    assert_eq!(store.query(), "");
+}
+
+fn scored(title: &str, score: f64) -> Match {
+    Match {
+        score,
+        ..create_match(title)
+    }
+}
+
+fn titles(items: &[Match]) -> Vec<&str> {
+    items.iter().map(|m| m.title.as_str()).collect()
+}
+
+#[test]
+fn test_deliver_holds_matches_past_the_page() {
+    let mut store = MatchStore::new();
+    store.set_page_size(2);
+    store.reset(1);
+    store.expect("plugin.a");
+
+    let stamped = store
+        .extend(1, "plugin.a", &[scored("a", 0.1), scored("b", 0.2)])
+        .unwrap();
+    assert_eq!(titles(&store.deliver(stamped)), vec!["a", "b"]);
+    let stamped = store.extend(1, "plugin.a", &[scored("c", 0.9)]).unwrap();
+    assert!(store.deliver(stamped).is_empty());
+
+    // the held match outranks a sent one once the search completes
+    assert!(store.finish(1, "plugin.a"));
+    assert_eq!(titles(&store.fill_page()), vec!["c"]);
+    assert_eq!(
+        store
+            .snapshot()
+            .iter()
+            .map(|item| item.id)
+            .collect::<Vec<_>>(),
+        vec![2, 1]
+    );
+}
+
+#[test]
+fn test_fallback_matches_wait_for_the_search_to_complete() {
+    let mut store = MatchStore::new();
+    store.set_fallback_score(0.8);
+    store.reset(1);
+    store.expect("plugin.a");
+    store.expect("plugin.web");
+    store.expect_fallback("plugin.web");
+
+    let stamped = store
+        .extend(1, "plugin.web", &[scored("search the web", 0.1)])
+        .unwrap();
+    assert!(store.deliver(stamped).is_empty());
+    assert!(store.snapshot().is_empty());
+    let stamped = store.extend(1, "plugin.a", &[scored("a", 0.5)]).unwrap();
+    assert_eq!(titles(&store.deliver(stamped)), vec!["a"]);
+
+    store.finish(1, "plugin.web");
+    assert!(store.finish(1, "plugin.a"));
+    assert_eq!(titles(&store.fill_page()), vec!["search the web"]);
+    assert_eq!(store.snapshot().len(), 2);
+}
+
+#[test]
+fn test_fallback_matches_stay_hidden_behind_a_good_match() {
+    let mut store = MatchStore::new();
+    store.set_fallback_score(0.8);
+    store.reset(1);
+    store.expect("plugin.a");
+    store.expect("plugin.web");
+    store.expect_fallback("plugin.web");
+
+    store
+        .extend(1, "plugin.web", &[scored("search the web", 0.1)])
+        .unwrap();
+    store.extend(1, "plugin.a", &[scored("a", 0.9)]).unwrap();
+    store.finish(1, "plugin.web");
+    assert!(store.finish(1, "plugin.a"));
+
+    assert!(store.fill_page().iter().all(|item| item.title == "a"));
+    assert_eq!(
+        store
+            .snapshot()
+            .iter()
+            .map(|item| item.id)
+            .collect::<Vec<_>>(),
+        vec![1]
+    );
+
+    // a new search forgets the fallback plugins of the last one
+    store.reset(2);
+    store.expect("plugin.web");
+    let stamped = store
+        .extend(2, "plugin.web", &[scored("search the web", 0.1)])
+        .unwrap();
+    assert_eq!(store.deliver(stamped).len(), 1);
+}
+
+#[test]
+fn test_more_grows_the_page() {
+    let mut store = MatchStore::new();
+    store.set_page_size(1);
+    store.reset(1);
+    store.expect("plugin.a");
+    let stamped = store
+        .extend(1, "plugin.a", &[scored("a", 0.9), scored("b", 0.5)])
+        .unwrap();
+    store.deliver(stamped);
+    store.finish(1, "plugin.a");
+    store.fill_page();
+
+    assert_eq!(store.more(2, 1, 1), None, "stale generation");
+    assert_eq!(
+        store.more(1, 1, 1),
+        Some(vec![]),
+        "held matches fill the page"
+    );
+    assert!(store.is_complete());
+    assert_eq!(titles(&store.fill_page()), vec!["b"]);
+    assert_eq!(store.snapshot().len(), 2);
+    assert!(store.fill_page().is_empty(), "the client holds the page");
+}
+
+#[test]
+fn test_more_asks_paginated_plugins() {
+    let mut store = MatchStore::new();
+    store.set_page_size(1);
+    store.reset(1);
+    store.expect("plugin.a");
+    store.expect_pages("plugin.a");
+    store.expect("plugin.b");
+    store.expect_pages("plugin.b");
+    store.extend(1, "plugin.a", &[scored("a", 0.9)]).unwrap();
+    store.finish(1, "plugin.a");
+    store.finish(1, "plugin.b");
+
+    // plugin.b sent nothing, it has no more to give
+    assert_eq!(
+        store.more(1, 1, 10),
+        Some(vec![("plugin.a".to_string(), 1)])
+    );
+    assert!(!store.is_complete());
+    assert!(store.finish(1, "plugin.a"));
+
+    // nothing arrived since it was asked
+    assert_eq!(store.more(1, 11, 10), Some(vec![]));
+}
+
+#[test]
+fn test_without_page_size_everything_is_delivered() {
+    let mut store = MatchStore::new();
+    store.reset(1);
+
+    let stamped = store
+        .extend(1, "plugin.a", &[create_match("a"), create_match("b")])
+        .unwrap();
+    assert_eq!(store.deliver(stamped).len(), 2);
+    assert_eq!(store.more(1, 2, 10), Some(vec![]));
+    assert!(store.fill_page().is_empty());
+}
+
+#[test]
+fn test_prefetch_asks_for_each_detail_once() {
+    let mut store = MatchStore::new();
+    store.reset(1);
+    let described = Match {
+        detail: Some(Detail::text("known")),
+        ..create_match("b")
+    };
+    store
+        .extend(
+            1,
+            "plugin.a",
+            &[create_match("a"), described, create_match("c")],
+        )
+        .unwrap();
+
+    assert_eq!(store.prefetch(1, &[2, 1, 0, 9]), vec![2, 0]);
+    assert!(store.prefetch(1, &[0, 2]).is_empty());
+    assert!(store.prefetch(2, &[0]).is_empty());
+
+    assert!(store.set_detail(1, 0, Detail::text("fetched")));
+    assert_eq!(
+        store.get(1, 0).unwrap().match_.detail,
+        Some(Detail::text("fetched"))
+    );
+    assert!(!store.set_detail(2, 0, Detail::text("stale")));
+    assert!(!store.set_detail(1, 9, Detail::text("unknown")));
+
+    store.reset(2);
+    store.extend(2, "plugin.a", &[create_match("a")]).unwrap();
+    assert_eq!(store.prefetch(2, &[0]), vec![0]);
+}
+
+#[test]
+fn test_rows_follow_the_ranking() {
+    let mut store = MatchStore::new();
+    store.reset(1);
+    let ranked = |title: &str, score: f64| Match {
+        score,
+        ..create_match(title)
+    };
+    store
+        .extend(
+            1,
+            "plugin.a",
+            &[ranked("low", 0.1), ranked("high", 0.9), ranked("mid", 0.5)],
+        )
+        .unwrap();
+
+    assert_eq!(store.rows(1, 0..=1), vec![1, 2]);
+    assert_eq!(store.rows(1, 2..=9), vec![0]);
+    assert!(store.rows(1, 5..=9).is_empty());
+    assert!(store.rows(2, 0..=1).is_empty());
+}
+
+#[test]
+fn test_categorize_keeps_categories_plugins_set() {
+    let mut items = vec![
+        create_match("firefox"),
+        Match {
+            category: Some("Bookmarks".to_string()),
+            ..create_match("rust docs")
+        },
+    ];
+    categorize(&mut items, "Apps");
+
+    assert_eq!(items[0].category.as_deref(), Some("Apps"));
+    assert_eq!(items[1].category.as_deref(), Some("Bookmarks"));
+}
+
+#[test]
+fn test_highlight_marks_fuzzy_matched_characters() {
+    let mut items = vec![
+        create_match("Firefox"),
+        create_match("4"),
+        Match {
+            highlights: Some(Highlights {
+                title: vec![(0, 1)],
+                description: vec![],
+            }),
+            ..create_match("Fire")
+        },
+    ];
+    highlight(&mut items, "fifo");
+
+    let highlights = items[0].highlights.as_ref().unwrap();
+    assert_eq!(highlights.title, vec![(0, 2), (4, 6)]);
+    assert_eq!(highlights.description, vec![(0, 2), (4, 6)]);
+    assert_eq!(items[1].highlights, None);
+    assert_eq!(items[2].highlights.as_ref().unwrap().title, vec![(0, 1)]);
+}
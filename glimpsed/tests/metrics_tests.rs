@@ -0,0 +1,124 @@
+use std::{sync::Arc, time::Duration};
+
+use glimpsed::{
+    config::DaemonConfig,
+    metrics::{self, Metrics},
+    stats::Outcome,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+fn answered(millis: u64) -> Outcome {
+    Outcome::Answered(Duration::from_millis(millis))
+}
+
+#[test]
+fn test_summary() {
+    let metrics = Metrics::default();
+    for millis in [3, 8, 8, 40] {
+        metrics.search("me.aresa.glimpse.apps", answered(millis));
+    }
+    metrics.search(
+        "me.aresa.glimpse.apps",
+        Outcome::Failed(Duration::from_millis(1)),
+    );
+    metrics.search("me.aresa.glimpse.apps", Outcome::TimedOut);
+    metrics.results("me.aresa.glimpse.apps", 12);
+    metrics.restarted("me.aresa.glimpse.files");
+
+    let summary = metrics.summary();
+    assert_eq!(summary.len(), 2);
+    let apps = &summary[0];
+    assert_eq!(apps.plugin_id, "me.aresa.glimpse.apps");
+    assert_eq!(apps.searches, 6);
+    assert_eq!(apps.results, 12);
+    assert_eq!((apps.errors, apps.timeouts), (1, 1));
+    assert!((apps.error_rate - 1.0 / 3.0).abs() < 1e-9);
+    // timeouts have no latency
+    assert_eq!(apps.average_latency_ms, 12);
+    assert_eq!(apps.p95_latency_ms, 40);
+    assert_eq!(apps.max_latency_ms, 40);
+    assert_eq!(summary[1].restarts, 1);
+    assert_eq!(summary[1].searches, 0);
+}
+
+#[test]
+fn test_p95_stays_within_latencies_seen() {
+    let metrics = Metrics::default();
+    for _ in 0..19 {
+        metrics.search("a", answered(7));
+    }
+    metrics.search("a", answered(9000));
+    let summary = metrics.summary();
+    assert_eq!(summary[0].p95_latency_ms, 10);
+    assert_eq!(summary[0].max_latency_ms, 9000);
+    // all of them over the last bound
+    metrics.search("b", answered(7000));
+    assert_eq!(metrics.summary()[1].p95_latency_ms, 7000);
+}
+
+#[test]
+fn test_prometheus_text() {
+    let metrics = Metrics::default();
+    metrics.search("me.aresa.glimpse.apps", answered(20));
+    metrics.search("me.aresa.glimpse.apps", answered(700));
+    metrics.results("me.aresa.glimpse.apps", 3);
+    metrics.restarted("odd\"id");
+
+    let text = metrics.prometheus();
+    assert!(text.contains("# TYPE glimpse_plugin_searches_total counter\n"));
+    assert!(text.contains("glimpse_plugin_searches_total{plugin=\"me.aresa.glimpse.apps\"} 2\n"));
+    assert!(text.contains("glimpse_plugin_results_total{plugin=\"me.aresa.glimpse.apps\"} 3\n"));
+    assert!(text.contains("glimpse_plugin_restarts_total{plugin=\"odd\\\"id\"} 1\n"));
+    assert!(text.contains("# TYPE glimpse_plugin_search_latency_seconds histogram\n"));
+    assert!(text.contains(
+        "glimpse_plugin_search_latency_seconds_bucket{plugin=\"me.aresa.glimpse.apps\",le=\"0.025\"} 1\n"
+    ));
+    assert!(text.contains(
+        "glimpse_plugin_search_latency_seconds_bucket{plugin=\"me.aresa.glimpse.apps\",le=\"1\"} 2\n"
+    ));
+    assert!(text.contains(
+        "glimpse_plugin_search_latency_seconds_bucket{plugin=\"me.aresa.glimpse.apps\",le=\"+Inf\"} 2\n"
+    ));
+    assert!(text.contains(
+        "glimpse_plugin_search_latency_seconds_sum{plugin=\"me.aresa.glimpse.apps\"} 0.72\n"
+    ));
+    assert!(text.contains(
+        "glimpse_plugin_search_latency_seconds_count{plugin=\"me.aresa.glimpse.apps\"} 2\n"
+    ));
+}
+
+#[tokio::test]
+async fn test_serve_prometheus() {
+    let listener = metrics::bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let metrics = Arc::new(Metrics::default());
+    metrics.search("me.aresa.glimpse.apps", answered(20));
+    tokio::spawn(metrics::serve_prometheus(Arc::new(listener), metrics));
+
+    let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+    stream
+        .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(
+        response.contains("glimpse_plugin_searches_total{plugin=\"me.aresa.glimpse.apps\"} 1\n")
+    );
+}
+
+#[tokio::test]
+async fn test_metrics_stay_local() {
+    assert!(metrics::bind("0.0.0.0:0".parse().unwrap()).await.is_err());
+}
+
+#[test]
+fn test_config() {
+    let config = DaemonConfig::from_toml("[metrics]\nprometheus = \"127.0.0.1:9464\"").unwrap();
+    assert_eq!(
+        config.metrics.prometheus,
+        Some("127.0.0.1:9464".parse().unwrap())
+    );
+    assert_eq!(DaemonConfig::default().metrics.prometheus, None);
+}
@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use glimpse_sdk::{Match, Message, MethodResult, RpcError, SnapshotItem};
+use glimpsed::outbox::{Outbox, OutboxConfig, OutboxError};
+
+fn create_outbox(capacity: usize, stall_secs: u64) -> Outbox {
+    Outbox::new(OutboxConfig {
+        capacity,
+        stall_secs,
+    })
+}
+
+fn matches(id: usize, title: &str) -> Message {
+    Message::Response {
+        id,
+        error: None,
+        result: Some(MethodResult::Matches {
+            items: vec![Match {
+                title: title.to_string(),
+                ..Default::default()
+            }],
+        }),
+        plugin_id: Some("test.plugin".to_string()),
+    }
+}
+
+fn snapshot(id: usize) -> Message {
+    Message::Response {
+        id,
+        error: None,
+        result: Some(MethodResult::Snapshot {
+            items: vec![SnapshotItem { id: 0, score: 1.0 }],
+        }),
+        plugin_id: None,
+    }
+}
+
+fn update(id: usize, topic: &str, title: &str) -> Message {
+    Message::Response {
+        id,
+        error: None,
+        result: Some(MethodResult::Update {
+            topic: topic.to_string(),
+            items: vec![Match {
+                title: title.to_string(),
+                ..Default::default()
+            }],
+        }),
+        plugin_id: Some("test.plugin".to_string()),
+    }
+}
+
+fn error(id: usize) -> Message {
+    Message::Response {
+        id,
+        error: Some(RpcError::rejected("unknown match")),
+        result: None,
+        plugin_id: None,
+    }
+}
+
+#[tokio::test]
+async fn test_pop_returns_messages_in_order() {
+    let outbox = create_outbox(10, 30);
+    outbox.push(matches(1, "a")).unwrap();
+    outbox.push(snapshot(1)).unwrap();
+
+    assert_eq!(outbox.pop().await, Some(matches(1, "a")));
+    assert_eq!(outbox.pop().await, Some(snapshot(1)));
+    assert!(outbox.is_empty());
+}
+
+#[tokio::test]
+async fn test_supersede_drops_queued_results_of_older_searches() {
+    let outbox = create_outbox(10, 30);
+    outbox.push(matches(1, "old")).unwrap();
+    outbox.push(error(7)).unwrap();
+    outbox.push(matches(2, "new")).unwrap();
+
+    outbox.supersede(2);
+    outbox.push(snapshot(1)).unwrap();
+
+    assert_eq!(outbox.pop().await, Some(error(7)));
+    assert_eq!(outbox.pop().await, Some(matches(2, "new")));
+    assert!(outbox.is_empty());
+    assert_eq!(outbox.dropped(), 2);
+}
+
+#[tokio::test]
+async fn test_updates_replace_queued_update_for_same_topic() {
+    let outbox = create_outbox(10, 30);
+    outbox.push(update(3, "player", "paused")).unwrap();
+    outbox.push(update(3, "volume", "50%")).unwrap();
+    outbox.push(update(3, "player", "playing")).unwrap();
+
+    assert_eq!(outbox.len(), 2);
+    assert_eq!(outbox.pop().await, Some(update(3, "player", "playing")));
+    assert_eq!(outbox.pop().await, Some(update(3, "volume", "50%")));
+}
+
+#[tokio::test]
+async fn test_full_queue_evicts_search_results_first() {
+    let outbox = create_outbox(2, 30);
+    outbox.push(error(7)).unwrap();
+    outbox.push(matches(1, "a")).unwrap();
+
+    outbox.push(matches(1, "b")).unwrap();
+
+    assert_eq!(outbox.len(), 2);
+    assert_eq!(outbox.dropped(), 1);
+    assert_eq!(outbox.pop().await, Some(error(7)));
+    assert_eq!(outbox.pop().await, Some(matches(1, "b")));
+}
+
+#[tokio::test]
+async fn test_stalled_client_is_disconnected() {
+    let outbox = create_outbox(1, 0);
+    outbox.push(matches(1, "a")).unwrap();
+
+    assert_eq!(outbox.push(matches(1, "b")), Err(OutboxError::Closed));
+    assert!(outbox.is_closed());
+    assert_eq!(outbox.push(error(2)), Err(OutboxError::Closed));
+    assert_eq!(outbox.pop().await, None);
+}
+
+#[tokio::test]
+async fn test_close_wakes_waiting_reader() {
+    let outbox = std::sync::Arc::new(create_outbox(10, 30));
+    let reader = tokio::spawn({
+        let outbox = outbox.clone();
+        async move { outbox.pop().await }
+    });
+    let closed = tokio::spawn({
+        let outbox = outbox.clone();
+        async move { outbox.closed().await }
+    });
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    outbox.close();
+
+    assert_eq!(reader.await.unwrap(), None);
+    closed.await.unwrap();
+}
+
+#[test]
+fn test_config_outbox_section() {
+    let config = glimpsed::config::DaemonConfig::from_toml(
+        r#"
+        [outbox]
+        capacity = 32
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.outbox,
+        OutboxConfig {
+            capacity: 32,
+            stall_secs: 30,
+        }
+    );
+}
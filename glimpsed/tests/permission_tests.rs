@@ -0,0 +1,364 @@
+use std::path::{Path, PathBuf};
+
+use glimpse_sdk::{
+    Action, Capability, ConnectionTarget, Metadata, Permission, RpcError, SystemCommand, file_uri,
+};
+use glimpsed::permissions::{
+    Grants, PermissionError, is_within, opened_path, required, with_configured_roots,
+};
+use tempfile::TempDir;
+
+fn metadata(permissions: Vec<Permission>) -> Metadata {
+    Metadata {
+        id: "test".to_string(),
+        capabilities: vec![Capability::Permissions],
+        permissions,
+        ..Default::default()
+    }
+}
+
+fn exec() -> Action {
+    Action::Exec {
+        command: "ls".to_string(),
+        args: vec![],
+    }
+}
+
+fn open(uri: &str) -> Action {
+    Action::Open {
+        uri: uri.to_string(),
+    }
+}
+
+#[test]
+fn test_required_permissions() {
+    assert_eq!(required(&exec()), Some(Permission::Exec));
+    assert_eq!(
+        required(&Action::Clipboard {
+            text: "text".into()
+        }),
+        Some(Permission::Clipboard)
+    );
+    assert_eq!(
+        required(&Action::TypeText {
+            text: "text".into()
+        }),
+        Some(Permission::Keyboard)
+    );
+    assert_eq!(
+        required(&Action::System {
+            command: SystemCommand::PowerOff
+        }),
+        Some(Permission::Power)
+    );
+    assert_eq!(
+        required(&Action::Connection {
+            target: ConnectionTarget::Network {
+                uuid: "7d5e6c1a-8f3b-4a52-9c0e-2b1f4d6a8e90".to_string()
+            },
+            connect: true,
+        }),
+        Some(Permission::Devices)
+    );
+    assert_eq!(
+        required(&open("https://example.com")),
+        Some(Permission::Network)
+    );
+    assert_eq!(
+        required(&open("file:///tmp/a.txt")),
+        Some(Permission::HomeRead)
+    );
+    assert_eq!(required(&open("/tmp/a.txt")), Some(Permission::HomeRead));
+    assert_eq!(
+        required(&open("file:/etc/shadow")),
+        Some(Permission::HomeRead)
+    );
+    assert_eq!(
+        required(&open("FILE:////etc/shadow")),
+        Some(Permission::HomeRead)
+    );
+    assert_eq!(
+        required(&open("smb://server/share")),
+        Some(Permission::Network)
+    );
+    assert_eq!(
+        required(&open("sftp://host/etc/passwd")),
+        Some(Permission::Network)
+    );
+    assert_eq!(
+        required(&open("mailto:me@example.com")),
+        Some(Permission::Network)
+    );
+}
+
+#[test]
+fn test_undeclared_permission_is_refused() {
+    let grants = Grants::in_memory();
+    let result = grants.check(&metadata(vec![Permission::Clipboard]), &exec());
+    assert_eq!(
+        result,
+        Err(PermissionError::Undeclared {
+            plugin_id: "test".to_string(),
+            permission: Permission::Exec,
+        })
+    );
+    assert_eq!(result.unwrap_err().to_rpc().code, RpcError::REJECTED);
+}
+
+#[test]
+fn test_plain_permission_needs_no_grant() {
+    let grants = Grants::in_memory();
+    let metadata = metadata(vec![Permission::Network]);
+    assert!(
+        grants
+            .check(&metadata, &open("https://example.com"))
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_sensitive_permission_needs_grant() {
+    let mut grants = Grants::in_memory();
+    let metadata = metadata(vec![Permission::Exec]);
+
+    let err = grants.check(&metadata, &exec()).unwrap_err();
+    let rpc = err.to_rpc();
+    assert_eq!(rpc.code, RpcError::PERMISSION_REQUIRED);
+    assert_eq!(
+        rpc.data,
+        Some(serde_json::json!({"plugin_id": "test", "permission": "exec"}))
+    );
+
+    grants.grant("test", Permission::Exec).unwrap();
+    assert!(grants.check(&metadata, &exec()).is_ok());
+}
+
+#[test]
+fn test_plugins_without_manifest_declare_nothing() {
+    let mut grants = Grants::in_memory();
+    grants.grant("legacy", Permission::Exec).unwrap();
+    // lists exec but predates manifests, so nothing it lists counts
+    let metadata = Metadata {
+        id: "legacy".to_string(),
+        permissions: vec![Permission::Exec],
+        ..Default::default()
+    };
+    assert_eq!(
+        grants.check(&metadata, &exec()),
+        Err(PermissionError::Undeclared {
+            plugin_id: "legacy".to_string(),
+            permission: Permission::Exec,
+        })
+    );
+    let launch = Action::Launch {
+        app_id: "firefox.desktop".to_string(),
+        action: None,
+    };
+    assert!(grants.check(&metadata, &launch).is_ok());
+}
+
+#[test]
+fn test_activation_without_metadata_is_refused() {
+    let grants = Grants::in_memory();
+    assert_eq!(
+        grants.check_plugin("unnamed", None, &exec()),
+        Err(PermissionError::Undeclared {
+            plugin_id: "unnamed".to_string(),
+            permission: Permission::Exec,
+        })
+    );
+    assert!(
+        grants
+            .check_plugin("unnamed", None, &open("file:/etc/shadow"))
+            .is_err()
+    );
+    let metadata = metadata(vec![Permission::Network]);
+    assert!(
+        grants
+            .check_plugin("test", Some(&metadata), &open("https://example.com"))
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_grants_persist() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("glimpse/permissions.json");
+
+    let mut grants = Grants::load(&path);
+    assert!(!grants.is_granted("test", Permission::Exec));
+    grants.grant("test", Permission::Exec).unwrap();
+
+    let reloaded = Grants::load(&path);
+    assert!(reloaded.is_granted("test", Permission::Exec));
+    assert!(!reloaded.is_granted("test", Permission::Secrets));
+    assert!(!reloaded.is_granted("other", Permission::Exec));
+}
+
+#[test]
+fn test_unknown_permissions_deserialize() {
+    let metadata: Metadata = serde_json::from_value(serde_json::json!({
+        "id": "test",
+        "name": "Test",
+        "version": "1.0.0",
+        "description": "",
+        "author": "",
+        "permissions": ["exec", "camera"],
+    }))
+    .unwrap();
+    assert_eq!(
+        metadata.permissions,
+        vec![Permission::Exec, Permission::Unknown]
+    );
+}
+
+#[test]
+fn test_opened_path() {
+    assert_eq!(
+        opened_path("file:///tmp/My%20Notes/a.txt"),
+        Some(PathBuf::from("/tmp/My Notes/a.txt"))
+    );
+    // what plugins build with `file_uri` opens the file they found
+    let found = Path::new("/tmp/100% done #2?.txt");
+    assert_eq!(opened_path(&file_uri(found)), Some(found.to_path_buf()));
+    assert_eq!(
+        opened_path("file://localhost/tmp/a.txt"),
+        Some(PathBuf::from("/tmp/a.txt"))
+    );
+    assert_eq!(
+        opened_path("/tmp/docs/../../etc/passwd"),
+        Some(PathBuf::from("/etc/passwd"))
+    );
+    assert_eq!(
+        opened_path("file:///tmp/docs/%2e%2e/secret"),
+        Some(PathBuf::from("/tmp/secret"))
+    );
+    assert_eq!(
+        opened_path("~/a.txt"),
+        dirs::home_dir().map(|home| home.join("a.txt"))
+    );
+    assert_eq!(
+        opened_path("file:/etc/shadow"),
+        Some(PathBuf::from("/etc/shadow"))
+    );
+    assert_eq!(
+        opened_path("file:////etc/shadow"),
+        Some(PathBuf::from("/etc/shadow"))
+    );
+    assert_eq!(
+        opened_path("file:///tmp/a.txt#page=2"),
+        Some(PathBuf::from("/tmp/a.txt"))
+    );
+    assert_eq!(opened_path("file://server/share/a.txt"), None);
+    assert_eq!(opened_path("file:a.txt"), None);
+    assert_eq!(opened_path("a.txt"), None);
+    assert_eq!(opened_path("https://example.com/a.txt"), None);
+    assert_eq!(opened_path("smb://server/share/a.txt"), None);
+}
+
+#[test]
+fn test_declared_paths_limit_files() {
+    let grants = Grants::in_memory();
+    let docs = Metadata {
+        paths: vec!["/tmp/docs".to_string()],
+        ..metadata(vec![Permission::HomeRead])
+    };
+    let roots = &docs.paths;
+    assert!(is_within(Path::new("/tmp/docs/a.txt"), roots));
+    assert!(!is_within(Path::new("/tmp/docs-old/a.txt"), roots));
+
+    assert!(grants.check(&docs, &open("/tmp/docs/a.txt")).is_ok());
+    assert!(
+        grants
+            .check(&docs, &open("file:///tmp/docs/sub/b.pdf"))
+            .is_ok()
+    );
+    let result = grants.check(&docs, &open("file:///tmp/docs/../keys"));
+    assert_eq!(
+        result,
+        Err(PermissionError::OutsidePaths {
+            plugin_id: "test".to_string(),
+            path: PathBuf::from("/tmp/keys"),
+        })
+    );
+    assert_eq!(result.unwrap_err().to_rpc().code, RpcError::REJECTED);
+}
+
+#[test]
+fn test_no_declared_paths_limit_files_to_home() {
+    let grants = Grants::in_memory();
+    let metadata = metadata(vec![Permission::HomeRead]);
+    assert_eq!(
+        grants.check(&metadata, &open("/etc/hosts")),
+        Err(PermissionError::OutsidePaths {
+            plugin_id: "test".to_string(),
+            path: PathBuf::from("/etc/hosts"),
+        })
+    );
+    assert!(grants.check(&metadata, &open("file:/etc/shadow")).is_err());
+    assert!(grants.check(&metadata, &open("~/notes.txt")).is_ok());
+    if let Some(home) = dirs::home_dir() {
+        let uri = format!("file://{}", home.join("notes.txt").display());
+        assert!(grants.check(&metadata, &open(&uri)).is_ok());
+    }
+}
+
+#[test]
+fn test_configured_roots_are_declared_paths() {
+    let grants = Grants::in_memory();
+    let metadata = metadata(vec![Permission::HomeRead]);
+    let config = serde_json::json!({"roots": ["/mnt/data", "Documents"], "max_depth": 3});
+    let configured = with_configured_roots(&metadata, Some(&config));
+
+    assert_eq!(configured.paths, vec!["~", "/mnt/data", "~/Documents"]);
+    assert!(
+        grants
+            .check(&configured, &open("file:///mnt/data/report%20final.pdf"))
+            .is_ok()
+    );
+    assert!(grants.check(&configured, &open("~/notes.txt")).is_ok());
+    assert!(
+        grants
+            .check(&configured, &open("/mnt/other/report.pdf"))
+            .is_err()
+    );
+    assert!(
+        grants
+            .check(&metadata, &open("/mnt/data/report.pdf"))
+            .is_err()
+    );
+
+    // without a roots setting the declared paths stay as they are
+    let unconfigured = with_configured_roots(&metadata, Some(&serde_json::json!({})));
+    assert!(unconfigured.paths.is_empty());
+}
+
+#[test]
+fn test_sequence_needs_every_step_permission() {
+    let mut grants = Grants::in_memory();
+    let sequence = Action::Sequence {
+        actions: vec![
+            Action::Clipboard {
+                text: "text".into(),
+            },
+            exec(),
+        ],
+    };
+    assert_eq!(required(&sequence), None);
+    assert_eq!(
+        grants.check(&metadata(vec![Permission::Clipboard]), &sequence),
+        Err(PermissionError::Undeclared {
+            plugin_id: "test".to_string(),
+            permission: Permission::Exec,
+        })
+    );
+    grants.grant("test", Permission::Exec).unwrap();
+    assert!(
+        grants
+            .check(
+                &metadata(vec![Permission::Clipboard, Permission::Exec]),
+                &sequence
+            )
+            .is_ok()
+    );
+}
@@ -0,0 +1,86 @@
+use std::fs;
+
+use glimpse_sdk::{ConfigField, ConfigKind, ConfigSchema};
+use glimpsed::plugin_config::{self, PluginConfigError};
+use serde_json::json;
+use tempfile::TempDir;
+
+fn create_schema() -> ConfigSchema {
+    ConfigSchema::new()
+        .field(ConfigField::new("max_results", ConfigKind::Integer).default_value(20))
+}
+
+#[test]
+fn test_load_converts_toml_to_json() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        plugin_config::config_path(dir.path(), "test.files"),
+        "max_results = 5\n",
+    )
+    .unwrap();
+
+    let config = plugin_config::load(dir.path(), "test.files", Some(&create_schema())).unwrap();
+
+    assert_eq!(config, Some(json!({"max_results": 5})));
+}
+
+#[test]
+fn test_load_missing_file_returns_defaults() {
+    let dir = TempDir::new().unwrap();
+
+    let config = plugin_config::load(dir.path(), "test.files", Some(&create_schema())).unwrap();
+
+    assert_eq!(config, Some(json!({"max_results": 20})));
+}
+
+#[test]
+fn test_load_missing_file_without_schema() {
+    let dir = TempDir::new().unwrap();
+
+    assert_eq!(
+        plugin_config::load(dir.path(), "test.files", None).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_load_without_schema_passes_file_through() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        plugin_config::config_path(dir.path(), "test.files"),
+        "anything = \"goes\"\n",
+    )
+    .unwrap();
+
+    let config = plugin_config::load(dir.path(), "test.files", None).unwrap();
+
+    assert_eq!(config, Some(json!({"anything": "goes"})));
+}
+
+#[test]
+fn test_load_reports_invalid_settings() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        plugin_config::config_path(dir.path(), "test.files"),
+        "max_results = \"all\"\n",
+    )
+    .unwrap();
+
+    let err = plugin_config::load(dir.path(), "test.files", Some(&create_schema())).unwrap_err();
+
+    assert!(matches!(err, PluginConfigError::Invalid(errors) if errors.len() == 1));
+}
+
+#[test]
+fn test_load_reports_toml_syntax_errors() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        plugin_config::config_path(dir.path(), "test.files"),
+        "max_results = \n",
+    )
+    .unwrap();
+
+    let err = plugin_config::load(dir.path(), "test.files", Some(&create_schema())).unwrap_err();
+
+    assert!(matches!(err, PluginConfigError::Toml(_)));
+}
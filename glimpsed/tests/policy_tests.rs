@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use glimpse_sdk::{Action, AlternateAction, Match, MatchAction, Modifiers};
+use glimpsed::{
+    config::DaemonConfig,
+    policy::{PolicyConfig, PolicyError},
+};
+
+fn action(title: &str, action: Action) -> MatchAction {
+    MatchAction {
+        title: title.to_string(),
+        action,
+        close_on_action: true,
+        alternates: vec![],
+        requires_confirmation: false,
+        confirmation_prompt: None,
+        expand: vec![],
+    }
+}
+
+fn exec() -> Action {
+    Action::Exec {
+        command: "rm".to_string(),
+        args: vec!["-rf".to_string()],
+    }
+}
+
+fn copy() -> Action {
+    Action::Clipboard {
+        text: "hello".into(),
+    }
+}
+
+fn callback() -> Action {
+    Action::Callback {
+        key: "delete".to_string(),
+        params: HashMap::new(),
+    }
+}
+
+const LOCKDOWN: PolicyConfig = PolicyConfig { lockdown: true };
+
+#[test]
+fn test_lockdown_is_read_from_policy_section() {
+    let config = DaemonConfig::from_toml("[policy]\nlockdown = true\n").unwrap();
+    assert_eq!(config.policy, LOCKDOWN);
+    assert!(!DaemonConfig::from_toml("").unwrap().policy.lockdown);
+}
+
+#[test]
+fn test_lockdown_rejects_exec_class_actions() {
+    assert_eq!(
+        LOCKDOWN.check(&exec()),
+        Err(PolicyError::Lockdown { action: "exec" })
+    );
+    assert_eq!(
+        LOCKDOWN.check(&callback()),
+        Err(PolicyError::Lockdown { action: "callback" })
+    );
+    assert!(LOCKDOWN.check(&copy()).is_ok());
+    assert!(
+        LOCKDOWN
+            .check(&Action::Open {
+                uri: "file:///tmp".to_string()
+            })
+            .is_ok()
+    );
+    assert!(PolicyConfig::default().check(&exec()).is_ok());
+    assert!(
+        LOCKDOWN
+            .check(&exec())
+            .unwrap_err()
+            .to_string()
+            .contains("lockdown")
+    );
+}
+
+#[test]
+fn test_lockdown_filters_actions_and_empty_matches() {
+    let mut copy_action = action("Copy", copy());
+    copy_action.alternates = vec![AlternateAction {
+        modifiers: Modifiers {
+            shift: true,
+            ..Default::default()
+        },
+        title: "Run".to_string(),
+        action: exec(),
+    }];
+    let items = vec![
+        Match {
+            title: "mixed".to_string(),
+            actions: vec![action("Run", exec()), copy_action],
+            ..Default::default()
+        },
+        Match {
+            title: "command".to_string(),
+            actions: vec![action("Run", exec()), action("Forget", callback())],
+            ..Default::default()
+        },
+    ];
+
+    let filtered = LOCKDOWN.filter(items.clone());
+    assert_eq!(filtered.len(), 1);
+    assert_eq!(filtered[0].title, "mixed");
+    assert_eq!(filtered[0].actions, vec![action("Copy", copy())]);
+
+    assert_eq!(PolicyConfig::default().filter(items.clone()), items);
+}
+
+#[test]
+fn test_lockdown_checks_sequence_steps() {
+    let sequence = |actions| Action::Sequence { actions };
+    assert_eq!(
+        LOCKDOWN.check(&sequence(vec![copy(), sequence(vec![exec()])])),
+        Err(PolicyError::Lockdown { action: "exec" })
+    );
+    assert!(LOCKDOWN.check(&sequence(vec![copy(), copy()])).is_ok());
+}
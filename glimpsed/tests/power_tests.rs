@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use glimpse_sdk::{Message, Method, PowerProfile};
+use glimpsed::{
+    config::DaemonConfig,
+    power::{PowerConfig, PowerMode, PowerState, PowerStats, debounce},
+};
+
+fn battery(on_battery: bool, percentage: f64) -> Option<PowerState> {
+    Some(PowerState {
+        on_battery,
+        percentage,
+    })
+}
+
+#[test]
+fn test_auto_switches_on_low_battery_only() {
+    let config = PowerConfig::default();
+
+    assert_eq!(
+        config.profile_for(battery(true, 20.0)),
+        PowerProfile::LowPower
+    );
+    assert_eq!(
+        config.profile_for(battery(true, 80.0)),
+        PowerProfile::Normal
+    );
+    assert_eq!(
+        config.profile_for(battery(false, 5.0)),
+        PowerProfile::Normal
+    );
+    assert_eq!(config.profile_for(None), PowerProfile::Normal);
+}
+
+#[test]
+fn test_config_overrides_battery() {
+    let config = DaemonConfig::from_toml(
+        r#"
+        [power]
+        mode = "normal"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.power.mode, PowerMode::Normal);
+    assert_eq!(
+        config.power.profile_for(battery(true, 5.0)),
+        PowerProfile::Normal
+    );
+
+    let config = DaemonConfig::from_toml(
+        r#"
+        [power]
+        mode = "low_power"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.power.profile_for(None), PowerProfile::LowPower);
+
+    let config = DaemonConfig::from_toml(
+        r#"
+        [power]
+        battery_threshold = 50
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.power.profile_for(battery(true, 40.0)),
+        PowerProfile::LowPower
+    );
+}
+
+#[test]
+fn test_stats_count_switches() {
+    let stats = PowerStats::default();
+    assert_eq!(stats.profile(), PowerProfile::Normal);
+
+    assert!(!stats.switch(PowerProfile::Normal));
+    assert!(stats.switch(PowerProfile::LowPower));
+    assert!(!stats.switch(PowerProfile::LowPower));
+    assert!(stats.switch(PowerProfile::Normal));
+    assert_eq!(stats.switches(), 2);
+
+    stats.record_state(PowerState {
+        on_battery: true,
+        percentage: 42.5,
+    });
+    assert!(stats.on_battery());
+    assert_eq!(stats.percentage(), 42.5);
+}
+
+#[test]
+fn test_low_power_debounce_is_longer() {
+    let normal = Duration::from_millis(500);
+    assert_eq!(debounce(PowerProfile::Normal, normal), normal);
+    assert!(debounce(PowerProfile::LowPower, normal) > normal);
+}
+
+#[test]
+fn test_power_profile_notification_json() {
+    let message = Message::Notification {
+        method: Method::PowerProfile(PowerProfile::LowPower),
+        plugin_id: None,
+    };
+    let json = serde_json::to_value(&message).unwrap();
+    assert_eq!(json["method"], "power_profile");
+    assert_eq!(json["params"], "low_power");
+}
@@ -0,0 +1,59 @@
+use glimpse_sdk::{Detail, Message, Method};
+use glimpsed::{
+    config::DaemonConfig,
+    prefetch::{PrefetchConfig, detail_ready},
+};
+
+#[test]
+fn test_visible_keeps_the_first_matches() {
+    let config = PrefetchConfig {
+        enabled: true,
+        max_matches: 2,
+    };
+    assert_eq!(config.visible(&[7, 3, 9]), &[7, 3]);
+    assert_eq!(config.visible(&[7]), &[7]);
+
+    let disabled = PrefetchConfig {
+        enabled: false,
+        ..config
+    };
+    assert!(disabled.visible(&[7, 3]).is_empty());
+}
+
+#[test]
+fn test_config_section() {
+    let config = DaemonConfig::from_toml("[prefetch]\nmax_matches = 4\n").unwrap();
+    assert_eq!(
+        config.prefetch,
+        PrefetchConfig {
+            enabled: true,
+            max_matches: 4
+        }
+    );
+    assert_eq!(DaemonConfig::default().prefetch, PrefetchConfig::default());
+}
+
+#[test]
+fn test_viewport_and_detail_ready_on_the_wire() {
+    let viewport = Message::Notification {
+        method: Method::Viewport {
+            generation: 2,
+            first_index: 10,
+            last_index: 21,
+        },
+        plugin_id: None,
+    };
+    let json = serde_json::to_value(&viewport).unwrap();
+    assert_eq!(json["method"], "viewport");
+    assert_eq!(json["params"]["first_index"], 10);
+    assert_eq!(json["params"]["last_index"], 21);
+    assert!(json.get("id").is_none());
+    assert_eq!(serde_json::from_value::<Message>(json).unwrap(), viewport);
+
+    let ready = detail_ready(2, 4, Detail::text("described"));
+    let json = serde_json::to_value(&ready).unwrap();
+    assert_eq!(json["method"], "detail_ready");
+    assert_eq!(json["params"]["match_id"], 4);
+    assert_eq!(json["params"]["detail"]["content"], "described");
+    assert_eq!(serde_json::from_value::<Message>(json).unwrap(), ready);
+}
@@ -0,0 +1,198 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use glimpse_sdk::Match;
+use glimpsed::{
+    config::DaemonConfig,
+    history::boost_for,
+    matches::MatchStore,
+    ranking::{
+        Features, LinearModel, PluginScore, RankingEvent, RankingLog, RankingStrategy, Strategy,
+        WeightedFrecency, evaluate,
+    },
+};
+use tempfile::TempDir;
+
+fn features(plugin_score: f64, frecency: f64) -> Features {
+    Features {
+        plugin_score,
+        frecency,
+    }
+}
+
+fn item(title: &str, score: f64) -> Match {
+    Match {
+        title: title.to_string(),
+        score,
+        ..Default::default()
+    }
+}
+
+/// Pages where the user always picks the match with the highest frecency, which the plugin
+/// ranked last.
+fn habitual_events(count: usize) -> Vec<RankingEvent> {
+    (0..count)
+        .map(|_| {
+            let shown = vec![features(0.9, 0.0), features(0.8, 0.0), features(0.5, 400.0)];
+            RankingEvent::new(2, shown, SystemTime::now())
+        })
+        .collect()
+}
+
+#[test]
+fn test_builtin_strategies_score() {
+    let picked = features(0.5, 100.0);
+    assert_eq!(PluginScore.score(&picked), 0.5);
+    assert_eq!(
+        WeightedFrecency { weight: 1.0 }.score(&picked),
+        0.5 + boost_for(100.0)
+    );
+    assert_eq!(WeightedFrecency { weight: 0.0 }.score(&picked), 0.5);
+
+    let linear = LinearModel {
+        score_weight: 2.0,
+        frecency_weight: 0.5,
+        learning_rate: 0.1,
+    };
+    assert_eq!(linear.score(&picked), 1.0 + 0.5 * boost_for(100.0));
+}
+
+#[test]
+fn test_linear_model_learns_from_activations() {
+    let mut linear = LinearModel {
+        score_weight: 1.0,
+        frecency_weight: 0.0,
+        learning_rate: 0.5,
+    };
+    let shown = [features(0.9, 0.0), features(0.5, 400.0)];
+    assert!(linear.score(&shown[0]) > linear.score(&shown[1]));
+
+    linear.learn(&shown[1], &shown);
+    linear.learn(&shown[1], &shown);
+
+    assert!(linear.frecency_weight > 0.0);
+    assert!(linear.score(&shown[1]) > linear.score(&shown[0]));
+
+    // a match ranked first already teaches nothing
+    let weights = (linear.score_weight, linear.frecency_weight);
+    linear.learn(&shown[1], &shown);
+    assert_eq!(weights, (linear.score_weight, linear.frecency_weight));
+}
+
+#[test]
+fn test_evaluate_reports_mrr_and_hit_rates() {
+    let events = habitual_events(4);
+
+    let plugin_score = evaluate(&mut PluginScore, &events);
+    assert_eq!(plugin_score.events, 4);
+    assert!((plugin_score.mrr - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(plugin_score.top_1, 0.0);
+    assert_eq!(plugin_score.top_3, 1.0);
+    assert_eq!(plugin_score.top_5, 1.0);
+
+    let frecency = evaluate(&mut WeightedFrecency { weight: 1.0 }, &events);
+    assert_eq!(frecency.mrr, 1.0);
+    assert_eq!(frecency.top_1, 1.0);
+
+    // the model is measured on each page before it learns from it
+    let mut linear = LinearModel {
+        score_weight: 1.0,
+        frecency_weight: 0.0,
+        learning_rate: 0.5,
+    };
+    let learned = evaluate(&mut linear, &events);
+    assert!(learned.mrr > plugin_score.mrr);
+    assert!(learned.top_1 < 1.0);
+}
+
+#[test]
+fn test_evaluate_skips_invalid_events() {
+    let events = vec![RankingEvent::new(
+        3,
+        vec![features(1.0, 0.0)],
+        SystemTime::now(),
+    )];
+    let evaluation = evaluate(&mut PluginScore, &events);
+    assert_eq!(evaluation.events, 0);
+    assert_eq!(evaluation.mrr, 0.0);
+}
+
+#[test]
+fn test_ranking_log_round_trip() {
+    let dir = TempDir::new().unwrap();
+    let log = RankingLog::new(&dir.path().join("glimpse/ranking.log"));
+    assert!(log.read().unwrap().is_empty());
+
+    let events = habitual_events(2);
+    for event in &events {
+        log.append(event).unwrap();
+    }
+    std::fs::write(
+        dir.path().join("glimpse/ranking.log.1"),
+        "not json\n{\"at\":1,\"chosen\":0,\"shown\":[]}\n",
+    )
+    .unwrap();
+
+    let read = log.read().unwrap();
+    assert_eq!(read.len(), 3);
+    assert_eq!(read[0].at, 1);
+    assert_eq!(&read[1..], &events[..]);
+}
+
+#[test]
+fn test_ranking_config() {
+    let config = DaemonConfig::from_toml("").unwrap();
+    assert_eq!(config.ranking.strategy, Strategy::default());
+    assert!(config.ranking.log);
+
+    let config = DaemonConfig::from_toml(
+        "[ranking]\nlog = false\n[ranking.strategy]\nname = \"linear\"\nlearning_rate = 0.2\n",
+    )
+    .unwrap();
+    assert!(!config.ranking.log);
+    assert_eq!(
+        config.ranking.strategy,
+        Strategy::Linear {
+            score_weight: 1.0,
+            frecency_weight: 1.0,
+            learning_rate: 0.2,
+        }
+    );
+    assert_eq!(config.ranking.strategy.build().name(), "linear");
+
+    let names = config
+        .ranking
+        .strategy
+        .builtins()
+        .iter()
+        .map(|strategy| strategy.build().name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["plugin_score", "weighted_frecency", "linear"]);
+    assert!(
+        config
+            .ranking
+            .strategy
+            .builtins()
+            .contains(&config.ranking.strategy)
+    );
+
+    let config = DaemonConfig::from_toml("[ranking.strategy]\nname = \"plugin_score\"\n").unwrap();
+    assert_eq!(config.ranking.strategy, Strategy::PluginScore);
+}
+
+#[test]
+fn test_match_store_impression() {
+    let mut store = MatchStore::new();
+    store.reset(1);
+    let frecency = HashMap::from([("Files".to_string(), 400.0)]);
+    let items = [item("Firefox", 0.9), item("Files", 0.5)];
+    let features = Features::of(&items, &frecency);
+    assert_eq!(features[1], self::features(0.5, 400.0));
+    store.extend_ranked(1, "apps", &items, &features).unwrap();
+    store.extend(1, "calc", &[item("42", 0.7)]).unwrap();
+
+    let (chosen, shown) = store.impression(1, 1).unwrap();
+    assert_eq!(chosen, 2);
+    assert_eq!(shown, [features[0], self::features(0.7, 0.0), features[1]]);
+    assert!(store.impression(2, 1).is_none());
+    assert!(store.impression(1, 5).is_none());
+}
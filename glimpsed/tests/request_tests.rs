@@ -0,0 +1,107 @@
+use std::time::{Duration, Instant};
+
+use glimpsed::{
+    config::DaemonConfig,
+    requests::{RequestConfig, RequestTracker},
+};
+
+#[test]
+fn test_complete_stops_tracking() {
+    let mut tracker = RequestTracker::new();
+    let deadline = Instant::now() + Duration::from_secs(3);
+    tracker.track(1, "/plugins/apps", deadline);
+    tracker.track(1, "/plugins/files", deadline);
+
+    assert!(tracker.is_pending(1, "/plugins/apps"));
+    assert!(tracker.complete(1, "/plugins/apps"));
+    assert!(!tracker.complete(1, "/plugins/apps"));
+    assert!(!tracker.is_pending(1, "/plugins/apps"));
+    assert!(!tracker.is_pending(2, "/plugins/files"));
+    assert_eq!(tracker.len(), 1);
+}
+
+#[test]
+fn test_finish_measures_answer_time() {
+    let mut tracker = RequestTracker::new();
+    tracker.track(1, "/plugins/apps", Instant::now() + Duration::from_secs(3));
+    std::thread::sleep(Duration::from_millis(5));
+
+    let elapsed = tracker.finish(1, "/plugins/apps").unwrap();
+    assert!(elapsed >= Duration::from_millis(5));
+    assert_eq!(tracker.finish(1, "/plugins/apps"), None);
+}
+
+#[test]
+fn test_expire_returns_overdue_plugins_oldest_first() {
+    let mut tracker = RequestTracker::new();
+    let now = Instant::now();
+    tracker.track(1, "/plugins/slow", now + Duration::from_millis(200));
+    tracker.track(1, "/plugins/stuck", now + Duration::from_millis(100));
+    tracker.track(1, "/plugins/fast", now + Duration::from_secs(3));
+
+    assert_eq!(
+        tracker.next_deadline(),
+        Some(now + Duration::from_millis(100))
+    );
+    assert!(tracker.expire(now).is_empty());
+
+    let expired = tracker.expire(now + Duration::from_millis(500));
+    assert_eq!(
+        expired,
+        vec![
+            (1, "/plugins/stuck".to_string()),
+            (1, "/plugins/slow".to_string()),
+        ]
+    );
+    assert!(tracker.is_pending(1, "/plugins/fast"));
+    assert!(!tracker.complete(1, "/plugins/slow"));
+}
+
+#[test]
+fn test_cancel_and_forget_plugin() {
+    let mut tracker = RequestTracker::new();
+    let deadline = Instant::now() + Duration::from_secs(3);
+    tracker.track(1, "/plugins/apps", deadline);
+    tracker.track(2, "/plugins/apps", deadline);
+    tracker.track(2, "/plugins/files", deadline);
+
+    assert_eq!(tracker.cancel(1), vec!["/plugins/apps".to_string()]);
+    assert!(!tracker.is_pending(1, "/plugins/apps"));
+
+    assert_eq!(tracker.forget_plugin("/plugins/apps"), vec![2]);
+    assert!(tracker.is_pending(2, "/plugins/files"));
+
+    tracker.clear();
+    assert!(tracker.is_empty());
+    assert_eq!(tracker.next_deadline(), None);
+}
+
+#[test]
+fn test_config_requests_section() {
+    let config = DaemonConfig::from_toml(
+        r#"
+        [requests]
+        timeout_ms = 750
+        page_size = 0
+        debounce_ms = 0
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.requests,
+        RequestConfig {
+            timeout_ms: 750,
+            page_size: 0,
+            debounce_ms: 0,
+        }
+    );
+    assert_eq!(config.requests.timeout(), Duration::from_millis(750));
+    assert_eq!(config.requests.page_size(), 1);
+    assert_eq!(DaemonConfig::default().requests.timeout_ms, 3000);
+    assert_eq!(DaemonConfig::default().requests.page_size, 50);
+    assert_eq!(
+        DaemonConfig::default().requests.debounce(),
+        Duration::from_millis(50)
+    );
+}
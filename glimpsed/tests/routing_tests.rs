@@ -0,0 +1,247 @@
+use glimpse_sdk::Metadata;
+use glimpsed::routing::{Route, route, takes_unrouted};
+
+fn create_metadata(id: &str, prefix: Option<&str>) -> Metadata {
+    Metadata {
+        id: id.to_string(),
+        name: id.to_string(),
+        prefix: prefix.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_single_prefix_routes_to_plugin() {
+    let plugins = [
+        create_metadata("calc", Some("=")),
+        create_metadata("emoji", Some(":")),
+        create_metadata("files", None),
+    ];
+
+    assert_eq!(
+        route("= 2+2", &plugins),
+        Route::Prefixed {
+            plugin_id: "calc".to_string(),
+            query: "2+2".to_string(),
+        }
+    );
+    assert_eq!(
+        route(":smile", &plugins),
+        Route::Prefixed {
+            plugin_id: "emoji".to_string(),
+            query: "smile".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_unprefixed_query_is_broadcast() {
+    let plugins = [
+        create_metadata("calc", Some("=")),
+        create_metadata("files", None),
+    ];
+
+    assert_eq!(route("notes", &plugins), Route::Broadcast);
+    assert_eq!(route("", &plugins), Route::Broadcast);
+}
+
+#[test]
+fn test_ambiguous_prefix_is_broadcast() {
+    let plugins = [
+        create_metadata("calc", Some("=")),
+        create_metadata("units", Some("==")),
+    ];
+
+    assert_eq!(route("==5kg", &plugins), Route::Broadcast);
+}
+
+#[test]
+fn test_empty_prefix_is_ignored() {
+    let plugins = [
+        create_metadata("calc", Some("")),
+        create_metadata("emoji", Some(":")),
+    ];
+
+    assert_eq!(
+        route(":x", &plugins),
+        Route::Prefixed {
+            plugin_id: "emoji".to_string(),
+            query: "x".to_string(),
+        }
+    );
+    assert_eq!(route("x", &plugins), Route::Broadcast);
+}
+
+#[test]
+fn test_backslash_escapes_prefix() {
+    let plugins = [
+        create_metadata("calc", Some("=")),
+        create_metadata("files", None),
+    ];
+
+    assert_eq!(
+        route("\\=5", &plugins),
+        Route::Literal {
+            query: "=5".to_string(),
+        }
+    );
+    assert_eq!(
+        route("\\\\x", &plugins),
+        Route::Literal {
+            query: "\\x".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_quoted_query_is_literal() {
+    let plugins = [create_metadata("calc", Some("="))];
+
+    assert_eq!(
+        route("\"= 2+2\"", &plugins),
+        Route::Literal {
+            query: "= 2+2".to_string(),
+        }
+    );
+    assert_eq!(
+        route("\"\"", &plugins),
+        Route::Literal {
+            query: String::new(),
+        }
+    );
+}
+
+#[test]
+fn test_unbalanced_quote_is_not_an_escape() {
+    let plugins = [create_metadata("calc", Some("="))];
+
+    assert_eq!(route("\"notes", &plugins), Route::Broadcast);
+    assert_eq!(route("\"", &plugins), Route::Broadcast);
+    assert_eq!(route("notes\"", &plugins), Route::Broadcast);
+}
+
+#[test]
+fn test_escape_inside_query_is_kept() {
+    let plugins = [create_metadata("calc", Some("="))];
+
+    assert_eq!(
+        route("=\\5", &plugins),
+        Route::Prefixed {
+            plugin_id: "calc".to_string(),
+            query: "\\5".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_prefix_only_plugins_skip_unrouted_queries() {
+    let calc = create_metadata("calc", Some("="));
+    let clipboard = Metadata {
+        prefix_only: true,
+        ..create_metadata("clipboard", Some("clip "))
+    };
+    let files = create_metadata("files", None);
+
+    assert!(takes_unrouted(Some(&calc), false));
+    assert!(!takes_unrouted(Some(&calc), true));
+    assert!(!takes_unrouted(Some(&clipboard), false));
+    assert!(takes_unrouted(Some(&files), true));
+    // not authenticated yet, its prefix is unknown
+    assert!(takes_unrouted(None, true));
+}
+
+#[test]
+fn test_trigger_word_routes_to_plugin() {
+    let clipboard = Metadata {
+        triggers: vec!["clip".to_string(), "cb".to_string()],
+        ..create_metadata("clipboard", None)
+    };
+    let plugins = [clipboard, create_metadata("calc", Some("="))];
+
+    assert_eq!(
+        route("clip  token", &plugins),
+        Route::Prefixed {
+            plugin_id: "clipboard".to_string(),
+            query: "token".to_string(),
+        }
+    );
+    assert_eq!(
+        route("cb", &plugins),
+        Route::Prefixed {
+            plugin_id: "clipboard".to_string(),
+            query: String::new(),
+        }
+    );
+    // a trigger is a whole word
+    assert_eq!(route("clipboard", &plugins), Route::Broadcast);
+    assert_eq!(
+        route("\"clip x\"", &plugins),
+        Route::Literal {
+            query: "clip x".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_kept_trigger_word_stays_in_the_query() {
+    let media = Metadata {
+        prefix: Some("media ".to_string()),
+        triggers: vec!["play".to_string(), "next".to_string()],
+        keep_triggers: true,
+        ..create_metadata("media", None)
+    };
+    let plugins = [media, create_metadata("calc", Some("="))];
+
+    assert_eq!(
+        route("next", &plugins),
+        Route::Prefixed {
+            plugin_id: "media".to_string(),
+            query: "next".to_string(),
+        }
+    );
+    assert_eq!(
+        route("play  jazz", &plugins),
+        Route::Prefixed {
+            plugin_id: "media".to_string(),
+            query: "play  jazz".to_string(),
+        }
+    );
+    // the prefix is stripped all the same
+    assert_eq!(
+        route("media next", &plugins),
+        Route::Prefixed {
+            plugin_id: "media".to_string(),
+            query: "next".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_trigger_shared_by_plugins_is_broadcast() {
+    let plugins = [
+        Metadata {
+            triggers: vec!["run".to_string()],
+            ..create_metadata("shell", None)
+        },
+        create_metadata("apps", Some("run")),
+    ];
+
+    assert_eq!(route("run top", &plugins), Route::Broadcast);
+}
+
+#[test]
+fn test_trigger_plugins_skip_escaped_queries() {
+    let clipboard = Metadata {
+        triggers: vec!["clip".to_string()],
+        ..create_metadata("clipboard", None)
+    };
+    let empty = Metadata {
+        triggers: vec![String::new()],
+        ..create_metadata("files", None)
+    };
+
+    assert!(takes_unrouted(Some(&clipboard), false));
+    assert!(!takes_unrouted(Some(&clipboard), true));
+    assert!(takes_unrouted(Some(&empty), true));
+    assert_eq!(route("x", &[empty]), Route::Broadcast);
+}
@@ -0,0 +1,164 @@
+#![cfg(unix)]
+
+use std::{os::unix::fs::PermissionsExt, path::Path};
+
+use glimpsed::{
+    config::DaemonConfig,
+    sandbox::{Limits, Sandbox, SandboxConfig},
+};
+
+fn script(dir: &Path, body: &str) -> std::path::PathBuf {
+    let path = dir.join("glimpse-plugin-test");
+    std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+fn sandbox(work_dir: &Path) -> Sandbox {
+    Sandbox {
+        env: vec!["PATH".to_string(), "LC_*".to_string()],
+        limits: Limits {
+            cpu_secs: Some(30),
+            memory_mb: Some(512),
+            open_files: Some(64),
+        },
+        seccomp: false,
+        work_dir: work_dir.to_path_buf(),
+    }
+}
+
+async fn run(sandbox: &Sandbox, path: &Path) -> String {
+    let mut command = tokio::process::Command::new(path);
+    sandbox.apply(&mut command).unwrap();
+    let output = command.output().await.unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_env_is_filtered() {
+    let sandbox = sandbox(Path::new("/tmp"));
+    let vars = [
+        ("PATH", "/usr/bin"),
+        ("LC_TIME", "C"),
+        ("LC", "x"),
+        ("GITHUB_TOKEN", "secret"),
+        ("AWS_SECRET_ACCESS_KEY", "secret"),
+    ]
+    .map(|(name, value)| (name.to_string(), value.to_string()));
+
+    let names = sandbox
+        .filter_env(vars)
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["PATH", "LC_TIME"]);
+}
+
+#[test]
+fn test_plugin_overrides() {
+    let config = DaemonConfig::from_toml(
+        r#"
+        [sandbox]
+        env = ["PATH"]
+        limits = { open_files = 256 }
+
+        [sandbox.plugins.glimpse-plugin-ssh]
+        env = ["SSH_AUTH_SOCK"]
+        limits = { memory_mb = 128 }
+        seccomp = true
+
+        [sandbox.plugins.glimpse-plugin-trusted]
+        enabled = false
+        "#,
+    )
+    .unwrap();
+
+    let sandbox = config
+        .sandbox
+        .for_plugin(Path::new("/usr/lib/glimpsed/plugins/glimpse-plugin-ssh"))
+        .unwrap();
+    assert_eq!(sandbox.env, vec!["PATH", "SSH_AUTH_SOCK"]);
+    assert_eq!(
+        sandbox.limits,
+        Limits {
+            cpu_secs: None,
+            memory_mb: Some(128),
+            open_files: Some(256),
+        }
+    );
+    assert!(sandbox.seccomp);
+    assert!(sandbox.work_dir.ends_with("plugin-data/glimpse-plugin-ssh"));
+
+    let other = config
+        .sandbox
+        .for_plugin(Path::new("/usr/lib/glimpsed/plugins/glimpse-plugin-apps"))
+        .unwrap();
+    assert_eq!(other.env, vec!["PATH"]);
+    assert!(!other.seccomp);
+
+    assert_eq!(
+        config
+            .sandbox
+            .for_plugin(Path::new("/opt/glimpse-plugin-trusted")),
+        None
+    );
+    let disabled = SandboxConfig {
+        enabled: false,
+        ..Default::default()
+    };
+    assert_eq!(disabled.for_plugin(Path::new("/opt/plugin")), None);
+}
+
+#[tokio::test]
+async fn test_plugins_start_confined() {
+    let dir = tempfile::tempdir().unwrap();
+    let work_dir = dir.path().join("data").join("glimpse-plugin-test");
+    let path = script(
+        dir.path(),
+        r#"echo "token=${GLIMPSE_TEST_TOKEN:-unset}"; pwd; ulimit -n; ulimit -t; ulimit -d"#,
+    );
+    // SAFETY: no other test of this binary reads the variable.
+    unsafe {
+        std::env::set_var("GLIMPSE_TEST_TOKEN", "secret");
+    }
+
+    let output = run(&sandbox(&work_dir), &path).await;
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        vec![
+            "token=unset",
+            work_dir.to_str().unwrap(),
+            "64",
+            "30",
+            "524288",
+        ]
+    );
+    assert_eq!(
+        std::fs::metadata(&work_dir).unwrap().permissions().mode() & 0o777,
+        0o700
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_seccomp_blocks_syscalls() {
+    let dir = tempfile::tempdir().unwrap();
+    // unshare(2) fails with EPERM even where unprivileged namespaces are allowed
+    let path = script(
+        dir.path(),
+        "if unshare --user true 2>/dev/null; then echo allowed; else echo blocked; fi",
+    );
+    if std::process::Command::new("unshare")
+        .arg("--version")
+        .output()
+        .is_err()
+    {
+        return;
+    }
+
+    let mut sandbox = sandbox(&dir.path().join("work"));
+    sandbox.seccomp = true;
+    assert_eq!(run(&sandbox, &path).await.trim(), "blocked");
+}
@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+
+use glimpsed::{
+    config::DaemonConfig,
+    shortcut::{ShortcutConfig, request_path},
+};
+
+#[test]
+fn test_shortcut_is_off_by_default() {
+    let config = DaemonConfig::default();
+
+    assert!(!config.shortcut.enabled);
+    assert_eq!(config.shortcut.trigger, "LOGO+space");
+    assert_eq!(config.shortcut.launcher, None);
+}
+
+#[test]
+fn test_shortcut_config() {
+    let config = DaemonConfig::from_toml(
+        r#"
+        [shortcut]
+        enabled = true
+        trigger = "CTRL+ALT+space"
+        launcher = "/opt/glimpse/glimpse"
+        "#,
+    )
+    .unwrap();
+
+    assert!(config.shortcut.enabled);
+    assert_eq!(config.shortcut.trigger, "CTRL+ALT+space");
+    assert_eq!(
+        config.shortcut.launcher(),
+        PathBuf::from("/opt/glimpse/glimpse")
+    );
+}
+
+#[test]
+fn test_configured_launcher_wins() {
+    let config = ShortcutConfig {
+        launcher: Some(PathBuf::from("/usr/local/bin/glimpse")),
+        ..Default::default()
+    };
+
+    assert_eq!(config.launcher(), PathBuf::from("/usr/local/bin/glimpse"));
+}
+
+#[test]
+fn test_request_path_is_derived_from_the_unique_name() {
+    assert_eq!(
+        request_path(":1.42", "glimpse_7_0"),
+        "/org/freedesktop/portal/desktop/request/1_42/glimpse_7_0"
+    );
+}
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use glimpsed::stats::{Counters, Outcome, StatsStore};
+use tempfile::TempDir;
+
+#[test]
+fn test_record_outcomes() {
+    let mut stats = StatsStore::in_memory();
+    stats.record("apps", Outcome::Answered(Duration::from_millis(10)));
+    stats.record("apps", Outcome::Failed(Duration::from_millis(20)));
+    stats.record("apps", Outcome::TimedOut);
+    stats.activated("apps");
+
+    assert_eq!(
+        stats.counters("apps"),
+        Some(&Counters {
+            searches: 3,
+            errors: 1,
+            timeouts: 1,
+            activations: 1,
+            latency_ms: 30,
+        })
+    );
+    assert_eq!(stats.counters("files"), None);
+}
+
+#[test]
+fn test_summary() {
+    let mut stats = StatsStore::in_memory();
+    for _ in 0..3 {
+        stats.record("files", Outcome::Answered(Duration::from_millis(40)));
+    }
+    stats.record("files", Outcome::TimedOut);
+    stats.record("apps", Outcome::Answered(Duration::from_millis(5)));
+    stats.activated("apps");
+    stats.activated("apps");
+    stats.activated("apps");
+    stats.activated("files");
+
+    let summary = stats.summary();
+    assert_eq!(summary[0].plugin_id, "files");
+    assert_eq!(summary[0].searches, 4);
+    // timeouts never answered, they leave the latency alone
+    assert_eq!(summary[0].average_latency_ms, 40);
+    assert_eq!(summary[0].error_rate, 0.25);
+    assert_eq!(summary[0].activation_share, 0.25);
+    assert_eq!(summary[1].plugin_id, "apps");
+    assert_eq!(summary[1].average_latency_ms, 5);
+    assert_eq!(summary[1].error_rate, 0.0);
+    assert_eq!(summary[1].activation_share, 0.75);
+
+    // plugins that only timed out or were only activated
+    let mut stats = StatsStore::in_memory();
+    stats.record("slow", Outcome::TimedOut);
+    stats.activated("history");
+    let summary = stats.summary();
+    assert_eq!(summary[0].average_latency_ms, 0);
+    assert_eq!(summary[0].error_rate, 1.0);
+    assert_eq!(summary[1].error_rate, 0.0);
+}
+
+#[test]
+fn test_stats_persist() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("glimpse/plugin_stats.json");
+
+    let mut stats = StatsStore::load(&path);
+    stats.record("apps", Outcome::Answered(Duration::from_millis(12)));
+    stats.activated("apps");
+    stats.save().unwrap();
+
+    let mut reloaded = StatsStore::load(&path);
+    assert_eq!(reloaded.summary(), stats.summary());
+    reloaded.record("apps", Outcome::TimedOut);
+    reloaded.save().unwrap();
+    assert_eq!(
+        StatsStore::load(&path).counters("apps").unwrap().searches,
+        2
+    );
+
+    // broken counters start over
+    std::fs::write(&path, "[]").unwrap();
+    assert!(StatsStore::load(&path).summary().is_empty());
+}
@@ -0,0 +1,80 @@
+use glimpsed::subscriptions::SubscriptionRegistry;
+
+#[test]
+fn test_routes_updates_to_subscribing_request() {
+    let mut registry = SubscriptionRegistry::new();
+    registry.subscribe("/plugins/media", "player", 7);
+
+    assert_eq!(registry.route("/plugins/media", "player"), Some(7));
+    assert_eq!(registry.route("/plugins/media", "volume"), None);
+    assert_eq!(registry.route("/plugins/other", "player"), None);
+}
+
+#[test]
+fn test_resubscribe_replaces_route() {
+    let mut registry = SubscriptionRegistry::new();
+    registry.subscribe("/plugins/media", "player", 7);
+
+    assert_eq!(registry.subscribe("/plugins/media", "player", 9), Some(7));
+    assert_eq!(registry.route("/plugins/media", "player"), Some(9));
+    assert_eq!(registry.len(), 1);
+}
+
+#[test]
+fn test_unsubscribe_stops_routing() {
+    let mut registry = SubscriptionRegistry::new();
+    registry.subscribe("/plugins/media", "player", 7);
+
+    assert_eq!(registry.unsubscribe("/plugins/media", "player"), Some(7));
+    assert_eq!(registry.unsubscribe("/plugins/media", "player"), None);
+    assert_eq!(registry.route("/plugins/media", "player"), None);
+    assert!(registry.is_empty());
+}
+
+#[test]
+fn test_remove_rejected_request() {
+    let mut registry = SubscriptionRegistry::new();
+    registry.subscribe("/plugins/media", "player", 7);
+    registry.subscribe("/plugins/load", "cpu", 8);
+
+    assert_eq!(registry.remove_request("/plugins/media", 8), None);
+    assert_eq!(
+        registry.remove_request("/plugins/media", 7),
+        Some("player".to_string())
+    );
+    assert_eq!(registry.len(), 1);
+}
+
+#[test]
+fn test_drain_returns_all_subscriptions() {
+    let mut registry = SubscriptionRegistry::new();
+    registry.subscribe("/plugins/media", "player", 7);
+    registry.subscribe("/plugins/load", "cpu", 8);
+
+    let mut drained = registry.drain();
+    drained.sort();
+
+    assert_eq!(
+        drained,
+        vec![
+            ("/plugins/load".to_string(), "cpu".to_string()),
+            ("/plugins/media".to_string(), "player".to_string()),
+        ]
+    );
+    assert!(registry.is_empty());
+}
+
+#[test]
+fn test_remove_plugin_drops_its_subscriptions() {
+    let mut registry = SubscriptionRegistry::new();
+    registry.subscribe("/plugins/media", "player", 7);
+    registry.subscribe("/plugins/media", "volume", 8);
+    registry.subscribe("/plugins/other", "player", 9);
+
+    let mut removed = registry.remove_plugin("/plugins/media");
+    removed.sort();
+    assert_eq!(removed, vec![7, 8]);
+    assert_eq!(registry.route("/plugins/media", "player"), None);
+    assert_eq!(registry.route("/plugins/other", "player"), Some(9));
+    assert!(registry.remove_plugin("/plugins/media").is_empty());
+}
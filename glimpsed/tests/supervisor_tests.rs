@@ -0,0 +1,134 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use glimpsed::{
+    config::DaemonConfig,
+    supervisor::{Restart, Supervisor, SupervisorConfig, SupervisorStats, TaskState},
+};
+
+fn create_supervisor(max_restarts: usize) -> Supervisor {
+    let config = SupervisorConfig {
+        max_restarts,
+        window_secs: 60,
+        restart_delay_ms: 1,
+    };
+    Supervisor::new(config, Arc::new(SupervisorStats::default()))
+}
+
+/// Spawn a task that panics the first `panics` times it runs, then waits forever.
+fn spawn_flaky(
+    supervisor: &mut Supervisor,
+    name: &'static str,
+    restart: Restart,
+    panics: usize,
+) -> Arc<AtomicUsize> {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let counter = runs.clone();
+    supervisor.spawn(name, restart, move || {
+        let run = counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            if run < panics {
+                panic!("run {} failed", run);
+            }
+            std::future::pending::<()>().await
+        }
+    });
+    runs
+}
+
+/// Let the supervisor work until its tasks settle, returns the task it shut down for, if any.
+/// Tests run with time paused, the clock only jumps to the timeout once every restart delay
+/// has passed and no task can make progress, however loaded the machine is.
+async fn watch(supervisor: &mut Supervisor) -> Option<&'static str> {
+    tokio::time::timeout(Duration::from_millis(300), supervisor.watch())
+        .await
+        .ok()
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_panicked_tasks_restart() {
+    let mut supervisor = create_supervisor(5);
+    let runs = spawn_flaky(&mut supervisor, "timeouts", Restart::OnPanic, 2);
+
+    assert_eq!(watch(&mut supervisor).await, None);
+    assert_eq!(runs.load(Ordering::SeqCst), 3);
+
+    let stats = supervisor.stats();
+    assert_eq!(stats.panics(), 2);
+    assert_eq!(stats.restarts(), 2);
+    assert_eq!(stats.tasks()[0].state, TaskState::Running);
+    assert!(stats.healthy());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_ending_critical_task_shuts_down() {
+    let mut supervisor = create_supervisor(5);
+    supervisor.spawn("thumbnails", Restart::OnPanic, || async {});
+    assert_eq!(watch(&mut supervisor).await, None);
+    assert_eq!(supervisor.stats().tasks()[0].state, TaskState::Finished);
+
+    supervisor.spawn("plugin messages", Restart::Critical, || async {});
+    assert_eq!(watch(&mut supervisor).await, Some("plugin messages"));
+    assert_eq!(supervisor.stats().tasks()[1].state, TaskState::Finished);
+    // finished tasks are no failures
+    assert!(supervisor.stats().healthy());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_restart_budget() {
+    let mut supervisor = create_supervisor(3);
+    let runs = spawn_flaky(&mut supervisor, "power", Restart::OnPanic, usize::MAX);
+
+    assert_eq!(watch(&mut supervisor).await, None);
+    assert_eq!(runs.load(Ordering::SeqCst), 4);
+    let stats = supervisor.stats();
+    assert_eq!(stats.tasks()[0].state, TaskState::Failed);
+    assert_eq!(stats.restarts(), 3);
+    assert!(!stats.healthy());
+
+    spawn_flaky(
+        &mut supervisor,
+        "plugin messages",
+        Restart::Critical,
+        usize::MAX,
+    );
+    assert_eq!(watch(&mut supervisor).await, Some("plugin messages"));
+    assert_eq!(stats.panics(), 8);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_shutdown_stops_tasks() {
+    let mut supervisor = create_supervisor(5);
+    let runs = spawn_flaky(&mut supervisor, "timeouts", Restart::Critical, 0);
+    supervisor.shutdown().await;
+
+    assert_eq!(watch(&mut supervisor).await, None);
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_supervisor_config() {
+    let config = DaemonConfig::from_toml(
+        r#"
+        [supervisor]
+        max_restarts = 10
+        restart_delay_ms = 50
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.supervisor.max_restarts, 10);
+    assert_eq!(config.supervisor.window(), Duration::from_secs(60));
+    assert_eq!(
+        config.supervisor.restart_delay(0),
+        Duration::from_millis(50)
+    );
+    assert_eq!(
+        config.supervisor.restart_delay(3),
+        Duration::from_millis(400)
+    );
+}
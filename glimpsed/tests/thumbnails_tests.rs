@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use glimpsed::thumbnails::{MediaKind, Thumbnails, file_uri, mtime, stamp, text_chunk};
+
+/// Smallest PNG the thumbnail functions accept: a header and the end marker.
+fn png() -> Vec<u8> {
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    png.extend_from_slice(&13u32.to_be_bytes());
+    png.extend_from_slice(b"IHDR");
+    png.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 1, 8, 6, 0, 0, 0]);
+    png.extend_from_slice(&[0; 4]);
+    png.extend_from_slice(&0u32.to_be_bytes());
+    png.extend_from_slice(b"IEND");
+    png.extend_from_slice(&[0xae, 0x42, 0x60, 0x82]);
+    png
+}
+
+#[test]
+fn test_media_kind_from_extension() {
+    assert_eq!(
+        MediaKind::of(Path::new("a/photo.JPG")),
+        Some(MediaKind::Image)
+    );
+    assert_eq!(
+        MediaKind::of(Path::new("clip.webm")),
+        Some(MediaKind::Video)
+    );
+    assert_eq!(MediaKind::of(Path::new("notes.txt")), None);
+    assert_eq!(MediaKind::of(Path::new("Makefile")), None);
+}
+
+#[test]
+fn test_file_uri_matches_spec_hash() {
+    let path = Path::new("/home/jens/photos/me.png");
+    assert_eq!(file_uri(path), "file:///home/jens/photos/me.png");
+    // example from the thumbnail specification
+    assert_eq!(
+        Thumbnails::new("/cache").target(path),
+        Path::new("/cache/normal/c6ee772d9e49320e97ec29a7eb5b1697.png")
+    );
+    assert_eq!(
+        file_uri(Path::new("/tmp/my photo#1.png")),
+        "file:///tmp/my%20photo%231.png"
+    );
+}
+
+#[test]
+fn test_stamp_adds_text_chunks_after_header() {
+    let stamped = stamp(&png(), "file:///a.png", 1700000000).unwrap();
+
+    assert_eq!(
+        text_chunk(&stamped, "Thumb::URI").as_deref(),
+        Some("file:///a.png")
+    );
+    assert_eq!(
+        text_chunk(&stamped, "Thumb::MTime").as_deref(),
+        Some("1700000000")
+    );
+    assert!(stamped.ends_with(&png()[33..]));
+    assert!(stamp(b"not a png", "file:///a.png", 1).is_none());
+}
+
+#[test]
+fn test_lookup_rejects_outdated_thumbnails() {
+    let dir = tempfile::tempdir().unwrap();
+    let image = dir.path().join("photo.png");
+    std::fs::write(&image, png()).unwrap();
+    let modified = mtime(&image).unwrap();
+
+    let thumbnails = Thumbnails::new(dir.path().join("thumbnails"));
+    assert_eq!(thumbnails.lookup(&image, modified), None);
+
+    let target = thumbnails.target(&image);
+    std::fs::create_dir_all(target.parent().unwrap()).unwrap();
+    let uri = file_uri(&image);
+    std::fs::write(&target, stamp(&png(), &uri, modified - 1).unwrap()).unwrap();
+    assert_eq!(thumbnails.lookup(&image, modified), None);
+
+    std::fs::write(&target, stamp(&png(), &uri, modified).unwrap()).unwrap();
+    assert_eq!(thumbnails.lookup(&image, modified), Some(target));
+    assert!(!thumbnails.failed(&image, modified));
+}
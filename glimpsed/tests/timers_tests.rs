@@ -0,0 +1,96 @@
+use std::{sync::Arc, time::Duration};
+
+use glimpse_sdk::unix_millis;
+use glimpsed::timers::{Reminder, Timer, TimerStore, Timers};
+use tokio::sync::mpsc;
+
+fn reminder(title: &str) -> Reminder {
+    Reminder {
+        title: title.to_string(),
+        body: None,
+        open_launcher: false,
+    }
+}
+
+fn titles(timers: &[Timer]) -> Vec<&str> {
+    timers
+        .iter()
+        .map(|timer| timer.reminder.title.as_str())
+        .collect()
+}
+
+#[test]
+fn test_store_keeps_timers_soonest_first() {
+    let mut store = TimerStore::in_memory();
+    let tea = store.add(600, reminder("tea"));
+    let eggs = store.add(300, reminder("eggs"));
+    store.add(900, reminder("stretch"));
+
+    assert_ne!(tea, eggs);
+    assert_eq!(titles(store.timers()), vec!["eggs", "tea", "stretch"]);
+    assert_eq!(store.next_due(), Some(300));
+}
+
+#[test]
+fn test_store_takes_due_timers_only() {
+    let mut store = TimerStore::in_memory();
+    store.add(300, reminder("eggs"));
+    store.add(600, reminder("tea"));
+
+    assert!(store.take_due(299).is_empty());
+    assert_eq!(titles(&store.take_due(600)), vec!["eggs", "tea"]);
+    assert_eq!(store.next_due(), None);
+}
+
+#[test]
+fn test_store_saves_and_loads() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("glimpse").join("timers.json");
+    let mut store = TimerStore::load(&path);
+    assert!(store.timers().is_empty());
+    store.add(
+        600,
+        Reminder {
+            title: "tea".to_string(),
+            body: Some("10 min are up".to_string()),
+            open_launcher: true,
+        },
+    );
+    store.add(300, reminder("eggs"));
+    store.save().unwrap();
+
+    let loaded = TimerStore::load(&path);
+    assert_eq!(loaded.timers(), store.timers());
+}
+
+#[test]
+fn test_store_load_ignores_invalid_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("timers.json");
+    std::fs::write(&path, "not json").unwrap();
+
+    assert!(TimerStore::load(&path).timers().is_empty());
+}
+
+#[tokio::test]
+async fn test_timers_fire_missed_and_scheduled() {
+    let mut store = TimerStore::in_memory();
+    // set by a daemon that exited before it was due
+    store.add(unix_millis() - 1000, reminder("missed"));
+    let timers = Arc::new(Timers::new(store));
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    tokio::spawn(timers.clone().run(move |timer| {
+        let tx = tx.clone();
+        async move {
+            tx.send(timer.reminder.title).unwrap();
+        }
+    }));
+
+    assert_eq!(rx.recv().await.unwrap(), "missed");
+    timers
+        .schedule(Duration::from_millis(50), reminder("tea"))
+        .await
+        .unwrap();
+    let fired = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await;
+    assert_eq!(fired.unwrap().unwrap(), "tea");
+}
@@ -0,0 +1,123 @@
+use std::path::Path;
+
+use glimpse_sdk::UntrustedPlugin;
+use glimpsed::{
+    config::DaemonConfig,
+    trust::{self, Admitted, TrustConfig, TrustError, TrustStore},
+};
+use tempfile::TempDir;
+
+fn write_plugin(dir: &Path, name: &str, content: &str) -> String {
+    let path = dir.join(name);
+    std::fs::write(&path, content).unwrap();
+    path.to_string_lossy().to_string()
+}
+
+fn config(trusted_dir: &Path) -> TrustConfig {
+    TrustConfig {
+        enabled: true,
+        trusted_dirs: vec![trusted_dir.to_path_buf()],
+    }
+}
+
+#[test]
+fn test_digest() {
+    let dir = TempDir::new().unwrap();
+    let path = write_plugin(dir.path(), "glimpse-plugin-abc", "abc");
+    assert_eq!(
+        trust::digest(Path::new(&path)).unwrap(),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
+
+#[test]
+fn test_unknown_plugins_are_held_back() {
+    let dir = TempDir::new().unwrap();
+    let system = TempDir::new().unwrap();
+    let user = write_plugin(dir.path(), "glimpse-plugin-user", "v1");
+    let packaged = write_plugin(system.path(), "glimpse-plugin-packaged", "v1");
+    let store = TrustStore::in_memory(config(system.path()));
+
+    let (admitted, held) = store.admit(vec![user.clone(), packaged.clone()]);
+    // plugins in trusted directories run as they are
+    assert_eq!(
+        admitted,
+        vec![Admitted {
+            path: packaged,
+            sha256: None,
+        }]
+    );
+    assert_eq!(
+        held,
+        vec![UntrustedPlugin {
+            path: user.clone(),
+            sha256: trust::digest(Path::new(&user)).unwrap(),
+            changed: false,
+        }]
+    );
+}
+
+#[test]
+fn test_trusted_plugins_are_pinned() {
+    let dir = TempDir::new().unwrap();
+    let path = write_plugin(dir.path(), "glimpse-plugin-user", "v1");
+    let mut store = TrustStore::in_memory(config(Path::new("/nonexistent")));
+
+    let held = store.check(&path).unwrap().unwrap();
+    store.trust(&path, &held.sha256).unwrap();
+    assert_eq!(store.check(&path).unwrap(), None);
+    let (admitted, _) = store.admit(vec![path.clone()]);
+    assert_eq!(admitted[0].sha256.as_deref(), Some(held.sha256.as_str()));
+
+    // a replaced executable is asked about again
+    std::fs::write(&path, "v2").unwrap();
+    let changed = store.check(&path).unwrap().unwrap();
+    assert!(changed.changed);
+    assert_ne!(changed.sha256, held.sha256);
+}
+
+#[test]
+fn test_trust_refuses_changed_executable() {
+    let dir = TempDir::new().unwrap();
+    let path = write_plugin(dir.path(), "glimpse-plugin-user", "v1");
+    let mut store = TrustStore::in_memory(TrustConfig::default());
+
+    let shown = store.check(&path).unwrap().unwrap();
+    std::fs::write(&path, "v2").unwrap();
+    assert!(matches!(
+        store.trust(&path, &shown.sha256),
+        Err(TrustError::Changed { .. })
+    ));
+    assert_eq!(store.pinned(&path), None);
+}
+
+#[test]
+fn test_store_persists() {
+    let dir = TempDir::new().unwrap();
+    let path = write_plugin(dir.path(), "glimpse-plugin-user", "v1");
+    let store_path = dir.path().join("glimpse/trusted-plugins.toml");
+
+    let mut store = TrustStore::load(&store_path, TrustConfig::default());
+    let sha256 = trust::digest(Path::new(&path)).unwrap();
+    store.trust(&path, &sha256).unwrap();
+
+    let reloaded = TrustStore::load(&store_path, TrustConfig::default());
+    assert_eq!(reloaded.pinned(&path), Some(sha256.as_str()));
+    assert_eq!(reloaded.check(&path).unwrap(), None);
+
+    // a broken store trusts nothing
+    std::fs::write(&store_path, "plugins = 1").unwrap();
+    let broken = TrustStore::load(&store_path, TrustConfig::default());
+    assert_eq!(broken.pinned(&path), None);
+}
+
+#[test]
+fn test_disabled_trust_runs_everything() {
+    let dir = TempDir::new().unwrap();
+    let path = write_plugin(dir.path(), "glimpse-plugin-user", "v1");
+    let config = DaemonConfig::from_toml("[trust]\nenabled = false").unwrap();
+    let store = TrustStore::in_memory(config.trust);
+
+    assert_eq!(store.check(&path).unwrap(), None);
+    assert!(DaemonConfig::default().trust.enabled);
+}
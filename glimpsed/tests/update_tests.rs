@@ -0,0 +1,116 @@
+use glimpse_sdk::{Action, Metadata};
+use glimpsed::{
+    config::DaemonConfig,
+    updates::{ReleaseManifest, is_update_query, update_matches},
+};
+
+fn create_metadata(id: &str, version: &str) -> Metadata {
+    Metadata {
+        id: id.to_string(),
+        name: id.to_string(),
+        version: version.to_string(),
+        ..Default::default()
+    }
+}
+
+const MANIFEST: &str = r#"{
+    "daemon": {"version": "0.2.0", "url": "https://example.com/glimpsed", "notes": "faster search"},
+    "plugins": {
+        "files": {"version": "1.2.0"},
+        "calc": {"version": "1.0.0"},
+        "emoji": {"version": "nightly"}
+    }
+}"#;
+
+#[test]
+fn test_available_updates_lists_newer_releases() {
+    let manifest = ReleaseManifest::from_json(MANIFEST).unwrap();
+    let installed = [
+        create_metadata("files", "1.1.9"),
+        create_metadata("calc", "1.0.0"),
+        create_metadata("emoji", "0.1.0"),
+        create_metadata("notes", "0.1.0"),
+    ];
+
+    let updates = manifest.available_updates("0.1.0", &installed);
+    let components = updates
+        .iter()
+        .map(|update| (update.component.as_str(), update.latest.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(components, vec![("glimpsed", "0.2.0"), ("files", "1.2.0")]);
+    assert_eq!(updates[0].installed, "0.1.0");
+    assert_eq!(updates[0].notes.as_deref(), Some("faster search"));
+}
+
+#[test]
+fn test_current_daemon_is_not_an_update() {
+    let manifest = ReleaseManifest::from_json(MANIFEST).unwrap();
+    assert!(manifest.available_updates("0.2.0", &[]).is_empty());
+    assert!(manifest.available_updates("0.3.0-beta.1", &[]).is_empty());
+}
+
+#[test]
+fn test_invalid_manifest_is_an_error() {
+    assert!(ReleaseManifest::from_json("{\"daemon\": 5}").is_err());
+    assert_eq!(
+        ReleaseManifest::from_json("{}").unwrap(),
+        ReleaseManifest::default()
+    );
+}
+
+#[test]
+fn test_update_rows_open_release_page() {
+    let manifest = ReleaseManifest::from_json(MANIFEST).unwrap();
+    let updates = manifest.available_updates("0.1.0", &[create_metadata("files", "1.0.0")]);
+    let rows = update_matches(&updates);
+
+    assert_eq!(rows[0].title, "Update glimpsed to 0.2.0");
+    assert_eq!(rows[0].description, "installed 0.1.0, faster search");
+    assert_eq!(
+        rows[0].actions[0].action,
+        Action::Open {
+            uri: "https://example.com/glimpsed".to_string(),
+        }
+    );
+    assert!(rows[1].actions.is_empty());
+}
+
+#[test]
+fn test_update_query() {
+    assert!(is_update_query("upd"));
+    assert!(is_update_query("Updates"));
+    assert!(is_update_query("update glimpsed"));
+    assert!(!is_update_query("up"));
+    assert!(!is_update_query("firefox"));
+}
+
+#[test]
+fn test_config_updates_section() {
+    let config = DaemonConfig::default();
+    assert_eq!(config.updates.manifest_url(), None);
+
+    let config = DaemonConfig::from_toml(
+        r#"
+        [updates]
+        manifest_url = "https://example.com/manifest.json"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.updates.manifest_url(), None, "checks are opt-in");
+    assert_eq!(config.updates.interval_hours, 24);
+
+    let config = DaemonConfig::from_toml(
+        r#"
+        [updates]
+        enabled = true
+        manifest_url = "https://example.com/manifest.json"
+        interval_hours = 6
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.updates.manifest_url(),
+        Some("https://example.com/manifest.json")
+    );
+    assert_eq!(config.updates.interval().as_secs(), 6 * 60 * 60);
+}